@@ -0,0 +1,68 @@
+//! Glyph atlas cost of a text-heavy frame.
+//!
+//! The first frame rasterizes and packs every glyph; later frames drawing the
+//! same text only look them up. Run with `cargo bench --bench glyph_atlas`.
+
+use centered_engine::text::{GlyphAtlas, GlyphBitmap, GlyphKey};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+/// A screenful of UI text at a few sizes
+const LINES: &[(&str, f32)] = &[
+    ("Settings", 24.0),
+    ("Account  Notifications  Privacy  Appearance", 16.0),
+    ("The quick brown fox jumps over the lazy dog.", 14.0),
+    ("Sphinx of black quartz, judge my vow! 0123456789", 14.0),
+    ("Last synced 2 minutes ago - 1,024 items (3.2 MB)", 12.0),
+];
+
+/// Stand-in for a platform rasterizer: an anti-aliased disc of coverage
+fn rasterize(size: f32) -> GlyphBitmap {
+    let side = size.ceil() as u32;
+    let radius = size / 2.0;
+    let mut data = Vec::with_capacity((side * side * 4) as usize);
+    for y in 0..side {
+        for x in 0..side {
+            let (dx, dy) = (x as f32 + 0.5 - radius, y as f32 + 0.5 - radius);
+            let coverage = (radius - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+            let alpha = (coverage * 255.0) as u8;
+            data.extend_from_slice(&[alpha, alpha, alpha, alpha]);
+        }
+    }
+    GlyphBitmap { data, width: side, height: side, bearing_x: 0.0, bearing_y: size, advance: size * 0.6 }
+}
+
+/// Look up every glyph of the frame's text, rasterizing the ones missing
+fn draw_frame(atlas: &mut GlyphAtlas) {
+    atlas.begin_frame();
+    for &(text, size) in LINES {
+        let mut pen_x = 0.0;
+        for ch in text.chars() {
+            let key = GlyphKey::with_subpixel(1, ch as u32, size, pen_x);
+            let advance = match atlas.get(&key) {
+                Some(entry) => entry.advance,
+                None => atlas.insert(key, rasterize(size)).map_or(0.0, |entry| entry.advance),
+            };
+            pen_x += advance;
+        }
+    }
+}
+
+fn bench_glyph_atlas(c: &mut Criterion) {
+    c.bench_function("first frame (cold atlas)", |b| {
+        b.iter_batched(
+            || GlyphAtlas::new(1024, 1024),
+            |mut atlas| {
+                draw_frame(&mut atlas);
+                atlas
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let mut atlas = GlyphAtlas::new(1024, 1024);
+    draw_frame(&mut atlas);
+    c.bench_function("second frame (warm atlas)", |b| b.iter(|| draw_frame(black_box(&mut atlas))));
+}
+
+criterion_group!(benches, bench_glyph_atlas);
+criterion_main!(benches);
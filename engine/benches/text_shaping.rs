@@ -0,0 +1,50 @@
+//! Text layout cost of a frame of labels, with and without the shaping cache.
+//!
+//! DrawText lays its string out (shaping, measuring, line breaking) before
+//! building glyph quads. With the cache, the first frame lays out every
+//! label and later frames drawing the same labels reuse those layouts.
+//! `text_glyph_positions` goes through the same layout as DrawText without
+//! needing a GPU. Run with `cargo bench --bench text_shaping`.
+
+use centered_engine::platform::WgpuBackend;
+use centered_engine::text::{FontDescriptor, TextLayoutConfig};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// A screenful of UI labels at a few sizes, some wrapped
+const LABELS: &[(&str, f32, Option<f32>)] = &[
+    ("Settings", 24.0, None),
+    ("Account  Notifications  Privacy  Appearance", 16.0, None),
+    ("The quick brown fox jumps over the lazy dog.", 14.0, Some(160.0)),
+    ("Sphinx of black quartz, judge my vow! 0123456789", 14.0, Some(160.0)),
+    ("Last synced 2 minutes ago - 1,024 items (3.2 MB)", 12.0, None),
+    ("Save", 14.0, None),
+    ("Cancel", 14.0, None),
+];
+
+/// Lay out every label the way the frame's DrawText commands would
+fn draw_frame(backend: &mut WgpuBackend) {
+    for &(text, size, max_width) in LABELS {
+        let font = FontDescriptor { size, ..Default::default() };
+        let layout = TextLayoutConfig { max_width, ..Default::default() };
+        black_box(backend.text_glyph_positions(text, &font, 0.0, &layout));
+    }
+}
+
+fn bench_text_shaping(c: &mut Criterion) {
+    let mut uncached = WgpuBackend::new();
+    uncached.set_shaping_cache_capacity(0);
+    draw_frame(&mut uncached);
+    c.bench_function("frame of labels (no shaping cache)", |b| b.iter(|| draw_frame(&mut uncached)));
+
+    let mut cached = WgpuBackend::new();
+    draw_frame(&mut cached);
+    let misses = cached.shaping_cache_metrics().misses;
+    c.bench_function("frame of labels (warm shaping cache)", |b| b.iter(|| draw_frame(&mut cached)));
+
+    // Only the first frame laid anything out
+    assert_eq!(cached.shaping_cache_metrics().misses, misses, "repeated labels were laid out again");
+    println!("shaping cache hit rate: {:.1}%", cached.shaping_cache_metrics().hit_rate() * 100.0);
+}
+
+criterion_group!(benches, bench_text_shaping);
+criterion_main!(benches);
@@ -2,8 +2,12 @@
 //!
 //! This module provides audio playback and input functionality using platform-native APIs:
 //! - macOS/iOS: AVFoundation / AVAudioEngine (hardware-accelerated, respects system devices)
-//! - Linux: PulseAudio or PipeWire (planned)
-//! - Windows: WASAPI (planned)
+//! - Android: MediaPlayer / AudioRecord via JNI
+//! - Linux: rodio / cpal
+//! - Windows: WASAPI
+//!
+//! Players are driven from the `centered_audio_*` FFI functions by ID, like
+//! video players, and report the same `PlaybackState` codes.
 //!
 //! The audio system supports:
 //! - Background music and sound effects (playback)
@@ -175,6 +179,23 @@ mod tests {
         assert_eq!(PlaybackState::Error as i32, 5);
     }
 
+    #[test]
+    fn test_playback_state_matches_video() {
+        // Go shares state handling between audio and video players
+        use crate::video::PlaybackState as Video;
+        let pairs = [
+            (PlaybackState::Idle, Video::Idle),
+            (PlaybackState::Loading, Video::Loading),
+            (PlaybackState::Playing, Video::Playing),
+            (PlaybackState::Paused, Video::Paused),
+            (PlaybackState::Ended, Video::Ended),
+            (PlaybackState::Error, Video::Error),
+        ];
+        for (audio, video) in pairs {
+            assert_eq!(audio as i32, video as i32, "{:?}", audio);
+        }
+    }
+
     #[test]
     fn test_audio_info_default() {
         let info = AudioInfo::default();
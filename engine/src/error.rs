@@ -0,0 +1,125 @@
+//! FFI error codes
+//!
+//! FFI functions return 0 (or a non-negative value such as an ID or a count)
+//! on success and a negative `EngineError` code on failure. Each code means
+//! the same thing from every function, so callers can handle them in one
+//! place, and `centered_error_string` turns a code into a message for logs.
+//! The values are part of the FFI and must never be renumbered.
+
+use std::ffi::CStr;
+
+/// Why an FFI call failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum EngineError {
+    /// A null pointer, invalid UTF-8 or JSON, or an out-of-range argument
+    InvalidArgument = -1,
+    /// No player, input, texture or layer with the given ID
+    NotFound = -2,
+    /// The operation ran and failed (decoding, loading, a platform call)
+    OperationFailed = -3,
+    /// Uploading to the GPU failed
+    GpuUploadFailed = -4,
+    /// Not supported on this platform or GPU
+    Unsupported = -5,
+    /// The engine, GPU backend or tray icon hasn't been created yet
+    NotInitialized = -6,
+    /// No app event loop is running
+    NoEventLoop = -7,
+    /// The output buffer is too small for the result
+    BufferTooSmall = -8,
+    /// Nothing to return yet (no media loaded, no camera frame captured)
+    NotReady = -9,
+}
+
+impl EngineError {
+    /// Every error, in code order
+    pub const ALL: [EngineError; 9] = [
+        EngineError::InvalidArgument,
+        EngineError::NotFound,
+        EngineError::OperationFailed,
+        EngineError::GpuUploadFailed,
+        EngineError::Unsupported,
+        EngineError::NotInitialized,
+        EngineError::NoEventLoop,
+        EngineError::BufferTooSmall,
+        EngineError::NotReady,
+    ];
+
+    /// The value returned across the FFI
+    pub const fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// The error a code stands for, if any
+    pub fn from_code(code: i32) -> Option<EngineError> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// A short human-readable description
+    pub const fn message(self) -> &'static CStr {
+        match self {
+            EngineError::InvalidArgument => c"invalid argument",
+            EngineError::NotFound => c"not found",
+            EngineError::OperationFailed => c"operation failed",
+            EngineError::GpuUploadFailed => c"failed to upload to the GPU",
+            EngineError::Unsupported => c"not supported on this platform",
+            EngineError::NotInitialized => c"not initialized",
+            EngineError::NoEventLoop => c"no event loop running",
+            EngineError::BufferTooSmall => c"buffer too small",
+            EngineError::NotReady => c"nothing available yet",
+        }
+    }
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message().to_str().unwrap_or_default())
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<EngineError> for i32 {
+    fn from(error: EngineError) -> i32 {
+        error.code()
+    }
+}
+
+/// Message for any FFI return code: "success" for 0 and above, "unknown
+/// error" for negative codes this version doesn't define
+pub fn error_string(code: i32) -> &'static CStr {
+    if code >= 0 {
+        return c"success";
+    }
+    EngineError::from_code(code).map_or(c"unknown error", EngineError::message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_error_codes_are_distinct_and_described() {
+        let codes: HashSet<i32> = EngineError::ALL.iter().map(|e| e.code()).collect();
+        assert_eq!(codes.len(), EngineError::ALL.len());
+
+        let messages: HashSet<&CStr> = EngineError::ALL.iter().map(|e| e.message()).collect();
+        assert_eq!(messages.len(), EngineError::ALL.len());
+
+        for error in EngineError::ALL {
+            assert!(error.code() < 0, "{error:?} must be negative");
+            assert!(!error.message().is_empty());
+            assert_eq!(EngineError::from_code(error.code()), Some(error));
+            assert_eq!(error_string(error.code()), error.message());
+        }
+
+        // Codes stay put across versions
+        assert_eq!(EngineError::InvalidArgument.code(), -1);
+        assert_eq!(EngineError::NotReady.code(), -9);
+
+        assert_eq!(error_string(0), c"success");
+        assert_eq!(error_string(-1000), c"unknown error");
+    }
+}
@@ -166,6 +166,8 @@ pub struct EventDispatcher {
     focused_widget: Option<WidgetId>,
     /// Widget being pressed (for click detection)
     pressed_widget: Option<WidgetId>,
+    /// Whether a mouse move replaces the move right before it in the batch
+    coalesce_mouse_moves: bool,
 }
 
 impl EventDispatcher {
@@ -176,9 +178,22 @@ impl EventDispatcher {
             hovered_widget: None,
             focused_widget: None,
             pressed_widget: None,
+            coalesce_mouse_moves: true,
         }
     }
 
+    /// Set whether consecutive mouse moves within a frame collapse into the
+    /// latest one. On by default; editors that draw every sample of a
+    /// stroke turn it off.
+    pub fn set_coalesce_mouse_moves(&mut self, coalesce: bool) {
+        self.coalesce_mouse_moves = coalesce;
+    }
+
+    /// Whether consecutive mouse moves are coalesced
+    pub fn coalesce_mouse_moves(&self) -> bool {
+        self.coalesce_mouse_moves
+    }
+
     /// Start a new frame
     pub fn begin_frame(&mut self) {
         self.frame_number += 1;
@@ -213,6 +228,15 @@ impl EventDispatcher {
             _ => {}
         }
 
+        // Only a move directly after another is dropped, so presses and
+        // releases still see the position they happened at
+        if self.coalesce_mouse_moves && matches!(event, Event::MouseMove { .. }) {
+            if let Some(last @ Event::MouseMove { .. }) = self.current_batch.events.last_mut() {
+                *last = event;
+                return;
+            }
+        }
+
         self.current_batch.push(event);
     }
 
@@ -230,6 +254,14 @@ impl EventDispatcher {
         batch
     }
 
+    /// Hand over the frame's events and start the next frame, so Go drains
+    /// everything that happened once per frame
+    pub fn end_frame(&mut self) -> EventBatch {
+        let batch = self.take_batch();
+        self.begin_frame();
+        batch
+    }
+
     /// Get currently hovered widget
     pub fn hovered_widget(&self) -> Option<WidgetId> {
         self.hovered_widget
@@ -291,6 +323,38 @@ mod tests {
         assert_eq!(dispatcher.current_batch().len(), 0);
     }
 
+    #[test]
+    fn test_mouse_moves_coalesce_within_frame() {
+        let mouse_move = |x: f32| Event::MouseMove { x, y: 0.0, widget: None };
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.begin_frame();
+
+        dispatcher.push_event(mouse_move(1.0));
+        dispatcher.push_event(mouse_move(2.0));
+        dispatcher.push_event(Event::MouseDown { x: 2.0, y: 0.0, button: MouseButton::Left, widget: None });
+        dispatcher.push_event(mouse_move(3.0));
+        dispatcher.push_event(mouse_move(4.0));
+
+        let batch = dispatcher.end_frame();
+        let xs: Vec<f32> = batch
+            .events
+            .iter()
+            .map(|e| match e {
+                Event::MouseMove { x, .. } | Event::MouseDown { x, .. } => *x,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(xs, vec![2.0, 2.0, 4.0]);
+        assert!(dispatcher.current_batch().is_empty());
+        assert_eq!(dispatcher.current_batch().frame_number, batch.frame_number + 1);
+
+        // Opting out keeps every sample
+        dispatcher.set_coalesce_mouse_moves(false);
+        dispatcher.push_event(mouse_move(1.0));
+        dispatcher.push_event(mouse_move(2.0));
+        assert_eq!(dispatcher.end_frame().len(), 2);
+    }
+
     #[test]
     fn test_focus_tracking() {
         let mut dispatcher = EventDispatcher::new();
@@ -8,11 +8,14 @@
 
 use crate::{
     Engine, EngineConfig,
-    event::EventBatch,
-    render::{RenderCommand, RenderMode},
-    text::{FontDescriptor, FontSource, FontStyle, TextLayoutConfig, TextAlign, VerticalAlign, WordBreak, TextOverflow, WhiteSpace},
+    error::EngineError,
+    logging::verbose,
+    render::{FillRule, LineCap, LineJoin, PathSegment, RenderCommand, RenderMode, Sampling, Stroke},
+    text::{FontDescriptor, FontSource, FontStyle, TextLayoutConfig, TextAlign, TextAnchor, TextDirection, TextRun, VerticalAlign, WordBreak, TextOverflow, WhiteSpace},
     widget::WidgetDelta,
 };
+#[cfg(feature = "winit")]
+use crate::widget::WidgetTree;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
@@ -109,8 +112,14 @@ pub unsafe extern "C" fn centered_engine_submit_frame(
     // Parse widget tree from JSON
     // TODO: Process widget tree and render
 
-    // Return empty event batch for now
-    let event_batch = EventBatch::default();
+    // Hand over the frame's events, mouse moves coalesced unless the
+    // engine was configured otherwise
+    let event_batch = ENGINE_MAP
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|engine| engine.event_dispatcher.end_frame())
+        .unwrap_or_default();
     let events_json = match serde_json::to_string(&event_batch) {
         Ok(json) => json,
         Err(_) => return ptr::null_mut(),
@@ -143,15 +152,25 @@ pub unsafe extern "C" fn centered_engine_submit_delta(
         Err(_) => return ptr::null_mut(),
     };
 
-    let _delta: WidgetDelta = match serde_json::from_str(delta_str) {
+    let delta: WidgetDelta = match serde_json::from_str(delta_str) {
         Ok(d) => d,
         Err(_) => return ptr::null_mut(),
     };
 
-    // TODO: Apply delta to widget tree and re-render if needed
+    // The tree draws itself on the next frame that renders it
+    if let Some(engine) = ENGINE_MAP.lock().unwrap().as_mut() {
+        engine.widget_tree.apply_delta(delta);
+        crate::widget::accessibility::publish(&engine.widget_tree);
+    }
 
-    // Return empty event batch for now
-    let event_batch = EventBatch::default();
+    // Hand over the frame's events, mouse moves coalesced unless the
+    // engine was configured otherwise
+    let event_batch = ENGINE_MAP
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|engine| engine.event_dispatcher.end_frame())
+        .unwrap_or_default();
     let events_json = match serde_json::to_string(&event_batch) {
         Ok(json) => json,
         Err(_) => return ptr::null_mut(),
@@ -164,7 +183,8 @@ pub unsafe extern "C" fn centered_engine_submit_delta(
 }
 
 /// Load styles from TOML configuration
-/// Returns 0 on success, non-zero on error
+/// Returns 0 on success, non-zero on error (invalid TOML, or an undefined or
+/// cyclic theme variable reference)
 ///
 /// # Safety
 /// - handle must be valid
@@ -176,23 +196,80 @@ pub unsafe extern "C" fn centered_engine_load_styles(
     toml: *const c_char,
 ) -> i32 {
     if toml.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let toml_str = match CStr::from_ptr(toml).to_str() {
         Ok(s) => s,
-        Err(_) => return -1,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     let mut map = ENGINE_MAP.lock().unwrap();
     if let Some(engine) = map.as_mut() {
         match engine.style_system.load_theme(toml_str) {
             Ok(_) => 0,
-            Err(_) => -1,
+            Err(e) => {
+                eprintln!("Failed to load theme: {}", e);
+                EngineError::InvalidArgument.code()
+            }
         }
     } else {
-        -1
+        EngineError::NotInitialized.code()
+    }
+}
+
+/// Reload styles from TOML while the app runs
+///
+/// Replaces the theme like centered_engine_load_styles, marks the widgets
+/// whose classes use a changed color, variable or custom class dirty and,
+/// when anything changed, requests a redraw of the app window (desktop).
+///
+/// # Returns
+/// Number of theme keys that changed (0 if the theme is the same), or a
+/// negative error code:
+/// - `InvalidArgument`: null pointer, invalid TOML, or an undefined or
+///   cyclic theme variable reference
+/// - `NotInitialized`: engine not initialized
+///
+/// # Safety
+/// - handle must be valid
+/// - toml must be a valid null-terminated UTF-8 string
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_engine_reload_styles(
+    _handle: EngineHandle,
+    toml: *const c_char,
+) -> i32 {
+    if toml.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let toml_str = match CStr::from_ptr(toml).to_str() {
+        Ok(s) => s,
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let changed = {
+        let mut map = ENGINE_MAP.lock().unwrap();
+        let Some(engine) = map.as_mut() else {
+            return EngineError::NotInitialized.code();
+        };
+        match engine.reload_styles(toml_str) {
+            Ok(changes) => changes.keys.len(),
+            Err(e) => {
+                eprintln!("Failed to reload theme: {}", e);
+                return EngineError::InvalidArgument.code();
+            }
+        }
+    };
+
+    #[cfg(feature = "winit")]
+    if changed > 0 {
+        if let Some(proxy) = get_event_loop_proxy().lock().unwrap().as_ref() {
+            let _ = proxy.send_event(UserEvent::RequestRedraw);
+        }
     }
+    changed.min(i32::MAX as usize) as i32
 }
 
 /// Resize the rendering surface
@@ -227,10 +304,80 @@ pub unsafe extern "C" fn centered_engine_get_mode(_handle: EngineHandle) -> i32
             RenderMode::Retained => 1,
         }
     } else {
-        -1
+        EngineError::NotInitialized.code()
     }
 }
 
+/// Caches cleared by `centered_engine_invalidate_caches`; combine with `|`
+/// Loaded font faces and font lookups (family name to font file)
+pub const CACHE_TEXT: u32 = 0x01;
+/// Rasterized glyphs
+pub const CACHE_GLYPH_ATLAS: u32 = 0x02;
+/// Style classes parsed against the theme
+pub const CACHE_STYLE: u32 = 0x04;
+/// Laid-out text (see `centered_text_set_shaping_cache_capacity`)
+pub const CACHE_SHAPING: u32 = 0x08;
+pub const CACHE_ALL: u32 = CACHE_TEXT | CACHE_GLYPH_ATLAS | CACHE_STYLE | CACHE_SHAPING;
+
+/// Clear engine caches after the app font or theme changes at runtime, so
+/// the next frame doesn't mix stale and fresh glyphs or styles.
+/// `flags` selects caches with the CACHE_* bits. The next frame is
+/// rendered in full, and a redraw is requested if the app loop is running.
+///
+/// Returns 0 on success, `EngineError::InvalidArgument` if `flags` has unknown bits
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_engine_invalidate_caches(flags: u32) -> i32 {
+    if flags & !CACHE_ALL != 0 {
+        return EngineError::InvalidArgument.code();
+    }
+
+    if flags & CACHE_TEXT != 0 {
+        get_font_manager().lock().unwrap().clear_cache();
+    }
+    if let Some(backend) = get_backend().lock().unwrap().as_mut() {
+        if flags & CACHE_TEXT != 0 {
+            backend.clear_font_caches();
+        }
+        if flags & CACHE_GLYPH_ATLAS != 0 {
+            backend.clear_glyph_atlas();
+        }
+        if flags & CACHE_SHAPING != 0 {
+            backend.clear_shaping_cache();
+        }
+    }
+    if flags & CACHE_STYLE != 0 {
+        if let Some(engine) = ENGINE_MAP.lock().unwrap().as_mut() {
+            engine.style_system.clear_cache();
+        }
+    }
+
+    #[cfg(feature = "winit")]
+    let _ = centered_app_request_redraw();
+    0
+}
+
+/// Set how many laid-out texts are kept for reuse
+///
+/// DrawText and text measurement reuse the layout of a string drawn before
+/// in the same font and layout, skipping shaping and line breaking. Least
+/// recently used layouts are dropped beyond `capacity` (512 by default); 0
+/// turns the cache off. Clear it with `centered_engine_invalidate_caches`
+/// and `CACHE_SHAPING`.
+///
+/// Returns 0 on success, `EngineError::NotInitialized` if the backend is not initialized
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_text_set_shaping_cache_capacity(capacity: u32) -> i32 {
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+    let Some(backend) = guard.as_mut() else {
+        return EngineError::NotInitialized.code();
+    };
+    backend.set_shaping_cache_capacity(capacity as usize);
+    0
+}
+
 /// Free a string returned by the engine
 ///
 /// # Safety
@@ -252,6 +399,31 @@ pub extern "C" fn centered_engine_version() -> *const c_char {
     "0.1.0\0".as_ptr() as *const c_char
 }
 
+/// Describe an error code returned by any FFI function (see `EngineError`).
+/// Returns a static string (do NOT free); unknown negative codes give
+/// "unknown error" and codes of 0 or above give "success".
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_error_string(code: i32) -> *const c_char {
+    crate::error::error_string(code).as_ptr()
+}
+
+/// Enable or disable verbose diagnostic logging (window lifecycle, resizes,
+/// safe-area changes). Off by default; errors are always printed to stderr.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_set_verbose_logging(enabled: bool) {
+    crate::logging::set_verbose(enabled);
+}
+
+/// Route verbose log messages to `callback` instead of stdout.
+/// Pass null to restore stdout. The message pointer is only valid during the call.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_set_log_callback(callback: Option<crate::logging::LogCallback>) {
+    crate::logging::set_log_callback(callback);
+}
+
 /// Get the app's internal files directory path (Android only).
 /// Returns NULL on non-Android platforms or if not yet initialized.
 /// The returned string is owned by the engine - do NOT free it.
@@ -292,6 +464,18 @@ pub enum FFIFontSourceType {
     System = 0,
     Bundled = 1,
     Memory = 2,
+    /// Font name is a comma-separated list of system families, primary first
+    SystemWithFallback = 3,
+}
+
+/// Family names of a comma-separated fallback list
+fn fallback_families(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 /// C-compatible draw text command
@@ -321,6 +505,7 @@ pub struct FFIDrawTextCommand {
     // Layout
     pub max_width: f32,            // 0.0 = no constraint
     pub max_height: f32,           // 0.0 = no constraint
+    pub max_lines: u32,            // 0 = unlimited
     pub line_height: f32,          // Multiplier
     pub letter_spacing: f32,       // em units
     pub word_spacing: f32,         // em units
@@ -328,11 +513,17 @@ pub struct FFIDrawTextCommand {
     // Alignment
     pub alignment: u8,             // TextAlign
     pub vertical_align: u8,        // VerticalAlign
+    pub anchor: u8,                // TextAnchor: what y is (top, baseline, center)
 
     // Behavior
     pub word_break: u8,            // WordBreak
     pub overflow: u8,              // TextOverflow
     pub white_space: u8,           // WhiteSpace
+    pub base_direction: u8,        // TextDirection
+
+    // Fill
+    pub gradient_ptr: *const u8,   // gradient_data as in the binary frame; null = flat color
+    pub gradient_len: usize,
 }
 
 /// C-compatible draw rect command
@@ -346,6 +537,40 @@ pub struct FFIDrawRectCommand {
     pub border_radius: f32,
 }
 
+/// C-compatible draw line command
+#[repr(C)]
+pub struct FFIDrawLineCommand {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub width: f32,
+    pub color: u32,
+    pub cap: u8,                   // 0=Butt, 1=Round, 2=Square
+}
+
+/// C-compatible draw arc command
+#[repr(C)]
+pub struct FFIDrawArcCommand {
+    pub cx: f32,
+    pub cy: f32,
+    pub radius: f32,
+    pub start_angle: f32,          // radians clockwise from 3 o'clock
+    pub end_angle: f32,
+    pub thickness: f32,            // 0 = filled wedge
+    pub color: u32,
+}
+
+/// C-compatible push rounded clip command
+#[repr(C)]
+pub struct FFIPushRoundedClipCommand {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub corner_radii: [f32; 4],    // top-left, top-right, bottom-right, bottom-left
+}
+
 /// C-compatible render command type
 #[repr(u8)]
 pub enum FFIRenderCommandType {
@@ -355,6 +580,12 @@ pub enum FFIRenderCommandType {
     PopClip = 3,
     SetOpacity = 4,
     Clear = 5,
+    DrawLine = 6,
+    /// Clip to a rounded rect until the matching PopClip; nested rounded
+    /// clips intersect
+    PushRoundedClip = 7,
+    /// Antialiased ring segment, or filled wedge when thickness is 0
+    DrawArc = 8,
 }
 
 /// C-compatible render command (tagged union)
@@ -371,6 +602,9 @@ pub union FFIRenderCommandData {
     pub draw_rect: std::mem::ManuallyDrop<FFIDrawRectCommand>,
     pub draw_text: std::mem::ManuallyDrop<FFIDrawTextCommand>,
     pub push_clip: std::mem::ManuallyDrop<FFIDrawRectCommand>,  // Same layout
+    pub draw_line: std::mem::ManuallyDrop<FFIDrawLineCommand>,
+    pub push_rounded_clip: std::mem::ManuallyDrop<FFIPushRoundedClipCommand>,
+    pub draw_arc: std::mem::ManuallyDrop<FFIDrawArcCommand>,
     pub set_opacity: f32,
     pub clear_color: u32,
 }
@@ -399,6 +633,7 @@ impl FFIDrawTextCommand {
                 name: font_name,
                 data_hash: self.font_data_hash,
             },
+            3 => FontSource::SystemWithFallback(fallback_families(&font_name)),
             _ => FontSource::System("system".to_string()),
         };
 
@@ -414,7 +649,7 @@ impl FFIDrawTextCommand {
         let layout = TextLayoutConfig {
             max_width: if self.max_width > 0.0 { Some(self.max_width) } else { None },
             max_height: if self.max_height > 0.0 { Some(self.max_height) } else { None },
-            max_lines: None,  // Not exposed in FFI yet
+            max_lines: if self.max_lines > 0 { Some(self.max_lines as usize) } else { None },
             line_height: self.line_height,
             letter_spacing: self.letter_spacing,
             word_spacing: self.word_spacing,
@@ -423,6 +658,16 @@ impl FFIDrawTextCommand {
             word_break: WordBreak::from(self.word_break),
             overflow: TextOverflow::from(self.overflow),
             white_space: WhiteSpace::from(self.white_space),
+            anchor: TextAnchor::from(self.anchor),
+            base_direction: TextDirection::from(self.base_direction),
+        };
+
+        // Truncated or unknown gradient data falls back to the flat color
+        let gradient = if self.gradient_ptr.is_null() || self.gradient_len == 0 {
+            None
+        } else {
+            let data = std::slice::from_raw_parts(self.gradient_ptr, self.gradient_len);
+            parse_batch_gradient(data, &mut 0).flatten()
         };
 
         RenderCommand::DrawText {
@@ -432,6 +677,7 @@ impl FFIDrawTextCommand {
             font,
             color: self.color,
             layout,
+            gradient,
         }
     }
 }
@@ -455,8 +701,11 @@ impl FFIRenderCommand {
                     color: rect.color,
                     corner_radii: [r, r, r, r],
                     rotation: 0.0, // C FFI doesn't support rotation yet
+                    pivot: None,
                     border: None,
                     gradient: None,
+                    inner_shadow: None,
+                    shadow: None,
                 }
             },
             1 => {
@@ -482,6 +731,40 @@ impl FFIRenderCommand {
                 let a = (color_u32 & 0xFF) as u8;
                 RenderCommand::Clear(crate::style::Color { r, g, b, a })
             },
+            6 => {
+                let line = &*self.data.draw_line;
+                RenderCommand::DrawLine {
+                    x1: line.x1,
+                    y1: line.y1,
+                    x2: line.x2,
+                    y2: line.y2,
+                    width: line.width,
+                    color: line.color,
+                    cap: LineCap::from(line.cap),
+                }
+            },
+            7 => {
+                let clip = &*self.data.push_rounded_clip;
+                RenderCommand::PushRoundedClip {
+                    x: clip.x,
+                    y: clip.y,
+                    width: clip.width,
+                    height: clip.height,
+                    corner_radii: clip.corner_radii,
+                }
+            },
+            8 => {
+                let arc = &*self.data.draw_arc;
+                RenderCommand::DrawArc {
+                    cx: arc.cx,
+                    cy: arc.cy,
+                    radius: arc.radius,
+                    start_angle: arc.start_angle,
+                    end_angle: arc.end_angle,
+                    thickness: arc.thickness,
+                    color: arc.color,
+                }
+            },
             _ => RenderCommand::PopClip {},  // Fallback
         }
     }
@@ -500,7 +783,7 @@ pub unsafe extern "C" fn centered_engine_render_batch(
     commands_len: usize,
 ) -> i32 {
     if commands_ptr.is_null() || commands_len == 0 {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     // Convert FFI commands to internal commands
@@ -514,7 +797,7 @@ pub unsafe extern "C" fn centered_engine_render_batch(
     // TODO: Execute commands through platform backend
     // For now, just validate we can convert them
     #[cfg(debug_assertions)]
-    println!("Received {} render commands via FFI", render_commands.len());
+    verbose!("Received {} render commands via FFI", render_commands.len());
 
     0  // Success
 }
@@ -527,7 +810,11 @@ pub unsafe extern "C" fn centered_engine_render_batch(
 // The C FFI backend functions are not used on wasm32 - web uses wasm-bindgen in platform/web.rs.
 #[cfg(not(target_arch = "wasm32"))]
 use crate::platform::wgpu_backend::{SurfaceConfig, WgpuBackend};
-use crate::platform::window_styling::{apply_window_style, WindowStyleOptions};
+#[cfg(feature = "winit")]
+use crate::platform::window_styling::{
+    apply_window_effects, apply_window_style, set_window_blur_behind, set_window_opacity,
+    WindowStyleOptions,
+};
 use std::sync::OnceLock;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -597,31 +884,35 @@ fn get_frameless_state() -> &'static Mutex<FramelessState> {
 /// # Arguments
 /// * `window_handle` - Platform-specific window handle:
 ///   - macOS: NSView pointer (from GLFW: glfwGetCocoaWindow, then contentView)
-///   - Windows: HWND
-///   - Linux/X11: Window (XID)
-///   - Linux/Wayland: wl_surface pointer
+///   - Windows: HWND of a window the caller created (validated with IsWindow)
+///   - Linux: not accepted, as the handle doesn't say which window system
+///     it's from; use `centered_backend_init_x11` or
+///     `centered_backend_init_wayland`
+/// * `display_handle` - Reserved; ignored
 /// * `width` - Width in physical pixels
 /// * `height` - Height in physical pixels
 /// * `scale_factor` - HiDPI scale factor (e.g., 2.0 for Retina)
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// 0 on success, `EngineError::InvalidArgument` for a null handle (or on
+/// Windows, one that isn't a window), `EngineError::Unsupported` on Linux,
+/// or `EngineError::OperationFailed` if the surface or GPU device couldn't
+/// be created
 ///
 /// # Safety
 /// - window_handle must be a valid native window/view pointer
 /// - The window must remain valid for the lifetime of the backend
-#[cfg(not(target_arch = "wasm32"))]
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(any(target_arch = "wasm32", target_os = "linux")))]
 #[no_mangle]
 pub unsafe extern "C" fn centered_backend_init(
     window_handle: *mut std::ffi::c_void,
-    _display_handle: *mut std::ffi::c_void,  // Reserved for Linux/Wayland
+    display_handle: *mut std::ffi::c_void,
     width: u32,
     height: u32,
     scale_factor: f64,
 ) -> i32 {
     if window_handle.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     // Create the backend
@@ -662,6 +953,9 @@ pub unsafe extern "C" fn centered_backend_init(
             }
         }
 
+        // AppKit has no display connection to pass
+        let _ = display_handle;
+
         // SAFETY: Caller guarantees window_handle is a valid NSView
         let wrapper = MacOSWindowHandle {
             ns_view: window_handle,
@@ -674,17 +968,126 @@ pub unsafe extern "C" fn centered_backend_init(
             vsync: true,
             low_power_gpu: false,
             allow_software_fallback: false,
+            gamma_correct_text: false,
+            subpixel_text: false,
+            wide_gamut: false,
+            display_p3_colors: false,
         };
 
         // Initialize backend with window
         pollster::block_on(backend.init_with_window(&wrapper, config))
     };
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "windows")]
+    let result = {
+        use raw_window_handle::{
+            HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle, Win32WindowHandle,
+            WindowsDisplayHandle,
+        };
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::IsWindow;
+
+        // Win32 has no display connection to pass
+        let _ = display_handle;
+
+        let hwnd = HWND(window_handle);
+        if !IsWindow(hwnd).as_bool() {
+            return EngineError::InvalidArgument.code();
+        }
+
+        // The module that registered the window's class; optional, but
+        // passed along like winit does
+        #[cfg(target_pointer_width = "64")]
+        let hinstance = windows::Win32::UI::WindowsAndMessaging::GetWindowLongPtrW(
+            hwnd,
+            windows::Win32::UI::WindowsAndMessaging::GWLP_HINSTANCE,
+        );
+        #[cfg(target_pointer_width = "32")]
+        let hinstance = windows::Win32::UI::WindowsAndMessaging::GetWindowLongW(
+            hwnd,
+            windows::Win32::UI::WindowsAndMessaging::GWLP_HINSTANCE,
+        ) as isize;
+
+        struct WindowsWindowHandle {
+            hwnd: std::num::NonZeroIsize,
+            hinstance: Option<std::num::NonZeroIsize>,
+        }
+
+        // SAFETY: The HWND is valid and we only use it from one thread
+        unsafe impl Send for WindowsWindowHandle {}
+        unsafe impl Sync for WindowsWindowHandle {}
+
+        impl HasWindowHandle for WindowsWindowHandle {
+            fn window_handle(&self) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+                let mut handle = Win32WindowHandle::new(self.hwnd);
+                handle.hinstance = self.hinstance;
+                let raw = RawWindowHandle::Win32(handle);
+                // SAFETY: The handle is valid for the lifetime of self
+                Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
+            }
+        }
+
+        impl HasDisplayHandle for WindowsWindowHandle {
+            fn display_handle(&self) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+                let raw = RawDisplayHandle::Windows(WindowsDisplayHandle::new());
+                // SAFETY: Windows display handle is always valid
+                Ok(unsafe { raw_window_handle::DisplayHandle::borrow_raw(raw) })
+            }
+        }
+
+        // SAFETY: window_handle was checked non-null above and is a live window
+        let wrapper = WindowsWindowHandle {
+            hwnd: std::num::NonZeroIsize::new_unchecked(window_handle as isize),
+            hinstance: std::num::NonZeroIsize::new(hinstance),
+        };
+
+        let config = SurfaceConfig {
+            width,
+            height,
+            scale_factor,
+            vsync: true,
+            low_power_gpu: false,
+            allow_software_fallback: false,
+            gamma_correct_text: false,
+            subpixel_text: false,
+            wide_gamut: false,
+            display_p3_colors: false,
+        };
+
+        pollster::block_on(backend.init_with_window(&wrapper, config))
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     let result: Result<(), Box<dyn std::error::Error>> = {
+        let _ = display_handle;
         Err("Platform not yet supported for FFI window initialization".into())
     };
 
+    store_initialized_backend(backend, result)
+}
+
+/// `centered_backend_init` on Linux, which can't tell an X11 window from a
+/// Wayland surface: always `EngineError::Unsupported`
+///
+/// # Safety
+/// None of the arguments are read
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_init(
+    _window_handle: *mut std::ffi::c_void,
+    _display_handle: *mut std::ffi::c_void,
+    _width: u32,
+    _height: u32,
+    _scale_factor: f64,
+) -> i32 {
+    eprintln!("Failed to initialize backend: use centered_backend_init_x11 or centered_backend_init_wayland on Linux");
+    EngineError::Unsupported.code()
+}
+
+/// Keep `backend` as the rendering backend if its initialization succeeded,
+/// returning the FFI status code
+#[cfg(not(target_arch = "wasm32"))]
+fn store_initialized_backend(backend: WgpuBackend, result: Result<(), Box<dyn std::error::Error>>) -> i32 {
     match result {
         Ok(()) => {
             // Store the backend
@@ -695,9 +1098,181 @@ pub unsafe extern "C" fn centered_backend_init(
         }
         Err(e) => {
             eprintln!("Failed to initialize backend: {}", e);
-            -2
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
+/// Initialize the rendering backend with an X11 window (Linux)
+///
+/// The Linux counterpart of `centered_backend_init` for a window the caller
+/// created on an X server through Xlib (e.g. GLFW's glfwGetX11Window and
+/// glfwGetX11Display).
+///
+/// # Arguments
+/// * `window` - The window's XID
+/// * `display` - The `Display*` the window was created on
+/// * `screen` - The window's screen number (e.g. `XDefaultScreen(display)`)
+/// * `width` - Width in physical pixels
+/// * `height` - Height in physical pixels
+/// * `scale_factor` - HiDPI scale factor
+///
+/// # Returns
+/// 0 on success, `EngineError::InvalidArgument` for a zero window, a null
+/// display or a negative screen, or `EngineError::OperationFailed` if the
+/// surface or GPU device couldn't be created
+///
+/// # Safety
+/// - display must be an open Xlib connection and window a window on it
+/// - Both must remain valid for the lifetime of the backend
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_init_x11(
+    window: std::ffi::c_ulong,
+    display: *mut std::ffi::c_void,
+    screen: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+) -> i32 {
+    use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+    use std::ptr::NonNull;
+
+    let Some(display) = NonNull::new(display) else {
+        return EngineError::InvalidArgument.code();
+    };
+    if window == 0 || screen < 0 {
+        return EngineError::InvalidArgument.code();
+    }
+
+    // X11 window (an XID, not a pointer) on its Xlib display
+    struct X11WindowHandle {
+        window: std::ffi::c_ulong,
+        display: NonNull<std::ffi::c_void>,
+        screen: i32,
+    }
+
+    // SAFETY: The window and display are valid and we only use them from
+    // one thread
+    unsafe impl Send for X11WindowHandle {}
+    unsafe impl Sync for X11WindowHandle {}
+
+    impl HasWindowHandle for X11WindowHandle {
+        fn window_handle(&self) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+            let raw = RawWindowHandle::Xlib(raw_window_handle::XlibWindowHandle::new(self.window));
+            // SAFETY: The handle is valid for the lifetime of self
+            Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
+        }
+    }
+
+    impl HasDisplayHandle for X11WindowHandle {
+        fn display_handle(&self) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+            let raw = RawDisplayHandle::Xlib(raw_window_handle::XlibDisplayHandle::new(Some(self.display), self.screen));
+            // SAFETY: The display outlives the window on it
+            Ok(unsafe { raw_window_handle::DisplayHandle::borrow_raw(raw) })
+        }
+    }
+
+    let config = SurfaceConfig {
+        width,
+        height,
+        scale_factor,
+        vsync: true,
+        low_power_gpu: false,
+        allow_software_fallback: false,
+        gamma_correct_text: false,
+        subpixel_text: false,
+        wide_gamut: false,
+        display_p3_colors: false,
+    };
+
+    let mut backend = WgpuBackend::new();
+    let wrapper = X11WindowHandle { window, display, screen };
+    let result = pollster::block_on(backend.init_with_window(&wrapper, config));
+    store_initialized_backend(backend, result)
+}
+
+/// Initialize the rendering backend with a Wayland surface (Linux)
+///
+/// The Linux counterpart of `centered_backend_init` for a surface the
+/// caller created on a Wayland compositor (e.g. GLFW's
+/// glfwGetWaylandWindow and glfwGetWaylandDisplay).
+///
+/// # Arguments
+/// * `surface` - The `wl_surface*`
+/// * `display` - The `wl_display*` the surface was created on
+/// * `width` - Width in physical pixels
+/// * `height` - Height in physical pixels
+/// * `scale_factor` - HiDPI scale factor
+///
+/// # Returns
+/// 0 on success, `EngineError::InvalidArgument` for a null surface or
+/// display, or `EngineError::OperationFailed` if the surface or GPU device
+/// couldn't be created
+///
+/// # Safety
+/// - display must be a live compositor connection and surface a surface on it
+/// - Both must remain valid for the lifetime of the backend
+#[cfg(target_os = "linux")]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_init_wayland(
+    surface: *mut std::ffi::c_void,
+    display: *mut std::ffi::c_void,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+) -> i32 {
+    use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+    use std::ptr::NonNull;
+
+    let (Some(surface), Some(display)) = (NonNull::new(surface), NonNull::new(display)) else {
+        return EngineError::InvalidArgument.code();
+    };
+
+    // Wayland surface on its compositor connection
+    struct WaylandSurfaceHandle {
+        surface: NonNull<std::ffi::c_void>,
+        display: NonNull<std::ffi::c_void>,
+    }
+
+    // SAFETY: The surface and display are valid and we only use them from
+    // one thread
+    unsafe impl Send for WaylandSurfaceHandle {}
+    unsafe impl Sync for WaylandSurfaceHandle {}
+
+    impl HasWindowHandle for WaylandSurfaceHandle {
+        fn window_handle(&self) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+            let raw = RawWindowHandle::Wayland(raw_window_handle::WaylandWindowHandle::new(self.surface));
+            // SAFETY: The handle is valid for the lifetime of self
+            Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
+        }
+    }
+
+    impl HasDisplayHandle for WaylandSurfaceHandle {
+        fn display_handle(&self) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+            let raw = RawDisplayHandle::Wayland(raw_window_handle::WaylandDisplayHandle::new(self.display));
+            // SAFETY: The display outlives the surface on it
+            Ok(unsafe { raw_window_handle::DisplayHandle::borrow_raw(raw) })
         }
     }
+
+    let config = SurfaceConfig {
+        width,
+        height,
+        scale_factor,
+        vsync: true,
+        low_power_gpu: false,
+        allow_software_fallback: false,
+        gamma_correct_text: false,
+        subpixel_text: false,
+        wide_gamut: false,
+        display_p3_colors: false,
+    };
+
+    let mut backend = WgpuBackend::new();
+    let wrapper = WaylandSurfaceHandle { surface, display };
+    let result = pollster::block_on(backend.init_with_window(&wrapper, config));
+    store_initialized_backend(backend, result)
 }
 
 /// Destroy the rendering backend and free resources
@@ -734,10 +1309,58 @@ pub unsafe extern "C" fn centered_backend_resize(width: u32, height: u32, scale_
     if let Some(backend) = guard.as_mut() {
         match backend.resize(width, height, scale_factor) {
             Ok(()) => 0,
-            Err(_) => -2,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotInitialized.code()
+    }
+}
+
+/// Turn vsync on or off without recreating the backend
+///
+/// The surface is reconfigured in place; pipelines, textures and the glyph
+/// atlas are kept. If the surface is currently unavailable (minimized or
+/// lost) the setting applies when it is next configured. Safe to call from
+/// the frame callback.
+///
+/// # Returns
+/// 0 on success, `EngineError::NotInitialized` if there is no backend
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_backend_set_vsync(enabled: bool) -> i32 {
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_mut() {
+        backend.set_present_mode(enabled);
+        0
+    } else {
+        EngineError::NotInitialized.code()
+    }
+}
+
+/// Pre-warm GPU pipelines, buffers and the glyph atlas
+///
+/// Renders one off-screen frame that exercises every pipeline so the first
+/// real frame doesn't stutter on driver shader compilation. Call once after
+/// `centered_backend_init`. Nothing is presented.
+///
+/// # Returns
+/// 0 on success, `EngineError::NotInitialized` if there is no backend, or
+/// `EngineError::OperationFailed` if prewarming failed
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_backend_prewarm() -> i32 {
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_mut() {
+        match backend.prewarm() {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -1
+        EngineError::NotInitialized.code()
     }
 }
 
@@ -760,12 +1383,12 @@ pub unsafe extern "C" fn centered_backend_render_frame(
     commands_json: *const c_char,
 ) -> i32 {
     if commands_json.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let json_str = match CStr::from_ptr(commands_json).to_str() {
         Ok(s) => s,
-        Err(_) => return -2,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     // Parse commands from JSON
@@ -773,18 +1396,126 @@ pub unsafe extern "C" fn centered_backend_render_frame(
         Ok(cmds) => cmds,
         Err(e) => {
             eprintln!("Failed to parse render commands: {}", e);
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
     };
 
+    render_commands(&commands)
+}
+
+/// Render a frame from binary-encoded commands
+///
+/// Same as centered_backend_render_frame, but takes the compact binary
+/// format described at `decode_render_commands` instead of JSON, so no
+/// parsing work scales with field names or number formatting.
+///
+/// # Arguments
+/// * `data_ptr` - Pointer to the encoded frame
+/// * `data_len` - Length of the frame in bytes
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer or malformed frame
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: rendering failed
+///
+/// # Safety
+/// - data_ptr must point to valid memory of at least data_len bytes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_render_frame_binary(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> i32 {
+    if data_ptr.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    let data = std::slice::from_raw_parts(data_ptr, data_len);
+
+    let commands = match decode_render_commands(data) {
+        Ok(cmds) => cmds,
+        Err(e) => {
+            eprintln!("Failed to decode render commands: {}", e);
+            return EngineError::InvalidArgument.code();
+        }
+    };
+
+    render_commands(&commands)
+}
+
+/// Windowless renderer behind `centered_render_to_buffer`, created on first use
+#[cfg(not(target_arch = "wasm32"))]
+static HEADLESS_RENDERER: Mutex<Option<crate::render::Renderer>> = Mutex::new(None);
+
+/// Render commands without a window into a caller-provided RGBA buffer
+///
+/// Independent of the engine, its render mode and the window backend, so it
+/// works in CI without a display or GPU (a software adapter is used when no
+/// GPU is available). Animations are frozen at their first frame, so the
+/// same commands produce the same bytes on a given machine, e.g. for hashing
+/// in golden-image tests.
+///
+/// # Arguments
+/// * `commands_json` - JSON array of render commands
+/// * `width`, `height` - Output size in pixels
+/// * `out_ptr` - Receives RGBA8 sRGB pixels, rows top to bottom, no padding
+/// * `out_len` - Size of the buffer, at least width * height * 4 bytes
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer, bad JSON, zero size, or buffer too small
+/// - `OperationFailed`: no adapter available, or rendering failed
+///
+/// # Safety
+/// - commands_json must be a valid null-terminated UTF-8 string
+/// - out_ptr must point to writable memory of at least out_len bytes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_render_to_buffer(
+    commands_json: *const c_char,
+    width: u32,
+    height: u32,
+    out_ptr: *mut u8,
+    out_len: usize,
+) -> i32 {
+    if commands_json.is_null() || out_ptr.is_null() || width == 0 || height == 0 {
+        return EngineError::InvalidArgument.code();
+    }
+    if (out_len as u64) < width as u64 * height as u64 * 4 {
+        return EngineError::InvalidArgument.code();
+    }
+    let commands: Vec<RenderCommand> = match CStr::from_ptr(commands_json).to_str().map(serde_json::from_str) {
+        Ok(Ok(cmds)) => cmds,
+        _ => return EngineError::InvalidArgument.code(),
+    };
+
+    let mut guard = HEADLESS_RENDERER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let renderer = guard.get_or_insert_with(|| crate::render::Renderer::new(RenderMode::Immediate));
+    match renderer.render_offscreen(&commands, width, height) {
+        Ok(pixels) => {
+            std::ptr::copy_nonoverlapping(pixels.as_ptr(), out_ptr, pixels.len());
+            0
+        }
+        Err(e) => {
+            eprintln!("Headless render failed: {}", e);
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
+/// Render decoded commands with the platform's backend, returning an FFI code
+#[cfg(not(target_arch = "wasm32"))]
+fn render_commands(commands: &[RenderCommand]) -> i32 {
     // On iOS, use the thread-local backend
     #[cfg(target_os = "ios")]
     {
-        match crate::platform::ios::render_frame(&commands) {
+        let result = crate::platform::ios::render_frame(commands);
+        record_render_result(&result);
+        match result {
             Ok(()) => return 0,
             Err(e) => {
                 eprintln!("iOS render error: {}", e);
-                return -4;
+                return EngineError::OperationFailed.code();
             }
         }
     }
@@ -796,16 +1527,18 @@ pub unsafe extern "C" fn centered_backend_render_frame(
         let mut guard = backend_lock.lock().unwrap();
 
         if let Some(backend) = guard.as_mut() {
-            match backend.render_frame(&commands) {
+            let result = backend.render_frame(commands);
+            record_render_result(&result);
+            match result {
                 Ok(()) => 0,
                 Err(e) => {
                     eprintln!("Render error: {}", e);
-                    -4
+                    EngineError::OperationFailed.code()
                 }
             }
         } else {
             eprintln!("Backend not initialized");
-            -5
+            EngineError::NotInitialized.code()
         }
     }
 }
@@ -836,11 +1569,13 @@ pub unsafe extern "C" fn centered_backend_end_frame() -> i32 {
 // Image/Texture Management FFI
 // ============================================================================
 
-use crate::image::LoadedImage;
+use crate::image::{CompressedFormat, ImageUploadOptions, LoadedImage};
 
 /// Load an image from raw bytes and return a texture ID
 ///
-/// Supports PNG and JPEG formats. The image is decoded and uploaded to the GPU.
+/// Supports PNG, JPEG and GIF formats. The image is decoded and uploaded to
+/// the GPU; animated images load their first frame (see
+/// centered_backend_load_animated_image).
 ///
 /// # Arguments
 /// * `data_ptr` - Pointer to image file data (PNG, JPEG, etc.)
@@ -848,10 +1583,10 @@ use crate::image::LoadedImage;
 ///
 /// # Returns
 /// Positive texture ID on success, negative error code on failure:
-/// - -1: Invalid parameters (null pointer or zero length)
-/// - -2: Backend not initialized
-/// - -3: Failed to decode image
-/// - -4: Failed to upload to GPU
+/// - `InvalidArgument`: null pointer or zero length
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: failed to decode image
+/// - `GpuUploadFailed`: failed to upload to GPU
 ///
 /// # Safety
 /// - data_ptr must point to valid memory of at least data_len bytes
@@ -862,37 +1597,198 @@ pub unsafe extern "C" fn centered_backend_load_image(
     data_ptr: *const u8,
     data_len: usize,
 ) -> i32 {
-    if data_ptr.is_null() || data_len == 0 {
-        return -1;
+    let loaded_image = match decode_image_data(data_ptr, data_len) {
+        Ok(img) => img,
+        Err(code) => return code,
+    };
+
+    // Get the backend and upload
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_mut() {
+        upload_image(backend, &loaded_image)
+    } else {
+        eprintln!("Backend not initialized");
+        EngineError::NotInitialized.code()
     }
+}
 
-    // Copy the data
-    let data = std::slice::from_raw_parts(data_ptr, data_len);
+// Upload options for `centered_backend_load_image_with_options`; combine with `|`
+
+/// Premultiply straight-alpha pixels on upload. Off by default, which keeps
+/// the data as decoded; turn it on for images with soft or antialiased
+/// edges, which otherwise show a dark fringe where they are scaled or
+/// filtered against transparent pixels.
+pub const IMAGE_PREMULTIPLY: u32 = 0x01;
+/// Upload only the full-size image, without a mip chain
+pub const IMAGE_NO_MIPMAPS: u32 = 0x02;
+/// Every upload option bit; anything outside it is rejected
+pub const IMAGE_ALL: u32 = IMAGE_PREMULTIPLY | IMAGE_NO_MIPMAPS;
+
+/// The upload options for a set of IMAGE_* bits, or None if it has unknown bits
+fn image_upload_options(flags: u32) -> Option<ImageUploadOptions> {
+    if flags & !IMAGE_ALL != 0 {
+        return None;
+    }
+    Some(ImageUploadOptions {
+        premultiply: flags & IMAGE_PREMULTIPLY != 0,
+        mipmaps: flags & IMAGE_NO_MIPMAPS == 0,
+    })
+}
 
-    // Decode the image
-    let loaded_image = match LoadedImage::from_bytes(data) {
+/// Load an image like `centered_backend_load_image`, with upload options
+///
+/// # Arguments
+/// * `data_ptr` - Pointer to encoded image data (PNG, JPEG, etc.)
+/// * `data_len` - Length of data in bytes
+/// * `flags` - IMAGE_* bits; 0 uploads exactly like `centered_backend_load_image`
+///
+/// # Returns
+/// Positive texture ID on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer, zero length or unknown flag bits
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: failed to decode image
+/// - `GpuUploadFailed`: failed to upload to GPU
+///
+/// # Safety
+/// - data_ptr must point to valid memory of at least data_len bytes
+/// - The data is copied, so the caller can free data_ptr after this returns
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_load_image_with_options(
+    data_ptr: *const u8,
+    data_len: usize,
+    flags: u32,
+) -> i32 {
+    let Some(options) = image_upload_options(flags) else {
+        return EngineError::InvalidArgument.code();
+    };
+    let loaded_image = match decode_image_data(data_ptr, data_len) {
         Ok(img) => img,
-        Err(e) => {
-            eprintln!("Failed to decode image: {}", e);
-            return -3;
-        }
+        Err(code) => return code,
     };
 
-    // Get the backend and upload
     let backend_lock = get_backend();
     let mut guard = backend_lock.lock().unwrap();
 
     if let Some(backend) = guard.as_mut() {
-        match backend.load_image(&loaded_image) {
-            Ok(texture_id) => texture_id as i32,
-            Err(e) => {
-                eprintln!("Failed to upload image to GPU: {}", e);
-                -4
-            }
-        }
+        upload_image_with_options(backend, &loaded_image, options)
     } else {
         eprintln!("Backend not initialized");
-        -2
+        EngineError::NotInitialized.code()
+    }
+}
+
+/// Encoded image bytes passed to `centered_backend_load_images`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FFIImageData {
+    /// Pointer to image file data (PNG, JPEG, etc.)
+    pub data_ptr: *const u8,
+    /// Length of data in bytes
+    pub data_len: usize,
+}
+
+/// Load a batch of images in one call, returning a texture ID for each
+///
+/// Decodes every image, then uploads them all under a single backend lock,
+/// so loading many icons at startup costs one FFI crossing instead of one
+/// per image.
+///
+/// # Arguments
+/// * `images` - Array of `count` image buffers
+/// * `count` - Number of images
+/// * `out_ids` - Array of `count` i32s that receives, in order, each image's
+///   texture ID or the negative error code `centered_backend_load_image`
+///   would have returned for it
+///
+/// # Returns
+/// The number of images loaded, or a negative error code:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+///
+/// # Safety
+/// - images must point to `count` FFIImageData, each valid as for
+///   `centered_backend_load_image`
+/// - out_ids must point to at least `count` i32s
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_load_images(
+    images: *const FFIImageData,
+    count: usize,
+    out_ids: *mut i32,
+) -> i32 {
+    if count > 0 && (images.is_null() || out_ids.is_null()) {
+        return EngineError::InvalidArgument.code();
+    }
+    if count == 0 {
+        return 0;
+    }
+    let images = std::slice::from_raw_parts(images, count);
+    let out_ids = std::slice::from_raw_parts_mut(out_ids, count);
+
+    // Decode outside the lock so rendering isn't held up
+    let decoded: Vec<Result<LoadedImage, i32>> = images
+        .iter()
+        .map(|image| decode_image_data(image.data_ptr, image.data_len))
+        .collect();
+
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+    let Some(backend) = guard.as_mut() else {
+        eprintln!("Backend not initialized");
+        return EngineError::NotInitialized.code();
+    };
+
+    let mut loaded = 0;
+    for (out_id, image) in out_ids.iter_mut().zip(&decoded) {
+        *out_id = match image {
+            Ok(image) => upload_image(backend, image),
+            Err(code) => *code,
+        };
+        if *out_id > 0 {
+            loaded += 1;
+        }
+    }
+    loaded
+}
+
+/// Decode image bytes for upload, or the load error code
+///
+/// # Safety
+/// data_ptr must point to valid memory of at least data_len bytes
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn decode_image_data(data_ptr: *const u8, data_len: usize) -> Result<LoadedImage, i32> {
+    if data_ptr.is_null() || data_len == 0 {
+        return Err(EngineError::InvalidArgument.code());
+    }
+
+    // Copy the data
+    let data = std::slice::from_raw_parts(data_ptr, data_len);
+
+    // Decode the image
+    LoadedImage::from_bytes(data).map_err(|e| {
+        eprintln!("Failed to decode image: {}", e);
+        EngineError::OperationFailed.code()
+    })
+}
+
+/// Upload a decoded image, returning its texture ID or `GpuUploadFailed`
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_image(backend: &mut WgpuBackend, image: &LoadedImage) -> i32 {
+    upload_image_with_options(backend, image, ImageUploadOptions::default())
+}
+
+/// `upload_image` with upload options
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_image_with_options(backend: &mut WgpuBackend, image: &LoadedImage, options: ImageUploadOptions) -> i32 {
+    match backend.load_image_with_options(image, options) {
+        Ok(texture_id) => texture_id as i32,
+        Err(e) => {
+            eprintln!("Failed to upload image to GPU: {}", e);
+            EngineError::GpuUploadFailed.code()
+        }
     }
 }
 
@@ -914,12 +1810,12 @@ pub unsafe extern "C" fn centered_backend_load_image_file(
     path: *const c_char,
 ) -> i32 {
     if path.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let path_str = match CStr::from_ptr(path).to_str() {
         Ok(s) => s,
-        Err(_) => return -1,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     // Load from file
@@ -927,7 +1823,7 @@ pub unsafe extern "C" fn centered_backend_load_image_file(
         Ok(img) => img,
         Err(e) => {
             eprintln!("Failed to load image file '{}': {}", path_str, e);
-            return -3;
+            return EngineError::OperationFailed.code();
         }
     };
 
@@ -940,541 +1836,1433 @@ pub unsafe extern "C" fn centered_backend_load_image_file(
             Ok(texture_id) => texture_id as i32,
             Err(e) => {
                 eprintln!("Failed to upload image to GPU: {}", e);
-                -4
+                EngineError::GpuUploadFailed.code()
             }
         }
     } else {
         eprintln!("Backend not initialized");
-        -2
+        EngineError::NotInitialized.code()
     }
 }
 
-/// Unload an image texture and free GPU resources
+/// Rasterize an SVG and return a texture ID
+///
+/// The SVG is drawn at exactly the requested pixel size, so pass the size it
+/// will be displayed at (logical size × scale factor) for crisp icons. The
+/// drawing keeps its aspect ratio, centered; a 0 dimension is derived from
+/// the other, and 0 x 0 uses the SVG's own size. Nothing is cached.
 ///
 /// # Arguments
-/// * `texture_id` - Texture ID returned by centered_backend_load_image
+/// * `data_ptr` - Pointer to SVG file data
+/// * `data_len` - Length of data in bytes
+/// * `width` - Texture width in pixels
+/// * `height` - Texture height in pixels
 ///
 /// # Returns
-/// 0 on success, negative error code on failure:
-/// - -1: Invalid texture ID
-/// - -2: Backend not initialized
+/// Positive texture ID on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer or zero length
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: failed to parse or rasterize the SVG
+/// - `GpuUploadFailed`: failed to upload to GPU
+///
+/// # Safety
+/// - data_ptr must point to valid memory of at least data_len bytes
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_backend_unload_image(texture_id: u32) -> i32 {
+pub unsafe extern "C" fn centered_backend_load_svg(
+    data_ptr: *const u8,
+    data_len: usize,
+    width: u32,
+    height: u32,
+) -> i32 {
+    if data_ptr.is_null() || data_len == 0 {
+        return EngineError::InvalidArgument.code();
+    }
+    let data = std::slice::from_raw_parts(data_ptr, data_len);
+    let loaded_image = match LoadedImage::from_svg_bytes(data, width, height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("Failed to rasterize SVG: {}", e);
+            return EngineError::OperationFailed.code();
+        }
+    };
+
     let backend_lock = get_backend();
     let mut guard = backend_lock.lock().unwrap();
 
     if let Some(backend) = guard.as_mut() {
-        backend.unload_image(texture_id);
-        0
+        upload_image(backend, &loaded_image)
     } else {
-        -2
+        EngineError::NotInitialized.code()
     }
 }
 
-/// Get texture dimensions for a loaded image
+/// Upload block-compressed texture data (BCn, ETC2, ASTC) without
+/// decompressing it and return a texture ID
 ///
 /// # Arguments
-/// * `texture_id` - Texture ID returned by centered_backend_load_image
-/// * `width_out` - Pointer to store width (in pixels)
-/// * `height_out` - Pointer to store height (in pixels)
+/// * `format` - CompressedFormat code (see image.rs)
+/// * `data_ptr` - Pointer to the compressed blocks, all mip levels
+///   largest first, each tightly packed
+/// * `data_len` - Length of data in bytes
+/// * `width`, `height` - Size of level 0 in pixels, a multiple of the block size
+/// * `mip_levels` - Number of mip levels in the data (at least 1)
 ///
 /// # Returns
-/// 0 on success, negative error code on failure:
-/// - -1: Invalid texture ID or texture not found
-/// - -2: Backend not initialized
-/// - -3: Null pointer for width_out or height_out
+/// Positive texture ID on success, negative error code on failure:
+/// - `InvalidArgument`: unknown format, bad size, or wrong data length
+/// - `NotInitialized`: backend not initialized
+/// - `GpuUploadFailed`: failed to upload to GPU
+/// - `Unsupported`: format not supported by the GPU
 ///
 /// # Safety
-/// - width_out and height_out must be valid pointers to u32
+/// - data_ptr must point to valid memory of at least data_len bytes
+/// - The data is copied, so the caller can free data_ptr after this returns
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_backend_get_texture_size(
-    texture_id: u32,
-    width_out: *mut u32,
-    height_out: *mut u32,
+pub unsafe extern "C" fn centered_backend_load_compressed_texture(
+    format: u32,
+    data_ptr: *const u8,
+    data_len: usize,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
 ) -> i32 {
-    if width_out.is_null() || height_out.is_null() {
-        return -3;
+    if data_ptr.is_null() || data_len == 0 {
+        return EngineError::InvalidArgument.code();
+    }
+    let Some(format) = CompressedFormat::from_u32(format) else {
+        eprintln!("Unknown compressed texture format: {}", format);
+        return EngineError::InvalidArgument.code();
+    };
+
+    let data = std::slice::from_raw_parts(data_ptr, data_len);
+    if let Err(e) = format.validate(data.len(), width, height, mip_levels) {
+        eprintln!("Invalid compressed texture: {}", e);
+        return EngineError::InvalidArgument.code();
     }
 
     let backend_lock = get_backend();
-    let guard = backend_lock.lock().unwrap();
+    let mut guard = backend_lock.lock().unwrap();
 
-    if let Some(backend) = guard.as_ref() {
-        if let Some((width, height)) = backend.get_texture_size(texture_id) {
-            *width_out = width;
-            *height_out = height;
-            0
-        } else {
-            -1
+    if let Some(backend) = guard.as_mut() {
+        if !backend.supports_compressed_format(format) {
+            eprintln!("{:?} textures are not supported by this GPU", format);
+            return EngineError::Unsupported.code();
+        }
+        match backend.load_compressed_texture(format, data, width, height, mip_levels) {
+            Ok(texture_id) => texture_id as i32,
+            Err(e) => {
+                eprintln!("Failed to upload compressed texture to GPU: {}", e);
+                EngineError::GpuUploadFailed.code()
+            }
         }
     } else {
-        -2
+        eprintln!("Backend not initialized");
+        EngineError::NotInitialized.code()
     }
 }
 
-// ============================================================================
-// Video FFI
-// ============================================================================
-//
-// Video playback API for loading and playing videos.
-// Uses platform-native decoders (AVFoundation on macOS) for hardware-accelerated decoding.
-
-use crate::video::player::VideoPlayer;
-use crate::video::VideoFrame;
-
-// Global video player storage
-lazy_static::lazy_static! {
-    static ref VIDEO_PLAYERS: std::sync::Mutex<std::collections::HashMap<u32, VideoPlayer>> = std::sync::Mutex::new(std::collections::HashMap::new());
-    static ref NEXT_PLAYER_ID: std::sync::Mutex<u32> = std::sync::Mutex::new(1);
-}
-
-/// Create a new video player
+/// Render text once into a reusable label texture and return a texture ID
+///
+/// The text is laid out with the command's font and layout and rasterized
+/// into a tight RGBA texture in physical pixels (x and y are ignored). Draw
+/// it with `DrawImage` at its size divided by the scale factor, and free it
+/// with `centered_backend_unload_image`.
 ///
 /// # Returns
-/// A unique player ID (always positive), or 0 on error
+/// Positive texture ID on success, negative error code on failure:
+/// - `InvalidArgument`: null command
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text has no visible glyphs
+/// - `GpuUploadFailed`: failed to upload to GPU
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_create() -> u32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    let mut next_id = NEXT_PLAYER_ID.lock().unwrap();
+pub unsafe extern "C" fn centered_backend_create_text_texture(cmd: *const FFIDrawTextCommand) -> i32 {
+    if cmd.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    let RenderCommand::DrawText { text, font, color, layout, .. } = (*cmd).to_render_command() else {
+        return EngineError::InvalidArgument.code();
+    };
 
-    let player_id = *next_id;
-    *next_id += 1;
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
 
-    players.insert(player_id, VideoPlayer::new());
-    player_id
+    if let Some(backend) = guard.as_mut() {
+        if backend.measure_text_box(&text, &font, &layout).is_none_or(|(w, h)| w <= 0.0 || h <= 0.0) {
+            return EngineError::OperationFailed.code();
+        }
+        match backend.create_text_texture(&text, &font, color, &layout) {
+            Ok(texture_id) => texture_id as i32,
+            Err(e) => {
+                eprintln!("Failed to create text texture: {}", e);
+                EngineError::GpuUploadFailed.code()
+            }
+        }
+    } else {
+        eprintln!("Backend not initialized");
+        EngineError::NotInitialized.code()
+    }
 }
 
-/// Destroy a video player and free resources
-///
-/// # Arguments
-/// * `player_id` - Player ID from centered_video_create
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_destroy(player_id: u32) {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    players.remove(&player_id);
+/// C-compatible position of one cluster of laid-out text
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FFIGlyphPosition {
+    /// Byte offset of the cluster in the text
+    pub byte_index: u32,
+    /// Left edge of the cluster in logical pixels (absolute, like the command's x)
+    pub x: f32,
+    /// Advance to the next cluster in logical pixels
+    pub advance: f32,
+    /// Visible line the cluster is on (0-based)
+    pub line: u32,
 }
 
-/// Load video from a URL (file:// or http://)
+/// Get the position of every cluster of a DrawText command after layout
+///
+/// For caret placement in text editors: positions are in drawing order and
+/// match the actual DrawText layout (wrapping, alignment, spacing and
+/// truncation), so Go can binary-search a click x within a line to find the
+/// byte index to put the caret at. The caret after a cluster is at
+/// `x + advance`.
 ///
 /// # Arguments
-/// * `player_id` - Player ID from centered_video_create
-/// * `url` - Null-terminated UTF-8 URL string
+/// * `cmd` - The DrawText command as it will be drawn
+/// * `out_ptr` - Receives the array (null when empty); free it with
+///   centered_text_glyph_positions_free
+/// * `out_len` - Receives the number of positions
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text could not be laid out (font unavailable)
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
+/// - out_ptr and out_len must be valid for writes
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_video_load_url(
-    player_id: u32,
-    url: *const c_char,
+pub unsafe extern "C" fn centered_text_glyph_positions(
+    cmd: *const FFIDrawTextCommand,
+    out_ptr: *mut *mut FFIGlyphPosition,
+    out_len: *mut usize,
 ) -> i32 {
-    if url.is_null() {
-        return -1;
+    if cmd.is_null() || out_ptr.is_null() || out_len.is_null() {
+        return EngineError::InvalidArgument.code();
     }
+    *out_ptr = ptr::null_mut();
+    *out_len = 0;
+    let RenderCommand::DrawText { x, text, font, layout, .. } = (*cmd).to_render_command() else {
+        return EngineError::InvalidArgument.code();
+    };
 
-    let url_str = match CStr::from_ptr(url).to_str() {
-        Ok(s) => s,
-        Err(_) => return -1,
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    let Some(backend) = guard.as_mut() else {
+        eprintln!("Backend not initialized");
+        return EngineError::NotInitialized.code();
+    };
+    if text.is_empty() {
+        return 0;
+    }
+    let Some(positions) = backend.text_glyph_positions(&text, &font, x, &layout) else {
+        return EngineError::OperationFailed.code();
     };
 
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        match player.load_url(url_str) {
-            Ok(()) => 0,
-            Err(e) => {
-                eprintln!("Video load error: {}", e);
-                -3
-            }
-        }
-    } else {
-        -2 // Player not found
+    let positions: Box<[FFIGlyphPosition]> = positions
+        .into_iter()
+        .map(|p| FFIGlyphPosition { byte_index: p.byte_index as u32, x: p.x, advance: p.advance, line: p.line })
+        .collect();
+    *out_len = positions.len();
+    if !positions.is_empty() {
+        *out_ptr = Box::into_raw(positions) as *mut FFIGlyphPosition;
     }
+    0
 }
 
-/// Load video from a file path
-///
-/// # Arguments
-/// * `player_id` - Player ID from centered_video_create
-/// * `path` - Null-terminated UTF-8 file path
+/// Free an array returned by centered_text_glyph_positions
 ///
-/// # Returns
-/// 0 on success, negative error code on failure
+/// # Safety
+/// - ptr and len must be exactly as returned by centered_text_glyph_positions
+/// - ptr must not be used after this call
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_video_load_file(
-    player_id: u32,
-    path: *const c_char,
-) -> i32 {
-    if path.is_null() {
-        return -1;
+pub unsafe extern "C" fn centered_text_glyph_positions_free(ptr: *mut FFIGlyphPosition, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
     }
+}
 
-    let path_str = match CStr::from_ptr(path).to_str() {
-        Ok(s) => s,
-        Err(_) => return -1,
+/// Lay out a DrawText command for selection geometry
+///
+/// # Safety
+/// - cmd must be null or point to a valid FFIDrawTextCommand whose text and
+///   font name pointers are valid UTF-8 for their lengths
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn draw_text_layout(cmd: *const FFIDrawTextCommand) -> Result<crate::text::TextLayout, i32> {
+    if cmd.is_null() {
+        return Err(EngineError::InvalidArgument.code());
+    }
+    let RenderCommand::DrawText { x, y, text, font, layout, .. } = (*cmd).to_render_command() else {
+        return Err(EngineError::InvalidArgument.code());
     };
 
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        match player.load_file(path_str) {
-            Ok(()) => 0,
-            Err(e) => {
-                eprintln!("Video load error: {}", e);
-                -3
-            }
-        }
-    } else {
-        -2
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    let Some(backend) = guard.as_mut() else {
+        eprintln!("Backend not initialized");
+        return Err(EngineError::NotInitialized.code());
+    };
+    backend.text_layout(&text, &font, x, y, &layout).ok_or(EngineError::OperationFailed.code())
+}
+
+/// C-compatible rectangle in logical pixels
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FFITextRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl From<crate::geometry::Rect> for FFITextRect {
+    fn from(rect: crate::geometry::Rect) -> Self {
+        Self { x: rect.x, y: rect.y, width: rect.width, height: rect.height }
     }
 }
 
-/// Initialize frame buffer for raw frame input (video streams)
+/// Find the byte index a click on a DrawText command puts the caret at
+///
+/// Uses the layout the command is drawn with, bidi reordering included: a
+/// click on the right half of a right-to-left cluster goes before it. A
+/// click above or below the text picks the first or last line, and one
+/// past either end of a line goes to that end of it.
 ///
 /// # Arguments
-/// * `player_id` - Player ID from centered_video_create
-/// * `width` - Initial frame width
-/// * `height` - Initial frame height
+/// * `cmd` - The DrawText command as it will be drawn
+/// * `x`, `y` - The point in logical pixels (absolute, like the command's x and y)
+/// * `byte_index_out` - Receives the byte index, always a cluster boundary
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text could not be laid out (font unavailable)
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
+/// - byte_index_out must be valid for writes
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_init_stream(
-    player_id: u32,
-    width: u32,
-    height: u32,
+pub unsafe extern "C" fn centered_text_hit_test(
+    cmd: *const FFIDrawTextCommand,
+    x: f32,
+    y: f32,
+    byte_index_out: *mut u32,
 ) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.init_frame_buffer(width, height);
-        0
-    } else {
-        -2
+    if byte_index_out.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    match draw_text_layout(cmd) {
+        Ok(layout) => {
+            *byte_index_out = layout.hit_test(x, y) as u32;
+            0
+        }
+        Err(code) => code,
     }
 }
 
-/// Push a raw frame for video streams
+/// Get the caret rectangle for a byte index of a DrawText command
+///
+/// The rectangle has zero width and spans the caret's line; draw it with a
+/// `Caret` command at its x and y. The index where a wrapped line ends is on
+/// the next line, and an index past the end of the text at the end of the
+/// last line.
 ///
 /// # Arguments
-/// * `player_id` - Player ID from centered_video_create
-/// * `width` - Frame width in pixels
-/// * `height` - Frame height in pixels
-/// * `data` - RGBA pixel data (width * height * 4 bytes)
-/// * `data_len` - Length of data in bytes
-/// * `timestamp_ms` - Presentation timestamp in milliseconds
+/// * `cmd` - The DrawText command as it will be drawn
+/// * `byte_index` - Byte offset of the caret in the text
+/// * `rect_out` - Receives the rectangle in logical pixels
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text could not be laid out (font unavailable)
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
+/// - rect_out must be valid for writes
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_video_push_frame(
-    player_id: u32,
-    width: u32,
-    height: u32,
-    data: *const u8,
-    data_len: usize,
-    timestamp_ms: u64,
+pub unsafe extern "C" fn centered_text_cursor_rect(
+    cmd: *const FFIDrawTextCommand,
+    byte_index: u32,
+    rect_out: *mut FFITextRect,
 ) -> i32 {
-    if data.is_null() || data_len == 0 {
-        return -1;
+    if rect_out.is_null() {
+        return EngineError::InvalidArgument.code();
     }
-
-    let expected_len = (width * height * 4) as usize;
-    if data_len < expected_len {
-        return -1;
+    match draw_text_layout(cmd) {
+        Ok(layout) => {
+            *rect_out = layout.cursor_rect(byte_index as usize).into();
+            0
+        }
+        Err(code) => code,
     }
+}
 
-    let frame_data = std::slice::from_raw_parts(data, data_len).to_vec();
-    let frame = VideoFrame::new(width, height, frame_data, timestamp_ms);
+/// Get the highlight rectangles for a byte range of a DrawText command
+///
+/// Returns one rectangle per visible line the range covers, top to bottom,
+/// each spanning the line's selected clusters; lines with nothing selected
+/// get none. `start` and `end` may be in either order.
+///
+/// # Arguments
+/// * `cmd` - The DrawText command as it will be drawn
+/// * `start`, `end` - Byte offsets bounding the selection
+/// * `out_ptr` - Receives the array (null when empty); free it with
+///   centered_text_selection_rects_free
+/// * `out_len` - Receives the number of rectangles
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text could not be laid out (font unavailable)
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
+/// - out_ptr and out_len must be valid for writes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_text_selection_rects(
+    cmd: *const FFIDrawTextCommand,
+    start: u32,
+    end: u32,
+    out_ptr: *mut *mut FFITextRect,
+    out_len: *mut usize,
+) -> i32 {
+    if out_ptr.is_null() || out_len.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    *out_ptr = ptr::null_mut();
+    *out_len = 0;
+    let layout = match draw_text_layout(cmd) {
+        Ok(layout) => layout,
+        Err(code) => return code,
+    };
 
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.push_frame(frame);
-        0
-    } else {
-        -2
+    let rects: Box<[FFITextRect]> = layout
+        .selection_rects(start as usize, end as usize)
+        .into_iter()
+        .map(FFITextRect::from)
+        .collect();
+    *out_len = rects.len();
+    if !rects.is_empty() {
+        *out_ptr = Box::into_raw(rects) as *mut FFITextRect;
     }
+    0
 }
 
-/// Start or resume video playback
+/// Free an array returned by centered_text_selection_rects
+///
+/// # Safety
+/// - ptr and len must be exactly as returned by centered_text_selection_rects
+/// - ptr must not be used after this call
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_play(player_id: u32) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.play();
-        0
-    } else {
-        -2
+pub unsafe extern "C" fn centered_text_selection_rects_free(ptr: *mut FFITextRect, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
     }
 }
 
-/// Pause video playback
+/// Measure the text of a DrawText command without drawing it
+///
+/// Lays the text out exactly as DrawText would (font, wrapping at
+/// `max_width`, explicit newlines, line height, spacing, `max_lines` and
+/// `max_height`), so Go can size a layout box before rendering. A
+/// `max_width` of 0 leaves lines unwrapped.
+///
+/// # Arguments
+/// * `cmd` - The DrawText command to measure (x, y and color are ignored)
+/// * `width_out` - Receives the widest visible line in logical pixels
+/// * `height_out` - Receives the first line's ascent to the last line's
+///   descent in logical pixels
+/// * `line_count_out` - Receives the number of visible lines
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: text could not be laid out (font unavailable)
+///
+/// # Safety
+/// - cmd must point to a valid FFIDrawTextCommand whose text and font name
+///   pointers are valid UTF-8 for their lengths
+/// - width_out, height_out and line_count_out must be valid for writes
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_pause(player_id: u32) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.pause();
-        0
-    } else {
-        -2
+pub unsafe extern "C" fn centered_text_measure(
+    cmd: *const FFIDrawTextCommand,
+    width_out: *mut f32,
+    height_out: *mut f32,
+    line_count_out: *mut u32,
+) -> i32 {
+    if cmd.is_null() || width_out.is_null() || height_out.is_null() || line_count_out.is_null() {
+        return EngineError::InvalidArgument.code();
     }
+    let RenderCommand::DrawText { text, font, layout, .. } = (*cmd).to_render_command() else {
+        return EngineError::InvalidArgument.code();
+    };
+
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    let Some(backend) = guard.as_mut() else {
+        eprintln!("Backend not initialized");
+        return EngineError::NotInitialized.code();
+    };
+    let Some(measurement) = backend.measure_text_layout(&text, &font, &layout) else {
+        return EngineError::OperationFailed.code();
+    };
+    *width_out = measurement.width;
+    *height_out = measurement.height;
+    *line_count_out = measurement.line_count;
+    0
 }
 
-/// Seek to a specific position
+/// Render commands into a new texture and return its texture ID
+///
+/// Caches an expensive sub-tree as an image: draw the returned ID with
+/// `DrawImage` at `width` x `height` to composite it, and free it with
+/// `centered_backend_unload_image`. Commands use the same JSON format and
+/// logical coordinates as `centered_backend_render_frame`, relative to the
+/// texture's top-left corner. Without a `Clear` command the texture starts
+/// transparent. The window's frame is not affected.
 ///
 /// # Arguments
-/// * `player_id` - Player ID
-/// * `timestamp_ms` - Target position in milliseconds
+/// * `commands_json` - JSON array of render commands
+/// * `width`, `height` - Texture size in logical pixels
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// Positive texture ID on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer, invalid JSON or zero size
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: size over the GPU's limit or rendering failed
+///
+/// # Safety
+/// - commands_json must be a valid null-terminated UTF-8 string
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_seek(player_id: u32, timestamp_ms: u64) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        match player.seek(timestamp_ms) {
-            Ok(()) => 0,
-            Err(_) => -3,
+pub unsafe extern "C" fn centered_backend_render_to_texture(
+    commands_json: *const c_char,
+    width: u32,
+    height: u32,
+) -> i32 {
+    if commands_json.is_null() || width == 0 || height == 0 {
+        return EngineError::InvalidArgument.code();
+    }
+    let commands: Vec<RenderCommand> = match CStr::from_ptr(commands_json).to_str().map(serde_json::from_str) {
+        Ok(Ok(cmds)) => cmds,
+        Ok(Err(e)) => {
+            eprintln!("Failed to parse render commands: {}", e);
+            return EngineError::InvalidArgument.code();
+        }
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_mut() {
+        match backend.render_to_texture(&commands, width, height) {
+            Ok(texture_id) => texture_id as i32,
+            Err(e) => {
+                eprintln!("Render to texture error: {}", e);
+                EngineError::OperationFailed.code()
+            }
         }
     } else {
-        -2
+        EngineError::NotInitialized.code()
     }
 }
 
-/// Set looping behavior
+/// Unload an image texture and free GPU resources
+///
+/// # Arguments
+/// * `texture_id` - Texture ID returned by centered_backend_load_image
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `NotInitialized`: backend not initialized
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_set_looping(player_id: u32, looping: bool) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.set_looping(looping);
+pub unsafe extern "C" fn centered_backend_unload_image(texture_id: u32) -> i32 {
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_mut() {
+        backend.unload_image(texture_id);
         0
     } else {
-        -2
+        EngineError::NotInitialized.code()
     }
 }
 
-/// Set muted state
+/// An animated image uploaded as one texture per frame
 #[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_set_muted(player_id: u32, muted: bool) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.set_muted(muted);
-        0
-    } else {
-        -2
-    }
+struct Animation {
+    texture_ids: Vec<u32>,
+    timing: crate::image::AnimationTiming,
 }
 
-/// Set volume (0.0 - 1.0)
+// Global animation storage
 #[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_set_volume(player_id: u32, volume: f32) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get_mut(&player_id) {
-        player.set_volume(volume);
-        0
-    } else {
-        -2
-    }
+lazy_static::lazy_static! {
+    static ref ANIMATIONS: std::sync::Mutex<std::collections::HashMap<u32, Animation>> = std::sync::Mutex::new(std::collections::HashMap::new());
+    static ref NEXT_ANIMATION_ID: std::sync::Mutex<u32> = std::sync::Mutex::new(1);
 }
 
-/// Get current playback state
+/// Load an animated GIF or APNG, uploading every frame as a texture
 ///
-/// # Returns
-/// PlaybackState as i32:
-/// - 0: Idle
-/// - 1: Loading
-/// - 2: Playing
-/// - 3: Paused
-/// - 4: Ended
-/// - 5: Error
-/// - Negative: Player not found
+/// Frames are composited with each frame's disposal method applied, so
+/// every texture is a complete picture. Still images load as a single frame.
+/// Draw the texture `centered_backend_animation_frame` returns each frame.
+///
+/// # Arguments
+/// * `data_ptr` - Pointer to image file data
+/// * `data_len` - Length of data in bytes
+///
+/// # Returns
+/// Positive animation handle on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer or zero length
+/// - `NotInitialized`: backend not initialized
+/// - `OperationFailed`: failed to decode image
+/// - `GpuUploadFailed`: failed to upload a frame to the GPU
+///
+/// # Safety
+/// - data_ptr must point to valid memory of at least data_len bytes
+/// - The data is copied, so the caller can free data_ptr after this returns
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_get_state(player_id: u32) -> i32 {
-    let players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get(&player_id) {
-        player.state() as i32
+pub unsafe extern "C" fn centered_backend_load_animated_image(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> i32 {
+    let loaded_image = match decode_image_data(data_ptr, data_len) {
+        Ok(img) => img,
+        Err(code) => return code,
+    };
+    let (frames, timing) = if loaded_image.is_animated() {
+        (loaded_image.frame_images(), loaded_image.timing())
     } else {
-        -2
+        let timing = crate::image::AnimationTiming { delays_ms: vec![0], loop_count: 0 };
+        (vec![loaded_image], timing)
+    };
+
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+    let Some(backend) = guard.as_mut() else {
+        return EngineError::NotInitialized.code();
+    };
+    let mut texture_ids = Vec::with_capacity(frames.len());
+    for frame in &frames {
+        let result = upload_image(backend, frame);
+        if result < 0 {
+            for texture_id in texture_ids {
+                backend.unload_image(texture_id);
+            }
+            return result;
+        }
+        texture_ids.push(result as u32);
     }
+    drop(guard);
+
+    let mut animations = ANIMATIONS.lock().unwrap();
+    let mut next_id = NEXT_ANIMATION_ID.lock().unwrap();
+    let handle = *next_id;
+    *next_id += 1;
+    animations.insert(handle, Animation { texture_ids, timing });
+    handle as i32
 }
 
-/// Get current playback position in milliseconds
+/// Texture to draw for an animation `elapsed_ms` after it started
+///
+/// Loops as many times as the image asks, then holds the last frame.
+///
+/// # Returns
+/// Texture ID of the current frame, or `NotFound` for an unknown handle
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_get_time(player_id: u32) -> u64 {
-    let players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get(&player_id) {
-        player.current_time_ms()
-    } else {
-        0
+pub extern "C" fn centered_backend_animation_frame(handle: u32, elapsed_ms: u64) -> i32 {
+    let animations = ANIMATIONS.lock().unwrap();
+    match animations.get(&handle) {
+        Some(animation) => animation.texture_ids[animation.timing.frame_at(elapsed_ms)] as i32,
+        None => EngineError::NotFound.code(),
     }
 }
 
-/// Get video info
+/// Unload an animation and free its frames' textures
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `NotFound`: unknown handle
+/// - `NotInitialized`: backend not initialized
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_backend_unload_animation(handle: u32) -> i32 {
+    let Some(animation) = ANIMATIONS.lock().unwrap().remove(&handle) else {
+        return EngineError::NotFound.code();
+    };
+    let backend_lock = get_backend();
+    let mut guard = backend_lock.lock().unwrap();
+    match guard.as_mut() {
+        Some(backend) => {
+            for texture_id in animation.texture_ids {
+                backend.unload_image(texture_id);
+            }
+            0
+        }
+        None => EngineError::NotInitialized.code(),
+    }
+}
+
+/// Get texture dimensions for a loaded image
 ///
 /// # Arguments
-/// * `player_id` - Player ID
-/// * `width_out` - Pointer to store video width
-/// * `height_out` - Pointer to store video height
-/// * `duration_ms_out` - Pointer to store duration in milliseconds
+/// * `texture_id` - Texture ID returned by centered_backend_load_image
+/// * `width_out` - Pointer to store width (in pixels)
+/// * `height_out` - Pointer to store height (in pixels)
 ///
 /// # Returns
-/// 0 on success, negative error code on failure
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer for width_out or height_out
+/// - `NotFound`: no texture with this ID
+/// - `NotInitialized`: backend not initialized
+///
+/// # Safety
+/// - width_out and height_out must be valid pointers to u32
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_video_get_info(
-    player_id: u32,
+pub unsafe extern "C" fn centered_backend_get_texture_size(
+    texture_id: u32,
     width_out: *mut u32,
     height_out: *mut u32,
-    duration_ms_out: *mut u64,
 ) -> i32 {
-    if width_out.is_null() || height_out.is_null() || duration_ms_out.is_null() {
-        return -1;
+    if width_out.is_null() || height_out.is_null() {
+        return EngineError::InvalidArgument.code();
     }
 
-    let players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get(&player_id) {
-        if let Some(info) = player.info() {
-            *width_out = info.width;
-            *height_out = info.height;
-            *duration_ms_out = info.duration_ms;
+    let backend_lock = get_backend();
+    let guard = backend_lock.lock().unwrap();
+
+    if let Some(backend) = guard.as_ref() {
+        if let Some((width, height)) = backend.get_texture_size(texture_id) {
+            *width_out = width;
+            *height_out = height;
             0
         } else {
-            -3 // No video loaded
+            EngineError::NotFound.code()
         }
     } else {
-        -2
+        EngineError::NotInitialized.code()
     }
 }
 
-/// Update video player and get texture ID for current frame
+/// Check a JSON frame for mistakes without rendering it
 ///
-/// This should be called once per frame. It advances the video playback
-/// and uploads new frames to the GPU as needed.
+/// Reports unbalanced clips and scroll views, texture ids that aren't
+/// loaded, NaN/infinite coordinates, empty text, and out-of-range opacities,
+/// gradient stops and colors. Texture ids are only checked once the backend
+/// is initialized.
 ///
-/// # Arguments
-/// * `player_id` - Player ID
+/// # Returns
+/// A JSON array of `{"index", "kind", "message"}` issues, empty for a clean
+/// frame (caller must free with centered_free_string), or null if
+/// commands_json is null or not a JSON array
+///
+/// # Safety
+/// - commands_json must be a valid null-terminated UTF-8 string
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_validate_commands(commands_json: *const c_char) -> *mut c_char {
+    if commands_json.is_null() {
+        return ptr::null_mut();
+    }
+    let json = match CStr::from_ptr(commands_json).to_str() {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let guard = get_backend().lock().unwrap();
+    let issues = match guard.as_ref() {
+        Some(backend) => crate::render::validate_commands_json(json, |id| backend.has_texture(id)),
+        None => crate::render::validate_commands_json(json, |_| true),
+    };
+    drop(guard);
+
+    let report = match issues.and_then(|issues| serde_json::to_string(&issues)) {
+        Ok(report) => report,
+        Err(_) => return ptr::null_mut(),
+    };
+    match CString::new(report) {
+        Ok(c_str) => c_str.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Lay out a JSON tree of flexbox, block and absolute nodes
+///
+/// Each node has an `id`, the `LayoutConstraints` fields (`width`, `height`,
+/// their min/max as CSS dimensions, `padding_*`, `margin_*`), the flex
+/// properties (`flex_direction`, `flex_wrap`, `justify_content`,
+/// `align_items`, `flex_grow`, `flex_shrink`, `flex_basis`, `gap`) and
+/// `children`. Missing fields take their CSS defaults.
 ///
 /// # Returns
-/// Texture ID if a frame is available (use with DrawImage), 0 if no frame, negative on error
+/// A JSON array of `{"id", "x", "y", "width", "height"}` rects, one per node
+/// in depth-first order, in the coordinates of a width x height area
+/// (caller must free with centered_free_string), or null if tree_json is
+/// null or not a valid tree
+///
+/// # Safety
+/// - tree_json must be a valid null-terminated UTF-8 string
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_update(player_id: u32) -> i32 {
-    let mut players = VIDEO_PLAYERS.lock().unwrap();
-    let player = match players.get_mut(&player_id) {
-        Some(p) => p,
-        None => {
-            eprintln!("VideoUpdate: player {} not found", player_id);
-            return -2;
-        }
+pub unsafe extern "C" fn centered_layout_compute(tree_json: *const c_char, width: f32, height: f32) -> *mut c_char {
+    if tree_json.is_null() || !width.is_finite() || !height.is_finite() {
+        return ptr::null_mut();
+    }
+    let tree: crate::layout::LayoutTreeNode = match CStr::from_ptr(tree_json)
+        .to_str()
+        .ok()
+        .and_then(|json| serde_json::from_str(json).ok())
+    {
+        Some(tree) => tree,
+        None => return ptr::null_mut(),
     };
 
-    // Update playback timing
-    let has_new_frame = player.update();
+    let rects = crate::layout::compute_tree(&tree, crate::layout::LayoutSize::new(width, height));
+    let json = match serde_json::to_string(&rects) {
+        Ok(json) => json,
+        Err(_) => return ptr::null_mut(),
+    };
+    match CString::new(json) {
+        Ok(c_str) => c_str.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
 
-    // Get or create texture
-    if has_new_frame {
-        if let Some(frame) = player.take_frame() {
-            // Get backend for texture upload
-            let backend_lock = get_backend();
-            let mut backend_guard = backend_lock.lock().unwrap();
+/// C-compatible GPU limits
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct GpuLimitsFFI {
+    /// Largest width or height of a texture, in pixels
+    pub max_texture_dimension: u32,
+    pub max_bind_groups: u32,
+    /// 1 if the feature is available, 0 otherwise
+    pub timestamp_queries: u8,
+    pub bc_compression: u8,
+    pub etc2_compression: u8,
+    pub astc_compression: u8,
+}
 
-            if let Some(backend) = backend_guard.as_mut() {
-                // Get or create texture ID
-                let texture_id = match player.texture_id() {
-                    Some(id) => id,
-                    None => {
-                        // Create new video texture
-                        match backend.create_video_texture(frame.width, frame.height) {
-                            Ok(id) => {
-                                player.set_texture_id(id);
-                                id
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to create video texture: {}", e);
-                                return -4;
-                            }
-                        }
-                    }
-                };
+/// Get the GPU's limits and optional features.
+///
+/// Images larger than `max_texture_dimension` on either side fail to
+/// upload; callers should downscale them before `centered_backend_load_image`.
+///
+/// # Returns
+/// 0 on success, `EngineError::NotInitialized` if there is no backend, or
+/// `EngineError::InvalidArgument` if `out` is null
+///
+/// # Safety
+/// - out must be a valid pointer to a GpuLimitsFFI struct
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_get_limits(out: *mut GpuLimitsFFI) -> i32 {
+    if out.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
 
-                // Update texture with new frame
-                if let Err(e) = backend.update_video_texture(
-                    texture_id,
-                    frame.width,
-                    frame.height,
-                    &frame.data,
-                ) {
-                    eprintln!("Failed to update video texture: {}", e);
-                    return -5;
-                }
+    let backend_lock = get_backend();
+    let guard = backend_lock.lock().unwrap();
 
-                return texture_id as i32;
-            } else {
-                return -3; // Backend not initialized
-            }
+    match guard.as_ref().and_then(|backend| backend.gpu_limits()) {
+        Some(limits) => {
+            *out = GpuLimitsFFI {
+                max_texture_dimension: limits.max_texture_dimension_2d,
+                max_bind_groups: limits.max_bind_groups,
+                timestamp_queries: limits.timestamp_queries as u8,
+                bc_compression: limits.bc_compression as u8,
+                etc2_compression: limits.etc2_compression as u8,
+                astc_compression: limits.astc_compression as u8,
+            };
+            0
         }
+        None => EngineError::NotInitialized.code(),
     }
+}
 
-    // Return existing texture ID if we have one
-    if let Some(id) = player.texture_id() {
-        id as i32
-    } else {
-        0 // No texture yet
+/// Get draw-call count, triangle count and frame time of the last presented
+/// frame.
+///
+/// Frame time is measured with GPU timestamps where the device supports them,
+/// starting with the first call (so the first few reports are wall-clock time
+/// of submit and present, which is also the fallback). Any out-param may be
+/// null to skip it.
+///
+/// # Returns
+/// 0 on success, `EngineError::NotInitialized` if there is no backend
+///
+/// # Safety
+/// - Each non-null pointer must be valid for writes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_backend_get_stats(
+    draw_calls: *mut u32,
+    triangles: *mut u32,
+    frame_time_ms: *mut f32,
+    gpu_timed: *mut bool,
+) -> i32 {
+    let backend_lock = get_backend();
+    let guard = backend_lock.lock().unwrap();
+
+    let Some(backend) = guard.as_ref() else {
+        return EngineError::NotInitialized.code();
+    };
+    let stats = backend.last_frame_stats();
+    if !draw_calls.is_null() {
+        *draw_calls = stats.draw_calls;
+    }
+    if !triangles.is_null() {
+        *triangles = stats.triangles;
     }
+    if !frame_time_ms.is_null() {
+        *frame_time_ms = stats.frame_time_ms;
+    }
+    if !gpu_timed.is_null() {
+        *gpu_timed = stats.gpu_timed;
+    }
+    0
 }
 
-/// Get texture ID for current video frame (without updating)
+/// Get the color space the surface presents in
+///
+/// # Returns
+/// 0 for sRGB, 1 for extended-range linear sRGB (when `wide_gamut` was
+/// requested and the display supports it), or `EngineError::NotInitialized`
+/// if there is no backend
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_video_get_texture_id(player_id: u32) -> u32 {
-    let players = VIDEO_PLAYERS.lock().unwrap();
-    if let Some(player) = players.get(&player_id) {
-        player.texture_id().unwrap_or(0)
-    } else {
-        0
+pub extern "C" fn centered_backend_surface_color_space() -> i32 {
+    use crate::platform::SurfaceColorSpace;
+
+    let backend_lock = get_backend();
+    let guard = backend_lock.lock().unwrap();
+    match guard.as_ref().map(|backend| backend.surface_color_space()) {
+        Some(SurfaceColorSpace::Srgb) => 0,
+        Some(SurfaceColorSpace::ExtendedSrgb) => 1,
+        None => EngineError::NotInitialized.code(),
     }
 }
 
 // ============================================================================
-// Application Lifecycle FFI - Rust Owns Window
+// Video FFI
 // ============================================================================
 //
-// This API is for the "Rust owns window" model where:
-// - Rust creates and manages the window via winit
-// - Rust owns the event loop and rendering
-// - Go provides a callback that receives events and returns render commands
-//
-// Benefits:
-// - Smooth rendering during Go GC pauses
-// - Proper window resize handling
-// - Simpler FFI (no native handle passing)
-// - Consistent cross-platform behavior
+// Video playback API for loading and playing videos.
+// Uses platform-native decoders (AVFoundation on macOS) for hardware-accelerated decoding.
 
-use winit::{
-    application::ApplicationHandler,
-    event::{ElementState, WindowEvent},
-    event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
-    window::{Fullscreen, Window, WindowId},
-    dpi::LogicalSize,
-};
+use crate::video::player::VideoPlayer;
+use crate::video::VideoFrame;
 
-// Scancode extension is only available on desktop platforms
-#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-use winit::platform::scancode::PhysicalKeyExtScancode;
+// Global video player storage
+lazy_static::lazy_static! {
+    static ref VIDEO_PLAYERS: std::sync::Mutex<std::collections::HashMap<u32, VideoPlayer>> = std::sync::Mutex::new(std::collections::HashMap::new());
+    static ref NEXT_PLAYER_ID: std::sync::Mutex<u32> = std::sync::Mutex::new(1);
+}
+
+/// Create a new video player
+///
+/// # Returns
+/// A unique player ID (always positive), or 0 on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_create() -> u32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    let mut next_id = NEXT_PLAYER_ID.lock().unwrap();
+
+    let player_id = *next_id;
+    *next_id += 1;
+
+    players.insert(player_id, VideoPlayer::new());
+    player_id
+}
+
+/// Destroy a video player and free resources
+///
+/// # Arguments
+/// * `player_id` - Player ID from centered_video_create
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_destroy(player_id: u32) {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    players.remove(&player_id);
+}
+
+/// Load video from a URL (file:// or http://)
+///
+/// # Arguments
+/// * `player_id` - Player ID from centered_video_create
+/// * `url` - Null-terminated UTF-8 URL string
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_video_load_url(
+    player_id: u32,
+    url: *const c_char,
+) -> i32 {
+    if url.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let url_str = match CStr::from_ptr(url).to_str() {
+        Ok(s) => s,
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        match player.load_url(url_str) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Video load error: {}", e);
+                EngineError::OperationFailed.code()
+            }
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Load video from a file path
+///
+/// # Arguments
+/// * `player_id` - Player ID from centered_video_create
+/// * `path` - Null-terminated UTF-8 file path
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_video_load_file(
+    player_id: u32,
+    path: *const c_char,
+) -> i32 {
+    if path.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let path_str = match CStr::from_ptr(path).to_str() {
+        Ok(s) => s,
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        match player.load_file(path_str) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Video load error: {}", e);
+                EngineError::OperationFailed.code()
+            }
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Initialize frame buffer for raw frame input (video streams)
+///
+/// # Arguments
+/// * `player_id` - Player ID from centered_video_create
+/// * `width` - Initial frame width
+/// * `height` - Initial frame height
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_init_stream(
+    player_id: u32,
+    width: u32,
+    height: u32,
+) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.init_frame_buffer(width, height);
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Push a raw frame for video streams
+///
+/// # Arguments
+/// * `player_id` - Player ID from centered_video_create
+/// * `width` - Frame width in pixels
+/// * `height` - Frame height in pixels
+/// * `data` - RGBA pixel data (width * height * 4 bytes)
+/// * `data_len` - Length of data in bytes
+/// * `timestamp_ms` - Presentation timestamp in milliseconds
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_video_push_frame(
+    player_id: u32,
+    width: u32,
+    height: u32,
+    data: *const u8,
+    data_len: usize,
+    timestamp_ms: u64,
+) -> i32 {
+    if data.is_null() || data_len == 0 {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let expected_len = (width * height * 4) as usize;
+    if data_len < expected_len {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let frame_data = std::slice::from_raw_parts(data, data_len).to_vec();
+    let frame = VideoFrame::new(width, height, frame_data, timestamp_ms);
+
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.push_frame(frame);
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Start or resume video playback
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_play(player_id: u32) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.play();
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Pause video playback
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_pause(player_id: u32) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.pause();
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Seek to a specific position
+///
+/// # Arguments
+/// * `player_id` - Player ID
+/// * `timestamp_ms` - Target position in milliseconds
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_seek(player_id: u32, timestamp_ms: u64) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        match player.seek(timestamp_ms) {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Set looping behavior
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_set_looping(player_id: u32, looping: bool) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.set_looping(looping);
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Set muted state
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_set_muted(player_id: u32, muted: bool) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.set_muted(muted);
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Set volume (0.0 - 1.0)
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_set_volume(player_id: u32, volume: f32) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get_mut(&player_id) {
+        player.set_volume(volume);
+        0
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Get current playback state
+///
+/// # Returns
+/// PlaybackState as i32:
+/// - 0: Idle
+/// - 1: Loading
+/// - 2: Playing
+/// - 3: Paused
+/// - 4: Ended
+/// - 5: Error
+/// - 6: Buffering (stalled mid-playback waiting for data)
+/// - Negative: Player not found
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_get_state(player_id: u32) -> i32 {
+    let players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get(&player_id) {
+        player.state() as i32
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Get current playback position in milliseconds
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_get_time(player_id: u32) -> u64 {
+    let players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get(&player_id) {
+        player.current_time_ms()
+    } else {
+        0
+    }
+}
+
+/// Get the position in milliseconds up to which the video is downloaded
+///
+/// Counts on from the playback position, so a progress bar can draw it
+/// against the duration. Local files report their full duration.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_get_buffered_ms(player_id: u32) -> u64 {
+    let players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get(&player_id) {
+        player.buffered_ms()
+    } else {
+        0
+    }
+}
+
+/// Get video info
+///
+/// # Arguments
+/// * `player_id` - Player ID
+/// * `width_out` - Pointer to store video width
+/// * `height_out` - Pointer to store video height
+/// * `duration_ms_out` - Pointer to store duration in milliseconds
+///
+/// # Returns
+/// 0 on success, negative error code on failure
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_video_get_info(
+    player_id: u32,
+    width_out: *mut u32,
+    height_out: *mut u32,
+    duration_ms_out: *mut u64,
+) -> i32 {
+    if width_out.is_null() || height_out.is_null() || duration_ms_out.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get(&player_id) {
+        if let Some(info) = player.info() {
+            *width_out = info.width;
+            *height_out = info.height;
+            *duration_ms_out = info.duration_ms;
+            0
+        } else {
+            EngineError::NotReady.code()
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Update video player and get texture ID for current frame
+///
+/// This should be called once per frame. It advances the video playback
+/// and uploads new frames to the GPU as needed.
+///
+/// # Arguments
+/// * `player_id` - Player ID
+///
+/// # Returns
+/// Texture ID if a frame is available (use with DrawImage), 0 if no frame, negative on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_update(player_id: u32) -> i32 {
+    let mut players = VIDEO_PLAYERS.lock().unwrap();
+    let player = match players.get_mut(&player_id) {
+        Some(p) => p,
+        None => {
+            eprintln!("VideoUpdate: player {} not found", player_id);
+            return EngineError::NotFound.code();
+        }
+    };
+
+    // Update playback timing
+    let has_new_frame = player.update();
+
+    // Get or create texture
+    if has_new_frame {
+        if let Some(frame) = player.take_frame() {
+            // Get backend for texture upload
+            let backend_lock = get_backend();
+            let mut backend_guard = backend_lock.lock().unwrap();
+
+            if let Some(backend) = backend_guard.as_mut() {
+                // Get or create texture ID
+                let texture_id = match player.texture_id() {
+                    Some(id) => id,
+                    None => {
+                        // Create new video texture
+                        match backend.create_video_texture(frame.width, frame.height) {
+                            Ok(id) => {
+                                player.set_texture_id(id);
+                                id
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to create video texture: {}", e);
+                                return EngineError::GpuUploadFailed.code();
+                            }
+                        }
+                    }
+                };
+
+                // Update texture with new frame
+                if let Err(e) = backend.update_video_texture(
+                    texture_id,
+                    frame.width,
+                    frame.height,
+                    &frame.data,
+                ) {
+                    eprintln!("Failed to update video texture: {}", e);
+                    return EngineError::GpuUploadFailed.code();
+                }
+
+                return texture_id as i32;
+            } else {
+                return EngineError::NotInitialized.code();
+            }
+        }
+    }
+
+    // Return existing texture ID if we have one
+    if let Some(id) = player.texture_id() {
+        id as i32
+    } else {
+        0 // No texture yet
+    }
+}
+
+/// Get texture ID for current video frame (without updating)
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_get_texture_id(player_id: u32) -> u32 {
+    let players = VIDEO_PLAYERS.lock().unwrap();
+    if let Some(player) = players.get(&player_id) {
+        player.texture_id().unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+// ============================================================================
+// Application Lifecycle FFI - Rust Owns Window
+// ============================================================================
+//
+// This API is for the "Rust owns window" model where:
+// - Rust creates and manages the window via winit
+// - Rust owns the event loop and rendering
+// - Go provides a callback that receives events and returns render commands
+//
+// Benefits:
+// - Smooth rendering during Go GC pauses
+// - Proper window resize handling
+// - Simpler FFI (no native handle passing)
+// - Consistent cross-platform behavior
+
+#[cfg(feature = "winit")]
+use winit::{
+    application::ApplicationHandler,
+    event::{DeviceEvent, DeviceId, ElementState, Ime, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
+    window::{Fullscreen, Window, WindowId},
+    dpi::{LogicalPosition, LogicalSize},
+};
+
+// Scancode extension is only available on desktop platforms
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+use winit::platform::scancode::PhysicalKeyExtScancode;
 
 /// Custom user events for cross-thread communication
+#[cfg(feature = "winit")]
 #[derive(Debug, Clone)]
 enum UserEvent {
     /// Request a redraw from outside the event loop
@@ -1493,14 +3281,40 @@ enum UserEvent {
     Close,
     /// Set window title
     SetTitle(String),
+    /// Start a native drag session out of the window
+    BeginDrag(crate::platform::drag::DragPayload),
+    /// Replace the application menu bar
+    SetMenuBar(Vec<crate::platform::menu::Menu>),
+    /// A menu bar item was clicked (its id)
+    MenuItemClicked(u32),
+    /// Set (Some) or clear (None) the custom cursor image
+    SetCursorImage(Option<crate::platform::cursor::CursorImage>),
+    /// Set the standard cursor shape shown when no custom image is set
+    SetCursorIcon(winit::window::CursorIcon),
+    /// Show or hide the cursor over the window
+    SetCursorVisible(bool),
+    /// Turn pointer capture on or off (enabled, release on the last button-up)
+    SetPointerCapture(bool, bool),
+    /// Turn blur-behind (vibrancy) on or off
+    SetBlurBehind(bool),
+    /// Set the whole window's opacity (0.0 - 1.0)
+    SetOpacity(f32),
+    /// Lock (Some) or unlock (None) the window's aspect ratio
+    SetAspectRatio(Option<crate::platform::aspect::AspectRatio>),
+    /// Cap continuous redraws at this many frames per second (0 = uncapped)
+    SetTargetFps(u32),
+    /// Place the IME candidate window next to this logical rect (x, y, width, height)
+    SetImeCursorArea(f64, f64, f64, f64),
     /// System theme changed (Linux only) - true = dark mode
     #[cfg(target_os = "linux")]
     SystemThemeChanged(bool),
 }
 
 /// Global event loop proxy for requesting redraws from any thread
+#[cfg(feature = "winit")]
 static EVENT_LOOP_PROXY: std::sync::OnceLock<std::sync::Mutex<Option<EventLoopProxy<UserEvent>>>> = std::sync::OnceLock::new();
 
+#[cfg(feature = "winit")]
 fn get_event_loop_proxy() -> &'static std::sync::Mutex<Option<EventLoopProxy<UserEvent>>> {
     EVENT_LOOP_PROXY.get_or_init(|| std::sync::Mutex::new(None))
 }
@@ -1514,7 +3328,7 @@ pub struct AppConfig {
     pub width: u32,
     /// Initial window height in logical pixels
     pub height: u32,
-    /// Enable VSync
+    /// Enable VSync (change at runtime with `centered_backend_set_vsync`)
     pub vsync: bool,
     /// Prefer low power GPU (integrated over discrete)
     pub low_power_gpu: bool,
@@ -1523,6 +3337,7 @@ pub struct AppConfig {
     /// Target frames per second (default: 60)
     /// Use lower values (e.g., 30) for lighter apps to save battery
     /// Use higher values (e.g., 120) for games on high refresh rate displays
+    /// Change at runtime with `centered_app_set_target_fps`
     pub target_fps: u32,
     /// User data pointer passed to callbacks
     pub user_data: *mut std::ffi::c_void,
@@ -1562,6 +3377,34 @@ pub struct AppConfig {
     pub enable_maximize: bool,
     /// Dark mode for window controls: 0 = light, 1 = dark, 2 = auto/system
     pub dark_mode: u8,
+    /// Also deliver touches as left-button mouse press/move/release events,
+    /// for apps that only handle the mouse. Touch events are sent either way.
+    pub touch_mouse_emulation: bool,
+
+    // Text rendering options (see `SurfaceConfig`); both off draws grayscale
+    // antialiased text blended in the surface's color space
+    /// Blend text in linear light on surfaces without an sRGB format
+    pub gamma_correct_text: bool,
+    /// LCD subpixel antialiasing where the GPU and an opaque surface allow it
+    pub subpixel_text: bool,
+
+    // Color space options (see `SurfaceConfig`)
+    /// Present in extended-range sRGB where the display supports it
+    pub wide_gamut: bool,
+    /// Take colors and images as Display-P3 rather than sRGB
+    pub display_p3_colors: bool,
+
+    // Window effects (see `platform::window_styling`)
+    /// Blur what's behind the window's transparent areas; implies `transparent`
+    pub blur_behind: bool,
+    /// Opacity of the whole window. 0 (the zero value) and 1 both mean opaque.
+    pub window_opacity: f32,
+
+    // Frame pacing (see `platform::frame_pacing`)
+    /// Rate of FixedUpdate events per second of wall-clock time, sent before
+    /// each RedrawRequested for the steps that fell due. 0 (the zero value)
+    /// turns fixed-timestep mode off.
+    pub fixed_timestep_hz: u32,
 }
 
 /// Event type for FFI
@@ -1582,9 +3425,11 @@ pub enum AppEventType {
     MousePressed = 5,
     /// Mouse button released (data: button index)
     MouseReleased = 6,
-    /// Key pressed (data: keycode)
+    /// Key pressed (data1: physical keycode; data2: modifier flags, plus
+    /// KEY_REPEAT for auto-repeat; `text`: the logical key)
     KeyPressed = 7,
-    /// Key released (data: keycode)
+    /// Key released (data1: physical keycode; data2: modifier flags; `text`:
+    /// the logical key)
     KeyReleased = 8,
     /// Character input (data: UTF-32 codepoint)
     CharInput = 9,
@@ -1596,10 +3441,89 @@ pub enum AppEventType {
     Resumed = 12,
     /// Keyboard frame changed (data1: height in logical points, 0 if hidden; data2: animation duration in seconds)
     KeyboardFrameChanged = 13,
+    /// Touch point began, moved, ended or was cancelled (data: x, y in
+    /// logical pixels; `touch_id` and `touch_phase` say which and how)
+    Touch = 14,
+    /// IME composition changed (`text`: the composition string, empty when
+    /// composition ends; data1, data2: selected byte range within it, -1 if
+    /// the cursor is hidden)
+    ImePreedit = 15,
+    /// IME committed final text (`text`); CharInput events for the same
+    /// text follow
+    ImeCommit = 16,
+    /// One fixed timestep of `frame_dt` seconds, in fixed-timestep mode
+    /// (`AppConfig::fixed_timestep_hz`). Sent once per step that fell due,
+    /// just before the RedrawRequested of the same frame; its response is
+    /// ignored, so draw in RedrawRequested.
+    FixedUpdate = 17,
+    /// An item of the menu bar set with `centered_app_set_menu` was clicked,
+    /// or its shortcut pressed (data1: the item's id)
+    MenuItemClicked = 18,
+    /// Files were dragged into the window (data: cursor x, y in logical
+    /// pixels; `text`: JSON array of the file paths)
+    FileDragEnter = 19,
+    /// Files dragged over the window moved (data: cursor x, y)
+    FileDragOver = 20,
+    /// Files were dropped on the window (data: cursor x, y; `text`: JSON
+    /// array of the file paths)
+    FileDrop = 21,
+    /// Files dragged over the window left it or the drag was cancelled
+    FileDragCancel = 22,
+    /// The window gained keyboard focus. On iOS/Android, sent with Resumed.
+    FocusGained = 23,
+    /// The window lost keyboard focus, e.g. to dim it while inactive. On
+    /// iOS/Android, sent with Suspended.
+    FocusLost = 24,
+    /// The window was minimized, so nothing of it is visible; pause
+    /// animations until Restored. On iOS/Android, sent with Suspended.
+    Minimized = 25,
+    /// The window was restored after Minimized. On iOS/Android, sent with
+    /// Resumed.
+    Restored = 26,
+    /// The window became hidden without being minimized, e.g. covered by
+    /// other windows (data1: 1 hidden, 0 visible again). Not every
+    /// platform reports it.
+    Occluded = 27,
+}
+
+/// Where a MouseWheel event falls in a trackpad scroll gesture
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollPhase {
+    /// Not part of a gesture: a mouse wheel notch, a platform that doesn't
+    /// report phases, or not a MouseWheel event
+    None = 0,
+    /// Fingers touched down and started scrolling
+    Started = 1,
+    /// Fingers moving
+    Moved = 2,
+    /// Fingers lifted; Momentum events may follow
+    Ended = 3,
+    /// Inertial scrolling after the fingers lifted (a flick's tail)
+    Momentum = 4,
+    /// Inertial scrolling came to rest or was stopped by a new touch
+    MomentumEnded = 5,
+}
+
+/// What happened to the touch point of a Touch event
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    /// Not a Touch event
+    None = 0,
+    /// A finger touched down
+    Began = 1,
+    /// A finger moved
+    Moved = 2,
+    /// A finger lifted
+    Ended = 3,
+    /// The system took the touch away (an incoming call, a system gesture)
+    Cancelled = 4,
 }
 
 /// Event data passed to callback
 #[repr(C)]
+#[derive(Debug, Clone, Copy)]
 pub struct AppEvent {
     pub event_type: AppEventType,
     /// Width for resize, x for mouse, keycode for key, etc.
@@ -1608,6 +3532,379 @@ pub struct AppEvent {
     pub data2: f64,
     /// Scale factor (for resize events)
     pub scale_factor: f64,
+    /// Seconds since the previous frame started on a RedrawRequested event
+    /// (0 for the first frame), the step length on a FixedUpdate, 0 for
+    /// other events
+    pub frame_dt: f64,
+    /// Consecutive failed renders/presents before this event (0 = last
+    /// present succeeded). Filled in when the event is dispatched so Go can
+    /// back off instead of requesting redraws in a tight loop.
+    pub render_failures: u32,
+    /// Gesture phase of a MouseWheel event, so Go can tell a flick's
+    /// momentum from fingers still on the trackpad
+    pub scroll_phase: ScrollPhase,
+    /// What happened to the touch point of a Touch event
+    pub touch_phase: TouchPhase,
+    /// Id of a Touch event's touch point, the same from Began through
+    /// Ended/Cancelled, so each finger of a pinch can be followed
+    pub touch_id: u64,
+    /// Null-terminated UTF-8 text of an ImePreedit/ImeCommit event, the
+    /// logical key of a KeyPressed/KeyReleased event, or the paths of a
+    /// FileDragEnter/FileDrop event; only valid during the callback, null
+    /// for other events
+    pub text: *const c_char,
+}
+
+/// Consecutive render/present failures, reported on the next AppEvent
+static RENDER_FAILURES: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// Record the outcome of a render so the next callback can report it
+fn record_render_result<T, E>(result: &Result<T, E>) {
+    use std::sync::atomic::Ordering;
+    if result.is_ok() {
+        RENDER_FAILURES.store(0, Ordering::Relaxed);
+    } else {
+        let _ = RENDER_FAILURES.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| Some(n.saturating_add(1)));
+    }
+}
+
+/// Invoke the Go callback with the current render status stamped on the event
+///
+/// # Safety
+/// `callback` must be a valid AppCallback and `user_data` whatever it expects
+#[cfg(feature = "winit")]
+unsafe fn dispatch_event(
+    callback: AppCallback,
+    event: &AppEvent,
+    response: &mut FrameResponse,
+    user_data: *mut std::ffi::c_void,
+) {
+    let event = AppEvent {
+        render_failures: RENDER_FAILURES.load(std::sync::atomic::Ordering::Relaxed),
+        ..*event
+    };
+    match CALLBACK_WATCHDOG.get() {
+        Some(watchdog) => watchdog.watch(|| callback(&event, response, user_data)),
+        None => callback(&event, response, user_data),
+    }
+}
+
+/// Scroll phase of a winit wheel event.
+///
+/// Line deltas come from mouse wheels and have no gesture. `momentum` says
+/// the event is inertial scrolling, which winit reports with the same
+/// Started/Moved/Ended phases as the fingers' own scrolling.
+#[cfg(feature = "winit")]
+fn wheel_scroll_phase(phase: winit::event::TouchPhase, is_line_delta: bool, momentum: bool) -> ScrollPhase {
+    use winit::event::TouchPhase;
+    if is_line_delta {
+        return ScrollPhase::None;
+    }
+    match (phase, momentum) {
+        (TouchPhase::Ended | TouchPhase::Cancelled, true) => ScrollPhase::MomentumEnded,
+        (_, true) => ScrollPhase::Momentum,
+        (TouchPhase::Started, false) => ScrollPhase::Started,
+        (TouchPhase::Moved, false) => ScrollPhase::Moved,
+        (TouchPhase::Ended | TouchPhase::Cancelled, false) => ScrollPhase::Ended,
+    }
+}
+
+/// Phase of a winit touch as reported to Go
+#[cfg(feature = "winit")]
+fn touch_phase(phase: winit::event::TouchPhase) -> TouchPhase {
+    match phase {
+        winit::event::TouchPhase::Started => TouchPhase::Began,
+        winit::event::TouchPhase::Moved => TouchPhase::Moved,
+        winit::event::TouchPhase::Ended => TouchPhase::Ended,
+        winit::event::TouchPhase::Cancelled => TouchPhase::Cancelled,
+    }
+}
+
+/// A Touch event for touch point `id` at logical (x, y)
+#[cfg(feature = "winit")]
+fn touch_event(id: u64, phase: TouchPhase, x: f64, y: f64, scale_factor: f64) -> AppEvent {
+    AppEvent {
+        event_type: AppEventType::Touch,
+        data1: x,
+        data2: y,
+        scale_factor,
+        frame_dt: 0.0,
+        render_failures: 0,
+        scroll_phase: ScrollPhase::None,
+        touch_phase: phase,
+        touch_id: id,
+        text: ptr::null(),
+    }
+}
+
+/// An ImePreedit or ImeCommit event carrying `text`, which must outlive the
+/// callback, and the preedit cursor byte range
+#[cfg(feature = "winit")]
+fn ime_event(event_type: AppEventType, text: &CStr, cursor: Option<(usize, usize)>) -> AppEvent {
+    let (start, end) = cursor.map_or((-1.0, -1.0), |(s, e)| (s as f64, e as f64));
+    AppEvent {
+        event_type,
+        data1: start,
+        data2: end,
+        scale_factor: 1.0,
+        frame_dt: 0.0,
+        render_failures: 0,
+        scroll_phase: ScrollPhase::None,
+        touch_phase: TouchPhase::None,
+        touch_id: 0,
+        text: text.as_ptr(),
+    }
+}
+
+/// The event reporting a window focus, minimize or occlusion change
+#[cfg(feature = "winit")]
+fn window_state_event(change: crate::platform::window_state::WindowStateChange) -> AppEvent {
+    use crate::platform::window_state::WindowStateChange;
+    let (event_type, data1) = match change {
+        WindowStateChange::FocusGained => (AppEventType::FocusGained, 0.0),
+        WindowStateChange::FocusLost => (AppEventType::FocusLost, 0.0),
+        WindowStateChange::Minimized => (AppEventType::Minimized, 0.0),
+        WindowStateChange::Restored => (AppEventType::Restored, 0.0),
+        WindowStateChange::Occluded(hidden) => (AppEventType::Occluded, if hidden { 1.0 } else { 0.0 }),
+    };
+    AppEvent {
+        event_type,
+        data1,
+        data2: 0.0,
+        scale_factor: 1.0,
+        frame_dt: 0.0,
+        render_failures: 0,
+        scroll_phase: ScrollPhase::None,
+        touch_phase: TouchPhase::None,
+        touch_id: 0,
+        text: ptr::null(),
+    }
+}
+
+/// A FixedUpdate event for one step of `step` seconds
+#[cfg(feature = "winit")]
+fn fixed_update_event(step: f64) -> AppEvent {
+    AppEvent {
+        event_type: AppEventType::FixedUpdate,
+        data1: 0.0,
+        data2: 0.0,
+        scale_factor: 1.0,
+        frame_dt: step,
+        render_failures: 0,
+        scroll_phase: ScrollPhase::None,
+        touch_phase: TouchPhase::None,
+        touch_id: 0,
+        text: ptr::null(),
+    }
+}
+
+/// Start a mobile frame on `pacer`, sending Go a FixedUpdate for each fixed
+/// step that fell due, and return the seconds since the last frame
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "ios", target_os = "android"))]
+unsafe fn begin_mobile_frame(
+    callback: AppCallback,
+    user_data: *mut std::ffi::c_void,
+    pacer: &mut crate::platform::frame_pacing::FramePacer,
+) -> f64 {
+    let timing = pacer.begin_frame(std::time::Instant::now());
+    if let Some(step) = pacer.step_seconds() {
+        let event = fixed_update_event(step);
+        for _ in 0..timing.fixed_steps {
+            let mut step_response = FrameResponse {
+                immediate_commands: std::ptr::null_mut(),
+                widget_delta: std::ptr::null_mut(),
+                request_redraw: false,
+                redraw_after_ms: 0,
+                dark_mode: 2,
+                layers: std::ptr::null_mut(),
+            };
+            dispatch_event(callback, &event, &mut step_response, user_data);
+        }
+    }
+    timing.dt
+}
+
+/// The platform's response to a mobile frame: the redraw Go asked for,
+/// plus the ones the engine's own animations in the last rendered frame
+/// need (spinners every frame, the caret when it next blinks)
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "ios", target_os = "android"))]
+fn mobile_frame_response(frame_response: &FrameResponse) -> crate::platform::backend::EventResponse {
+    let (engine_animating, engine_redraw_ms) = match get_backend().lock() {
+        Ok(guard) => guard
+            .as_ref()
+            .map_or((false, None), |backend| (backend.needs_continuous_redraw(), backend.redraw_after_ms())),
+        Err(_) => (false, None),
+    };
+    let redraw_after_ms = match (frame_response.redraw_after_ms, engine_redraw_ms) {
+        (0, engine_ms) => engine_ms.unwrap_or(0),
+        (go_ms, Some(engine_ms)) => go_ms.min(engine_ms),
+        (go_ms, None) => go_ms,
+    };
+    crate::platform::backend::EventResponse {
+        request_redraw: frame_response.request_redraw || engine_animating,
+        exit: false, // FrameResponse doesn't have exit
+        redraw_after_ms,
+    }
+}
+
+/// Send Go the window state changes of a mobile app suspending or resuming,
+/// ahead of the Suspended or Resumed event itself
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "ios", target_os = "android"))]
+unsafe fn dispatch_mobile_window_state(
+    callback: AppCallback,
+    user_data: *mut std::ffi::c_void,
+    changes: Vec<crate::platform::window_state::WindowStateChange>,
+) {
+    for change in changes {
+        let mut state_response = FrameResponse {
+            immediate_commands: std::ptr::null_mut(),
+            widget_delta: std::ptr::null_mut(),
+            request_redraw: false,
+            redraw_after_ms: 0,
+            dark_mode: 2,
+            layers: std::ptr::null_mut(),
+            dirty_region: std::ptr::null_mut(),
+        };
+        dispatch_event(callback, &window_state_event(change), &mut state_response, user_data);
+    }
+}
+
+/// Send a mobile touch to Go and return the event the frame answers: the
+/// Touch event itself, or with mouse emulation on, the emulated `mouse`
+/// event at the same point, sent after the Touch event
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "ios", target_os = "android"))]
+unsafe fn touch_or_emulated_mouse(
+    callback: AppCallback,
+    user_data: *mut std::ffi::c_void,
+    emulate: bool,
+    touch: AppEvent,
+    mouse: AppEventType,
+) -> AppEvent {
+    if !emulate {
+        return touch;
+    }
+    let mut touch_response = FrameResponse {
+        immediate_commands: std::ptr::null_mut(),
+        widget_delta: std::ptr::null_mut(),
+        request_redraw: false,
+        redraw_after_ms: 0,
+        dark_mode: 2,
+        layers: std::ptr::null_mut(),
+    };
+    dispatch_event(callback, &touch, &mut touch_response, user_data);
+    AppEvent { event_type: mouse, touch_phase: TouchPhase::None, touch_id: 0, text: ptr::null(), ..touch }
+}
+
+/// Send a mobile IME commit to Go: the ImeCommit event, then the same text
+/// as CharInput events
+#[cfg(feature = "winit")]
+#[cfg(any(target_os = "ios", target_os = "android"))]
+unsafe fn dispatch_ime_commit(callback: AppCallback, user_data: *mut std::ffi::c_void, text: &str) {
+    let Ok(c_text) = CString::new(text) else {
+        return;
+    };
+    let commit = ime_event(AppEventType::ImeCommit, &c_text, None);
+    let chars = text.chars().map(|c| AppEvent {
+        event_type: AppEventType::CharInput,
+        data1: c as u32 as f64,
+        data2: 0.0,
+        scale_factor: 1.0,
+        frame_dt: 0.0,
+        render_failures: 0,
+        scroll_phase: ScrollPhase::None,
+        touch_phase: TouchPhase::None,
+        touch_id: 0,
+        text: ptr::null(),
+    });
+    for event in std::iter::once(commit).chain(chars) {
+        let mut response = FrameResponse {
+            immediate_commands: std::ptr::null_mut(),
+            widget_delta: std::ptr::null_mut(),
+            request_redraw: false,
+            redraw_after_ms: 0,
+            dark_mode: 2,
+            layers: std::ptr::null_mut(),
+        };
+        dispatch_event(callback, &event, &mut response, user_data);
+    }
+}
+
+/// Whether the wheel event AppKit is dispatching is inertial scrolling,
+/// read from the NSEvent itself since winit folds it into the touch phase
+#[cfg(all(feature = "winit", target_os = "macos"))]
+fn current_scroll_is_momentum() -> bool {
+    use cocoa::base::{id, nil};
+    // NSEventTypeScrollWheel
+    const SCROLL_WHEEL: u64 = 22;
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let event: id = msg_send![app, currentEvent];
+        if event == nil {
+            return false;
+        }
+        let event_type: u64 = msg_send![event, type];
+        // NSEventPhaseNone is 0
+        let momentum_phase: u64 = msg_send![event, momentumPhase];
+        event_type == SCROLL_WHEEL && momentum_phase != 0
+    }
+}
+
+/// Watchdog timing the Go callback, created by centered_set_callback_watchdog
+#[cfg(feature = "winit")]
+static CALLBACK_WATCHDOG: std::sync::OnceLock<crate::watchdog::Watchdog> = std::sync::OnceLock::new();
+
+/// Whether a stalled callback re-presents the last frame
+#[cfg(feature = "winit")]
+static WATCHDOG_PRESENTS_LAST_FRAME: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Warn when the Go callback runs longer than a threshold
+///
+/// If the callback deadlocks or runs long (GC pause, blocking call) the UI
+/// freezes. With the watchdog on, a callback running past `threshold_ms`
+/// logs one warning through the log callback (or stderr). The callback is
+/// never interrupted.
+///
+/// # Arguments
+/// * `threshold_ms` - Threshold in milliseconds; 0 turns the watchdog off
+/// * `present_last_frame` - While the callback stays stalled, re-present the
+///   last frame every threshold interval so the window keeps its content
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_set_callback_watchdog(threshold_ms: u32, present_last_frame: bool) {
+    let threshold = (threshold_ms > 0).then(|| std::time::Duration::from_millis(threshold_ms as u64));
+    WATCHDOG_PRESENTS_LAST_FRAME.store(present_last_frame, std::sync::atomic::Ordering::Relaxed);
+    if threshold.is_none() && CALLBACK_WATCHDOG.get().is_none() {
+        return;
+    }
+    CALLBACK_WATCHDOG
+        .get_or_init(|| crate::watchdog::Watchdog::spawn(None, report_callback_stall))
+        .set_threshold(threshold);
+}
+
+/// Runs on the watchdog thread while the Go callback is stalled
+#[cfg(feature = "winit")]
+fn report_callback_stall(stall: crate::watchdog::Stall) {
+    if stall.first {
+        crate::logging::log_warning(format_args!(
+            "[Watchdog] App callback has not returned after {} ms; the UI is frozen until it does",
+            stall.elapsed.as_millis(),
+        ));
+    }
+    if WATCHDOG_PRESENTS_LAST_FRAME.load(std::sync::atomic::Ordering::Relaxed) {
+        // The app loop may hold the backend; skip this round rather than wait
+        if let Ok(mut guard) = get_backend().try_lock() {
+            if let Some(backend) = guard.as_mut() {
+                if let Err(e) = backend.present_last_frame() {
+                    eprintln!("[Watchdog] Failed to present last frame: {}", e);
+                }
+            }
+        }
+    }
 }
 
 /// Frame response from Go callback
@@ -1672,6 +3969,117 @@ pub struct LayerInfo {
     pub commands: Vec<RenderCommand>,
 }
 
+impl LayerInfo {
+    /// The layer's bounds
+    pub fn rect(&self) -> crate::geometry::Rect {
+        crate::geometry::Rect::new(self.x, self.y, self.width, self.height)
+    }
+}
+
+/// Layers under a point, topmost first, in the order input should try them.
+///
+/// Layers are stacked as the compositor draws them: by `z_order`, with later
+/// layers on top among equal z-orders. A transparent layer lets the point
+/// pass through to the layers below; the first opaque layer blocks the rest
+/// and ends the list.
+pub fn layers_at_point(layers: &[LayerInfo], x: f32, y: f32) -> Vec<u32> {
+    let mut stacked: Vec<&LayerInfo> = layers.iter().collect();
+    stacked.sort_by_key(|l| l.z_order);
+
+    let mut hits = Vec::new();
+    for layer in stacked.into_iter().rev().filter(|l| l.rect().contains(crate::geometry::Point2::new(x, y))) {
+        hits.push(layer.id);
+        if layer.opaque {
+            break;
+        }
+    }
+    hits
+}
+
+/// Find the topmost layer at a point
+///
+/// `layers_json` is the same layer list the frame response carries (commands
+/// may be omitted). Layers stack by `z_order` as the compositor draws them.
+/// Use `centered_layers_hit_test_stack` to also get the layers a transparent
+/// top layer lets the point through to.
+///
+/// # Arguments
+/// * `out_id` - Receives the layer id (any u32, so it isn't mixed with the
+///   status)
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `NotFound`: no layer contains the point
+/// - `InvalidArgument`: null `out_id` or invalid JSON
+///
+/// # Safety
+/// - layers_json must be a valid null-terminated UTF-8 string
+/// - out_id must be valid for writes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_layers_hit_test(layers_json: *const c_char, x: f32, y: f32, out_id: *mut u32) -> i32 {
+    if out_id.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    let Some(layers) = parse_layers_json(layers_json) else {
+        return EngineError::InvalidArgument.code();
+    };
+    match layers_at_point(&layers, x, y).first() {
+        Some(&id) => {
+            *out_id = id;
+            0
+        }
+        None => EngineError::NotFound.code(),
+    }
+}
+
+/// Find every layer a point reaches, topmost first
+///
+/// Transparent layers pass the point through; the list ends at the first
+/// opaque layer. Writes up to `capacity` ids to `out_ids`.
+///
+/// # Returns
+/// The number of layers hit (which may exceed `capacity`), or
+/// `EngineError::InvalidArgument` if the JSON is invalid
+///
+/// # Safety
+/// - layers_json must be a valid null-terminated UTF-8 string
+/// - out_ids must point to at least `capacity` u32s, or be null with capacity 0
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_layers_hit_test_stack(
+    layers_json: *const c_char,
+    x: f32,
+    y: f32,
+    out_ids: *mut u32,
+    capacity: usize,
+) -> i32 {
+    let Some(layers) = parse_layers_json(layers_json) else {
+        return EngineError::InvalidArgument.code();
+    };
+    let hits = layers_at_point(&layers, x, y);
+    if !out_ids.is_null() {
+        for (i, &id) in hits.iter().take(capacity).enumerate() {
+            *out_ids.add(i) = id;
+        }
+    }
+    hits.len() as i32
+}
+
+unsafe fn parse_layers_json(layers_json: *const c_char) -> Option<Vec<LayerInfo>> {
+    if layers_json.is_null() {
+        return None;
+    }
+    let json = CStr::from_ptr(layers_json).to_str().ok()?;
+    match serde_json::from_str(json) {
+        Ok(layers) => Some(layers),
+        Err(e) => {
+            eprintln!("Failed to parse layers: {}", e);
+            None
+        }
+    }
+}
+
 /// Callback function type for the application loop
 ///
 /// Called by Rust for each event. Go should:
@@ -1716,6 +4124,7 @@ pub type AppCallback = unsafe extern "C" fn(
 );
 
 /// Application state for the event loop
+#[cfg(feature = "winit")]
 struct App {
     window: Option<Window>,
     backend: Option<WgpuBackend>,
@@ -1727,6 +4136,29 @@ struct App {
     modifiers: winit::keyboard::ModifiersState,
     // Scheduled redraw time (for cursor blink, etc.)
     next_redraw_at: Option<std::time::Instant>,
+    // When the engine-managed caret next toggles; that frame must render
+    // even if the widget tree hasn't changed
+    engine_redraw_at: Option<std::time::Instant>,
+    // Custom cursor bitmap (logical size) and the cursor built for the current scale
+    cursor_image: Option<crate::platform::cursor::CursorImage>,
+    custom_cursor: Option<winit::window::CustomCursor>,
+    // Standard cursor shown when neither a custom image nor a resize edge applies
+    cursor_icon: winit::window::CursorIcon,
+    // Locked content aspect ratio, enforced after each resize
+    aspect_ratio: Option<crate::platform::aspect::AspectRatio>,
+    // Frame rate cap for continuous redraws set at runtime (None = paced by vsync)
+    frame_cap: Option<u32>,
+    // Frame clock: when the last frame started (for pacing to frame_cap),
+    // RedrawRequested's frame_dt, and the fixed-timestep accumulator
+    frame_pacer: crate::platform::frame_pacing::FramePacer,
+    // Keeps mouse moves and releases coming while a drag is outside the window
+    pointer_capture: crate::platform::pointer_capture::PointerCapture,
+    // Files dragged in from the OS, gathered from winit's per-file events
+    file_drop: crate::platform::file_drop::FileDrop,
+    // Focus, minimize and occlusion as last reported to Go
+    window_state: crate::platform::window_state::WindowState,
+    // Retained mode widget tree; its dirty flags decide whether a frame renders
+    widget_tree: WidgetTree,
     // Linux-specific: window controls and resize handling
     #[cfg(target_os = "linux")]
     mouse_position: (f64, f64),
@@ -1749,13 +4181,36 @@ struct App {
 
 // Modifier flags for keyboard events (passed in data2)
 // These match common conventions and can be combined with bitwise OR
+#[cfg(feature = "winit")]
 const MOD_SHIFT: u32 = 1;
+#[cfg(feature = "winit")]
 const MOD_CTRL: u32 = 2;
+#[cfg(feature = "winit")]
 const MOD_ALT: u32 = 4;
+#[cfg(feature = "winit")]
 const MOD_SUPER: u32 = 8; // Cmd on macOS, Win on Windows
+/// Set in data2 of a KeyPressed that is an auto-repeat of a held key
+#[cfg(feature = "winit")]
+const KEY_REPEAT: u32 = 16;
+
+/// The logical key of a keyboard event as text: the character the active
+/// layout produces (so a physical KeyZ is "y" on a German layout), or the
+/// W3C name of a named key such as "Enter" or "ArrowLeft"
+#[cfg(feature = "winit")]
+fn logical_key_text(key: &winit::keyboard::Key) -> Option<CString> {
+    use winit::keyboard::Key;
+    let text = match key {
+        Key::Character(c) => c.to_string(),
+        Key::Named(named) => format!("{:?}", named),
+        Key::Dead(Some(c)) => c.to_string(),
+        Key::Dead(None) | Key::Unidentified(_) => return None,
+    };
+    CString::new(text).ok()
+}
 
 /// Convert winit KeyCode to a stable integer value for FFI
 /// These values are stable and cross-platform, matching the Go constants
+#[cfg(feature = "winit")]
 fn keycode_to_u32(key: winit::keyboard::KeyCode) -> u32 {
     use winit::keyboard::KeyCode::*;
     match key {
@@ -1812,6 +4267,7 @@ fn keycode_to_u32(key: winit::keyboard::KeyCode) -> u32 {
 
 /// Helper to get window size - uses outer_size on iOS for full screen rendering,
 /// inner_size on other platforms for safe area rendering.
+#[cfg(feature = "winit")]
 #[inline]
 fn get_window_size(window: &winit::window::Window) -> winit::dpi::PhysicalSize<u32> {
     #[cfg(target_os = "ios")]
@@ -1831,6 +4287,7 @@ fn get_window_size(window: &winit::window::Window) -> winit::dpi::PhysicalSize<u
 /// manual frame adjustments), the safe area insets reported by the system are in the
 /// interface orientation, not the device orientation. We need to rotate them to match
 /// the actual device orientation.
+#[cfg(feature = "winit")]
 #[inline]
 fn update_safe_area_from_window(window: &winit::window::Window) {
     #[cfg(target_os = "ios")]
@@ -1914,22 +4371,22 @@ fn update_safe_area_from_window(window: &winit::window::Window) {
             let (top, left, bottom, right) = if device_is_landscape && interface_is_portrait {
                 // Device is landscape but interface is portrait - rotate insets 90°
                 if device_orientation == UI_DEVICE_ORIENTATION_LANDSCAPE_LEFT {
-                    println!("Safe area: Rotating insets for landscape left (from portrait)");
+                    verbose!("Safe area: Rotating insets for landscape left (from portrait)");
                     (raw_left, raw_bottom, raw_right, raw_top)
                 } else {
-                    println!("Safe area: Rotating insets for landscape right (from portrait)");
+                    verbose!("Safe area: Rotating insets for landscape right (from portrait)");
                     (raw_right, raw_top, raw_left, raw_bottom)
                 }
             } else if device_is_portrait && !interface_is_portrait {
                 // Device is portrait but interface is landscape - rotate insets back
-                println!("Safe area: Rotating insets for portrait (from landscape)");
+                verbose!("Safe area: Rotating insets for portrait (from landscape)");
                 (raw_left, raw_bottom, raw_right, raw_top)
             } else {
                 // No transformation needed (orientations match or unknown orientation)
                 (raw_top, raw_left, raw_bottom, raw_right)
             };
 
-            println!("Safe area insets (logical): top={}, left={}, bottom={}, right={} (device_orientation={}, interface_portrait={})",
+            verbose!("Safe area insets (logical): top={}, left={}, bottom={}, right={} (device_orientation={}, interface_portrait={})",
                      top, left, bottom, right, device_orientation, interface_is_portrait);
             update_safe_area_insets(top, left, bottom, right);
         }
@@ -1942,6 +4399,7 @@ fn update_safe_area_from_window(window: &winit::window::Window) {
     }
 }
 
+#[cfg(feature = "winit")]
 impl ApplicationHandler<UserEvent> for App {
     fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
         // Check if we woke up due to a scheduled redraw
@@ -1967,226 +4425,30 @@ impl ApplicationHandler<UserEvent> for App {
             tray_icon::process_events();
         }
 
-        // Reset to Wait by default, will be updated by event handlers
-        event_loop.set_control_flow(ControlFlow::Wait);
+        // Reset to Wait by default (keeping any scheduled redraw), will be
+        // updated by event handlers
+        event_loop.set_control_flow(match self.next_redraw_at {
+            Some(wake_time) => ControlFlow::WaitUntil(wake_time),
+            None => ControlFlow::Wait,
+        });
+
+        // Linux: a drag's events arrive on GTK's X connection, which doesn't
+        // wake winit, so poll GTK at frame rate until the drag ends
+        #[cfg(target_os = "linux")]
+        if crate::platform::drag::in_progress() {
+            let poll_at = std::time::Instant::now() + std::time::Duration::from_millis(16);
+            let wake_time = self.next_redraw_at.map_or(poll_at, |at| at.min(poll_at));
+            event_loop.set_control_flow(ControlFlow::WaitUntil(wake_time));
+        }
     }
 
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         match event {
             UserEvent::RequestRedraw => {
                 // Directly trigger a redraw with current state
                 // This is more reliable than window.request_redraw() which queues
                 // an event that might be processed with stale state
-                let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
-                let size = self.window.as_ref().map(|w| get_window_size(w)).unwrap_or_default();
-
-                let logical_width = size.width as f64 / scale_factor;
-                let logical_height = size.height as f64 / scale_factor;
-                let app_event = AppEvent {
-                    event_type: AppEventType::RedrawRequested,
-                    data1: logical_width,
-                    data2: logical_height,
-                    scale_factor,
-                };
-
-                // Call Go callback and render
-                let response = self.call_callback(&app_event);
-
-                // Linux: update window controls theme if dark mode changed
-                #[cfg(target_os = "linux")]
-                self.update_dark_mode(response.dark_mode);
-
-                // Render frame
-                {
-                    let backend_lock = get_backend();
-                    let mut guard = backend_lock.lock().unwrap();
-                    if let Some(ref mut backend) = *guard {
-                        let mut all_commands = Vec::new();
-
-                        // Check for layer-based rendering first
-                        if let Some(ref json) = response.layers {
-                            match serde_json::from_str::<Vec<LayerInfo>>(json) {
-                                Ok(layers) => {
-                                    // Sort layers by z_order (lower = further back)
-                                    let mut sorted_layers = layers;
-                                    sorted_layers.sort_by_key(|l| l.z_order);
-
-                                    // Collect commands from all layers in z-order
-                                    for layer in &sorted_layers {
-                                        all_commands.extend(layer.commands.clone());
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse layers: {}", e);
-                                }
-                            }
-                        } else if let Some(ref json) = response.immediate_commands {
-                            // Fall back to immediate commands if no layers
-                            match serde_json::from_str::<Vec<RenderCommand>>(json) {
-                                Ok(commands) => {
-                                    all_commands.extend(commands);
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse immediate commands: {}", e);
-                                }
-                            }
-                        }
-
-                        // Linux frameless window: add rounded corner clipping, window controls, and border
-                        #[cfg(target_os = "linux")]
-                        {
-                            if !all_commands.is_empty() && !self.config.decorations {
-                                let window_radius = crate::platform::linux::WINDOW_CORNER_RADIUS;
-
-                                // Extract the background color from Clear command and replace with transparent
-                                // This is needed because the render pass clear happens BEFORE stencil clipping,
-                                // so we need to draw the background as a rect INSIDE the stencil clip instead.
-                                let mut bg_color: Option<crate::style::Color> = None;
-                                for cmd in all_commands.iter_mut() {
-                                    if let RenderCommand::Clear(color) = cmd {
-                                        bg_color = Some(*color);
-                                        // Replace with transparent clear
-                                        *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
-                                        break;
-                                    }
-                                }
-
-                                // Insert rounded corner clipping at the beginning (after Clear)
-                                let rounded_clip = RenderCommand::PushRoundedClip {
-                                    x: 0.0,
-                                    y: 0.0,
-                                    width: logical_width as f32,
-                                    height: logical_height as f32,
-                                    corner_radii: [window_radius, window_radius, window_radius, window_radius],
-                                };
-
-                                // Find the position after Clear command (if any)
-                                let insert_pos = all_commands.iter()
-                                    .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
-                                    .unwrap_or(0);
-                                all_commands.insert(insert_pos, rounded_clip);
-
-                                // If we had a background color, draw it as a fullscreen rect right after PushRoundedClip
-                                // This rect will be clipped to the rounded corners by the stencil
-                                if let Some(color) = bg_color {
-                                    let bg_rect = RenderCommand::DrawRect {
-                                        x: 0.0,
-                                        y: 0.0,
-                                        width: logical_width as f32,
-                                        height: logical_height as f32,
-                                        color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
-                                        corner_radii: [0.0, 0.0, 0.0, 0.0], // No corner radius needed, stencil handles it
-                                        rotation: 0.0,
-                                        border: None,
-                                        gradient: None,
-                                    };
-                                    // Insert right after the PushRoundedClip
-                                    all_commands.insert(insert_pos + 1, bg_rect);
-                                }
-
-                                // Add window controls (inside the clipped area)
-                                if let Some(ref controls) = self.window_controls {
-                                    let control_commands = controls.to_render_commands(logical_width as f32);
-                                    all_commands.extend(control_commands);
-                                }
-
-                                // End rounded corner clipping before drawing border
-                                all_commands.push(RenderCommand::PopClip {});
-
-                                // Add window border (rendered last, on top as outline, outside clip)
-                                let is_dark = self.current_dark_mode == 1 ||
-                                    (self.current_dark_mode == 2 && crate::platform::linux::is_dark_mode());
-                                let border_cmd = crate::platform::linux::window_border_command(
-                                    logical_width as f32,
-                                    logical_height as f32,
-                                    is_dark,
-                                );
-                                all_commands.push(border_cmd);
-                            }
-                        }
-
-                        // Windows frameless window: add rounded corner clipping, window controls, and border
-                        #[cfg(target_os = "windows")]
-                        {
-                            if !all_commands.is_empty() && !self.config.decorations {
-                                let window_radius = crate::platform::windows::WINDOW_CORNER_RADIUS;
-
-                                // Extract the background color from Clear command and replace with transparent
-                                let mut bg_color: Option<crate::style::Color> = None;
-                                for cmd in all_commands.iter_mut() {
-                                    if let RenderCommand::Clear(color) = cmd {
-                                        bg_color = Some(*color);
-                                        *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
-                                        break;
-                                    }
-                                }
-
-                                // Insert rounded corner clipping at the beginning (after Clear)
-                                let rounded_clip = RenderCommand::PushRoundedClip {
-                                    x: 0.0,
-                                    y: 0.0,
-                                    width: logical_width as f32,
-                                    height: logical_height as f32,
-                                    corner_radii: [window_radius, window_radius, window_radius, window_radius],
-                                };
-
-                                let insert_pos = all_commands.iter()
-                                    .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
-                                    .unwrap_or(0);
-                                all_commands.insert(insert_pos, rounded_clip);
-
-                                // Draw background rect inside the stencil clip
-                                if let Some(color) = bg_color {
-                                    let bg_rect = RenderCommand::DrawRect {
-                                        x: 0.0,
-                                        y: 0.0,
-                                        width: logical_width as f32,
-                                        height: logical_height as f32,
-                                        color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
-                                        corner_radii: [0.0, 0.0, 0.0, 0.0],
-                                        rotation: 0.0,
-                                        border: None,
-                                        gradient: None,
-                                    };
-                                    all_commands.insert(insert_pos + 1, bg_rect);
-                                }
-
-                                // Add window controls (inside the clipped area, like Linux)
-                                if let Some(ref controls) = self.window_controls {
-                                    let control_commands = controls.to_render_commands(logical_width as f32);
-                                    all_commands.extend(control_commands);
-                                }
-
-                                // End rounded corner clipping
-                                all_commands.push(RenderCommand::PopClip {});
-
-                                // Add window border
-                                let is_dark = self.current_dark_mode == 1;
-                                let border_cmd = crate::platform::windows::window_border_command(
-                                    logical_width as f32,
-                                    logical_height as f32,
-                                    is_dark,
-                                );
-                                all_commands.push(border_cmd);
-                            }
-                        }
-
-                        if !all_commands.is_empty() {
-                            // Get scissor rect from dirty region (if any)
-                            let scissor = response.get_scissor_rect(scale_factor);
-                            if let Err(e) = backend.render_frame_with_scissor(&all_commands, scissor) {
-                                eprintln!("Render error: {}", e);
-                            }
-                        }
-                    }
-                }
-
-                // If response wants continuous redraw, schedule another
-                if response.request_redraw {
-                    if let Some(ref window) = self.window {
-                        window.request_redraw();
-                    }
-                }
+                self.redraw(event_loop);
             }
             UserEvent::Minimize => {
                 if let Some(ref window) = self.window {
@@ -2229,6 +4491,93 @@ impl ApplicationHandler<UserEvent> for App {
                     window.set_title(&title);
                 }
             }
+            UserEvent::SetCursorImage(image) => {
+                self.cursor_image = image;
+                self.apply_cursor_image(event_loop);
+            }
+            UserEvent::SetCursorIcon(icon) => {
+                self.cursor_icon = icon;
+                // A custom image keeps precedence until it is cleared
+                if let (Some(ref window), None) = (&self.window, &self.custom_cursor) {
+                    window.set_cursor(icon);
+                }
+            }
+            UserEvent::SetCursorVisible(visible) => {
+                if let Some(ref window) = self.window {
+                    window.set_cursor_visible(visible);
+                }
+            }
+            UserEvent::SetPointerCapture(enabled, auto_release) => {
+                self.pointer_capture.set(enabled, auto_release);
+            }
+            UserEvent::SetBlurBehind(enabled) => {
+                if let Some(ref window) = self.window {
+                    if let Err(e) = set_window_blur_behind(window, enabled) {
+                        eprintln!("Failed to set blur behind: {}", e);
+                    }
+                    // KDE's Wayland blur protocol goes through winit
+                    #[cfg(target_os = "linux")]
+                    window.set_blur(enabled);
+                }
+            }
+            UserEvent::SetOpacity(opacity) => {
+                if let Some(ref window) = self.window {
+                    if let Err(e) = set_window_opacity(window, opacity) {
+                        eprintln!("Failed to set window opacity: {}", e);
+                    }
+                }
+            }
+            UserEvent::SetAspectRatio(ratio) => {
+                self.aspect_ratio = ratio;
+                // Snap the current size; the Resized event that follows does the rest
+                if let Some(ref window) = self.window {
+                    if let Some(corrected) = self.aspect_corrected_size(window.inner_size()) {
+                        let _ = window.request_inner_size(corrected);
+                    }
+                }
+            }
+            UserEvent::SetTargetFps(fps) => {
+                self.config.target_fps = fps;
+                self.frame_cap = (fps > 0).then_some(fps);
+            }
+            UserEvent::SetImeCursorArea(x, y, width, height) => {
+                if let Some(ref window) = self.window {
+                    window.set_ime_cursor_area(LogicalPosition::new(x, y), LogicalSize::new(width, height));
+                }
+            }
+            UserEvent::BeginDrag(payload) => {
+                if let Some(ref window) = self.window {
+                    if let Err(e) = crate::platform::drag::begin_drag(window, &payload) {
+                        eprintln!("Failed to begin drag: {}", e);
+                        crate::platform::drag::notify_drag_result(crate::platform::drag::DragResult::Failed);
+                    }
+                }
+            }
+            UserEvent::SetMenuBar(menus) => {
+                if let Err(e) = crate::platform::menu::set_menu_bar(&menus, send_menu_item_clicked) {
+                    eprintln!("Failed to set menu bar: {}", e);
+                }
+            }
+            UserEvent::MenuItemClicked(id) => {
+                let event = AppEvent {
+                    event_type: AppEventType::MenuItemClicked,
+                    data1: id as f64,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                };
+                let response = self.call_callback(&event);
+                if response.request_redraw {
+                    if let Some(ref window) = self.window {
+                        window.request_redraw();
+                    }
+                }
+            }
             #[cfg(target_os = "linux")]
             UserEvent::SystemThemeChanged(is_dark) => {
                 // Update window controls based on system theme change
@@ -2266,10 +4615,12 @@ impl ApplicationHandler<UserEvent> for App {
 
         // Create window with all config options
         // On Linux, frameless windows need transparency for rounded corners
+        // Blur-behind only shows through transparent areas
         #[cfg(target_os = "linux")]
-        let needs_transparent = self.config.transparent || !self.config.decorations;
+        let needs_transparent =
+            self.config.transparent || self.config.blur_behind || !self.config.decorations;
         #[cfg(not(target_os = "linux"))]
-        let needs_transparent = self.config.transparent;
+        let needs_transparent = self.config.transparent || self.config.blur_behind;
 
         let mut window_attrs = Window::default_attributes()
             .with_title(&title)
@@ -2308,6 +4659,8 @@ impl ApplicationHandler<UserEvent> for App {
                 return;
             }
         };
+        // Deliver composition (preedit) text as ImePreedit/ImeCommit events
+        window.set_ime_allowed(true);
 
         // Get physical size and scale factor
         // On iOS, use outer_size for full screen rendering
@@ -2323,6 +4676,10 @@ impl ApplicationHandler<UserEvent> for App {
             vsync: self.config.vsync,
             low_power_gpu: self.config.low_power_gpu,
             allow_software_fallback: self.config.allow_software_fallback,
+            gamma_correct_text: self.config.gamma_correct_text,
+            subpixel_text: self.config.subpixel_text,
+            wide_gamut: self.config.wide_gamut,
+            display_p3_colors: self.config.display_p3_colors,
         };
 
         if let Err(e) = pollster::block_on(backend.init_with_window(&window, config)) {
@@ -2331,19 +4688,40 @@ impl ApplicationHandler<UserEvent> for App {
             return;
         }
 
+        // Warm up pipelines and glyphs so the first real frame doesn't hitch
+        if let Err(e) = backend.prewarm() {
+            eprintln!("Backend prewarm failed: {}", e);
+        }
+
         // Apply platform-specific window styling for frameless windows
         // This must happen after window creation but before we move the window
+        let style_options = WindowStyleOptions {
+            corner_radius: self.config.corner_radius,
+            show_native_controls: self.config.show_native_controls,
+            enable_minimize: self.config.enable_minimize,
+            enable_maximize: self.config.enable_maximize,
+            blur_behind: self.config.blur_behind,
+            opacity: if self.config.window_opacity > 0.0 {
+                self.config.window_opacity.min(1.0)
+            } else {
+                1.0
+            },
+        };
         if !self.config.decorations {
-            let style_options = WindowStyleOptions {
-                corner_radius: self.config.corner_radius,
-                show_native_controls: self.config.show_native_controls,
-                enable_minimize: self.config.enable_minimize,
-                enable_maximize: self.config.enable_maximize,
-            };
             if let Err(e) = apply_window_style(&window, style_options) {
                 eprintln!("Failed to apply window style: {}", e);
             }
         }
+        if let Err(e) = apply_window_effects(&window, style_options) {
+            eprintln!("Failed to apply window effects: {}", e);
+        }
+        if let Err(e) = crate::widget::accessibility::install(&window) {
+            eprintln!("Failed to install accessibility support: {}", e);
+        }
+        #[cfg(target_os = "linux")]
+        if self.config.blur_behind {
+            window.set_blur(true);
+        }
 
         // Update safe area insets before storing window (iOS only)
         update_safe_area_from_window(&window);
@@ -2361,13 +4739,19 @@ impl ApplicationHandler<UserEvent> for App {
         // Send Ready event to Go with logical pixels
         let logical_width = size.width as f64 / scale_factor;
         let logical_height = size.height as f64 / scale_factor;
-        println!("Window Ready: physical={}x{}, logical={}x{}, scale_factor={}",
+        verbose!("Window Ready: physical={}x{}, logical={}x{}, scale_factor={}",
             size.width, size.height, logical_width, logical_height, scale_factor);
         let event = AppEvent {
             event_type: AppEventType::Ready,
             data1: logical_width,
             data2: logical_height,
             scale_factor,
+            frame_dt: 0.0,
+            render_failures: 0,
+            scroll_phase: ScrollPhase::None,
+            touch_phase: TouchPhase::None,
+            touch_id: 0,
+            text: ptr::null(),
         };
         self.call_callback(&event);
 
@@ -2385,6 +4769,12 @@ impl ApplicationHandler<UserEvent> for App {
                     data1: 0.0,
                     data2: 0.0,
                     scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
                 };
                 self.call_callback(&event);
                 self.should_exit = true;
@@ -2394,6 +4784,14 @@ impl ApplicationHandler<UserEvent> for App {
             WindowEvent::Resized(size) => {
                 let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
 
+                // Correct off-ratio sizes when the aspect ratio is locked. If the
+                // platform applies the new size immediately, continue with it;
+                // otherwise another Resized event follows with the corrected size.
+                let size = match (self.aspect_corrected_size(size), self.window.as_ref()) {
+                    (Some(corrected), Some(window)) => window.request_inner_size(corrected).unwrap_or(size),
+                    _ => size,
+                };
+
                 // Update safe area insets (may change on orientation change - iOS)
                 if let Some(ref window) = self.window {
                     update_safe_area_from_window(window);
@@ -2402,15 +4800,18 @@ impl ApplicationHandler<UserEvent> for App {
                 // Use the size from the event directly. On iOS, this comes from layoutSubviews
                 // which has the correct window bounds. Don't use window.outer_size() because
                 // it may return stale data during rotation transitions.
-                println!("[FFI] WindowEvent::Resized - size: {:?}x{:?}, scale: {}",
+                verbose!("[FFI] WindowEvent::Resized - size: {:?}x{:?}, scale: {}",
                     size.width, size.height, scale_factor);
 
+                // The presented frame no longer matches the surface
+                self.widget_tree.invalidate_frame();
+
                 // Resize backend (uses physical pixels)
                 {
                     let backend_lock = get_backend();
                     let mut guard = backend_lock.lock().unwrap();
                     if let Some(ref mut backend) = *guard {
-                        println!("[FFI] Calling backend.resize({}, {}, {})", size.width, size.height, scale_factor);
+                        verbose!("[FFI] Calling backend.resize({}, {}, {})", size.width, size.height, scale_factor);
                         if let Err(e) = backend.resize(size.width, size.height, scale_factor) {
                             eprintln!("Resize error: {}", e);
                         }
@@ -2421,15 +4822,26 @@ impl ApplicationHandler<UserEvent> for App {
                 // This ensures Go's coordinate system matches mouse events
                 let logical_width = size.width as f64 / scale_factor;
                 let logical_height = size.height as f64 / scale_factor;
-                println!("[FFI] Sending EventResized to Go: logical {}x{}", logical_width, logical_height);
+                verbose!("[FFI] Sending EventResized to Go: logical {}x{}", logical_width, logical_height);
                 let event = AppEvent {
                     event_type: AppEventType::Resized,
                     data1: logical_width,
                     data2: logical_height,
                     scale_factor,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
                 };
                 self.call_callback(&event);
 
+                // Windows minimizes the window to a zero size
+                let minimized = self.window.as_ref().and_then(|w| w.is_minimized());
+                let change = self.window_state.resized(size.width, size.height, minimized);
+                self.send_window_state(change);
+
                 // Linux/Windows: update frameless state in a single lock acquisition
                 #[cfg(any(target_os = "linux", target_os = "windows"))]
                 {
@@ -2442,279 +4854,49 @@ impl ApplicationHandler<UserEvent> for App {
                         {
                             if let Some(ref mut controls) = self.window_controls {
                                 if let Some(ref window) = self.window {
-                                    controls.maximize_state.maximized = window.is_maximized();
-                                    state.window_controls = Some(controls.clone());
-                                }
-                            }
-                        }
-
-                        #[cfg(target_os = "windows")]
-                        {
-                            if let Some(ref mut controls) = self.window_controls {
-                                if let Some(ref window) = self.window {
-                                    controls.maximize_state.maximized = window.is_maximized();
-                                    state.window_controls = Some(controls.clone());
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Request redraw after resize
-                if let Some(ref window) = self.window {
-                    window.request_redraw();
-                }
-            }
-
-            WindowEvent::RedrawRequested => {
-                let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
-                let size = self.window.as_ref().map(|w| get_window_size(w)).unwrap_or_default();
-
-                // Report logical pixels to Go (physical / scale_factor)
-                let logical_width = size.width as f64 / scale_factor;
-                let logical_height = size.height as f64 / scale_factor;
-                let event = AppEvent {
-                    event_type: AppEventType::RedrawRequested,
-                    data1: logical_width,
-                    data2: logical_height,
-                    scale_factor,
-                };
-
-                // Call Go callback and get response
-                let response = self.call_callback(&event);
-
-                // Linux: update window controls theme if dark mode changed
-                #[cfg(target_os = "linux")]
-                self.update_dark_mode(response.dark_mode);
-
-                // Process retained mode widget delta (if any)
-                // TODO: Apply widget_delta to internal widget tree
-                // For now, we just acknowledge it
-                if let Some(ref _delta_json) = response.widget_delta {
-                    // let delta: WidgetDelta = serde_json::from_str(&delta_json)?;
-                    // self.widget_tree.apply_delta(delta);
-                    // This marks affected widgets dirty for re-render
-                }
-
-                // Render frame
-                // In hybrid mode, retained widgets render first, then immediate commands on top
-                {
-                    let backend_lock = get_backend();
-                    let mut guard = backend_lock.lock().unwrap();
-                    if let Some(ref mut backend) = *guard {
-                        let mut all_commands = Vec::new();
-
-                        // Check for layer-based rendering first
-                        if let Some(ref json) = response.layers {
-                            match serde_json::from_str::<Vec<LayerInfo>>(json) {
-                                Ok(layers) => {
-                                    // Sort layers by z_order (lower = further back)
-                                    let mut sorted_layers = layers;
-                                    sorted_layers.sort_by_key(|l| l.z_order);
-
-                                    // Collect commands from all layers in z-order
-                                    for layer in &sorted_layers {
-                                        all_commands.extend(layer.commands.clone());
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse layers: {}", e);
-                                }
-                            }
-                        } else if let Some(ref json) = response.immediate_commands {
-                            // Fall back to immediate commands if no layers
-                            match serde_json::from_str::<Vec<RenderCommand>>(json) {
-                                Ok(commands) => {
-                                    all_commands.extend(commands);
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse immediate commands: {}", e);
-                                }
-                            }
-                        }
-
-                        // Linux frameless window: add rounded corner clipping, window controls, and border
-                        // IMPORTANT: Only add if Go sent commands via JSON (not binary path)
-                        // If Go used RenderFrameBinary, it already rendered and we'd cause a double-clear
-                        #[cfg(target_os = "linux")]
-                        {
-                            if !all_commands.is_empty() && !self.config.decorations {
-                                let window_radius = crate::platform::linux::WINDOW_CORNER_RADIUS;
-
-                                // Extract the background color from Clear command and replace with transparent
-                                // This is needed because the render pass clear happens BEFORE stencil clipping,
-                                // so we need to draw the background as a rect INSIDE the stencil clip instead.
-                                let mut bg_color: Option<crate::style::Color> = None;
-                                for cmd in all_commands.iter_mut() {
-                                    if let RenderCommand::Clear(color) = cmd {
-                                        bg_color = Some(*color);
-                                        // Replace with transparent clear
-                                        *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
-                                        break;
-                                    }
-                                }
-
-                                // Insert rounded corner clipping at the beginning (after Clear)
-                                let rounded_clip = RenderCommand::PushRoundedClip {
-                                    x: 0.0,
-                                    y: 0.0,
-                                    width: logical_width as f32,
-                                    height: logical_height as f32,
-                                    corner_radii: [window_radius, window_radius, window_radius, window_radius],
-                                };
-
-                                // Find the position after Clear command (if any)
-                                let insert_pos = all_commands.iter()
-                                    .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
-                                    .unwrap_or(0);
-                                all_commands.insert(insert_pos, rounded_clip);
-
-                                // If we had a background color, draw it as a fullscreen rect right after PushRoundedClip
-                                // This rect will be clipped to the rounded corners by the stencil
-                                if let Some(color) = bg_color {
-                                    let bg_rect = RenderCommand::DrawRect {
-                                        x: 0.0,
-                                        y: 0.0,
-                                        width: logical_width as f32,
-                                        height: logical_height as f32,
-                                        color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
-                                        corner_radii: [0.0, 0.0, 0.0, 0.0], // No corner radius needed, stencil handles it
-                                        rotation: 0.0,
-                                        border: None,
-                                        gradient: None,
-                                    };
-                                    // Insert right after the PushRoundedClip
-                                    all_commands.insert(insert_pos + 1, bg_rect);
-                                }
-
-                                // Add window controls (inside the clipped area)
-                                if let Some(ref controls) = self.window_controls {
-                                    let control_commands = controls.to_render_commands(logical_width as f32);
-                                    all_commands.extend(control_commands);
-                                }
-
-                                // End rounded corner clipping before drawing border
-                                all_commands.push(RenderCommand::PopClip {});
-
-                                // Add window border (rendered last, on top as outline, outside clip)
-                                let is_dark = self.current_dark_mode == 1 ||
-                                    (self.current_dark_mode == 2 && crate::platform::linux::is_dark_mode());
-                                let border_cmd = crate::platform::linux::window_border_command(
-                                    logical_width as f32,
-                                    logical_height as f32,
-                                    is_dark,
-                                );
-                                all_commands.push(border_cmd);
-                            }
-                        }
-
-                        // Windows frameless window: add rounded corner clipping, window controls, and border
-                        // Only process if Go sent commands - if empty, Go rendered via different path
-                        #[cfg(target_os = "windows")]
-                        {
-                            if !all_commands.is_empty() && !self.config.decorations {
-                                let window_radius = crate::platform::windows::WINDOW_CORNER_RADIUS;
-
-                                let mut bg_color: Option<crate::style::Color> = None;
-                                for cmd in all_commands.iter_mut() {
-                                    if let RenderCommand::Clear(color) = cmd {
-                                        bg_color = Some(*color);
-                                        *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
-                                        break;
-                                    }
-                                }
-
-                                let rounded_clip = RenderCommand::PushRoundedClip {
-                                    x: 0.0,
-                                    y: 0.0,
-                                    width: logical_width as f32,
-                                    height: logical_height as f32,
-                                    corner_radii: [window_radius, window_radius, window_radius, window_radius],
-                                };
-
-                                let insert_pos = all_commands.iter()
-                                    .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
-                                    .unwrap_or(0);
-                                all_commands.insert(insert_pos, rounded_clip);
-
-                                if let Some(color) = bg_color {
-                                    let bg_rect = RenderCommand::DrawRect {
-                                        x: 0.0,
-                                        y: 0.0,
-                                        width: logical_width as f32,
-                                        height: logical_height as f32,
-                                        color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
-                                        corner_radii: [0.0, 0.0, 0.0, 0.0],
-                                        rotation: 0.0,
-                                        border: None,
-                                        gradient: None,
-                                    };
-                                    all_commands.insert(insert_pos + 1, bg_rect);
-                                }
-
-                                // Add window controls (inside the clipped area, like Linux)
-                                if let Some(ref controls) = self.window_controls {
-                                    let control_commands = controls.to_render_commands(logical_width as f32);
-                                    all_commands.extend(control_commands);
+                                    controls.maximize_state.maximized = window.is_maximized();
+                                    state.window_controls = Some(controls.clone());
                                 }
-
-                                all_commands.push(RenderCommand::PopClip {});
-
-                                let is_dark = self.current_dark_mode == 1;
-                                let border_cmd = crate::platform::windows::window_border_command(
-                                    logical_width as f32,
-                                    logical_height as f32,
-                                    is_dark,
-                                );
-                                all_commands.push(border_cmd);
                             }
                         }
 
-                        // Execute all commands
-                        if !all_commands.is_empty() {
-                            // Get scissor rect from dirty region (if any)
-                            let scissor = response.get_scissor_rect(scale_factor);
-                            if let Err(e) = backend.render_frame_with_scissor(&all_commands, scissor) {
-                                eprintln!("Render error: {}", e);
+                        #[cfg(target_os = "windows")]
+                        {
+                            if let Some(ref mut controls) = self.window_controls {
+                                if let Some(ref window) = self.window {
+                                    controls.maximize_state.maximized = window.is_maximized();
+                                    state.window_controls = Some(controls.clone());
+                                }
                             }
                         }
                     }
                 }
 
-                // Handle redraw scheduling
-                self.update_scheduled_redraw(&response);
+                // Request redraw after resize
+                if let Some(ref window) = self.window {
+                    window.request_redraw();
+                }
+            }
+
+            WindowEvent::ScaleFactorChanged { .. } => {
+                self.widget_tree.invalidate_frame();
 
-                if response.request_redraw {
-                    // Immediate redraw requested (animations, scrolling, etc.)
-                    if let Some(ref window) = self.window {
-                        window.request_redraw();
-                    }
-                    // Clear scheduled redraw since we're doing immediate
-                    self.next_redraw_at = None;
-                    event_loop.set_control_flow(ControlFlow::Poll);
-                } else if let Some(wake_time) = self.next_redraw_at {
-                    // Delayed redraw scheduled (cursor blink, etc.)
-                    if wake_time <= std::time::Instant::now() {
-                        // Time already passed, request immediate redraw
-                        if let Some(ref window) = self.window {
-                            window.request_redraw();
-                        }
-                        self.next_redraw_at = None;
-                    } else {
-                        // Schedule wakeup at the specified time
-                        event_loop.set_control_flow(ControlFlow::WaitUntil(wake_time));
-                    }
-                } else {
-                    // No redraw needed, wait for events
-                    event_loop.set_control_flow(ControlFlow::Wait);
+                // Window moved to a monitor with a different DPI: rebuild the
+                // custom cursor so it keeps the same physical size
+                if self.cursor_image.is_some() {
+                    self.apply_cursor_image(event_loop);
                 }
             }
 
+            WindowEvent::RedrawRequested => self.redraw(event_loop),
+
             WindowEvent::CursorMoved { position, .. } => {
                 let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
                 // Convert to logical pixels to match our coordinate system
                 let logical_x = position.x / scale_factor;
                 let logical_y = position.y / scale_factor;
+                self.pointer_capture.cursor_moved(logical_x, logical_y);
+                self.file_drop.cursor_moved(logical_x, logical_y);
 
                 // Linux: track mouse position for window controls and resize
                 #[cfg(target_os = "linux")]
@@ -2763,9 +4945,12 @@ impl ApplicationHandler<UserEvent> for App {
                                 Some(crate::platform::linux::window_controls::ResizeEdge::BottomRight) => CursorIcon::NwseResize,
                                 Some(crate::platform::linux::window_controls::ResizeEdge::TopRight) |
                                 Some(crate::platform::linux::window_controls::ResizeEdge::BottomLeft) => CursorIcon::NeswResize,
-                                None => CursorIcon::Default,
+                                None => self.cursor_icon,
                             };
-                            window.set_cursor(cursor);
+                            match (&edge, &self.custom_cursor) {
+                                (None, Some(custom)) => window.set_cursor(custom.clone()),
+                                _ => window.set_cursor(cursor),
+                            }
                         }
 
                         self.resize_direction = edge.map(|e| e.to_resize_direction());
@@ -2816,9 +5001,12 @@ impl ApplicationHandler<UserEvent> for App {
                                 Some(crate::platform::windows::window_controls::ResizeEdge::BottomRight) => CursorIcon::NwseResize,
                                 Some(crate::platform::windows::window_controls::ResizeEdge::TopRight) |
                                 Some(crate::platform::windows::window_controls::ResizeEdge::BottomLeft) => CursorIcon::NeswResize,
-                                None => CursorIcon::Default,
+                                None => self.cursor_icon,
                             };
-                            window.set_cursor(cursor);
+                            match (&edge, &self.custom_cursor) {
+                                (None, Some(custom)) => window.set_cursor(custom.clone()),
+                                _ => window.set_cursor(cursor),
+                            }
                         }
 
                         self.resize_direction = edge.map(|e| e.to_resize_direction());
@@ -2830,6 +5018,12 @@ impl ApplicationHandler<UserEvent> for App {
                     data1: logical_x,
                     data2: logical_y,
                     scale_factor,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
                 };
                 let response = self.call_callback(&event);
                 // Input events can trigger state changes that need redraw
@@ -2841,6 +5035,8 @@ impl ApplicationHandler<UserEvent> for App {
             }
 
             WindowEvent::CursorLeft { .. } => {
+                self.pointer_capture.cursor_left();
+
                 // Linux: clear hover states when cursor leaves window
                 #[cfg(target_os = "linux")]
                 {
@@ -2892,6 +5088,12 @@ impl ApplicationHandler<UserEvent> for App {
                                             data1: 0.0,
                                             data2: 0.0,
                                             scale_factor: 1.0,
+                                            frame_dt: 0.0,
+                                            render_failures: 0,
+                                            scroll_phase: ScrollPhase::None,
+                                            touch_phase: TouchPhase::None,
+                                            touch_id: 0,
+                                            text: ptr::null(),
                                         };
                                         let _ = self.call_callback(&close_event);
                                         self.should_exit = true;
@@ -2974,6 +5176,12 @@ impl ApplicationHandler<UserEvent> for App {
                                             data1: 0.0,
                                             data2: 0.0,
                                             scale_factor: 1.0,
+                                            frame_dt: 0.0,
+                                            render_failures: 0,
+                                            scroll_phase: ScrollPhase::None,
+                                            touch_phase: TouchPhase::None,
+                                            touch_id: 0,
+                                            text: ptr::null(),
                                         };
                                         let _ = self.call_callback(&close_event);
                                         self.should_exit = true;
@@ -3047,11 +5255,21 @@ impl ApplicationHandler<UserEvent> for App {
                     winit::event::MouseButton::Forward => 4.0,
                     winit::event::MouseButton::Other(n) => n as f64,
                 };
+                // Already reported while the cursor was outside the window
+                if !self.pointer_capture.button(button_idx as u32, state == ElementState::Pressed) {
+                    return;
+                }
                 let event = AppEvent {
                     event_type,
                     data1: button_idx,
                     data2: 0.0,
                     scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
                 };
                 let response = self.call_callback(&event);
                 // Click events often trigger hover/active state animations
@@ -3062,12 +5280,18 @@ impl ApplicationHandler<UserEvent> for App {
                 }
             }
 
-            WindowEvent::MouseWheel { delta, .. } => {
+            WindowEvent::MouseWheel { delta, phase, .. } => {
                 let (mut dx, mut dy, is_line_delta) = match delta {
                     winit::event::MouseScrollDelta::LineDelta(x, y) => (x as f64 * 20.0, y as f64 * 20.0, true),
                     winit::event::MouseScrollDelta::PixelDelta(pos) => (pos.x, pos.y, false),
                 };
 
+                #[cfg(target_os = "macos")]
+                let momentum = current_scroll_is_momentum();
+                #[cfg(not(target_os = "macos"))]
+                let momentum = false;
+                let scroll_phase = wheel_scroll_phase(phase, is_line_delta, momentum);
+
                 // On Linux, winit gives us "natural" scroll deltas.
                 // Flip to traditional if the user has natural scrolling disabled.
                 #[cfg(target_os = "linux")]
@@ -3098,14 +5322,14 @@ impl ApplicationHandler<UserEvent> for App {
                     }
                 }
 
-                // Suppress unused variable warning on non-Windows platforms
-                let _ = is_line_delta;
-
                 let event = AppEvent {
                     event_type: AppEventType::MouseWheel,
                     data1: dx,
                     data2: dy,
                     scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase,
                 };
                 let response = self.call_callback(&event);
                 // Scroll typically needs immediate redraw
@@ -3145,12 +5369,19 @@ impl ApplicationHandler<UserEvent> for App {
                 if self.modifiers.super_key() {
                     mods |= MOD_SUPER;
                 }
+                let key_text = logical_key_text(&event.logical_key);
 
                 let app_event = AppEvent {
                     event_type,
                     data1: keycode,
-                    data2: mods as f64,
+                    data2: (mods | if event.repeat { KEY_REPEAT } else { 0 }) as f64,
                     scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: key_text.as_ref().map_or(ptr::null(), |t| t.as_ptr()),
                 };
                 let response = self.call_callback(&app_event);
 
@@ -3163,6 +5394,12 @@ impl ApplicationHandler<UserEvent> for App {
                                 data1: c as u32 as f64,
                                 data2: mods as f64, // Include modifiers for char input too
                                 scale_factor: 1.0,
+                                frame_dt: 0.0,
+                                render_failures: 0,
+                                scroll_phase: ScrollPhase::None,
+                                touch_phase: TouchPhase::None,
+                                touch_id: 0,
+                                text: ptr::null(),
                             };
                             self.call_callback(&char_event);
                         }
@@ -3176,6 +5413,44 @@ impl ApplicationHandler<UserEvent> for App {
                 }
             }
 
+            WindowEvent::Ime(ime) => {
+                let (event_type, text, cursor) = match ime {
+                    Ime::Preedit(text, cursor) => (AppEventType::ImePreedit, text, cursor),
+                    Ime::Commit(text) => (AppEventType::ImeCommit, text, None),
+                    // Composition state is reported through Preedit alone
+                    Ime::Enabled | Ime::Disabled => return,
+                };
+                let Ok(c_text) = CString::new(text.as_str()) else {
+                    return;
+                };
+                let mut response = self.call_callback(&ime_event(event_type, &c_text, cursor));
+
+                // Committed text also arrives as CharInput, like typed keys
+                if matches!(event_type, AppEventType::ImeCommit) {
+                    for c in text.chars() {
+                        let char_event = AppEvent {
+                            event_type: AppEventType::CharInput,
+                            data1: c as u32 as f64,
+                            data2: 0.0,
+                            scale_factor: 1.0,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
+                        };
+                        response.request_redraw |= self.call_callback(&char_event).request_redraw;
+                    }
+                }
+
+                if response.request_redraw {
+                    if let Some(ref window) = self.window {
+                        window.request_redraw();
+                    }
+                }
+            }
+
             WindowEvent::Touch(touch) => {
                 let scale_factor = self
                     .window
@@ -3183,11 +5458,29 @@ impl ApplicationHandler<UserEvent> for App {
                     .map(|w| w.scale_factor())
                     .unwrap_or(1.0);
 
-                println!(
+                verbose!(
                     "[FFI] Touch event: phase={:?}, location=({:.1}, {:.1}), scale={:.1}",
                     touch.phase, touch.location.x, touch.location.y, scale_factor
                 );
 
+                let touch_app_event = touch_event(
+                    touch.id,
+                    touch_phase(touch.phase),
+                    touch.location.x / scale_factor,
+                    touch.location.y / scale_factor,
+                    scale_factor,
+                );
+                let response = self.call_callback(&touch_app_event);
+                if response.request_redraw {
+                    if let Some(ref window) = self.window {
+                        window.request_redraw();
+                    }
+                }
+                if !self.config.touch_mouse_emulation {
+                    return;
+                }
+
+                // Mouse emulation: the touch also acts as the left button
                 match touch.phase {
                     winit::event::TouchPhase::Started => {
                         // First send mouse move to update position (touch includes location)
@@ -3196,6 +5489,12 @@ impl ApplicationHandler<UserEvent> for App {
                             data1: touch.location.x / scale_factor,
                             data2: touch.location.y / scale_factor,
                             scale_factor,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
                         };
                         self.call_callback(&move_event);
 
@@ -3205,6 +5504,12 @@ impl ApplicationHandler<UserEvent> for App {
                             data1: 0.0, // Button 0 = left mouse button
                             data2: 0.0,
                             scale_factor,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
                         };
                         let response = self.call_callback(&press_event);
                         if response.request_redraw {
@@ -3220,6 +5525,12 @@ impl ApplicationHandler<UserEvent> for App {
                             data1: touch.location.x / scale_factor,
                             data2: touch.location.y / scale_factor,
                             scale_factor,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
                         };
                         let response = self.call_callback(&event);
                         if response.request_redraw {
@@ -3235,6 +5546,12 @@ impl ApplicationHandler<UserEvent> for App {
                             data1: touch.location.x / scale_factor,
                             data2: touch.location.y / scale_factor,
                             scale_factor,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
                         };
                         self.call_callback(&move_event);
 
@@ -3244,6 +5561,12 @@ impl ApplicationHandler<UserEvent> for App {
                             data1: 0.0, // Button 0 = left mouse button
                             data2: 0.0,
                             scale_factor,
+                            frame_dt: 0.0,
+                            render_failures: 0,
+                            scroll_phase: ScrollPhase::None,
+                            touch_phase: TouchPhase::None,
+                            touch_id: 0,
+                            text: ptr::null(),
                         };
                         let response = self.call_callback(&release_event);
                         if response.request_redraw {
@@ -3255,12 +5578,86 @@ impl ApplicationHandler<UserEvent> for App {
                 }
             }
 
+            WindowEvent::Focused(focused) => {
+                let change = self.window_state.focused(focused);
+                self.send_window_state(change);
+            }
+
+            // macOS reports minimizing as the window becoming occluded
+            WindowEvent::Occluded(occluded) => {
+                let minimized = self.window.as_ref().and_then(|w| w.is_minimized());
+                let change = self.window_state.occluded(occluded, minimized);
+                self.send_window_state(change);
+            }
+
+            // Reported together once the event loop is about to wait
+            WindowEvent::HoveredFile(path) => self.file_drop.hovered(path),
+            WindowEvent::DroppedFile(path) => self.file_drop.dropped(path),
+            WindowEvent::HoveredFileCancelled => self.file_drop.cancelled(),
+
             _ => {}
         }
     }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.dispatch_file_drop_events(event_loop);
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        // Raw mouse input only matters while pointer capture follows a
+        // cursor the window has lost track of
+        if !self.pointer_capture.is_enabled() {
+            return;
+        }
+        let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+        let event = match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } => {
+                let Some((x, y)) = self.pointer_capture.device_motion(dx / scale_factor, dy / scale_factor) else {
+                    return;
+                };
+                AppEvent {
+                    event_type: AppEventType::MouseMoved,
+                    data1: x,
+                    data2: y,
+                    scale_factor,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
+            }
+            DeviceEvent::Button { state: ElementState::Released, .. } => {
+                let Some(button) = self.pointer_capture.device_button_released() else {
+                    return;
+                };
+                AppEvent {
+                    event_type: AppEventType::MouseReleased,
+                    data1: button as f64,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
+            }
+            _ => return,
+        };
+        let response = self.call_callback(&event);
+        if response.request_redraw {
+            if let Some(ref window) = self.window {
+                window.request_redraw();
+            }
+        }
+    }
 }
 
 /// Processed response from callback
+#[cfg(feature = "winit")]
 struct ProcessedResponse {
     immediate_commands: Option<String>,
     widget_delta: Option<String>,
@@ -3271,20 +5668,29 @@ struct ProcessedResponse {
     dirty_region: Option<String>,
 }
 
+#[cfg(feature = "winit")]
 impl ProcessedResponse {
+    /// Physical pixel scissor rect for the frame: Go's dirty_region if it
+    /// sent one, otherwise the retained tree's damage when the tree is all
+    /// that's drawn, since immediate commands and layers can change pixels
+    /// the tree doesn't know about.
+    /// Returns None for a full redraw.
+    fn scissor_rect(&self, damage: Option<crate::geometry::Rect>, scale_factor: f64) -> Option<(u32, u32, u32, u32)> {
+        if self.dirty_region.is_some() {
+            return self.get_scissor_rect(scale_factor);
+        }
+        if self.immediate_commands.is_some() || self.layers.is_some() {
+            return None;
+        }
+        damage.map(|rect| physical_scissor(rect.x, rect.y, rect.width, rect.height, scale_factor))
+    }
+
     /// Parse dirty_region JSON and convert to physical pixel scissor rect.
     /// Returns None if no dirty region (meaning full redraw).
     fn get_scissor_rect(&self, scale_factor: f64) -> Option<(u32, u32, u32, u32)> {
         let json = self.dirty_region.as_ref()?;
         match serde_json::from_str::<DirtyRegion>(json) {
-            Ok(region) => {
-                // Convert logical pixels to physical pixels
-                let x = (region.x as f64 * scale_factor) as u32;
-                let y = (region.y as f64 * scale_factor) as u32;
-                let w = (region.width as f64 * scale_factor).ceil() as u32;
-                let h = (region.height as f64 * scale_factor).ceil() as u32;
-                Some((x, y, w.max(1), h.max(1)))
-            }
+            Ok(region) => Some(physical_scissor(region.x, region.y, region.width, region.height, scale_factor)),
             Err(e) => {
                 eprintln!("Failed to parse dirty_region: {}", e);
                 None
@@ -3293,7 +5699,159 @@ impl ProcessedResponse {
     }
 }
 
+/// Convert a logical rect to a physical pixel scissor rect covering it
+#[cfg(feature = "winit")]
+fn physical_scissor(x: f32, y: f32, width: f32, height: f32, scale_factor: f64) -> (u32, u32, u32, u32) {
+    let left = (x as f64 * scale_factor).max(0.0).floor();
+    let top = (y as f64 * scale_factor).max(0.0).floor();
+    let right = ((x + width) as f64 * scale_factor).ceil();
+    let bottom = ((y + height) as f64 * scale_factor).ceil();
+    (
+        left as u32,
+        top as u32,
+        ((right - left) as u32).max(1),
+        ((bottom - top) as u32).max(1),
+    )
+}
+
+#[cfg(feature = "winit")]
 impl App {
+    /// Report a window state change, if there is one
+    fn send_window_state(&self, change: Option<crate::platform::window_state::WindowStateChange>) {
+        let Some(change) = change else { return };
+        let response = self.call_callback(&window_state_event(change));
+        if response.request_redraw {
+            if let Some(ref window) = self.window {
+                window.request_redraw();
+            }
+        }
+    }
+
+    /// Report the drag of files winit's events since the last call add up
+    /// to. While files are over the window the cursor is polled, so
+    /// FileDragOver follows it.
+    fn dispatch_file_drop_events(&mut self, event_loop: &ActiveEventLoop) {
+        use crate::platform::file_drop::{cursor_position, FileDropEvent, CURSOR_POLL_INTERVAL};
+
+        let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+        if self.file_drop.is_dragging() {
+            if let Some((x, y)) = self.window.as_ref().and_then(cursor_position) {
+                self.file_drop.cursor_moved(x / scale_factor, y / scale_factor);
+            }
+        }
+
+        for drop_event in self.file_drop.take_events() {
+            let (event_type, x, y, paths) = match drop_event {
+                FileDropEvent::Enter { paths, x, y } => (AppEventType::FileDragEnter, x, y, Some(paths)),
+                FileDropEvent::Over { x, y } => (AppEventType::FileDragOver, x, y, None),
+                FileDropEvent::Drop { paths, x, y } => (AppEventType::FileDrop, x, y, Some(paths)),
+                FileDropEvent::Cancel => (AppEventType::FileDragCancel, 0.0, 0.0, None),
+            };
+            // Paths never contain NUL, so the JSON array always converts
+            let paths_json = paths.and_then(|paths| serde_json::to_string(&paths).ok()).and_then(|json| CString::new(json).ok());
+            let event = AppEvent {
+                event_type,
+                data1: x,
+                data2: y,
+                scale_factor,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: paths_json.as_ref().map_or(ptr::null(), |json| json.as_ptr()),
+            };
+            let response = self.call_callback(&event);
+            if response.request_redraw {
+                if let Some(ref window) = self.window {
+                    window.request_redraw();
+                }
+            }
+        }
+
+        if self.file_drop.is_dragging() {
+            let poll_at = std::time::Instant::now() + CURSOR_POLL_INTERVAL;
+            event_loop.set_control_flow(match event_loop.control_flow() {
+                ControlFlow::WaitUntil(wake_time) if wake_time < poll_at => ControlFlow::WaitUntil(wake_time),
+                ControlFlow::Poll => ControlFlow::Poll,
+                _ => ControlFlow::WaitUntil(poll_at),
+            });
+        }
+    }
+
+    /// The size a resize to `size` should be corrected to under the locked
+    /// aspect ratio, or None if it needs no correction.
+    ///
+    /// Maximized and fullscreen windows are sized by the system and left alone.
+    fn aspect_corrected_size(&self, size: winit::dpi::PhysicalSize<u32>) -> Option<winit::dpi::PhysicalSize<u32>> {
+        use crate::platform::aspect::SizeLimits;
+
+        let ratio = self.aspect_ratio?;
+        let window = self.window.as_ref()?;
+        if size.width == 0 || size.height == 0 || window.is_maximized() || window.fullscreen().is_some() {
+            return None;
+        }
+
+        let scale_factor = window.scale_factor();
+        let physical = |logical: u32| (logical as f64 * scale_factor).round() as u32;
+        let limits = SizeLimits {
+            min: (self.config.min_width > 0 || self.config.min_height > 0)
+                .then(|| (physical(self.config.min_width), physical(self.config.min_height))),
+            max: (self.config.max_width > 0 || self.config.max_height > 0).then(|| {
+                (
+                    if self.config.max_width > 0 { physical(self.config.max_width) } else { u32::MAX },
+                    if self.config.max_height > 0 { physical(self.config.max_height) } else { u32::MAX },
+                )
+            }),
+        };
+
+        let (width, height) = ratio.constrain(size.width, size.height, limits);
+        // Rounding can leave a pixel of slack; don't chase it
+        if width.abs_diff(size.width) <= 1 && height.abs_diff(size.height) <= 1 {
+            return None;
+        }
+        Some(winit::dpi::PhysicalSize::new(width, height))
+    }
+
+    /// Build the custom cursor for the window's current scale factor (when
+    /// its window system takes physical pixels) and apply it, or restore the
+    /// default cursor when no image is set.
+    fn apply_cursor_image(&mut self, event_loop: &ActiveEventLoop) {
+        use raw_window_handle::HasWindowHandle;
+        use crate::platform::cursor::CursorUnits;
+
+        let Some(ref window) = self.window else {
+            return;
+        };
+
+        let Some(ref image) = self.cursor_image else {
+            self.custom_cursor = None;
+            window.set_cursor(self.cursor_icon);
+            return;
+        };
+
+        let units = match window.window_handle() {
+            Ok(handle) => CursorUnits::for_window(&handle.as_raw()),
+            Err(_) => CursorUnits::Logical,
+        };
+        let scaled = image.for_window_system(units, window.scale_factor());
+        let source = winit::window::CustomCursor::from_rgba(
+            scaled.rgba,
+            scaled.width.min(u16::MAX as u32) as u16,
+            scaled.height.min(u16::MAX as u32) as u16,
+            scaled.hotspot_x.min(u16::MAX as u32) as u16,
+            scaled.hotspot_y.min(u16::MAX as u32) as u16,
+        );
+        match source {
+            Ok(source) => {
+                let cursor = event_loop.create_custom_cursor(source);
+                window.set_cursor(cursor.clone());
+                self.custom_cursor = Some(cursor);
+            }
+            Err(e) => eprintln!("Invalid cursor image: {}", e),
+        }
+    }
+
     fn call_callback(&self, event: &AppEvent) -> ProcessedResponse {
         // Create response struct for callback to fill
         let mut response = FrameResponse {
@@ -3308,11 +5866,7 @@ impl App {
 
         // Call the Go callback
         unsafe {
-            (self.callback)(
-                event as *const AppEvent,
-                &mut response as *mut FrameResponse,
-                self.user_data,
-            );
+            dispatch_event(self.callback, event, &mut response, self.user_data);
         }
 
         // Process the response - read strings WITHOUT taking ownership
@@ -3378,17 +5932,344 @@ impl App {
         }
     }
 
+    /// Run a frame: send Go the RedrawRequested event, apply what it
+    /// returns, render unless nothing changed, and schedule the next frame
+    fn redraw(&mut self, event_loop: &ActiveEventLoop) {
+        let frame_dt = self.begin_frame();
+        let scale_factor = self.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+        let size = self.window.as_ref().map(|w| get_window_size(w)).unwrap_or_default();
+
+        // Report logical pixels to Go (physical / scale_factor)
+        let logical_width = size.width as f64 / scale_factor;
+        let logical_height = size.height as f64 / scale_factor;
+        let event = AppEvent {
+            event_type: AppEventType::RedrawRequested,
+            data1: logical_width,
+            data2: logical_height,
+            scale_factor,
+            frame_dt,
+            render_failures: 0,
+            scroll_phase: ScrollPhase::None,
+            touch_phase: TouchPhase::None,
+            touch_id: 0,
+            text: ptr::null(),
+        };
+
+        // Call Go callback and get response
+        let response = self.call_callback(&event);
+
+        // Linux: update window controls theme if dark mode changed
+        #[cfg(target_os = "linux")]
+        self.update_dark_mode(response.dark_mode);
+
+        // Process retained mode widget delta (if any).
+        // Applying it marks the changed subtrees dirty; when nothing is
+        // dirty the previously presented frame is still correct and
+        // rendering is skipped entirely.
+        let retained = response.widget_delta.is_some();
+        if let Some(ref delta_json) = response.widget_delta {
+            match serde_json::from_str::<WidgetDelta>(delta_json) {
+                Ok(delta) => {
+                    self.widget_tree.apply_delta(delta);
+                    crate::widget::accessibility::publish(&self.widget_tree);
+                }
+                Err(e) => {
+                    eprintln!("Failed to parse widget delta: {}", e);
+                    self.widget_tree.invalidate_frame();
+                }
+            }
+        }
+        if response.request_redraw {
+            // Go is animating: each frame differs even without a delta
+            self.widget_tree.invalidate_frame();
+        }
+        if self.engine_redraw_at.is_some_and(|at| at <= std::time::Instant::now()) {
+            // The caret blinks without touching the tree
+            self.widget_tree.invalidate_frame();
+        }
+        if response.immediate_commands.is_some() || response.layers.is_some() {
+            // Hybrid mode: the overlay drawn over the widgets can change
+            // while the tree stays clean
+            self.widget_tree.invalidate_frame();
+        }
+        let skip_render = retained && !self.widget_tree.needs_render();
+
+        // Render frame
+        // In hybrid mode, retained widgets render first, then immediate commands on top
+        let mut engine_animating = false;
+        let mut engine_redraw_ms = None;
+        {
+            let backend_lock = get_backend();
+            let mut guard = backend_lock.lock().unwrap();
+            if let Some(ref mut backend) = *guard {
+                // Retained widgets first; layers or immediate commands draw on top
+                let mut all_commands = self.widget_tree.render_commands();
+
+                // Check for layer-based rendering first
+                if let Some(ref json) = response.layers {
+                    match serde_json::from_str::<Vec<LayerInfo>>(json) {
+                        Ok(layers) => {
+                            // Sort layers by z_order (lower = further back)
+                            let mut sorted_layers = layers;
+                            sorted_layers.sort_by_key(|l| l.z_order);
+
+                            // Collect commands from all layers in z-order
+                            for layer in &sorted_layers {
+                                all_commands.extend(layer.commands.clone());
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to parse layers: {}", e);
+                        }
+                    }
+                } else if let Some(ref json) = response.immediate_commands {
+                    // Fall back to immediate commands if no layers
+                    match serde_json::from_str::<Vec<RenderCommand>>(json) {
+                        Ok(commands) => {
+                            all_commands.extend(commands);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to parse immediate commands: {}", e);
+                        }
+                    }
+                }
+
+                // Linux frameless window: add rounded corner clipping, window controls, and border
+                // IMPORTANT: Only add if Go sent commands via JSON (not binary path)
+                // If Go used RenderFrameBinary, it already rendered and we'd cause a double-clear
+                #[cfg(target_os = "linux")]
+                {
+                    if !all_commands.is_empty() && !self.config.decorations {
+                        let window_radius = crate::platform::linux::WINDOW_CORNER_RADIUS;
+
+                        // Extract the background color from Clear command and replace with transparent
+                        // This is needed because the render pass clear happens BEFORE stencil clipping,
+                        // so we need to draw the background as a rect INSIDE the stencil clip instead.
+                        let mut bg_color: Option<crate::style::Color> = None;
+                        for cmd in all_commands.iter_mut() {
+                            if let RenderCommand::Clear(color) = cmd {
+                                bg_color = Some(*color);
+                                // Replace with transparent clear
+                                *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
+                                break;
+                            }
+                        }
+
+                        // Insert rounded corner clipping at the beginning (after Clear)
+                        let rounded_clip = RenderCommand::PushRoundedClip {
+                            x: 0.0,
+                            y: 0.0,
+                            width: logical_width as f32,
+                            height: logical_height as f32,
+                            corner_radii: [window_radius, window_radius, window_radius, window_radius],
+                        };
+
+                        // Find the position after Clear command (if any)
+                        let insert_pos = all_commands.iter()
+                            .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
+                            .unwrap_or(0);
+                        all_commands.insert(insert_pos, rounded_clip);
+
+                        // If we had a background color, draw it as a fullscreen rect right after PushRoundedClip
+                        // This rect will be clipped to the rounded corners by the stencil
+                        if let Some(color) = bg_color {
+                            let bg_rect = RenderCommand::DrawRect {
+                                x: 0.0,
+                                y: 0.0,
+                                width: logical_width as f32,
+                                height: logical_height as f32,
+                                color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
+                                corner_radii: [0.0, 0.0, 0.0, 0.0], // No corner radius needed, stencil handles it
+                                rotation: 0.0,
+                                pivot: None,
+                                border: None,
+                                gradient: None,
+                                inner_shadow: None,
+                                shadow: None,
+                            };
+                            // Insert right after the PushRoundedClip
+                            all_commands.insert(insert_pos + 1, bg_rect);
+                        }
+
+                        // Add window controls (inside the clipped area)
+                        if let Some(ref controls) = self.window_controls {
+                            let control_commands = controls.to_render_commands(logical_width as f32);
+                            all_commands.extend(control_commands);
+                        }
+
+                        // End rounded corner clipping before drawing border
+                        all_commands.push(RenderCommand::PopClip {});
+
+                        // Add window border (rendered last, on top as outline, outside clip)
+                        let is_dark = self.current_dark_mode == 1 ||
+                            (self.current_dark_mode == 2 && crate::platform::linux::is_dark_mode());
+                        let border_cmd = crate::platform::linux::window_border_command(
+                            logical_width as f32,
+                            logical_height as f32,
+                            is_dark,
+                        );
+                        all_commands.push(border_cmd);
+                    }
+                }
+
+                // Windows frameless window: add rounded corner clipping, window controls, and border
+                // Only process if Go sent commands - if empty, Go rendered via different path
+                #[cfg(target_os = "windows")]
+                {
+                    if !all_commands.is_empty() && !self.config.decorations {
+                        let window_radius = crate::platform::windows::WINDOW_CORNER_RADIUS;
+
+                        let mut bg_color: Option<crate::style::Color> = None;
+                        for cmd in all_commands.iter_mut() {
+                            if let RenderCommand::Clear(color) = cmd {
+                                bg_color = Some(*color);
+                                *color = crate::style::Color { r: 0, g: 0, b: 0, a: 0 };
+                                break;
+                            }
+                        }
+
+                        let rounded_clip = RenderCommand::PushRoundedClip {
+                            x: 0.0,
+                            y: 0.0,
+                            width: logical_width as f32,
+                            height: logical_height as f32,
+                            corner_radii: [window_radius, window_radius, window_radius, window_radius],
+                        };
+
+                        let insert_pos = all_commands.iter()
+                            .position(|cmd| !matches!(cmd, RenderCommand::Clear(_)))
+                            .unwrap_or(0);
+                        all_commands.insert(insert_pos, rounded_clip);
+
+                        if let Some(color) = bg_color {
+                            let bg_rect = RenderCommand::DrawRect {
+                                x: 0.0,
+                                y: 0.0,
+                                width: logical_width as f32,
+                                height: logical_height as f32,
+                                color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
+                                corner_radii: [0.0, 0.0, 0.0, 0.0],
+                                rotation: 0.0,
+                                pivot: None,
+                                border: None,
+                                gradient: None,
+                                inner_shadow: None,
+                                shadow: None,
+                            };
+                            all_commands.insert(insert_pos + 1, bg_rect);
+                        }
+
+                        // Add window controls (inside the clipped area, like Linux)
+                        if let Some(ref controls) = self.window_controls {
+                            let control_commands = controls.to_render_commands(logical_width as f32);
+                            all_commands.extend(control_commands);
+                        }
+
+                        all_commands.push(RenderCommand::PopClip {});
+
+                        let is_dark = self.current_dark_mode == 1;
+                        let border_cmd = crate::platform::windows::window_border_command(
+                            logical_width as f32,
+                            logical_height as f32,
+                            is_dark,
+                        );
+                        all_commands.push(border_cmd);
+                    }
+                }
+
+                // Execute all commands
+                if !all_commands.is_empty() && !skip_render {
+                    // Scissor to Go's dirty region, or to what the delta changed
+                    let scissor = response.scissor_rect(self.widget_tree.take_damage(), scale_factor);
+                    let result = backend.render_frame_with_scissor(&all_commands, scissor);
+                    record_render_result(&result);
+                    if let Err(e) = result {
+                        eprintln!("Render error: {}", e);
+                    }
+                    self.widget_tree.mark_rendered();
+                }
+                engine_animating = backend.needs_continuous_redraw();
+                if engine_animating {
+                    // Engine animations (spinners, video) change pixels
+                    // without touching the tree
+                    self.widget_tree.invalidate_frame();
+                }
+                engine_redraw_ms = backend.redraw_after_ms();
+            }
+        }
+
+        // Handle redraw scheduling
+        self.update_scheduled_redraw(&response);
+        self.engine_redraw_at = engine_redraw_ms.map(|ms| self.schedule_redraw_after(ms));
+
+        let continuous = response.request_redraw || engine_animating;
+        if let Some(due) = self.capped_frame_due().filter(|_| continuous) {
+            // Too soon for the frame cap: wake for the next frame instead
+            self.next_redraw_at = Some(self.next_redraw_at.map_or(due, |at| at.min(due)));
+            event_loop.set_control_flow(ControlFlow::WaitUntil(due));
+        } else if continuous {
+            // Immediate redraw requested (animations, scrolling, engine spinners, etc.)
+            if let Some(ref window) = self.window {
+                window.request_redraw();
+            }
+            // Clear scheduled redraw since we're doing immediate
+            self.next_redraw_at = None;
+            event_loop.set_control_flow(ControlFlow::Poll);
+        } else if let Some(wake_time) = self.next_redraw_at {
+            // Delayed redraw scheduled (cursor blink, etc.)
+            if wake_time <= std::time::Instant::now() {
+                // Time already passed, request immediate redraw
+                if let Some(ref window) = self.window {
+                    window.request_redraw();
+                }
+                self.next_redraw_at = None;
+            } else {
+                // Schedule wakeup at the specified time
+                event_loop.set_control_flow(ControlFlow::WaitUntil(wake_time));
+            }
+        } else {
+            // No redraw needed, wait for events
+            event_loop.set_control_flow(ControlFlow::Wait);
+        }
+    }
+
     /// Update the scheduled redraw time based on response
     fn update_scheduled_redraw(&mut self, response: &ProcessedResponse) {
         if response.redraw_after_ms > 0 {
-            let new_time = std::time::Instant::now()
-                + std::time::Duration::from_millis(response.redraw_after_ms as u64);
-            // Keep the earliest scheduled time
-            self.next_redraw_at = Some(match self.next_redraw_at {
-                Some(existing) if existing < new_time => existing,
-                _ => new_time,
-            });
+            self.schedule_redraw_after(response.redraw_after_ms);
+        }
+    }
+
+    /// Start a frame on the frame clock, first sending Go a FixedUpdate for
+    /// each fixed step that fell due. Returns the seconds since the last frame.
+    fn begin_frame(&mut self) -> f64 {
+        let timing = self.frame_pacer.begin_frame(std::time::Instant::now());
+        if let Some(step) = self.frame_pacer.step_seconds() {
+            let event = fixed_update_event(step);
+            for _ in 0..timing.fixed_steps {
+                self.call_callback(&event);
+            }
         }
+        timing.dt
+    }
+
+    /// When the next continuous frame may start if it would come too soon
+    /// for the frame cap, None if it can start now
+    fn capped_frame_due(&self) -> Option<std::time::Instant> {
+        let fps = self.frame_cap?;
+        let due = self.frame_pacer.last_frame_at()? + std::time::Duration::from_secs_f64(1.0 / fps as f64);
+        (due > std::time::Instant::now()).then_some(due)
+    }
+
+    /// Schedule a redraw `ms` from now, returning when it is due
+    fn schedule_redraw_after(&mut self, ms: u32) -> std::time::Instant {
+        let new_time = std::time::Instant::now() + std::time::Duration::from_millis(ms as u64);
+        // Keep the earliest scheduled time
+        self.next_redraw_at = Some(match self.next_redraw_at {
+            Some(existing) if existing < new_time => existing,
+            _ => new_time,
+        });
+        new_time
     }
 }
 
@@ -3408,6 +6289,7 @@ impl App {
 /// - config.title must be a valid null-terminated UTF-8 string (or null for default)
 /// - callback must be a valid function pointer
 /// - user_data lifetime must exceed the application run
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub unsafe extern "C" fn centered_app_run(
@@ -3415,7 +6297,7 @@ pub unsafe extern "C" fn centered_app_run(
     callback: AppCallback,
 ) -> i32 {
     if config.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let config = &*config;
@@ -3449,11 +6331,12 @@ pub unsafe extern "C" fn centered_app_run(
 ///     return centered_ios_main(argc, argv);
 /// }
 /// ```
+#[cfg(feature = "winit")]
 #[cfg(target_os = "ios")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_ios_main(argc: i32, argv: *mut *mut i8) -> i32 {
-    println!("[FFI] centered_ios_main called");
+    verbose!("[FFI] centered_ios_main called");
 
     // CRITICAL: Register the AppDelegate class with the ObjC runtime BEFORE
     // calling UIApplicationMain. Without this, UIApplicationMain can't find
@@ -3480,7 +6363,7 @@ pub extern "C" fn centered_ios_main(argc: i32, argv: *mut *mut i8) -> i32 {
 
         let delegate_class = objc2_foundation::NSString::from_str("CenteredAppDelegate");
 
-        println!("[FFI] Calling UIApplicationMain with delegate: CenteredAppDelegate");
+        verbose!("[FFI] Calling UIApplicationMain with delegate: CenteredAppDelegate");
 
         UIApplicationMain(
             argc,
@@ -3494,6 +6377,7 @@ pub extern "C" fn centered_ios_main(argc: i32, argv: *mut *mut i8) -> i32 {
 /// iOS-specific app runner using native UIKit
 /// On iOS, this is called from Go's ready handler (after UIApplicationMain is already running).
 /// It just registers the callback - the event loop is already running.
+#[cfg(feature = "winit")]
 #[cfg(target_os = "ios")]
 unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
     use crate::platform::ios::{register_callback, set_target_fps};
@@ -3503,13 +6387,18 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
     let fps = if config.target_fps == 0 { 60 } else { config.target_fps };
     set_target_fps(fps);
 
-    println!("[FFI] run_ios_app: registering callback (target FPS: {})", fps);
+    verbose!("[FFI] run_ios_app: registering callback (target FPS: {})", fps);
 
     // Wrap the C callback in a Rust closure that translates events
     let user_data = config.user_data;
     let c_callback = callback;
+    let touch_mouse_emulation = config.touch_mouse_emulation;
+    let mut frame_pacer = crate::platform::frame_pacing::FramePacer::new(config.fixed_timestep_hz);
+    let mut window_state = crate::platform::window_state::WindowState::default();
 
     let rust_callback = move |event: PlatformEvent| -> EventResponse {
+        // Backs the text pointer of an ImePreedit event until the callback returns
+        let preedit_text: CString;
         // Translate PlatformEvent to AppEvent
         let app_event = match event {
             PlatformEvent::Ready { width, height, scale_factor } => AppEvent {
@@ -3517,72 +6406,122 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
                 data1: width,
                 data2: height,
                 scale_factor,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::RedrawRequested => AppEvent {
                 event_type: AppEventType::RedrawRequested,
                 data1: 0.0,
                 data2: 0.0,
                 scale_factor: 1.0,
+                frame_dt: begin_mobile_frame(c_callback, user_data, &mut frame_pacer),
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::Resized { width, height, scale_factor } => AppEvent {
                 event_type: AppEventType::Resized,
                 data1: width,
                 data2: height,
                 scale_factor,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::CloseRequested => AppEvent {
                 event_type: AppEventType::CloseRequested,
                 data1: 0.0,
                 data2: 0.0,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
-            PlatformEvent::TouchBegan { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MousePressed,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchMoved { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseMoved,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchEnded { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseReleased,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchCancelled { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseReleased,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::Resumed => AppEvent {
-                event_type: AppEventType::Resumed,
-                data1: 0.0,
-                data2: 0.0,
-                scale_factor: 1.0,
+            PlatformEvent::TouchBegan { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Began, x, y, 1.0),
+                AppEventType::MousePressed,
+            ),
+            PlatformEvent::TouchMoved { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Moved, x, y, 1.0),
+                AppEventType::MouseMoved,
+            ),
+            PlatformEvent::TouchEnded { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Ended, x, y, 1.0),
+                AppEventType::MouseReleased,
+            ),
+            PlatformEvent::TouchCancelled { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Cancelled, x, y, 1.0),
+                AppEventType::MouseReleased,
+            ),
+            PlatformEvent::Resumed => {
+                dispatch_mobile_window_state(c_callback, user_data, window_state.resumed());
+                AppEvent {
+                    event_type: AppEventType::Resumed,
+                    data1: 0.0,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
             },
-            PlatformEvent::Suspended => AppEvent {
-                event_type: AppEventType::Suspended,
-                data1: 0.0,
-                data2: 0.0,
-                scale_factor: 1.0,
+            PlatformEvent::Suspended => {
+                dispatch_mobile_window_state(c_callback, user_data, window_state.suspended());
+                AppEvent {
+                    event_type: AppEventType::Suspended,
+                    data1: 0.0,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
             },
             PlatformEvent::KeyPressed { keycode, modifiers } => AppEvent {
                 event_type: AppEventType::KeyPressed,
                 data1: keycode as f64,
                 data2: modifiers as f64,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::KeyReleased { keycode, modifiers } => AppEvent {
                 event_type: AppEventType::KeyReleased,
                 data1: keycode as f64,
                 data2: modifiers as f64,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::TextInput { text } => {
                 // Send each character as a CharInput event
@@ -3592,6 +6531,12 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
                         data1: c as u32 as f64,
                         data2: 0.0, // no modifiers for text input
                         scale_factor: 1.0,
+                        frame_dt: 0.0,
+                        render_failures: 0,
+                        scroll_phase: ScrollPhase::None,
+                        touch_phase: TouchPhase::None,
+                        touch_id: 0,
+                        text: ptr::null(),
                     };
                     let mut temp_response = FrameResponse {
                         immediate_commands: std::ptr::null_mut(),
@@ -3601,21 +6546,41 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
                         dark_mode: 2,
                         layers: std::ptr::null_mut(),
                     };
-                    c_callback(&char_event, &mut temp_response, user_data);
+                    dispatch_event(c_callback, &char_event, &mut temp_response, user_data);
                 }
                 return EventResponse::default();
             },
+            PlatformEvent::ImePreedit { text, cursor } => {
+                preedit_text = CString::new(text).unwrap_or_default();
+                ime_event(AppEventType::ImePreedit, &preedit_text, cursor)
+            },
+            PlatformEvent::ImeCommit { text } => {
+                dispatch_ime_commit(c_callback, user_data, &text);
+                return EventResponse::default();
+            },
             PlatformEvent::Scroll { dx, dy } => AppEvent {
                 event_type: AppEventType::MouseWheel,
                 data1: dx,
                 data2: dy,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::KeyboardFrameChanged { height, animation_duration } => AppEvent {
                 event_type: AppEventType::KeyboardFrameChanged,
                 data1: height,
                 data2: animation_duration,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             _ => return EventResponse::default(),
         };
@@ -3630,7 +6595,7 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
             layers: std::ptr::null_mut(),
         };
 
-        c_callback(&app_event, &mut frame_response, user_data);
+        dispatch_event(c_callback, &app_event, &mut frame_response, user_data);
 
         // iOS-specific: Process immediate commands if provided
         // In retained mode, commands are only sent when widgets are dirty
@@ -3662,24 +6627,22 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
                 };
 
                 if !commands.is_empty() {
-                    if let Err(e) = crate::platform::ios::render_frame(&commands) {
+                    let result = crate::platform::ios::render_frame(&commands);
+                    record_render_result(&result);
+                    if let Err(e) = result {
                         eprintln!("[iOS] Render error: {}", e);
                     }
                 }
             }
         }
 
-        EventResponse {
-            request_redraw: frame_response.request_redraw,
-            exit: false, // FrameResponse doesn't have exit
-            redraw_after_ms: frame_response.redraw_after_ms,
-        }
+        mobile_frame_response(&frame_response)
     };
 
     // On iOS, just register the callback - UIApplicationMain is already running
     register_callback(Box::new(rust_callback));
 
-    println!("[FFI] run_ios_app: callback registered, returning");
+    verbose!("[FFI] run_ios_app: callback registered, returning");
 
     // Return 0 - the event loop is already running via UIApplicationMain
     // This function returns immediately on iOS (unlike desktop where it blocks)
@@ -3689,6 +6652,7 @@ unsafe fn run_ios_app(config: &AppConfig, callback: AppCallback) -> i32 {
 /// Android app runner using android-activity
 /// On Android, this is called from Go's ready handler (after the native activity is running).
 /// Similar to iOS - it registers the callback, the event loop is already managed by android-activity.
+#[cfg(feature = "winit")]
 #[cfg(target_os = "android")]
 unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
     use crate::platform::android::{register_callback, set_target_fps};
@@ -3703,8 +6667,13 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
     // Wrap the C callback in a Rust closure that translates events
     let user_data = config.user_data;
     let c_callback = callback;
+    let touch_mouse_emulation = config.touch_mouse_emulation;
+    let mut frame_pacer = crate::platform::frame_pacing::FramePacer::new(config.fixed_timestep_hz);
+    let mut window_state = crate::platform::window_state::WindowState::default();
 
     let rust_callback = move |event: PlatformEvent| -> EventResponse {
+        // Backs the text pointer of an ImePreedit event until the callback returns
+        let preedit_text: CString;
         // Translate PlatformEvent to AppEvent
         let app_event = match event {
             PlatformEvent::Ready { width, height, scale_factor } => AppEvent {
@@ -3712,60 +6681,92 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
                 data1: width,
                 data2: height,
                 scale_factor,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::RedrawRequested => AppEvent {
                 event_type: AppEventType::RedrawRequested,
                 data1: 0.0,
                 data2: 0.0,
                 scale_factor: 1.0,
+                frame_dt: begin_mobile_frame(c_callback, user_data, &mut frame_pacer),
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::Resized { width, height, scale_factor } => AppEvent {
                 event_type: AppEventType::Resized,
                 data1: width,
                 data2: height,
                 scale_factor,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::CloseRequested => AppEvent {
                 event_type: AppEventType::CloseRequested,
                 data1: 0.0,
                 data2: 0.0,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
-            PlatformEvent::TouchBegan { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MousePressed,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchMoved { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseMoved,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchEnded { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseReleased,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
-            PlatformEvent::TouchCancelled { id: _, x, y } => AppEvent {
-                event_type: AppEventType::MouseReleased,
-                data1: x,
-                data2: y,
-                scale_factor: 1.0,
-            },
+            PlatformEvent::TouchBegan { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Began, x, y, 1.0),
+                AppEventType::MousePressed,
+            ),
+            PlatformEvent::TouchMoved { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Moved, x, y, 1.0),
+                AppEventType::MouseMoved,
+            ),
+            PlatformEvent::TouchEnded { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Ended, x, y, 1.0),
+                AppEventType::MouseReleased,
+            ),
+            PlatformEvent::TouchCancelled { id, x, y } => touch_or_emulated_mouse(
+                c_callback, user_data, touch_mouse_emulation,
+                touch_event(id, TouchPhase::Cancelled, x, y, 1.0),
+                AppEventType::MouseReleased,
+            ),
             PlatformEvent::KeyPressed { keycode, modifiers } => AppEvent {
                 event_type: AppEventType::KeyPressed,
                 data1: keycode as f64,
                 data2: modifiers as f64,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::KeyReleased { keycode, modifiers } => AppEvent {
                 event_type: AppEventType::KeyReleased,
                 data1: keycode as f64,
                 data2: modifiers as f64,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::TextInput { text } => {
                 // For text input, we need to return characters through the callback
@@ -3777,6 +6778,12 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
                         data1: c as u32 as f64,
                         data2: 0.0,
                         scale_factor: 1.0,
+                        frame_dt: 0.0,
+                        render_failures: 0,
+                        scroll_phase: ScrollPhase::None,
+                        touch_phase: TouchPhase::None,
+                        touch_id: 0,
+                        text: ptr::null(),
                     };
                     let mut temp_response = FrameResponse {
                         immediate_commands: std::ptr::null_mut(),
@@ -3786,22 +6793,40 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
                         dark_mode: 2,
                         layers: std::ptr::null_mut(),
                     };
-                    c_callback(&char_event, &mut temp_response, user_data);
+                    dispatch_event(c_callback, &char_event, &mut temp_response, user_data);
                 }
                 // Return a placeholder event (the actual char events were already sent)
                 return EventResponse::default();
             },
-            PlatformEvent::Suspended => AppEvent {
-                event_type: AppEventType::Suspended,
-                data1: 0.0,
-                data2: 0.0,
-                scale_factor: 1.0,
+            PlatformEvent::Suspended => {
+                dispatch_mobile_window_state(c_callback, user_data, window_state.suspended());
+                AppEvent {
+                    event_type: AppEventType::Suspended,
+                    data1: 0.0,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
             },
-            PlatformEvent::Resumed => AppEvent {
-                event_type: AppEventType::Resumed,
-                data1: 0.0,
-                data2: 0.0,
-                scale_factor: 1.0,
+            PlatformEvent::Resumed => {
+                dispatch_mobile_window_state(c_callback, user_data, window_state.resumed());
+                AppEvent {
+                    event_type: AppEventType::Resumed,
+                    data1: 0.0,
+                    data2: 0.0,
+                    scale_factor: 1.0,
+                    frame_dt: 0.0,
+                    render_failures: 0,
+                    scroll_phase: ScrollPhase::None,
+                    touch_phase: TouchPhase::None,
+                    touch_id: 0,
+                    text: ptr::null(),
+                }
             },
             PlatformEvent::MemoryWarning => {
                 // No direct equivalent in AppEventType, just log it
@@ -3813,6 +6838,12 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
                 data1: height,
                 data2: animation_duration,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             // Mouse events (desktop) - shouldn't happen on Android but handle anyway
             PlatformEvent::PointerMoved { x, y } => AppEvent {
@@ -3820,24 +6851,56 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
                 data1: x,
                 data2: y,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::PointerPressed { x, y, button: _ } => AppEvent {
                 event_type: AppEventType::MousePressed,
                 data1: x,
                 data2: y,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
             PlatformEvent::PointerReleased { x, y, button: _ } => AppEvent {
                 event_type: AppEventType::MouseReleased,
                 data1: x,
                 data2: y,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
+            },
+            PlatformEvent::ImePreedit { text, cursor } => {
+                preedit_text = CString::new(text).unwrap_or_default();
+                ime_event(AppEventType::ImePreedit, &preedit_text, cursor)
+            },
+            PlatformEvent::ImeCommit { text } => {
+                dispatch_ime_commit(c_callback, user_data, &text);
+                return EventResponse::default();
             },
             PlatformEvent::Scroll { dx, dy } => AppEvent {
                 event_type: AppEventType::MouseWheel,
                 data1: dx,
                 data2: dy,
                 scale_factor: 1.0,
+                frame_dt: 0.0,
+                render_failures: 0,
+                scroll_phase: ScrollPhase::None,
+                touch_phase: TouchPhase::None,
+                touch_id: 0,
+                text: ptr::null(),
             },
         };
 
@@ -3851,7 +6914,7 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
             layers: std::ptr::null_mut(),
         };
 
-        c_callback(&app_event, &mut frame_response, user_data);
+        dispatch_event(c_callback, &app_event, &mut frame_response, user_data);
 
         // Process immediate commands if provided
         if !frame_response.immediate_commands.is_null() {
@@ -3862,17 +6925,15 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
 
             if let Ok(commands) = serde_json::from_str::<Vec<RenderCommand>>(json_str) {
                 // Use thread-local backend for Android (similar to iOS)
-                if let Err(e) = crate::platform::android::render_frame(&commands) {
+                let result = crate::platform::android::render_frame(&commands);
+                record_render_result(&result);
+                if let Err(e) = result {
                     log::error!("[Android] Render error: {}", e);
                 }
             }
         }
 
-        EventResponse {
-            request_redraw: frame_response.request_redraw,
-            exit: false,
-            redraw_after_ms: frame_response.redraw_after_ms,
-        }
+        mobile_frame_response(&frame_response)
     };
 
     // On Android, register the callback - the event loop is already running via android-activity
@@ -3885,6 +6946,7 @@ unsafe fn run_android_app(config: &AppConfig, callback: AppCallback) -> i32 {
 }
 
 /// Desktop app runner using winit
+#[cfg(feature = "winit")]
 #[cfg(not(any(target_os = "ios", target_os = "android")))]
 unsafe fn run_winit_app(config: &AppConfig, callback: AppCallback) -> i32 {
     // Initialize GTK on Linux (required for tray icon menus)
@@ -3902,7 +6964,7 @@ unsafe fn run_winit_app(config: &AppConfig, callback: AppCallback) -> i32 {
         Ok(el) => el,
         Err(e) => {
             eprintln!("Failed to create event loop: {}", e);
-            return -2;
+            return EngineError::OperationFailed.code();
         }
     };
 
@@ -3957,10 +7019,29 @@ unsafe fn run_winit_app(config: &AppConfig, callback: AppCallback) -> i32 {
             enable_maximize: config.enable_maximize,
             target_fps: config.target_fps,
             dark_mode: config.dark_mode,
+            touch_mouse_emulation: config.touch_mouse_emulation,
+            gamma_correct_text: config.gamma_correct_text,
+            subpixel_text: config.subpixel_text,
+            wide_gamut: config.wide_gamut,
+            display_p3_colors: config.display_p3_colors,
+            blur_behind: config.blur_behind,
+            window_opacity: config.window_opacity,
+            fixed_timestep_hz: config.fixed_timestep_hz,
         },
         should_exit: false,
         modifiers: winit::keyboard::ModifiersState::empty(),
         next_redraw_at: None,
+        engine_redraw_at: None,
+        cursor_image: None,
+        custom_cursor: None,
+        cursor_icon: winit::window::CursorIcon::Default,
+        aspect_ratio: None,
+        frame_cap: None,
+        frame_pacer: crate::platform::frame_pacing::FramePacer::new(config.fixed_timestep_hz),
+        pointer_capture: Default::default(),
+        file_drop: Default::default(),
+        window_state: Default::default(),
+        widget_tree: WidgetTree::new(),
         #[cfg(target_os = "linux")]
         mouse_position: (0.0, 0.0),
         #[cfg(target_os = "linux")]
@@ -4019,7 +7100,7 @@ unsafe fn run_winit_app(config: &AppConfig, callback: AppCallback) -> i32 {
     // Run the event loop (blocks until exit)
     if let Err(e) = event_loop.run_app(&mut app) {
         eprintln!("Event loop error: {}", e);
-        return -3;
+        return EngineError::OperationFailed.code();
     }
 
     0
@@ -4027,6 +7108,7 @@ unsafe fn run_winit_app(config: &AppConfig, callback: AppCallback) -> i32 {
 
 /// Request the application to exit
 /// Call this from within the callback to trigger a clean shutdown
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_app_request_exit() {
@@ -4039,7 +7121,8 @@ pub extern "C" fn centered_app_request_exit() {
 /// It wakes up the event loop and triggers a redraw on the next tick.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_app_request_redraw() -> i32 {
@@ -4047,10 +7130,49 @@ pub extern "C" fn centered_app_request_redraw() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::RequestRedraw) {
             Ok(()) => 0,
-            Err(_) => -1, // Event loop closed
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1 // No event loop running
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Change the target frame rate while the app runs, e.g. 30 while idle and
+/// 120 during an animation
+///
+/// On iOS and Android this replaces `AppConfig.target_fps` (0 = the default
+/// 60) and must be called on the main thread, as the frame callback is. On
+/// desktop, continuous redraws
+/// (`request_redraw`) are capped at `fps` from then on; 0 removes the cap so
+/// vsync paces them again. Safe to call from any thread on desktop.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_app_set_target_fps(fps: u32) -> i32 {
+    #[cfg(target_os = "ios")]
+    {
+        crate::platform::ios::set_target_fps(if fps == 0 { 60 } else { fps });
+        0
+    }
+    #[cfg(target_os = "android")]
+    {
+        crate::platform::android::set_target_fps(if fps == 0 { 60 } else { fps });
+        0
+    }
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    {
+        let guard = get_event_loop_proxy().lock().unwrap();
+        if let Some(ref proxy) = *guard {
+            match proxy.send_event(UserEvent::SetTargetFps(fps)) {
+                Ok(()) => 0,
+                Err(_) => EngineError::NoEventLoop.code(),
+            }
+        } else {
+            EngineError::NoEventLoop.code()
+        }
     }
 }
 
@@ -4062,7 +7184,8 @@ pub extern "C" fn centered_app_request_redraw() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_minimize() -> i32 {
@@ -4070,10 +7193,10 @@ pub extern "C" fn centered_window_minimize() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::Minimize) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4081,7 +7204,8 @@ pub extern "C" fn centered_window_minimize() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_toggle_maximize() -> i32 {
@@ -4089,10 +7213,10 @@ pub extern "C" fn centered_window_toggle_maximize() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::ToggleMaximize) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4100,7 +7224,8 @@ pub extern "C" fn centered_window_toggle_maximize() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_enter_fullscreen() -> i32 {
@@ -4108,10 +7233,10 @@ pub extern "C" fn centered_window_enter_fullscreen() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::EnterFullscreen) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4119,7 +7244,8 @@ pub extern "C" fn centered_window_enter_fullscreen() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_exit_fullscreen() -> i32 {
@@ -4127,10 +7253,10 @@ pub extern "C" fn centered_window_exit_fullscreen() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::ExitFullscreen) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4138,7 +7264,8 @@ pub extern "C" fn centered_window_exit_fullscreen() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_toggle_fullscreen() -> i32 {
@@ -4146,10 +7273,10 @@ pub extern "C" fn centered_window_toggle_fullscreen() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::ToggleFullscreen) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4157,7 +7284,8 @@ pub extern "C" fn centered_window_toggle_fullscreen() -> i32 {
 /// Safe to call from any thread.
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_window_close() -> i32 {
@@ -4165,10 +7293,10 @@ pub extern "C" fn centered_window_close() -> i32 {
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::Close) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
     }
 }
 
@@ -4179,30 +7307,396 @@ pub extern "C" fn centered_window_close() -> i32 {
 /// - title must be a valid null-terminated UTF-8 string
 ///
 /// # Returns
-/// 0 on success, -1 if no event loop is running, -2 if title is invalid
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running, or
+/// `EngineError::InvalidArgument` if title is invalid
+#[cfg(feature = "winit")]
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub unsafe extern "C" fn centered_window_set_title(title: *const c_char) -> i32 {
     if title.is_null() {
-        return -2;
+        return EngineError::InvalidArgument.code();
     }
 
     let title_str = match CStr::from_ptr(title).to_str() {
         Ok(s) => s.to_string(),
-        Err(_) => return -2,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     let guard = get_event_loop_proxy().lock().unwrap();
     if let Some(ref proxy) = *guard {
         match proxy.send_event(UserEvent::SetTitle(title_str)) {
             Ok(()) => 0,
-            Err(_) => -1,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Set where the IME candidate window should appear
+/// Safe to call from any thread.
+///
+/// Pass the rect of the text cursor (or the whole preedit span) so the
+/// platform places its candidate list next to it instead of at the window
+/// corner.
+///
+/// # Arguments
+/// * `x`, `y` - Top-left of the area in logical pixels
+/// * `width`, `height` - Size of the area in logical pixels
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_ime_cursor_area(x: f64, y: f64, width: f64, height: f64) -> i32 {
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetImeCursorArea(x, y, width, height)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Set a custom cursor from an RGBA bitmap
+/// Safe to call from any thread.
+///
+/// The bitmap is given at 1x (logical pixels) and is rescaled for the current
+/// monitor's scale factor, and again whenever the window moves to a monitor
+/// with a different DPI. Pass a null `rgba` to restore the default cursor.
+///
+/// # Arguments
+/// * `rgba` - Pixel data, `width * height * 4` bytes (unpremultiplied RGBA), or null
+/// * `width`, `height` - Bitmap size in logical pixels
+/// * `hotspot_x`, `hotspot_y` - Click point within the bitmap
+///
+/// # Safety
+/// - rgba must point to at least `width * height * 4` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running, or
+/// `EngineError::InvalidArgument` if the image is invalid
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_window_set_cursor_image(
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+) -> i32 {
+    let image = if rgba.is_null() {
+        None
+    } else {
+        let len = (width as usize) * (height as usize) * 4;
+        let data = std::slice::from_raw_parts(rgba, len).to_vec();
+        match crate::platform::cursor::CursorImage::new(data, width, height, hotspot_x, hotspot_y) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                eprintln!("Invalid cursor image: {}", e);
+                return EngineError::InvalidArgument.code();
+            }
+        }
+    };
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetCursorImage(image)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Set the cursor shape shown over the window
+/// Safe to call from any thread.
+///
+/// `cursor_kind` is one of: 0 Default, 1 Pointer, 2 Text, 3 Grab,
+/// 4 Grabbing, 5 NotAllowed, 6 Crosshair, 7 Move, 8 Wait, 9 Progress,
+/// 10 Help, 11 EwResize, 12 NsResize, 13 NeswResize, 14 NwseResize,
+/// 15 ColResize, 16 RowResize, 17 ZoomIn, 18 ZoomOut. The shape stays until
+/// changed, except over the window's resize edges. A custom image set with
+/// `centered_window_set_cursor_image` takes precedence until it is cleared.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running, or
+/// `EngineError::InvalidArgument` for an unknown cursor kind
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_cursor(cursor_kind: u32) -> i32 {
+    let Some(kind) = crate::platform::cursor::CursorKind::from_ffi(cursor_kind) else {
+        eprintln!("Unknown cursor kind {}", cursor_kind);
+        return EngineError::InvalidArgument.code();
+    };
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetCursorIcon(kind.to_winit())) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Show or hide the cursor while it is over the window, e.g. for games
+/// Safe to call from any thread.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_cursor_visible(visible: bool) -> i32 {
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetCursorVisible(visible)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Turn pointer capture on or off, so a drag that leaves the window keeps
+/// sending `MouseMoved` and `MouseReleased`. With `auto_release`, capture
+/// turns itself off when the last held mouse button is released.
+/// Safe to call from any thread.
+///
+/// Positions outside the window may be negative or past its size. Where
+/// the system doesn't keep reporting a drag that left the window, the
+/// cursor is followed from the mouse's raw motion, which is approximate.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_pointer_capture(enabled: bool, auto_release: bool) -> i32 {
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetPointerCapture(enabled, auto_release)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Turn blur-behind (vibrancy) on or off for the window
+/// Safe to call from any thread.
+///
+/// The blur shows through wherever the window is transparent, so create it
+/// with `blur_behind` (or `transparent`) set and clear with a translucent
+/// color. Uses an `NSVisualEffectView` on macOS, acrylic (or the older
+/// blur-behind on Windows 10) on Windows, and KDE's blur hint or protocol on
+/// Linux. A no-op where unsupported: GNOME, other window managers, or no
+/// compositor at all.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_blur_behind(enabled: bool) -> i32 {
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetBlurBehind(enabled)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Set the opacity of the whole window, from 0.0 (invisible) to 1.0 (opaque)
+/// Safe to call from any thread.
+///
+/// A no-op on Wayland, and on X11 without a compositor.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running, or
+/// `EngineError::InvalidArgument` if `alpha` is outside 0.0 - 1.0
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_opacity(alpha: f32) -> i32 {
+    if !(0.0..=1.0).contains(&alpha) {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetOpacity(alpha)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Lock the window's content to an aspect ratio while resizing
+/// Safe to call from any thread.
+///
+/// After each interactive resize the window snaps to the nearest size with
+/// the ratio `num:den` that respects its min/max size. Pass `0, 0` to unlock.
+///
+/// # Returns
+/// 0 on success, `EngineError::NoEventLoop` if no event loop is running, or
+/// `EngineError::InvalidArgument` if only one of num/den is 0
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_aspect_ratio(num: u32, den: u32) -> i32 {
+    let ratio = match crate::platform::aspect::AspectRatio::from_ffi(num, den) {
+        Ok(ratio) => ratio,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EngineError::InvalidArgument.code();
+        }
+    };
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetAspectRatio(ratio)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Start a native drag of files or text out of the window
+/// Safe to call from any thread; the session starts on the main thread.
+/// Call while the mouse button is held (e.g. from a drag gesture handler).
+///
+/// Payload JSON: `{"type":"files","paths":["/abs/path"]}` or `{"type":"text","text":"..."}`.
+/// The outcome is delivered to the callback set with `centered_window_set_drag_callback`.
+/// Linux supports X11 only: on Wayland no drag starts and the callback gets
+/// `DragResult::Failed` (-1).
+///
+/// # Safety
+/// - payload_json must be a valid null-terminated UTF-8 string
+///
+/// # Returns
+/// 0 if the drag was queued, `EngineError::NoEventLoop` if no event loop is
+/// running, or `EngineError::InvalidArgument` if the payload is invalid
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_window_begin_drag(payload_json: *const c_char) -> i32 {
+    if payload_json.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let payload = match CStr::from_ptr(payload_json).to_str() {
+        Ok(s) => match crate::platform::drag::DragPayload::from_json(s) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("Invalid drag payload: {}", e);
+                return EngineError::InvalidArgument.code();
+            }
+        },
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::BeginDrag(payload)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
+        }
+    } else {
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Replace the application menu bar with the menus described by `menu_json`
+/// Safe to call from any thread; the menus are installed on the main thread.
+/// Call once the app is running (e.g. on the Ready event), and again with
+/// the whole bar to change it.
+///
+/// JSON: an array of `{"title": "File", "items": [...]}` menus. An item is
+/// `{"id": 1, "title": "Save", "key": "s", "modifiers": ["cmd", "shift"],
+/// "enabled": true, "checked": false}` (only id and title are required;
+/// modifiers default to Cmd when there's a key), `{"separator": true}`, or
+/// `{"title": "Export", "items": [...]}` for a submenu. Ids are non-zero and
+/// unique; clicking an item, or pressing its shortcut, sends a
+/// MenuItemClicked event with the id.
+///
+/// On macOS the first menu is the application menu, titled by the system
+/// with the app's name. A no-op on Windows and Linux for now.
+///
+/// # Safety
+/// - menu_json must be a valid null-terminated UTF-8 string
+///
+/// # Returns
+/// 0 if the menu bar was queued, `EngineError::NoEventLoop` if no event loop
+/// is running, or `EngineError::InvalidArgument` if the JSON is invalid
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_app_set_menu(menu_json: *const c_char) -> i32 {
+    if menu_json.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+
+    let menus = match CStr::from_ptr(menu_json).to_str() {
+        Ok(s) => match crate::platform::menu::menus_from_json(s) {
+            Ok(menus) => menus,
+            Err(e) => {
+                eprintln!("Invalid menu bar: {}", e);
+                return EngineError::InvalidArgument.code();
+            }
+        },
+        Err(_) => return EngineError::InvalidArgument.code(),
+    };
+
+    let guard = get_event_loop_proxy().lock().unwrap();
+    if let Some(ref proxy) = *guard {
+        match proxy.send_event(UserEvent::SetMenuBar(menus)) {
+            Ok(()) => 0,
+            Err(_) => EngineError::NoEventLoop.code(),
         }
     } else {
-        -1
+        EngineError::NoEventLoop.code()
+    }
+}
+
+/// Forward a menu bar click to the event loop, which sends it to Go
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+fn send_menu_item_clicked(id: u32) {
+    if let Some(ref proxy) = *get_event_loop_proxy().lock().unwrap() {
+        let _ = proxy.send_event(UserEvent::MenuItemClicked(id));
     }
 }
 
+/// Set the callback invoked when a drag session started with
+/// `centered_window_begin_drag` ends.
+///
+/// The callback receives -1 = failed to start (e.g. unsupported platform),
+/// 0 = cancelled, 1 = copied, 2 = moved, 3 = linked.
+#[cfg(feature = "winit")]
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_window_set_drag_callback(callback: extern "C" fn(i32)) {
+    crate::platform::drag::set_drag_callback(callback);
+}
+
 // ============================================================================
 // Safe Area Insets FFI
 // ============================================================================
@@ -4255,7 +7749,7 @@ pub extern "C" fn centered_get_safe_area_insets() -> SafeAreaInsetsFFI {
 #[no_mangle]
 pub extern "C" fn centered_get_safe_area_insets_ptr(out: *mut SafeAreaInsetsFFI) -> i32 {
     if out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
     let insets = SAFE_AREA_INSETS.lock().unwrap();
     unsafe {
@@ -4268,6 +7762,7 @@ pub extern "C" fn centered_get_safe_area_insets_ptr(out: *mut SafeAreaInsetsFFI)
 }
 
 /// Internal function to update safe area insets (called from window setup on iOS/Android)
+#[cfg(feature = "winit")]
 fn update_safe_area_insets(top: f32, left: f32, bottom: f32, right: f32) {
     let mut insets = SAFE_AREA_INSETS.lock().unwrap();
     insets.top = top;
@@ -4285,7 +7780,8 @@ fn update_safe_area_insets(top: f32, left: f32, bottom: f32, right: f32) {
 /// Returns:
 /// - 1 if dark mode is enabled
 /// - 0 if light mode is enabled
-/// - -1 if unable to determine (error or unsupported platform)
+/// - `EngineError::OperationFailed` if the setting can't be read, or
+///   `EngineError::Unsupported` on other platforms
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub extern "C" fn centered_system_dark_mode() -> i32 {
@@ -4342,7 +7838,7 @@ pub extern "C" fn centered_system_dark_mode() -> i32 {
             );
 
             if result.is_err() {
-                return -1; // Unable to open registry key
+                return EngineError::OperationFailed.code(); // Unable to open registry key
             }
 
             let mut value: u32 = 1; // Default to light mode
@@ -4361,7 +7857,7 @@ pub extern "C" fn centered_system_dark_mode() -> i32 {
             let _ = RegCloseKey(hkey);
 
             if query_result.is_err() {
-                return -1; // Unable to query registry value
+                return EngineError::OperationFailed.code(); // Unable to query registry value
             }
 
             // AppsUseLightTheme: 0 = dark mode, 1 = light mode
@@ -4388,7 +7884,7 @@ pub extern "C" fn centered_system_dark_mode() -> i32 {
             let trait_collection: *mut objc::runtime::Object =
                 msg_send![class!(UITraitCollection), currentTraitCollection];
             if trait_collection.is_null() {
-                return -1;
+                return EngineError::OperationFailed.code();
             }
             let style: i64 = msg_send![trait_collection, userInterfaceStyle];
             match style {
@@ -4401,7 +7897,7 @@ pub extern "C" fn centered_system_dark_mode() -> i32 {
 
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux", target_os = "ios")))]
     {
-        -1 // Unsupported platform
+        EngineError::Unsupported.code()
     }
 }
 
@@ -4698,6 +8194,181 @@ pub unsafe extern "C" fn centered_clipboard_set(text: *const c_char) {
     }
 }
 
+/// Get the image on the clipboard as RGBA pixels
+///
+/// PNG, TIFF (macOS) and bitmap (Windows `CF_DIB`) clipboard contents are
+/// decoded to straight-alpha RGBA8, rows top to bottom.
+///
+/// # Arguments
+/// * `out_ptr` - Receives the pixels; free them with centered_clipboard_image_free
+/// * `out_len` - Receives the number of bytes (width * height * 4)
+/// * `width_out`, `height_out` - Receive the image size in pixels
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer
+/// - `NotFound`: the clipboard holds no image
+/// - `Unsupported`: no image clipboard on this platform
+/// - `OperationFailed`: the clipboard couldn't be read or the image decoded
+///
+/// # Safety
+/// - out_ptr, out_len, width_out and height_out must be valid for writes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_clipboard_get_image(
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+    width_out: *mut u32,
+    height_out: *mut u32,
+) -> i32 {
+    use crate::platform::clipboard_image::{get_image, ClipboardImageError};
+
+    if out_ptr.is_null() || out_len.is_null() || width_out.is_null() || height_out.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    *out_ptr = ptr::null_mut();
+    *out_len = 0;
+
+    match get_image() {
+        Ok(image) => {
+            let pixels = image.data.into_boxed_slice();
+            *width_out = image.width;
+            *height_out = image.height;
+            *out_len = pixels.len();
+            *out_ptr = Box::into_raw(pixels) as *mut u8;
+            0
+        }
+        Err(ClipboardImageError::NoImage) => EngineError::NotFound.code(),
+        Err(ClipboardImageError::Unsupported) => EngineError::Unsupported.code(),
+        Err(e) => {
+            eprintln!("{}", e);
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
+/// Free pixels returned by centered_clipboard_get_image
+///
+/// # Safety
+/// - ptr and len must be exactly as returned by centered_clipboard_get_image
+/// - ptr must not be used after this call
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_clipboard_image_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// Replace the clipboard contents with an image
+///
+/// # Arguments
+/// * `data_ptr` - Straight-alpha RGBA8 pixels, rows top to bottom
+/// * `data_len` - Must be width * height * 4
+/// * `width`, `height` - Image size in pixels
+///
+/// # Returns
+/// 0 on success, negative error code on failure:
+/// - `InvalidArgument`: null pointer, zero size, or data_len doesn't match
+/// - `Unsupported`: no image clipboard on this platform
+/// - `OperationFailed`: the clipboard couldn't be written
+///
+/// # Safety
+/// - data_ptr must point to valid memory of at least data_len bytes
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_clipboard_set_image(data_ptr: *const u8, data_len: usize, width: u32, height: u32) -> i32 {
+    use crate::platform::clipboard_image::{set_image, ClipboardImageError};
+
+    if data_ptr.is_null() || width == 0 || height == 0 || data_len != width as usize * height as usize * 4 {
+        return EngineError::InvalidArgument.code();
+    }
+    let rgba = std::slice::from_raw_parts(data_ptr, data_len);
+
+    match set_image(rgba, width, height) {
+        Ok(()) => 0,
+        Err(ClipboardImageError::Unsupported) => EngineError::Unsupported.code(),
+        Err(e) => {
+            eprintln!("{}", e);
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
+// ============================================================================
+// Notification FFI
+// ============================================================================
+
+/// Show a system notification
+///
+/// # Arguments
+/// * `title` - Notification title (UTF-8, required)
+/// * `body` - Notification text (UTF-8, null for none)
+/// * `icon_path` - Image file shown with the notification (null for none);
+///   on Windows it must be an .ico file, on Linux it may be an icon theme name
+///
+/// # Returns
+/// The notification's ID (> 0) for centered_dismiss_notification, or a
+/// negative error code:
+/// - `InvalidArgument`: null title or invalid UTF-8
+/// - `Unsupported`: no notifications on this platform
+/// - `OperationFailed`: the notification couldn't be shown (on macOS/iOS
+///   this includes running outside an app bundle)
+///
+/// # Safety
+/// - Non-null pointers must be valid null-terminated C strings
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_show_notification(
+    title: *const c_char,
+    body: *const c_char,
+    icon_path: *const c_char,
+) -> i32 {
+    use crate::platform::notifications::{show, NotificationError};
+
+    if title.is_null() {
+        return EngineError::InvalidArgument.code();
+    }
+    let Ok(title) = CStr::from_ptr(title).to_str() else {
+        return EngineError::InvalidArgument.code();
+    };
+    let body = if body.is_null() { Ok("") } else { CStr::from_ptr(body).to_str() };
+    let icon_path = if icon_path.is_null() { Ok(None) } else { CStr::from_ptr(icon_path).to_str().map(Some) };
+    let (Ok(body), Ok(icon_path)) = (body, icon_path) else {
+        return EngineError::InvalidArgument.code();
+    };
+
+    match show(title, body, icon_path) {
+        Ok(id) => id as i32,
+        Err(NotificationError::Unsupported) => EngineError::Unsupported.code(),
+        Err(e) => {
+            eprintln!("{}", e);
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
+/// Remove a notification shown by centered_show_notification
+///
+/// Dismissing a notification that is already gone succeeds.
+///
+/// # Returns
+/// 0 on success, or `Unsupported` / `OperationFailed`
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_dismiss_notification(id: u32) -> i32 {
+    use crate::platform::notifications::{dismiss, NotificationError};
+
+    match dismiss(id) {
+        Ok(()) => 0,
+        Err(NotificationError::Unsupported) => EngineError::Unsupported.code(),
+        Err(e) => {
+            eprintln!("{}", e);
+            EngineError::OperationFailed.code()
+        }
+    }
+}
+
 // ============================================================================
 // Keyboard FFI
 // ============================================================================
@@ -4915,7 +8586,8 @@ pub extern "C" fn centered_get_natural_scrolling() -> i32 {
 ///
 /// # Returns
 /// Pointer to a JSON string containing an array of selected paths, or null on cancel/error.
-/// Caller must free with `centered_file_dialog_result_free`.
+/// Caller must free with `centered_file_dialog_result_free`. Always null on
+/// Android, where pickers can't block; use `centered_file_dialog_open_async`.
 ///
 /// # Safety
 /// - All string parameters must be null-terminated UTF-8 strings or null
@@ -5107,7 +8779,38 @@ pub unsafe extern "C" fn centered_file_dialog_open(
         }
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    #[cfg(target_os = "windows")]
+    {
+        let string_arg = |s: *const c_char| if s.is_null() {
+            None
+        } else {
+            CStr::from_ptr(s).to_str().ok().filter(|s| !s.is_empty())
+        };
+        let exts: Vec<&str> = string_arg(filters)
+            .map(|f| f.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        match crate::platform::windows::open_files_dialog(string_arg(title), string_arg(directory), &exts, multiple != 0) {
+            Some(paths) => {
+                let path_strings: Vec<String> = paths.iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+
+                match serde_json::to_string(&path_strings) {
+                    Ok(json) => {
+                        match CString::new(json) {
+                            Ok(cstring) => cstring.into_raw(),
+                            Err(_) => ptr::null_mut(),
+                        }
+                    }
+                    Err(_) => ptr::null_mut(),
+                }
+            }
+            None => ptr::null_mut(),
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
         let _ = (title, directory, filters, multiple);
         ptr::null_mut()
@@ -5123,7 +8826,8 @@ pub unsafe extern "C" fn centered_file_dialog_open(
 ///
 /// # Returns
 /// Pointer to the selected path as a null-terminated string, or null on cancel/error.
-/// Caller must free with `centered_file_dialog_result_free`.
+/// Caller must free with `centered_file_dialog_result_free`. Always null on
+/// Android, where pickers can't block; use `centered_file_dialog_save_async`.
 ///
 /// # Safety
 /// - All string parameters must be null-terminated UTF-8 strings or null
@@ -5270,13 +8974,151 @@ pub unsafe extern "C" fn centered_file_dialog_save(
         }
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    #[cfg(target_os = "windows")]
+    {
+        let string_arg = |s: *const c_char| if s.is_null() {
+            None
+        } else {
+            CStr::from_ptr(s).to_str().ok().filter(|s| !s.is_empty())
+        };
+        let exts: Vec<&str> = string_arg(filters)
+            .map(|f| f.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        match crate::platform::windows::save_file_dialog(string_arg(title), string_arg(directory), &exts) {
+            Some(path) => {
+                let path_str = path.to_string_lossy().to_string();
+                match CString::new(path_str) {
+                    Ok(cstring) => cstring.into_raw(),
+                    Err(_) => ptr::null_mut(),
+                }
+            }
+            None => ptr::null_mut(),
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
         let _ = (title, directory, filters);
         ptr::null_mut()
     }
 }
 
+/// Receives the result of an async file dialog: the `request_id` it was
+/// opened with, and the result the synchronous call would have returned
+/// (null on cancel/error). A non-null result must be freed with
+/// `centered_file_dialog_result_free`.
+pub type FileDialogCallback = extern "C" fn(request_id: u32, result: *mut c_char);
+
+/// Open a file dialog without blocking for its result
+///
+/// Takes the same arguments as `centered_file_dialog_open` and passes its
+/// result (a JSON array) to `callback`. On Android this shows the Storage
+/// Access Framework picker and the result holds content URIs; `directory`
+/// may be a content URI to start in, and `title` is unused. `callback` runs
+/// on the main thread after the picker closes. On other platforms the dialog
+/// is modal and `callback` runs before this returns.
+///
+/// # Returns
+/// 0 if the dialog was shown, `EngineError::OperationFailed` if it couldn't
+/// be (`callback` is not called then)
+///
+/// # Safety
+/// - All string parameters must be null-terminated UTF-8 strings or null
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_file_dialog_open_async(
+    title: *const c_char,
+    directory: *const c_char,
+    filters: *const c_char,
+    multiple: i32,
+    request_id: u32,
+    callback: FileDialogCallback,
+) -> i32 {
+    #[cfg(target_os = "android")]
+    {
+        let _ = title;
+        let string_arg = |s: *const c_char| if s.is_null() {
+            None
+        } else {
+            CStr::from_ptr(s).to_str().ok().filter(|s| !s.is_empty())
+        };
+        let handler = Box::new(move |uris: Option<Vec<String>>| {
+            let result = uris
+                .filter(|uris| !uris.is_empty())
+                .and_then(|uris| serde_json::to_string(&uris).ok())
+                .and_then(|json| CString::new(json).ok())
+                .map_or(ptr::null_mut(), CString::into_raw);
+            callback(request_id, result);
+        });
+
+        let extensions = string_arg(filters).unwrap_or("");
+        if crate::platform::android::open_document(request_id, extensions, multiple != 0, string_arg(directory), handler) {
+            0
+        } else {
+            EngineError::OperationFailed.code()
+        }
+    }
+
+    #[cfg(not(target_os = "android"))]
+    {
+        callback(request_id, centered_file_dialog_open(title, directory, filters, multiple));
+        0
+    }
+}
+
+/// Open a save file dialog without blocking for its result
+///
+/// Like `centered_file_dialog_open_async`, for `centered_file_dialog_save`:
+/// `callback` gets the chosen path, or on Android the content URI of the
+/// newly created document.
+///
+/// # Returns
+/// 0 if the dialog was shown, `EngineError::OperationFailed` if it couldn't
+/// be (`callback` is not called then)
+///
+/// # Safety
+/// - All string parameters must be null-terminated UTF-8 strings or null
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_file_dialog_save_async(
+    title: *const c_char,
+    directory: *const c_char,
+    filters: *const c_char,
+    request_id: u32,
+    callback: FileDialogCallback,
+) -> i32 {
+    #[cfg(target_os = "android")]
+    {
+        let _ = title;
+        let string_arg = |s: *const c_char| if s.is_null() {
+            None
+        } else {
+            CStr::from_ptr(s).to_str().ok().filter(|s| !s.is_empty())
+        };
+        let handler = Box::new(move |uris: Option<Vec<String>>| {
+            let result = uris
+                .and_then(|uris| uris.into_iter().next())
+                .and_then(|uri| CString::new(uri).ok())
+                .map_or(ptr::null_mut(), CString::into_raw);
+            callback(request_id, result);
+        });
+
+        let extensions = string_arg(filters).unwrap_or("");
+        if crate::platform::android::create_document(request_id, extensions, string_arg(directory), handler) {
+            0
+        } else {
+            EngineError::OperationFailed.code()
+        }
+    }
+
+    #[cfg(not(target_os = "android"))]
+    {
+        callback(request_id, centered_file_dialog_save(title, directory, filters));
+        0
+    }
+}
+
 /// Free a file dialog result
 ///
 /// # Safety
@@ -5296,8 +9138,9 @@ pub unsafe extern "C" fn centered_file_dialog_result_free(result: *mut c_char) {
 
 #[cfg(target_os = "macos")]
 mod tray_icon {
+    use crate::error::EngineError;
     use cocoa::base::{id, nil, BOOL, YES, NO};
-    use cocoa::foundation::NSString;
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
     use objc::runtime::{Class, Object, Sel};
     use objc::{class, msg_send, sel, sel_impl};
     use std::sync::Mutex;
@@ -5333,7 +9176,7 @@ mod tray_icon {
     pub fn create() -> i32 {
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         if guard.is_some() {
@@ -5344,13 +9187,13 @@ mod tray_icon {
             // Get system status bar
             let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
             if status_bar.is_null() {
-                return -1;
+                return EngineError::OperationFailed.code();
             }
 
             // Create status item with variable length (-1.0)
             let status_item: id = msg_send![status_bar, statusItemWithLength: -1.0f64];
             if status_item.is_null() {
-                return -2;
+                return EngineError::OperationFailed.code();
             }
 
             // Retain the status item
@@ -5359,7 +9202,7 @@ mod tray_icon {
             // Set default title
             let button: id = msg_send![status_item, button];
             if !button.is_null() {
-                let default_title = NSString::alloc(nil).init_str("App");
+                let default_title = NSString::alloc(nil).init_str("App").autorelease();
                 let _: () = msg_send![button, setTitle: default_title];
             }
 
@@ -5398,39 +9241,39 @@ mod tray_icon {
     /// Set icon from file path
     pub unsafe fn set_icon_file(path: *const c_char) -> i32 {
         if path.is_null() {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let path_str = match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return -3,
+            Err(_) => return EngineError::InvalidArgument.code(),
         };
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         if state.status_item.is_null() {
-            return -1;
+            return EngineError::NotInitialized.code();
         }
 
         let button: id = msg_send![state.status_item, button];
         if button.is_null() {
-            return -2;
+            return EngineError::OperationFailed.code();
         }
 
-        let ns_path = NSString::alloc(nil).init_str(path_str);
+        let ns_path = NSString::alloc(nil).init_str(path_str).autorelease();
         let image: id = msg_send![class!(NSImage), alloc];
         let image: id = msg_send![image, initWithContentsOfFile: ns_path];
 
         if image.is_null() {
-            return -3;
+            return EngineError::OperationFailed.code();
         }
 
         // Set template mode for dark/light mode support
@@ -5448,7 +9291,7 @@ mod tray_icon {
         let _: () = msg_send![button, setImage: image];
 
         // Clear title when we have an icon
-        let empty = NSString::alloc(nil).init_str("");
+        let empty = NSString::alloc(nil).init_str("").autorelease();
         let _: () = msg_send![button, setTitle: empty];
 
         0
@@ -5457,32 +9300,32 @@ mod tray_icon {
     /// Set icon from raw data
     pub unsafe fn set_icon_data(data: *const u8, length: usize) -> i32 {
         if data.is_null() || length == 0 {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         if state.status_item.is_null() {
-            return -1;
+            return EngineError::NotInitialized.code();
         }
 
         let button: id = msg_send![state.status_item, button];
         if button.is_null() {
-            return -2;
+            return EngineError::OperationFailed.code();
         }
 
         // Create NSData from bytes
         let ns_data: id = msg_send![class!(NSData), dataWithBytes: data length: length];
         if ns_data.is_null() {
-            return -3;
+            return EngineError::OperationFailed.code();
         }
 
         // Create NSImage from data
@@ -5490,7 +9333,7 @@ mod tray_icon {
         let image: id = msg_send![image, initWithData: ns_data];
 
         if image.is_null() {
-            return -3;
+            return EngineError::OperationFailed.code();
         }
 
         // Set template mode
@@ -5508,7 +9351,7 @@ mod tray_icon {
         let _: () = msg_send![button, setImage: image];
 
         // Clear title
-        let empty = NSString::alloc(nil).init_str("");
+        let empty = NSString::alloc(nil).init_str("").autorelease();
         let _: () = msg_send![button, setTitle: empty];
 
         0
@@ -5544,7 +9387,7 @@ mod tray_icon {
             return;
         }
 
-        let ns_tooltip = NSString::alloc(nil).init_str(tooltip_str);
+        let ns_tooltip = NSString::alloc(nil).init_str(tooltip_str).autorelease();
         let _: () = msg_send![button, setToolTip: ns_tooltip];
     }
 
@@ -5578,7 +9421,7 @@ mod tray_icon {
             return;
         }
 
-        let ns_title = NSString::alloc(nil).init_str(title_str);
+        let ns_title = NSString::alloc(nil).init_str(title_str).autorelease();
         let _: () = msg_send![button, setTitle: ns_title];
     }
 
@@ -5605,8 +9448,7 @@ mod tray_icon {
     fn ensure_menu(state: &mut TrayState) {
         if state.menu.is_null() {
             unsafe {
-                let menu: id = msg_send![class!(NSMenu), alloc];
-                let menu: id = msg_send![menu, init];
+                let menu = crate::platform::menu::macos::new_menu("");
                 state.menu = menu;
 
                 if !state.status_item.is_null() {
@@ -5625,20 +9467,18 @@ mod tray_icon {
     ) -> i32 {
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         ensure_menu(state);
 
-        let menu_item: id;
-
-        if is_separator != 0 {
-            menu_item = msg_send![class!(NSMenuItem), separatorItem];
+        let menu_item: id = if is_separator != 0 {
+            crate::platform::menu::macos::new_separator()
         } else {
             let label_str = if label.is_null() {
                 ""
@@ -5646,20 +9486,10 @@ mod tray_icon {
                 CStr::from_ptr(label).to_str().unwrap_or("")
             };
 
-            let ns_label = NSString::alloc(nil).init_str(label_str);
-            let key_equiv = NSString::alloc(nil).init_str("");
-
-            menu_item = msg_send![class!(NSMenuItem), alloc];
             // Note: Without action handler, menu items won't trigger callbacks
             // For now, we create items without actions (callbacks not yet implemented in Rust)
-            let menu_item: id = msg_send![menu_item, initWithTitle: ns_label action: nil keyEquivalent: key_equiv];
-
-            let _: () = msg_send![menu_item, setEnabled: if enabled != 0 { YES } else { NO }];
-
-            if checked != 0 {
-                let _: () = msg_send![menu_item, setState: 1i64]; // NSControlStateValueOn
-            }
-        }
+            crate::platform::menu::macos::new_item(label_str, "", enabled != 0, checked != 0)
+        };
 
         // Get current count for index
         let count: i64 = msg_send![state.menu, numberOfItems];
@@ -5669,8 +9499,9 @@ mod tray_icon {
             let _: () = msg_send![menu_item, setTag: count];
         }
 
-        // Add to menu
+        // Add to menu, which keeps the item
         let _: () = msg_send![state.menu, addItem: menu_item];
+        let _: () = msg_send![menu_item, release];
 
         count as i32
     }
@@ -5747,7 +9578,7 @@ mod tray_icon {
 
         let menu_item: id = msg_send![state.menu, itemAtIndex: index as i64];
         if !menu_item.is_null() {
-            let ns_label = NSString::alloc(nil).init_str(label_str);
+            let ns_label = NSString::alloc(nil).init_str(label_str).autorelease();
             let _: () = msg_send![menu_item, setTitle: ns_label];
         }
     }
@@ -5819,6 +9650,7 @@ mod tray_icon {
 
 #[cfg(target_os = "windows")]
 mod tray_icon {
+    use crate::error::EngineError;
     use std::ffi::CStr;
     use std::os::raw::c_char;
     use std::sync::Mutex;
@@ -5971,7 +9803,7 @@ mod tray_icon {
 
         match hwnd {
             Ok(h) if h != HWND::default() => Ok(h),
-            _ => Err(-1),
+            _ => Err(EngineError::OperationFailed.code()),
         }
     }
 
@@ -5979,7 +9811,7 @@ mod tray_icon {
     pub fn create() -> i32 {
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         if guard.is_some() {
@@ -6012,7 +9844,7 @@ mod tray_icon {
 
             if !Shell_NotifyIconW(NIM_ADD, &nid).as_bool() {
                 let _ = DestroyWindow(hwnd);
-                return -2;
+                return EngineError::OperationFailed.code();
             }
 
             // Set version for modern behavior
@@ -6062,7 +9894,7 @@ mod tray_icon {
     /// Create HICON from RGBA data
     unsafe fn create_icon_from_rgba(rgba: &[u8], width: u32, height: u32) -> Result<HICON, i32> {
         if rgba.len() != (width * height * 4) as usize {
-            return Err(-3);
+            return Err(EngineError::InvalidArgument.code());
         }
 
         let mut bmi = BITMAPINFO {
@@ -6092,7 +9924,7 @@ mod tray_icon {
             Ok(bmp) if !bmp.is_invalid() && !bits_ptr.is_null() => bmp,
             _ => {
                 ReleaseDC(None, hdc);
-                return Err(-3);
+                return Err(EngineError::OperationFailed.code());
             }
         };
 
@@ -6109,7 +9941,7 @@ mod tray_icon {
         if mask_bitmap.is_invalid() {
             let _ = DeleteObject(color_bitmap);
             ReleaseDC(None, hdc);
-            return Err(-3);
+            return Err(EngineError::OperationFailed.code());
         }
 
         let icon_info = ICONINFO {
@@ -6126,24 +9958,24 @@ mod tray_icon {
         let _ = DeleteObject(mask_bitmap);
         ReleaseDC(None, hdc);
 
-        hicon.map_err(|_| -3)
+        hicon.map_err(|_| EngineError::OperationFailed.code())
     }
 
     /// Set icon from file path
     pub unsafe fn set_icon_file(path: *const c_char) -> i32 {
         if path.is_null() {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let path_str = match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return -3,
+            Err(_) => return EngineError::InvalidArgument.code(),
         };
 
         // Load image using the image crate
         let img = match image::open(path_str) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let rgba = img.to_rgba8();
@@ -6157,12 +9989,12 @@ mod tray_icon {
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         // Update the icon
@@ -6176,7 +10008,7 @@ mod tray_icon {
         nid.hIcon = hicon;
 
         if !Shell_NotifyIconW(NIM_MODIFY, &nid).as_bool() {
-            return -2;
+            return EngineError::OperationFailed.code();
         }
 
         0
@@ -6185,7 +10017,7 @@ mod tray_icon {
     /// Set icon from raw image data (PNG/JPEG bytes)
     pub unsafe fn set_icon_data(data: *const u8, length: usize) -> i32 {
         if data.is_null() || length == 0 {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let bytes = std::slice::from_raw_parts(data, length);
@@ -6193,7 +10025,7 @@ mod tray_icon {
         // Decode image using the image crate
         let img = match image::load_from_memory(bytes) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let rgba = img.to_rgba8();
@@ -6206,12 +10038,12 @@ mod tray_icon {
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         state.hicon = hicon;
@@ -6224,7 +10056,7 @@ mod tray_icon {
         nid.hIcon = hicon;
 
         if !Shell_NotifyIconW(NIM_MODIFY, &nid).as_bool() {
-            return -2;
+            return EngineError::OperationFailed.code();
         }
 
         0
@@ -6343,12 +10175,12 @@ mod tray_icon {
     ) -> i32 {
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         let label_str = if is_separator != 0 || label.is_null() {
@@ -6504,6 +10336,7 @@ mod tray_icon {
 
 #[cfg(target_os = "linux")]
 mod tray_icon {
+    use crate::error::EngineError;
     use std::sync::Mutex;
     use std::os::raw::c_char;
     use std::ffi::CStr;
@@ -6549,7 +10382,7 @@ mod tray_icon {
             Ok(g) => g,
             Err(e) => {
                 eprintln!("[Rust] Failed to lock TRAY_STATE: {:?}", e);
-                return -1;
+                return EngineError::OperationFailed.code();
             }
         };
 
@@ -6567,7 +10400,7 @@ mod tray_icon {
             },
             None => {
                 eprintln!("[Rust] Failed to create default tray icon");
-                return -3;
+                return EngineError::OperationFailed.code();
             }
         };
 
@@ -6584,7 +10417,7 @@ mod tray_icon {
             },
             Err(e) => {
                 eprintln!("[Rust] Failed to create tray icon: {}", e);
-                return -2;
+                return EngineError::OperationFailed.code();
             }
         };
 
@@ -6614,33 +10447,33 @@ mod tray_icon {
     /// Set icon from file path
     pub unsafe fn set_icon_file(path: *const c_char) -> i32 {
         if path.is_null() {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let path_str = match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return -3,
+            Err(_) => return EngineError::InvalidArgument.code(),
         };
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         let tray = match state.tray.as_ref() {
             Some(t) => t,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         // Load image and convert to icon
         let img = match image::open(path_str) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let rgba = img.to_rgba8();
@@ -6648,11 +10481,11 @@ mod tray_icon {
 
         let icon = match tray_icon::Icon::from_rgba(rgba.into_raw(), width, height) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         if tray.set_icon(Some(icon)).is_err() {
-            return -4;
+            return EngineError::OperationFailed.code();
         }
 
         0
@@ -6661,29 +10494,29 @@ mod tray_icon {
     /// Set icon from raw data (PNG encoded)
     pub unsafe fn set_icon_data(data: *const u8, length: usize) -> i32 {
         if data.is_null() || length == 0 {
-            return -3;
+            return EngineError::InvalidArgument.code();
         }
 
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         let tray = match state.tray.as_ref() {
             Some(t) => t,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         // Load image from bytes
         let bytes = std::slice::from_raw_parts(data, length);
         let img = match image::load_from_memory(bytes) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let rgba = img.to_rgba8();
@@ -6691,11 +10524,11 @@ mod tray_icon {
 
         let icon = match tray_icon::Icon::from_rgba(rgba.into_raw(), width, height) {
             Ok(i) => i,
-            Err(_) => return -3,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         if tray.set_icon(Some(icon)).is_err() {
-            return -4;
+            return EngineError::OperationFailed.code();
         }
 
         0
@@ -6762,12 +10595,12 @@ mod tray_icon {
     ) -> i32 {
         let mut guard = match TRAY_STATE.lock() {
             Ok(g) => g,
-            Err(_) => return -1,
+            Err(_) => return EngineError::OperationFailed.code(),
         };
 
         let state = match guard.as_mut() {
             Some(s) => s,
-            None => return -1,
+            None => return EngineError::NotInitialized.code(),
         };
 
         // Create menu if it doesn't exist
@@ -6916,6 +10749,7 @@ mod tray_icon {
 
     /// Process pending menu events
     /// Should be called from the event loop to handle menu item clicks
+    #[cfg(feature = "winit")]
     pub fn process_events() {
         use tray_icon::menu::MenuEvent;
 
@@ -6981,7 +10815,7 @@ pub extern "C" fn centered_tray_icon_create() -> i32 {
     }
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
-        -1
+        EngineError::Unsupported.code()
     }
 }
 
@@ -7006,7 +10840,7 @@ pub unsafe extern "C" fn centered_tray_icon_set_icon_file(path: *const c_char) -
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         let _ = path;
-        -1
+        EngineError::Unsupported.code()
     }
 }
 
@@ -7021,7 +10855,7 @@ pub unsafe extern "C" fn centered_tray_icon_set_icon_data(data: *const u8, lengt
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         let _ = (data, length);
-        -1
+        EngineError::Unsupported.code()
     }
 }
 
@@ -7079,7 +10913,7 @@ pub unsafe extern "C" fn centered_tray_icon_add_menu_item(
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         let _ = (label, enabled, checked, is_separator);
-        -1
+        EngineError::Unsupported.code()
     }
 }
 
@@ -7171,13 +11005,112 @@ pub extern "C" fn centered_tray_icon_set_callback(callback: extern "C" fn(i32))
 // Text Measurement FFI
 // ============================================================================
 
-use crate::text::font_manager::FontManager;
-
-/// Global font manager for text measurement
-static FONT_MANAGER: OnceLock<Mutex<FontManager>> = OnceLock::new();
+use crate::text::font_manager::{shared_font_manager, FontManager};
 
+/// Global font manager for text measurement (shared with `text::resolve_font`)
 fn get_font_manager() -> &'static Mutex<FontManager> {
-    FONT_MANAGER.get_or_init(|| Mutex::new(FontManager::new()))
+    shared_font_manager()
+}
+
+/// Register font bytes (TTF/OTF) for text rendering
+///
+/// The font is used for the family `name` wherever it is requested, and can
+/// be referenced directly as a memory font with the returned hash. Identical
+/// bytes are stored once and return the same hash; each registration must be
+/// balanced by a `centered_unregister_font`.
+///
+/// # Arguments
+/// * `name_ptr` / `name_len` - Family name (UTF-8, not null-terminated)
+/// * `data_ptr` / `data_len` - Font file contents
+///
+/// # Returns
+/// Nonzero data hash on success, 0 on a null pointer, empty data, or a
+/// name that isn't UTF-8
+///
+/// # Safety
+/// - name_ptr must point to valid memory of at least name_len bytes
+/// - data_ptr must point to valid memory of at least data_len bytes
+/// - The data is copied, so the caller can free data_ptr after this returns
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_register_font_memory(
+    name_ptr: *const u8,
+    name_len: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u64 {
+    if name_ptr.is_null() || data_ptr.is_null() || data_len == 0 {
+        return 0;
+    }
+    let Ok(name) = std::str::from_utf8(std::slice::from_raw_parts(name_ptr, name_len)) else {
+        return 0;
+    };
+    let data = std::slice::from_raw_parts(data_ptr, data_len).to_vec();
+
+    let hash = get_font_manager().lock().unwrap().register_font_data(name, data);
+    // Text already drawn in this family's fallback is re-rasterized
+    centered_engine_invalidate_caches(CACHE_TEXT | CACHE_GLYPH_ATLAS);
+    hash
+}
+
+/// Drop a registration made by `centered_register_font_memory`
+///
+/// The bytes are freed with the last registration of the same data, after
+/// which text in that family falls back as if it had never been registered.
+///
+/// Returns 0 on success, `EngineError::InvalidArgument` if `hash` isn't registered
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_unregister_font(hash: u64) -> i32 {
+    if !get_font_manager().lock().unwrap().unregister_font_data(hash) {
+        return EngineError::InvalidArgument.code();
+    }
+    centered_engine_invalidate_caches(CACHE_TEXT | CACHE_GLYPH_ATLAS)
+}
+
+/// List the installed system font families
+///
+/// Enumeration is slow, so the list is built on the first call and cached
+/// for the life of the process.
+///
+/// # Returns
+/// A JSON array of `{"family", "weights", "has_italic"}` families sorted by
+/// name, with weights on the CSS scale (100-900). Caller must free with
+/// centered_free_string. Null if the list can't be serialized.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_list_fonts() -> *mut c_char {
+    let json = match serde_json::to_string(&crate::text::list_system_fonts()) {
+        Ok(json) => json,
+        Err(_) => return ptr::null_mut(),
+    };
+    match CString::new(json) {
+        Ok(c_str) => c_str.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Get the accessibility tree built from the retained widget tree
+///
+/// The tree is the one published after the last widget delta: each visible
+/// widget as `{"id", "role", "label", "value", "bounds", "focused",
+/// "disabled", "children"}`, with ids in the form widget deltas use.
+///
+/// # Returns
+/// A JSON object `{"root": node}`, with a null root in immediate mode.
+/// Caller must free with centered_free_string. Null if the tree can't be
+/// serialized.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_accessibility_tree() -> *mut c_char {
+    let json = match serde_json::to_string(&crate::widget::accessibility::current_tree()) {
+        Ok(json) => json,
+        Err(_) => return ptr::null_mut(),
+    };
+    match CString::new(json) {
+        Ok(c_str) => c_str.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 /// Get the current backend scale factor (for HiDPI displays)
@@ -7209,6 +11142,18 @@ pub struct TextMeasurement {
     pub ascent: f32,
     /// Font descent (distance from baseline to bottom, positive value)
     pub descent: f32,
+    /// Font line gap (extra spacing the font recommends between lines)
+    pub line_gap: f32,
+}
+
+/// Line gap of a font at its descriptor's size, or 0 if it can't be loaded
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+#[cfg(not(target_arch = "wasm32"))]
+fn font_line_gap(descriptor: &FontDescriptor) -> f32 {
+    let Ok(mut manager) = get_font_manager().lock() else {
+        return 0.0;
+    };
+    manager.load_font(descriptor).map_or(0.0, |font| font.line_gap())
 }
 
 /// Measure text dimensions with a specific font
@@ -7242,6 +11187,7 @@ pub unsafe extern "C" fn centered_measure_text(
         height: 0.0,
         ascent: 0.0,
         descent: 0.0,
+        line_gap: 0.0,
     };
 
     if text.is_null() || font_name.is_null() {
@@ -7279,6 +11225,7 @@ pub unsafe extern "C" fn centered_measure_text(
                 height,
                 ascent,
                 descent,
+                line_gap: font.line_gap(),
             }
         }
         Err(e) => {
@@ -7311,6 +11258,7 @@ pub unsafe extern "C" fn centered_measure_text(
             height: ascent + descent,
             ascent,
             descent,
+            line_gap: 0.0,
         }
     };
 
@@ -7320,6 +11268,7 @@ pub unsafe extern "C" fn centered_measure_text(
             height: font_size,
             ascent: font_size * 0.8,
             descent: font_size * 0.2,
+            line_gap: 0.0,
         };
     }
 
@@ -7357,6 +11306,7 @@ pub unsafe extern "C" fn centered_measure_text(
         height,
         ascent,
         descent,
+        line_gap: 0.0,
     }
 }
 
@@ -7372,7 +11322,7 @@ pub unsafe extern "C" fn centered_measure_text(
 /// * `out` - Pointer to TextMeasurement struct to write result into
 ///
 /// # Returns
-/// 0 on success, -1 on error
+/// 0 on success, `EngineError::InvalidArgument` if `out` is null
 ///
 /// # Safety
 /// - text must be a valid null-terminated UTF-8 string
@@ -7387,7 +11337,7 @@ pub unsafe extern "C" fn centered_measure_text_ptr(
     out: *mut TextMeasurement,
 ) -> i32 {
     if out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let result = centered_measure_text(text, font_name, font_size);
@@ -7606,6 +11556,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
         height: 0.0,
         ascent: 0.0,
         descent: 0.0,
+        line_gap: 0.0,
     };
 
     if text.is_null() || font_json.is_null() {
@@ -7677,6 +11628,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
                 height: height / scale_factor,
                 ascent: ascent / scale_factor,
                 descent: descent / scale_factor,
+                line_gap: font.line_gap() / scale_factor,
             }
         }
         Err(e) => {
@@ -7702,7 +11654,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font_ptr(
     out: *mut TextMeasurement,
 ) -> i32 {
     if out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let result = centered_measure_text_metrics_with_font(text, font_json);
@@ -7729,6 +11681,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
         height: 0.0,
         ascent: 0.0,
         descent: 0.0,
+        line_gap: 0.0,
     };
 
     if text.is_null() || font_json.is_null() {
@@ -7798,6 +11751,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
             height: height / scale_factor,
             ascent: ascent / scale_factor,
             descent: descent / scale_factor,
+            line_gap: font_line_gap(&scaled_descriptor) / scale_factor,
         }
     } else {
         error_result
@@ -7819,7 +11773,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font_ptr(
     out: *mut TextMeasurement,
 ) -> i32 {
     if out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let result = centered_measure_text_metrics_with_font(text, font_json);
@@ -8056,6 +12010,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
         height: 0.0,
         ascent: 0.0,
         descent: 0.0,
+        line_gap: 0.0,
     };
 
     if text.is_null() || font_json.is_null() {
@@ -8124,6 +12079,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font(
         height: height / scale_factor,
         ascent: ascent / scale_factor,
         descent: descent / scale_factor,
+        line_gap: font_line_gap(&scaled_descriptor) / scale_factor,
     }
 }
 
@@ -8142,7 +12098,7 @@ pub unsafe extern "C" fn centered_measure_text_metrics_with_font_ptr(
     out: *mut TextMeasurement,
 ) -> i32 {
     if out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let result = centered_measure_text_metrics_with_font(text, font_json);
@@ -8273,6 +12229,112 @@ pub unsafe extern "C" fn centered_measure_text_with_font(
     width / scale_factor
 }
 
+/// Detect the base direction of a string (first-strong-character rule)
+///
+/// Useful for choosing paragraph direction and alignment before laying out
+/// user content.
+///
+/// # Arguments
+/// * `text` - The text to inspect (null-terminated UTF-8)
+///
+/// # Returns
+/// 0 = LTR, 1 = RTL. Strings without a strong character (e.g. digits only),
+/// null pointers and invalid UTF-8 return 0 (LTR).
+///
+/// # Safety
+/// - text must be a valid null-terminated UTF-8 string or null
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_text_base_direction(text: *const c_char) -> i32 {
+    if text.is_null() {
+        return 0;
+    }
+
+    match CStr::from_ptr(text).to_str() {
+        Ok(s) => crate::text::base_direction(s) as i32,
+        Err(_) => 0,
+    }
+}
+
+/// Move a text caret by whole grapheme clusters
+///
+/// Text buffers that index by code point use this for arrow keys, selection
+/// and backspace so an emoji ZWJ sequence (family, skin tone, flag) is
+/// treated as a single character.
+///
+/// # Arguments
+/// * `text` - The buffer contents (null-terminated UTF-8)
+/// * `char_index` - Current caret position in chars (code points)
+/// * `delta` - Clusters to move; negative moves backward, 0 snaps a caret
+///   inside a cluster to the cluster start
+///
+/// # Returns
+/// The new caret position in chars, or `EngineError::InvalidArgument` for
+/// null/invalid input.
+///
+/// # Safety
+/// - text must be a valid null-terminated UTF-8 string
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_text_move_caret(text: *const c_char, char_index: i32, delta: i32) -> i32 {
+    if text.is_null() || char_index < 0 {
+        return EngineError::InvalidArgument.code();
+    }
+
+    match CStr::from_ptr(text).to_str() {
+        Ok(s) => crate::text::grapheme::move_caret_chars(s, char_index as usize, delta) as i32,
+        Err(_) => EngineError::InvalidArgument.code(),
+    }
+}
+
+/// Where text may wrap, as the renderer breaks lines
+///
+/// Text buffers that wrap on the Go side use this so their lines end where
+/// DrawText's do: between CJK ideographs, never at a no-break space, and
+/// not inside URLs.
+///
+/// # Arguments
+/// * `text` - The text (null-terminated UTF-8)
+/// * `word_break` - WordBreak value (0=Normal, 1=BreakAll, 2=KeepAll, 3=BreakWord)
+/// * `out` - Receives char (code point) indices a line may start at, in order
+/// * `capacity` - Length of `out`
+///
+/// # Returns
+/// The number of break opportunities, which may exceed `capacity` (only
+/// the first `capacity` are written), or `EngineError::InvalidArgument` for
+/// null/invalid input.
+///
+/// # Safety
+/// - text must be a valid null-terminated UTF-8 string
+/// - out must be valid for `capacity` writes (or null with capacity 0)
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_text_line_breaks(
+    text: *const c_char,
+    word_break: u8,
+    out: *mut u32,
+    capacity: usize,
+) -> i32 {
+    if text.is_null() || (out.is_null() && capacity > 0) {
+        return EngineError::InvalidArgument.code();
+    }
+    let Ok(text) = CStr::from_ptr(text).to_str() else {
+        return EngineError::InvalidArgument.code();
+    };
+
+    let breaks = crate::text::linebreak::break_opportunities(text, WordBreak::from(word_break));
+    let mut chars_seen = 0;
+    let mut last_offset = 0;
+    for (n, &offset) in breaks.iter().enumerate() {
+        chars_seen += text[last_offset..offset].chars().count();
+        last_offset = offset;
+        if n < capacity {
+            *out.add(n) = chars_seen as u32;
+        }
+    }
+    breaks.len() as i32
+}
+
 // ============================================================================
 // Audio FFI
 // ============================================================================
@@ -8325,9 +12387,9 @@ pub extern "C" fn centered_audio_destroy(player_id: u32) {
 ///
 /// # Returns
 /// 0 on success, negative error code on failure:
-/// - -1: Invalid parameters
-/// - -2: Player not found
-/// - -3: Load failed
+/// - `InvalidArgument`: null pointer or invalid UTF-8
+/// - `NotFound`: player not found
+/// - `OperationFailed`: load failed
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
 pub unsafe extern "C" fn centered_audio_load_url(
@@ -8335,12 +12397,12 @@ pub unsafe extern "C" fn centered_audio_load_url(
     url: *const c_char,
 ) -> i32 {
     if url.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let url_str = match CStr::from_ptr(url).to_str() {
         Ok(s) => s,
-        Err(_) => return -1,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     let mut players = AUDIO_PLAYERS.lock().unwrap();
@@ -8349,11 +12411,11 @@ pub unsafe extern "C" fn centered_audio_load_url(
             Ok(()) => 0,
             Err(e) => {
                 eprintln!("Audio load error: {}", e);
-                -3
+                EngineError::OperationFailed.code()
             }
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8372,12 +12434,12 @@ pub unsafe extern "C" fn centered_audio_load_file(
     path: *const c_char,
 ) -> i32 {
     if path.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let path_str = match CStr::from_ptr(path).to_str() {
         Ok(s) => s,
-        Err(_) => return -1,
+        Err(_) => return EngineError::InvalidArgument.code(),
     };
 
     let mut players = AUDIO_PLAYERS.lock().unwrap();
@@ -8386,11 +12448,11 @@ pub unsafe extern "C" fn centered_audio_load_file(
             Ok(()) => 0,
             Err(e) => {
                 eprintln!("Audio load error: {}", e);
-                -3
+                EngineError::OperationFailed.code()
             }
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8405,10 +12467,10 @@ pub extern "C" fn centered_audio_play(player_id: u32) -> i32 {
     if let Some(player) = players.get_mut(&player_id) {
         match player.play() {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8423,10 +12485,10 @@ pub extern "C" fn centered_audio_pause(player_id: u32) -> i32 {
     if let Some(player) = players.get_mut(&player_id) {
         match player.pause() {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8441,10 +12503,10 @@ pub extern "C" fn centered_audio_stop(player_id: u32) -> i32 {
     if let Some(player) = players.get_mut(&player_id) {
         match player.stop() {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8463,10 +12525,10 @@ pub extern "C" fn centered_audio_seek(player_id: u32, timestamp_ms: u64) -> i32
     if let Some(player) = players.get_mut(&player_id) {
         match player.seek(timestamp_ms) {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8486,7 +12548,7 @@ pub extern "C" fn centered_audio_set_looping(player_id: u32, looping: bool) -> i
         player.set_looping(looping);
         0
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8506,7 +12568,7 @@ pub extern "C" fn centered_audio_set_volume(player_id: u32, volume: f32) -> i32
         player.set_volume(volume);
         0
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8528,7 +12590,7 @@ pub extern "C" fn centered_audio_get_state(player_id: u32) -> i32 {
     if let Some(player) = players.get(&player_id) {
         player.state() as i32
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8566,7 +12628,7 @@ pub unsafe extern "C" fn centered_audio_get_info(
     channels_out: *mut u32,
 ) -> i32 {
     if duration_ms_out.is_null() || sample_rate_out.is_null() || channels_out.is_null() {
-        return -1;
+        return EngineError::InvalidArgument.code();
     }
 
     let players = AUDIO_PLAYERS.lock().unwrap();
@@ -8577,10 +12639,10 @@ pub unsafe extern "C" fn centered_audio_get_info(
             *channels_out = info.channels;
             0
         } else {
-            -3 // No audio loaded
+            EngineError::NotReady.code()
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8628,7 +12690,7 @@ pub extern "C" fn centered_audio_update(player_id: u32) -> i32 {
     if let Some(player) = players.get_mut(&player_id) {
         if player.update() { 1 } else { 0 }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8685,7 +12747,7 @@ pub extern "C" fn centered_audio_input_request_permission(input_id: u32) -> i32
             Err(_) => 1, // Permission needed or denied
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
@@ -8704,13 +12766,227 @@ pub extern "C" fn centered_audio_input_has_permission(input_id: u32) -> i32 {
     }
 }
 
-/// List available audio input devices
+/// List available audio input devices
+/// Returns a JSON array of device info, or null on error
+/// Caller must free the returned string with centered_free_string
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_list_devices(input_id: u32) -> *mut c_char {
+    let inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get(&input_id) {
+        match input.list_devices() {
+            Ok(devices) => {
+                let json = serde_json::json!(devices.iter().map(|d| {
+                    serde_json::json!({
+                        "id": d.id,
+                        "name": d.name,
+                        "is_default": d.is_default,
+                    })
+                }).collect::<Vec<_>>());
+                match CString::new(json.to_string()) {
+                    Ok(s) => s.into_raw(),
+                    Err(_) => ptr::null_mut(),
+                }
+            }
+            Err(_) => ptr::null_mut(),
+        }
+    } else {
+        ptr::null_mut()
+    }
+}
+
+/// Open an audio input device
+///
+/// # Arguments
+/// * `input_id` - Input ID
+/// * `device_id` - Device ID (null for default)
+/// * `sample_rate` - Sample rate (0 for default)
+/// * `channels` - Number of channels (0 for default)
+///
+/// # Returns
+/// 0 on success, negative on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn centered_audio_input_open(
+    input_id: u32,
+    device_id: *const c_char,
+    sample_rate: u32,
+    channels: u32,
+) -> i32 {
+    let device_str = if device_id.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(device_id).to_str() {
+            Ok(s) => Some(s),
+            Err(_) => return EngineError::InvalidArgument.code(),
+        }
+    };
+
+    let config = AudioInputConfig {
+        sample_rate: if sample_rate == 0 { 44100 } else { sample_rate },
+        channels: if channels == 0 { 1 } else { channels },
+        ..Default::default()
+    };
+
+    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        match input.open(device_str, &config) {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Start capturing audio
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_start(input_id: u32) -> i32 {
+    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        match input.start() {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Stop capturing audio
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_stop(input_id: u32) -> i32 {
+    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        match input.stop() {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Close the audio input device
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_close(input_id: u32) {
+    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        input.close();
+    }
+}
+
+/// Get audio input state
+///
+/// # Returns
+/// 0=Idle, 1=RequestingPermission, 2=Ready, 3=Capturing, 4=Stopped, 5=Error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_get_state(input_id: u32) -> i32 {
+    let inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get(&input_id) {
+        input.state().as_i32()
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Get current audio input level (0.0 - 1.0 RMS)
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_audio_input_get_level(input_id: u32) -> f32 {
+    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        // Call update() to read samples from the microphone
+        input.update();
+        input.level()
+    } else {
+        0.0
+    }
+}
+
+// ============================================================================
+// Video Input (Camera) FFI
+// ============================================================================
+
+use crate::video::input::{VideoInput, VideoInputConfig, VideoInputState};
+
+lazy_static::lazy_static! {
+    /// Global video input storage
+    static ref VIDEO_INPUTS: std::sync::Mutex<std::collections::HashMap<u32, VideoInput>> = std::sync::Mutex::new(std::collections::HashMap::new());
+    static ref NEXT_VIDEO_INPUT_ID: std::sync::Mutex<u32> = std::sync::Mutex::new(1);
+}
+
+/// Create a new video input (camera)
+///
+/// # Returns
+/// A unique input ID (always positive), or 0 on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_create() -> u32 {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
+    let mut next_id = NEXT_VIDEO_INPUT_ID.lock().unwrap();
+
+    let input_id = *next_id;
+    *next_id += 1;
+
+    inputs.insert(input_id, VideoInput::new());
+    input_id
+}
+
+/// Destroy a video input and free resources
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_destroy(input_id: u32) {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(mut input) = inputs.remove(&input_id) {
+        input.close();
+    }
+}
+
+/// Request camera permission
+///
+/// # Returns
+/// 0 on success, 1 if permission needs to be granted, negative on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_request_permission(input_id: u32) -> i32 {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        match input.request_permission() {
+            Ok(()) => 0,
+            Err(_) => 1,
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Check if camera permission is granted
+///
+/// # Returns
+/// 1 if granted, 0 if not
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_has_permission(input_id: u32) -> i32 {
+    let inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get(&input_id) {
+        if input.has_permission() { 1 } else { 0 }
+    } else {
+        0
+    }
+}
+
+/// List available video input devices (cameras)
 /// Returns a JSON array of device info, or null on error
 /// Caller must free the returned string with centered_free_string
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_audio_input_list_devices(input_id: u32) -> *mut c_char {
-    let inputs = AUDIO_INPUTS.lock().unwrap();
+pub extern "C" fn centered_video_input_list_devices(input_id: u32) -> *mut c_char {
+    let inputs = VIDEO_INPUTS.lock().unwrap();
     if let Some(input) = inputs.get(&input_id) {
         match input.list_devices() {
             Ok(devices) => {
@@ -8718,7 +12994,9 @@ pub extern "C" fn centered_audio_input_list_devices(input_id: u32) -> *mut c_cha
                     serde_json::json!({
                         "id": d.id,
                         "name": d.name,
+                        "position": d.position.as_i32(),
                         "is_default": d.is_default,
+                        "resolutions": d.resolutions,
                     })
                 }).collect::<Vec<_>>());
                 match CString::new(json.to_string()) {
@@ -8733,647 +13011,1462 @@ pub extern "C" fn centered_audio_input_list_devices(input_id: u32) -> *mut c_cha
     }
 }
 
-/// Open an audio input device
+/// Open a video input device (camera)
 ///
 /// # Arguments
 /// * `input_id` - Input ID
 /// * `device_id` - Device ID (null for default)
-/// * `sample_rate` - Sample rate (0 for default)
-/// * `channels` - Number of channels (0 for default)
+/// * `width` - Preferred width (0 for default)
+/// * `height` - Preferred height (0 for default)
+/// * `frame_rate` - Preferred frame rate (0 for default)
 ///
 /// # Returns
 /// 0 on success, negative on error
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub unsafe extern "C" fn centered_audio_input_open(
+pub unsafe extern "C" fn centered_video_input_open(
     input_id: u32,
     device_id: *const c_char,
-    sample_rate: u32,
-    channels: u32,
+    width: u32,
+    height: u32,
+    frame_rate: u32,
 ) -> i32 {
     let device_str = if device_id.is_null() {
         None
     } else {
         match CStr::from_ptr(device_id).to_str() {
             Ok(s) => Some(s),
-            Err(_) => return -1,
+            Err(_) => return EngineError::InvalidArgument.code(),
         }
     };
 
-    let config = AudioInputConfig {
-        sample_rate: if sample_rate == 0 { 44100 } else { sample_rate },
-        channels: if channels == 0 { 1 } else { channels },
+    let config = VideoInputConfig {
+        width: if width == 0 { 1280 } else { width },
+        height: if height == 0 { 720 } else { height },
+        frame_rate: if frame_rate == 0 { 30 } else { frame_rate },
         ..Default::default()
     };
 
-    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
     if let Some(input) = inputs.get_mut(&input_id) {
         match input.open(device_str, &config) {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
         }
     } else {
-        -2
+        EngineError::NotFound.code()
     }
 }
 
-/// Start capturing audio
+/// Start capturing video
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_audio_input_start(input_id: u32) -> i32 {
-    let mut inputs = AUDIO_INPUTS.lock().unwrap();
+pub extern "C" fn centered_video_input_start(input_id: u32) -> i32 {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
     if let Some(input) = inputs.get_mut(&input_id) {
         match input.start() {
             Ok(()) => 0,
-            Err(_) => -3,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Stop capturing video
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_stop(input_id: u32) -> i32 {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        match input.stop() {
+            Ok(()) => 0,
+            Err(_) => EngineError::OperationFailed.code(),
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Close the video input device
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_close(input_id: u32) {
+    let mut inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get_mut(&input_id) {
+        input.close();
+    }
+}
+
+/// Get video input state
+///
+/// # Returns
+/// 0=Idle, 1=RequestingPermission, 2=Ready, 3=Capturing, 4=Stopped, 5=Error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_get_state(input_id: u32) -> i32 {
+    let inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get(&input_id) {
+        input.state().as_i32()
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Get video input dimensions
+///
+/// # Returns
+/// Width in the high 16 bits, height in the low 16 bits, or 0 on error
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_get_dimensions(input_id: u32, width_out: *mut u32, height_out: *mut u32) -> i32 {
+    let inputs = VIDEO_INPUTS.lock().unwrap();
+    if let Some(input) = inputs.get(&input_id) {
+        if let Some((w, h)) = input.dimensions() {
+            unsafe {
+                if !width_out.is_null() {
+                    *width_out = w;
+                }
+                if !height_out.is_null() {
+                    *height_out = h;
+                }
+            }
+            0
+        } else {
+            EngineError::NotReady.code()
+        }
+    } else {
+        EngineError::NotFound.code()
+    }
+}
+
+/// Get latest video frame as a GPU texture
+///
+/// This function gets the latest frame from the video input, uploads it to a GPU
+/// texture (creating one if needed), and returns the texture ID.
+/// If an existing_texture_id is provided and valid, it will be reused/updated.
+///
+/// # Returns
+/// Texture ID (positive), or negative error code:
+/// - `NotInitialized`: backend not initialized
+/// - `NotFound`: input not found
+/// - `NotReady`: no frame available yet
+/// - `GpuUploadFailed`: failed to upload to GPU
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub extern "C" fn centered_video_input_get_frame_texture(input_id: u32, existing_texture_id: u32) -> i32 {
+    // First update the input to capture new frames, then get the latest frame
+    let frame = {
+        let mut inputs = VIDEO_INPUTS.lock().unwrap();
+        if let Some(input) = inputs.get_mut(&input_id) {
+            // Call update() to read frames from the camera
+            input.update();
+            input.latest_frame()
+        } else {
+            return EngineError::NotFound.code();
+        }
+    };
+
+    let frame = match frame {
+        Some(f) => f,
+        None => return EngineError::NotReady.code(),
+    };
+
+    // Only convert BGRA to RGBA if the frame is in BGRA format
+    // Windows camera already outputs RGBA from process_sample()
+    let rgba_data = if frame.pixel_format == crate::video::input::PixelFormat::BGRA {
+        let mut data = frame.data.clone();
+        for chunk in data.chunks_exact_mut(4) {
+            chunk.swap(0, 2); // Swap B and R
+        }
+        data
+    } else {
+        // Don't clone - take ownership directly
+        frame.data
+    };
+
+    // Create LoadedImage for the backend
+    let loaded_image = crate::image::LoadedImage {
+        width: frame.width,
+        height: frame.height,
+        data: rgba_data,
+        premultiplied: false,
+        frames: Vec::new(),
+        loop_count: 0,
+    };
+
+    // Get backend and upload/update texture
+    let mut backend_guard = get_backend().lock().unwrap();
+    let backend = match backend_guard.as_mut() {
+        Some(b) => b,
+        None => return EngineError::NotInitialized.code(),
+    };
+
+    // If we have an existing texture, try to update it in-place for better performance
+    // This avoids creating/destroying GPU textures every frame
+    if existing_texture_id > 0 {
+        match backend.update_texture(existing_texture_id, &loaded_image) {
+            Ok(texture_id) => texture_id as i32,
+            Err(_) => EngineError::GpuUploadFailed.code(),
         }
     } else {
-        -2
+        // First frame - create new texture, without mipmaps since it is
+        // replaced every frame
+        match backend.load_image_with_options(&loaded_image, ImageUploadOptions { mipmaps: false, ..Default::default() }) {
+            Ok(texture_id) => texture_id as i32,
+            Err(_) => EngineError::GpuUploadFailed.code(),
+        }
     }
 }
 
-/// Stop capturing audio
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_audio_input_stop(input_id: u32) -> i32 {
-    let mut inputs = AUDIO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        match input.stop() {
-            Ok(()) => 0,
-            Err(_) => -3,
-        }
-    } else {
-        -2
-    }
+// ============================================================================
+// Batched Binary Commands (SharedMemory Transport)
+// ============================================================================
+
+/// Command types for the binary protocol (must match Go side exactly).
+/// Using u16 with 256-spacing between groups to allow room for growth.
+/// Each category has 256 slots available.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchCommandType {
+    // Text measurement commands (0x0000 - 0x00FF)
+    MeasureText = 0x0000,
+    MeasureTextBatch = 0x0001,
+    MeasureTextToCursor = 0x0002,
+    MeasureTextWithFont = 0x0003,
+
+    // Image commands (0x0100 - 0x01FF)
+    LoadImage = 0x0100,
+    LoadImageFile = 0x0101,
+    UnloadImage = 0x0102,
+    GetTextureSize = 0x0103,
+
+    // Render commands (0x0200 - 0x02FF)
+    RenderFrame = 0x0200,
+
+    // System queries (0x0300 - 0x03FF)
+    GetScaleFactor = 0x0300,
+    GetDarkMode = 0x0301,
+
+    // Audio playback commands (0x0400 - 0x04FF)
+    AudioCreate = 0x0400,
+    AudioDestroy = 0x0401,
+    AudioLoadURL = 0x0402,
+    AudioLoadFile = 0x0403,
+    AudioPlay = 0x0404,
+    AudioPause = 0x0405,
+    AudioStop = 0x0406,
+    AudioSeek = 0x0407,
+    AudioSetVolume = 0x0408,
+    AudioSetLooping = 0x0409,
+    AudioGetState = 0x040A,
+    AudioGetTime = 0x040B,
+    AudioGetInfo = 0x040C,
+    AudioGetVolume = 0x040D,
+    AudioIsLooping = 0x040E,
+    AudioUpdate = 0x040F,
+
+    // Audio input commands (0x0500 - 0x05FF)
+    AudioInputCreate = 0x0500,
+    AudioInputDestroy = 0x0501,
+    AudioInputRequestPermission = 0x0502,
+    AudioInputHasPermission = 0x0503,
+    AudioInputListDevices = 0x0504,
+    AudioInputOpen = 0x0505,
+    AudioInputStart = 0x0506,
+    AudioInputStop = 0x0507,
+    AudioInputClose = 0x0508,
+    AudioInputGetLevel = 0x0509,
+    AudioInputGetState = 0x050A,
+
+    // Video playback commands (0x0600 - 0x06FF)
+    VideoCreate = 0x0600,
+    VideoDestroy = 0x0601,
+    VideoLoadURL = 0x0602,
+    VideoLoadFile = 0x0603,
+    VideoInitStream = 0x0604,
+    VideoPushFrame = 0x0605,
+    VideoPlay = 0x0606,
+    VideoPause = 0x0607,
+    VideoSeek = 0x0608,
+    VideoSetLooping = 0x0609,
+    VideoSetMuted = 0x060A,
+    VideoSetVolume = 0x060B,
+    VideoGetState = 0x060C,
+    VideoGetTime = 0x060D,
+    VideoGetInfo = 0x060E,
+    VideoUpdate = 0x060F,
+    VideoGetTextureID = 0x0610,
+
+    // Video input commands (0x0700 - 0x07FF)
+    VideoInputCreate = 0x0700,
+    VideoInputDestroy = 0x0701,
+    VideoInputRequestPermission = 0x0702,
+    VideoInputHasPermission = 0x0703,
+    VideoInputListDevices = 0x0704,
+    VideoInputOpen = 0x0705,
+    VideoInputStart = 0x0706,
+    VideoInputStop = 0x0707,
+    VideoInputClose = 0x0708,
+    VideoInputGetState = 0x0709,
+    VideoInputGetDimensions = 0x070A,
+    VideoInputGetFrameTexture = 0x070B,
+
+    // Clipboard commands (0x0800 - 0x08FF)
+    ClipboardGet = 0x0800,
+    ClipboardSet = 0x0801,
+
+    // App lifecycle (0xFF00 - 0xFFFF)
+    RequestRedraw = 0xFF00,
+    RequestExit = 0xFF01,
 }
 
-/// Close the audio input device
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_audio_input_close(input_id: u32) {
-    let mut inputs = AUDIO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        input.close();
-    }
+/// Response types for the binary protocol.
+/// Using u8 since we don't need as many response types.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchResponseType {
+    Success = 0,
+    Error = 1,
+    Float32 = 2,
+    Int32 = 3,
+    Uint32 = 4,
+    Uint64 = 5,
+    String = 6,
+    Bytes = 7,
+    Bool = 8,
+    Float32Array = 9,
+    Uint32Pair = 10,     // For texture size, dimensions
+    Uint32Triple = 11,   // For audio info (duration, sample_rate, channels)
+    VideoInfo = 12,      // For video info (width, height, duration)
 }
 
-/// Get audio input state
+/// Execute a batch of binary commands.
 ///
-/// # Returns
-/// 0=Idle, 1=RequestingPermission, 2=Ready, 3=Capturing, 4=Stopped, 5=Error
+/// Buffer format (request):
+///   count: u32           - Number of commands
+///   For each command:
+///     cmd_type: u16      - Command type (little-endian)
+///     payload_len: u32   - Length of payload
+///     payload: [u8]      - Command-specific payload
+///
+/// Buffer format (response):
+///   count: u32           - Number of responses
+///   For each response:
+///     resp_type: u8      - Response type
+///     payload_len: u32   - Length of payload
+///     payload: [u8]      - Response-specific payload
+///
+/// # Safety
+/// - request_ptr must point to valid memory of at least request_len bytes
+/// - response_ptr must point to valid memory of at least response_capacity bytes
+/// - response_len_out must point to valid u32
 #[cfg(not(target_arch = "wasm32"))]
 #[no_mangle]
-pub extern "C" fn centered_audio_input_get_state(input_id: u32) -> i32 {
-    let inputs = AUDIO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get(&input_id) {
-        input.state().as_i32()
-    } else {
-        -2
+pub unsafe extern "C" fn centered_execute_batch(
+    request_ptr: *const u8,
+    request_len: usize,
+    response_ptr: *mut u8,
+    response_capacity: usize,
+    response_len_out: *mut usize,
+) -> i32 {
+    if request_ptr.is_null() || response_ptr.is_null() || response_len_out.is_null() {
+        return EngineError::InvalidArgument.code();
     }
-}
 
-/// Get current audio input level (0.0 - 1.0 RMS)
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_audio_input_get_level(input_id: u32) -> f32 {
-    let mut inputs = AUDIO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        // Call update() to read samples from the microphone
-        input.update();
-        input.level()
-    } else {
-        0.0
+    if request_len < 4 {
+        return EngineError::InvalidArgument.code();
     }
-}
 
-// ============================================================================
-// Video Input (Camera) FFI
-// ============================================================================
-
-use crate::video::input::{VideoInput, VideoInputConfig, VideoInputState};
-
-lazy_static::lazy_static! {
-    /// Global video input storage
-    static ref VIDEO_INPUTS: std::sync::Mutex<std::collections::HashMap<u32, VideoInput>> = std::sync::Mutex::new(std::collections::HashMap::new());
-    static ref NEXT_VIDEO_INPUT_ID: std::sync::Mutex<u32> = std::sync::Mutex::new(1);
-}
-
-/// Create a new video input (camera)
-///
-/// # Returns
-/// A unique input ID (always positive), or 0 on error
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_create() -> u32 {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    let mut next_id = NEXT_VIDEO_INPUT_ID.lock().unwrap();
-
-    let input_id = *next_id;
-    *next_id += 1;
+    let request = std::slice::from_raw_parts(request_ptr, request_len);
+    let response = std::slice::from_raw_parts_mut(response_ptr, response_capacity);
 
-    inputs.insert(input_id, VideoInput::new());
-    input_id
-}
+    // Read command count
+    let cmd_count = u32::from_le_bytes([request[0], request[1], request[2], request[3]]) as usize;
 
-/// Destroy a video input and free resources
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_destroy(input_id: u32) {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(mut input) = inputs.remove(&input_id) {
-        input.close();
-    }
-}
+    // Process commands and build responses
+    let mut req_offset = 4;
+    let mut resp_offset = 4; // Reserve space for response count
 
-/// Request camera permission
-///
-/// # Returns
-/// 0 on success, 1 if permission needs to be granted, negative on error
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_request_permission(input_id: u32) -> i32 {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        match input.request_permission() {
-            Ok(()) => 0,
-            Err(_) => 1,
+    for _ in 0..cmd_count {
+        if req_offset + 6 > request_len {
+            break;
         }
-    } else {
-        -2
-    }
-}
 
-/// Check if camera permission is granted
-///
-/// # Returns
-/// 1 if granted, 0 if not
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_has_permission(input_id: u32) -> i32 {
-    let inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get(&input_id) {
-        if input.has_permission() { 1 } else { 0 }
-    } else {
-        0
-    }
-}
+        // Read command type (u16)
+        let cmd_type = u16::from_le_bytes([request[req_offset], request[req_offset + 1]]);
+        req_offset += 2;
 
-/// List available video input devices (cameras)
-/// Returns a JSON array of device info, or null on error
-/// Caller must free the returned string with centered_free_string
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_list_devices(input_id: u32) -> *mut c_char {
-    let inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get(&input_id) {
-        match input.list_devices() {
-            Ok(devices) => {
-                let json = serde_json::json!(devices.iter().map(|d| {
-                    serde_json::json!({
-                        "id": d.id,
-                        "name": d.name,
-                        "position": d.position.as_i32(),
-                        "is_default": d.is_default,
-                        "resolutions": d.resolutions,
-                    })
-                }).collect::<Vec<_>>());
-                match CString::new(json.to_string()) {
-                    Ok(s) => s.into_raw(),
-                    Err(_) => ptr::null_mut(),
-                }
-            }
-            Err(_) => ptr::null_mut(),
-        }
-    } else {
-        ptr::null_mut()
-    }
-}
+        let payload_len = u32::from_le_bytes([
+            request[req_offset],
+            request[req_offset + 1],
+            request[req_offset + 2],
+            request[req_offset + 3],
+        ]) as usize;
+        req_offset += 4;
 
-/// Open a video input device (camera)
-///
-/// # Arguments
-/// * `input_id` - Input ID
-/// * `device_id` - Device ID (null for default)
-/// * `width` - Preferred width (0 for default)
-/// * `height` - Preferred height (0 for default)
-/// * `frame_rate` - Preferred frame rate (0 for default)
-///
-/// # Returns
-/// 0 on success, negative on error
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub unsafe extern "C" fn centered_video_input_open(
-    input_id: u32,
-    device_id: *const c_char,
-    width: u32,
-    height: u32,
-    frame_rate: u32,
-) -> i32 {
-    let device_str = if device_id.is_null() {
-        None
-    } else {
-        match CStr::from_ptr(device_id).to_str() {
-            Ok(s) => Some(s),
-            Err(_) => return -1,
+        if req_offset + payload_len > request_len {
+            break;
         }
-    };
 
-    let config = VideoInputConfig {
-        width: if width == 0 { 1280 } else { width },
-        height: if height == 0 { 720 } else { height },
-        frame_rate: if frame_rate == 0 { 30 } else { frame_rate },
-        ..Default::default()
-    };
+        let payload = &request[req_offset..req_offset + payload_len];
+        req_offset += payload_len;
 
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        match input.open(device_str, &config) {
-            Ok(()) => 0,
-            Err(_) => -3,
+        // Execute command and write response
+        let (resp_type, resp_payload) = execute_single_command(cmd_type, payload);
+
+        // Write response type
+        if resp_offset + 5 + resp_payload.len() > response_capacity {
+            // Response buffer full - return error
+            *response_len_out = 0;
+            return EngineError::BufferTooSmall.code();
         }
-    } else {
-        -2
+
+        response[resp_offset] = resp_type as u8;
+        resp_offset += 1;
+
+        // Write response payload length
+        let resp_payload_len = resp_payload.len() as u32;
+        response[resp_offset..resp_offset + 4].copy_from_slice(&resp_payload_len.to_le_bytes());
+        resp_offset += 4;
+
+        // Write response payload
+        response[resp_offset..resp_offset + resp_payload.len()].copy_from_slice(&resp_payload);
+        resp_offset += resp_payload.len();
     }
+
+    // Write response count at the beginning
+    response[0..4].copy_from_slice(&(cmd_count as u32).to_le_bytes());
+
+    *response_len_out = resp_offset;
+    0
 }
 
-/// Start capturing video
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_start(input_id: u32) -> i32 {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        match input.start() {
-            Ok(()) => 0,
-            Err(_) => -3,
-        }
-    } else {
-        -2
-    }
+/// Parse gradient data from a RenderFrame payload, advancing `offset`.
+///
+/// Layout: type(1) + params + stop_count(1) + stops(position(4) + color(4))...
+/// where params are angle(4) for linear (type 0), center_x(4) + center_y(4)
+/// for radial (type 1) and center_x(4) + center_y(4) + start_angle(4) for
+/// conic (type 2). Returns None if the payload is truncated, Some(None) for an
+/// unknown gradient type.
+fn parse_batch_gradient(payload: &[u8], offset: &mut usize) -> Option<Option<crate::render::Gradient>> {
+    let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+
+    if *offset + 1 > payload.len() {
+        return None;
+    }
+    let grad_type = payload[*offset];
+    *offset += 1;
+
+    let param_len = match grad_type {
+        0 => 4,
+        1 => 8,
+        2 => 12,
+        _ => return Some(None),
+    };
+    if *offset + param_len + 1 > payload.len() {
+        return None;
+    }
+    let first = f32::from_bits(read_u32(*offset));
+    let second = if grad_type >= 1 { f32::from_bits(read_u32(*offset + 4)) } else { 0.0 };
+    let third = if grad_type == 2 { f32::from_bits(read_u32(*offset + 8)) } else { 0.0 };
+    let stop_count = payload[*offset + param_len] as usize;
+    *offset += param_len + 1;
+
+    if *offset + stop_count * 8 > payload.len() {
+        return None;
+    }
+    let mut stops = Vec::with_capacity(stop_count);
+    for _ in 0..stop_count {
+        let position = f32::from_bits(read_u32(*offset));
+        let color = read_u32(*offset + 4);
+        *offset += 8;
+        stops.push(crate::render::GradientStop { position, color });
+    }
+
+    Some(Some(match grad_type {
+        0 => crate::render::Gradient::Linear { angle: first, stops },
+        1 => crate::render::Gradient::Radial { center_x: first, center_y: second, stops },
+        _ => crate::render::Gradient::Conic { center_x: first, center_y: second, start_angle: third, stops },
+    }))
 }
 
-/// Stop capturing video
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_stop(input_id: u32) -> i32 {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        match input.stop() {
-            Ok(()) => 0,
-            Err(_) => -3,
-        }
-    } else {
-        -2
-    }
+/// Parse the string table at the start of a RenderFrame payload, advancing
+/// `offset`.
+///
+/// Layout: string_count(4) + [str_len(4) + str]..., UTF-8 checked once
+/// here so commands can refer to texts by index
+fn parse_batch_string_table<'a>(payload: &'a [u8], offset: &mut usize) -> Result<Vec<&'a str>, String> {
+    let truncated = || "string table is truncated".to_string();
+    if *offset + 4 > payload.len() {
+        return Err("missing string count".to_string());
+    }
+    let count = u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]) as usize;
+    *offset += 4;
+
+    // Each string takes at least its length, so a bogus count can't
+    // reserve more than the payload holds
+    let mut strings = Vec::with_capacity(count.min((payload.len() - *offset) / 4));
+    for _ in 0..count {
+        if *offset + 4 > payload.len() {
+            return Err(truncated());
+        }
+        let len = u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]) as usize;
+        *offset += 4;
+        if len > payload.len() - *offset {
+            return Err(truncated());
+        }
+        let s = std::str::from_utf8(&payload[*offset..*offset + len]).map_err(|_| "invalid UTF-8 in string table".to_string())?;
+        *offset += len;
+        strings.push(s);
+    }
+    Ok(strings)
 }
 
-/// Close the video input device
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_close(input_id: u32) {
-    let mut inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get_mut(&input_id) {
-        input.close();
+/// Look up a text by its string table index (a u32 at `offset`),
+/// advancing `offset`
+fn parse_batch_text(payload: &[u8], offset: &mut usize, strings: &[&str], cmd_type: u8) -> Result<String, String> {
+    if *offset + 4 > payload.len() {
+        return Err(format!("render command {:#04x} is truncated", cmd_type));
+    }
+    let index = u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]) as usize;
+    *offset += 4;
+    match strings.get(index) {
+        Some(text) => Ok(text.to_string()),
+        None => Err(format!("render command {:#04x} refers to missing string {}", cmd_type, index)),
     }
 }
 
-/// Get video input state
+/// Parse a font descriptor from a RenderFrame payload, advancing `offset`.
 ///
-/// # Returns
-/// 0=Idle, 1=RequestingPermission, 2=Ready, 3=Capturing, 4=Stopped, 5=Error
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_get_state(input_id: u32) -> i32 {
-    let inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get(&input_id) {
-        input.state().as_i32()
-    } else {
-        -2
+/// Layout: source_type(1: 0 system, 1 bundled, 2 memory, 3 fallback chain) +
+/// name_len(4) + name + [data_hash(8) for memory] + weight(2) + style(1) +
+/// size(4), where a fallback chain's name is a comma-separated family list
+fn parse_batch_font(payload: &[u8], offset: &mut usize, cmd_type: u8) -> Result<FontDescriptor, String> {
+    let truncated = format!("render command {:#04x} is truncated", cmd_type);
+    if *offset + 1 > payload.len() {
+        return Err(truncated);
     }
-}
+    let source_type = payload[*offset];
+    *offset += 1;
 
-/// Get video input dimensions
-///
-/// # Returns
-/// Width in the high 16 bits, height in the low 16 bits, or 0 on error
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_get_dimensions(input_id: u32, width_out: *mut u32, height_out: *mut u32) -> i32 {
-    let inputs = VIDEO_INPUTS.lock().unwrap();
-    if let Some(input) = inputs.get(&input_id) {
-        if let Some((w, h)) = input.dimensions() {
-            unsafe {
-                if !width_out.is_null() {
-                    *width_out = w;
-                }
-                if !height_out.is_null() {
-                    *height_out = h;
-                }
-            }
-            0
-        } else {
-            -3
+    if *offset + 4 > payload.len() {
+        return Err(truncated);
+    }
+    let font_name_len = u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]) as usize;
+    *offset += 4;
+
+    if *offset + font_name_len > payload.len() {
+        return Err(truncated);
+    }
+    let font_name = match std::str::from_utf8(&payload[*offset..*offset + font_name_len]) {
+        Ok(s) => s.to_string(),
+        Err(_) => return Err(format!("invalid UTF-8 in render command {:#04x}", cmd_type)),
+    };
+    *offset += font_name_len;
+
+    let mut data_hash = 0;
+    if source_type == 2 {
+        if *offset + 8 > payload.len() {
+            return Err(truncated);
         }
-    } else {
-        -2
+        data_hash = u64::from_le_bytes(payload[*offset..*offset + 8].try_into().unwrap());
+        *offset += 8;
     }
+
+    if *offset + 7 > payload.len() {
+        return Err(truncated);
+    }
+    let weight = u16::from_le_bytes([payload[*offset], payload[*offset + 1]]);
+    let style = match payload[*offset + 2] {
+        1 => FontStyle::Italic,
+        _ => FontStyle::Normal,
+    };
+    let size = f32::from_bits(u32::from_le_bytes([payload[*offset + 3], payload[*offset + 4], payload[*offset + 5], payload[*offset + 6]]));
+    *offset += 7;
+
+    let source = match source_type {
+        1 => FontSource::Bundled(font_name),
+        2 => FontSource::Memory { name: font_name, data_hash },
+        3 => FontSource::SystemWithFallback(fallback_families(&font_name)),
+        _ => FontSource::System(font_name),
+    };
+    Ok(FontDescriptor { source, weight, style, size })
 }
 
-/// Get latest video frame as a GPU texture
-///
-/// This function gets the latest frame from the video input, uploads it to a GPU
-/// texture (creating one if needed), and returns the texture ID.
-/// If an existing_texture_id is provided and valid, it will be reused/updated.
+/// Parse a text layout config from a RenderFrame payload, advancing `offset`.
 ///
-/// # Returns
-/// Texture ID (positive), or negative error code:
-/// - -1: Backend not initialized
-/// - -2: Input not found
-/// - -3: No frame available
-/// - -4: Failed to upload to GPU
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub extern "C" fn centered_video_input_get_frame_texture(input_id: u32, existing_texture_id: u32) -> i32 {
-    // First update the input to capture new frames, then get the latest frame
-    let frame = {
-        let mut inputs = VIDEO_INPUTS.lock().unwrap();
-        if let Some(input) = inputs.get_mut(&input_id) {
-            // Call update() to read frames from the camera
-            input.update();
-            input.latest_frame()
-        } else {
-            return -2; // Input not found
-        }
+/// Layout: flags(1) + [max_width(4)] + [max_height(4)] + [max_lines(4)] +
+/// line_height(4) + letter_spacing(4) + word_spacing(4) + alignment(1) +
+/// vertical_align(1) + word_break(1) + overflow(1) + white_space(1) +
+/// [anchor(1)] + [base_direction(1)]. Also returns whether flag 0x10 says
+/// gradient data follows.
+fn parse_batch_text_layout(payload: &[u8], offset: &mut usize, cmd_type: u8) -> Result<(TextLayoutConfig, bool), String> {
+    let truncated = format!("render command {:#04x} is truncated", cmd_type);
+    if *offset + 1 > payload.len() {
+        return Err(truncated);
+    }
+    let layout_flags = payload[*offset];
+    *offset += 1;
+
+    let has_max_width = (layout_flags & 0x01) != 0;
+    let has_max_height = (layout_flags & 0x02) != 0;
+    let has_max_lines = (layout_flags & 0x04) != 0;
+    let has_anchor = (layout_flags & 0x08) != 0;
+    let has_gradient = (layout_flags & 0x10) != 0;
+    let has_base_direction = (layout_flags & 0x20) != 0;
+
+    let max_width = if has_max_width {
+        if *offset + 4 > payload.len() {
+            return Err(truncated);
+        }
+        let v = f32::from_bits(u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]));
+        *offset += 4;
+        Some(v)
+    } else {
+        None
     };
 
-    let frame = match frame {
-        Some(f) => f,
-        None => return -3, // No frame available
+    let max_height = if has_max_height {
+        if *offset + 4 > payload.len() {
+            return Err(truncated);
+        }
+        let v = f32::from_bits(u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]));
+        *offset += 4;
+        Some(v)
+    } else {
+        None
     };
 
-    // Only convert BGRA to RGBA if the frame is in BGRA format
-    // Windows camera already outputs RGBA from process_sample()
-    let rgba_data = if frame.pixel_format == crate::video::input::PixelFormat::BGRA {
-        let mut data = frame.data.clone();
-        for chunk in data.chunks_exact_mut(4) {
-            chunk.swap(0, 2); // Swap B and R
+    let max_lines = if has_max_lines {
+        if *offset + 4 > payload.len() {
+            return Err(truncated);
         }
-        data
+        let v = u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]) as usize;
+        *offset += 4;
+        Some(v)
     } else {
-        // Don't clone - take ownership directly
-        frame.data
+        None
     };
 
-    // Create LoadedImage for the backend
-    let loaded_image = crate::image::LoadedImage {
-        width: frame.width,
-        height: frame.height,
-        data: rgba_data,
+    if *offset + 17 > payload.len() {
+        return Err(truncated);
+    }
+    let line_height = f32::from_bits(u32::from_le_bytes([payload[*offset], payload[*offset + 1], payload[*offset + 2], payload[*offset + 3]]));
+    let letter_spacing = f32::from_bits(u32::from_le_bytes([payload[*offset + 4], payload[*offset + 5], payload[*offset + 6], payload[*offset + 7]]));
+    let word_spacing = f32::from_bits(u32::from_le_bytes([payload[*offset + 8], payload[*offset + 9], payload[*offset + 10], payload[*offset + 11]]));
+    let alignment = TextAlign::from(payload[*offset + 12]);
+    let vertical_align = match payload[*offset + 13] {
+        1 => VerticalAlign::Middle,
+        2 => VerticalAlign::Bottom,
+        3 => VerticalAlign::Baseline,
+        _ => VerticalAlign::Top,
     };
-
-    // Get backend and upload/update texture
-    let mut backend_guard = get_backend().lock().unwrap();
-    let backend = match backend_guard.as_mut() {
-        Some(b) => b,
-        None => return -1, // Backend not initialized
+    let word_break = match payload[*offset + 14] {
+        1 => WordBreak::BreakAll,
+        2 => WordBreak::KeepAll,
+        3 => WordBreak::BreakWord,
+        _ => WordBreak::Normal,
+    };
+    let overflow = match payload[*offset + 15] {
+        1 => TextOverflow::Ellipsis,
+        2 => TextOverflow::Wrap,
+        _ => TextOverflow::Clip,
+    };
+    let white_space = match payload[*offset + 16] {
+        1 => WhiteSpace::NoWrap,
+        2 => WhiteSpace::Pre,
+        3 => WhiteSpace::PreWrap,
+        _ => WhiteSpace::Normal,
     };
+    *offset += 17;
 
-    // If we have an existing texture, try to update it in-place for better performance
-    // This avoids creating/destroying GPU textures every frame
-    if existing_texture_id > 0 {
-        match backend.update_texture(existing_texture_id, &loaded_image) {
-            Ok(texture_id) => texture_id as i32,
-            Err(_) => -4, // Upload failed
+    let anchor = if has_anchor {
+        if *offset + 1 > payload.len() {
+            return Err(truncated);
         }
+        let v = TextAnchor::from(payload[*offset]);
+        *offset += 1;
+        v
     } else {
-        // First frame - create new texture
-        match backend.load_image(&loaded_image) {
-            Ok(texture_id) => texture_id as i32,
-            Err(_) => -4, // Upload failed
+        TextAnchor::TopLeft
+    };
+
+    let base_direction = if has_base_direction {
+        if *offset + 1 > payload.len() {
+            return Err(truncated);
         }
-    }
+        let v = TextDirection::from(payload[*offset]);
+        *offset += 1;
+        v
+    } else {
+        TextDirection::Auto
+    };
+
+    let layout = TextLayoutConfig {
+        max_width,
+        max_height,
+        max_lines,
+        line_height,
+        letter_spacing,
+        word_spacing,
+        alignment,
+        vertical_align,
+        word_break,
+        overflow,
+        white_space,
+        anchor,
+        base_direction,
+    };
+    Ok((layout, has_gradient))
 }
 
-// ============================================================================
-// Batched Binary Commands (SharedMemory Transport)
-// ============================================================================
+/// Decode a binary render frame into render commands, without serde
+///
+/// This is the payload of `centered_backend_render_frame_binary` and of the
+/// batched RenderFrame command (0x0200). Numbers are little-endian, f32s
+/// are IEEE 754 bits, colors are u32 0xRRGGBBAA (except Clear, which is
+/// r, g, b, a bytes) and strings are a u32 byte length followed by UTF-8.
+/// Bracketed fields are present only when their flag bit is set.
+///
+/// Layout: string_table + command_count(4) + [command_type(1) + command_data]...
+///
+/// Texts (DrawText and rich text runs) live in the string table, each
+/// distinct text once, and commands refer to them by index:
+///   string_table: string_count(4) + [str_len(4) + str]...
+///   text: string index(4)
+///
+/// Command types:
+///   0x00 - Clear: r(1) + g(1) + b(1) + a(1)
+///   0x01 - DrawRect: x(4) + y(4) + w(4) + h(4) + color(4) + radii(16) + rotation(4) + flags(1) + [border_w(4) + border_color(4) + border_style(1) + dash_length(4) + gap_length(4)] + [gradient_data] + [pivot_x(4) + pivot_y(4)] + [inner_shadow: blur(4) + spread(4) + offset_x(4) + offset_y(4) + color(4)] + [shadow: offset_x(4) + offset_y(4) + blur_radius(4) + spread(4) + color(4)]
+///   0x02 - DrawText: x(4) + y(4) + text(4) + font_data + color(4) + layout_data + [gradient_data]
+///   0x03 - DrawImage: x(4) + y(4) + w(4) + h(4) + texture_id(4) + flags(1) + [source_rect(16)] + radii(16) + [opacity(4)] + [rotation(4)] + [pivot_x(4) + pivot_y(4)] + [src(16)] + [tint(4)] + [sampling(1)]
+///   0x04 - DrawShadow: x(4) + y(4) + w(4) + h(4) + blur(4) + color(4) + offset_x(4) + offset_y(4) + radii(16)
+///   0x05 - PushClip: x(4) + y(4) + w(4) + h(4)
+///   0x06 - PopClip: (no data)
+///   0x07 - BeginScrollView: x(4) + y(4) + w(4) + h(4) + scroll_x(4) + scroll_y(4) + flags(1) + [content_w(4)] + [content_h(4)]
+///   0x08 - EndScrollView: (no data)
+///   0x09 - SetOpacity: opacity(4)
+///   0x0A - Caret: x(4) + y(4) + w(4) + h(4) + color(4) + blink_ms(4)
+///   0x0B - FocusRing: x(4) + y(4) + w(4) + h(4) + radii(16) + color(4) + thickness(4) + dash(4) + offset(4) + speed(4)
+///   0x0C - DrawLine: x1(4) + y1(4) + x2(4) + y2(4) + width(4) + color(4) + cap(1)
+///   0x0D - DrawRichText: x(4) + y(4) + layout_data + [gradient_data] + run_count(4) + [run]...
+///   0x0E - PushTransform: a(4) + b(4) + c(4) + d(4) + e(4) + f(4)
+///   0x0F - PopTransform: (no data)
+///   0x10 - PushRoundedClip: x(4) + y(4) + w(4) + h(4) + radii(16)
+///   0x11 - DrawArc: cx(4) + cy(4) + radius(4) + start_angle(4) + end_angle(4) + thickness(4) + color(4)
+///   0x12 - DrawNinePatch: texture_id(4) + x(4) + y(4) + w(4) + h(4) + insets(16)
+///   0x13 - DrawPath: flags(1) + [fill(4)] + [stroke_width(4) + stroke_color(4) + cap(1) + join(1)] + segment_count(4) + [segment]...
+///   0x14 - PushOpacityLayer: alpha(4)
+///   0x15 - PopOpacityLayer: (no data)
+///   0x16 - Spinner: cx(4) + cy(4) + radius(4) + color(4) + speed(4) + thickness(4)
+///
+/// Flags and sub-layouts:
+///   DrawRect flags: 0x01 border, 0x02 gradient, 0x04 pivot, 0x08 inner shadow, 0x10 shadow
+///   DrawImage flags: 0x01 source rect, 0x02 opacity, 0x04 rotation, 0x08 pivot, 0x10 src (pixels), 0x20 tint, 0x40 sampling (0 nearest, 1 linear, 2 trilinear; default trilinear)
+///   BeginScrollView flags: 0x01 content width, 0x02 content height
+///   DrawPath flags: 0x01 fill, 0x02 stroke, 0x04 even-odd fill rule
+///   segment: verb(1: 0 MoveTo, 1 LineTo, 2 QuadTo, 3 CubicTo, 4 Close) + points(4 each: 2 for MoveTo and
+///     LineTo, 4 for QuadTo with the control point first, 6 for CubicTo, none for Close)
+///   font_data: source_type(1: 0 system, 1 bundled, 2 memory, 3 fallback chain) + name_len(4) + name + [data_hash(8)] + weight(2) + style(1) + size(4)
+///   layout_data: flags(1: 0x01 max_width, 0x02 max_height, 0x04 max_lines, 0x08 anchor, 0x10 gradient, 0x20 base direction) +
+///     [max_width(4)] + [max_height(4)] + [max_lines(4)] + line_height(4) + letter_spacing(4) + word_spacing(4) +
+///     alignment(1) + vertical_align(1) + word_break(1) + overflow(1) + white_space(1) + [anchor(1)] + [base_direction(1)]
+///   run: kind(1) + (kind 0, text: text(4) + font_data + color(4)
+///     | kind 1, image: texture_id(4) + w(4) + h(4) + baseline_offset(4))
+///   gradient_data: see parse_batch_gradient
+fn decode_render_commands(payload: &[u8]) -> Result<Vec<RenderCommand>, String> {
+    let truncated = |cmd_type: u8| format!("render command {:#04x} is truncated", cmd_type);
+    let mut offset = 0;
+    let strings = parse_batch_string_table(payload, &mut offset)?;
+    if offset + 4 > payload.len() {
+        return Err("missing command count".to_string());
+    }
+
+    let command_count = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]) as usize;
+    offset += 4;
+    // Every command takes at least its type byte, so a bogus count can't
+    // reserve more than the payload holds
+    let mut commands: Vec<RenderCommand> = Vec::with_capacity(command_count.min(payload.len() - offset));
+
+    for _ in 0..command_count {
+        if offset >= payload.len() {
+            return Err("unexpected end of payload".to_string());
+        }
+
+        let cmd_type = payload[offset];
+        offset += 1;
+
+        match cmd_type {
+            // Clear: r(1) + g(1) + b(1) + a(1)
+            0x00 => {
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let r = payload[offset];
+                let g = payload[offset + 1];
+                let b = payload[offset + 2];
+                let a = payload[offset + 3];
+                offset += 4;
+                commands.push(RenderCommand::Clear(crate::style::Color { r, g, b, a }));
+            }
 
-/// Command types for the binary protocol (must match Go side exactly).
-/// Using u16 with 256-spacing between groups to allow room for growth.
-/// Each category has 256 slots available.
-#[repr(u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum BatchCommandType {
-    // Text measurement commands (0x0000 - 0x00FF)
-    MeasureText = 0x0000,
-    MeasureTextBatch = 0x0001,
-    MeasureTextToCursor = 0x0002,
-    MeasureTextWithFont = 0x0003,
+            // DrawRect: x(4) + y(4) + w(4) + h(4) + color(4) + radii(16) + rotation(4) + flags(1) + [border] + [gradient] + [pivot(8)] + [inner_shadow(20)] + [shadow(20)]
+            0x01 => {
+                if offset + 41 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                let color = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
+                let r0 = f32::from_bits(u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]));
+                let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 24], payload[offset + 25], payload[offset + 26], payload[offset + 27]]));
+                let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 28], payload[offset + 29], payload[offset + 30], payload[offset + 31]]));
+                let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 32], payload[offset + 33], payload[offset + 34], payload[offset + 35]]));
+                let rotation = f32::from_bits(u32::from_le_bytes([payload[offset + 36], payload[offset + 37], payload[offset + 38], payload[offset + 39]]));
+                let flags = payload[offset + 40];
+                offset += 41;
+
+                let has_border = (flags & 0x01) != 0;
+                let has_gradient = (flags & 0x02) != 0;
+                let has_pivot = (flags & 0x04) != 0;
+                let has_inner_shadow = (flags & 0x08) != 0;
+                let has_shadow = (flags & 0x10) != 0;
+
+                let border = if has_border {
+                    if offset + 17 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let bw = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let bc = u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]);
+                    let bs = match payload[offset + 8] {
+                        1 => crate::render::BorderStyle::Dashed,
+                        2 => crate::render::BorderStyle::Dotted,
+                        _ => crate::render::BorderStyle::Solid,
+                    };
+                    let dash_length = f32::from_bits(u32::from_le_bytes([payload[offset + 9], payload[offset + 10], payload[offset + 11], payload[offset + 12]]));
+                    let gap_length = f32::from_bits(u32::from_le_bytes([payload[offset + 13], payload[offset + 14], payload[offset + 15], payload[offset + 16]]));
+                    offset += 17;
+                    Some(crate::render::Border { width: bw, color: bc, style: bs, dash_length, gap_length })
+                } else {
+                    None
+                };
+
+                let gradient = if has_gradient {
+                    match parse_batch_gradient(payload, &mut offset) {
+                        Some(g) => g,
+                        None => return Err(truncated(cmd_type)),
+                    }
+                } else {
+                    None
+                };
+
+                let pivot = if has_pivot {
+                    if offset + 8 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let px = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let py = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    offset += 8;
+                    Some((px, py))
+                } else {
+                    None
+                };
+
+                let inner_shadow = if has_inner_shadow {
+                    if offset + 20 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let blur = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let spread = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    let offset_x = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                    let offset_y = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                    let color = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
+                    offset += 20;
+                    Some(crate::render::InnerShadow { blur, spread, offset_x, offset_y, color })
+                } else {
+                    None
+                };
+
+                let shadow = if has_shadow {
+                    if offset + 20 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let offset_x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let offset_y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    let blur_radius = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                    let spread = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                    let color = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
+                    offset += 20;
+                    Some(crate::render::BoxShadow { offset_x, offset_y, blur_radius, spread, color })
+                } else {
+                    None
+                };
+
+                commands.push(RenderCommand::DrawRect {
+                    x, y, width, height, color,
+                    corner_radii: [r0, r1, r2, r3],
+                    rotation,
+                    pivot,
+                    border,
+                    gradient,
+                    inner_shadow,
+                    shadow,
+                });
+            }
+
+            // DrawText: x(4) + y(4) + text(4) + font_data + color(4) + layout_data + [gradient_data]
+            0x02 => {
+                if offset + 8 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                offset += 8;
+
+                let text = parse_batch_text(payload, &mut offset, &strings, cmd_type)?;
+
+                let font = parse_batch_font(payload, &mut offset, cmd_type)?;
+
+                // Color
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let color = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]);
+                offset += 4;
+
+                let (layout, has_gradient) = parse_batch_text_layout(payload, &mut offset, cmd_type)?;
+
+                let gradient = if has_gradient {
+                    match parse_batch_gradient(payload, &mut offset) {
+                        Some(g) => g,
+                        None => return Err(truncated(cmd_type)),
+                    }
+                } else {
+                    None
+                };
+
+                commands.push(RenderCommand::DrawText { x, y, text, font, color, layout, gradient });
+            }
+
+            // DrawImage: x(4) + y(4) + w(4) + h(4) + texture_id(4) + flags(1) + [source_rect(16)] + radii(16) + [opacity(4)] + [rotation(4)] + [pivot(8)] + [src(16)] + [tint(4)] + [sampling(1)]
+            0x03 => {
+                if offset + 21 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                let texture_id = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
+                let flags = payload[offset + 20];
+                offset += 21;
+
+                let has_source_rect = (flags & 0x01) != 0;
+                let has_opacity = (flags & 0x02) != 0;
+                let has_rotation = (flags & 0x04) != 0;
+                let has_pivot = (flags & 0x08) != 0;
+                let has_src = (flags & 0x10) != 0;
+                let has_tint = (flags & 0x20) != 0;
+                let has_sampling = (flags & 0x40) != 0;
+
+                let source_rect = if has_source_rect {
+                    if offset + 16 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let sx = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let sy = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    let sw = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                    let sh = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                    offset += 16;
+                    Some((sx, sy, sw, sh))
+                } else {
+                    None
+                };
+
+                if offset + 16 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let r0 = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                offset += 16;
+
+                let opacity = if has_opacity {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let opacity = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    offset += 4;
+                    opacity
+                } else {
+                    1.0
+                };
+
+                let rotation = if has_rotation {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let rotation = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    offset += 4;
+                    rotation
+                } else {
+                    0.0
+                };
+
+                let pivot = if has_pivot {
+                    if offset + 8 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let px = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let py = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    offset += 8;
+                    Some((px, py))
+                } else {
+                    None
+                };
+
+                let src = if has_src {
+                    if offset + 16 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let sx = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    let sy = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                    let sw = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                    let sh = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                    offset += 16;
+                    Some(crate::geometry::Rect::new(sx, sy, sw, sh))
+                } else {
+                    None
+                };
+
+                let tint = if has_tint {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let tint = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]);
+                    offset += 4;
+                    Some(tint)
+                } else {
+                    None
+                };
+
+                let sampling = if has_sampling {
+                    if offset + 1 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let sampling = Sampling::from(payload[offset]);
+                    offset += 1;
+                    sampling
+                } else {
+                    Sampling::default()
+                };
+
+                commands.push(RenderCommand::DrawImage {
+                    x, y, width, height, texture_id,
+                    source_rect,
+                    src,
+                    tint,
+                    corner_radii: [r0, r1, r2, r3],
+                    opacity,
+                    rotation,
+                    pivot,
+                    sampling,
+                });
+            }
+
+            // DrawShadow: x(4) + y(4) + w(4) + h(4) + blur(4) + color(4) + offset_x(4) + offset_y(4) + radii(16)
+            0x04 => {
+                if offset + 48 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                let blur = f32::from_bits(u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]));
+                let color = u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]);
+                let offset_x = f32::from_bits(u32::from_le_bytes([payload[offset + 24], payload[offset + 25], payload[offset + 26], payload[offset + 27]]));
+                let offset_y = f32::from_bits(u32::from_le_bytes([payload[offset + 28], payload[offset + 29], payload[offset + 30], payload[offset + 31]]));
+                let r0 = f32::from_bits(u32::from_le_bytes([payload[offset + 32], payload[offset + 33], payload[offset + 34], payload[offset + 35]]));
+                let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 36], payload[offset + 37], payload[offset + 38], payload[offset + 39]]));
+                let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 40], payload[offset + 41], payload[offset + 42], payload[offset + 43]]));
+                let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 44], payload[offset + 45], payload[offset + 46], payload[offset + 47]]));
+                offset += 48;
+
+                commands.push(RenderCommand::DrawShadow {
+                    x, y, width, height, blur, color,
+                    offset_x, offset_y,
+                    corner_radii: [r0, r1, r2, r3],
+                });
+            }
 
-    // Image commands (0x0100 - 0x01FF)
-    LoadImage = 0x0100,
-    LoadImageFile = 0x0101,
-    UnloadImage = 0x0102,
-    GetTextureSize = 0x0103,
+            // PushClip: x(4) + y(4) + w(4) + h(4)
+            0x05 => {
+                if offset + 16 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                offset += 16;
+                commands.push(RenderCommand::PushClip { x, y, width, height });
+            }
 
-    // Render commands (0x0200 - 0x02FF)
-    RenderFrame = 0x0200,
+            // PopClip: (no data)
+            0x06 => {
+                commands.push(RenderCommand::PopClip {});
+            }
 
-    // System queries (0x0300 - 0x03FF)
-    GetScaleFactor = 0x0300,
-    GetDarkMode = 0x0301,
+            // BeginScrollView: x(4) + y(4) + w(4) + h(4) + scroll_x(4) + scroll_y(4) + flags(1) + [content_w(4)] + [content_h(4)]
+            0x07 => {
+                if offset + 25 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                let scroll_x = f32::from_bits(u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]));
+                let scroll_y = f32::from_bits(u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]));
+                let flags = payload[offset + 24];
+                offset += 25;
+
+                let has_content_width = (flags & 0x01) != 0;
+                let has_content_height = (flags & 0x02) != 0;
+
+                let content_width = if has_content_width {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    offset += 4;
+                    Some(v)
+                } else {
+                    None
+                };
 
-    // Audio playback commands (0x0400 - 0x04FF)
-    AudioCreate = 0x0400,
-    AudioDestroy = 0x0401,
-    AudioLoadURL = 0x0402,
-    AudioLoadFile = 0x0403,
-    AudioPlay = 0x0404,
-    AudioPause = 0x0405,
-    AudioStop = 0x0406,
-    AudioSeek = 0x0407,
-    AudioSetVolume = 0x0408,
-    AudioSetLooping = 0x0409,
-    AudioGetState = 0x040A,
-    AudioGetTime = 0x040B,
-    AudioGetInfo = 0x040C,
-    AudioGetVolume = 0x040D,
-    AudioIsLooping = 0x040E,
-    AudioUpdate = 0x040F,
+                let content_height = if has_content_height {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                    offset += 4;
+                    Some(v)
+                } else {
+                    None
+                };
 
-    // Audio input commands (0x0500 - 0x05FF)
-    AudioInputCreate = 0x0500,
-    AudioInputDestroy = 0x0501,
-    AudioInputRequestPermission = 0x0502,
-    AudioInputHasPermission = 0x0503,
-    AudioInputListDevices = 0x0504,
-    AudioInputOpen = 0x0505,
-    AudioInputStart = 0x0506,
-    AudioInputStop = 0x0507,
-    AudioInputClose = 0x0508,
-    AudioInputGetLevel = 0x0509,
-    AudioInputGetState = 0x050A,
+                commands.push(RenderCommand::BeginScrollView {
+                    x, y, width, height, scroll_x, scroll_y, content_width, content_height,
+                });
+            }
 
-    // Video playback commands (0x0600 - 0x06FF)
-    VideoCreate = 0x0600,
-    VideoDestroy = 0x0601,
-    VideoLoadURL = 0x0602,
-    VideoLoadFile = 0x0603,
-    VideoInitStream = 0x0604,
-    VideoPushFrame = 0x0605,
-    VideoPlay = 0x0606,
-    VideoPause = 0x0607,
-    VideoSeek = 0x0608,
-    VideoSetLooping = 0x0609,
-    VideoSetMuted = 0x060A,
-    VideoSetVolume = 0x060B,
-    VideoGetState = 0x060C,
-    VideoGetTime = 0x060D,
-    VideoGetInfo = 0x060E,
-    VideoUpdate = 0x060F,
-    VideoGetTextureID = 0x0610,
+            // EndScrollView: (no data)
+            0x08 => {
+                commands.push(RenderCommand::EndScrollView {});
+            }
 
-    // Video input commands (0x0700 - 0x07FF)
-    VideoInputCreate = 0x0700,
-    VideoInputDestroy = 0x0701,
-    VideoInputRequestPermission = 0x0702,
-    VideoInputHasPermission = 0x0703,
-    VideoInputListDevices = 0x0704,
-    VideoInputOpen = 0x0705,
-    VideoInputStart = 0x0706,
-    VideoInputStop = 0x0707,
-    VideoInputClose = 0x0708,
-    VideoInputGetState = 0x0709,
-    VideoInputGetDimensions = 0x070A,
-    VideoInputGetFrameTexture = 0x070B,
+            // SetOpacity: opacity(4)
+            0x09 => {
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let opacity = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                offset += 4;
+                commands.push(RenderCommand::SetOpacity(opacity));
+            }
 
-    // Clipboard commands (0x0800 - 0x08FF)
-    ClipboardGet = 0x0800,
-    ClipboardSet = 0x0801,
+            // Caret: x(4) + y(4) + w(4) + h(4) + color(4) + blink_ms(4)
+            0x0A => {
+                if offset + 24 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
+                let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
+                let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
+                let color = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
+                let blink_ms = u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]);
+                offset += 24;
+                commands.push(RenderCommand::Caret { x, y, width, height, color, blink_ms });
+            }
 
-    // App lifecycle (0xFF00 - 0xFFFF)
-    RequestRedraw = 0xFF00,
-    RequestExit = 0xFF01,
-}
+            // FocusRing: x(4) + y(4) + w(4) + h(4) + radii(16) + color(4) + thickness(4) + dash(4) + offset(4) + speed(4)
+            0x0B => {
+                if offset + 52 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let read_f32 = |at: usize| f32::from_bits(u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]));
+                let rect = crate::geometry::Rect::new(
+                    read_f32(offset), read_f32(offset + 4), read_f32(offset + 8), read_f32(offset + 12),
+                );
+                let corner_radii = [read_f32(offset + 16), read_f32(offset + 20), read_f32(offset + 24), read_f32(offset + 28)];
+                let color = u32::from_le_bytes([payload[offset + 32], payload[offset + 33], payload[offset + 34], payload[offset + 35]]);
+                let thickness = read_f32(offset + 36);
+                let dash = read_f32(offset + 40);
+                let ring_offset = read_f32(offset + 44);
+                let speed = read_f32(offset + 48);
+                offset += 52;
+                commands.push(RenderCommand::FocusRing { rect, corner_radii, color, thickness, dash, offset: ring_offset, speed });
+            }
 
-/// Response types for the binary protocol.
-/// Using u8 since we don't need as many response types.
-#[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum BatchResponseType {
-    Success = 0,
-    Error = 1,
-    Float32 = 2,
-    Int32 = 3,
-    Uint32 = 4,
-    Uint64 = 5,
-    String = 6,
-    Bytes = 7,
-    Bool = 8,
-    Float32Array = 9,
-    Uint32Pair = 10,     // For texture size, dimensions
-    Uint32Triple = 11,   // For audio info (duration, sample_rate, channels)
-    VideoInfo = 12,      // For video info (width, height, duration)
-}
+            // DrawLine: x1(4) + y1(4) + x2(4) + y2(4) + width(4) + color(4) + cap(1)
+            0x0C => {
+                if offset + 25 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let read_f32 = |at: usize| f32::from_bits(u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]));
+                let x1 = read_f32(offset);
+                let y1 = read_f32(offset + 4);
+                let x2 = read_f32(offset + 8);
+                let y2 = read_f32(offset + 12);
+                let width = read_f32(offset + 16);
+                let color = u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]);
+                let cap = LineCap::from(payload[offset + 24]);
+                offset += 25;
+                commands.push(RenderCommand::DrawLine { x1, y1, x2, y2, width, color, cap });
+            }
 
-/// Execute a batch of binary commands.
-///
-/// Buffer format (request):
-///   count: u32           - Number of commands
-///   For each command:
-///     cmd_type: u16      - Command type (little-endian)
-///     payload_len: u32   - Length of payload
-///     payload: [u8]      - Command-specific payload
-///
-/// Buffer format (response):
-///   count: u32           - Number of responses
-///   For each response:
-///     resp_type: u8      - Response type
-///     payload_len: u32   - Length of payload
-///     payload: [u8]      - Response-specific payload
-///
-/// # Safety
-/// - request_ptr must point to valid memory of at least request_len bytes
-/// - response_ptr must point to valid memory of at least response_capacity bytes
-/// - response_len_out must point to valid u32
-#[cfg(not(target_arch = "wasm32"))]
-#[no_mangle]
-pub unsafe extern "C" fn centered_execute_batch(
-    request_ptr: *const u8,
-    request_len: usize,
-    response_ptr: *mut u8,
-    response_capacity: usize,
-    response_len_out: *mut usize,
-) -> i32 {
-    if request_ptr.is_null() || response_ptr.is_null() || response_len_out.is_null() {
-        return -1;
-    }
+            // DrawRichText: x(4) + y(4) + layout_data + [gradient_data] + run_count(4) + runs
+            0x0D => {
+                let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+                if offset + 8 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let x = f32::from_bits(read_u32(offset));
+                let y = f32::from_bits(read_u32(offset + 4));
+                offset += 8;
+
+                let (layout, has_gradient) = parse_batch_text_layout(payload, &mut offset, cmd_type)?;
+                let gradient = if has_gradient {
+                    match parse_batch_gradient(payload, &mut offset) {
+                        Some(g) => g,
+                        None => return Err(truncated(cmd_type)),
+                    }
+                } else {
+                    None
+                };
 
-    if request_len < 4 {
-        return -1;
-    }
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let run_count = read_u32(offset) as usize;
+                offset += 4;
 
-    let request = std::slice::from_raw_parts(request_ptr, request_len);
-    let response = std::slice::from_raw_parts_mut(response_ptr, response_capacity);
+                let mut runs = Vec::new();
+                for _ in 0..run_count {
+                    if offset + 1 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let kind = payload[offset];
+                    offset += 1;
 
-    // Read command count
-    let cmd_count = u32::from_le_bytes([request[0], request[1], request[2], request[3]]) as usize;
+                    if kind == 0 {
+                        let text = parse_batch_text(payload, &mut offset, &strings, cmd_type)?;
 
-    // Process commands and build responses
-    let mut req_offset = 4;
-    let mut resp_offset = 4; // Reserve space for response count
+                        let font = parse_batch_font(payload, &mut offset, cmd_type)?;
+                        if offset + 4 > payload.len() {
+                            return Err(truncated(cmd_type));
+                        }
+                        let color = read_u32(offset);
+                        offset += 4;
+                        runs.push(TextRun::Text { text, color, font });
+                    } else {
+                        if offset + 16 > payload.len() {
+                            return Err(truncated(cmd_type));
+                        }
+                        runs.push(TextRun::Image {
+                            image_texture_id: read_u32(offset),
+                            width: f32::from_bits(read_u32(offset + 4)),
+                            height: f32::from_bits(read_u32(offset + 8)),
+                            baseline_offset: f32::from_bits(read_u32(offset + 12)),
+                        });
+                        offset += 16;
+                    }
+                }
+                commands.push(RenderCommand::DrawRichText { x, y, runs, layout, gradient });
+            }
 
-    for _ in 0..cmd_count {
-        if req_offset + 6 > request_len {
-            break;
-        }
+            // PushTransform: a(4) + b(4) + c(4) + d(4) + e(4) + f(4)
+            0x0E => {
+                if offset + 24 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let mut matrix = [0.0f32; 6];
+                for (i, value) in matrix.iter_mut().enumerate() {
+                    let at = offset + i * 4;
+                    *value = f32::from_bits(u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]));
+                }
+                offset += 24;
+                commands.push(RenderCommand::PushTransform { matrix });
+            }
 
-        // Read command type (u16)
-        let cmd_type = u16::from_le_bytes([request[req_offset], request[req_offset + 1]]);
-        req_offset += 2;
+            // PopTransform: (no data)
+            0x0F => {
+                commands.push(RenderCommand::PopTransform {});
+            }
 
-        let payload_len = u32::from_le_bytes([
-            request[req_offset],
-            request[req_offset + 1],
-            request[req_offset + 2],
-            request[req_offset + 3],
-        ]) as usize;
-        req_offset += 4;
+            // PushRoundedClip: x(4) + y(4) + w(4) + h(4) + radii(16)
+            0x10 => {
+                if offset + 32 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let mut values = [0.0f32; 8];
+                for (i, value) in values.iter_mut().enumerate() {
+                    let at = offset + i * 4;
+                    *value = f32::from_bits(u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]));
+                }
+                offset += 32;
+                let [x, y, width, height, r0, r1, r2, r3] = values;
+                commands.push(RenderCommand::PushRoundedClip { x, y, width, height, corner_radii: [r0, r1, r2, r3] });
+            }
 
-        if req_offset + payload_len > request_len {
-            break;
-        }
+            // DrawArc: cx(4) + cy(4) + radius(4) + start_angle(4) + end_angle(4) + thickness(4) + color(4)
+            0x11 => {
+                if offset + 28 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+                let read_f32 = |at: usize| f32::from_bits(read_u32(at));
+                let cx = read_f32(offset);
+                let cy = read_f32(offset + 4);
+                let radius = read_f32(offset + 8);
+                let start_angle = read_f32(offset + 12);
+                let end_angle = read_f32(offset + 16);
+                let thickness = read_f32(offset + 20);
+                let color = read_u32(offset + 24);
+                offset += 28;
+                commands.push(RenderCommand::DrawArc { cx, cy, radius, start_angle, end_angle, thickness, color });
+            }
 
-        let payload = &request[req_offset..req_offset + payload_len];
-        req_offset += payload_len;
+            // DrawNinePatch: texture_id(4) + x(4) + y(4) + w(4) + h(4) + insets(16)
+            0x12 => {
+                if offset + 36 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+                let read_f32 = |at: usize| f32::from_bits(read_u32(at));
+                let texture_id = read_u32(offset);
+                let dest_rect = crate::geometry::Rect::new(read_f32(offset + 4), read_f32(offset + 8), read_f32(offset + 12), read_f32(offset + 16));
+                let insets = [read_f32(offset + 20), read_f32(offset + 24), read_f32(offset + 28), read_f32(offset + 32)];
+                offset += 36;
+                commands.push(RenderCommand::DrawNinePatch { texture_id, dest_rect, insets });
+            }
 
-        // Execute command and write response
-        let (resp_type, resp_payload) = execute_single_command(cmd_type, payload);
+            // DrawPath: flags(1) + [fill(4)] + [stroke(10)] + segment_count(4) + segments
+            0x13 => {
+                let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+                let read_f32 = |at: usize| f32::from_bits(read_u32(at));
+                if offset + 1 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let flags = payload[offset];
+                offset += 1;
 
-        // Write response type
-        if resp_offset + 5 + resp_payload.len() > response_capacity {
-            // Response buffer full - return error
-            *response_len_out = 0;
-            return -2;
-        }
+                let mut fill = None;
+                if flags & 0x01 != 0 {
+                    if offset + 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    fill = Some(read_u32(offset));
+                    offset += 4;
+                }
+                let mut stroke = None;
+                if flags & 0x02 != 0 {
+                    if offset + 10 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    stroke = Some(Stroke {
+                        width: read_f32(offset),
+                        color: read_u32(offset + 4),
+                        cap: LineCap::from(payload[offset + 8]),
+                        join: LineJoin::from(payload[offset + 9]),
+                    });
+                    offset += 10;
+                }
+                let fill_rule = if flags & 0x04 != 0 { FillRule::EvenOdd } else { FillRule::NonZero };
 
-        response[resp_offset] = resp_type as u8;
-        resp_offset += 1;
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let segment_count = read_u32(offset) as usize;
+                offset += 4;
 
-        // Write response payload length
-        let resp_payload_len = resp_payload.len() as u32;
-        response[resp_offset..resp_offset + 4].copy_from_slice(&resp_payload_len.to_le_bytes());
-        resp_offset += 4;
+                let mut segments = Vec::new();
+                for _ in 0..segment_count {
+                    if offset + 1 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let verb = payload[offset];
+                    offset += 1;
+                    let points = match verb {
+                        0 | 1 => 2,
+                        2 => 4,
+                        3 => 6,
+                        4 => 0,
+                        _ => return Err(format!("unknown path segment {} in render command {:#04x}", verb, cmd_type)),
+                    };
+                    if offset + points * 4 > payload.len() {
+                        return Err(truncated(cmd_type));
+                    }
+                    let p = |i: usize| read_f32(offset + i * 4);
+                    segments.push(match verb {
+                        0 => PathSegment::MoveTo { x: p(0), y: p(1) },
+                        1 => PathSegment::LineTo { x: p(0), y: p(1) },
+                        2 => PathSegment::QuadTo { cx: p(0), cy: p(1), x: p(2), y: p(3) },
+                        3 => PathSegment::CubicTo { c1x: p(0), c1y: p(1), c2x: p(2), c2y: p(3), x: p(4), y: p(5) },
+                        _ => PathSegment::Close {},
+                    });
+                    offset += points * 4;
+                }
+                commands.push(RenderCommand::DrawPath { segments, fill, fill_rule, stroke });
+            }
 
-        // Write response payload
-        response[resp_offset..resp_offset + resp_payload.len()].copy_from_slice(&resp_payload);
-        resp_offset += resp_payload.len();
-    }
+            // PushOpacityLayer: alpha(4)
+            0x14 => {
+                if offset + 4 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let alpha = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
+                offset += 4;
+                commands.push(RenderCommand::PushOpacityLayer(alpha));
+            }
 
-    // Write response count at the beginning
-    response[0..4].copy_from_slice(&(cmd_count as u32).to_le_bytes());
+            // PopOpacityLayer: (no data)
+            0x15 => {
+                commands.push(RenderCommand::PopOpacityLayer {});
+            }
+
+            // Spinner: cx(4) + cy(4) + radius(4) + color(4) + speed(4) + thickness(4)
+            0x16 => {
+                if offset + 24 > payload.len() {
+                    return Err(truncated(cmd_type));
+                }
+                let read_u32 = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+                let read_f32 = |at: usize| f32::from_bits(read_u32(at));
+                let center_x = read_f32(offset);
+                let center_y = read_f32(offset + 4);
+                let radius = read_f32(offset + 8);
+                let color = read_u32(offset + 12);
+                let speed = read_f32(offset + 16);
+                let thickness = read_f32(offset + 20);
+                offset += 24;
+                commands.push(RenderCommand::Spinner { center_x, center_y, radius, color, speed, thickness });
+            }
+
+            // Unknown command type
+            _ => {
+                return Err(format!("unknown render command type: {}", cmd_type));
+            }
+        }
+    }
 
-    *response_len_out = resp_offset;
-    0
+    Ok(commands)
 }
 
 /// Execute a single command and return the response type and payload.
@@ -10472,472 +15565,12 @@ fn execute_single_command(cmd_type: u16, payload: &[u8]) -> (BatchResponseType,
         // Render Commands (0x0200)
         // ========================================================================
 
-        // RenderFrame (0x0200) - Binary render commands
-        // Payload: command_count(4) + [command_type(1) + command_data]...
-        //
-        // Command types:
-        //   0x00 - Clear: r(1) + g(1) + b(1) + a(1)
-        //   0x01 - DrawRect: x(4) + y(4) + w(4) + h(4) + color(4) + radii(16) + rotation(4) + flags(1) + [border_w(4) + border_color(4) + border_style(1)] + [gradient_data]
-        //   0x02 - DrawText: x(4) + y(4) + text_len(4) + text + font_data + color(4) + layout_data
-        //   0x03 - DrawImage: x(4) + y(4) + w(4) + h(4) + texture_id(4) + flags(1) + [source_rect(16)] + radii(16)
-        //   0x04 - DrawShadow: x(4) + y(4) + w(4) + h(4) + blur(4) + color(4) + offset_x(4) + offset_y(4) + radii(16)
-        //   0x05 - PushClip: x(4) + y(4) + w(4) + h(4)
-        //   0x06 - PopClip: (no data)
-        //   0x07 - BeginScrollView: x(4) + y(4) + w(4) + h(4) + scroll_x(4) + scroll_y(4) + flags(1) + [content_w(4)] + [content_h(4)]
-        //   0x08 - EndScrollView: (no data)
-        //   0x09 - SetOpacity: opacity(4)
+        // RenderFrame (0x0200) - Binary render commands (see decode_render_commands)
         0x0200 => {
-            if payload.len() < 4 {
-                return (BatchResponseType::Error, vec![]);
-            }
-
-            let command_count = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
-            let mut offset = 4;
-            let mut commands: Vec<RenderCommand> = Vec::with_capacity(command_count);
-
-            for _ in 0..command_count {
-                if offset >= payload.len() {
-                    return (BatchResponseType::Error, b"unexpected end of payload".to_vec());
-                }
-
-                let cmd_type = payload[offset];
-                offset += 1;
-
-                match cmd_type {
-                    // Clear: r(1) + g(1) + b(1) + a(1)
-                    0x00 => {
-                        if offset + 4 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let r = payload[offset];
-                        let g = payload[offset + 1];
-                        let b = payload[offset + 2];
-                        let a = payload[offset + 3];
-                        offset += 4;
-                        commands.push(RenderCommand::Clear(crate::style::Color { r, g, b, a }));
-                    }
-
-                    // DrawRect: x(4) + y(4) + w(4) + h(4) + color(4) + radii(16) + rotation(4) + flags(1) + [border] + [gradient]
-                    0x01 => {
-                        if offset + 41 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        let color = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
-                        let r0 = f32::from_bits(u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]));
-                        let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 24], payload[offset + 25], payload[offset + 26], payload[offset + 27]]));
-                        let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 28], payload[offset + 29], payload[offset + 30], payload[offset + 31]]));
-                        let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 32], payload[offset + 33], payload[offset + 34], payload[offset + 35]]));
-                        let rotation = f32::from_bits(u32::from_le_bytes([payload[offset + 36], payload[offset + 37], payload[offset + 38], payload[offset + 39]]));
-                        let flags = payload[offset + 40];
-                        offset += 41;
-
-                        let has_border = (flags & 0x01) != 0;
-                        let has_gradient = (flags & 0x02) != 0;
-
-                        let border = if has_border {
-                            if offset + 9 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let bw = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            let bc = u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]);
-                            let bs = match payload[offset + 8] {
-                                1 => crate::render::BorderStyle::Dashed,
-                                2 => crate::render::BorderStyle::Dotted,
-                                _ => crate::render::BorderStyle::Solid,
-                            };
-                            offset += 9;
-                            Some(crate::render::Border { width: bw, color: bc, style: bs })
-                        } else {
-                            None
-                        };
-
-                        let gradient = if has_gradient {
-                            if offset + 1 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let grad_type = payload[offset];
-                            offset += 1;
-
-                            match grad_type {
-                                // Linear gradient: angle(4) + stop_count(1) + stops(position(4) + color(4))...
-                                0 => {
-                                    if offset + 5 > payload.len() {
-                                        return (BatchResponseType::Error, vec![]);
-                                    }
-                                    let angle = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                                    let stop_count = payload[offset + 4] as usize;
-                                    offset += 5;
-
-                                    if offset + stop_count * 8 > payload.len() {
-                                        return (BatchResponseType::Error, vec![]);
-                                    }
-                                    let mut stops = Vec::with_capacity(stop_count);
-                                    for _ in 0..stop_count {
-                                        let pos = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                                        let col = u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]);
-                                        offset += 8;
-                                        stops.push(crate::render::GradientStop { position: pos, color: col });
-                                    }
-                                    Some(crate::render::Gradient::Linear { angle, stops })
-                                }
-                                // Radial gradient: center_x(4) + center_y(4) + stop_count(1) + stops...
-                                1 => {
-                                    if offset + 9 > payload.len() {
-                                        return (BatchResponseType::Error, vec![]);
-                                    }
-                                    let center_x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                                    let center_y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                                    let stop_count = payload[offset + 8] as usize;
-                                    offset += 9;
-
-                                    if offset + stop_count * 8 > payload.len() {
-                                        return (BatchResponseType::Error, vec![]);
-                                    }
-                                    let mut stops = Vec::with_capacity(stop_count);
-                                    for _ in 0..stop_count {
-                                        let pos = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                                        let col = u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]);
-                                        offset += 8;
-                                        stops.push(crate::render::GradientStop { position: pos, color: col });
-                                    }
-                                    Some(crate::render::Gradient::Radial { center_x, center_y, stops })
-                                }
-                                _ => None,
-                            }
-                        } else {
-                            None
-                        };
-
-                        commands.push(RenderCommand::DrawRect {
-                            x, y, width, height, color,
-                            corner_radii: [r0, r1, r2, r3],
-                            rotation,
-                            border,
-                            gradient,
-                        });
-                    }
-
-                    // DrawText: x(4) + y(4) + text_len(4) + text + font_data + color(4) + layout_data
-                    0x02 => {
-                        if offset + 12 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let text_len = u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]) as usize;
-                        offset += 12;
-
-                        if offset + text_len > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let text = match std::str::from_utf8(&payload[offset..offset + text_len]) {
-                            Ok(s) => s.to_string(),
-                            Err(_) => return (BatchResponseType::Error, vec![]),
-                        };
-                        offset += text_len;
-
-                        // Font descriptor: source_type(1) + name_len(4) + name + weight(2) + style(1) + size(4)
-                        if offset + 1 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let source_type = payload[offset];
-                        offset += 1;
-
-                        if offset + 4 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let font_name_len = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]) as usize;
-                        offset += 4;
-
-                        if offset + font_name_len > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let font_name = match std::str::from_utf8(&payload[offset..offset + font_name_len]) {
-                            Ok(s) => s.to_string(),
-                            Err(_) => return (BatchResponseType::Error, vec![]),
-                        };
-                        offset += font_name_len;
-
-                        if offset + 7 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let weight = u16::from_le_bytes([payload[offset], payload[offset + 1]]);
-                        let style = match payload[offset + 2] {
-                            1 => FontStyle::Italic,
-                            _ => FontStyle::Normal,
-                        };
-                        let size = f32::from_bits(u32::from_le_bytes([payload[offset + 3], payload[offset + 4], payload[offset + 5], payload[offset + 6]]));
-                        offset += 7;
-
-                        let source = match source_type {
-                            1 => FontSource::Bundled(font_name),
-                            _ => FontSource::System(font_name),
-                        };
-                        let font = FontDescriptor { source, weight, style, size };
-
-                        // Color
-                        if offset + 4 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let color = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]);
-                        offset += 4;
-
-                        // Layout config: flags(1) + [max_width(4)] + [max_height(4)] + [max_lines(4)] + line_height(4) + letter_spacing(4) + word_spacing(4) + alignment(1) + vertical_align(1) + word_break(1) + overflow(1) + white_space(1)
-                        if offset + 1 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let layout_flags = payload[offset];
-                        offset += 1;
-
-                        let has_max_width = (layout_flags & 0x01) != 0;
-                        let has_max_height = (layout_flags & 0x02) != 0;
-                        let has_max_lines = (layout_flags & 0x04) != 0;
-
-                        let max_width = if has_max_width {
-                            if offset + 4 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            offset += 4;
-                            Some(v)
-                        } else {
-                            None
-                        };
-
-                        let max_height = if has_max_height {
-                            if offset + 4 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            offset += 4;
-                            Some(v)
-                        } else {
-                            None
-                        };
-
-                        let max_lines = if has_max_lines {
-                            if offset + 4 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let v = u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]) as usize;
-                            offset += 4;
-                            Some(v)
-                        } else {
-                            None
-                        };
-
-                        if offset + 17 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let line_height = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let letter_spacing = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let word_spacing = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let alignment = match payload[offset + 12] {
-                            1 => TextAlign::Center,
-                            2 => TextAlign::Right,
-                            3 => TextAlign::Justify,
-                            _ => TextAlign::Left,
-                        };
-                        let vertical_align = match payload[offset + 13] {
-                            1 => VerticalAlign::Middle,
-                            2 => VerticalAlign::Bottom,
-                            3 => VerticalAlign::Baseline,
-                            _ => VerticalAlign::Top,
-                        };
-                        let word_break = match payload[offset + 14] {
-                            1 => WordBreak::BreakAll,
-                            2 => WordBreak::KeepAll,
-                            3 => WordBreak::BreakWord,
-                            _ => WordBreak::Normal,
-                        };
-                        let overflow = match payload[offset + 15] {
-                            1 => TextOverflow::Ellipsis,
-                            2 => TextOverflow::Wrap,
-                            _ => TextOverflow::Clip,
-                        };
-                        let white_space = match payload[offset + 16] {
-                            1 => WhiteSpace::NoWrap,
-                            2 => WhiteSpace::Pre,
-                            3 => WhiteSpace::PreWrap,
-                            _ => WhiteSpace::Normal,
-                        };
-                        offset += 17;
-
-                        let layout = TextLayoutConfig {
-                            max_width,
-                            max_height,
-                            max_lines,
-                            line_height,
-                            letter_spacing,
-                            word_spacing,
-                            alignment,
-                            vertical_align,
-                            word_break,
-                            overflow,
-                            white_space,
-                        };
-
-                        commands.push(RenderCommand::DrawText { x, y, text, font, color, layout });
-                    }
-
-                    // DrawImage: x(4) + y(4) + w(4) + h(4) + texture_id(4) + flags(1) + [source_rect(16)] + radii(16)
-                    0x03 => {
-                        if offset + 21 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        let texture_id = u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]);
-                        let flags = payload[offset + 20];
-                        offset += 21;
-
-                        let has_source_rect = (flags & 0x01) != 0;
-
-                        let source_rect = if has_source_rect {
-                            if offset + 16 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let sx = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            let sy = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                            let sw = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                            let sh = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                            offset += 16;
-                            Some((sx, sy, sw, sh))
-                        } else {
-                            None
-                        };
-
-                        if offset + 16 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let r0 = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        offset += 16;
-
-                        commands.push(RenderCommand::DrawImage {
-                            x, y, width, height, texture_id,
-                            source_rect,
-                            corner_radii: [r0, r1, r2, r3],
-                        });
-                    }
-
-                    // DrawShadow: x(4) + y(4) + w(4) + h(4) + blur(4) + color(4) + offset_x(4) + offset_y(4) + radii(16)
-                    0x04 => {
-                        if offset + 48 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        let blur = f32::from_bits(u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]));
-                        let color = u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]);
-                        let offset_x = f32::from_bits(u32::from_le_bytes([payload[offset + 24], payload[offset + 25], payload[offset + 26], payload[offset + 27]]));
-                        let offset_y = f32::from_bits(u32::from_le_bytes([payload[offset + 28], payload[offset + 29], payload[offset + 30], payload[offset + 31]]));
-                        let r0 = f32::from_bits(u32::from_le_bytes([payload[offset + 32], payload[offset + 33], payload[offset + 34], payload[offset + 35]]));
-                        let r1 = f32::from_bits(u32::from_le_bytes([payload[offset + 36], payload[offset + 37], payload[offset + 38], payload[offset + 39]]));
-                        let r2 = f32::from_bits(u32::from_le_bytes([payload[offset + 40], payload[offset + 41], payload[offset + 42], payload[offset + 43]]));
-                        let r3 = f32::from_bits(u32::from_le_bytes([payload[offset + 44], payload[offset + 45], payload[offset + 46], payload[offset + 47]]));
-                        offset += 48;
-
-                        commands.push(RenderCommand::DrawShadow {
-                            x, y, width, height, blur, color,
-                            offset_x, offset_y,
-                            corner_radii: [r0, r1, r2, r3],
-                        });
-                    }
-
-                    // PushClip: x(4) + y(4) + w(4) + h(4)
-                    0x05 => {
-                        if offset + 16 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        offset += 16;
-                        commands.push(RenderCommand::PushClip { x, y, width, height });
-                    }
-
-                    // PopClip: (no data)
-                    0x06 => {
-                        commands.push(RenderCommand::PopClip {});
-                    }
-
-                    // BeginScrollView: x(4) + y(4) + w(4) + h(4) + scroll_x(4) + scroll_y(4) + flags(1) + [content_w(4)] + [content_h(4)]
-                    0x07 => {
-                        if offset + 25 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let x = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        let y = f32::from_bits(u32::from_le_bytes([payload[offset + 4], payload[offset + 5], payload[offset + 6], payload[offset + 7]]));
-                        let width = f32::from_bits(u32::from_le_bytes([payload[offset + 8], payload[offset + 9], payload[offset + 10], payload[offset + 11]]));
-                        let height = f32::from_bits(u32::from_le_bytes([payload[offset + 12], payload[offset + 13], payload[offset + 14], payload[offset + 15]]));
-                        let scroll_x = f32::from_bits(u32::from_le_bytes([payload[offset + 16], payload[offset + 17], payload[offset + 18], payload[offset + 19]]));
-                        let scroll_y = f32::from_bits(u32::from_le_bytes([payload[offset + 20], payload[offset + 21], payload[offset + 22], payload[offset + 23]]));
-                        let flags = payload[offset + 24];
-                        offset += 25;
-
-                        let has_content_width = (flags & 0x01) != 0;
-                        let has_content_height = (flags & 0x02) != 0;
-
-                        let content_width = if has_content_width {
-                            if offset + 4 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            offset += 4;
-                            Some(v)
-                        } else {
-                            None
-                        };
-
-                        let content_height = if has_content_height {
-                            if offset + 4 > payload.len() {
-                                return (BatchResponseType::Error, vec![]);
-                            }
-                            let v = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                            offset += 4;
-                            Some(v)
-                        } else {
-                            None
-                        };
-
-                        commands.push(RenderCommand::BeginScrollView {
-                            x, y, width, height, scroll_x, scroll_y, content_width, content_height,
-                        });
-                    }
-
-                    // EndScrollView: (no data)
-                    0x08 => {
-                        commands.push(RenderCommand::EndScrollView {});
-                    }
-
-                    // SetOpacity: opacity(4)
-                    0x09 => {
-                        if offset + 4 > payload.len() {
-                            return (BatchResponseType::Error, vec![]);
-                        }
-                        let opacity = f32::from_bits(u32::from_le_bytes([payload[offset], payload[offset + 1], payload[offset + 2], payload[offset + 3]]));
-                        offset += 4;
-                        commands.push(RenderCommand::SetOpacity(opacity));
-                    }
-
-                    // Unknown command type
-                    _ => {
-                        return (BatchResponseType::Error, format!("unknown render command type: {}", cmd_type).into_bytes());
-                    }
-                }
-            }
+            let commands = match decode_render_commands(payload) {
+                Ok(commands) => commands,
+                Err(e) => return (BatchResponseType::Error, e.into_bytes()),
+            };
 
             // Execute the render commands via the backend
             let backend_lock = get_backend();
@@ -10996,8 +15629,11 @@ fn execute_single_command(cmd_type: u16, payload: &[u8]) -> (BatchResponseType,
                                             color: ((color.r as u32) << 24) | ((color.g as u32) << 16) | ((color.b as u32) << 8) | (color.a as u32),
                                             corner_radii: [0.0, 0.0, 0.0, 0.0],
                                             rotation: 0.0,
+                                            pivot: None,
                                             border: None,
                                             gradient: None,
+                                            inner_shadow: None,
+                                            shadow: None,
                                         };
                                         all_commands.insert(insert_pos + 1, bg_rect);
                                     }
@@ -11041,7 +15677,9 @@ fn execute_single_command(cmd_type: u16, payload: &[u8]) -> (BatchResponseType,
                         #[cfg(not(any(target_os = "linux", target_os = "windows")))]
                         let final_commands = commands;
 
-                        match backend.render_frame(&final_commands) {
+                        let result = backend.render_frame(&final_commands);
+                        record_render_result(&result);
+                        match result {
                             Ok(()) => (BatchResponseType::Success, vec![]),
                             Err(e) => (BatchResponseType::Error, format!("render error: {}", e).into_bytes()),
                         }
@@ -11062,6 +15700,138 @@ fn execute_single_command(cmd_type: u16, payload: &[u8]) -> (BatchResponseType,
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_layers_hit_test_respects_z_order_and_opacity() {
+        // A dialog over a list, overlapping at (150, 150)
+        let layers = r#"[
+            {"id": 7, "x": 100, "y": 100, "width": 200, "height": 200, "z_order": 5, "opaque": true, "dirty": false},
+            {"id": 3, "x": 0, "y": 0, "width": 400, "height": 400, "z_order": 1, "opaque": true, "dirty": false}
+        ]"#;
+        let layers = CString::new(layers).unwrap();
+        unsafe {
+            let mut id = 0u32;
+            assert_eq!(centered_layers_hit_test(layers.as_ptr(), 150.0, 150.0, &mut id), 0);
+            assert_eq!(id, 7);
+            assert_eq!(centered_layers_hit_test(layers.as_ptr(), 50.0, 50.0, &mut id), 0);
+            assert_eq!(id, 3);
+            assert_eq!(centered_layers_hit_test(layers.as_ptr(), 500.0, 500.0, &mut id), EngineError::NotFound.code());
+            assert_eq!(centered_layers_hit_test(layers.as_ptr(), 50.0, 50.0, ptr::null_mut()), EngineError::InvalidArgument.code());
+
+            // The opaque dialog hides the list
+            let mut ids = [0u32; 4];
+            assert_eq!(centered_layers_hit_test_stack(layers.as_ptr(), 150.0, 150.0, ids.as_mut_ptr(), 4), 1);
+            assert_eq!(ids[0], 7);
+
+            let bad = CString::new("not json").unwrap();
+            assert_eq!(centered_layers_hit_test(bad.as_ptr(), 0.0, 0.0, &mut id), EngineError::InvalidArgument.code());
+
+            // Ids past i32::MAX come back intact rather than as error codes
+            let high = CString::new(r#"[{"id": 4000000000, "x": 0, "y": 0, "width": 10, "height": 10, "z_order": 0, "opaque": true, "dirty": false}]"#).unwrap();
+            assert_eq!(centered_layers_hit_test(high.as_ptr(), 5.0, 5.0, &mut id), 0);
+            assert_eq!(id, 4_000_000_000);
+        }
+
+        // A transparent overlay passes the point through to the list
+        let overlay = r#"[
+            {"id": 3, "x": 0, "y": 0, "width": 400, "height": 400, "z_order": 1, "opaque": true, "dirty": false},
+            {"id": 9, "x": 0, "y": 0, "width": 400, "height": 400, "z_order": 2, "opaque": false, "dirty": false}
+        ]"#;
+        let layers: Vec<LayerInfo> = serde_json::from_str(overlay).unwrap();
+        assert_eq!(layers_at_point(&layers, 10.0, 10.0), vec![9, 3]);
+    }
+
+    /// Held by tests that install and use the global backend
+    static BACKEND_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_load_images_returns_ids_in_order() {
+        let _lock = BACKEND_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(backend) = crate::platform::wgpu_backend::tests::offscreen_backend(16, 16) else { return };
+        set_backend(backend);
+
+        let png = |rgba: [u8; 4]| {
+            let mut bytes = Vec::new();
+            ::image::RgbaImage::from_pixel(2, 2, ::image::Rgba(rgba))
+                .write_to(&mut std::io::Cursor::new(&mut bytes), ::image::ImageFormat::Png)
+                .unwrap();
+            bytes
+        };
+        let files = [png([255, 0, 0, 255]), png([0, 255, 0, 255]), png([0, 0, 255, 255])];
+        let images: Vec<FFIImageData> = files.iter()
+            .map(|file| FFIImageData { data_ptr: file.as_ptr(), data_len: file.len() })
+            .collect();
+
+        let mut ids = [0i32; 3];
+        assert_eq!(unsafe { centered_backend_load_images(images.as_ptr(), 3, ids.as_mut_ptr()) }, 3);
+        assert!(ids[0] > 0 && ids[0] < ids[1] && ids[1] < ids[2], "ids {:?}", ids);
+
+        // A bad entry fails on its own without stopping the batch
+        let garbage = [1u8, 2, 3];
+        let mixed = [
+            images[0],
+            FFIImageData { data_ptr: garbage.as_ptr(), data_len: garbage.len() },
+            FFIImageData { data_ptr: ptr::null(), data_len: 0 },
+        ];
+        assert_eq!(unsafe { centered_backend_load_images(mixed.as_ptr(), 3, ids.as_mut_ptr()) }, 1);
+        assert!(ids[0] > 0);
+        assert_eq!(ids[1..], [EngineError::OperationFailed.code(), EngineError::InvalidArgument.code()]);
+    }
+
+    #[test]
+    fn test_image_upload_flags() {
+        assert_eq!(image_upload_options(0), Some(ImageUploadOptions::default()));
+        assert_eq!(
+            image_upload_options(IMAGE_PREMULTIPLY | IMAGE_NO_MIPMAPS),
+            Some(ImageUploadOptions { premultiply: true, mipmaps: false })
+        );
+
+        // Unknown bits are rejected before the data is looked at
+        let code = unsafe { centered_backend_load_image_with_options(ptr::null(), 0, 0x80) };
+        assert_eq!(code, EngineError::InvalidArgument.code());
+
+        let _lock = BACKEND_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(backend) = crate::platform::wgpu_backend::tests::offscreen_backend(16, 16) else { return };
+        set_backend(backend);
+
+        // An antialiased edge: an opaque white pixel next to a transparent
+        // black one, stretched over white. Premultiplied, the filtered edge
+        // stays white; straight alpha mixes in the black.
+        let mut png = Vec::new();
+        ::image::RgbaImage::from_raw(2, 1, vec![255, 255, 255, 255, 0, 0, 0, 0])
+            .unwrap()
+            .write_to(&mut std::io::Cursor::new(&mut png), ::image::ImageFormat::Png)
+            .unwrap();
+        let load = |flags| unsafe { centered_backend_load_image_with_options(png.as_ptr(), png.len(), flags) };
+        let (straight, premultiplied) = (load(0), load(IMAGE_PREMULTIPLY));
+        assert!(straight > 0 && premultiplied > 0, "ids {} {}", straight, premultiplied);
+
+        let edge_pixel = |texture_id: i32| {
+            let commands = [
+                RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 }),
+                RenderCommand::DrawImage {
+                    x: 0.0, y: 0.0, width: 16.0, height: 16.0,
+                    texture_id: texture_id as u32,
+                    source_rect: None,
+                    src: None,
+                    tint: None,
+                    corner_radii: [0.0; 4],
+                    opacity: 1.0,
+                    rotation: 0.0,
+                    pivot: None,
+                    sampling: Sampling::default(),
+                },
+            ];
+            let mut guard = get_backend().lock().unwrap();
+            let pixels = guard.as_mut().unwrap().render_to_pixels(&commands, 16, 16).unwrap();
+            let at = 8 * 4;
+            [pixels[at], pixels[at + 1], pixels[at + 2], pixels[at + 3]]
+        };
+        let pixel = edge_pixel(premultiplied);
+        assert!(pixel[..3].iter().all(|&c| c >= 253), "premultiplied edge {:?}", pixel);
+        let pixel = edge_pixel(straight);
+        assert!(pixel[0] < 250, "straight edge {:?}", pixel);
+    }
+
     #[test]
     fn test_version() {
         let version = centered_engine_version();
@@ -11072,6 +15842,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_error_string_describes_every_code() {
+        for error in EngineError::ALL {
+            let message = unsafe { CStr::from_ptr(centered_error_string(error.code())) };
+            assert_eq!(message.to_str().unwrap(), error.to_string());
+            assert!(!message.is_empty());
+        }
+        let unknown = unsafe { CStr::from_ptr(centered_error_string(-1000)) };
+        assert_eq!(unknown.to_str().unwrap(), "unknown error");
+    }
+
     #[test]
     fn test_engine_lifecycle() {
         let config = EngineConfig::default();
@@ -11088,4 +15869,551 @@ mod tests {
             centered_engine_destroy(handle);
         }
     }
+
+    #[test]
+    fn test_text_move_caret_over_zwj_sequence() {
+        // "a" + man/woman/girl/boy family (7 code points) + "b"
+        let text = CString::new("a\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}b").unwrap();
+        unsafe {
+            assert_eq!(centered_text_move_caret(text.as_ptr(), 1, 1), 8);
+            assert_eq!(centered_text_move_caret(text.as_ptr(), 8, -1), 1);
+            assert_eq!(centered_text_move_caret(std::ptr::null(), 0, 1), EngineError::InvalidArgument.code());
+        }
+    }
+
+    #[test]
+    fn test_register_font_memory_dedupes_identical_bytes() {
+        let name = "No Such FFI Font 7f3a";
+        let data: Vec<u8> = b"not really a font 7f3a".to_vec();
+        unsafe {
+            let first = centered_register_font_memory(name.as_ptr(), name.len(), data.as_ptr(), data.len());
+            let second = centered_register_font_memory(name.as_ptr(), name.len(), data.as_ptr(), data.len());
+            assert_ne!(first, 0);
+            assert_eq!(first, second);
+            assert_eq!(get_font_manager().lock().unwrap().font_data(first), Some(data.as_slice()));
+
+            assert_eq!(centered_register_font_memory(name.as_ptr(), name.len(), std::ptr::null(), 0), 0);
+            let invalid = [0xFFu8];
+            assert_eq!(centered_register_font_memory(invalid.as_ptr(), 1, data.as_ptr(), data.len()), 0);
+
+            assert_eq!(centered_unregister_font(first), 0);
+            assert!(get_font_manager().lock().unwrap().font_data(first).is_some());
+            assert_eq!(centered_unregister_font(first), 0);
+            assert!(get_font_manager().lock().unwrap().font_data(first).is_none());
+            assert_eq!(centered_unregister_font(first), EngineError::InvalidArgument.code());
+        }
+    }
+
+    #[test]
+    fn test_text_base_direction() {
+        let rtl = CString::new("مرحبا").unwrap();
+        let ltr = CString::new("Hello").unwrap();
+        unsafe {
+            assert_eq!(centered_text_base_direction(rtl.as_ptr()), 1);
+            assert_eq!(centered_text_base_direction(ltr.as_ptr()), 0);
+            assert_eq!(centered_text_base_direction(std::ptr::null()), 0);
+        }
+    }
+
+    #[test]
+    fn test_ffi_draw_text_max_lines() {
+        let text = "one\ntwo\nthree\nfour\nfive";
+        let font = "system";
+        let mut cmd = FFIDrawTextCommand {
+            x: 0.0,
+            y: 0.0,
+            text_ptr: text.as_ptr(),
+            text_len: text.len(),
+            font_source_type: FFIFontSourceType::System as u8,
+            font_name_ptr: font.as_ptr(),
+            font_name_len: font.len(),
+            font_data_hash: 0,
+            font_weight: 400,
+            font_style: 0,
+            font_size: 14.0,
+            color: 0x000000FF,
+            max_width: 0.0,
+            max_height: 0.0,
+            max_lines: 2,
+            line_height: 1.2,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            alignment: 0,
+            vertical_align: 0,
+            anchor: TextAnchor::Baseline as u8,
+            word_break: 0,
+            overflow: TextOverflow::Ellipsis as u8,
+            white_space: 0,
+            base_direction: TextDirection::Rtl as u8,
+            gradient_ptr: ptr::null(),
+            gradient_len: 0,
+        };
+
+        let RenderCommand::DrawText { layout, gradient, .. } = (unsafe { cmd.to_render_command() }) else {
+            panic!("expected DrawText");
+        };
+        assert_eq!(gradient, None);
+        assert_eq!(layout.max_lines, Some(2));
+        assert_eq!(layout.overflow, TextOverflow::Ellipsis);
+        assert_eq!(layout.base_direction, TextDirection::Rtl);
+        assert_eq!(layout.anchor, TextAnchor::Baseline);
+
+        // 0 means unlimited
+        cmd.max_lines = 0;
+        let RenderCommand::DrawText { layout, .. } = (unsafe { cmd.to_render_command() }) else {
+            panic!("expected DrawText");
+        };
+        assert_eq!(layout.max_lines, None);
+
+        // A linear gradient at 90 degrees from red to blue
+        let mut gradient_data = vec![0];
+        gradient_data.extend_from_slice(&90.0f32.to_le_bytes());
+        gradient_data.push(2);
+        for (position, color) in [(0.0f32, 0xFF0000FFu32), (1.0, 0x0000FFFF)] {
+            gradient_data.extend_from_slice(&position.to_le_bytes());
+            gradient_data.extend_from_slice(&color.to_le_bytes());
+        }
+        cmd.gradient_ptr = gradient_data.as_ptr();
+        cmd.gradient_len = gradient_data.len();
+        let RenderCommand::DrawText { gradient, .. } = (unsafe { cmd.to_render_command() }) else {
+            panic!("expected DrawText");
+        };
+        assert_eq!(gradient, Some(crate::render::Gradient::Linear {
+            angle: 90.0,
+            stops: vec![
+                crate::render::GradientStop { position: 0.0, color: 0xFF0000FF },
+                crate::render::GradientStop { position: 1.0, color: 0x0000FFFF },
+            ],
+        }));
+
+        // Truncated data draws the flat color
+        cmd.gradient_len = 6;
+        let RenderCommand::DrawText { gradient, .. } = (unsafe { cmd.to_render_command() }) else {
+            panic!("expected DrawText");
+        };
+        assert_eq!(gradient, None);
+    }
+
+    #[test]
+    fn test_binary_frame_matches_json_frame() {
+        let commands = vec![
+            RenderCommand::Clear(crate::style::Color { r: 10, g: 20, b: 30, a: 255 }),
+            RenderCommand::DrawRect {
+                x: 1.0, y: 2.0, width: 30.0, height: 40.0, color: 0xFF0000FF,
+                corner_radii: [1.0, 2.0, 3.0, 4.0],
+                rotation: 0.5,
+                pivot: Some((0.25, 0.75)),
+                border: None,
+                gradient: None,
+                inner_shadow: None,
+                shadow: Some(crate::render::BoxShadow { offset_x: 2.0, offset_y: 3.0, blur_radius: 4.0, spread: 1.0, color: 0x00000080 }),
+            },
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 100.0, height: 50.0 },
+            RenderCommand::DrawText {
+                x: 5.0,
+                y: 6.0,
+                text: "héllo 👋".to_string(),
+                font: FontDescriptor::system("Inter", 600, FontStyle::Italic, 14.0),
+                color: 0x112233FF,
+                layout: TextLayoutConfig {
+                    max_width: Some(200.0),
+                    max_lines: Some(2),
+                    line_height: 1.25,
+                    alignment: TextAlign::Center,
+                    vertical_align: VerticalAlign::Middle,
+                    overflow: TextOverflow::Ellipsis,
+                    anchor: TextAnchor::Baseline,
+                    base_direction: TextDirection::Rtl,
+                    ..Default::default()
+                },
+                gradient: None,
+            },
+            RenderCommand::SetOpacity(0.5),
+            RenderCommand::DrawLine { x1: 0.0, y1: 0.0, x2: 10.0, y2: 10.0, width: 2.0, color: 0x00FF00FF, cap: LineCap::Round },
+            RenderCommand::DrawRichText {
+                x: 4.0,
+                y: 8.0,
+                runs: vec![
+                    TextRun::Text {
+                        text: "Hi ".to_string(),
+                        color: 0xFF0000FF,
+                        font: FontDescriptor {
+                            source: FontSource::Memory { name: "Brand".to_string(), data_hash: 0x0123_4567_89AB_CDEF },
+                            weight: 400,
+                            style: FontStyle::Normal,
+                            size: 12.0,
+                        },
+                    },
+                    TextRun::Image { image_texture_id: 3, width: 16.0, height: 16.0, baseline_offset: 2.0 },
+                ],
+                layout: TextLayoutConfig { max_width: Some(80.0), ..Default::default() },
+                gradient: Some(crate::render::Gradient::Radial {
+                    center_x: 0.5,
+                    center_y: 0.25,
+                    stops: vec![
+                        crate::render::GradientStop { position: 0.0, color: 0xFFFFFFFF },
+                        crate::render::GradientStop { position: 1.0, color: 0x000000FF },
+                    ],
+                }),
+            },
+            RenderCommand::PopClip {},
+            RenderCommand::PushTransform { matrix: [2.0, 0.0, 0.0, 2.0, 10.0, -5.0] },
+            RenderCommand::PopTransform {},
+            RenderCommand::PushOpacityLayer(0.75),
+            RenderCommand::PopOpacityLayer {},
+            RenderCommand::PushRoundedClip { x: 1.0, y: 2.0, width: 20.0, height: 10.0, corner_radii: [1.0, 2.0, 3.0, 4.0] },
+            RenderCommand::PopClip {},
+            RenderCommand::DrawArc { cx: 8.0, cy: 8.0, radius: 6.0, start_angle: 0.0, end_angle: 1.5, thickness: 0.0, color: 0x0000FFFF },
+            RenderCommand::DrawPath {
+                segments: vec![
+                    PathSegment::MoveTo { x: 0.0, y: 0.0 },
+                    PathSegment::QuadTo { cx: 5.0, cy: -5.0, x: 10.0, y: 0.0 },
+                    PathSegment::CubicTo { c1x: 10.0, c1y: 4.0, c2x: 6.0, c2y: 8.0, x: 0.0, y: 8.0 },
+                    PathSegment::Close {},
+                ],
+                fill: Some(0x336699FF),
+                fill_rule: FillRule::EvenOdd,
+                stroke: Some(Stroke { width: 1.5, color: 0x000000FF, cap: LineCap::Round, join: LineJoin::Bevel }),
+            },
+            RenderCommand::DrawNinePatch {
+                texture_id: 5,
+                dest_rect: crate::geometry::Rect::new(0.0, 20.0, 120.0, 40.0),
+                insets: [8.0, 12.0, 8.0, 12.0],
+            },
+            RenderCommand::DrawImage {
+                x: 0.0, y: 0.0, width: 32.0, height: 16.0, texture_id: 7,
+                source_rect: None,
+                src: Some(crate::geometry::Rect::new(64.0, 32.0, 32.0, 16.0)),
+                tint: Some(0xFF8000FF),
+                corner_radii: [0.0; 4],
+                opacity: 1.0,
+                rotation: 0.0,
+                pivot: None,
+                sampling: Sampling::Nearest,
+            },
+            RenderCommand::Caret { x: 12.0, y: 4.0, width: 1.5, height: 18.0, color: 0x000000FF, blink_ms: 530 },
+            RenderCommand::FocusRing {
+                rect: crate::geometry::Rect::new(2.0, 3.0, 60.0, 24.0),
+                corner_radii: [4.0, 4.0, 6.0, 6.0],
+                color: 0x3B82F6FF,
+                thickness: 2.0,
+                dash: 3.0,
+                offset: 1.0,
+                speed: 20.0,
+            },
+            RenderCommand::Spinner { center_x: 50.0, center_y: 40.0, radius: 12.0, color: 0x3B82F6FF, speed: 1.5, thickness: 3.0 },
+        ];
+
+        let mut frame = Vec::new();
+        let f32s = |frame: &mut Vec<u8>, values: &[f32]| values.iter().for_each(|v| frame.extend_from_slice(&v.to_le_bytes()));
+        let string = |frame: &mut Vec<u8>, s: &str| {
+            frame.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            frame.extend_from_slice(s.as_bytes());
+        };
+        // String table: the DrawText's text, then the rich text run's
+        frame.extend_from_slice(&2u32.to_le_bytes());
+        string(&mut frame, "héllo 👋");
+        string(&mut frame, "Hi ");
+        frame.extend_from_slice(&(commands.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&[0x00, 10, 20, 30, 255]);
+
+        frame.push(0x01);
+        f32s(&mut frame, &[1.0, 2.0, 30.0, 40.0]);
+        frame.extend_from_slice(&0xFF0000FFu32.to_le_bytes());
+        f32s(&mut frame, &[1.0, 2.0, 3.0, 4.0, 0.5]);
+        frame.push(0x04 | 0x10); // pivot, shadow
+        f32s(&mut frame, &[0.25, 0.75, 2.0, 3.0, 4.0, 1.0]);
+        frame.extend_from_slice(&0x00000080u32.to_le_bytes());
+
+        frame.push(0x05);
+        f32s(&mut frame, &[0.0, 0.0, 100.0, 50.0]);
+
+        frame.push(0x02);
+        f32s(&mut frame, &[5.0, 6.0]);
+        frame.extend_from_slice(&0u32.to_le_bytes()); // text 0
+        frame.push(0); // system font
+        string(&mut frame, "Inter");
+        frame.extend_from_slice(&600u16.to_le_bytes());
+        frame.push(1); // italic
+        f32s(&mut frame, &[14.0]);
+        frame.extend_from_slice(&0x112233FFu32.to_le_bytes());
+        frame.push(0x01 | 0x04 | 0x08 | 0x20); // max_width, max_lines, anchor, base direction
+        f32s(&mut frame, &[200.0]);
+        frame.extend_from_slice(&2u32.to_le_bytes());
+        f32s(&mut frame, &[1.25, 0.0, 0.0]);
+        frame.extend_from_slice(&[1, 1, 0, 1, 0, 1, 2]); // center, middle, normal, ellipsis, normal, baseline, rtl
+
+        frame.push(0x09);
+        f32s(&mut frame, &[0.5]);
+        frame.push(0x0C);
+        f32s(&mut frame, &[0.0, 0.0, 10.0, 10.0, 2.0]);
+        frame.extend_from_slice(&0x00FF00FFu32.to_le_bytes());
+        frame.push(1); // round cap
+
+        frame.push(0x0D);
+        f32s(&mut frame, &[4.0, 8.0]);
+        frame.push(0x01 | 0x10); // max_width, gradient
+        f32s(&mut frame, &[80.0, 1.5, 0.0, 0.0]);
+        frame.extend_from_slice(&[0, 0, 0, 2, 0]); // left, top, normal, wrap, normal
+        frame.push(1); // radial
+        f32s(&mut frame, &[0.5, 0.25]);
+        frame.push(2);
+        f32s(&mut frame, &[0.0]);
+        frame.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes());
+        f32s(&mut frame, &[1.0]);
+        frame.extend_from_slice(&0x000000FFu32.to_le_bytes());
+        frame.extend_from_slice(&2u32.to_le_bytes());
+        frame.push(0); // text run
+        frame.extend_from_slice(&1u32.to_le_bytes()); // text 1
+        frame.push(2); // memory font
+        string(&mut frame, "Brand");
+        frame.extend_from_slice(&0x0123_4567_89AB_CDEFu64.to_le_bytes());
+        frame.extend_from_slice(&400u16.to_le_bytes());
+        frame.push(0);
+        f32s(&mut frame, &[12.0]);
+        frame.extend_from_slice(&0xFF0000FFu32.to_le_bytes());
+        frame.push(1); // image run
+        frame.extend_from_slice(&3u32.to_le_bytes());
+        f32s(&mut frame, &[16.0, 16.0, 2.0]);
+
+        frame.push(0x06);
+        frame.push(0x0E);
+        f32s(&mut frame, &[2.0, 0.0, 0.0, 2.0, 10.0, -5.0]);
+        frame.push(0x0F);
+        frame.push(0x14);
+        f32s(&mut frame, &[0.75]);
+        frame.push(0x15);
+        frame.push(0x10);
+        f32s(&mut frame, &[1.0, 2.0, 20.0, 10.0, 1.0, 2.0, 3.0, 4.0]);
+        frame.push(0x06);
+        frame.push(0x11);
+        f32s(&mut frame, &[8.0, 8.0, 6.0, 0.0, 1.5, 0.0]);
+        frame.extend_from_slice(&0x0000FFFFu32.to_le_bytes());
+        frame.push(0x13);
+        frame.push(0x01 | 0x02 | 0x04); // fill, stroke, even-odd
+        frame.extend_from_slice(&0x336699FFu32.to_le_bytes());
+        f32s(&mut frame, &[1.5]);
+        frame.extend_from_slice(&0x000000FFu32.to_le_bytes());
+        frame.extend_from_slice(&[1, 2]); // round cap, bevel join
+        frame.extend_from_slice(&4u32.to_le_bytes());
+        frame.push(0);
+        f32s(&mut frame, &[0.0, 0.0]);
+        frame.push(2);
+        f32s(&mut frame, &[5.0, -5.0, 10.0, 0.0]);
+        frame.push(3);
+        f32s(&mut frame, &[10.0, 4.0, 6.0, 8.0, 0.0, 8.0]);
+        frame.push(4);
+        frame.push(0x12);
+        frame.extend_from_slice(&5u32.to_le_bytes());
+        f32s(&mut frame, &[0.0, 20.0, 120.0, 40.0, 8.0, 12.0, 8.0, 12.0]);
+
+        frame.push(0x03);
+        f32s(&mut frame, &[0.0, 0.0, 32.0, 16.0]);
+        frame.extend_from_slice(&7u32.to_le_bytes());
+        frame.push(0x10 | 0x20 | 0x40); // src, tint, sampling
+        f32s(&mut frame, &[0.0, 0.0, 0.0, 0.0, 64.0, 32.0, 32.0, 16.0]);
+        frame.extend_from_slice(&0xFF8000FFu32.to_le_bytes());
+        frame.push(0); // nearest
+
+        frame.push(0x0A);
+        f32s(&mut frame, &[12.0, 4.0, 1.5, 18.0]);
+        frame.extend_from_slice(&0x000000FFu32.to_le_bytes());
+        frame.extend_from_slice(&530u32.to_le_bytes());
+        frame.push(0x0B);
+        f32s(&mut frame, &[2.0, 3.0, 60.0, 24.0, 4.0, 4.0, 6.0, 6.0]);
+        frame.extend_from_slice(&0x3B82F6FFu32.to_le_bytes());
+        f32s(&mut frame, &[2.0, 3.0, 1.0, 20.0]);
+        frame.push(0x16);
+        f32s(&mut frame, &[50.0, 40.0, 12.0]);
+        frame.extend_from_slice(&0x3B82F6FFu32.to_le_bytes());
+        f32s(&mut frame, &[1.5, 3.0]);
+
+        let from_json: Vec<RenderCommand> = serde_json::from_str(&serde_json::to_string(&commands).unwrap()).unwrap();
+        assert_eq!(decode_render_commands(&frame).unwrap(), from_json);
+        assert_eq!(from_json, commands);
+
+        // Truncated and unknown commands are rejected, not half-decoded
+        assert!(decode_render_commands(&frame[..frame.len() - 2]).is_err());
+        assert!(decode_render_commands(&[0, 0, 0, 0, 1, 0, 0, 0, 0xEE]).is_err());
+
+        // So are texts missing from the string table
+        let mut missing = vec![0, 0, 0, 0, 1, 0, 0, 0, 0x02];
+        f32s(&mut missing, &[0.0, 0.0]);
+        missing.extend_from_slice(&0u32.to_le_bytes());
+        let err = decode_render_commands(&missing).unwrap_err();
+        assert!(err.contains("missing string 0"), "{}", err);
+        assert!(decode_render_commands(&[1, 0, 0, 0, 5, 0, 0, 0, b'a']).is_err());
+
+        // A huge command count with a short payload fails without reserving it
+        let err = decode_render_commands(&[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0x06]).unwrap_err();
+        assert_eq!(err, "unexpected end of payload");
+        assert_eq!(unsafe { centered_backend_render_frame_binary(ptr::null(), 0) }, EngineError::InvalidArgument.code());
+    }
+
+    #[test]
+    fn test_render_to_buffer_checks_arguments() {
+        let commands = CString::new(r#"[{"Clear": {"r": 0, "g": 255, "b": 255, "a": 255}}]"#).unwrap();
+        let mut pixels = vec![0u8; 4 * 2 * 4];
+        unsafe {
+            let render = |json: &CString, width, height, pixels: &mut [u8]| {
+                centered_render_to_buffer(json.as_ptr(), width, height, pixels.as_mut_ptr(), pixels.len())
+            };
+            assert_eq!(render(&commands, 4, 3, &mut pixels), EngineError::InvalidArgument.code());
+            assert_eq!(render(&commands, 0, 2, &mut pixels), EngineError::InvalidArgument.code());
+            assert_eq!(render(&CString::new("not json").unwrap(), 4, 2, &mut pixels), EngineError::InvalidArgument.code());
+            assert_eq!(
+                centered_render_to_buffer(ptr::null(), 4, 2, pixels.as_mut_ptr(), pixels.len()),
+                EngineError::InvalidArgument.code()
+            );
+
+            // Skip the rest on machines without any GPU or software adapter
+            if render(&commands, 4, 2, &mut pixels) != 0 {
+                return;
+            }
+        }
+        assert!(pixels.chunks_exact(4).all(|pixel| pixel == [0, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_fallback_font_names_split_on_commas() {
+        assert_eq!(
+            fallback_families("Inter, Noto Sans CJK JP,,Noto Color Emoji "),
+            vec!["Inter", "Noto Sans CJK JP", "Noto Color Emoji"]
+        );
+        assert!(fallback_families("").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn test_present_failure_reported_on_next_callback() {
+        unsafe extern "C" fn record(event: *const AppEvent, _response: *mut FrameResponse, user_data: *mut std::ffi::c_void) {
+            *(user_data as *mut u32) = (*event).render_failures;
+        }
+
+        let event = AppEvent {
+            event_type: AppEventType::RedrawRequested,
+            data1: 0.0,
+            data2: 0.0,
+            scale_factor: 1.0,
+            frame_dt: 0.0,
+            render_failures: 0,
+            scroll_phase: ScrollPhase::None,
+            touch_phase: TouchPhase::None,
+            touch_id: 0,
+            text: ptr::null(),
+        };
+        let mut response = FrameResponse {
+            immediate_commands: ptr::null_mut(),
+            widget_delta: ptr::null_mut(),
+            request_redraw: true,
+            redraw_after_ms: 0,
+            dark_mode: 2,
+            layers: ptr::null_mut(),
+            dirty_region: ptr::null_mut(),
+        };
+        let mut seen = 0u32;
+        let seen_ptr = &mut seen as *mut u32 as *mut std::ffi::c_void;
+
+        // Induced present error (surface unavailable mid-drag)
+        record_render_result::<(), _>(&Err("surface lost"));
+        unsafe { dispatch_event(record, &event, &mut response, seen_ptr) };
+        assert_eq!(seen, 1);
+
+        record_render_result::<(), _>(&Err("surface lost"));
+        unsafe { dispatch_event(record, &event, &mut response, seen_ptr) };
+        assert_eq!(seen, 2);
+
+        // A successful present clears the flag
+        record_render_result::<(), &str>(&Ok(()));
+        unsafe { dispatch_event(record, &event, &mut response, seen_ptr) };
+        assert_eq!(seen, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn test_wheel_scroll_phase_separates_momentum() {
+        use winit::event::TouchPhase;
+
+        // A flick: fingers down, moving, lifted, then the momentum tail
+        let flick: Vec<ScrollPhase> = [
+            (TouchPhase::Started, false),
+            (TouchPhase::Moved, false),
+            (TouchPhase::Ended, false),
+            (TouchPhase::Started, true),
+            (TouchPhase::Moved, true),
+            (TouchPhase::Ended, true),
+        ]
+        .into_iter()
+        .map(|(phase, momentum)| wheel_scroll_phase(phase, false, momentum))
+        .collect();
+        assert_eq!(flick, vec![
+            ScrollPhase::Started,
+            ScrollPhase::Moved,
+            ScrollPhase::Ended,
+            ScrollPhase::Momentum,
+            ScrollPhase::Momentum,
+            ScrollPhase::MomentumEnded,
+        ]);
+
+        // Mouse wheel notches aren't a gesture
+        assert_eq!(wheel_scroll_phase(TouchPhase::Moved, true, false), ScrollPhase::None);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn test_touch_events_follow_each_finger() {
+        // Two fingers of a pinch keep their own ids and positions
+        let first = touch_event(7, touch_phase(winit::event::TouchPhase::Started), 10.0, 20.0, 2.0);
+        let second = touch_event(9, touch_phase(winit::event::TouchPhase::Moved), 50.0, 60.0, 2.0);
+        assert!(matches!(first.event_type, AppEventType::Touch));
+        assert_eq!((first.touch_id, first.touch_phase, first.data1, first.data2), (7, TouchPhase::Began, 10.0, 20.0));
+        assert_eq!((second.touch_id, second.touch_phase), (9, TouchPhase::Moved));
+        assert_eq!(touch_phase(winit::event::TouchPhase::Cancelled), TouchPhase::Cancelled);
+
+        // Go's AppEventC mirrors this layout
+        assert_eq!(std::mem::size_of::<AppEvent>(), 56);
+        assert_eq!(std::mem::offset_of!(AppEvent, touch_phase), 37);
+        assert_eq!(std::mem::offset_of!(AppEvent, touch_id), 40);
+        assert_eq!(std::mem::offset_of!(AppEvent, text), 48);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn test_ime_events_carry_text_and_cursor() {
+        let text = CString::new("にほん").unwrap();
+        let preedit = ime_event(AppEventType::ImePreedit, &text, Some((3, 9)));
+        assert!(matches!(preedit.event_type, AppEventType::ImePreedit));
+        assert_eq!((preedit.data1, preedit.data2), (3.0, 9.0));
+        assert_eq!(unsafe { CStr::from_ptr(preedit.text) }.to_str(), Ok("にほん"));
+
+        // A hidden cursor reads as -1 on both ends
+        let commit = ime_event(AppEventType::ImeCommit, &text, None);
+        assert_eq!((commit.data1, commit.data2), (-1.0, -1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn test_logical_key_text_follows_layout() {
+        use winit::keyboard::{Key, NamedKey};
+        // What the layout produced, not the physical key
+        let key = Key::Character("y".into());
+        assert_eq!(logical_key_text(&key).unwrap().to_str(), Ok("y"));
+        assert_eq!(logical_key_text(&Key::Named(NamedKey::ArrowLeft)).unwrap().to_str(), Ok("ArrowLeft"));
+        assert!(logical_key_text(&Key::Dead(None)).is_none());
+    }
+
+    #[test]
+    fn test_clipboard_image_checks_arguments() {
+        let invalid = EngineError::InvalidArgument.code();
+        let pixels = [0u8; 16];
+        unsafe {
+            assert_eq!(centered_clipboard_set_image(ptr::null(), 16, 2, 2), invalid);
+            assert_eq!(centered_clipboard_set_image(pixels.as_ptr(), 16, 0, 2), invalid);
+            // Length must match the size exactly
+            assert_eq!(centered_clipboard_set_image(pixels.as_ptr(), 16, 3, 2), invalid);
+
+            let (mut len, mut width, mut height) = (0usize, 0u32, 0u32);
+            assert_eq!(centered_clipboard_get_image(ptr::null_mut(), &mut len, &mut width, &mut height), invalid);
+        }
+    }
 }
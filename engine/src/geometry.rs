@@ -8,13 +8,144 @@
 //! - Gradient fills (linear, radial)
 //!
 //! All geometry is generated in screen-space coordinates.
-
-use crate::render::{Gradient, GradientStop, Vertex};
+//!
+//! It also defines `Point2`, `Size2` and `Rect`, which new APIs take in
+//! place of loose `x, y, width, height` floats. They serialize as flat
+//! `x`/`y`/`width`/`height` fields, matching the JSON protocol.
+
+use crate::render::{
+    Border, BorderStyle, BoxShadow, FillRule, Gradient, GradientStop, InnerShadow, LineCap, LineJoin, PathSegment, Stroke,
+    Vertex,
+};
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
 
+/// A point in screen coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Point2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point2 {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A width and height in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Size2 {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size2 {
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// An axis-aligned rectangle: top-left corner plus size
+///
+/// Edges are half-open - a point on the right or bottom edge is outside -
+/// so rects that share an edge don't overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub const fn from_origin_size(origin: Point2, size: Size2) -> Self {
+        Self::new(origin.x, origin.y, size.width, size.height)
+    }
+
+    pub fn origin(&self) -> Point2 {
+        Point2::new(self.x, self.y)
+    }
+
+    pub fn size(&self) -> Size2 {
+        Size2::new(self.width, self.height)
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn center(&self) -> Point2 {
+        Point2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Whether the rect covers no area
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        point.x >= self.x && point.x < self.right() && point.y >= self.y && point.y < self.bottom()
+    }
+
+    /// The overlap of two rects, or None if they don't overlap
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if right <= x || bottom <= y {
+            return None;
+        }
+        Some(Rect::new(x, y, right - x, bottom - y))
+    }
+
+    /// The smallest rect covering both; an empty rect adds nothing
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+
+    /// Shrink by `dx` on the left and right and `dy` on the top and bottom
+    /// (negative values grow the rect). An inset larger than the rect
+    /// collapses it to zero size at its center.
+    pub fn inset(&self, dx: f32, dy: f32) -> Rect {
+        let width = (self.width - 2.0 * dx).max(0.0);
+        let height = (self.height - 2.0 * dy).max(0.0);
+        let center = self.center();
+        Rect::new(center.x - width / 2.0, center.y - height / 2.0, width, height)
+    }
+}
+
 /// Number of segments to use for each rounded corner
 const CORNER_SEGMENTS: usize = 8;
 
+/// Clamp corner radii to what fits a `width` x `height` rect
+///
+/// Each radius is limited to half the smaller dimension, so an over-large
+/// radius gives a clean pill (or circle) rather than overlapping corner
+/// arcs. Negative and NaN radii become square corners.
+pub fn clamp_radii(radii: [f32; 4], width: f32, height: f32) -> [f32; 4] {
+    let max_radius = (width.min(height) / 2.0).max(0.0);
+    // f32::max ignores NaN, so a NaN radius becomes 0
+    radii.map(|r| r.max(0.0).min(max_radius))
+}
+
 /// Generate vertices and indices for a rectangle with optional rounded corners
 ///
 /// # Arguments
@@ -34,13 +165,7 @@ pub fn rounded_rect(
     radii: [f32; 4],
 ) -> (Vec<Vertex>, Vec<u16>) {
     // Clamp radii to half the smallest dimension
-    let max_radius = (width.min(height)) / 2.0;
-    let radii = [
-        radii[0].min(max_radius),
-        radii[1].min(max_radius),
-        radii[2].min(max_radius),
-        radii[3].min(max_radius),
-    ];
+    let radii = clamp_radii(radii, width, height);
 
     // Check if we have any rounded corners
     let has_rounded = radii.iter().any(|&r| r > 0.5);
@@ -227,6 +352,7 @@ pub fn border_rect(
     radii: [f32; 4],
 ) -> (Vec<Vertex>, Vec<u16>) {
     let rgba = color_to_rgba(color);
+    let radii = clamp_radii(radii, width, height);
 
     // For now, generate as a simple frame (4 rectangles)
     // TODO: Proper rounded border with inner/outer arcs
@@ -278,13 +404,7 @@ fn rounded_border(
     let mut indices = Vec::new();
 
     let bw = border_width;
-    let max_radius = (width.min(height)) / 2.0;
-    let radii = [
-        radii[0].min(max_radius),
-        radii[1].min(max_radius),
-        radii[2].min(max_radius),
-        radii[3].min(max_radius),
-    ];
+    let radii = clamp_radii(radii, width, height);
 
     // Corner centers and angles - same as rounded_rect
     let corners = [
@@ -425,6 +545,179 @@ pub fn circle(
     (vertices, indices)
 }
 
+/// Generate a stroked arc (a ring segment)
+///
+/// `start_angle` and `sweep` are in radians, measured clockwise from the
+/// positive X axis in screen space. `radius` is the outer edge of the stroke.
+#[allow(clippy::too_many_arguments)]
+pub fn arc(
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    thickness: f32,
+    start_angle: f32,
+    sweep: f32,
+    color: u32,
+    segments: usize,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let rgba = color_to_rgba(color);
+    let segments = segments.max(1);
+    let inner = (radius - thickness).max(0.0);
+    let mut vertices = Vec::with_capacity((segments + 1) * 2);
+    let mut indices = Vec::with_capacity(segments * 6);
+
+    for i in 0..=segments {
+        let t = i as f32 / segments as f32;
+        let angle = start_angle + sweep * t;
+        let (sin, cos) = angle.sin_cos();
+
+        vertices.push(Vertex {
+            position: [cx + cos * radius, cy + sin * radius, 0.0],
+            texcoord: [t, 0.0],
+            color: rgba,
+        });
+        vertices.push(Vertex {
+            position: [cx + cos * inner, cy + sin * inner, 0.0],
+            texcoord: [t, 1.0],
+            color: rgba,
+        });
+    }
+
+    for i in 0..segments {
+        let base = (i * 2) as u16;
+        indices.extend_from_slice(&[
+            base, base + 2, base + 1,
+            base + 1, base + 2, base + 3,
+        ]);
+    }
+
+    (vertices, indices)
+}
+
+/// Farthest an antialiased arc's chords stray from the true circle, in pixels
+const ARC_TOLERANCE: f32 = 0.1;
+
+/// Most segments an antialiased arc is split into, keeping its vertices
+/// within u16 indices
+const MAX_ARC_SEGMENTS: usize = 1024;
+
+/// Generate an arc antialiased in the fragment shader, for the coverage
+/// pipeline.
+///
+/// Angles are in radians clockwise from the positive X axis in screen
+/// space, like `arc`. The arc covers the sweep from `start_angle` to
+/// `end_angle` in either direction; a full turn or more is a whole ring.
+/// `radius` is the outer edge and `thickness` the stroke width inside it;
+/// a thickness of 0, or one reaching the center, fills the wedge instead.
+///
+/// Each vertex's texcoord holds its signed distance in pixels to the
+/// nearest edge across the stroke (x) and to the nearer end of the arc
+/// (y), positive inside. The geometry reaches half a pixel past the edges
+/// so the shader has room to fade them out.
+pub fn antialiased_arc(
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    thickness: f32,
+    start_angle: f32,
+    end_angle: f32,
+    color: u32,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let sweep = end_angle - start_angle;
+    if radius <= 0.0 || sweep == 0.0 {
+        return (vec![], vec![]);
+    }
+    let rgba = color_to_rgba(color);
+    let full = sweep.abs() >= 2.0 * PI;
+    let (start, sweep) = if full {
+        (start_angle, 2.0 * PI)
+    } else if sweep < 0.0 {
+        (end_angle, -sweep)
+    } else {
+        (start_angle, sweep)
+    };
+    let inner = radius - thickness;
+    let filled = thickness <= 0.0 || inner <= 0.0;
+    // Distance inside the ends stays meaningful up to a quarter turn from
+    // each; past that the middle of the arc is nowhere near either end
+    let cap_reach = if full { 0.0 } else { (sweep / 2.0).min(PI / 2.0) };
+
+    // Segment boundaries: even steps that keep chords within tolerance,
+    // plus the edges of the zones measured from each end
+    let step = 2.0 * (1.0 - ARC_TOLERANCE / radius.max(ARC_TOLERANCE)).acos();
+    let count = ((sweep / step.max(1e-3)).ceil() as usize).clamp(1, MAX_ARC_SEGMENTS);
+    let mut stations: Vec<f32> = (0..=count).map(|i| sweep * i as f32 / count as f32).collect();
+    if !full {
+        stations.extend([cap_reach, sweep - cap_reach]);
+        stations.sort_by(f32::total_cmp);
+        stations.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
+    }
+
+    // Radii a segment's vertices sit at, outside in, with each one's
+    // distance to the nearest edge across the stroke
+    let across = |r: f32| if filled { radius - r } else { (radius - r).min(r - inner) };
+    let radii: Vec<(f32, f32)> = if filled {
+        vec![(radius + 0.5, -0.5), (0.0, radius)]
+    } else {
+        let mid = (radius + inner) / 2.0;
+        let inside = (inner - 0.5).max(0.0);
+        vec![(radius + 0.5, -0.5), (mid, across(mid)), (inside, across(inside))]
+    };
+
+    // Inward normals of the lines the two ends lie on
+    let start_normal = (-(start.sin()), start.cos());
+    let end = start + sweep;
+    let end_normal = (end.sin(), -(end.cos()));
+
+    let mut vertices = Vec::with_capacity((stations.len() - 1) * radii.len() * 2);
+    let mut indices = Vec::with_capacity((stations.len() - 1) * (radii.len() - 1) * 6);
+
+    for segment in stations.windows(2) {
+        let (a0, a1) = (segment[0], segment[1]);
+        let middle = (a0 + a1) / 2.0;
+        // Segments near an end measure the distance to it, pushing the
+        // end's own vertices half a pixel out for the fringe
+        let cap = if middle < cap_reach {
+            Some((start_normal, 0.0))
+        } else if middle > sweep - cap_reach {
+            Some((end_normal, sweep))
+        } else {
+            None
+        };
+
+        let base = vertices.len() as u16;
+        for angle in [a0, a1] {
+            let (sin, cos) = (start + angle).sin_cos();
+            for &(r, distance) in &radii {
+                let (mut px, mut py) = (cx + cos * r, cy + sin * r);
+                let along = match cap {
+                    Some(((nx, ny), at)) => {
+                        if (angle - at).abs() < 1e-5 && r > 0.0 {
+                            px -= nx * 0.5;
+                            py -= ny * 0.5;
+                        }
+                        (px - cx) * nx + (py - cy) * ny
+                    }
+                    None => radius + 1.0,
+                };
+                vertices.push(Vertex { position: [px, py, 0.0], texcoord: [distance, along], color: rgba });
+            }
+        }
+
+        let per_side = radii.len() as u16;
+        for i in 0..per_side - 1 {
+            let (a, b) = (base + i, base + per_side + i);
+            if filled {
+                indices.extend_from_slice(&[a, b, a + 1]);
+            } else {
+                indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
 /// Generate a line with thickness
 pub fn line(
     x1: f32,
@@ -477,6 +770,72 @@ pub fn line(
     (vertices, indices)
 }
 
+/// Segments in each half circle of a round line cap
+const ROUND_CAP_SEGMENTS: usize = 12;
+
+/// Segments in each dot of a dotted border
+const DOT_SEGMENTS: usize = 12;
+
+/// Generate a line with thickness and end caps
+///
+/// Butt caps end flat at the endpoints; square caps extend half the width
+/// past them; round caps add a half circle at each end. A zero-length line
+/// has no direction, so round caps draw a dot and square caps an
+/// axis-aligned square, while butt caps draw nothing.
+#[allow(clippy::too_many_arguments)]
+pub fn capped_line(
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+    width: f32,
+    color: u32,
+    cap: LineCap,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let half = width * 0.5;
+    if half <= 0.0 {
+        return (vec![], vec![]);
+    }
+
+    let len = (x2 - x1).hypot(y2 - y1);
+    if len < 0.001 {
+        return match cap {
+            LineCap::Butt => (vec![], vec![]),
+            LineCap::Round => circle(x1, y1, half, color, ROUND_CAP_SEGMENTS * 2),
+            LineCap::Square => simple_rect(x1 - half, y1 - half, width, width, color),
+        };
+    }
+
+    // Unit direction along the line
+    let (dx, dy) = ((x2 - x1) / len, (y2 - y1) / len);
+    match cap {
+        LineCap::Butt => line(x1, y1, x2, y2, width, color),
+        LineCap::Square => line(x1 - dx * half, y1 - dy * half, x2 + dx * half, y2 + dy * half, width, color),
+        LineCap::Round => {
+            let (mut vertices, mut indices) = line(x1, y1, x2, y2, width, color);
+            let rgba = color_to_rgba(color);
+            // Half circle fans facing away from the line at each end
+            let start_angle = dy.atan2(dx);
+            for (cx, cy, facing) in [(x1, y1, start_angle + PI), (x2, y2, start_angle)] {
+                let center = vertices.len() as u16;
+                vertices.push(Vertex { position: [cx, cy, 0.0], texcoord: [0.5, 0.5], color: rgba });
+                for i in 0..=ROUND_CAP_SEGMENTS {
+                    let angle = facing - PI / 2.0 + PI * i as f32 / ROUND_CAP_SEGMENTS as f32;
+                    vertices.push(Vertex {
+                        position: [cx + angle.cos() * half, cy + angle.sin() * half, 0.0],
+                        texcoord: [0.5 + angle.cos() * 0.5, 0.5 + angle.sin() * 0.5],
+                        color: rgba,
+                    });
+                }
+                for i in 0..ROUND_CAP_SEGMENTS as u16 {
+                    indices.extend([center, center + 1 + i, center + 2 + i]);
+                }
+            }
+            (vertices, indices)
+        }
+    }
+}
+
 /// Helper to add a simple rectangle's vertices and indices
 fn add_rect_vertices(
     vertices: &mut Vec<Vertex>,
@@ -517,7 +876,7 @@ fn add_rect_vertices(
 }
 
 /// Convert u32 color (0xRRGGBBAA) to [f32; 4] RGBA
-fn color_to_rgba(color: u32) -> [f32; 4] {
+pub(crate) fn color_to_rgba(color: u32) -> [f32; 4] {
     [
         ((color >> 24) & 0xFF) as f32 / 255.0,
         ((color >> 16) & 0xFF) as f32 / 255.0,
@@ -526,14 +885,127 @@ fn color_to_rgba(color: u32) -> [f32; 4] {
     ]
 }
 
+// ===== Rotation =====
+
+/// A rotation by `angle` radians about a fixed point in screen coordinates
+///
+/// Positive angles turn clockwise on screen (y points down).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    pub angle: f32,
+    pub pivot: (f32, f32),
+}
+
+impl Rotation {
+    /// Rotation of `rect` about `pivot`, given in the rect's local space
+    /// (relative to its top-left corner). Without a pivot the rect turns
+    /// about its center.
+    pub fn about(rect: Rect, angle: f32, pivot: Option<(f32, f32)>) -> Self {
+        let (px, py) = pivot.unwrap_or((rect.width / 2.0, rect.height / 2.0));
+        Self { angle, pivot: (rect.x + px, rect.y + py) }
+    }
+
+    /// Rotate a point; angles too small to matter leave it untouched
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        if self.angle.abs() <= 0.0001 {
+            return (x, y);
+        }
+        let (sin_r, cos_r) = self.angle.sin_cos();
+        let (cx, cy) = self.pivot;
+        let dx = x - cx;
+        let dy = y - cy;
+        (cx + dx * cos_r - dy * sin_r, cy + dx * sin_r + dy * cos_r)
+    }
+}
+
+// ===== Affine Transforms =====
+
+/// A 2D affine transform, stored as the `[a, b, c, d, e, f]` of CSS and
+/// canvas `matrix()`: a point (x, y) maps to (a·x + c·y + e, b·x + d·y + f)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D(pub [f32; 6]);
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self([sx, 0.0, 0.0, sy, 0.0, 0.0])
+    }
+
+    /// The transform that applies `inner` first and then `self`, as
+    /// pushing `inner` inside `self` does
+    pub fn then(&self, inner: &Transform2D) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [ia, ib, ic, id, ie, if_] = inner.0;
+        Self([
+            a * ia + c * ib,
+            b * ia + d * ib,
+            a * ic + c * id,
+            b * ic + d * id,
+            a * ie + c * if_ + e,
+            b * ie + d * if_ + f,
+        ])
+    }
+
+    /// The same transform for coordinates multiplied by `factor` (logical
+    /// to physical pixels): only the translation scales
+    pub fn scaled(&self, factor: f32) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        Self([a, b, c, d, e * factor, f * factor])
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Axis-aligned bounds of a rect after transforming its corners
+    pub fn bounds(&self, rect: Rect) -> Rect {
+        let corners = [
+            self.apply(rect.x, rect.y),
+            self.apply(rect.x + rect.width, rect.y),
+            self.apply(rect.x, rect.y + rect.height),
+            self.apply(rect.x + rect.width, rect.y + rect.height),
+        ];
+        let (min_x, min_y, max_x, max_y) = corners.iter().fold(
+            (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        );
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+}
+
 // ===== Gradient Support =====
 
+/// Angular segments per ring of a radial gradient fill
+const RADIAL_GRADIENT_SEGMENTS: usize = 32;
+
+/// Rings per unit of radial gradient position between the first and last stop
+const RADIAL_GRADIENT_RINGS: usize = 16;
+
+/// Wedges per full turn of a conic gradient fill
+const CONIC_GRADIENT_SEGMENTS: usize = 64;
+
 /// Generate vertices and indices for a rectangle with a gradient fill
 ///
+/// Colors are interpolated between vertices, so the fill is split wherever
+/// a stop falls: every triangle lies between two neighbouring stops and
+/// blends only their colors. Stops may come in any order; positions are
+/// clamped to 0.0-1.0, two stops at the same position make a hard edge,
+/// and a single stop is a solid fill.
+///
 /// # Arguments
 /// * `x`, `y` - Top-left position in screen coordinates
 /// * `width`, `height` - Size in pixels
-/// * `gradient` - Gradient specification (linear or radial)
+/// * `gradient` - Gradient specification (linear, radial or conic)
 /// * `radii` - Corner radii [top-left, top-right, bottom-right, bottom-left]
 ///
 /// # Returns
@@ -547,225 +1019,278 @@ pub fn gradient_rect(
     radii: [f32; 4],
 ) -> (Vec<Vertex>, Vec<u16>) {
     // Clamp radii to half the smallest dimension
-    let max_radius = (width.min(height)) / 2.0;
-    let radii = [
-        radii[0].min(max_radius),
-        radii[1].min(max_radius),
-        radii[2].min(max_radius),
-        radii[3].min(max_radius),
-    ];
+    let radii = clamp_radii(radii, width, height);
+
+    let (Gradient::Linear { stops, .. } | Gradient::Radial { stops, .. } | Gradient::Conic { stops, .. }) = gradient;
+    let stops = sorted_stops(stops);
+    match stops.as_slice() {
+        [] => return rounded_rect(x, y, width, height, 0xFFFFFFFF, radii),
+        [only] => return rounded_rect(x, y, width, height, only.color, radii),
+        _ => {}
+    }
 
-    // Check if we have any rounded corners
-    let has_rounded = radii.iter().any(|&r| r > 0.5);
+    let outline = fill_outline(x, y, width, height, radii);
+    let bands = gradient_bands(&stops);
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
 
-    if !has_rounded {
-        // Simple rectangle with gradient
-        return simple_gradient_rect(x, y, width, height, gradient);
+    match *gradient {
+        Gradient::Linear { angle, .. } => {
+            let position = |(px, py): (f32, f32)| linear_gradient_position(angle, px - x, py - y, width, height);
+
+            // One strip of the outline per band
+            for band in &bands {
+                let mut piece = outline.clone();
+                if let Some(start) = band.start {
+                    piece = clip_polygon(&piece, |p| position(p) - start);
+                }
+                if let Some(end) = band.end {
+                    piece = clip_polygon(&piece, |p| end - position(p));
+                }
+                push_gradient_fan(&mut vertices, &mut indices, &piece, x, y, width, height, |p| band.color_at(position(p)));
+            }
+        }
+        Gradient::Radial { center_x, center_y, .. } => {
+            let position = |(px, py): (f32, f32)| {
+                radial_gradient_position(center_x, center_y, px - x, py - y, width, height)
+            };
+            let ring_point = |t: f32, angle: f32| {
+                (
+                    x + width * (center_x + RADIAL_GRADIENT_REACH * t * angle.cos()),
+                    y + height * (center_y + RADIAL_GRADIENT_REACH * t * angle.sin()),
+                )
+            };
+
+            // Rings out to the farthest corner, split at every stop so each
+            // ring lies in one band. The last ring is pushed out so its
+            // chords still cover the corners.
+            let reach = outline.iter().map(|&p| position(p)).fold(0.0, f32::max);
+            let (first, last) = (stops[0].position, stops[stops.len() - 1].position);
+            let mut rings = vec![0.0];
+            rings.extend(stops.iter().map(|stop| stop.position).filter(|&t| t > 0.0 && t < reach));
+            rings.extend(
+                (1..RADIAL_GRADIENT_RINGS)
+                    .map(|i| i as f32 / RADIAL_GRADIENT_RINGS as f32)
+                    .filter(|&t| t > first && t < last.min(reach)),
+            );
+            rings.push(reach / (PI / RADIAL_GRADIENT_SEGMENTS as f32).cos());
+            rings.sort_by(f32::total_cmp);
+            rings.dedup();
+
+            let orientation = polygon_orientation(&outline);
+            for ring in rings.windows(2) {
+                let (inner, outer) = (ring[0], ring[1]);
+                let middle = (inner + outer) / 2.0;
+                let Some(band) = bands.iter().find(|band| band.contains(middle)) else { continue };
+
+                for segment in 0..RADIAL_GRADIENT_SEGMENTS {
+                    let a0 = 2.0 * PI * segment as f32 / RADIAL_GRADIENT_SEGMENTS as f32;
+                    let a1 = 2.0 * PI * (segment + 1) as f32 / RADIAL_GRADIENT_SEGMENTS as f32;
+                    let mut piece = vec![ring_point(outer, a0), ring_point(outer, a1)];
+                    if inner > 0.0 {
+                        piece.extend([ring_point(inner, a1), ring_point(inner, a0)]);
+                    } else {
+                        piece.push(ring_point(0.0, a0));
+                    }
+
+                    let piece = clip_to_outline(piece, &outline, orientation);
+                    push_gradient_fan(&mut vertices, &mut indices, &piece, x, y, width, height, |p| band.color_at(position(p)));
+                }
+            }
+        }
+        Gradient::Conic { center_x, center_y, start_angle, .. } => {
+            let position = |(px, py): (f32, f32)| {
+                conic_gradient_position(center_x, center_y, start_angle, px - x, py - y, width, height)
+            };
+            let center = (x + width * center_x, y + height * center_y);
+            // Wedge edges run well past the outline
+            let reach = 2.0 * outline.iter().map(|&(px, py)| (px - center.0).hypot(py - center.1)).fold(1.0, f32::max);
+            let ray = |t: f32| {
+                let angle = (start_angle + t * 360.0).to_radians();
+                (center.0 + reach * angle.sin(), center.1 - reach * angle.cos())
+            };
+
+            // Wedges from the start angle round one full turn, split at
+            // every stop so each wedge lies in one band. The seam is a wedge
+            // edge, so the last color never blends back into the first.
+            let mut edges: Vec<f32> = (0..=CONIC_GRADIENT_SEGMENTS)
+                .map(|i| i as f32 / CONIC_GRADIENT_SEGMENTS as f32)
+                .collect();
+            edges.extend(stops.iter().map(|stop| stop.position).filter(|&t| t > 0.0 && t < 1.0));
+            edges.sort_by(f32::total_cmp);
+            edges.dedup();
+
+            let orientation = polygon_orientation(&outline);
+            for wedge in edges.windows(2) {
+                let (start, end) = (wedge[0], wedge[1]);
+                let middle = (start + end) / 2.0;
+                let Some(band) = bands.iter().find(|band| band.contains(middle)) else { continue };
+
+                let piece = clip_to_outline(vec![center, ray(start), ray(end)], &outline, orientation);
+                push_gradient_fan(&mut vertices, &mut indices, &piece, x, y, width, height, |(px, py)| {
+                    // The center is in every wedge, and the seam at both
+                    // ends of the turn
+                    let t = if (px - center.0).hypot(py - center.1) < 1e-3 {
+                        middle
+                    } else {
+                        let t = position((px, py));
+                        if t < start - 0.5 {
+                            t + 1.0
+                        } else if t > end + 0.5 {
+                            t - 1.0
+                        } else {
+                            t
+                        }
+                    };
+                    band.color_at(t)
+                });
+            }
+        }
     }
 
-    // Generate rounded rectangle with gradient
-    rounded_gradient_rect(x, y, width, height, gradient, radii)
+    (vertices, indices)
 }
 
-/// Simple rectangle with gradient (no rounded corners)
-fn simple_gradient_rect(
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-    gradient: &Gradient,
-) -> (Vec<Vertex>, Vec<u16>) {
-    // For a simple rect, we can use 4 vertices with gradient-computed colors
-    let vertices = vec![
-        Vertex {
-            position: [x, y, 0.0],
-            texcoord: [0.0, 0.0],
-            color: compute_gradient_color(gradient, 0.0, 0.0, width, height),
-        },
-        Vertex {
-            position: [x + width, y, 0.0],
-            texcoord: [1.0, 0.0],
-            color: compute_gradient_color(gradient, width, 0.0, width, height),
-        },
-        Vertex {
-            position: [x, y + height, 0.0],
-            texcoord: [0.0, 1.0],
-            color: compute_gradient_color(gradient, 0.0, height, width, height),
-        },
-        Vertex {
-            position: [x + width, y + height, 0.0],
-            texcoord: [1.0, 1.0],
-            color: compute_gradient_color(gradient, width, height, width, height),
-        },
-    ];
+/// A rounded rect's outline as a convex polygon, without repeated points
+fn fill_outline(x: f32, y: f32, width: f32, height: f32, radii: [f32; 4]) -> Vec<(f32, f32)> {
+    let mut outline = inset_outline(x, y, width, height, radii, 0.0);
+    outline.dedup_by(|a, b| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3);
+    if outline.len() > 1 {
+        let (first, last) = (outline[0], outline[outline.len() - 1]);
+        if (first.0 - last.0).abs() < 1e-3 && (first.1 - last.1).abs() < 1e-3 {
+            outline.pop();
+        }
+    }
+    outline
+}
 
-    let indices = vec![0, 1, 2, 1, 3, 2];
+/// 1.0 for a clockwise polygon (on screen, y down), -1.0 otherwise
+fn polygon_orientation(polygon: &[(f32, f32)]) -> f32 {
+    let twice_area: f32 = (0..polygon.len())
+        .map(|i| {
+            let (ax, ay) = polygon[i];
+            let (bx, by) = polygon[(i + 1) % polygon.len()];
+            ax * by - bx * ay
+        })
+        .sum();
+    if twice_area >= 0.0 { 1.0 } else { -1.0 }
+}
 
-    (vertices, indices)
+/// The part of a convex polygon inside a convex `outline` whose
+/// `polygon_orientation` is `orientation`
+fn clip_to_outline(mut piece: Vec<(f32, f32)>, outline: &[(f32, f32)], orientation: f32) -> Vec<(f32, f32)> {
+    for (i, &(ax, ay)) in outline.iter().enumerate() {
+        let (bx, by) = outline[(i + 1) % outline.len()];
+        piece = clip_polygon(&piece, |(px, py)| orientation * ((bx - ax) * (py - ay) - (by - ay) * (px - ax)));
+        if piece.is_empty() {
+            break;
+        }
+    }
+    piece
+}
+
+/// The part of a convex polygon where `side` is non-negative
+/// (Sutherland-Hodgman). `side` must be affine, so an edge's crossing is
+/// found by interpolating it.
+fn clip_polygon(polygon: &[(f32, f32)], side: impl Fn((f32, f32)) -> f32) -> Vec<(f32, f32)> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 2);
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (side_a, side_b) = (side(a), side(b));
+        if side_a >= 0.0 {
+            clipped.push(a);
+        }
+        if (side_a >= 0.0) != (side_b >= 0.0) {
+            let t = side_a / (side_a - side_b);
+            clipped.push((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+        }
+    }
+    clipped
 }
 
-/// Rounded rectangle with gradient fill
-fn rounded_gradient_rect(
+/// Append a convex polygon as a triangle fan, colored per vertex
+#[allow(clippy::too_many_arguments)]
+fn push_gradient_fan(
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u16>,
+    polygon: &[(f32, f32)],
     x: f32,
     y: f32,
     width: f32,
     height: f32,
+    color: impl Fn((f32, f32)) -> [f32; 4],
+) {
+    if polygon.len() < 3 {
+        return;
+    }
+    let base = vertices.len() as u16;
+    for &(px, py) in polygon {
+        let u = if width > 0.0 { (px - x) / width } else { 0.0 };
+        let v = if height > 0.0 { (py - y) / height } else { 0.0 };
+        vertices.push(Vertex {
+            position: [px, py, 0.0],
+            texcoord: [u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)],
+            color: color((px, py)),
+        });
+    }
+    for i in 1..polygon.len() - 1 {
+        indices.extend_from_slice(&[base, base + i as u16, base + i as u16 + 1]);
+    }
+}
+
+/// Compute the gradient color at a given position within a rectangle
+///
+/// # Arguments
+/// * `gradient` - The gradient specification
+/// * `local_x`, `local_y` - Position relative to the rect's top-left
+/// * `width`, `height` - Rectangle dimensions
+pub fn compute_gradient_color(
     gradient: &Gradient,
-    radii: [f32; 4],
-) -> (Vec<Vertex>, Vec<u16>) {
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+    local_x: f32,
+    local_y: f32,
+    width: f32,
+    height: f32,
+) -> [f32; 4] {
+    match gradient {
+        Gradient::Linear { angle, stops } => {
+            interpolate_gradient_stops(stops, linear_gradient_position(*angle, local_x, local_y, width, height))
+        }
+        Gradient::Radial { center_x, center_y, stops } => {
+            interpolate_gradient_stops(
+                stops,
+                radial_gradient_position(*center_x, *center_y, local_x, local_y, width, height),
+            )
+        }
+        Gradient::Conic { center_x, center_y, start_angle, stops } => {
+            interpolate_gradient_stops(
+                stops,
+                conic_gradient_position(*center_x, *center_y, *start_angle, local_x, local_y, width, height),
+            )
+        }
+    }
+}
 
-    // Center point for fan triangulation
-    let center_x = x + width / 2.0;
-    let center_y = y + height / 2.0;
-    let center_idx = 0u16;
-
-    // Center vertex gets the gradient color at center
-    vertices.push(Vertex {
-        position: [center_x, center_y, 0.0],
-        texcoord: [0.5, 0.5],
-        color: compute_gradient_color(gradient, width / 2.0, height / 2.0, width, height),
-    });
-
-    // Corner definitions - same as rounded_rect
-    let corners = [
-        (x + radii[0], y + radii[0], PI, PI / 2.0, radii[0]),                    // top-left
-        (x + width - radii[1], y + radii[1], PI / 2.0, 0.0, radii[1]),           // top-right
-        (x + width - radii[2], y + height - radii[2], 0.0, -PI / 2.0, radii[2]), // bottom-right
-        (x + radii[3], y + height - radii[3], -PI / 2.0, -PI, radii[3]),         // bottom-left
-    ];
-
-    // Sharp corner positions for radius 0
-    let sharp_corners = [
-        (x, y),
-        (x + width, y),
-        (x + width, y + height),
-        (x, y + height),
-    ];
-
-    for (corner_idx, &(cx, cy, start_angle, end_angle, radius)) in corners.iter().enumerate() {
-        if radius > 0.5 {
-            // Rounded corner - generate arc with gradient colors
-            for i in 0..=CORNER_SEGMENTS {
-                let t = i as f32 / CORNER_SEGMENTS as f32;
-                let angle = start_angle + (end_angle - start_angle) * t;
-
-                let px = cx + angle.cos() * radius;
-                let py = cy - angle.sin() * radius;
-
-                // Compute position relative to rect origin for gradient
-                let rel_x = px - x;
-                let rel_y = py - y;
-
-                let u = rel_x / width;
-                let v = rel_y / height;
-
-                vertices.push(Vertex {
-                    position: [px, py, 0.0],
-                    texcoord: [u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)],
-                    color: compute_gradient_color(gradient, rel_x, rel_y, width, height),
-                });
-            }
-
-            // Create triangles from center to edge vertices
-            let start_vertex = 1 + corner_idx * (CORNER_SEGMENTS + 1);
-            for i in 0..CORNER_SEGMENTS {
-                let v1 = (start_vertex + i) as u16;
-                let v2 = (start_vertex + i + 1) as u16;
-                indices.push(center_idx);
-                indices.push(v1);
-                indices.push(v2);
-            }
-        } else {
-            // Sharp corner
-            let (sharp_x, sharp_y) = sharp_corners[corner_idx];
-            let rel_x = sharp_x - x;
-            let rel_y = sharp_y - y;
-            let u = rel_x / width;
-            let v = rel_y / height;
-            let color = compute_gradient_color(gradient, rel_x, rel_y, width, height);
-
-            for _ in 0..=CORNER_SEGMENTS {
-                vertices.push(Vertex {
-                    position: [sharp_x, sharp_y, 0.0],
-                    texcoord: [u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)],
-                    color,
-                });
-            }
-        }
-    }
-
-    // Connect corners with straight edges (same as rounded_rect)
-    let verts_per_corner = CORNER_SEGMENTS + 1;
-
-    let corner_end = |corner: usize| -> u16 {
-        (1 + corner * verts_per_corner + CORNER_SEGMENTS) as u16
-    };
-
-    let corner_start = |corner: usize| -> u16 {
-        (1 + corner * verts_per_corner) as u16
-    };
-
-    // Connect corners
-    indices.push(center_idx);
-    indices.push(corner_end(0));
-    indices.push(corner_start(1));
-
-    indices.push(center_idx);
-    indices.push(corner_end(1));
-    indices.push(corner_start(2));
-
-    indices.push(center_idx);
-    indices.push(corner_end(2));
-    indices.push(corner_start(3));
-
-    indices.push(center_idx);
-    indices.push(corner_end(3));
-    indices.push(corner_start(0));
-
-    (vertices, indices)
-}
-
-/// Compute the gradient color at a given position within a rectangle
-///
-/// # Arguments
-/// * `gradient` - The gradient specification
-/// * `local_x`, `local_y` - Position relative to the rect's top-left
-/// * `width`, `height` - Rectangle dimensions
-fn compute_gradient_color(
-    gradient: &Gradient,
-    local_x: f32,
-    local_y: f32,
-    width: f32,
-    height: f32,
-) -> [f32; 4] {
-    match gradient {
-        Gradient::Linear { angle, stops } => {
-            compute_linear_gradient_color(*angle, stops, local_x, local_y, width, height)
-        }
-        Gradient::Radial { center_x, center_y, stops } => {
-            compute_radial_gradient_color(*center_x, *center_y, stops, local_x, local_y, width, height)
-        }
-    }
-}
-
-/// Compute color for linear gradient
-fn compute_linear_gradient_color(
-    angle_deg: f32,
-    stops: &[GradientStop],
-    local_x: f32,
-    local_y: f32,
-    width: f32,
-    height: f32,
-) -> [f32; 4] {
-    if stops.is_empty() {
-        return [1.0, 1.0, 1.0, 1.0]; // Default to white
-    }
-    if stops.len() == 1 {
-        return color_to_rgba(stops[0].color);
-    }
+/// The gradient's colors at `samples` evenly spaced positions from 0.0 to
+/// 1.0, as RGBA8 rows: a lookup ramp for shaders that compute only the
+/// position along the gradient per pixel
+pub fn gradient_ramp(gradient: &Gradient, samples: usize) -> Vec<u8> {
+    let stops = match gradient {
+        Gradient::Linear { stops, .. } | Gradient::Radial { stops, .. } | Gradient::Conic { stops, .. } => stops,
+    };
+    let bands = gradient_bands(&sorted_stops(stops));
+    let last = samples.saturating_sub(1).max(1) as f32;
+    (0..samples)
+        .flat_map(|i| {
+            let t = i as f32 / last;
+            let color = bands.iter().find(|band| band.contains(t)).map_or([1.0; 4], |band| band.color_at(t));
+            color.map(|c| (c * 255.0).round() as u8)
+        })
+        .collect()
+}
 
+/// Position along a linear gradient (0.0 at the start edge, 1.0 at the
+/// end). Not clamped, and affine in the local coordinates.
+fn linear_gradient_position(angle_deg: f32, local_x: f32, local_y: f32, width: f32, height: f32) -> f32 {
     // Convert angle to radians (CSS convention: 0 = up, 90 = right)
     // We adjust so 0 = right (like standard math), then apply CSS offset
     let angle_rad = (angle_deg - 90.0).to_radians();
@@ -787,28 +1312,22 @@ fn compute_linear_gradient_color(
     let projection = centered_x * dir_x + centered_y * dir_y;
 
     // Map from [-0.5, 0.5] to [0, 1]
-    let t = (projection + 0.5).clamp(0.0, 1.0);
-
-    interpolate_gradient_stops(stops, t)
+    projection + 0.5
 }
 
-/// Compute color for radial gradient
-fn compute_radial_gradient_color(
+/// Normalized distance at which a radial gradient reaches position 1.0
+/// (1/sqrt(2), so a centered gradient ends at the corners of a square)
+const RADIAL_GRADIENT_REACH: f32 = 0.707;
+
+/// Position along a radial gradient (0.0 at the center). Not clamped.
+fn radial_gradient_position(
     center_x: f32,
     center_y: f32,
-    stops: &[GradientStop],
     local_x: f32,
     local_y: f32,
     width: f32,
     height: f32,
-) -> [f32; 4] {
-    if stops.is_empty() {
-        return [1.0, 1.0, 1.0, 1.0];
-    }
-    if stops.len() == 1 {
-        return color_to_rgba(stops[0].color);
-    }
-
+) -> f32 {
     // Normalize position to 0-1 range
     let norm_x = if width > 0.0 { local_x / width } else { 0.5 };
     let norm_y = if height > 0.0 { local_y / height } else { 0.5 };
@@ -816,16 +1335,88 @@ fn compute_radial_gradient_color(
     // Distance from center (normalized)
     let dx = norm_x - center_x;
     let dy = norm_y - center_y;
+    (dx * dx + dy * dy).sqrt() / RADIAL_GRADIENT_REACH
+}
+
+/// Position around a conic gradient: the fraction of a clockwise turn from
+/// `start_angle` (degrees from 12 o'clock), in 0.0-1.0. Angles are measured
+/// in pixels, so the sweep stays even in a rect that isn't square.
+fn conic_gradient_position(
+    center_x: f32,
+    center_y: f32,
+    start_angle: f32,
+    local_x: f32,
+    local_y: f32,
+    width: f32,
+    height: f32,
+) -> f32 {
+    let dx = local_x - center_x * width;
+    let dy = local_y - center_y * height;
+    let angle = dx.atan2(-dy).to_degrees();
+    ((angle - start_angle) / 360.0).rem_euclid(1.0)
+}
 
-    // For a circular gradient that reaches the corners, max distance is ~0.707 from center
-    // We scale so that distance 0.5 = edge of the inscribed circle
-    let distance = (dx * dx + dy * dy).sqrt();
+/// Stops with positions clamped to 0.0-1.0 (NaN counts as 0.0), sorted by
+/// position. The sort is stable, so stops sharing a position keep their
+/// order and make a hard edge.
+fn sorted_stops(stops: &[GradientStop]) -> Vec<GradientStop> {
+    let mut sorted: Vec<GradientStop> = stops
+        .iter()
+        .map(|stop| GradientStop {
+            position: if stop.position.is_nan() { 0.0 } else { stop.position.clamp(0.0, 1.0) },
+            color: stop.color,
+        })
+        .collect();
+    sorted.sort_by(|a, b| a.position.total_cmp(&b.position));
+    sorted
+}
 
-    // Map distance to gradient position (0 at center, 1 at edge/corner)
-    // Using 0.707 (1/sqrt(2)) as the "full" distance for a square
-    let t = (distance / 0.707).clamp(0.0, 1.0);
+/// A stretch of gradient positions between two neighbouring stops. The
+/// bands before the first stop and after the last are open-ended and solid.
+struct GradientBand {
+    start: Option<f32>,
+    end: Option<f32>,
+    from: [f32; 4],
+    to: [f32; 4],
+}
 
-    interpolate_gradient_stops(stops, t)
+impl GradientBand {
+    /// Whether position `t` falls in this band (start inclusive)
+    fn contains(&self, t: f32) -> bool {
+        self.start.is_none_or(|start| t >= start) && self.end.is_none_or(|end| t < end)
+    }
+
+    /// Color at position `t`, held at the band's end colors outside it
+    fn color_at(&self, t: f32) -> [f32; 4] {
+        let local_t = match (self.start, self.end) {
+            (Some(start), Some(end)) if end > start => ((t - start) / (end - start)).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        std::array::from_fn(|i| self.from[i] + (self.to[i] - self.from[i]) * local_t)
+    }
+}
+
+/// Bands covering every position for stops from [`sorted_stops`]. Stops
+/// sharing a position have no band between them.
+fn gradient_bands(stops: &[GradientStop]) -> Vec<GradientBand> {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return Vec::new();
+    };
+    let solid = |start, end, color| GradientBand { start, end, from: color, to: color };
+
+    let mut bands = vec![solid(None, Some(first.position), color_to_rgba(first.color))];
+    for pair in stops.windows(2) {
+        if pair[1].position > pair[0].position {
+            bands.push(GradientBand {
+                start: Some(pair[0].position),
+                end: Some(pair[1].position),
+                from: color_to_rgba(pair[0].color),
+                to: color_to_rgba(pair[1].color),
+            });
+        }
+    }
+    bands.push(solid(Some(last.position), None, color_to_rgba(last.color)));
+    bands
 }
 
 // ===== Shadow Support =====
@@ -968,13 +1559,7 @@ fn shadow_layer_rect(
     radii: [f32; 4],
 ) -> (Vec<Vertex>, Vec<u16>) {
     // Clamp radii
-    let max_radius = (width.min(height)) / 2.0;
-    let radii = [
-        radii[0].min(max_radius).max(0.0),
-        radii[1].min(max_radius).max(0.0),
-        radii[2].min(max_radius).max(0.0),
-        radii[3].min(max_radius).max(0.0),
-    ];
+    let radii = clamp_radii(radii, width, height);
 
     let has_rounded = radii.iter().any(|&r| r > 0.5);
 
@@ -1089,45 +1674,591 @@ fn shadow_layer_rect(
     (vertices, indices)
 }
 
-/// Interpolate between gradient stops at position t (0.0 to 1.0)
-fn interpolate_gradient_stops(stops: &[GradientStop], t: f32) -> [f32; 4] {
-    // Find the two stops we're between
-    let mut prev_stop = &stops[0];
-    let mut next_stop = &stops[stops.len() - 1];
-
-    for i in 0..stops.len() - 1 {
-        if t >= stops[i].position && t <= stops[i + 1].position {
-            prev_stop = &stops[i];
-            next_stop = &stops[i + 1];
-            break;
+// ===== Box Shadow Support =====
+
+/// Generate vertices and indices for a shadow cast outward from a rounded rect
+///
+/// The shadow's shape is the rect moved by the shadow offset and grown by
+/// the spread (its corners grow with it). Its edge is blurred across
+/// `blur_radius` pixels on either side, with an S-shaped falloff close to
+/// the Gaussian blur CSS `box-shadow` uses. The geometry is a stack of rings
+/// following the shape's outline from outside the blur inward, each vertex
+/// shaded by its distance from the edge, around a solid middle.
+///
+/// # Arguments
+/// * `x`, `y` - Top-left position of the rect
+/// * `width`, `height` - Size of the rect
+/// * `corner_radii` - Corner radii of the rect [top-left, top-right, bottom-right, bottom-left]
+/// * `shadow` - Offset, blur radius and spread in pixels, and color
+///
+/// # Returns
+/// (vertices, indices) for rendering with DrawTriangles
+pub fn box_shadow_rect(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    corner_radii: [f32; 4],
+    shadow: &BoxShadow,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let radii = clamp_radii(corner_radii, width, height);
+    let shape = Rect::new(x + shadow.offset_x, y + shadow.offset_y, width, height)
+        .inset(-shadow.spread, -shadow.spread);
+    if shape.is_empty() || shadow.color & 0xFF == 0 {
+        return (vertices, indices);
+    }
+    // Square corners stay square, as in CSS
+    let shape_radii = clamp_radii(
+        radii.map(|r| if r > 0.0 { r + shadow.spread } else { 0.0 }),
+        shape.width,
+        shape.height,
+    );
+
+    let blur = shadow.blur_radius.max(0.0);
+    let base = color_to_rgba(shadow.color);
+    let shade = |px: f32, py: f32| -> [f32; 4] {
+        // Half shaded on the shape's edge, fading out across the blur
+        let distance = rounded_rect_distance(px, py, shape, shape_radii);
+        let coverage = if blur > 0.0 {
+            (0.5 - distance / (2.0 * blur)).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        [base[0], base[1], base[2], base[3] * shadow_ease(coverage)]
+    };
+    let push_ring = |vertices: &mut Vec<Vertex>, outline: Vec<(f32, f32)>| {
+        for (px, py) in outline {
+            vertices.push(Vertex {
+                position: [px, py, 0.0],
+                texcoord: [(px - shape.x) / shape.width, (py - shape.y) / shape.height],
+                color: shade(px, py),
+            });
         }
+    };
+
+    // Rings from `blur` outside the edge to `blur` inside it (or the middle)
+    let depth = blur.min(shape.width.min(shape.height) / 2.0);
+    let rings = if blur > 0.0 { shadow_layer_count(blur) } else { 0 };
+    let mut ring_len = 0;
+    for ring in 0..=rings {
+        let inset = if rings == 0 { 0.0 } else { -blur + (blur + depth) * ring as f32 / rings as f32 };
+        let outline = inset_outline(shape.x, shape.y, shape.width, shape.height, shape_radii, inset);
+        ring_len = outline.len();
+        push_ring(&mut vertices, outline);
     }
 
-    // Handle edge cases
-    if t <= prev_stop.position {
-        return color_to_rgba(prev_stop.color);
+    // Stitch each ring to the next, skipping quads too faint to see
+    for ring in 0..rings {
+        let outer = ring * ring_len;
+        let inner = outer + ring_len;
+        for i in 0..ring_len {
+            let next = (i + 1) % ring_len;
+            let quad = [outer + i, outer + next, inner + next, inner + i];
+            if quad.iter().all(|&v| vertices[v].color[3] < 0.005) {
+                continue;
+            }
+            indices.extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]].map(|v| v as u16));
+        }
     }
-    if t >= next_stop.position {
-        return color_to_rgba(next_stop.color);
+
+    // The innermost ring is convex, so a fan fills the middle
+    let middle = rings * ring_len;
+    for i in 1..ring_len - 1 {
+        indices.extend([middle, middle + i, middle + i + 1].map(|v| v as u16));
     }
 
-    // Interpolate between the two stops
-    let range = next_stop.position - prev_stop.position;
-    let local_t = if range > 0.0 {
-        (t - prev_stop.position) / range
-    } else {
-        0.0
+    (vertices, indices)
+}
+
+// ===== Inner Shadow Support =====
+
+/// Points added along each straight edge of an inner shadow ring, so the
+/// falloff of an offset shadow is sampled between the corners too
+const INNER_SHADOW_EDGE_SEGMENTS: usize = 8;
+
+/// Generate vertices and indices for a shadow inside a rounded rect
+///
+/// The shadow covers the rect outside a "hole" - the rect moved by the
+/// shadow offset and shrunk by the spread - with the hole's edge blurred
+/// over `blur` pixels. The geometry is a stack of rings following the
+/// rect's outline inward, each vertex shaded by its distance from the hole,
+/// so it never leaves the rect and leaves the middle untouched.
+///
+/// # Arguments
+/// * `x`, `y` - Top-left position of the rect
+/// * `width`, `height` - Size of the rect
+/// * `corner_radii` - Corner radii of the rect [top-left, top-right, bottom-right, bottom-left]
+/// * `shadow` - Blur, spread and offset in pixels, and color
+///
+/// # Returns
+/// (vertices, indices) for rendering with DrawTriangles
+pub fn inner_shadow_rect(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    corner_radii: [f32; 4],
+    shadow: &InnerShadow,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let max_radius = width.min(height) / 2.0;
+    let blur = shadow.blur.max(0.0);
+    // Deeper into the rect than this, every point is well inside the hole
+    let offset = shadow.offset_x.abs().max(shadow.offset_y.abs());
+    let depth = (shadow.spread.max(0.0) + blur + offset).min(max_radius);
+    if depth <= 0.0 || shadow.color & 0xFF == 0 {
+        return (vertices, indices);
+    }
+
+    let radii = clamp_radii(corner_radii, width, height);
+    let hole = Rect::new(x + shadow.offset_x, y + shadow.offset_y, width, height)
+        .inset(shadow.spread, shadow.spread);
+    let hole_radii = radii.map(|r| (r - shadow.spread).max(0.0));
+
+    let base = color_to_rgba(shadow.color);
+    let shade = |px: f32, py: f32| -> [f32; 4] {
+        // Half shaded on the hole's edge, fading across the blur
+        let distance = rounded_rect_distance(px, py, hole, hole_radii);
+        let coverage = if blur > 0.0 {
+            (distance / blur + 0.5).clamp(0.0, 1.0)
+        } else if distance > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        [base[0], base[1], base[2], base[3] * shadow_ease(coverage)]
     };
 
-    let c1 = color_to_rgba(prev_stop.color);
-    let c2 = color_to_rgba(next_stop.color);
+    // Rings from the rect's outline inward, all with the same point count
+    let rings = shadow_layer_count(depth);
+    let mut ring_len = 0;
+    for ring in 0..=rings {
+        let outline = inset_outline(x, y, width, height, radii, depth * ring as f32 / rings as f32);
+        ring_len = outline.len();
+        for (px, py) in outline {
+            vertices.push(Vertex {
+                position: [px, py, 0.0],
+                texcoord: [(px - x) / width, (py - y) / height],
+                color: shade(px, py),
+            });
+        }
+    }
 
-    [
-        c1[0] + (c2[0] - c1[0]) * local_t,
-        c1[1] + (c2[1] - c1[1]) * local_t,
-        c1[2] + (c2[2] - c1[2]) * local_t,
-        c1[3] + (c2[3] - c1[3]) * local_t,
-    ]
+    // Stitch each ring to the next, skipping quads too faint to see
+    for ring in 0..rings {
+        let outer = ring * ring_len;
+        let inner = outer + ring_len;
+        for i in 0..ring_len {
+            let next = (i + 1) % ring_len;
+            let quad = [outer + i, outer + next, inner + next, inner + i];
+            if quad.iter().all(|&v| vertices[v].color[3] < 0.005) {
+                continue;
+            }
+            indices.extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]].map(|v| v as u16));
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Generate a dashed ring just outside a rounded rect, as drawn for a focus ring
+///
+/// The ring is `thickness` wide and follows the rect's corners. Dashes and
+/// gaps of equal length run clockwise from the top-left corner, shifted
+/// along the outline by `offset`. The dash length is stretched slightly so
+/// a whole number of dashes fits around the ring with no seam.
+///
+/// # Arguments
+/// * `rect` - The rect the ring surrounds
+/// * `corner_radii` - Corner radii of the rect [top-left, top-right, bottom-right, bottom-left]
+/// * `thickness` - Width of the ring in pixels
+/// * `dash` - Length of each dash (and each gap) in pixels
+/// * `offset` - Distance along the outline the dash pattern is shifted by
+/// * `color` - Color as 0xRRGGBBAA
+///
+/// # Returns
+/// (vertices, indices) for rendering with DrawTriangles
+pub fn dashed_outline(
+    rect: Rect,
+    corner_radii: [f32; 4],
+    thickness: f32,
+    dash: f32,
+    offset: f32,
+    color: u32,
+) -> (Vec<Vertex>, Vec<u16>) {
+    if rect.is_empty() || thickness <= 0.0 || color & 0xFF == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let Rect { x, y, width, height } = rect;
+    let radii = clamp_radii(corner_radii, width, height);
+    let inner = inset_outline(x, y, width, height, radii, 0.0);
+    let outer = inset_outline(x, y, width, height, radii, -thickness);
+    let center = inset_outline(x, y, width, height, radii, -thickness / 2.0);
+    dashed_band(&inner, &outer, &center, dash.max(1.0), dash.max(1.0), offset, color)
+}
+
+/// Farthest a tessellated path's flattened curves stray from the true
+/// curves, in pixels
+const PATH_TOLERANCE: f32 = 0.1;
+
+/// Generate the triangles of a filled and/or stroked path
+///
+/// Curves are flattened and the shapes tessellated with lyon. The fill
+/// comes first in the buffers so the stroke draws over it; subpaths left
+/// open are filled as if closed. Empty buffers are returned when there is
+/// nothing to draw, or when the path needs more vertices than u16 indices
+/// can address.
+///
+/// # Arguments
+/// * `segments` - The outline, with coordinates multiplied by `scale`
+/// * `fill` - Fill color as 0xRRGGBBAA, or None for no fill
+/// * `fill_rule` - Which parts of overlapping subpaths are inside
+/// * `stroke` - Stroke width (also multiplied by `scale`), color, caps and joins
+/// * `scale` - Pixels per path unit
+///
+/// # Returns
+/// (vertices, indices) for rendering with DrawTriangles
+pub fn tessellated_path(
+    segments: &[PathSegment],
+    fill: Option<u32>,
+    fill_rule: FillRule,
+    stroke: Option<&Stroke>,
+    scale: f32,
+) -> (Vec<Vertex>, Vec<u16>) {
+    use lyon::tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator, StrokeVertex,
+        VertexBuffers,
+    };
+
+    let path = lyon_path(segments, scale);
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+
+    if let Some(color) = fill.filter(|c| c & 0xFF != 0) {
+        let rgba = color_to_rgba(color);
+        let rule = match fill_rule {
+            FillRule::NonZero => lyon::tessellation::FillRule::NonZero,
+            FillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        };
+        let options = FillOptions::tolerance(PATH_TOLERANCE).with_fill_rule(rule);
+        let mut builder = BuffersBuilder::new(&mut buffers, |vertex: FillVertex| path_vertex(vertex.position(), rgba));
+        if FillTessellator::new().tessellate_path(&path, &options, &mut builder).is_err() {
+            return (Vec::new(), Vec::new());
+        }
+    }
+
+    if let Some(stroke) = stroke.filter(|s| s.width > 0.0 && s.color & 0xFF != 0) {
+        let rgba = color_to_rgba(stroke.color);
+        let cap = match stroke.cap {
+            LineCap::Butt => lyon::tessellation::LineCap::Butt,
+            LineCap::Round => lyon::tessellation::LineCap::Round,
+            LineCap::Square => lyon::tessellation::LineCap::Square,
+        };
+        let join = match stroke.join {
+            LineJoin::Miter => lyon::tessellation::LineJoin::Miter,
+            LineJoin::Round => lyon::tessellation::LineJoin::Round,
+            LineJoin::Bevel => lyon::tessellation::LineJoin::Bevel,
+        };
+        let options = StrokeOptions::tolerance(PATH_TOLERANCE)
+            .with_line_width(stroke.width * scale)
+            .with_line_cap(cap)
+            .with_line_join(join);
+        let mut builder = BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| path_vertex(vertex.position(), rgba));
+        if StrokeTessellator::new().tessellate_path(&path, &options, &mut builder).is_err() {
+            return (Vec::new(), Vec::new());
+        }
+    }
+
+    (buffers.vertices, buffers.indices)
+}
+
+fn path_vertex(position: lyon::math::Point, rgba: [f32; 4]) -> Vertex {
+    Vertex { position: [position.x, position.y, 0.0], texcoord: [0.0, 0.0], color: rgba }
+}
+
+/// Build a lyon path from path segments, in pixels.
+///
+/// A segment before any MoveTo starts from the origin, and one after a
+/// Close starts a new subpath where the closed one began, as in SVG.
+fn lyon_path(segments: &[PathSegment], scale: f32) -> lyon::path::Path {
+    use lyon::math::point;
+
+    let mut builder = lyon::path::Path::builder();
+    let mut open = false;
+    let mut start = point(0.0, 0.0);
+    let mut current = start;
+    for segment in segments {
+        if !open && !matches!(segment, PathSegment::MoveTo { .. } | PathSegment::Close {}) {
+            builder.begin(current);
+            start = current;
+            open = true;
+        }
+        match *segment {
+            PathSegment::MoveTo { x, y } => {
+                if open {
+                    builder.end(false);
+                }
+                current = point(x * scale, y * scale);
+                builder.begin(current);
+                start = current;
+                open = true;
+            }
+            PathSegment::LineTo { x, y } => {
+                current = point(x * scale, y * scale);
+                builder.line_to(current);
+            }
+            PathSegment::QuadTo { cx, cy, x, y } => {
+                current = point(x * scale, y * scale);
+                builder.quadratic_bezier_to(point(cx * scale, cy * scale), current);
+            }
+            PathSegment::CubicTo { c1x, c1y, c2x, c2y, x, y } => {
+                current = point(x * scale, y * scale);
+                builder.cubic_bezier_to(point(c1x * scale, c1y * scale), point(c2x * scale, c2y * scale), current);
+            }
+            PathSegment::Close {} => {
+                if open {
+                    builder.end(true);
+                    open = false;
+                }
+                current = start;
+            }
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+    builder.build()
+}
+
+/// Generate a dashed or dotted border inside a rounded rect
+///
+/// Solid borders go through `border_rect`. Dashes are `dash_length` long
+/// with `gap_length` between them (0 picks 3x the border width and a gap
+/// the same as the dash); dots are round, as wide as the border and
+/// `gap_length` apart (0 picks the border width). Either pattern is
+/// stretched slightly so it runs around the border with no seam.
+///
+/// # Arguments
+/// * `x`, `y` - Top-left position
+/// * `width`, `height` - Outer size
+/// * `border` - Border width, color, style and pattern lengths in pixels
+/// * `radii` - Corner radii [top-left, top-right, bottom-right, bottom-left]
+pub fn styled_border(x: f32, y: f32, width: f32, height: f32, border: &Border, radii: [f32; 4]) -> (Vec<Vertex>, Vec<u16>) {
+    let border_width = border.width;
+    if border.style == BorderStyle::Solid {
+        return border_rect(x, y, width, height, border_width, border.color, radii);
+    }
+    if width <= 0.0 || height <= 0.0 || border_width <= 0.0 || border.color & 0xFF == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let radii = clamp_radii(radii, width, height);
+    let center = inset_outline(x, y, width, height, radii, border_width / 2.0);
+    if border.style == BorderStyle::Dashed {
+        let outer = inset_outline(x, y, width, height, radii, 0.0);
+        let inner = inset_outline(x, y, width, height, radii, border_width);
+        let dash = if border.dash_length > 0.0 { border.dash_length } else { (border_width * 3.0).max(1.0) };
+        let gap = if border.gap_length > 0.0 { border.gap_length } else { dash };
+        return dashed_band(&outer, &inner, &center, dash, gap, 0.0, border.color);
+    }
+
+    // Dotted: a round dot every period along the border's centerline
+    let (distances, length) = outline_distances(&center);
+    let gap = if border.gap_length > 0.0 { border.gap_length } else { border_width };
+    let dots = (length / (border_width + gap).max(1.0)).round().max(1.0) as usize;
+    let period = length / dots as f32;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for k in 0..dots {
+        let (cx, cy) = point_along(&center, &distances, k as f32 * period);
+        let (dot_vertices, dot_indices) = circle(cx, cy, border_width / 2.0, border.color, DOT_SEGMENTS);
+        let base = vertices.len() as u16;
+        vertices.extend(dot_vertices);
+        indices.extend(dot_indices.into_iter().map(|i| base + i));
+    }
+    (vertices, indices)
+}
+
+/// Dashes along a closed band between two outlines
+///
+/// `edge_a`, `edge_b` and `center` are the band's two edges and its
+/// centerline, with matching points as from `inset_outline`. Dashes are
+/// measured along the centerline, run from its first point shifted by
+/// `offset`, and are stretched so a whole number of dash+gap periods fits.
+fn dashed_band(
+    edge_a: &[(f32, f32)],
+    edge_b: &[(f32, f32)],
+    center: &[(f32, f32)],
+    dash: f32,
+    gap: f32,
+    offset: f32,
+    color: u32,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let count = center.len();
+    let (distances, length) = outline_distances(center);
+    if length <= 0.0 {
+        return (vertices, indices);
+    }
+
+    // Edge points at a distance along the centerline
+    let edge_at = |segment: usize, distance: f32| -> [(f32, f32); 2] {
+        let (start, end) = (distances[segment], distances[segment + 1]);
+        let t = if end > start { (distance - start) / (end - start) } else { 0.0 };
+        let lerp = |from: (f32, f32), to: (f32, f32)| (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        let next = (segment + 1) % count;
+        [lerp(edge_a[segment], edge_a[next]), lerp(edge_b[segment], edge_b[next])]
+    };
+
+    let rgba = color_to_rgba(color);
+    let mut emit_dash = |from: f32, to: f32| {
+        let mut edges = Vec::new();
+        let first = distances.partition_point(|&d| d <= from).saturating_sub(1).min(count - 1);
+        edges.push(edge_at(first, from));
+        for segment in first + 1..count {
+            if distances[segment] >= to {
+                break;
+            }
+            edges.push([edge_a[segment], edge_b[segment]]);
+        }
+        let last = distances.partition_point(|&d| d < to).saturating_sub(1).min(count - 1);
+        edges.push(edge_at(last, to));
+
+        let base = vertices.len() as u16;
+        for (i, [(ax, ay), (bx, by)]) in edges.iter().copied().enumerate() {
+            let t = i as f32 / (edges.len() - 1) as f32;
+            vertices.push(Vertex { position: [ax, ay, 0.0], texcoord: [t, 0.0], color: rgba });
+            vertices.push(Vertex { position: [bx, by, 0.0], texcoord: [t, 1.0], color: rgba });
+        }
+        for i in 0..edges.len() as u16 - 1 {
+            let v = base + i * 2;
+            indices.extend([v, v + 2, v + 1, v + 1, v + 2, v + 3]);
+        }
+    };
+
+    // Fit a whole number of dash+gap periods around the band
+    let periods = (length / (dash + gap)).round().max(1.0);
+    let period = length / periods;
+    let dash_length = period * dash / (dash + gap);
+    let start = offset.rem_euclid(period);
+    for k in 0..periods as usize {
+        let from = start + k as f32 * period;
+        let to = from + dash_length;
+        if to <= length {
+            emit_dash(from, to);
+        } else {
+            // Wraps past the first point
+            emit_dash(from, length);
+            emit_dash(0.0, to - length);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Distance along a closed outline to each of its points, plus a final
+/// entry for the way back to the first point, and the total length
+fn outline_distances(points: &[(f32, f32)]) -> (Vec<f32>, f32) {
+    let count = points.len();
+    let mut distances = Vec::with_capacity(count + 1);
+    let mut length = 0.0;
+    for i in 0..=count {
+        if i > 0 {
+            let (ax, ay) = points[i - 1];
+            let (bx, by) = points[i % count];
+            length += (bx - ax).hypot(by - ay);
+        }
+        distances.push(length);
+    }
+    (distances, length)
+}
+
+/// The point `distance` along a closed outline, given its `outline_distances`
+fn point_along(points: &[(f32, f32)], distances: &[f32], distance: f32) -> (f32, f32) {
+    let count = points.len();
+    let segment = distances.partition_point(|&d| d <= distance).saturating_sub(1).min(count - 1);
+    let (start, end) = (distances[segment], distances[segment + 1]);
+    let t = if end > start { (distance - start) / (end - start) } else { 0.0 };
+    let (ax, ay) = points[segment];
+    let (bx, by) = points[(segment + 1) % count];
+    (ax + (bx - ax) * t, ay + (by - ay) * t)
+}
+
+/// Points around a rounded rect's outline moved `inset` pixels inward (or
+/// outward when negative), clockwise from the top-left corner. Every inset
+/// gives the same number of points, so consecutive outlines can be stitched
+/// into rings.
+fn inset_outline(x: f32, y: f32, width: f32, height: f32, radii: [f32; 4], inset: f32) -> Vec<(f32, f32)> {
+    // A corner's arc center stays put until the inset passes its radius
+    let corner = |r: f32| (r.max(inset), (r - inset).max(0.0));
+    let (c0, r0) = corner(radii[0]);
+    let (c1, r1) = corner(radii[1]);
+    let (c2, r2) = corner(radii[2]);
+    let (c3, r3) = corner(radii[3]);
+    let corners = [
+        (x + c0, y + c0, PI, PI / 2.0, r0),                    // top-left
+        (x + width - c1, y + c1, PI / 2.0, 0.0, r1),           // top-right
+        (x + width - c2, y + height - c2, 0.0, -PI / 2.0, r2), // bottom-right
+        (x + c3, y + height - c3, -PI / 2.0, -PI, r3),         // bottom-left
+    ];
+
+    let mut points = Vec::with_capacity(4 * (CORNER_SEGMENTS + 1 + INNER_SHADOW_EDGE_SEGMENTS));
+    for (i, &(cx, cy, start_angle, end_angle, radius)) in corners.iter().enumerate() {
+        for segment in 0..=CORNER_SEGMENTS {
+            let angle = start_angle + (end_angle - start_angle) * segment as f32 / CORNER_SEGMENTS as f32;
+            points.push((cx + angle.cos() * radius, cy - angle.sin() * radius)); // Flip Y for screen coordinates
+        }
+
+        // Straight edge to the start of the next corner
+        let (from_x, from_y) = points[points.len() - 1];
+        let (nx, ny, next_angle, _, next_radius) = corners[(i + 1) % 4];
+        let (to_x, to_y) = (nx + next_angle.cos() * next_radius, ny - next_angle.sin() * next_radius);
+        for segment in 1..=INNER_SHADOW_EDGE_SEGMENTS {
+            let t = segment as f32 / (INNER_SHADOW_EDGE_SEGMENTS + 1) as f32;
+            points.push((from_x + (to_x - from_x) * t, from_y + (to_y - from_y) * t));
+        }
+    }
+    points
+}
+
+/// Signed distance from a point to a rounded rect's outline (negative
+/// inside). An empty rect is infinitely far away.
+fn rounded_rect_distance(px: f32, py: f32, rect: Rect, radii: [f32; 4]) -> f32 {
+    if rect.is_empty() {
+        return f32::INFINITY;
+    }
+    let half_width = rect.width / 2.0;
+    let half_height = rect.height / 2.0;
+    let center = rect.center();
+    let dx = px - center.x;
+    let dy = py - center.y;
+    let radius = match (dx >= 0.0, dy >= 0.0) {
+        (false, false) => radii[0],
+        (true, false) => radii[1],
+        (true, true) => radii[2],
+        (false, true) => radii[3],
+    }
+    .min(half_width.min(half_height));
+
+    let qx = dx.abs() - half_width + radius;
+    let qy = dy.abs() - half_height + radius;
+    qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius
+}
+
+/// Color at gradient position `t`; positions outside the stops take the
+/// nearest end stop's color
+fn interpolate_gradient_stops(stops: &[GradientStop], t: f32) -> [f32; 4] {
+    gradient_bands(&sorted_stops(stops))
+        .iter()
+        .find(|band| band.contains(t))
+        .map_or([1.0, 1.0, 1.0, 1.0], |band| band.color_at(t)) // No stops: white
 }
 
 #[cfg(test)]
@@ -1149,6 +2280,147 @@ mod tests {
         assert!(indices.len() > 0);
     }
 
+    #[test]
+    fn test_oversized_radius_clamps_to_pill() {
+        let positions = |(verts, _): (Vec<Vertex>, Vec<u16>)| -> Vec<[f32; 3]> {
+            verts.iter().map(|v| v.position).collect()
+        };
+
+        // A 100 radius on a 40x40 rect is the same circle as a 20 radius
+        assert_eq!(
+            positions(rounded_rect(0.0, 0.0, 40.0, 40.0, 0xFFFFFFFF, [100.0; 4])),
+            positions(rounded_rect(0.0, 0.0, 40.0, 40.0, 0xFFFFFFFF, [20.0; 4])),
+        );
+        assert_eq!(
+            positions(border_rect(0.0, 0.0, 40.0, 40.0, 2.0, 0xFFFFFFFF, [100.0; 4])),
+            positions(border_rect(0.0, 0.0, 40.0, 40.0, 2.0, 0xFFFFFFFF, [20.0; 4])),
+        );
+        let gradient = Gradient::horizontal(0xFF0000FF, 0x0000FFFF);
+        assert_eq!(
+            positions(gradient_rect(0.0, 0.0, 40.0, 40.0, &gradient, [100.0; 4])),
+            positions(gradient_rect(0.0, 0.0, 40.0, 40.0, &gradient, [20.0; 4])),
+        );
+
+        // Every vertex stays inside the rect
+        let (verts, _) = rounded_rect(0.0, 0.0, 100.0, 40.0, 0xFFFFFFFF, [100.0, 0.0, 100.0, 0.0]);
+        assert!(verts.iter().all(|v| (0.0..=100.001).contains(&v.position[0]) && (0.0..=40.001).contains(&v.position[1])));
+
+        // Negative and NaN radii are square corners
+        assert_eq!(clamp_radii([-5.0, f32::NAN, 8.0, 30.0], 40.0, 20.0), [0.0, 0.0, 8.0, 10.0]);
+        assert_eq!(clamp_radii([5.0; 4], -10.0, 20.0), [0.0; 4]);
+    }
+
+    #[test]
+    fn test_gradient_stops_split_the_fill() {
+        let stop = |position, color| GradientStop { position, color };
+        let colors_at_x = |verts: &[Vertex], x: f32| -> Vec<[f32; 4]> {
+            verts.iter().filter(|v| (v.position[0] - x).abs() < 0.01).map(|v| v.color).collect()
+        };
+
+        // A middle stop gets its own vertices, in any order the stops come in
+        let three = Gradient::Linear {
+            angle: 90.0,
+            stops: vec![stop(1.0, 0x0000FFFF), stop(0.0, 0xFF0000FF), stop(0.5, 0x00FF00FF)],
+        };
+        let (verts, indices) = gradient_rect(0.0, 0.0, 100.0, 20.0, &three, [0.0; 4]);
+        assert!(!indices.is_empty());
+        let middle = colors_at_x(&verts, 50.0);
+        assert!(!middle.is_empty() && middle.iter().all(|&c| c == [0.0, 1.0, 0.0, 1.0]));
+        assert_eq!(compute_gradient_color(&three, 25.0, 10.0, 100.0, 20.0), [0.5, 0.5, 0.0, 1.0]);
+
+        // Stops sharing a position make a hard edge; offsets are clamped
+        let hard = Gradient::Linear {
+            angle: 90.0,
+            stops: vec![stop(-1.0, 0xFF0000FF), stop(0.5, 0xFF0000FF), stop(0.5, 0x0000FFFF), stop(2.0, 0x0000FFFF)],
+        };
+        let (verts, _) = gradient_rect(0.0, 0.0, 100.0, 20.0, &hard, [4.0; 4]);
+        assert!(verts.iter().filter(|v| v.position[0] < 49.99).all(|v| v.color == [1.0, 0.0, 0.0, 1.0]));
+        assert!(verts.iter().filter(|v| v.position[0] > 50.01).all(|v| v.color == [0.0, 0.0, 1.0, 1.0]));
+        let edge = colors_at_x(&verts, 50.0);
+        assert!(edge.contains(&[1.0, 0.0, 0.0, 1.0]) && edge.contains(&[0.0, 0.0, 1.0, 1.0]));
+
+        // One stop is a solid fill
+        let solid = Gradient::Radial { center_x: 0.5, center_y: 0.5, stops: vec![stop(0.3, 0x00FF00FF)] };
+        let (verts, _) = gradient_rect(0.0, 0.0, 40.0, 40.0, &solid, [0.0; 4]);
+        assert!(verts.iter().all(|v| v.color == [0.0, 1.0, 0.0, 1.0]));
+
+        // Radial: the first stop at the center, the last at the corners, and
+        // nothing outside the rect
+        let radial = Gradient::Radial {
+            center_x: 0.5,
+            center_y: 0.5,
+            stops: vec![stop(0.0, 0xFFFFFFFF), stop(0.5, 0xFF0000FF), stop(1.0, 0x000000FF)],
+        };
+        let (verts, indices) = gradient_rect(0.0, 0.0, 40.0, 40.0, &radial, [0.0; 4]);
+        assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+        assert!(verts.iter().all(|v| (-0.01..=40.01).contains(&v.position[0]) && (-0.01..=40.01).contains(&v.position[1])));
+        let at = |x: f32, y: f32| verts.iter().find(|v| (v.position[0] - x).abs() < 0.01 && (v.position[1] - y).abs() < 0.01).map(|v| v.color);
+        assert_eq!(at(20.0, 20.0), Some([1.0, 1.0, 1.0, 1.0]));
+        assert_eq!(at(0.0, 0.0).map(|c| c[0] < 0.01), Some(true));
+    }
+
+    #[test]
+    fn test_gradient_ramp_samples_stops() {
+        let ramp = gradient_ramp(&Gradient::horizontal(0xFF0000FF, 0x0000FFFF), 3);
+        assert_eq!(ramp, vec![255, 0, 0, 255, 128, 0, 128, 255, 0, 0, 255, 255]);
+
+        // No stops is white, like the vertex path
+        let empty = Gradient::Linear { angle: 0.0, stops: Vec::new() };
+        assert_eq!(gradient_ramp(&empty, 2), vec![255; 8]);
+    }
+
+    #[test]
+    fn test_conic_gradient_sweeps_from_start_angle() {
+        let stop = |position, color| GradientStop { position, color };
+        let near = |a: [f32; 4], b: [f32; 4]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 0.02);
+
+        // Red at 12 o'clock through green at 6 back to red: a ring with no seam
+        let ring = Gradient::Conic {
+            center_x: 0.5,
+            center_y: 0.5,
+            start_angle: 0.0,
+            stops: vec![stop(0.0, 0xFF0000FF), stop(0.5, 0x00FF00FF), stop(1.0, 0xFF0000FF)],
+        };
+        assert!(near(compute_gradient_color(&ring, 20.0, 0.0, 40.0, 40.0), [1.0, 0.0, 0.0, 1.0]));
+        assert!(near(compute_gradient_color(&ring, 40.0, 20.0, 40.0, 40.0), [0.5, 0.5, 0.0, 1.0]));
+        assert!(near(compute_gradient_color(&ring, 20.0, 40.0, 40.0, 40.0), [0.0, 1.0, 0.0, 1.0]));
+        // Just either side of the seam
+        assert!(near(compute_gradient_color(&ring, 19.9, 0.0, 40.0, 40.0), [1.0, 0.0, 0.0, 1.0]));
+        assert!(near(compute_gradient_color(&ring, 20.1, 0.0, 40.0, 40.0), [1.0, 0.0, 0.0, 1.0]));
+
+        // The fill stays in the rect, and vertices take their angle's color
+        let (verts, indices) = gradient_rect(0.0, 0.0, 40.0, 40.0, &ring, [20.0; 4]);
+        assert!(!indices.is_empty() && indices.iter().all(|&i| (i as usize) < verts.len()));
+        assert!(verts.iter().all(|v| (-0.01..=40.01).contains(&v.position[0]) && (-0.01..=40.01).contains(&v.position[1])));
+        for v in verts.iter().filter(|v| (v.position[0] - 20.0).hypot(v.position[1] - 20.0) > 1.0) {
+            let expected = compute_gradient_color(&ring, v.position[0], v.position[1], 40.0, 40.0);
+            assert!(near(v.color, expected), "{:?} at {:?}", v.color, v.position);
+        }
+
+        // A progress ring at 25% from 12 o'clock: a hard edge at 3 o'clock,
+        // and the seam keeps the track color instead of blending into blue
+        let progress = Gradient::Conic {
+            center_x: 0.5,
+            center_y: 0.5,
+            start_angle: 0.0,
+            stops: vec![stop(0.0, 0x0000FFFF), stop(0.25, 0x0000FFFF), stop(0.25, 0xDDDDDDFF), stop(1.0, 0xDDDDDDFF)],
+        };
+        let (verts, _) = gradient_rect(0.0, 0.0, 40.0, 40.0, &progress, [0.0; 4]);
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        assert!(verts.iter().filter(|v| v.position[0] > 20.01 && v.position[1] < 19.99).all(|v| v.color == blue));
+        assert!(verts.iter().filter(|v| v.position[0] < 19.99).all(|v| v.color != blue));
+
+        // A start angle rotates the sweep; offsets are clamped
+        let rotated = Gradient::Conic {
+            center_x: 0.5,
+            center_y: 0.5,
+            start_angle: 90.0,
+            stops: vec![stop(-1.0, 0xFF0000FF), stop(2.0, 0x0000FFFF)],
+        };
+        assert!(near(compute_gradient_color(&rotated, 40.0, 20.1, 40.0, 40.0), [1.0, 0.0, 0.0, 1.0]));
+        assert!(near(compute_gradient_color(&rotated, 20.0, 40.0, 40.0, 40.0), [0.75, 0.0, 0.25, 1.0]));
+    }
+
     #[test]
     fn test_circle() {
         let (verts, indices) = circle(50.0, 50.0, 25.0, 0x00FF00FF, 16);
@@ -1156,6 +2428,159 @@ mod tests {
         assert_eq!(indices.len(), 48); // 16 triangles * 3
     }
 
+    #[test]
+    fn test_arc() {
+        let (verts, indices) = arc(50.0, 50.0, 20.0, 4.0, 0.0, PI, 0xFFFFFFFF, 12);
+        assert_eq!(verts.len(), 26); // (12 + 1) * 2
+        assert_eq!(indices.len(), 72); // 12 quads * 6
+        // First outer vertex sits on the positive X axis at the outer radius
+        assert!((verts[0].position[0] - 70.0).abs() < 0.001);
+        assert!((verts[1].position[0] - 66.0).abs() < 0.001);
+    }
+
+    /// Coverage the coverage shader gives the pixel at (x, y), from the
+    /// texcoords of the triangle it falls in
+    fn coverage_at(verts: &[Vertex], indices: &[u16], x: f32, y: f32) -> f32 {
+        indices
+            .chunks(3)
+            .filter_map(|tri| {
+                let [a, b, c] = [0, 1, 2].map(|i| &verts[tri[i] as usize]);
+                let (ax, ay, bx, by, cx, cy) = (a.position[0], a.position[1], b.position[0], b.position[1], c.position[0], c.position[1]);
+                let det = (by - cy) * (ax - cx) + (cx - bx) * (ay - cy);
+                if det.abs() < 1e-6 {
+                    return None;
+                }
+                let wa = ((by - cy) * (x - cx) + (cx - bx) * (y - cy)) / det;
+                let wb = ((cy - ay) * (x - cx) + (ax - cx) * (y - cy)) / det;
+                let wc = 1.0 - wa - wb;
+                if wa < -1e-4 || wb < -1e-4 || wc < -1e-4 {
+                    return None;
+                }
+                let d = |i: usize| wa * a.texcoord[i] + wb * b.texcoord[i] + wc * c.texcoord[i];
+                Some((d(0).min(d(1)) + 0.5).clamp(0.0, 1.0))
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Whether any triangle covers the point (x, y)
+    fn covers(verts: &[Vertex], indices: &[u16], x: f32, y: f32) -> bool {
+        indices.chunks(3).any(|tri| {
+            let [a, b, c] = [0, 1, 2].map(|i| verts[tri[i] as usize].position);
+            let side = |p: [f32; 3], q: [f32; 3]| (q[0] - p[0]) * (y - p[1]) - (q[1] - p[1]) * (x - p[0]);
+            let (ab, bc, ca) = (side(a, b), side(b, c), side(c, a));
+            (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+        })
+    }
+
+    #[test]
+    fn test_path_fill_rules_on_self_intersecting_star() {
+        // A five-pointed star drawn as one outline crossing itself, so its
+        // center is wound around twice
+        let mut star: Vec<PathSegment> = (0..5)
+            .map(|i| {
+                let angle = -PI / 2.0 + i as f32 * 4.0 * PI / 5.0;
+                let (x, y) = (50.0 + 40.0 * angle.cos(), 50.0 + 40.0 * angle.sin());
+                if i == 0 { PathSegment::MoveTo { x, y } } else { PathSegment::LineTo { x, y } }
+            })
+            .collect();
+        star.push(PathSegment::Close {});
+
+        let (verts, indices) = tessellated_path(&star, Some(0xFF0000FF), FillRule::NonZero, None, 1.0);
+        assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+        assert!(covers(&verts, &indices, 50.0, 50.0));
+        assert!(covers(&verts, &indices, 50.0, 15.0));
+        assert!(!covers(&verts, &indices, 5.0, 5.0));
+
+        // Even-odd leaves the center as a hole but keeps the points
+        let (verts, indices) = tessellated_path(&star, Some(0xFF0000FF), FillRule::EvenOdd, None, 1.0);
+        assert!(!covers(&verts, &indices, 50.0, 50.0));
+        assert!(covers(&verts, &indices, 50.0, 15.0));
+
+        // Coordinates are scaled to pixels
+        let (verts, indices) = tessellated_path(&star, Some(0xFF0000FF), FillRule::EvenOdd, None, 2.0);
+        assert!(covers(&verts, &indices, 100.0, 30.0));
+    }
+
+    #[test]
+    fn test_path_stroke_follows_open_curve() {
+        let curve = [
+            PathSegment::MoveTo { x: 0.0, y: 50.0 },
+            PathSegment::QuadTo { cx: 50.0, cy: 0.0, x: 100.0, y: 50.0 },
+        ];
+        let stroke = Stroke { width: 4.0, color: 0x000000FF, cap: LineCap::Butt, join: LineJoin::Miter };
+        let (verts, indices) = tessellated_path(&curve, None, FillRule::NonZero, Some(&stroke), 1.0);
+
+        // The curve peaks at y = 25 halfway along; its inside isn't filled
+        assert!(covers(&verts, &indices, 50.0, 25.0));
+        assert!(!covers(&verts, &indices, 50.0, 40.0));
+        // A transparent fill draws nothing
+        let (_, indices) = tessellated_path(&curve, Some(0xFF000000), FillRule::NonZero, None, 1.0);
+        assert!(indices.is_empty());
+    }
+
+    #[test]
+    fn test_antialiased_arc_full_turn_is_a_ring() {
+        let (cx, cy) = (32.0, 32.0);
+        let (verts, indices) = antialiased_arc(cx, cy, 20.0, 6.0, 0.0, 2.0 * PI, 0xFFFFFFFF);
+        assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+
+        for py in 0..64 {
+            for px in 0..64 {
+                let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+                let r = (x - cx).hypot(y - cy);
+                let inside = (20.0 - r).min(r - 14.0);
+                let coverage = coverage_at(&verts, &indices, x, y);
+                if inside > 0.6 {
+                    assert!(coverage > 0.99, "({}, {}) covered {}", x, y, coverage);
+                } else if inside < -0.6 {
+                    assert_eq!(coverage, 0.0, "({}, {}) covered", x, y);
+                }
+            }
+        }
+        // Edges are blended, not stair-stepped
+        let edge = coverage_at(&verts, &indices, cx + 20.0, cy);
+        assert!((edge - 0.5).abs() < 0.1, "outer edge covered {}", edge);
+
+        // Going round the other way, or from another angle, draws the same ring
+        let (reverse, reverse_indices) = antialiased_arc(cx, cy, 20.0, 6.0, 1.0, 1.0 - 2.0 * PI, 0xFFFFFFFF);
+        assert!(coverage_at(&reverse, &reverse_indices, cx, cy + 17.0) > 0.99);
+        assert_eq!(coverage_at(&reverse, &reverse_indices, cx, cy), 0.0);
+    }
+
+    #[test]
+    fn test_antialiased_arc_wedge_covers_its_sector() {
+        let (cx, cy) = (32.0, 32.0);
+        // Thickness 0 fills from 3 o'clock clockwise to 6 o'clock
+        let (verts, indices) = antialiased_arc(cx, cy, 24.0, 0.0, 0.0, PI / 2.0, 0xFF0000FF);
+
+        for py in 0..64 {
+            for px in 0..64 {
+                let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+                let r = (x - cx).hypot(y - cy);
+                let inside = (24.0 - r).min(x - cx).min(y - cy);
+                let coverage = coverage_at(&verts, &indices, x, y);
+                if inside > 0.6 {
+                    assert!(coverage > 0.99, "({}, {}) covered {}", x, y, coverage);
+                } else if r > 24.6 || x - cx < -0.6 || y - cy < -0.6 {
+                    assert_eq!(coverage, 0.0, "({}, {}) covered", x, y);
+                }
+            }
+        }
+        // The straight edges are antialiased too
+        let side = coverage_at(&verts, &indices, cx + 10.0, cy);
+        assert!((side - 0.5).abs() < 0.1, "side covered {}", side);
+
+        // Swapped angles fill the same sector; a stroke only reaches in by
+        // its thickness
+        let (swapped, swapped_indices) = antialiased_arc(cx, cy, 24.0, 0.0, PI / 2.0, 0.0, 0xFF0000FF);
+        assert!(coverage_at(&swapped, &swapped_indices, cx + 5.0, cy + 5.0) > 0.99);
+        let (stroke, stroke_indices) = antialiased_arc(cx, cy, 24.0, 4.0, 0.0, PI / 2.0, 0xFF0000FF);
+        assert_eq!(coverage_at(&stroke, &stroke_indices, cx + 5.0, cy + 5.0), 0.0);
+        assert!(coverage_at(&stroke, &stroke_indices, cx + 15.0, cy + 15.0) > 0.99);
+
+        assert!(antialiased_arc(cx, cy, 24.0, 0.0, 1.0, 1.0, 0xFF0000FF).1.is_empty());
+    }
+
     #[test]
     fn test_line() {
         let (verts, indices) = line(0.0, 0.0, 100.0, 0.0, 2.0, 0x0000FFFF);
@@ -1163,6 +2588,265 @@ mod tests {
         assert_eq!(indices.len(), 6);
     }
 
+    #[test]
+    fn test_line_caps() {
+        let extent = |verts: &[Vertex]| {
+            verts.iter().fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v.position[0]), max.max(v.position[0])))
+        };
+
+        // Butt ends at the endpoints; square and round reach half the width past them
+        let (butt, _) = capped_line(10.0, 0.0, 50.0, 0.0, 4.0, 0xFFFFFFFF, LineCap::Butt);
+        assert_eq!(extent(&butt), (10.0, 50.0));
+        let (square, _) = capped_line(10.0, 0.0, 50.0, 0.0, 4.0, 0xFFFFFFFF, LineCap::Square);
+        assert_eq!(extent(&square), (8.0, 52.0));
+        let (round, indices) = capped_line(10.0, 0.0, 50.0, 0.0, 4.0, 0xFFFFFFFF, LineCap::Round);
+        let (min, max) = extent(&round);
+        assert!((min - 8.0).abs() < 1e-4 && (max - 52.0).abs() < 1e-4);
+        assert_eq!(indices.len(), 6 + 2 * ROUND_CAP_SEGMENTS * 3);
+
+        // A zero-length round-capped line is still a dot
+        let (dot, indices) = capped_line(5.0, 5.0, 5.0, 5.0, 6.0, 0xFFFFFFFF, LineCap::Round);
+        assert!(!indices.is_empty());
+        for v in &dot {
+            assert!((v.position[0] - 5.0).hypot(v.position[1] - 5.0) <= 3.0 + 1e-4);
+        }
+        assert!(capped_line(5.0, 5.0, 5.0, 5.0, 6.0, 0xFFFFFFFF, LineCap::Butt).1.is_empty());
+    }
+
+    #[test]
+    fn test_rotation_about_top_left_corner() {
+        // 90° clockwise about the top-left corner swings the rect into the
+        // quadrant left of and below the corner (a hinge on that corner)
+        let rotation = Rotation::about(Rect::new(10.0, 10.0, 40.0, 20.0), PI / 2.0, Some((0.0, 0.0)));
+        let (verts, _) = simple_rect(10.0, 10.0, 40.0, 20.0, 0xFFFFFFFF);
+        for v in &verts {
+            let (x, y) = rotation.apply(v.position[0], v.position[1]);
+            assert!((-10.001..=10.001).contains(&x), "x = {}", x);
+            assert!((9.999..=50.001).contains(&y), "y = {}", y);
+        }
+        // The pivot itself doesn't move; the far corner lands at (-10, 50)
+        let (x, y) = rotation.apply(10.0, 10.0);
+        assert!((x - 10.0).abs() < 0.001 && (y - 10.0).abs() < 0.001);
+        let (x, y) = rotation.apply(50.0, 30.0);
+        assert!((x + 10.0).abs() < 0.001 && (y - 50.0).abs() < 0.001);
+
+        // Without a pivot the rect turns about its center
+        let centered = Rotation::about(Rect::new(10.0, 10.0, 40.0, 20.0), PI / 2.0, None);
+        assert_eq!(centered.pivot, (30.0, 20.0));
+    }
+
+    #[test]
+    fn test_transform_nesting_and_bounds() {
+        // A pan pushed inside a zoom is zoomed too
+        let zoom = Transform2D::scale(2.0, 2.0);
+        let zoom_then_pan = zoom.then(&Transform2D::translate(10.0, 5.0));
+        assert_eq!(zoom_then_pan.apply(1.0, 1.0), (22.0, 12.0));
+        let pan_then_zoom = Transform2D::translate(10.0, 5.0).then(&zoom);
+        assert_eq!(pan_then_zoom.apply(1.0, 1.0), (12.0, 7.0));
+        assert!(Transform2D::IDENTITY.then(&zoom) == zoom && zoom.then(&Transform2D::IDENTITY) == zoom);
+
+        // At a 2x scale factor (1, 1) is physical (2, 2) and lands on twice
+        // its logical position
+        assert_eq!(zoom_then_pan.scaled(2.0).apply(2.0, 2.0), (44.0, 24.0));
+
+        // Bounds of a rotated rect cover all four corners
+        let quarter_turn = Transform2D([0.0, 1.0, -1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(quarter_turn.bounds(Rect::new(0.0, 0.0, 40.0, 20.0)), Rect::new(-20.0, 0.0, 20.0, 40.0));
+        assert!(Transform2D::IDENTITY.is_identity() && !zoom.is_identity());
+    }
+
+    #[test]
+    fn test_rect_intersect() {
+        let a = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let b = Rect::new(60.0, 20.0, 100.0, 100.0);
+        assert_eq!(a.intersect(&b), Some(Rect::new(60.0, 20.0, 40.0, 30.0)));
+        assert_eq!(b.intersect(&a), a.intersect(&b));
+
+        // Containment yields the inner rect
+        let inner = Rect::new(10.0, 10.0, 5.0, 5.0);
+        assert_eq!(a.intersect(&inner), Some(inner));
+
+        // Disjoint rects, and rects that only share an edge, don't overlap
+        assert_eq!(a.intersect(&Rect::new(200.0, 0.0, 10.0, 10.0)), None);
+        assert_eq!(a.intersect(&Rect::new(100.0, 0.0, 10.0, 50.0)), None);
+        assert_eq!(a.intersect(&Rect::new(0.0, 50.0, 100.0, 10.0)), None);
+        assert_eq!(a.intersect(&Rect::default()), None);
+    }
+
+    #[test]
+    fn test_rect_union() {
+        let a = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let b = Rect::new(60.0, 20.0, 100.0, 100.0);
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 160.0, 120.0));
+        assert_eq!(b.union(&a), a.union(&b));
+
+        // Empty rects don't stretch the union towards their origin
+        assert_eq!(b.union(&Rect::default()), b);
+        assert_eq!(Rect::default().union(&b), b);
+    }
+
+    #[test]
+    fn test_rect_inset() {
+        let rect = Rect::new(10.0, 20.0, 100.0, 40.0);
+        assert_eq!(rect.inset(5.0, 10.0), Rect::new(15.0, 30.0, 90.0, 20.0));
+        assert_eq!(rect.inset(-5.0, 0.0), Rect::new(5.0, 20.0, 110.0, 40.0));
+        assert_eq!(rect.inset(5.0, 10.0).center(), rect.center());
+
+        // Insetting past the size collapses to an empty rect at the center
+        let collapsed = rect.inset(10.0, 25.0);
+        assert!(collapsed.is_empty());
+        assert_eq!(collapsed, Rect::new(20.0, 40.0, 80.0, 0.0));
+        let gone = rect.inset(60.0, 60.0);
+        assert_eq!(gone, Rect::new(60.0, 40.0, 0.0, 0.0));
+        assert!(!gone.contains(gone.center()));
+    }
+
+    #[test]
+    fn test_rect_serializes_flat() {
+        let rect = Rect::new(1.0, 2.0, 3.0, 4.0);
+        let json = serde_json::to_value(rect).unwrap();
+        assert_eq!(json, serde_json::json!({ "x": 1.0, "y": 2.0, "width": 3.0, "height": 4.0 }));
+        let clip: Rect = serde_json::from_str(r#"{"x": 1, "y": 2, "width": 3, "height": 4}"#).unwrap();
+        assert_eq!(clip, rect);
+        assert!(rect.contains(Point2::new(1.0, 2.0)) && !rect.contains(Point2::new(4.0, 2.0)));
+    }
+
+    #[test]
+    fn test_dashed_outline_surrounds_rect() {
+        let rect = Rect::new(20.0, 20.0, 60.0, 40.0);
+        let (vertices, indices) = dashed_outline(rect, [8.0; 4], 2.0, 4.0, 0.0, 0x3B82F6FF);
+        assert!(!indices.is_empty());
+
+        // Every vertex lies in the band between the rect and 2px outside it
+        for v in &vertices {
+            let [px, py, _] = v.position;
+            let distance = rounded_rect_distance(px, py, rect, [8.0; 4]);
+            assert!((-0.05..=2.05).contains(&distance), "({px}, {py}) is {distance}px out");
+            assert_eq!(v.color, color_to_rgba(0x3B82F6FF));
+        }
+
+        // Dashes cover about half the ring's area
+        let area: f32 = indices
+            .chunks(3)
+            .map(|tri| {
+                let [a, b, c] = [0, 1, 2].map(|i| vertices[tri[i] as usize].position);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.0
+            })
+            .sum();
+        let ring_length = 2.0 * (44.0 + 24.0) + 2.0 * PI * 9.0;
+        assert!((area - ring_length).abs() < ring_length * 0.05, "area {area}");
+
+        // Shifting by a whole period leaves the pattern unchanged
+        let period = ring_length / (ring_length / 8.0).round();
+        let (shifted, _) = dashed_outline(rect, [8.0; 4], 2.0, 4.0, period, 0x3B82F6FF);
+        assert_eq!(shifted.len(), vertices.len());
+        let (moved, _) = dashed_outline(rect, [8.0; 4], 2.0, 4.0, 3.0, 0x3B82F6FF);
+        assert_ne!(moved[0].position, vertices[0].position);
+    }
+
+    #[test]
+    fn test_dashed_and_dotted_borders_stay_inside_rect() {
+        let rect = Rect::new(10.0, 10.0, 80.0, 50.0);
+        let radii = [12.0; 4];
+        let triangle_area = |vertices: &[Vertex], indices: &[u16]| -> f32 {
+            indices
+                .chunks(3)
+                .map(|tri| {
+                    let [a, b, c] = [0, 1, 2].map(|i| vertices[tri[i] as usize].position);
+                    ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.0
+                })
+                .sum()
+        };
+        let styled = |style: BorderStyle, dash_length: f32, gap_length: f32| {
+            let border = Border { width: 4.0, color: 0x111111FF, style, dash_length, gap_length };
+            styled_border(rect.x, rect.y, rect.width, rect.height, &border, radii)
+        };
+
+        // Solid is the plain border
+        let (solid, solid_indices) = styled(BorderStyle::Solid, 0.0, 0.0);
+        let (plain, _) = border_rect(rect.x, rect.y, rect.width, rect.height, 4.0, 0x111111FF, radii);
+        assert_eq!(solid.len(), plain.len());
+        let solid_area = triangle_area(&solid, &solid_indices);
+
+        for (style, dash, gap, coverage) in [
+            (BorderStyle::Dashed, 0.0, 0.0, 0.5),
+            (BorderStyle::Dashed, 9.0, 3.0, 0.75),
+            (BorderStyle::Dotted, 0.0, 0.0, PI / 8.0),
+        ] {
+            let (vertices, indices) = styled(style, dash, gap);
+            assert!(!indices.is_empty(), "{style:?}");
+            for v in &vertices {
+                let [px, py, _] = v.position;
+                let distance = rounded_rect_distance(px, py, rect, radii);
+                assert!((-4.05..=0.05).contains(&distance), "{style:?}: ({px}, {py}) is {distance}px out");
+            }
+            let area = triangle_area(&vertices, &indices);
+            assert!((area - solid_area * coverage).abs() < solid_area * 0.06, "{style:?}: area {area} of {solid_area}");
+        }
+
+        // Nothing to draw for a transparent or zero-width border
+        let border = Border { width: 0.0, ..Border::solid(0.0, 0x111111FF) };
+        let dashed = Border { style: BorderStyle::Dashed, ..border };
+        assert!(styled_border(0.0, 0.0, 10.0, 10.0, &dashed, [0.0; 4]).1.is_empty());
+    }
+
+    #[test]
+    fn test_inner_shadow_shades_edges_not_middle() {
+        let shadow = InnerShadow { blur: 8.0, spread: 0.0, offset_x: 0.0, offset_y: 0.0, color: 0x000000FF };
+        let (verts, indices) = inner_shadow_rect(0.0, 0.0, 100.0, 60.0, [10.0; 4], &shadow);
+        assert!(!indices.is_empty());
+        assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+
+        // Half dark on the outline, no geometry in the middle
+        let alpha_at = |x: f32, y: f32| {
+            verts.iter().find(|v| (v.position[0] - x).abs() < 0.01 && (v.position[1] - y).abs() < 0.01)
+                .map(|v| v.color[3])
+        };
+        assert!((alpha_at(0.0, 10.0).unwrap() - 0.5).abs() < 0.01);
+        assert!(verts.iter().all(|v| {
+            let (x, y) = (v.position[0], v.position[1]);
+            (0.0..=100.0).contains(&x) && (0.0..=60.0).contains(&y) && !(x > 12.0 && x < 88.0 && y > 12.0 && y < 48.0)
+        }));
+
+        // Offset down, the top edge is darker than the bottom
+        let offset = InnerShadow { offset_y: 4.0, ..shadow };
+        let (verts, _) = inner_shadow_rect(0.0, 0.0, 100.0, 60.0, [0.0; 4], &offset);
+        let edge_alpha = |y: f32| verts.iter()
+            .filter(|v| (v.position[1] - y).abs() < 0.01 && (v.position[0] - 50.0).abs() < 10.0)
+            .map(|v| v.color[3])
+            .fold(0.0f32, f32::max);
+        assert!(edge_alpha(0.0) > 0.9 && edge_alpha(60.0) < 0.2);
+    }
+
+    #[test]
+    fn test_box_shadow_spreads_and_blurs_outside_the_rect() {
+        let shadow = BoxShadow { offset_x: 0.0, offset_y: 10.0, blur_radius: 8.0, spread: 4.0, color: 0x00000080 };
+        let (verts, indices) = box_shadow_rect(0.0, 0.0, 100.0, 60.0, [10.0; 4], &shadow);
+        assert!(!indices.is_empty());
+        assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+
+        // Grown by the spread, moved by the offset, feathered by the blur
+        let (min_y, max_y) = verts.iter().fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v.position[1]), hi.max(v.position[1])));
+        assert!((min_y - (10.0 - 4.0 - 8.0)).abs() < 0.01);
+        assert!((max_y - (70.0 + 4.0 + 8.0)).abs() < 0.01);
+
+        // Transparent outside the blur, half on the edge, solid in the middle
+        let left_edge_alpha = |x: f32| {
+            verts.iter().find(|v| (v.position[0] - x).abs() < 0.01 && (30.0..50.0).contains(&v.position[1]))
+                .map(|v| v.color[3])
+        };
+        assert!(left_edge_alpha(-12.0).unwrap() < 0.005);
+        assert!((left_edge_alpha(-4.0).unwrap() - 0.25).abs() < 0.01);
+        assert!(verts.iter().map(|v| v.color[3]).fold(0.0f32, f32::max) > 0.49);
+
+        // A hard shadow is a single solid shape; one shrunk away draws nothing
+        let hard = BoxShadow { blur_radius: 0.0, ..shadow };
+        let (verts, _) = box_shadow_rect(0.0, 0.0, 100.0, 60.0, [0.0; 4], &hard);
+        assert!(verts.iter().all(|v| (v.color[3] - 0.5).abs() < 0.01));
+        let gone = BoxShadow { spread: -40.0, ..shadow };
+        assert!(box_shadow_rect(0.0, 0.0, 100.0, 60.0, [0.0; 4], &gone).1.is_empty());
+    }
+
     #[test]
     fn test_color_conversion() {
         let rgba = color_to_rgba(0xFF8040C0);
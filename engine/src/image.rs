@@ -2,11 +2,29 @@
 //!
 //! This module handles loading images from files or memory, decoding them,
 //! and managing GPU textures for rendering.
+//!
+//! Pixel data is sRGB-encoded RGBA with either straight alpha (what decoders
+//! produce) or premultiplied alpha. Premultiplied pixels hold color × alpha,
+//! with the multiply done in linear light and the result re-encoded as sRGB,
+//! so the GPU's sRGB decode yields exactly the linear premultiplied value it
+//! blends with. The image shader converts straight textures after sampling
+//! and the pipeline always blends premultiplied, so both kinds draw the same
+//! except at edges: filtering a straight texture mixes in the color of fully
+//! transparent neighbours (usually black), which shows as a dark halo around
+//! antialiased edges. Premultiply images with soft edges to avoid it.
+//!
+//! Animated GIFs and APNGs decode to one full-size frame per animation step,
+//! already composited with the previous frames' disposal applied, so any
+//! frame can be shown on its own.
 
 use std::collections::HashMap;
 use std::error::Error;
+use std::io::Cursor;
+
+use image::{AnimationDecoder, ImageFormat};
 
 /// A loaded image ready for GPU upload
+#[derive(Clone)]
 pub struct LoadedImage {
     /// Image width in pixels
     pub width: u32,
@@ -14,11 +32,108 @@ pub struct LoadedImage {
     pub height: u32,
     /// RGBA pixel data (4 bytes per pixel)
     pub data: Vec<u8>,
+    /// Whether `data` has premultiplied alpha (see the module docs)
+    pub premultiplied: bool,
+    /// Every frame of an animated image, the first matching `data`; empty
+    /// for still images
+    pub frames: Vec<ImageFrame>,
+    /// How many times an animation plays, 0 for forever
+    pub loop_count: u32,
+}
+
+/// One frame of an animated image
+#[derive(Clone)]
+pub struct ImageFrame {
+    /// RGBA pixel data covering the whole image, in the same alpha mode as
+    /// the image's `data`
+    pub data: Vec<u8>,
+    /// How long the frame shows, in milliseconds
+    pub delay_ms: u32,
+}
+
+/// Shortest frame delay honored; shorter ones (including 0) play at
+/// `DEFAULT_FRAME_DELAY_MS`, as browsers do for GIFs
+pub const MIN_FRAME_DELAY_MS: u32 = 20;
+/// Delay used for frames that are too short
+pub const DEFAULT_FRAME_DELAY_MS: u32 = 100;
+
+/// When each frame of an animation shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationTiming {
+    /// Delay of each frame, in milliseconds
+    pub delays_ms: Vec<u32>,
+    /// How many times the animation plays, 0 for forever
+    pub loop_count: u32,
+}
+
+impl AnimationTiming {
+    /// Index of the frame showing `elapsed_ms` after the animation started.
+    /// Once a finite animation has played through, it stays on its last frame.
+    pub fn frame_at(&self, elapsed_ms: u64) -> usize {
+        let total: u64 = self.delays_ms.iter().map(|&delay| delay as u64).sum();
+        if total == 0 {
+            return 0;
+        }
+        if self.loop_count > 0 && elapsed_ms >= total * self.loop_count as u64 {
+            return self.delays_ms.len() - 1;
+        }
+
+        let mut remaining = elapsed_ms % total;
+        for (index, &delay) in self.delays_ms.iter().enumerate() {
+            if remaining < delay as u64 {
+                return index;
+            }
+            remaining -= delay as u64;
+        }
+        self.delays_ms.len() - 1
+    }
 }
 
 impl LoadedImage {
-    /// Load an image from raw bytes (PNG, JPEG, etc.)
+    /// Load an image from raw bytes (PNG, JPEG, GIF, etc.)
+    ///
+    /// Animated GIFs and APNGs fill in `frames` and `loop_count`; `data`
+    /// holds the first frame.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let frames = match image::guess_format(bytes)? {
+            ImageFormat::Gif => {
+                let decoder = image::codecs::gif::GifDecoder::new(Cursor::new(bytes))?;
+                decoder.into_frames().collect_frames()?
+            }
+            ImageFormat::Png => {
+                let decoder = image::codecs::png::PngDecoder::new(Cursor::new(bytes))?;
+                if decoder.is_apng()? {
+                    decoder.apng()?.into_frames().collect_frames()?
+                } else {
+                    Vec::new()
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        if frames.len() > 1 {
+            let (width, height) = frames[0].buffer().dimensions();
+            let frames: Vec<ImageFrame> = frames
+                .into_iter()
+                .map(|frame| {
+                    let (numer, denom) = frame.delay().numer_denom_ms();
+                    let delay_ms = numer / denom.max(1);
+                    ImageFrame {
+                        data: frame.into_buffer().into_raw(),
+                        delay_ms: if delay_ms < MIN_FRAME_DELAY_MS { DEFAULT_FRAME_DELAY_MS } else { delay_ms },
+                    }
+                })
+                .collect();
+            return Ok(Self {
+                width,
+                height,
+                data: frames[0].data.clone(),
+                premultiplied: false,
+                frames,
+                loop_count: loop_count(bytes),
+            });
+        }
+
         let img = image::load_from_memory(bytes)?;
         let rgba = img.to_rgba8();
         let (width, height) = rgba.dimensions();
@@ -27,9 +142,51 @@ impl LoadedImage {
             width,
             height,
             data: rgba.into_raw(),
+            premultiplied: false,
+            frames: Vec::new(),
+            loop_count: 0,
         })
     }
 
+    /// Rasterize an SVG at `target_width` x `target_height` pixels
+    ///
+    /// The drawing is scaled to fit, keeping its aspect ratio, and centered.
+    /// A 0 dimension is derived from the other one, and 0 x 0 renders at
+    /// the SVG's own size.
+    pub fn from_svg_bytes(data: &[u8], target_width: u32, target_height: u32) -> Result<Self, Box<dyn Error>> {
+        use resvg::{tiny_skia, usvg};
+
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default())?;
+        let (svg_width, svg_height) = (tree.size().width(), tree.size().height());
+        let scale = match (target_width, target_height) {
+            (0, 0) => 1.0,
+            (0, height) => height as f32 / svg_height,
+            (width, 0) => width as f32 / svg_width,
+            (width, height) => (width as f32 / svg_width).min(height as f32 / svg_height),
+        };
+        let width = if target_width == 0 { (svg_width * scale).round() as u32 } else { target_width };
+        let height = if target_height == 0 { (svg_height * scale).round() as u32 } else { target_height };
+
+        let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or("SVG size is empty or too large")?;
+        let transform = tiny_skia::Transform::from_translate(
+            (width as f32 - svg_width * scale) / 2.0,
+            (height as f32 - svg_height * scale) / 2.0,
+        )
+        .pre_scale(scale, scale);
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+        // tiny-skia premultiplies in sRGB space; hand back straight alpha
+        let data = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        Ok(Self { width, height, data, premultiplied: false, frames: Vec::new(), loop_count: 0 })
+    }
+
     /// Load an image from a file path
     pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
         let bytes = std::fs::read(path)?;
@@ -46,7 +203,322 @@ impl LoadedImage {
             data.push(b);
             data.push(a);
         }
-        Self { width, height, data }
+        Self { width, height, data, premultiplied: false, frames: Vec::new(), loop_count: 0 }
+    }
+
+    /// Whether the image has more than one frame
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// When each frame shows
+    pub fn timing(&self) -> AnimationTiming {
+        AnimationTiming {
+            delays_ms: self.frames.iter().map(|frame| frame.delay_ms).collect(),
+            loop_count: self.loop_count,
+        }
+    }
+
+    /// Each frame as a still image, for uploading one texture per frame
+    pub fn frame_images(&self) -> Vec<LoadedImage> {
+        self.frames
+            .iter()
+            .map(|frame| LoadedImage {
+                width: self.width,
+                height: self.height,
+                data: frame.data.clone(),
+                premultiplied: self.premultiplied,
+                frames: Vec::new(),
+                loop_count: 0,
+            })
+            .collect()
+    }
+
+    /// Convert straight alpha to premultiplied, in linear light. Does nothing
+    /// if the data is already premultiplied.
+    pub fn premultiply(&mut self) {
+        if self.premultiplied {
+            return;
+        }
+        premultiply_pixels(&mut self.data);
+        for frame in &mut self.frames {
+            premultiply_pixels(&mut frame.data);
+        }
+        self.premultiplied = true;
+    }
+
+    /// Mip levels of a full chain: one more than the times the larger side
+    /// halves (rounding down) before reaching 1
+    pub fn mip_level_count(&self) -> u32 {
+        32 - self.width.max(self.height).max(1).leading_zeros()
+    }
+
+    /// The mip levels below the image, each half the size of the one above
+    /// (rounded down, at least 1 pixel) down to 1x1.
+    ///
+    /// A texel averages the area of the level above it covers, so odd sizes
+    /// take a fractional share of the texels on their boundaries instead of
+    /// dropping them. Averaging happens in linear light and weighted by
+    /// alpha, so straight-alpha edges don't darken. Levels keep the image's
+    /// alpha convention; animation frames are not included.
+    pub fn mip_chain(&self) -> Vec<LoadedImage> {
+        let premultiplied = self.premultiplied;
+        let mut pixels: Vec<[f32; 4]> = self
+            .data
+            .chunks_exact(4)
+            .map(|p| {
+                let alpha = p[3] as f32 / 255.0;
+                let weight = if premultiplied { 1.0 } else { alpha };
+                [srgb_to_linear(p[0]) * weight, srgb_to_linear(p[1]) * weight, srgb_to_linear(p[2]) * weight, alpha]
+            })
+            .collect();
+
+        let (mut width, mut height) = (self.width.max(1), self.height.max(1));
+        let mut levels = Vec::new();
+        while width > 1 || height > 1 {
+            let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+            pixels = downsample(&pixels, width, height, next_width, next_height);
+            (width, height) = (next_width, next_height);
+
+            let data = pixels
+                .iter()
+                .flat_map(|&[r, g, b, a]| {
+                    let unweight = if premultiplied || a <= 0.0 { 1.0 } else { 1.0 / a };
+                    let alpha = (a * 255.0).round().clamp(0.0, 255.0) as u8;
+                    if !premultiplied && alpha == 0 {
+                        return [0, 0, 0, 0];
+                    }
+                    let encode = |c: f32| linear_to_srgb((c * unweight).clamp(0.0, 1.0));
+                    [encode(r), encode(g), encode(b), alpha]
+                })
+                .collect();
+            levels.push(LoadedImage { width, height, data, premultiplied, frames: Vec::new(), loop_count: 0 });
+        }
+        levels
+    }
+}
+
+/// For each of `dst` output texels along one axis, the `src` texels it
+/// covers and the share of each (summing to 1)
+fn box_filter_taps(src: u32, dst: u32) -> Vec<Vec<(usize, f32)>> {
+    let scale = src as f32 / dst as f32;
+    (0..dst)
+        .map(|i| {
+            let (start, end) = (i as f32 * scale, (i + 1) as f32 * scale);
+            (start.floor() as usize..(end.ceil() as usize).min(src as usize))
+                .map(|s| (s, (end.min(s as f32 + 1.0) - start.max(s as f32)) / scale))
+                .filter(|&(_, weight)| weight > 0.0)
+                .collect()
+        })
+        .collect()
+}
+
+/// Box-filter linear RGBA texels from `src_width` x `src_height` down to
+/// `dst_width` x `dst_height`, rows then columns
+fn downsample(pixels: &[[f32; 4]], src_width: u32, src_height: u32, dst_width: u32, dst_height: u32) -> Vec<[f32; 4]> {
+    let blend = |taps: &[(usize, f32)], texel: &dyn Fn(usize) -> [f32; 4]| {
+        taps.iter().fold([0.0; 4], |mut sum, &(i, weight)| {
+            let t = texel(i);
+            for c in 0..4 {
+                sum[c] += t[c] * weight;
+            }
+            sum
+        })
+    };
+
+    let columns = box_filter_taps(src_width, dst_width);
+    let mut narrowed = Vec::with_capacity((dst_width * src_height) as usize);
+    for y in 0..src_height as usize {
+        let row = &pixels[y * src_width as usize..(y + 1) * src_width as usize];
+        narrowed.extend(columns.iter().map(|taps| blend(taps, &|x| row[x])));
+    }
+
+    let rows = box_filter_taps(src_height, dst_height);
+    let mut out = Vec::with_capacity((dst_width * dst_height) as usize);
+    for taps in &rows {
+        out.extend((0..dst_width as usize).map(|x| blend(taps, &|y| narrowed[y * dst_width as usize + x])));
+    }
+    out
+}
+
+fn premultiply_pixels(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        match pixel[3] {
+            255 => {}
+            0 => pixel[..3].fill(0),
+            a => {
+                let alpha = a as f32 / 255.0;
+                for c in &mut pixel[..3] {
+                    *c = linear_to_srgb(srgb_to_linear(*c) * alpha);
+                }
+            }
+        }
+    }
+}
+
+/// How many times an animated GIF or APNG asks to play, 0 for forever.
+///
+/// GIFs loop through a NETSCAPE2.0 application extension; without one they
+/// play once. APNGs store the count in their acTL chunk.
+fn loop_count(bytes: &[u8]) -> u32 {
+    if bytes.starts_with(b"GIF8") {
+        // Sub-block: length 3, id 1, little-endian u16 repeat count (the
+        // number of extra plays, 0 meaning forever)
+        for marker in [&b"NETSCAPE2.0"[..], &b"ANIMEXTS1.0"[..]] {
+            let Some(at) = bytes.windows(marker.len()).position(|window| window == marker) else {
+                continue;
+            };
+            if let [3, 1, lo, hi, ..] = bytes[at + marker.len()..] {
+                let repeats = u16::from_le_bytes([lo, hi]) as u32;
+                return if repeats == 0 { 0 } else { repeats + 1 };
+            }
+        }
+        return 1;
+    }
+
+    // PNG chunks after the 8-byte signature: length, type, data, CRC
+    let mut at = 8;
+    while let Some(header) = bytes.get(at..at + 8) {
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        match &header[4..8] {
+            b"acTL" => {
+                return bytes
+                    .get(at + 12..at + 16)
+                    .map_or(0, |plays| u32::from_be_bytes([plays[0], plays[1], plays[2], plays[3]]));
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => at += 12 + length,
+        }
+    }
+    0
+}
+
+/// How `WgpuBackend::load_image_with_options` uploads an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageUploadOptions {
+    /// Premultiply straight-alpha data before upload, so soft edges filter
+    /// without dark halos
+    pub premultiply: bool,
+    /// Upload a full mip chain, so the image stays smooth when drawn
+    /// well below its size. On by default; turn it off for images that
+    /// change every frame or are only ever drawn at 1:1.
+    pub mipmaps: bool,
+}
+
+impl Default for ImageUploadOptions {
+    fn default() -> Self {
+        Self { premultiply: false, mipmaps: true }
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (c * 255.0).round() as u8
+}
+
+/// Block-compressed texture formats that can be uploaded as-is
+///
+/// Values are the FFI format codes. BCn is generally available on desktop
+/// GPUs, ETC2 and ASTC on mobile ones.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Bc1RgbaUnormSrgb = 0,
+    Bc3RgbaUnormSrgb = 1,
+    Bc4RUnorm = 2,
+    Bc5RgUnorm = 3,
+    Bc7RgbaUnormSrgb = 4,
+    Etc2Rgb8UnormSrgb = 5,
+    Etc2Rgba8UnormSrgb = 6,
+    Astc4x4RgbaUnormSrgb = 7,
+    Astc8x8RgbaUnormSrgb = 8,
+}
+
+/// Compression family, each gated by one device feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFamily {
+    Bc,
+    Etc2,
+    Astc,
+}
+
+impl CompressedFormat {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        Some(match value {
+            0 => Self::Bc1RgbaUnormSrgb,
+            1 => Self::Bc3RgbaUnormSrgb,
+            2 => Self::Bc4RUnorm,
+            3 => Self::Bc5RgUnorm,
+            4 => Self::Bc7RgbaUnormSrgb,
+            5 => Self::Etc2Rgb8UnormSrgb,
+            6 => Self::Etc2Rgba8UnormSrgb,
+            7 => Self::Astc4x4RgbaUnormSrgb,
+            8 => Self::Astc8x8RgbaUnormSrgb,
+            _ => return None,
+        })
+    }
+
+    pub fn family(self) -> CompressionFamily {
+        match self {
+            Self::Bc1RgbaUnormSrgb | Self::Bc3RgbaUnormSrgb | Self::Bc4RUnorm
+            | Self::Bc5RgUnorm | Self::Bc7RgbaUnormSrgb => CompressionFamily::Bc,
+            Self::Etc2Rgb8UnormSrgb | Self::Etc2Rgba8UnormSrgb => CompressionFamily::Etc2,
+            Self::Astc4x4RgbaUnormSrgb | Self::Astc8x8RgbaUnormSrgb => CompressionFamily::Astc,
+        }
+    }
+
+    /// Block width and height in pixels
+    pub fn block_dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Astc8x8RgbaUnormSrgb => (8, 8),
+            _ => (4, 4),
+        }
+    }
+
+    /// Bytes per compressed block
+    pub fn block_bytes(self) -> u32 {
+        match self {
+            Self::Bc1RgbaUnormSrgb | Self::Bc4RUnorm | Self::Etc2Rgb8UnormSrgb => 8,
+            _ => 16,
+        }
+    }
+
+    /// Blocks across and down a mip level of the given size
+    pub fn blocks(self, width: u32, height: u32) -> (u32, u32) {
+        let (bw, bh) = self.block_dimensions();
+        (width.div_ceil(bw), height.div_ceil(bh))
+    }
+
+    /// Total bytes for `mip_levels` levels, largest first, tightly packed
+    pub fn data_size(self, width: u32, height: u32, mip_levels: u32) -> usize {
+        (0..mip_levels)
+            .map(|level| {
+                let (bx, by) = self.blocks((width >> level).max(1), (height >> level).max(1));
+                bx as usize * by as usize * self.block_bytes() as usize
+            })
+            .sum()
+    }
+
+    /// Check dimensions, mip count and data length for an upload
+    pub fn validate(self, data_len: usize, width: u32, height: u32, mip_levels: u32) -> Result<(), String> {
+        let (bw, bh) = self.block_dimensions();
+        if width == 0 || height == 0 || width % bw != 0 || height % bh != 0 {
+            return Err(format!("{:?} texture size {}x{} must be a non-zero multiple of {}x{}", self, width, height, bw, bh));
+        }
+        let max_levels = 32 - width.max(height).leading_zeros();
+        if mip_levels == 0 || mip_levels > max_levels {
+            return Err(format!("mip level count {} out of range 1..={}", mip_levels, max_levels));
+        }
+        let expected = self.data_size(width, height, mip_levels);
+        if data_len != expected {
+            return Err(format!("compressed data is {} bytes, expected {}", data_len, expected));
+        }
+        Ok(())
     }
 }
 
@@ -116,6 +588,192 @@ mod tests {
         assert_eq!(&img.data[0..4], &[255, 0, 0, 255]); // First pixel is red
     }
 
+    #[test]
+    fn test_mip_chain_covers_odd_sizes() {
+        let image = |width, height, data: Vec<u8>| LoadedImage { width, height, data, premultiplied: false, frames: Vec::new(), loop_count: 0 };
+
+        // Each level halves rounding down, to 1x1
+        let odd = LoadedImage::solid_color(5, 3, 10, 20, 30, 255);
+        assert_eq!(odd.mip_level_count(), 3);
+        let sizes: Vec<_> = odd.mip_chain().iter().map(|level| (level.width, level.height)).collect();
+        assert_eq!(sizes, [(2, 1), (1, 1)]);
+        assert!(odd.mip_chain().iter().all(|level| level.data.chunks(4).all(|p| p == [10, 20, 30, 255])));
+        assert_eq!(image(1, 1, vec![0; 4]).mip_chain().len(), 0);
+
+        // Three texels into one: all three count, in linear light. Dropping
+        // the last would give sRGB 188 (half of linear white).
+        let stripes = image(3, 1, vec![255, 255, 255, 255, 0, 0, 0, 255, 255, 255, 255, 255]);
+        assert_eq!(stripes.mip_chain()[0].data, [213, 213, 213, 255]);
+
+        // A straight-alpha edge keeps its color instead of darkening
+        let edge = image(2, 1, vec![255, 0, 0, 255, 0, 0, 0, 0]);
+        assert_eq!(edge.mip_chain()[0].data, [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_premultiply_in_linear_light() {
+        let mut img = LoadedImage {
+            width: 3,
+            height: 1,
+            data: vec![255, 255, 255, 128, 255, 0, 0, 255, 90, 200, 40, 0],
+            premultiplied: false,
+            frames: Vec::new(),
+            loop_count: 0,
+        };
+        img.premultiply();
+        assert!(img.premultiplied);
+        // Half of linear white, not half of the sRGB byte (which reads as dark gray)
+        assert_eq!(&img.data[0..4], &[188, 188, 188, 128]);
+        // Opaque pixels are unchanged and transparent ones become black
+        assert_eq!(&img.data[4..8], &[255, 0, 0, 255]);
+        assert_eq!(&img.data[8..12], &[0, 0, 0, 0]);
+
+        // Already premultiplied data is left alone
+        img.premultiply();
+        assert_eq!(&img.data[0..4], &[188, 188, 188, 128]);
+    }
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    /// One row of RGBA pixels as an uncompressed zlib stream
+    fn zlib_row(pixels: &[[u8; 4]]) -> Vec<u8> {
+        let mut raw = vec![0u8]; // filter: none
+        raw.extend(pixels.iter().flatten());
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in &raw {
+            a = (a + byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        let mut out = vec![0x78, 0x01, 0x01];
+        out.extend_from_slice(&(raw.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(raw.len() as u16)).to_le_bytes());
+        out.extend_from_slice(&raw);
+        out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+        out
+    }
+
+    /// frame control: sequence, size, offset, delay in 1/100 s, dispose and blend ops
+    fn fctl(sequence: u32, width: u32, x: u32, delay: u16, dispose: u8, blend: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in [sequence, width, 1, x, 0] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&delay.to_be_bytes());
+        data.extend_from_slice(&100u16.to_be_bytes());
+        data.extend_from_slice(&[dispose, blend]);
+        data
+    }
+
+    #[test]
+    fn test_apng_frames_compose_with_disposal_and_loop_count() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const GREEN: [u8; 4] = [0, 255, 0, 255];
+        const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+        // 2x1: red, then green drawn over the right pixel and cleared
+        // afterwards, then a transparent pixel blended over the left
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &[0, 0, 0, 2, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+        chunk(&mut png, b"acTL", &[0, 0, 0, 3, 0, 0, 0, 2]);
+        chunk(&mut png, b"fcTL", &fctl(0, 2, 0, 10, 0, 0));
+        chunk(&mut png, b"IDAT", &zlib_row(&[RED, RED]));
+        chunk(&mut png, b"fcTL", &fctl(1, 1, 1, 0, 1, 0));
+        chunk(&mut png, b"fdAT", &[&2u32.to_be_bytes()[..], &zlib_row(&[GREEN])].concat());
+        chunk(&mut png, b"fcTL", &fctl(3, 1, 0, 5, 0, 1));
+        chunk(&mut png, b"fdAT", &[&4u32.to_be_bytes()[..], &zlib_row(&[CLEAR])].concat());
+        chunk(&mut png, b"IEND", &[]);
+
+        let image = LoadedImage::from_bytes(&png).unwrap();
+        assert!(image.is_animated());
+        assert_eq!((image.width, image.height), (2, 1));
+        let frames: Vec<&[u8]> = image.frames.iter().map(|frame| &frame.data[..]).collect();
+        assert_eq!(frames[0], [RED, RED].concat());
+        assert_eq!(frames[1], [RED, GREEN].concat());
+        assert_eq!(frames[2], [RED, CLEAR].concat());
+        assert_eq!(image.data, frames[0]);
+
+        // A zero delay plays at the default
+        let timing = image.timing();
+        assert_eq!(timing, AnimationTiming { delays_ms: vec![100, 100, 50], loop_count: 2 });
+        assert_eq!(image.frame_images().len(), 3);
+
+        // A still PNG has no frames
+        let mut still = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut still, b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+        chunk(&mut still, b"IDAT", &zlib_row(&[GREEN]));
+        chunk(&mut still, b"IEND", &[]);
+        let still = LoadedImage::from_bytes(&still).unwrap();
+        assert!(!still.is_animated() && still.frames.is_empty());
+        assert_eq!(still.data, GREEN);
+    }
+
+    #[test]
+    fn test_animation_timing_honors_loop_count() {
+        let mut timing = AnimationTiming { delays_ms: vec![100, 100, 50], loop_count: 2 };
+        let frames: Vec<usize> = [0, 99, 100, 200, 249, 250, 499, 500, 10_000]
+            .into_iter()
+            .map(|ms| timing.frame_at(ms))
+            .collect();
+        assert_eq!(frames, vec![0, 0, 1, 2, 2, 0, 2, 2, 2]);
+
+        timing.loop_count = 0;
+        assert_eq!(timing.frame_at(10_000), 0);
+        assert_eq!(timing.frame_at(10_120), 1);
+
+        // GIF repeat counts are extra plays; no extension plays once
+        let gif = |extension: &[u8]| [&b"GIF89a"[..], extension, &[0x3B]].concat();
+        assert_eq!(loop_count(&gif(b"!\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")), 0);
+        assert_eq!(loop_count(&gif(b"!\xFF\x0BNETSCAPE2.0\x03\x01\x02\x00\x00")), 3);
+        assert_eq!(loop_count(&gif(b"")), 1);
+    }
+
+    #[test]
+    fn test_svg_rasterizes_to_fit_target_size() {
+        // Left half red, right half empty
+        let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+            <rect width="5" height="10" fill="#ff0000"/>
+        </svg>"##;
+        let pixel = |image: &LoadedImage, x: u32, y: u32| {
+            let i = ((y * image.width + x) * 4) as usize;
+            image.data[i..i + 4].to_vec()
+        };
+
+        let image = LoadedImage::from_svg_bytes(svg, 20, 20).unwrap();
+        assert_eq!((image.width, image.height), (20, 20));
+        assert_eq!(pixel(&image, 4, 10), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 15, 10)[3], 0);
+
+        // A wider target keeps the aspect ratio and centers the drawing
+        let image = LoadedImage::from_svg_bytes(svg, 40, 20).unwrap();
+        assert_eq!(pixel(&image, 5, 10)[3], 0);
+        assert_eq!(pixel(&image, 14, 10), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 25, 10)[3], 0);
+
+        // 0 follows the other dimension
+        let image = LoadedImage::from_svg_bytes(svg, 0, 30).unwrap();
+        assert_eq!((image.width, image.height), (30, 30));
+
+        assert!(LoadedImage::from_svg_bytes(b"not svg", 16, 16).is_err());
+    }
+
     #[test]
     fn test_texture_manager() {
         let mut manager = TextureManager::new();
@@ -130,4 +788,21 @@ mod tests {
         assert_eq!(info.width, 100);
         assert_eq!(info.height, 100);
     }
+
+    #[test]
+    fn test_compressed_data_size() {
+        let bc1 = CompressedFormat::Bc1RgbaUnormSrgb;
+        // 16x16 BC1: 4x4 blocks of 8 bytes, then 2x2, 1x1, 1x1, 1x1
+        assert_eq!(bc1.data_size(16, 16, 1), 128);
+        assert_eq!(bc1.data_size(16, 16, 5), 128 + 32 + 8 + 8 + 8);
+        assert!(bc1.validate(128, 16, 16, 1).is_ok());
+        assert!(bc1.validate(127, 16, 16, 1).is_err());
+        assert!(bc1.validate(128, 16, 16, 6).is_err());
+        assert!(bc1.validate(128, 15, 16, 1).is_err());
+
+        let astc8 = CompressedFormat::Astc8x8RgbaUnormSrgb;
+        assert_eq!(astc8.data_size(16, 16, 1), 4 * 16);
+        assert_eq!(CompressedFormat::from_u32(8), Some(astc8));
+        assert_eq!(CompressedFormat::from_u32(9), None);
+    }
 }
@@ -5,8 +5,11 @@
 //! - Dirty tracking to avoid unnecessary recalculations
 //! - Cache-friendly data structures
 
+use std::fmt;
+use std::str::FromStr;
+
 use euclid::{Point2D, Rect, Size2D};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use slotmap::{new_key_type, SlotMap};
 
 /// Unit type for layout space
@@ -74,7 +77,10 @@ pub enum AlignItems {
 }
 
 /// Dimension constraint
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+///
+/// Serialized in CSS form: `"auto"`, `"16px"` (or a bare number), `"50%"`,
+/// or `"calc(100% - 16px)"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Dimension {
     /// Undefined/auto
     Auto,
@@ -82,6 +88,9 @@ pub enum Dimension {
     Points(f32),
     /// Percentage of parent
     Percent(f32),
+    /// Percentage of parent plus (or minus) a fixed size, as in
+    /// `calc(100% - 16px)`
+    Calc { percent: f32, points: f32 },
 }
 
 impl Default for Dimension {
@@ -90,8 +99,124 @@ impl Default for Dimension {
     }
 }
 
+impl Dimension {
+    /// Resolve against the parent's content size along the same axis.
+    ///
+    /// `parent` is None when the parent is itself auto-sized along that
+    /// axis; percentages of it resolve to auto, like in CSS. Returns None
+    /// for auto.
+    pub fn resolve(self, parent: Option<f32>) -> Option<f32> {
+        match self {
+            Dimension::Auto => None,
+            Dimension::Points(points) => Some(points),
+            Dimension::Percent(percent) => parent.map(|parent| parent * percent / 100.0),
+            Dimension::Calc { percent, points } => parent.map(|parent| parent * percent / 100.0 + points),
+        }
+    }
+}
+
+/// Error parsing a `Dimension` from CSS form
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionParseError(String);
+
+impl fmt::Display for DimensionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dimension {:?}", self.0)
+    }
+}
+
+impl std::error::Error for DimensionParseError {}
+
+impl FromStr for Dimension {
+    type Err = DimensionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DimensionParseError(s.to_string());
+        let s = s.trim();
+        if s == "auto" {
+            return Ok(Dimension::Auto);
+        }
+        let Some(expr) = s.strip_prefix("calc(").and_then(|rest| rest.strip_suffix(')')) else {
+            return match parse_term(s).ok_or_else(error)? {
+                (value, true) => Ok(Dimension::Percent(value)),
+                (value, false) => Ok(Dimension::Points(value)),
+            };
+        };
+
+        // Sum of terms joined by " + " / " - "; CSS requires the spaces, which
+        // keeps a term's own sign ("-8px") unambiguous
+        let (mut percent, mut points) = (0.0, 0.0);
+        let mut sign = 1.0;
+        for (i, token) in expr.split_whitespace().enumerate() {
+            if i % 2 == 1 {
+                sign = match token {
+                    "+" => 1.0,
+                    "-" => -1.0,
+                    _ => return Err(error()),
+                };
+                continue;
+            }
+            match parse_term(token).ok_or_else(error)? {
+                (value, true) => percent += sign * value,
+                (value, false) => points += sign * value,
+            }
+        }
+        if expr.split_whitespace().count() % 2 == 0 {
+            // Empty, or ends with an operator
+            return Err(error());
+        }
+        Ok(Dimension::Calc { percent, points })
+    }
+}
+
+/// Parse `50%` (percent = true), `16px` or `16`
+fn parse_term(term: &str) -> Option<(f32, bool)> {
+    let (number, percent) = match term.strip_suffix('%') {
+        Some(number) => (number, true),
+        None => (term.strip_suffix("px").unwrap_or(term), false),
+    };
+    let value: f32 = number.parse().ok()?;
+    value.is_finite().then_some((value, percent))
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dimension::Auto => write!(f, "auto"),
+            Dimension::Points(points) => write!(f, "{}px", points),
+            Dimension::Percent(percent) => write!(f, "{}%", percent),
+            Dimension::Calc { percent, points } => {
+                let op = if points.is_sign_negative() { '-' } else { '+' };
+                write!(f, "calc({}% {} {}px)", percent, op, points.abs())
+            }
+        }
+    }
+}
+
+impl Serialize for Dimension {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dimension {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Points(f32),
+            Css(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Points(points) => Ok(Dimension::Points(points)),
+            Repr::Css(css) => css.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 /// Layout constraints for a node
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LayoutConstraints {
     pub width: Dimension,
     pub height: Dimension,
@@ -116,6 +241,8 @@ pub struct LayoutConstraints {
 pub struct ComputedLayout {
     /// Position relative to parent
     pub position: LayoutPoint,
+    /// Position relative to the area the tree was laid out in
+    pub absolute_position: LayoutPoint,
     /// Computed size
     pub size: LayoutSize,
     /// Content size (excluding padding/border)
@@ -145,6 +272,8 @@ pub struct LayoutNode {
     pub flex_grow: f32,
     pub flex_shrink: f32,
     pub flex_basis: Dimension,
+    /// Space between adjacent items and between wrapped lines
+    pub gap: f32,
 }
 
 impl Default for LayoutNode {
@@ -162,6 +291,7 @@ impl Default for LayoutNode {
             flex_grow: 0.0,
             flex_shrink: 1.0,
             flex_basis: Dimension::Auto,
+            gap: 0.0,
         }
     }
 }
@@ -172,6 +302,8 @@ pub struct LayoutEngine {
     nodes: SlotMap<LayoutNodeId, LayoutNode>,
     /// Root node ID
     root: Option<LayoutNodeId>,
+    /// Root and available size of the last `compute`, to skip clean trees
+    last_computed: Option<(LayoutNodeId, LayoutSize)>,
 }
 
 impl LayoutEngine {
@@ -179,12 +311,15 @@ impl LayoutEngine {
         Self {
             nodes: SlotMap::with_key(),
             root: None,
+            last_computed: None,
         }
     }
 
     /// Create a new layout node
     pub fn create_node(&mut self) -> LayoutNodeId {
-        self.nodes.insert(LayoutNode::default())
+        let mut node = LayoutNode::default();
+        node.computed.dirty = true;
+        self.nodes.insert(node)
     }
 
     /// Get a node by ID
@@ -202,6 +337,17 @@ impl LayoutEngine {
         self.root = Some(id);
     }
 
+    /// Append `child` to `parent`'s children, marking both for layout
+    pub fn add_child(&mut self, parent: LayoutNodeId, child: LayoutNodeId) {
+        if let Some(node) = self.nodes.get_mut(child) {
+            node.parent = Some(parent);
+        }
+        if let Some(node) = self.nodes.get_mut(parent) {
+            node.children.push(child);
+        }
+        self.mark_dirty(child);
+    }
+
     /// Mark a node as dirty (needs layout recalculation)
     pub fn mark_dirty(&mut self, id: LayoutNodeId) {
         if let Some(node) = self.nodes.get_mut(id) {
@@ -217,134 +363,488 @@ impl LayoutEngine {
     /// Calculate layout for the entire tree
     pub fn calculate_layout(&mut self, available_width: f32, available_height: f32) {
         if let Some(root_id) = self.root {
-            self.calculate_node_layout(root_id, available_width, available_height);
+            self.compute(root_id, LayoutSize::new(available_width, available_height));
         }
     }
 
-    /// Calculate layout for a specific node (recursive)
-    fn calculate_node_layout(&mut self, node_id: LayoutNodeId, available_width: f32, available_height: f32) {
-        // Get the node (we'll need to be careful with borrowing)
-        let node = match self.nodes.get(node_id) {
-            Some(n) => n,
-            None => return,
+    /// Lay out the tree under `root` in an `available` area, giving every
+    /// node a size, a position relative to its parent and an absolute
+    /// position relative to the area's top-left corner.
+    ///
+    /// An auto-sized root fills the area's width; a flex root fills its
+    /// height too, while block and absolute roots take their content's
+    /// height. Nothing is recomputed if no node was marked dirty and the
+    /// area is unchanged.
+    pub fn compute(&mut self, root: LayoutNodeId, available: LayoutSize) {
+        let Some(node) = self.nodes.get(root) else {
+            return;
         };
-
-        // Skip if not dirty
-        if !node.computed.dirty {
+        if !node.computed.dirty && self.last_computed == Some((root, available)) {
             return;
         }
 
-        let algorithm = node.algorithm;
-        let children: Vec<LayoutNodeId> = node.children.clone();
+        let c = node.constraints;
+        let (width, height) = (Some(available.width), Some(available.height));
+        let fills_height = node.algorithm == LayoutAlgorithm::Flex;
+        let own_height = c.height.resolve(height);
+        let measured_height = if own_height.is_none() && !fills_height {
+            self.measure(root, width, height).1
+        } else {
+            0.0
+        };
+        let size = LayoutSize::new(
+            clamp_dimension(
+                c.width.resolve(width).unwrap_or(available.width - c.margin_left - c.margin_right),
+                c.min_width, c.max_width, width,
+            ),
+            clamp_dimension(
+                own_height.unwrap_or(if fills_height {
+                    available.height - c.margin_top - c.margin_bottom
+                } else {
+                    measured_height
+                }),
+                c.min_height, c.max_height, height,
+            ),
+        );
+        self.place(root, LayoutPoint::new(c.margin_left, c.margin_top), size);
+        self.layout_children(root, true, own_height.is_some() || fills_height);
+        self.update_absolute(root, LayoutPoint::origin());
+        self.last_computed = Some((root, available));
+    }
 
-        // Calculate based on algorithm
-        match algorithm {
-            LayoutAlgorithm::Flex => {
-                self.calculate_flex_layout(node_id, available_width, available_height);
+    /// Size a node wants on its own, border box included: its set width
+    /// and height, or its content's size along auto axes, within its
+    /// min/max. `parent_width` and `parent_height` are what percentages
+    /// resolve against (None where the parent is auto-sized).
+    fn measure(&self, id: LayoutNodeId, parent_width: Option<f32>, parent_height: Option<f32>) -> (f32, f32) {
+        let Some(node) = self.nodes.get(id) else {
+            return (0.0, 0.0);
+        };
+        let c = &node.constraints;
+        let (padding_x, padding_y) = padding(c);
+        let width = c.width.resolve(parent_width);
+        let height = c.height.resolve(parent_height);
+
+        let (content_width, content_height) = if width.is_some() && height.is_some() {
+            (0.0, 0.0)
+        } else {
+            let inner_width = width.map(|w| (w - padding_x).max(0.0));
+            let inner_height = height.map(|h| (h - padding_y).max(0.0));
+            let children = node.children.iter().map(|&child| {
+                let (w, h) = self.measure(child, inner_width, inner_height);
+                let cc = &self.nodes[child].constraints;
+                (cc.margin_left + w + cc.margin_right, cc.margin_top + h + cc.margin_bottom)
+            });
+            let gaps = node.gap * node.children.len().saturating_sub(1) as f32;
+            match node.algorithm {
+                LayoutAlgorithm::Flex if is_row(node.flex_direction) => children
+                    .fold((gaps, 0.0f32), |(w, h), (cw, ch)| (w + cw, h.max(ch))),
+                LayoutAlgorithm::Flex => children
+                    .fold((0.0f32, gaps), |(w, h), (cw, ch)| (w.max(cw), h + ch)),
+                LayoutAlgorithm::Block | LayoutAlgorithm::Grid => children
+                    .fold((0.0f32, 0.0), |(w, h), (cw, ch)| (w.max(cw), h + ch)),
+                LayoutAlgorithm::Absolute => children
+                    .fold((0.0f32, 0.0f32), |(w, h), (cw, ch)| (w.max(cw), h.max(ch))),
             }
-            LayoutAlgorithm::Block => {
-                self.calculate_block_layout(node_id, available_width, available_height);
+        };
+
+        (
+            clamp_dimension(width.unwrap_or(content_width + padding_x), c.min_width, c.max_width, parent_width),
+            clamp_dimension(height.unwrap_or(content_height + padding_y), c.min_height, c.max_height, parent_height),
+        )
+    }
+
+    /// Place a node (already sized and positioned by its parent) and
+    /// recurse. `definite_width` and `definite_height` say whether its
+    /// children can resolve percentages against its size.
+    fn layout_children(&mut self, id: LayoutNodeId, definite_width: bool, definite_height: bool) {
+        let Some(node) = self.nodes.get(id) else {
+            return;
+        };
+        let content = node.computed.content_size;
+        let inner_width = definite_width.then_some(content.width);
+        let inner_height = definite_height.then_some(content.height);
+        let origin = LayoutPoint::new(node.constraints.padding_left, node.constraints.padding_top);
+
+        let placements = match node.algorithm {
+            LayoutAlgorithm::Flex => self.flex_placements(id, content, inner_width, inner_height),
+            // TODO: Implement grid layout
+            LayoutAlgorithm::Block | LayoutAlgorithm::Grid => {
+                self.block_placements(id, content, inner_width, inner_height)
             }
-            LayoutAlgorithm::Absolute => {
-                self.calculate_absolute_layout(node_id);
+            LayoutAlgorithm::Absolute => self.absolute_placements(id, inner_width, inner_height),
+        };
+
+        for placement in placements {
+            self.place(placement.id, origin + placement.position.to_vector(), placement.size);
+            self.layout_children(placement.id, placement.definite.0, placement.definite.1);
+        }
+    }
+
+    /// Flexbox: size items along the main axis from their basis, growing
+    /// or shrinking them to fill each line, then size and align them across
+    fn flex_placements(
+        &self,
+        id: LayoutNodeId,
+        content: LayoutSize,
+        inner_width: Option<f32>,
+        inner_height: Option<f32>,
+    ) -> Vec<Placement> {
+        let node = &self.nodes[id];
+        let row = is_row(node.flex_direction);
+        let reverse = matches!(node.flex_direction, FlexDirection::RowReverse | FlexDirection::ColumnReverse);
+        let wrap_reverse = node.flex_wrap == FlexWrap::WrapReverse;
+        let (main_size, cross_size) =
+            if row { (content.width, content.height) } else { (content.height, content.width) };
+        let (main_parent, cross_parent) = if row { (inner_width, inner_height) } else { (inner_height, inner_width) };
+        let gap = node.gap;
+
+        let mut items: Vec<FlexItem> = node.children.iter().filter_map(|&child_id| {
+            let child = self.nodes.get(child_id)?;
+            let c = &child.constraints;
+            let measured = self.measure(child_id, inner_width, inner_height);
+            let (main_dim, min_main, max_main, cross_dim, min_cross, max_cross) = if row {
+                (c.width, c.min_width, c.max_width, c.height, c.min_height, c.max_height)
+            } else {
+                (c.height, c.min_height, c.max_height, c.width, c.min_width, c.max_width)
+            };
+            let (measured_main, measured_cross) = if row { measured } else { (measured.1, measured.0) };
+            let (mut main_margins, mut cross_margins) = if row {
+                ((c.margin_left, c.margin_right), (c.margin_top, c.margin_bottom))
+            } else {
+                ((c.margin_top, c.margin_bottom), (c.margin_left, c.margin_right))
+            };
+            // Reversed axes start from the other side
+            if reverse {
+                main_margins = (main_margins.1, main_margins.0);
+            }
+            if wrap_reverse {
+                cross_margins = (cross_margins.1, cross_margins.0);
             }
-            LayoutAlgorithm::Grid => {
-                // TODO: Implement grid layout
-                self.calculate_block_layout(node_id, available_width, available_height);
+
+            let basis = child.flex_basis.resolve(main_parent)
+                .or_else(|| main_dim.resolve(main_parent))
+                .unwrap_or(measured_main);
+            let min = min_main.resolve(main_parent).unwrap_or(0.0).max(0.0);
+            let max = max_main.resolve(main_parent).unwrap_or(f32::INFINITY);
+            let cross = cross_dim.resolve(cross_parent);
+            Some(FlexItem {
+                id: child_id,
+                basis,
+                hypothetical: basis.min(max).max(min),
+                min,
+                max,
+                grow: child.flex_grow,
+                shrink: child.flex_shrink,
+                main_margins,
+                cross_margins,
+                main: 0.0,
+                cross: clamp_dimension(cross.unwrap_or(measured_cross), min_cross, max_cross, cross_parent),
+                cross_definite: cross.is_some(),
+                stretch_limits: (min_cross.resolve(cross_parent), max_cross.resolve(cross_parent)),
+                frozen: false,
+            })
+        }).collect();
+
+        // Break into lines where the next item would overflow
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        let mut used = 0.0;
+        for (i, item) in items.iter().enumerate() {
+            let outer = item.hypothetical + item.main_margins.0 + item.main_margins.1;
+            match lines.last_mut() {
+                Some(line) if node.flex_wrap == FlexWrap::NoWrap || used + gap + outer <= main_size + 0.01 => {
+                    line.push(i);
+                    used += gap + outer;
+                }
+                _ => {
+                    lines.push(vec![i]);
+                    used = outer;
+                }
             }
         }
 
-        // Calculate layout for children
-        for child_id in children {
-            if let Some(parent) = self.nodes.get(node_id) {
-                let content_width = parent.computed.content_size.width;
-                let content_height = parent.computed.content_size.height;
-                self.calculate_node_layout(child_id, content_width, content_height);
+        for line in &lines {
+            resolve_flexible_lengths(&mut items, line, main_size, gap);
+        }
+
+        // A line is as tall as its tallest item; a single unwrapped line
+        // fills the container. Leftover cross space is shared between lines
+        // (align-content: stretch).
+        let mut line_cross: Vec<f32> = lines.iter().map(|line| {
+            line.iter()
+                .map(|&i| items[i].cross + items[i].cross_margins.0 + items[i].cross_margins.1)
+                .fold(0.0, f32::max)
+        }).collect();
+        if node.flex_wrap == FlexWrap::NoWrap {
+            line_cross = vec![cross_size; lines.len()];
+        } else {
+            let free = cross_size - line_cross.iter().sum::<f32>() - gap * lines.len().saturating_sub(1) as f32;
+            if free > 0.0 {
+                line_cross.iter_mut().for_each(|cross| *cross += free / lines.len() as f32);
             }
         }
 
-        // Mark as clean
-        if let Some(node) = self.nodes.get_mut(node_id) {
-            node.computed.dirty = false;
+        let mut placements = Vec::with_capacity(items.len());
+        let mut cross_offset = 0.0;
+        for (line, &line_cross) in lines.iter().zip(&line_cross) {
+            let count = line.len() as f32;
+            let used = line.iter()
+                .map(|&i| items[i].main + items[i].main_margins.0 + items[i].main_margins.1)
+                .sum::<f32>()
+                + gap * (count - 1.0);
+            let free = main_size - used;
+            let (mut main_pos, between) = match node.justify_content {
+                JustifyContent::FlexStart => (0.0, 0.0),
+                JustifyContent::FlexEnd => (free, 0.0),
+                JustifyContent::Center => (free / 2.0, 0.0),
+                JustifyContent::SpaceBetween if free > 0.0 && count > 1.0 => (0.0, free / (count - 1.0)),
+                JustifyContent::SpaceBetween => (0.0, 0.0),
+                JustifyContent::SpaceAround if free > 0.0 => (free / count / 2.0, free / count),
+                JustifyContent::SpaceEvenly if free > 0.0 => (free / (count + 1.0), free / (count + 1.0)),
+                JustifyContent::SpaceAround | JustifyContent::SpaceEvenly => (free / 2.0, 0.0),
+            };
+
+            for &i in line {
+                let item = &mut items[i];
+                let (cross_start, cross_end) = item.cross_margins;
+                let stretched = node.align_items == AlignItems::Stretch && !item.cross_definite;
+                if stretched {
+                    let (min, max) = item.stretch_limits;
+                    let mut cross = line_cross - cross_start - cross_end;
+                    if let Some(max) = max {
+                        cross = cross.min(max);
+                    }
+                    if let Some(min) = min {
+                        cross = cross.max(min);
+                    }
+                    item.cross = cross.max(0.0);
+                }
+                let cross_pos = cross_offset + match node.align_items {
+                    AlignItems::FlexEnd => line_cross - item.cross - cross_end,
+                    AlignItems::Center => cross_start + (line_cross - cross_start - cross_end - item.cross) / 2.0,
+                    // No text baselines here, so baseline aligns like flex-start
+                    AlignItems::FlexStart | AlignItems::Stretch | AlignItems::Baseline => cross_start,
+                };
+
+                main_pos += item.main_margins.0;
+                let main = if reverse { main_size - main_pos - item.main } else { main_pos };
+                let cross = if wrap_reverse { cross_size - cross_pos - item.cross } else { cross_pos };
+                main_pos += item.main + item.main_margins.1 + gap + between;
+
+                let definite_cross = item.cross_definite || stretched;
+                placements.push(if row {
+                    Placement {
+                        id: item.id,
+                        position: LayoutPoint::new(main, cross),
+                        size: LayoutSize::new(item.main, item.cross),
+                        definite: (true, definite_cross),
+                    }
+                } else {
+                    Placement {
+                        id: item.id,
+                        position: LayoutPoint::new(cross, main),
+                        size: LayoutSize::new(item.cross, item.main),
+                        definite: (definite_cross, true),
+                    }
+                });
+            }
+            cross_offset += line_cross + gap;
         }
+        placements
     }
 
-    fn calculate_flex_layout(&mut self, node_id: LayoutNodeId, available_width: f32, available_height: f32) {
-        // Basic flex layout implementation
-        // TODO: Full flexbox algorithm implementation
-        let node = match self.nodes.get_mut(node_id) {
-            Some(n) => n,
-            None => return,
-        };
-
-        // Calculate node size
-        let width = match node.constraints.width {
-            Dimension::Points(w) => w,
-            Dimension::Percent(p) => available_width * p / 100.0,
-            Dimension::Auto => available_width,
-        };
+    /// Block: children fill the width and stack top to bottom
+    fn block_placements(
+        &self,
+        id: LayoutNodeId,
+        content: LayoutSize,
+        inner_width: Option<f32>,
+        inner_height: Option<f32>,
+    ) -> Vec<Placement> {
+        let mut y = 0.0;
+        self.nodes[id].children.iter().filter_map(|&child_id| {
+            let c = &self.nodes.get(child_id)?.constraints;
+            let width = c.width.resolve(inner_width)
+                .unwrap_or(content.width - c.margin_left - c.margin_right);
+            let height = c.height.resolve(inner_height);
+            let size = LayoutSize::new(
+                clamp_dimension(width, c.min_width, c.max_width, inner_width),
+                clamp_dimension(
+                    height.unwrap_or_else(|| self.measure(child_id, inner_width, inner_height).1),
+                    c.min_height, c.max_height, inner_height,
+                ),
+            );
+            let position = LayoutPoint::new(c.margin_left, y + c.margin_top);
+            y += c.margin_top + size.height + c.margin_bottom;
+            Some(Placement { id: child_id, position, size, definite: (true, height.is_some()) })
+        }).collect()
+    }
 
-        let height = match node.constraints.height {
-            Dimension::Points(h) => h,
-            Dimension::Percent(p) => available_height * p / 100.0,
-            Dimension::Auto => available_height,
-        };
+    /// Absolute: children keep their own size, offset by their margins
+    fn absolute_placements(
+        &self,
+        id: LayoutNodeId,
+        inner_width: Option<f32>,
+        inner_height: Option<f32>,
+    ) -> Vec<Placement> {
+        self.nodes[id].children.iter().filter_map(|&child_id| {
+            let c = &self.nodes.get(child_id)?.constraints;
+            let (width, height) = self.measure(child_id, inner_width, inner_height);
+            Some(Placement {
+                id: child_id,
+                position: LayoutPoint::new(c.margin_left, c.margin_top),
+                size: LayoutSize::new(width, height),
+                definite: (c.width.resolve(inner_width).is_some(), c.height.resolve(inner_height).is_some()),
+            })
+        }).collect()
+    }
 
-        node.computed.size = LayoutSize::new(width, height);
-        node.computed.content_size = LayoutSize::new(
-            width - node.constraints.padding_left - node.constraints.padding_right,
-            height - node.constraints.padding_top - node.constraints.padding_bottom,
-        );
+    /// Store a node's position (relative to its parent) and border-box size
+    fn place(&mut self, id: LayoutNodeId, position: LayoutPoint, size: LayoutSize) {
+        if let Some(node) = self.nodes.get_mut(id) {
+            let (padding_x, padding_y) = padding(&node.constraints);
+            node.computed.position = position;
+            node.computed.size = size;
+            node.computed.content_size = LayoutSize::new(
+                (size.width - padding_x).max(0.0),
+                (size.height - padding_y).max(0.0),
+            );
+        }
     }
 
-    fn calculate_block_layout(&mut self, node_id: LayoutNodeId, available_width: f32, available_height: f32) {
-        // Basic block layout implementation
-        let node = match self.nodes.get_mut(node_id) {
-            Some(n) => n,
-            None => return,
+    /// Fill in absolute positions from the parents' and mark the subtree clean
+    fn update_absolute(&mut self, id: LayoutNodeId, parent_origin: LayoutPoint) {
+        let Some(node) = self.nodes.get_mut(id) else {
+            return;
         };
+        let origin = parent_origin + node.computed.position.to_vector();
+        node.computed.absolute_position = origin;
+        node.computed.dirty = false;
+        for child in node.children.clone() {
+            self.update_absolute(child, origin);
+        }
+    }
+}
 
-        let width = match node.constraints.width {
-            Dimension::Points(w) => w,
-            Dimension::Percent(p) => available_width * p / 100.0,
-            Dimension::Auto => available_width,
-        };
+/// Where a parent puts one child
+struct Placement {
+    id: LayoutNodeId,
+    /// Relative to the parent's content box
+    position: LayoutPoint,
+    size: LayoutSize,
+    /// Whether the child's width and height count as definite for its own
+    /// children's percentages
+    definite: (bool, bool),
+}
 
-        let height = match node.constraints.height {
-            Dimension::Points(h) => h,
-            Dimension::Percent(p) => available_height * p / 100.0,
-            Dimension::Auto => 0.0, // Will be calculated based on content
-        };
+/// A child of a flex container while its line is being laid out
+struct FlexItem {
+    id: LayoutNodeId,
+    basis: f32,
+    /// The basis within min/max
+    hypothetical: f32,
+    min: f32,
+    max: f32,
+    grow: f32,
+    shrink: f32,
+    /// Margins at the main and cross start and end (swapped on reversed axes)
+    main_margins: (f32, f32),
+    cross_margins: (f32, f32),
+    /// Resolved main size
+    main: f32,
+    /// Cross size, before stretching
+    cross: f32,
+    /// The cross size was set, so the item isn't stretched
+    cross_definite: bool,
+    /// Cross min/max, applied when stretching
+    stretch_limits: (Option<f32>, Option<f32>),
+    frozen: bool,
+}
 
-        node.computed.size = LayoutSize::new(width, height);
-        node.computed.content_size = LayoutSize::new(
-            width - node.constraints.padding_left - node.constraints.padding_right,
-            height - node.constraints.padding_top - node.constraints.padding_bottom,
-        );
+/// Grow or shrink a line's items to fill `main_size` (CSS Flexbox 9.7):
+/// distribute the free space by flex factor, freeze items that hit their
+/// min or max, and repeat with what's left.
+fn resolve_flexible_lengths(items: &mut [FlexItem], line: &[usize], main_size: f32, gap: f32) {
+    let gaps = gap * line.len().saturating_sub(1) as f32;
+    let margins: f32 = line.iter().map(|&i| items[i].main_margins.0 + items[i].main_margins.1).sum();
+    let hypothetical: f32 = line.iter().map(|&i| items[i].hypothetical).sum();
+    let growing = hypothetical + margins + gaps < main_size;
+
+    for &i in line {
+        let item = &mut items[i];
+        item.main = item.hypothetical;
+        let factor = if growing { item.grow } else { item.shrink };
+        item.frozen = factor <= 0.0
+            || (growing && item.basis > item.hypothetical)
+            || (!growing && item.basis < item.hypothetical);
     }
+    let free_space = |items: &[FlexItem]| {
+        main_size - gaps - margins
+            - line.iter().map(|&i| if items[i].frozen { items[i].main } else { items[i].basis }).sum::<f32>()
+    };
+    let initial_free = free_space(items);
 
-    fn calculate_absolute_layout(&mut self, node_id: LayoutNodeId) {
-        // Absolute positioning
-        let node = match self.nodes.get_mut(node_id) {
-            Some(n) => n,
-            None => return,
-        };
+    loop {
+        let unfrozen: Vec<usize> = line.iter().copied().filter(|&i| !items[i].frozen).collect();
+        if unfrozen.is_empty() {
+            break;
+        }
+        let mut free = free_space(items);
+        let factors: f32 = unfrozen.iter().map(|&i| if growing { items[i].grow } else { items[i].shrink }).sum();
+        // Factors summing to less than 1 take only that share of the space
+        if factors < 1.0 && (initial_free * factors).abs() < free.abs() {
+            free = initial_free * factors;
+        }
 
-        let width = match node.constraints.width {
-            Dimension::Points(w) => w,
-            Dimension::Auto => 0.0,
-            _ => 0.0,
-        };
+        let scaled_shrink: f32 = unfrozen.iter().map(|&i| items[i].shrink * items[i].basis).sum();
+        let mut violation = 0.0;
+        let mut clamped = Vec::with_capacity(unfrozen.len());
+        for &i in &unfrozen {
+            let item = &mut items[i];
+            let target = if growing {
+                item.basis + free * item.grow / factors
+            } else if scaled_shrink > 0.0 {
+                item.basis + free * item.shrink * item.basis / scaled_shrink
+            } else {
+                item.basis
+            };
+            item.main = target.min(item.max).max(item.min);
+            violation += item.main - target;
+            clamped.push((i, item.main - target));
+        }
 
-        let height = match node.constraints.height {
-            Dimension::Points(h) => h,
-            Dimension::Auto => 0.0,
-            _ => 0.0,
-        };
+        // Freeze everything if nothing hit a limit, else only the items
+        // that hit the limit the line overshot past
+        for (i, adjustment) in clamped {
+            if violation.abs() < 1e-3
+                || (violation > 0.0 && adjustment > 0.0)
+                || (violation < 0.0 && adjustment < 0.0)
+            {
+                items[i].frozen = true;
+            }
+        }
+    }
+}
 
-        node.computed.size = LayoutSize::new(width, height);
-        node.computed.content_size = node.computed.size;
+fn is_row(direction: FlexDirection) -> bool {
+    matches!(direction, FlexDirection::Row | FlexDirection::RowReverse)
+}
+
+/// Horizontal and vertical padding
+fn padding(c: &LayoutConstraints) -> (f32, f32) {
+    (c.padding_left + c.padding_right, c.padding_top + c.padding_bottom)
+}
+
+/// Keep a size within min/max (min wins if they conflict) and non-negative
+fn clamp_dimension(value: f32, min: Dimension, max: Dimension, parent: Option<f32>) -> f32 {
+    let mut value = value;
+    if let Some(max) = max.resolve(parent) {
+        value = value.min(max);
+    }
+    if let Some(min) = min.resolve(parent) {
+        value = value.max(min);
     }
+    value.max(0.0)
 }
 
 impl Default for LayoutEngine {
@@ -353,6 +853,99 @@ impl Default for LayoutEngine {
     }
 }
 
+/// A layout tree as JSON, for laying out from outside the engine
+/// (`centered_layout_compute`). Missing fields take `LayoutNode`'s defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutTreeNode {
+    /// Caller's ID, echoed back in the node's `LayoutTreeRect`
+    pub id: u64,
+    pub algorithm: LayoutAlgorithm,
+    #[serde(flatten)]
+    pub constraints: LayoutConstraints,
+    pub flex_direction: FlexDirection,
+    pub flex_wrap: FlexWrap,
+    pub justify_content: JustifyContent,
+    pub align_items: AlignItems,
+    pub flex_grow: f32,
+    pub flex_shrink: f32,
+    pub flex_basis: Dimension,
+    pub gap: f32,
+    pub children: Vec<LayoutTreeNode>,
+}
+
+impl Default for LayoutTreeNode {
+    fn default() -> Self {
+        let node = LayoutNode::default();
+        Self {
+            id: 0,
+            algorithm: node.algorithm,
+            constraints: node.constraints,
+            flex_direction: node.flex_direction,
+            flex_wrap: node.flex_wrap,
+            justify_content: node.justify_content,
+            align_items: node.align_items,
+            flex_grow: node.flex_grow,
+            flex_shrink: node.flex_shrink,
+            flex_basis: node.flex_basis,
+            gap: node.gap,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Where a `LayoutTreeNode` ended up, in absolute coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayoutTreeRect {
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Lay out a JSON tree in an `available` area, returning every node's
+/// rect in depth-first order
+pub fn compute_tree(tree: &LayoutTreeNode, available: LayoutSize) -> Vec<LayoutTreeRect> {
+    fn build(engine: &mut LayoutEngine, tree: &LayoutTreeNode, ids: &mut Vec<(u64, LayoutNodeId)>) -> LayoutNodeId {
+        let id = engine.create_node();
+        let node = &mut engine.nodes[id];
+        node.algorithm = tree.algorithm;
+        node.constraints = tree.constraints;
+        node.flex_direction = tree.flex_direction;
+        node.flex_wrap = tree.flex_wrap;
+        node.justify_content = tree.justify_content;
+        node.align_items = tree.align_items;
+        node.flex_grow = tree.flex_grow;
+        node.flex_shrink = tree.flex_shrink;
+        node.flex_basis = tree.flex_basis;
+        node.gap = tree.gap;
+        ids.push((tree.id, id));
+        for child in &tree.children {
+            let child = build(engine, child, ids);
+            engine.add_child(id, child);
+        }
+        id
+    }
+
+    let mut engine = LayoutEngine::new();
+    let mut ids = Vec::new();
+    let root = build(&mut engine, tree, &mut ids);
+    engine.compute(root, available);
+    ids.into_iter()
+        .map(|(id, node)| {
+            let computed = &engine.nodes[node].computed;
+            LayoutTreeRect {
+                id,
+                x: computed.absolute_position.x,
+                y: computed.absolute_position.y,
+                width: computed.size.width,
+                height: computed.size.height,
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,4 +965,278 @@ mod tests {
         let node = engine.get_node(node_id).unwrap();
         assert!(node.computed.dirty);
     }
+
+    fn fixed(width: f32, height: f32) -> LayoutConstraints {
+        LayoutConstraints {
+            width: Dimension::Points(width),
+            height: Dimension::Points(height),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_percent_and_calc_resolve_against_parent() {
+        let mut engine = LayoutEngine::new();
+        let root = engine.create_node();
+        let parent = engine.get_node_mut(root).unwrap();
+        parent.constraints = fixed(200.0, 100.0);
+        parent.flex_direction = FlexDirection::Column;
+        engine.set_root(root);
+
+        let half = engine.create_node();
+        engine.get_node_mut(half).unwrap().constraints.width = "50%".parse().unwrap();
+        let inset = engine.create_node();
+        engine.get_node_mut(inset).unwrap().constraints.width = "calc(100% - 20px)".parse().unwrap();
+        engine.add_child(root, half);
+        engine.add_child(root, inset);
+
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(engine.get_node(half).unwrap().computed.size.width, 100.0);
+        assert_eq!(engine.get_node(inset).unwrap().computed.size.width, 180.0);
+
+        // Padding shrinks what percentages resolve against
+        engine.get_node_mut(root).unwrap().constraints.padding_left = 20.0;
+        engine.mark_dirty(half);
+        engine.mark_dirty(inset);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(engine.get_node(half).unwrap().computed.size.width, 90.0);
+        assert_eq!(engine.get_node(inset).unwrap().computed.size.width, 160.0);
+    }
+
+    #[test]
+    fn test_percent_of_auto_parent_is_auto() {
+        let mut engine = LayoutEngine::new();
+        // A block whose height comes from its content
+        let root = engine.create_node();
+        let parent = engine.get_node_mut(root).unwrap();
+        parent.algorithm = LayoutAlgorithm::Block;
+        parent.constraints.width = Dimension::Points(200.0);
+        engine.set_root(root);
+
+        let percent = engine.create_node();
+        let child = engine.get_node_mut(percent).unwrap();
+        child.algorithm = LayoutAlgorithm::Block;
+        child.constraints.height = Dimension::Percent(50.0);
+        let auto = engine.create_node();
+        engine.get_node_mut(auto).unwrap().algorithm = LayoutAlgorithm::Block;
+        engine.add_child(root, percent);
+        engine.add_child(root, auto);
+
+        engine.calculate_layout(800.0, 600.0);
+        let percent = engine.get_node(percent).unwrap().computed.size;
+        let auto = engine.get_node(auto).unwrap().computed.size;
+        assert_eq!(percent, auto);
+        assert_eq!(percent.width, 200.0);
+        assert_eq!(Dimension::Percent(50.0).resolve(None), None);
+    }
+
+    /// A root of `width` x `height` with a child per constraint set
+    fn flex_row(
+        width: f32,
+        height: f32,
+        children: &[LayoutConstraints],
+    ) -> (LayoutEngine, LayoutNodeId, Vec<LayoutNodeId>) {
+        let mut engine = LayoutEngine::new();
+        let root = engine.create_node();
+        engine.get_node_mut(root).unwrap().constraints = fixed(width, height);
+        engine.set_root(root);
+        let ids = children.iter().map(|&constraints| {
+            let child = engine.create_node();
+            engine.get_node_mut(child).unwrap().constraints = constraints;
+            engine.add_child(root, child);
+            child
+        }).collect();
+        (engine, root, ids)
+    }
+
+    /// x, y, width, height
+    fn rect(engine: &LayoutEngine, id: LayoutNodeId) -> (f32, f32, f32, f32) {
+        let computed = &engine.get_node(id).unwrap().computed;
+        let (position, size) = (computed.absolute_position, computed.size);
+        ((position.x * 100.0).round() / 100.0, (position.y * 100.0).round() / 100.0,
+         (size.width * 100.0).round() / 100.0, (size.height * 100.0).round() / 100.0)
+    }
+
+    fn width(width: f32) -> LayoutConstraints {
+        LayoutConstraints { width: Dimension::Points(width), ..Default::default() }
+    }
+
+    #[test]
+    fn test_flex_grow_shares_free_space_and_stretches() {
+        let (mut engine, _, ids) = flex_row(300.0, 100.0, &[
+            LayoutConstraints::default(),
+            LayoutConstraints::default(),
+            width(50.0),
+        ]);
+        engine.get_node_mut(ids[0]).unwrap().flex_grow = 1.0;
+        engine.get_node_mut(ids[1]).unwrap().flex_grow = 2.0;
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]), (0.0, 0.0, 83.33, 100.0));
+        assert_eq!(rect(&engine, ids[1]), (83.33, 0.0, 166.67, 100.0));
+        assert_eq!(rect(&engine, ids[2]), (250.0, 0.0, 50.0, 100.0));
+
+        // A max-width item freezes and the rest takes its share
+        engine.get_node_mut(ids[0]).unwrap().constraints.max_width = Dimension::Points(50.0);
+        engine.get_node_mut(ids[1]).unwrap().flex_grow = 1.0;
+        engine.mark_dirty(ids[0]);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]), (0.0, 0.0, 50.0, 100.0));
+        assert_eq!(rect(&engine, ids[1]), (50.0, 0.0, 200.0, 100.0));
+
+        // Grow factors summing below 1 only take that share
+        engine.get_node_mut(ids[0]).unwrap().flex_grow = 0.25;
+        engine.get_node_mut(ids[1]).unwrap().flex_grow = 0.25;
+        engine.get_node_mut(ids[0]).unwrap().constraints.max_width = Dimension::Auto;
+        engine.mark_dirty(ids[0]);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]).2, 62.5);
+        assert_eq!(rect(&engine, ids[1]).2, 62.5);
+    }
+
+    #[test]
+    fn test_flex_justify_and_align() {
+        let item = fixed(50.0, 20.0);
+        let (mut engine, root, ids) = flex_row(300.0, 100.0, &[item, item, item]);
+        let container = engine.get_node_mut(root).unwrap();
+        container.justify_content = JustifyContent::SpaceBetween;
+        container.align_items = AlignItems::Center;
+        engine.calculate_layout(800.0, 600.0);
+        let xs: Vec<_> = ids.iter().map(|&id| rect(&engine, id).0).collect();
+        assert_eq!(xs, vec![0.0, 125.0, 250.0]);
+        assert!(ids.iter().all(|&id| rect(&engine, id).1 == 40.0));
+
+        let cases = [
+            (JustifyContent::FlexEnd, AlignItems::FlexEnd, [150.0, 200.0, 250.0], 80.0),
+            (JustifyContent::Center, AlignItems::FlexStart, [75.0, 125.0, 175.0], 0.0),
+            (JustifyContent::SpaceAround, AlignItems::Stretch, [25.0, 125.0, 225.0], 0.0),
+            (JustifyContent::SpaceEvenly, AlignItems::Center, [37.5, 125.0, 212.5], 40.0),
+        ];
+        for (justify, align, xs, y) in cases {
+            let container = engine.get_node_mut(root).unwrap();
+            container.justify_content = justify;
+            container.align_items = align;
+            engine.mark_dirty(root);
+            engine.calculate_layout(800.0, 600.0);
+            for (&id, x) in ids.iter().zip(xs) {
+                assert_eq!(rect(&engine, id), (x, y, 50.0, 20.0), "{:?} {:?}", justify, align);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flex_shrink_respects_min_width() {
+        let (mut engine, _, ids) = flex_row(200.0, 50.0, &[width(150.0), width(150.0)]);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]), (0.0, 0.0, 100.0, 50.0));
+        assert_eq!(rect(&engine, ids[1]), (100.0, 0.0, 100.0, 50.0));
+
+        engine.get_node_mut(ids[0]).unwrap().constraints.min_width = Dimension::Points(120.0);
+        engine.mark_dirty(ids[0]);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]), (0.0, 0.0, 120.0, 50.0));
+        assert_eq!(rect(&engine, ids[1]), (120.0, 0.0, 80.0, 50.0));
+
+        // Shrinking is weighted by basis
+        let (mut engine, _, ids) = flex_row(200.0, 50.0, &[width(100.0), width(200.0)]);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]).2, 66.67);
+        assert_eq!(rect(&engine, ids[1]).2, 133.33);
+    }
+
+    #[test]
+    fn test_flex_wrap_breaks_lines_with_gap() {
+        let item = fixed(80.0, 30.0);
+        let (mut engine, root, ids) = flex_row(200.0, 100.0, &[item, item, item]);
+        let container = engine.get_node_mut(root).unwrap();
+        container.flex_wrap = FlexWrap::Wrap;
+        container.gap = 10.0;
+        engine.calculate_layout(800.0, 600.0);
+        // Two lines share the 30px left over, 15px each
+        assert_eq!(rect(&engine, ids[0]), (0.0, 0.0, 80.0, 30.0));
+        assert_eq!(rect(&engine, ids[1]), (90.0, 0.0, 80.0, 30.0));
+        assert_eq!(rect(&engine, ids[2]), (0.0, 55.0, 80.0, 30.0));
+
+        engine.get_node_mut(root).unwrap().flex_wrap = FlexWrap::WrapReverse;
+        engine.mark_dirty(root);
+        engine.calculate_layout(800.0, 600.0);
+        assert_eq!(rect(&engine, ids[0]), (0.0, 70.0, 80.0, 30.0));
+        assert_eq!(rect(&engine, ids[2]), (0.0, 15.0, 80.0, 30.0));
+
+        // Without wrapping, everything shrinks onto one line
+        engine.get_node_mut(root).unwrap().flex_wrap = FlexWrap::NoWrap;
+        engine.mark_dirty(root);
+        engine.calculate_layout(800.0, 600.0);
+        let xs: Vec<_> = ids.iter().map(|&id| rect(&engine, id).0).collect();
+        assert_eq!(xs, vec![0.0, 70.0, 140.0]);
+        assert_eq!(rect(&engine, ids[0]).2, 60.0);
+    }
+
+    #[test]
+    fn test_flex_column_reverse_with_margins_and_padding() {
+        let tall = LayoutConstraints { height: Dimension::Points(100.0), margin_bottom: 10.0, ..Default::default() };
+        let (mut engine, root, ids) = flex_row(100.0, 300.0, &[
+            LayoutConstraints { height: Dimension::Points(50.0), ..Default::default() },
+            tall,
+        ]);
+        let container = engine.get_node_mut(root).unwrap();
+        container.flex_direction = FlexDirection::ColumnReverse;
+        container.constraints.padding_left = 10.0;
+        container.constraints.padding_top = 20.0;
+        engine.calculate_layout(800.0, 600.0);
+        // 280px of content height, filled from the bottom
+        assert_eq!(rect(&engine, ids[0]), (10.0, 250.0, 90.0, 50.0));
+        assert_eq!(rect(&engine, ids[1]), (10.0, 140.0, 90.0, 100.0));
+        assert!(!engine.get_node(ids[1]).unwrap().computed.dirty);
+    }
+
+    #[test]
+    fn test_compute_tree_from_json() {
+        let tree: LayoutTreeNode = serde_json::from_value(serde_json::json!({
+            "id": 1, "padding_left": 8, "padding_top": 8, "gap": 4,
+            "children": [
+                { "id": 2, "width": 100 },
+                {
+                    "id": 3, "flex_grow": 1, "algorithm": "Flex", "flex_direction": "Column",
+                    "justify_content": "Center", "align_items": "Center",
+                    "children": [{ "id": 4, "width": "50%", "height": 20 }],
+                },
+            ],
+        }))
+        .unwrap();
+        let rects = compute_tree(&tree, LayoutSize::new(400.0, 200.0));
+        let rect = |id, x, y, width, height| LayoutTreeRect { id, x, y, width, height };
+        assert_eq!(rects, vec![
+            rect(1, 0.0, 0.0, 400.0, 200.0),
+            rect(2, 8.0, 8.0, 100.0, 192.0),
+            rect(3, 112.0, 8.0, 288.0, 192.0),
+            rect(4, 184.0, 94.0, 144.0, 20.0),
+        ]);
+    }
+
+    #[test]
+    fn test_dimension_css_round_trip() {
+        let parse = |css: &str| css.parse::<Dimension>();
+        assert_eq!(parse("auto"), Ok(Dimension::Auto));
+        assert_eq!(parse("16px"), Ok(Dimension::Points(16.0)));
+        assert_eq!(parse("16"), Ok(Dimension::Points(16.0)));
+        assert_eq!(parse("50%"), Ok(Dimension::Percent(50.0)));
+        assert_eq!(parse("calc(100% - 16px)"), Ok(Dimension::Calc { percent: 100.0, points: -16.0 }));
+        assert_eq!(parse("calc(8px + 50% - 2px)"), Ok(Dimension::Calc { percent: 50.0, points: 6.0 }));
+        for bad in ["", "wide", "calc(100% -16px)", "calc(100% -)", "calc()", "NaN%"] {
+            assert!(parse(bad).is_err(), "{:?} parsed", bad);
+        }
+
+        let constraints: LayoutConstraints = serde_json::from_value(serde_json::json!({
+            "width": "calc(100% - 16px)", "height": 40, "min_width": "auto", "min_height": "auto",
+            "max_width": "50%", "max_height": "auto",
+            "padding_top": 0, "padding_right": 0, "padding_bottom": 0, "padding_left": 0,
+            "margin_top": 0, "margin_right": 0, "margin_bottom": 0, "margin_left": 0,
+        }))
+        .unwrap();
+        assert_eq!(constraints.height, Dimension::Points(40.0));
+        let json = serde_json::to_value(constraints).unwrap();
+        assert_eq!(json["width"], "calc(100% - 16px)");
+        assert_eq!(json["max_width"], "50%");
+        assert!(serde_json::from_value::<Dimension>(serde_json::json!("calc(1px")).is_err());
+    }
 }
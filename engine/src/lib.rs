@@ -11,23 +11,27 @@ extern crate objc;
 
 // Core modules
 pub mod audio;
+pub mod error;
 pub mod event;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod ffi;
 pub mod geometry;
 pub mod image;
 pub mod layout;
+pub mod logging;
 pub mod platform;
 pub mod render;
 pub mod style;
 pub mod text;
 pub mod video;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watchdog;
 pub mod widget;
 
 // Re-exports for convenience
 pub use layout::LayoutEngine;
 pub use render::{RenderMode, Renderer};
-pub use style::StyleSystem;
+pub use style::{StyleSystem, ThemeChanges};
 pub use widget::WidgetTree;
 pub use event::EventDispatcher;
 
@@ -40,6 +44,14 @@ pub struct EngineConfig {
     pub height: u32,
     /// Rendering mode (immediate or retained)
     pub mode: RenderMode,
+    /// Collapse consecutive mouse moves within a frame into the latest
+    /// position; turn off to receive every sample
+    #[serde(default = "default_coalesce_mouse_moves")]
+    pub coalesce_mouse_moves: bool,
+}
+
+fn default_coalesce_mouse_moves() -> bool {
+    true
 }
 
 impl Default for EngineConfig {
@@ -48,6 +60,7 @@ impl Default for EngineConfig {
             width: 800,
             height: 600,
             mode: RenderMode::Retained,
+            coalesce_mouse_moves: true,
         }
     }
 }
@@ -65,12 +78,14 @@ pub struct Engine {
 impl Engine {
     /// Create a new engine instance with the given configuration
     pub fn new(config: EngineConfig) -> Self {
+        let mut event_dispatcher = EventDispatcher::new();
+        event_dispatcher.set_coalesce_mouse_moves(config.coalesce_mouse_moves);
         Self {
             layout_engine: LayoutEngine::new(),
             renderer: Renderer::new(config.mode),
             style_system: StyleSystem::new(),
             widget_tree: WidgetTree::new(),
-            event_dispatcher: EventDispatcher::new(),
+            event_dispatcher,
             config,
         }
     }
@@ -80,6 +95,17 @@ impl Engine {
         self.config.mode
     }
 
+    /// Replace the theme while the app runs, marking the widgets whose
+    /// classes use a changed key dirty so the next frame redraws them
+    pub fn reload_styles(&mut self, toml_str: &str) -> Result<ThemeChanges, String> {
+        let changes = self.style_system.reload_theme(toml_str)?;
+        if !changes.is_empty() {
+            let style_system = &self.style_system;
+            self.widget_tree.mark_dirty_by_classes(|classes| style_system.classes_affected(classes, &changes));
+        }
+        Ok(changes)
+    }
+
     /// Resize the rendering surface
     pub fn resize(&mut self, width: u32, height: u32) {
         self.config.width = width;
@@ -96,5 +122,39 @@ mod tests {
         let config = EngineConfig::default();
         let engine = Engine::new(config);
         assert_eq!(engine.mode(), RenderMode::Retained);
+        assert!(engine.event_dispatcher.coalesce_mouse_moves());
+
+        // Configs written before the option keep coalescing
+        let config: EngineConfig = serde_json::from_str(r#"{"width":1,"height":1,"mode":"Immediate"}"#).unwrap();
+        assert!(config.coalesce_mouse_moves);
+        let config = EngineConfig { coalesce_mouse_moves: false, ..EngineConfig::default() };
+        assert!(!Engine::new(config).event_dispatcher.coalesce_mouse_moves());
+    }
+
+    #[test]
+    fn test_reload_styles_marks_affected_widgets_dirty() {
+        let mut engine = Engine::new(EngineConfig::default());
+        engine.style_system.load_theme("[colors]\nprimary = \"#3B82F6\"\nsurface = \"#FFFFFF\"\n").unwrap();
+
+        let root = engine.widget_tree.create_widget(widget::WidgetKind::VStack);
+        let button = engine.widget_tree.create_widget(widget::WidgetKind::Button);
+        let card = engine.widget_tree.create_widget(widget::WidgetKind::Container);
+        engine.widget_tree.set_root(root);
+        engine.widget_tree.add_child(root, button);
+        engine.widget_tree.add_child(root, card);
+        engine.widget_tree.get_widget_mut(button).unwrap().data.classes = "bg-primary rounded".to_string();
+        engine.widget_tree.get_widget_mut(card).unwrap().data.classes = "bg-surface".to_string();
+        engine.widget_tree.mark_rendered();
+
+        let changes = engine.reload_styles("[colors]\nprimary = \"#EF4444\"\nsurface = \"#FFFFFF\"\n").unwrap();
+        assert_eq!(changes.keys, vec!["colors.primary"]);
+        assert!(engine.widget_tree.is_subtree_dirty(button));
+        assert!(engine.widget_tree.is_subtree_dirty(root));
+        assert!(!engine.widget_tree.is_subtree_dirty(card));
+
+        // A bad theme changes nothing
+        engine.widget_tree.mark_rendered();
+        assert!(engine.reload_styles("[colors]\nprimary = \"$missing\"\n").is_err());
+        assert!(!engine.widget_tree.needs_render());
     }
 }
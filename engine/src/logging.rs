@@ -0,0 +1,104 @@
+//! Verbose diagnostic logging
+//!
+//! Lifecycle traces (window ready, resizes, safe-area changes, iOS startup)
+//! are useful while developing but are noise in a shipped app, so they are
+//! off by default and enabled at runtime with `set_verbose`. Errors still go
+//! to stderr unconditionally.
+//!
+//! Enabled messages go to stdout, or to the log callback if one is set so
+//! the host app can route them into its own logging. Warnings are always
+//! emitted, to the log callback or stderr.
+
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Receives one verbose message (null-terminated UTF-8, valid for the call only)
+pub type LogCallback = extern "C" fn(message: *const c_char);
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static LOG_CALLBACK: Mutex<Option<LogCallback>> = Mutex::new(None);
+
+/// Turn verbose logging on or off
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Route verbose messages to `callback` instead of stdout (None restores stdout)
+pub fn set_log_callback(callback: Option<LogCallback>) {
+    *LOG_CALLBACK.lock().unwrap() = callback;
+}
+
+/// Emit a verbose message; use the `verbose!` macro instead of calling this
+pub fn log_verbose(args: std::fmt::Arguments) {
+    if !is_verbose() {
+        return;
+    }
+    if !send_to_callback(args) {
+        println!("{}", args);
+    }
+}
+
+/// Emit a warning whether or not verbose logging is on
+pub fn log_warning(args: std::fmt::Arguments) {
+    if !send_to_callback(args) {
+        eprintln!("{}", args);
+    }
+}
+
+/// Pass a message to the log callback, returning false if none is set
+fn send_to_callback(args: std::fmt::Arguments) -> bool {
+    let Some(callback) = *LOG_CALLBACK.lock().unwrap() else {
+        return false;
+    };
+    // Interior nul bytes can't cross the C boundary
+    let message = CString::new(args.to_string().replace('\0', "")).unwrap_or_default();
+    callback(message.as_ptr());
+    true
+}
+
+/// `println!` for diagnostics that only print with verbose logging enabled
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        $crate::logging::log_verbose(format_args!($($arg)*))
+    };
+}
+pub(crate) use verbose;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    static CAPTURED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    extern "C" fn capture(message: *const c_char) {
+        let message = unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned();
+        CAPTURED.lock().unwrap().push(message);
+    }
+
+    fn captured_resizes() -> Vec<String> {
+        CAPTURED.lock().unwrap().iter().filter(|m| m.contains("Resized")).cloned().collect()
+    }
+
+    #[test]
+    fn test_resize_trace_silent_unless_verbose() {
+        set_log_callback(Some(capture));
+
+        // Off by default: a resize logs nothing, to stdout or the callback
+        set_verbose(false);
+        verbose!("[FFI] WindowEvent::Resized - size: {}x{}, scale: {}", 1600, 1200, 2.0);
+        assert!(captured_resizes().is_empty());
+
+        set_verbose(true);
+        verbose!("[FFI] WindowEvent::Resized - size: {}x{}, scale: {}", 1600, 1200, 2.0);
+        assert_eq!(captured_resizes(), vec!["[FFI] WindowEvent::Resized - size: 1600x1200, scale: 2"]);
+
+        set_verbose(false);
+        set_log_callback(None);
+    }
+}
@@ -6,6 +6,7 @@
 #![cfg(target_os = "android")]
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
@@ -20,6 +21,7 @@ use jni::{JNIEnv, JavaVM};
 use log::info;
 
 use super::backend::{AppConfig, EventCallback, EventResponse, NativeHandle, PlatformBackend, PlatformEvent, SafeAreaInsets};
+use super::ready::{ReadyGate, WindowMetrics};
 use super::wgpu_backend::{SurfaceConfig, WgpuBackend};
 
 // Thread-local state for Android (main thread only)
@@ -39,8 +41,14 @@ thread_local! {
     static RENDER_UNTIL: RefCell<Option<std::time::Instant>> = RefCell::new(None);
     /// Target frames per second (configured at app init)
     static TARGET_FPS: RefCell<u32> = RefCell::new(60);
+    /// Holds Ready back until window insets have been dispatched
+    static READY_GATE: RefCell<ReadyGate> = RefCell::new(ReadyGate::new(READY_INSETS_MAX_POLLS));
 }
 
+/// Event loop iterations to wait for non-zero safe-area insets before
+/// sending Ready anyway (about 300ms at 60 FPS)
+const READY_INSETS_MAX_POLLS: u32 = 20;
+
 /// Set the target FPS for the render loop
 pub fn set_target_fps(fps: u32) {
     TARGET_FPS.with(|f| *f.borrow_mut() = fps.max(1)); // Minimum 1 FPS
@@ -92,11 +100,19 @@ static REQUEST_REDRAW: AtomicBool = AtomicBool::new(false);
 // Software keyboard input comes from the Android UI thread via JNI, but our callback
 // is registered on the main Rust thread. This queue bridges the two threads.
 lazy_static::lazy_static! {
-    static ref PENDING_TEXT_INPUT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    // TextInput, ImePreedit and ImeCommit share one queue so they keep their order
+    static ref PENDING_TEXT_INPUT: Mutex<Vec<PlatformEvent>> = Mutex::new(Vec::new());
     static ref PENDING_KEY_EVENTS: Mutex<Vec<(i32, i32)>> = Mutex::new(Vec::new()); // (keycode, action)
     static ref PENDING_KEYBOARD_HEIGHT: Mutex<Option<(f32, i32)>> = Mutex::new(None); // (height in dp, duration in ms)
+    // Document picker results from onActivityResult: (request ID, URIs or None if cancelled)
+    static ref PENDING_FILE_DIALOG_RESULTS: Mutex<Vec<(u32, Option<Vec<String>>)>> = Mutex::new(Vec::new());
+    static ref FILE_DIALOG_HANDLERS: Mutex<HashMap<u32, FileDialogHandler>> = Mutex::new(HashMap::new());
 }
 
+/// Receives the content URIs picked in a document picker, or None if it was
+/// cancelled. Runs on the main Rust thread.
+pub type FileDialogHandler = Box<dyn FnOnce(Option<Vec<String>>) + Send>;
+
 // C callback type for Go's ready handler
 type GoReadyCallback = unsafe extern "C" fn();
 static mut GO_READY_CALLBACK: Option<GoReadyCallback> = None;
@@ -286,9 +302,8 @@ fn process_queued_input() -> bool {
 
     // Process queued text input
     if let Ok(mut queue) = PENDING_TEXT_INPUT.lock() {
-        for text in queue.drain(..) {
-            info!("Processing queued text input: '{}'", text);
-            let event = PlatformEvent::TextInput { text };
+        for event in queue.drain(..) {
+            info!("Processing queued text input: {:?}", event);
             let response = send_event(event);
             if response.exit {
                 REQUEST_EXIT.store(true, Ordering::SeqCst);
@@ -321,6 +336,17 @@ fn process_queued_input() -> bool {
         }
     }
 
+    // Hand document picker results to whoever opened the picker
+    let results = PENDING_FILE_DIALOG_RESULTS.lock().map(|mut queue| std::mem::take(&mut *queue)).unwrap_or_default();
+    for (request_id, uris) in results {
+        let handler = FILE_DIALOG_HANDLERS.lock().ok().and_then(|mut handlers| handlers.remove(&request_id));
+        if let Some(handler) = handler {
+            info!("Processing file dialog result: request={}, picked={:?}", request_id, uris.as_ref().map(Vec::len));
+            handler(uris);
+            had_events = true;
+        }
+    }
+
     // Process queued keyboard height changes
     if let Ok(mut pending) = PENDING_KEYBOARD_HEIGHT.lock() {
         if let Some((height, duration_ms)) = pending.take() {
@@ -768,6 +794,10 @@ impl PlatformBackend for AndroidBackend {
                                         vsync: true,
                                         low_power_gpu: false,  // Prefer performance GPU
                                         allow_software_fallback: false,
+                                        gamma_correct_text: false,
+                                        subpixel_text: false,
+                                        wide_gamut: false,
+                                        display_p3_colors: false,
                                     };
 
                                     // Initialize with window (blocking on async)
@@ -1151,6 +1181,7 @@ fn run_android_event_loop(app: AndroidApp) {
                         MainEvent::TerminateWindow { .. } => {
                             info!("TerminateWindow received");
                             has_window = false;
+                            READY_GATE.with(|g| g.borrow_mut().reset());
                             // Clear the global backend
                             {
                                 let backend_lock = crate::ffi::get_backend();
@@ -1184,6 +1215,15 @@ fn run_android_event_loop(app: AndroidApp) {
 
                                 update_safe_area_insets();
 
+                                // Before Ready, the deferred Ready carries the new size
+                                let metrics = WindowMetrics { width: logical_width, height: logical_height, scale_factor: scale };
+                                let insets = SAFE_AREA.with(|s| *s.borrow());
+                                READY_GATE.with(|g| g.borrow_mut().resized(metrics, insets));
+                                if READY_GATE.with(|g| g.borrow().is_waiting()) {
+                                    poll_ready_gate();
+                                    return;
+                                }
+
                                 // Send logical dimensions to Go
                                 let response = send_event(PlatformEvent::Resized {
                                     width: logical_width,
@@ -1263,6 +1303,11 @@ fn run_android_event_loop(app: AndroidApp) {
             // The next frame response will tell us if we need to keep it
             CONTINUOUS_RENDER.with(|c| *c.borrow_mut() = true);
         }
+
+        // Deliver Ready once the window insets have arrived
+        if has_window {
+            poll_ready_gate();
+        }
     }
 
     info!("android_main: event loop exited");
@@ -1305,6 +1350,10 @@ fn handle_init_window(app: &AndroidApp) {
             vsync: true,
             low_power_gpu: false,
             allow_software_fallback: false,
+            gamma_correct_text: false,
+            subpixel_text: false,
+            wide_gamut: false,
+            display_p3_colors: false,
         };
 
         if let Err(e) = pollster::block_on(new_backend.init_with_window(&native_handle, surface_config)) {
@@ -1369,20 +1418,39 @@ fn handle_init_window(app: &AndroidApp) {
             }
         }
 
-        // Send Ready event to the registered callback with logical dimensions
-        // (Go's layout system works in logical pixels, like iOS points)
-        let response = send_event(PlatformEvent::Ready {
-            width: logical_width,
-            height: logical_height,
-            scale_factor: scale,
-        });
-
-        if response.exit {
-            REQUEST_EXIT.store(true, Ordering::SeqCst);
+        // Send Ready with logical dimensions (Go's layout system works in
+        // logical pixels, like iOS points) once the safe-area insets are
+        // known, so the first frame is laid out with them
+        let metrics = WindowMetrics { width: logical_width, height: logical_height, scale_factor: scale };
+        let insets = SAFE_AREA.with(|s| *s.borrow());
+        match READY_GATE.with(|g| g.borrow_mut().window_ready(metrics, insets)) {
+            Some(event) => deliver_gated_event(event),
+            None => info!("Deferring Ready until safe area insets are known"),
         }
+    }
+}
+
+/// While Ready is deferred, re-read the safe-area insets and deliver Ready
+/// once they are known (or the wait times out)
+fn poll_ready_gate() {
+    if !READY_GATE.with(|g| g.borrow().is_waiting()) {
+        return;
+    }
+    update_safe_area_insets();
+    let insets = SAFE_AREA.with(|s| *s.borrow());
+    if let Some(event) = READY_GATE.with(|g| g.borrow_mut().poll(insets)) {
+        deliver_gated_event(event);
+    }
+}
 
+/// Send a Ready or follow-up Resized event released by the ready gate
+fn deliver_gated_event(event: PlatformEvent) {
+    let is_ready = matches!(event, PlatformEvent::Ready { .. });
+    let response = send_event(event);
+    if is_ready {
         APP_READY.with(|r| *r.borrow_mut() = true);
     }
+    handle_event_response(&response, true);
 }
 
 // ============================================================================
@@ -1969,6 +2037,97 @@ pub fn scale_factor() -> f64 {
     SCALE_FACTOR.with(|s| *s.borrow())
 }
 
+/// Show the Storage Access Framework picker for opening documents
+/// (`ACTION_OPEN_DOCUMENT`).
+///
+/// `extensions` limit the choices by MIME type (comma-separated, empty for
+/// any file). `initial_uri` is a content URI to start in (API 26+). Returns
+/// false if the picker couldn't be launched; otherwise `handler` is called
+/// with the picked URIs once the user is done.
+pub fn open_document(request_id: u32, extensions: &str, multiple: bool, initial_uri: Option<&str>, handler: FileDialogHandler) -> bool {
+    launch_document_picker(request_id, "openDocument", extensions, Some(multiple), initial_uri, handler)
+}
+
+/// Show the Storage Access Framework picker for creating a document
+/// (`ACTION_CREATE_DOCUMENT`). Like `open_document`, but the handler gets at
+/// most one URI.
+pub fn create_document(request_id: u32, extensions: &str, initial_uri: Option<&str>, handler: FileDialogHandler) -> bool {
+    launch_document_picker(request_id, "createDocument", extensions, None, initial_uri, handler)
+}
+
+/// Call the activity's `openDocument` (with `multiple`) or `createDocument`,
+/// which starts the picker and reports back through `nativeOnFileDialogResult`
+fn launch_document_picker(
+    request_id: u32,
+    method: &str,
+    extensions: &str,
+    multiple: Option<bool>,
+    initial_uri: Option<&str>,
+    handler: FileDialogHandler,
+) -> bool {
+    let Some(vm) = (unsafe { JAVA_VM.as_ref() }) else {
+        info!("{}: JavaVM not available", method);
+        return false;
+    };
+    let mut env = match vm.attach_current_thread() {
+        Ok(env) => env,
+        Err(e) => {
+            info!("{}: Failed to attach thread: {:?}", method, e);
+            return false;
+        }
+    };
+    let _ = env.exception_clear();
+
+    let activity_ptr = match get_activity_ptr() {
+        ptr if !ptr.is_null() => ptr,
+        _ => {
+            info!("{}: Activity not available", method);
+            return false;
+        }
+    };
+    // Not ours to delete (see haptic_feedback)
+    let activity = std::mem::ManuallyDrop::new(unsafe { JObject::from_raw(activity_ptr as *mut _) });
+
+    let (Ok(extensions), Ok(initial_uri)) = (
+        env.new_string(extensions),
+        initial_uri.map_or(Ok(JObject::null()), |uri| env.new_string(uri).map(JObject::from)),
+    ) else {
+        let _ = env.exception_clear();
+        return false;
+    };
+
+    // Registered first: the result can't arrive before the picker starts,
+    // but it is delivered on another thread
+    if let Ok(mut handlers) = FILE_DIALOG_HANDLERS.lock() {
+        handlers.insert(request_id, handler);
+    }
+
+    let request = JValue::Int(request_id as i32);
+    let result = match multiple {
+        Some(multiple) => env.call_method(
+            &*activity,
+            method,
+            "(ILjava/lang/String;ZLjava/lang/String;)V",
+            &[request, (&extensions).into(), JValue::Bool(multiple as u8), (&initial_uri).into()],
+        ),
+        None => env.call_method(
+            &*activity,
+            method,
+            "(ILjava/lang/String;Ljava/lang/String;)V",
+            &[request, (&extensions).into(), (&initial_uri).into()],
+        ),
+    };
+    let _ = env.exception_clear();
+    if let Err(e) = result {
+        info!("{}: JNI call failed: {:?}", method, e);
+        if let Ok(mut handlers) = FILE_DIALOG_HANDLERS.lock() {
+            handlers.remove(&request_id);
+        }
+        return false;
+    }
+    true
+}
+
 // ============================================================================
 // FFI exports for Go
 // ============================================================================
@@ -2047,12 +2206,73 @@ pub extern "system" fn Java_com_centered_demo_CenteredActivity_nativeOnTextInput
 
     // Queue the text input for processing on the main thread
     if let Ok(mut queue) = PENDING_TEXT_INPUT.lock() {
-        queue.push(text_str);
+        queue.push(PlatformEvent::TextInput { text: text_str });
     }
     // Request a redraw so the main event loop processes the queued input
     REQUEST_REDRAW.store(true, Ordering::SeqCst);
 }
 
+/// Byte offset of UTF-16 offset `offset` in `text`, clamped to its length
+fn utf16_to_byte_offset(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units >= offset {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Called from Kotlin when the IME's composing (marked) text changes.
+/// `cursor_start`/`cursor_end` are the selection within `text` in UTF-16
+/// units, or -1 when there is none. An empty `text` ends composition.
+#[no_mangle]
+pub extern "system" fn Java_com_centered_demo_CenteredActivity_nativeOnImePreedit(
+    mut env: JNIEnv,
+    _class: JClass,
+    text: jni::objects::JString,
+    cursor_start: jni::sys::jint,
+    cursor_end: jni::sys::jint,
+) {
+    let text_str: String = match env.get_string(&text) {
+        Ok(s) => s.into(),
+        Err(_) => return,
+    };
+    let cursor = (cursor_start >= 0 && cursor_end >= 0).then(|| {
+        (
+            utf16_to_byte_offset(&text_str, cursor_start as usize),
+            utf16_to_byte_offset(&text_str, cursor_end as usize),
+        )
+    });
+
+    if let Ok(mut queue) = PENDING_TEXT_INPUT.lock() {
+        queue.push(PlatformEvent::ImePreedit { text: text_str, cursor });
+    }
+    REQUEST_REDRAW.store(true, Ordering::SeqCst);
+}
+
+/// Called from Kotlin when the IME commits its composing text
+#[no_mangle]
+pub extern "system" fn Java_com_centered_demo_CenteredActivity_nativeOnImeCommit(
+    mut env: JNIEnv,
+    _class: JClass,
+    text: jni::objects::JString,
+) {
+    let text_str: String = match env.get_string(&text) {
+        Ok(s) => s.into(),
+        Err(_) => return,
+    };
+    if text_str.is_empty() {
+        return;
+    }
+
+    if let Ok(mut queue) = PENDING_TEXT_INPUT.lock() {
+        queue.push(PlatformEvent::ImeCommit { text: text_str });
+    }
+    REQUEST_REDRAW.store(true, Ordering::SeqCst);
+}
+
 /// Called from Kotlin when a special key is pressed (backspace, enter, etc.)
 /// This is called from the Android UI thread, so we queue the event for
 /// processing on the main Rust thread.
@@ -2100,3 +2320,33 @@ pub extern "system" fn Java_com_centered_demo_CenteredActivity_nativeOnKeyboardH
     // Request a redraw so the main event loop processes the queued event
     REQUEST_REDRAW.store(true, Ordering::SeqCst);
 }
+
+/// Called from Kotlin's onActivityResult when a document picker closes, with
+/// the picked content URIs, or null if it was cancelled. This is called from
+/// the Android UI thread, so the result is queued for the main Rust thread.
+#[no_mangle]
+pub extern "system" fn Java_com_centered_demo_CenteredActivity_nativeOnFileDialogResult(
+    mut env: JNIEnv,
+    _class: JClass,
+    request_id: jni::sys::jint,
+    uris: jni::objects::JObjectArray,
+) {
+    let uris = if uris.is_null() {
+        None
+    } else {
+        let count = env.get_array_length(&uris).unwrap_or(0);
+        let uris: Vec<String> = (0..count)
+            .filter_map(|i| {
+                let uri = jni::objects::JString::from(env.get_object_array_element(&uris, i).ok()?);
+                env.get_string(&uri).ok().map(String::from)
+            })
+            .collect();
+        Some(uris)
+    };
+    let _ = env.exception_clear();
+
+    if let Ok(mut queue) = PENDING_FILE_DIALOG_RESULTS.lock() {
+        queue.push((request_id as u32, uris));
+    }
+    REQUEST_REDRAW.store(true, Ordering::SeqCst);
+}
@@ -0,0 +1,114 @@
+//! Window aspect ratio lock
+//!
+//! winit has no aspect ratio resize constraint, so the lock is enforced by
+//! correcting the size after each interactive resize: the window snaps to
+//! the nearest size with the locked ratio that also respects its min/max
+//! size. Sizes are physical pixels.
+
+/// Locked width:height ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub num: u32,
+    pub den: u32,
+}
+
+/// Min/max inner size of the window, in physical pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeLimits {
+    pub min: Option<(u32, u32)>,
+    pub max: Option<(u32, u32)>,
+}
+
+impl AspectRatio {
+    /// Parse an FFI ratio: `0:0` unlocks (Ok(None)), a single zero term is an error
+    pub fn from_ffi(num: u32, den: u32) -> Result<Option<Self>, String> {
+        match (num, den) {
+            (0, 0) => Ok(None),
+            (0, _) | (_, 0) => Err(format!("Invalid aspect ratio {}:{}", num, den)),
+            _ => Ok(Some(Self { num, den })),
+        }
+    }
+
+    fn ratio(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Nearest size with this ratio to `(width, height)`, within `limits`.
+    ///
+    /// "Nearest" is the closest point on the line of sizes with this ratio,
+    /// so both dimensions move part of the way rather than one absorbing
+    /// the whole correction. If the min/max size leaves no room for the
+    /// ratio (say a 16:9 lock with a max height that forces a narrower
+    /// window than the min width) the limits take precedence, as the window
+    /// system enforces them anyway, and the size is only clamped.
+    pub fn constrain(&self, width: u32, height: u32, limits: SizeLimits) -> (u32, u32) {
+        let ratio = self.ratio();
+        // Project (width, height) onto the direction (ratio, 1)
+        let t = (width as f64 * ratio + height as f64) / (ratio * ratio + 1.0);
+        let (mut w, mut h) = (t * ratio, t);
+
+        // Grow to the min size, then shrink to the max size, keeping the ratio
+        if let Some((min_w, min_h)) = limits.min {
+            let grow = (min_w as f64 / w).max(min_h as f64 / h);
+            if grow > 1.0 {
+                w *= grow;
+                h *= grow;
+            }
+        }
+        if let Some((max_w, max_h)) = limits.max {
+            let shrink = (max_w as f64 / w).min(max_h as f64 / h);
+            if shrink < 1.0 {
+                w *= shrink;
+                h *= shrink;
+            }
+        }
+
+        let (mut w, mut h) = ((w.round() as u32).max(1), (h.round() as u32).max(1));
+        if let Some((min_w, min_h)) = limits.min {
+            w = w.max(min_w);
+            h = h.max(min_h);
+        }
+        if let Some((max_w, max_h)) = limits.max {
+            w = w.min(max_w);
+            h = h.min(max_h);
+        }
+        (w, h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDESCREEN: AspectRatio = AspectRatio { num: 16, den: 9 };
+
+    #[test]
+    fn test_off_ratio_resize_snaps_to_16_9() {
+        let none = SizeLimits::default();
+
+        // Too tall: the height shrinks and the width grows a little
+        assert_eq!(WIDESCREEN.constrain(1600, 1000, none), (1643, 924));
+        // Too wide
+        assert_eq!(WIDESCREEN.constrain(2000, 900, none), (1904, 1071));
+        // Already on ratio
+        assert_eq!(WIDESCREEN.constrain(1920, 1080, none), (1920, 1080));
+    }
+
+    #[test]
+    fn test_ratio_respects_min_max_and_yields_on_conflict() {
+        let limits = SizeLimits { min: Some((800, 400)), max: Some((1920, 1080)) };
+        assert_eq!(WIDESCREEN.constrain(400, 300, limits), (800, 450));
+        assert_eq!(WIDESCREEN.constrain(4000, 2250, limits), (1920, 1080));
+
+        // A max height of 300 and min width of 800 can't both hold at 16:9
+        let conflict = SizeLimits { min: Some((800, 200)), max: Some((2000, 300)) };
+        assert_eq!(WIDESCREEN.constrain(1000, 600, conflict), (800, 300));
+    }
+
+    #[test]
+    fn test_aspect_ratio_from_ffi() {
+        assert_eq!(AspectRatio::from_ffi(0, 0), Ok(None));
+        assert_eq!(AspectRatio::from_ffi(16, 9), Ok(Some(WIDESCREEN)));
+        assert!(AspectRatio::from_ffi(16, 0).is_err());
+    }
+}
@@ -82,6 +82,14 @@ pub enum PlatformEvent {
     KeyReleased { keycode: u32, modifiers: u32 },
     /// Text input
     TextInput { text: String },
+    /// IME composition (marked text) changed; empty text ends composition.
+    /// Cursor is a byte range into the text, or None when hidden.
+    ImePreedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// IME composition committed as final text
+    ImeCommit { text: String },
     /// Application suspended (mobile)
     Suspended,
     /// Application resumed (mobile)
@@ -110,7 +118,7 @@ pub struct EventResponse {
 }
 
 /// Safe area insets (for notched devices, status bars, etc.)
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct SafeAreaInsets {
     pub top: f64,
     pub left: f64,
@@ -118,6 +126,12 @@ pub struct SafeAreaInsets {
     pub right: f64,
 }
 
+impl SafeAreaInsets {
+    pub fn is_zero(&self) -> bool {
+        self.top == 0.0 && self.left == 0.0 && self.bottom == 0.0 && self.right == 0.0
+    }
+}
+
 /// Callback type for event handling
 pub type EventCallback = Box<dyn FnMut(PlatformEvent) -> EventResponse>;
 
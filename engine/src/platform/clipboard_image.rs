@@ -0,0 +1,434 @@
+//! Images on the system clipboard
+//!
+//! Reads and writes straight-alpha RGBA8 pixels:
+//! - macOS: NSPasteboard PNG (`public.png`), reading TIFF as a fallback
+//! - iOS: UIPasteboard `image`
+//! - Windows: the registered "PNG" format, with `CF_DIB` as a fallback and
+//!   written alongside it for apps that only understand bitmaps
+//! - Linux: arboard (X11 + Wayland)
+//!
+//! Encoded images are decoded with `LoadedImage::from_bytes`.
+
+use crate::image::LoadedImage;
+
+/// Why a clipboard image couldn't be read or written
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardImageError {
+    /// The clipboard holds no image
+    NoImage,
+    /// No image clipboard on this platform
+    Unsupported,
+    /// The platform call failed or the image couldn't be decoded/encoded
+    Failed(String),
+}
+
+impl std::fmt::Display for ClipboardImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardImageError::NoImage => write!(f, "Clipboard holds no image"),
+            ClipboardImageError::Unsupported => write!(f, "Clipboard images are not supported on this platform"),
+            ClipboardImageError::Failed(e) => write!(f, "Clipboard image failed: {}", e),
+        }
+    }
+}
+
+/// Read the clipboard's image as RGBA pixels
+pub fn get_image() -> Result<LoadedImage, ClipboardImageError> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::get_image()
+    }
+
+    #[cfg(target_os = "ios")]
+    {
+        ios::get_image()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::get_image()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut clipboard = crate::platform::linux::LinuxClipboard::new()
+            .map_err(|e| ClipboardImageError::Failed(e.to_string()))?;
+        let (data, width, height) = clipboard.get_image().ok_or(ClipboardImageError::NoImage)?;
+        Ok(LoadedImage { width, height, data, premultiplied: false, frames: Vec::new(), loop_count: 0 })
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "linux")))]
+    {
+        Err(ClipboardImageError::Unsupported)
+    }
+}
+
+/// Replace the clipboard contents with an image
+///
+/// `rgba` must hold `width * height * 4` bytes of straight-alpha pixels.
+pub fn set_image(rgba: &[u8], width: u32, height: u32) -> Result<(), ClipboardImageError> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return Err(ClipboardImageError::Failed(format!(
+            "{} bytes is not a {}x{} RGBA image",
+            rgba.len(),
+            width,
+            height
+        )));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos::set_image(&encode_png(rgba, width, height)?)
+    }
+
+    #[cfg(target_os = "ios")]
+    {
+        ios::set_image(&encode_png(rgba, width, height)?)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::set_image(&encode_png(rgba, width, height)?, &rgba_to_dib(rgba, width, height))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut clipboard = crate::platform::linux::LinuxClipboard::new()
+            .map_err(|e| ClipboardImageError::Failed(e.to_string()))?;
+        if clipboard.set_image(rgba, width, height) {
+            Ok(())
+        } else {
+            Err(ClipboardImageError::Failed("arboard rejected the image".to_string()))
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "linux")))]
+    {
+        Err(ClipboardImageError::Unsupported)
+    }
+}
+
+/// Decode PNG/JPEG/GIF/BMP bytes taken off the clipboard
+#[allow(dead_code)]
+fn decode(bytes: &[u8]) -> Result<LoadedImage, ClipboardImageError> {
+    let mut image = LoadedImage::from_bytes(bytes).map_err(|e| ClipboardImageError::Failed(e.to_string()))?;
+    // Only the still image is pasted
+    image.frames.clear();
+    image.loop_count = 0;
+    Ok(image)
+}
+
+/// Encode RGBA pixels as PNG
+#[allow(dead_code)]
+fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, ClipboardImageError> {
+    use image::ImageEncoder;
+
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(rgba, width, height, image::ExtendedColorType::Rgba8)
+        .map_err(|e| ClipboardImageError::Failed(e.to_string()))?;
+    Ok(png)
+}
+
+/// Size of a BITMAPFILEHEADER
+const BMP_FILE_HEADER_LEN: usize = 14;
+/// Size of a BITMAPINFOHEADER
+const BMP_INFO_HEADER_LEN: usize = 40;
+/// biCompression value for three color masks after a BITMAPINFOHEADER
+const BI_BITFIELDS: u32 = 3;
+
+/// Decode a `CF_DIB` (a BMP file without its file header) by putting the
+/// file header back and handing it to the BMP decoder
+#[allow(dead_code)]
+fn decode_dib(dib: &[u8]) -> Result<LoadedImage, ClipboardImageError> {
+    let read_u32 = |at: usize| dib.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let read_u16 = |at: usize| dib.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let invalid = || ClipboardImageError::Failed("Malformed CF_DIB".to_string());
+
+    let header_len = read_u32(0).ok_or_else(invalid)? as usize;
+    let bit_count = read_u16(14).ok_or_else(invalid)?;
+    let compression = read_u32(16).ok_or_else(invalid)?;
+    let colors_used = read_u32(32).ok_or_else(invalid)? as usize;
+
+    // Pixels start after the header, the color masks of a bare
+    // BITMAPINFOHEADER with BI_BITFIELDS, and the palette
+    let masks_len = if header_len == BMP_INFO_HEADER_LEN && compression == BI_BITFIELDS { 12 } else { 0 };
+    let palette_len = match (colors_used, bit_count) {
+        (0, 1..=8) => (1usize << bit_count) * 4,
+        (n, _) => n * 4,
+    };
+    let pixels_offset = BMP_FILE_HEADER_LEN + header_len + masks_len + palette_len;
+
+    let mut bmp = Vec::with_capacity(BMP_FILE_HEADER_LEN + dib.len());
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&((BMP_FILE_HEADER_LEN + dib.len()) as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&(pixels_offset as u32).to_le_bytes());
+    bmp.extend_from_slice(dib);
+    decode(&bmp)
+}
+
+/// Encode RGBA pixels as a 32-bit bottom-up `CF_DIB`
+#[allow(dead_code)]
+fn rgba_to_dib(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let row_len = width as usize * 4;
+    let mut dib = Vec::with_capacity(BMP_INFO_HEADER_LEN + rgba.len());
+    dib.extend_from_slice(&(BMP_INFO_HEADER_LEN as u32).to_le_bytes());
+    dib.extend_from_slice(&(width as i32).to_le_bytes());
+    // Positive height: rows bottom to top, which every reader understands
+    dib.extend_from_slice(&(height as i32).to_le_bytes());
+    dib.extend_from_slice(&1u16.to_le_bytes()); // planes
+    dib.extend_from_slice(&32u16.to_le_bytes()); // bit count
+    dib.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB
+    dib.extend_from_slice(&(rgba.len() as u32).to_le_bytes());
+    dib.extend_from_slice(&[0; 16]); // resolution and palette counts
+    for row in rgba.chunks_exact(row_len).rev() {
+        for px in row.chunks_exact(4) {
+            dib.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+        }
+    }
+    dib
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{decode, ClipboardImageError};
+    use crate::image::LoadedImage;
+    use cocoa::appkit::NSPasteboard;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    // NSBitmapImageFileTypePNG
+    const NS_BITMAP_IMAGE_FILE_TYPE_PNG: usize = 4;
+
+    /// Copy the bytes out of an NSData
+    unsafe fn data_bytes(data: id) -> Vec<u8> {
+        let len: usize = msg_send![data, length];
+        let bytes: *const u8 = msg_send![data, bytes];
+        if bytes.is_null() || len == 0 {
+            return Vec::new();
+        }
+        std::slice::from_raw_parts(bytes, len).to_vec()
+    }
+
+    pub fn get_image() -> Result<LoadedImage, ClipboardImageError> {
+        unsafe {
+            let pasteboard: id = NSPasteboard::generalPasteboard(nil);
+            let png_type = NSString::alloc(nil).init_str("public.png");
+            let data: id = msg_send![pasteboard, dataForType: png_type];
+            let _: () = msg_send![png_type, release];
+            if data != nil {
+                return decode(&data_bytes(data));
+            }
+
+            // Screenshots and most apps also (or only) put TIFF on the
+            // pasteboard; convert it to PNG for the decoder
+            let tiff_type = NSString::alloc(nil).init_str("public.tiff");
+            let tiff: id = msg_send![pasteboard, dataForType: tiff_type];
+            let _: () = msg_send![tiff_type, release];
+            if tiff == nil {
+                return Err(ClipboardImageError::NoImage);
+            }
+            let rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+            if rep == nil {
+                return Err(ClipboardImageError::Failed("Unreadable TIFF on the pasteboard".to_string()));
+            }
+            let properties: id = msg_send![class!(NSDictionary), dictionary];
+            let png: id = msg_send![rep, representationUsingType: NS_BITMAP_IMAGE_FILE_TYPE_PNG properties: properties];
+            if png == nil {
+                return Err(ClipboardImageError::Failed("Could not convert TIFF to PNG".to_string()));
+            }
+            decode(&data_bytes(png))
+        }
+    }
+
+    pub fn set_image(png: &[u8]) -> Result<(), ClipboardImageError> {
+        unsafe {
+            let pasteboard: id = NSPasteboard::generalPasteboard(nil);
+            let _: () = msg_send![pasteboard, clearContents];
+            let data: id = msg_send![class!(NSData), dataWithBytes: png.as_ptr() length: png.len()];
+            let png_type = NSString::alloc(nil).init_str("public.png");
+            let ok: bool = msg_send![pasteboard, setData: data forType: png_type];
+            let _: () = msg_send![png_type, release];
+            if ok {
+                Ok(())
+            } else {
+                Err(ClipboardImageError::Failed("NSPasteboard refused the image".to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "ios")]
+mod ios {
+    use super::{decode, ClipboardImageError};
+    use crate::image::LoadedImage;
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "UIKit", kind = "framework")]
+    extern "C" {
+        fn UIImagePNGRepresentation(image: *mut Object) -> *mut Object;
+    }
+
+    pub fn get_image() -> Result<LoadedImage, ClipboardImageError> {
+        unsafe {
+            let pasteboard: *mut Object = msg_send![class!(UIPasteboard), generalPasteboard];
+            let image: *mut Object = msg_send![pasteboard, image];
+            if image.is_null() {
+                return Err(ClipboardImageError::NoImage);
+            }
+            let png = UIImagePNGRepresentation(image);
+            if png.is_null() {
+                return Err(ClipboardImageError::Failed("Could not encode the pasteboard image".to_string()));
+            }
+            let len: usize = msg_send![png, length];
+            let bytes: *const u8 = msg_send![png, bytes];
+            if bytes.is_null() || len == 0 {
+                return Err(ClipboardImageError::NoImage);
+            }
+            decode(std::slice::from_raw_parts(bytes, len))
+        }
+    }
+
+    pub fn set_image(png: &[u8]) -> Result<(), ClipboardImageError> {
+        unsafe {
+            let data: *mut Object = msg_send![class!(NSData), dataWithBytes: png.as_ptr() length: png.len()];
+            let image: *mut Object = msg_send![class!(UIImage), imageWithData: data];
+            if image.is_null() {
+                return Err(ClipboardImageError::Failed("UIImage rejected the PNG".to_string()));
+            }
+            let pasteboard: *mut Object = msg_send![class!(UIPasteboard), generalPasteboard];
+            let _: () = msg_send![pasteboard, setImage: image];
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use super::{decode, decode_dib, ClipboardImageError};
+    use crate::image::LoadedImage;
+    use windows::core::w;
+    use windows::Win32::Foundation::{HANDLE, HGLOBAL};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+        RegisterClipboardFormatW, SetClipboardData,
+    };
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE};
+
+    // CF_DIB = 8
+    const CF_DIB: u32 = 8;
+
+    /// The clipboard, closed again when dropped
+    struct OpenedClipboard;
+
+    impl OpenedClipboard {
+        fn open() -> Result<Self, ClipboardImageError> {
+            unsafe { OpenClipboard(None) }
+                .map(|_| OpenedClipboard)
+                .map_err(|e| ClipboardImageError::Failed(e.to_string()))
+        }
+    }
+
+    impl Drop for OpenedClipboard {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseClipboard() };
+        }
+    }
+
+    /// Copy the contents of a clipboard format out of its global memory
+    unsafe fn read_format(format: u32) -> Option<Vec<u8>> {
+        IsClipboardFormatAvailable(format).ok()?;
+        let handle = GetClipboardData(format).ok()?;
+        let hglobal = HGLOBAL(handle.0);
+        let data = GlobalLock(hglobal);
+        if data.is_null() {
+            return None;
+        }
+        let bytes = std::slice::from_raw_parts(data as *const u8, GlobalSize(hglobal)).to_vec();
+        let _ = GlobalUnlock(hglobal);
+        Some(bytes)
+    }
+
+    /// Hand a copy of `bytes` to the clipboard under `format`
+    unsafe fn write_format(format: u32, bytes: &[u8]) -> Result<(), ClipboardImageError> {
+        let failed = |e: windows::core::Error| ClipboardImageError::Failed(e.to_string());
+        let hmem = GlobalAlloc(GMEM_MOVEABLE, bytes.len()).map_err(failed)?;
+        let dest = GlobalLock(hmem);
+        if dest.is_null() {
+            let _ = GlobalFree(hmem);
+            return Err(ClipboardImageError::Failed("GlobalLock failed".to_string()));
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), dest as *mut u8, bytes.len());
+        let _ = GlobalUnlock(hmem);
+        // The clipboard owns the memory once this succeeds
+        if let Err(e) = SetClipboardData(format, HANDLE(hmem.0)) {
+            let _ = GlobalFree(hmem);
+            return Err(failed(e));
+        }
+        Ok(())
+    }
+
+    pub fn get_image() -> Result<LoadedImage, ClipboardImageError> {
+        let _clipboard = OpenedClipboard::open()?;
+        unsafe {
+            // PNG keeps alpha; browsers and Office put it next to CF_DIB
+            let png_format = RegisterClipboardFormatW(w!("PNG"));
+            if let Some(png) = read_format(png_format) {
+                return decode(&png);
+            }
+            match read_format(CF_DIB) {
+                Some(dib) => decode_dib(&dib),
+                None => Err(ClipboardImageError::NoImage),
+            }
+        }
+    }
+
+    pub fn set_image(png: &[u8], dib: &[u8]) -> Result<(), ClipboardImageError> {
+        let _clipboard = OpenedClipboard::open()?;
+        unsafe {
+            EmptyClipboard().map_err(|e| ClipboardImageError::Failed(e.to_string()))?;
+            write_format(RegisterClipboardFormatW(w!("PNG")), png)?;
+            write_format(CF_DIB, dib)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Vec<u8> {
+        // 2x2: red, green / blue, half-transparent white
+        vec![255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 128]
+    }
+
+    #[test]
+    fn test_png_round_trip_keeps_alpha() {
+        let png = encode_png(&checkerboard(), 2, 2).unwrap();
+        let image = decode(&png).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.data, checkerboard());
+    }
+
+    #[test]
+    fn test_dib_round_trip_flips_rows_back() {
+        let dib = rgba_to_dib(&checkerboard(), 2, 2);
+        assert_eq!(dib.len(), BMP_INFO_HEADER_LEN + 16);
+        // Bottom row first, BGRA
+        assert_eq!(&dib[BMP_INFO_HEADER_LEN..BMP_INFO_HEADER_LEN + 4], &[255, 0, 0, 255]);
+
+        let image = decode_dib(&dib).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        // 32-bit BI_RGB has no alpha channel, so everything comes back opaque
+        let opaque: Vec<u8> = checkerboard().chunks(4).flat_map(|p| [p[0], p[1], p[2], 255]).collect();
+        assert_eq!(image.data, opaque);
+    }
+
+    #[test]
+    fn test_set_image_rejects_wrong_length() {
+        assert!(matches!(set_image(&[0; 12], 2, 2), Err(ClipboardImageError::Failed(_))));
+    }
+}
@@ -0,0 +1,260 @@
+//! Cursor shapes and custom cursor images
+//!
+//! Standard shapes are numbered for the FFI by [`CursorKind`]. Cursor
+//! bitmaps are supplied at 1x (logical pixels). Window systems that show
+//! them at their pixel size (Windows, X11) get them rescaled for the scale
+//! factor of the monitor the window is on, so they keep the same physical
+//! size across displays with different DPI; the others (macOS, Wayland, the
+//! web) treat the size as logical points and scale them themselves.
+
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use raw_window_handle::RawWindowHandle;
+
+/// Standard system cursor shape. The FFI value is the position in this list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CursorKind {
+    #[default]
+    Default,
+    /// Pointing hand, for links and buttons
+    Pointer,
+    /// I-beam, for editable or selectable text
+    Text,
+    /// Open hand, for something that can be dragged
+    Grab,
+    /// Closed hand, while dragging
+    Grabbing,
+    NotAllowed,
+    Crosshair,
+    Move,
+    Wait,
+    /// Busy, but still accepting input
+    Progress,
+    Help,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+    ZoomIn,
+    ZoomOut,
+}
+
+impl CursorKind {
+    const ALL: [CursorKind; 19] = [
+        CursorKind::Default,
+        CursorKind::Pointer,
+        CursorKind::Text,
+        CursorKind::Grab,
+        CursorKind::Grabbing,
+        CursorKind::NotAllowed,
+        CursorKind::Crosshair,
+        CursorKind::Move,
+        CursorKind::Wait,
+        CursorKind::Progress,
+        CursorKind::Help,
+        CursorKind::EwResize,
+        CursorKind::NsResize,
+        CursorKind::NeswResize,
+        CursorKind::NwseResize,
+        CursorKind::ColResize,
+        CursorKind::RowResize,
+        CursorKind::ZoomIn,
+        CursorKind::ZoomOut,
+    ];
+
+    /// Look up a cursor by its FFI value; None if out of range
+    pub fn from_ffi(value: u32) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// The matching winit cursor icon
+    #[cfg(feature = "winit")]
+    pub fn to_winit(self) -> winit::window::CursorIcon {
+        use winit::window::CursorIcon;
+        match self {
+            CursorKind::Default => CursorIcon::Default,
+            CursorKind::Pointer => CursorIcon::Pointer,
+            CursorKind::Text => CursorIcon::Text,
+            CursorKind::Grab => CursorIcon::Grab,
+            CursorKind::Grabbing => CursorIcon::Grabbing,
+            CursorKind::NotAllowed => CursorIcon::NotAllowed,
+            CursorKind::Crosshair => CursorIcon::Crosshair,
+            CursorKind::Move => CursorIcon::Move,
+            CursorKind::Wait => CursorIcon::Wait,
+            CursorKind::Progress => CursorIcon::Progress,
+            CursorKind::Help => CursorIcon::Help,
+            CursorKind::EwResize => CursorIcon::EwResize,
+            CursorKind::NsResize => CursorIcon::NsResize,
+            CursorKind::NeswResize => CursorIcon::NeswResize,
+            CursorKind::NwseResize => CursorIcon::NwseResize,
+            CursorKind::ColResize => CursorIcon::ColResize,
+            CursorKind::RowResize => CursorIcon::RowResize,
+            CursorKind::ZoomIn => CursorIcon::ZoomIn,
+            CursorKind::ZoomOut => CursorIcon::ZoomOut,
+        }
+    }
+}
+
+/// Units a window system reads a custom cursor bitmap's size in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorUnits {
+    /// Physical pixels: the bitmap is shown at its pixel size (Windows, X11)
+    Physical,
+    /// Logical points: the system scales the bitmap itself (macOS, Wayland, web)
+    Logical,
+}
+
+impl CursorUnits {
+    /// The units of the window system a window handle belongs to
+    pub fn for_window(handle: &RawWindowHandle) -> Self {
+        match handle {
+            RawWindowHandle::Win32(_) | RawWindowHandle::WinRt(_) | RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_) => {
+                CursorUnits::Physical
+            }
+            _ => CursorUnits::Logical,
+        }
+    }
+}
+
+/// Custom cursor bitmap with its hotspot, in logical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorImage {
+    /// RGBA pixel data (4 bytes per pixel, unpremultiplied)
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+}
+
+impl CursorImage {
+    /// Create a cursor image, validating the buffer size and hotspot
+    pub fn new(rgba: Vec<u8>, width: u32, height: u32, hotspot_x: u32, hotspot_y: u32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err("Cursor image must not be empty".to_string());
+        }
+        if rgba.len() != (width as usize) * (height as usize) * 4 {
+            return Err(format!(
+                "Cursor image data is {} bytes, expected {} for {}x{} RGBA",
+                rgba.len(),
+                width * height * 4,
+                width,
+                height
+            ));
+        }
+        if hotspot_x >= width || hotspot_y >= height {
+            return Err("Cursor hotspot must be inside the image".to_string());
+        }
+
+        Ok(Self { rgba, width, height, hotspot_x, hotspot_y })
+    }
+
+    /// Return this cursor resampled for a monitor scale factor.
+    ///
+    /// A scale factor of 2.0 doubles the bitmap size and hotspot. Scale
+    /// factors at (or very near) 1.0 return an unchanged copy.
+    pub fn scaled(&self, scale_factor: f64) -> CursorImage {
+        if (scale_factor - 1.0).abs() < 0.01 || scale_factor <= 0.0 {
+            return self.clone();
+        }
+
+        let width = ((self.width as f64 * scale_factor).round() as u32).max(1);
+        let height = ((self.height as f64 * scale_factor).round() as u32).max(1);
+        let hotspot_x = ((self.hotspot_x as f64 * scale_factor).round() as u32).min(width - 1);
+        let hotspot_y = ((self.hotspot_y as f64 * scale_factor).round() as u32).min(height - 1);
+
+        let rgba = match RgbaImage::from_raw(self.width, self.height, self.rgba.clone()) {
+            Some(source) => imageops::resize(&source, width, height, FilterType::Triangle).into_raw(),
+            None => return self.clone(),
+        };
+
+        CursorImage { rgba, width, height, hotspot_x, hotspot_y }
+    }
+
+    /// This cursor as a window system in `units` takes it: resampled for
+    /// the scale factor when it reads physical pixels, unchanged otherwise
+    pub fn for_window_system(&self, units: CursorUnits, scale_factor: f64) -> CursorImage {
+        match units {
+            CursorUnits::Physical => self.scaled(scale_factor),
+            CursorUnits::Logical => self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker(width: u32, height: u32) -> CursorImage {
+        let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+        for i in 0..(width * height) {
+            let v = if i % 2 == 0 { 255 } else { 0 };
+            rgba.extend_from_slice(&[v, v, v, 255]);
+        }
+        CursorImage::new(rgba, width, height, 3, 5).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_kind_ffi_values() {
+        assert_eq!(CursorKind::from_ffi(0), Some(CursorKind::Default));
+        assert_eq!(CursorKind::from_ffi(1), Some(CursorKind::Pointer));
+        assert_eq!(CursorKind::from_ffi(2), Some(CursorKind::Text));
+        assert_eq!(CursorKind::from_ffi(18), Some(CursorKind::ZoomOut));
+        assert_eq!(CursorKind::from_ffi(19), None);
+        assert_eq!(CursorKind::from_ffi(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_cursor_rescaled_for_scale_factor() {
+        let cursor = checker(16, 16);
+
+        let at_1x = cursor.scaled(1.0);
+        assert_eq!((at_1x.width, at_1x.height), (16, 16));
+
+        let at_2x = cursor.scaled(2.0);
+        assert_eq!((at_2x.width, at_2x.height), (32, 32));
+        assert_eq!((at_2x.hotspot_x, at_2x.hotspot_y), (6, 10));
+        assert_eq!(at_2x.rgba.len(), 32 * 32 * 4);
+    }
+
+    #[test]
+    fn test_cursor_resampled_only_for_physical_pixel_systems() {
+        use raw_window_handle::{
+            AppKitWindowHandle, WaylandWindowHandle, WebWindowHandle, Win32WindowHandle, XcbWindowHandle,
+            XlibWindowHandle,
+        };
+        use std::num::{NonZeroIsize, NonZeroU32};
+        use std::ptr::NonNull;
+
+        let view = NonNull::<u8>::dangling().cast();
+        let physical = [
+            RawWindowHandle::Win32(Win32WindowHandle::new(NonZeroIsize::new(1).unwrap())),
+            RawWindowHandle::Xlib(XlibWindowHandle::new(1)),
+            RawWindowHandle::Xcb(XcbWindowHandle::new(NonZeroU32::new(1).unwrap())),
+        ];
+        let logical = [
+            RawWindowHandle::AppKit(AppKitWindowHandle::new(view)),
+            RawWindowHandle::Wayland(WaylandWindowHandle::new(view)),
+            RawWindowHandle::Web(WebWindowHandle::new(1)),
+        ];
+        assert!(physical.iter().all(|h| CursorUnits::for_window(h) == CursorUnits::Physical));
+        assert!(logical.iter().all(|h| CursorUnits::for_window(h) == CursorUnits::Logical));
+
+        // At 2x, Windows and X11 get a 2x bitmap; macOS and Wayland would
+        // double a 2x bitmap again, so they get the 1x one
+        let cursor = checker(16, 16);
+        let shown = cursor.for_window_system(CursorUnits::Physical, 2.0);
+        assert_eq!((shown.width, shown.height), (32, 32));
+        assert_eq!(cursor.for_window_system(CursorUnits::Logical, 2.0), cursor);
+    }
+
+    #[test]
+    fn test_cursor_image_validation() {
+        assert!(CursorImage::new(vec![0; 16], 2, 2, 0, 0).is_ok());
+        assert!(CursorImage::new(vec![0; 15], 2, 2, 0, 0).is_err());
+        assert!(CursorImage::new(vec![0; 16], 2, 2, 2, 0).is_err());
+        assert!(CursorImage::new(vec![], 0, 0, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,544 @@
+//! Native drag-and-drop out of the window
+//!
+//! Lets the app start a platform drag session carrying files or text so users
+//! can drag content into other applications:
+//! - macOS: NSDraggingSession (`beginDraggingSessionWithItems:event:source:`)
+//! - Windows: `SHDoDragDrop` with a shell-provided IDataObject
+//! - Linux (X11): a GTK drag source (`gtk_drag_begin_with_coordinates`)
+//!   offering `text/uri-list` for files and the text targets for text. The
+//!   drag runs on the event loop, which keeps dispatching GTK events until
+//!   it ends, and is cancelled if it's still running after a minute.
+//! - Linux (Wayland): not supported (`begin_drag` fails and the callback
+//!   gets `DragResult::Failed`); a Wayland drag must start from the client
+//!   that owns the surface under the pointer, which is winit's, and winit
+//!   has no drag source
+//!
+//! The session result is reported asynchronously through the callback set
+//! with `set_drag_callback`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Content carried by an outgoing drag
+///
+/// JSON form: `{"type":"files","paths":["/a.txt"]}` or `{"type":"text","text":"hi"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DragPayload {
+    /// One or more absolute file paths
+    Files { paths: Vec<String> },
+    /// Plain UTF-8 text
+    Text { text: String },
+}
+
+impl DragPayload {
+    /// Parse and validate a payload from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let payload: DragPayload = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if let DragPayload::Files { paths } = &payload {
+            if paths.is_empty() {
+                return Err("Drag payload has no files".to_string());
+            }
+        }
+        Ok(payload)
+    }
+}
+
+/// Outcome of a drag session, passed to the drag callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DragResult {
+    /// The session could not be started
+    Failed = -1,
+    /// Dropped nowhere or cancelled by the user
+    Cancelled = 0,
+    /// Target copied the content
+    Copied = 1,
+    /// Target moved the content
+    Moved = 2,
+    /// Target created a link/alias
+    Linked = 3,
+}
+
+/// Callback receiving a `DragResult` as i32
+static DRAG_CALLBACK: Mutex<Option<extern "C" fn(i32)>> = Mutex::new(None);
+
+/// Set the callback invoked when a drag session ends
+pub fn set_drag_callback(callback: extern "C" fn(i32)) {
+    if let Ok(mut guard) = DRAG_CALLBACK.lock() {
+        *guard = Some(callback);
+    }
+}
+
+/// Report a drag session result to the registered callback
+pub fn notify_drag_result(result: DragResult) {
+    let callback = DRAG_CALLBACK.lock().ok().and_then(|guard| *guard);
+    if let Some(callback) = callback {
+        callback(result as i32);
+    }
+}
+
+/// Start a native drag session for the given window.
+///
+/// Must be called on the main thread, ideally while the mouse button that
+/// started the gesture is still held down. Returns an error if the session
+/// could not be started; otherwise the result arrives via the drag callback.
+pub fn begin_drag(window: &winit::window::Window, payload: &DragPayload) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::begin_drag(window, payload)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::begin_drag(window, payload)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux::begin_drag(window, payload)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (window, payload);
+        Err("Drag-out is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{notify_drag_result, DragPayload, DragResult};
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSArray, NSPoint, NSRect, NSSize, NSString};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Class, Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use std::sync::Once;
+
+    // NSDragOperation values
+    const NS_DRAG_OPERATION_COPY: usize = 1;
+    const NS_DRAG_OPERATION_LINK: usize = 2;
+    const NS_DRAG_OPERATION_MOVE: usize = 16;
+
+    static SOURCE_CLASS: Once = Once::new();
+    static mut SOURCE_CLASS_PTR: *const Class = std::ptr::null();
+
+    /// Register the NSDraggingSource class (called once)
+    fn source_class() -> &'static Class {
+        SOURCE_CLASS.call_once(|| {
+            let superclass = class!(NSObject);
+            let mut decl = ClassDecl::new("CenteredDraggingSource", superclass)
+                .expect("Failed to create dragging source class");
+
+            unsafe {
+                decl.add_method(
+                    sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+                    source_operation_mask as extern "C" fn(&Object, Sel, id, isize) -> usize,
+                );
+                decl.add_method(
+                    sel!(draggingSession:endedAtPoint:operation:),
+                    session_ended as extern "C" fn(&Object, Sel, id, NSPoint, usize),
+                );
+            }
+
+            let cls = decl.register();
+            unsafe {
+                SOURCE_CLASS_PTR = cls;
+            }
+        });
+
+        unsafe { &*SOURCE_CLASS_PTR }
+    }
+
+    extern "C" fn source_operation_mask(_this: &Object, _sel: Sel, _session: id, _context: isize) -> usize {
+        NS_DRAG_OPERATION_COPY | NS_DRAG_OPERATION_MOVE | NS_DRAG_OPERATION_LINK
+    }
+
+    extern "C" fn session_ended(this: &Object, _sel: Sel, _session: id, _point: NSPoint, operation: usize) {
+        let result = if operation & NS_DRAG_OPERATION_MOVE != 0 {
+            DragResult::Moved
+        } else if operation & NS_DRAG_OPERATION_COPY != 0 {
+            DragResult::Copied
+        } else if operation & NS_DRAG_OPERATION_LINK != 0 {
+            DragResult::Linked
+        } else {
+            DragResult::Cancelled
+        };
+        notify_drag_result(result);
+
+        // The source was retained for the session's lifetime in begin_drag
+        unsafe {
+            let _: () = msg_send![this, release];
+        }
+    }
+
+    pub fn begin_drag(window: &winit::window::Window, payload: &DragPayload) -> Result<(), String> {
+        let handle = window.window_handle().map_err(|e| e.to_string())?;
+        let ns_view = match handle.as_raw() {
+            RawWindowHandle::AppKit(h) => h.ns_view.as_ptr() as id,
+            _ => return Err("Not an AppKit window".to_string()),
+        };
+
+        unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let event: id = msg_send![app, currentEvent];
+            if event == nil {
+                return Err("A drag must start from a mouse event".to_string());
+            }
+
+            let location: NSPoint = msg_send![event, locationInWindow];
+            let origin: NSPoint = msg_send![ns_view, convertPoint: location fromView: nil];
+            let frame = NSRect::new(
+                NSPoint::new(origin.x - 16.0, origin.y - 16.0),
+                NSSize::new(32.0, 32.0),
+            );
+
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let mut items: Vec<id> = Vec::new();
+            match payload {
+                DragPayload::Files { paths } => {
+                    for path in paths {
+                        let ns_path = NSString::alloc(nil).init_str(path);
+                        let url: id = msg_send![class!(NSURL), fileURLWithPath: ns_path];
+                        let icon: id = msg_send![workspace, iconForFile: ns_path];
+                        let item: id = msg_send![class!(NSDraggingItem), alloc];
+                        let item: id = msg_send![item, initWithPasteboardWriter: url];
+                        let _: () = msg_send![item, setDraggingFrame: frame contents: icon];
+                        let _: id = msg_send![item, autorelease];
+                        items.push(item);
+                    }
+                }
+                DragPayload::Text { text } => {
+                    let ns_text = NSString::alloc(nil).init_str(text);
+                    let item: id = msg_send![class!(NSDraggingItem), alloc];
+                    let item: id = msg_send![item, initWithPasteboardWriter: ns_text];
+                    let _: () = msg_send![item, setDraggingFrame: frame contents: nil];
+                    let _: id = msg_send![item, autorelease];
+                    items.push(item);
+                }
+            }
+
+            let items_array = NSArray::arrayWithObjects(nil, &items);
+            // Released in session_ended
+            let source: id = msg_send![source_class(), new];
+            let session: id = msg_send![ns_view, beginDraggingSessionWithItems: items_array event: event source: source];
+            if session == nil {
+                let _: () = msg_send![source, release];
+                return Err("Failed to start dragging session".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use super::{notify_drag_result, DragPayload, DragResult};
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{CoTaskMemFree, IDataObject, DVASPECT_CONTENT, FORMATETC, STGMEDIUM, STGMEDIUM_0, TYMED_HGLOBAL};
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use windows::Win32::System::Ole::{CF_UNICODETEXT, DROPEFFECT_COPY, DROPEFFECT_LINK, DROPEFFECT_MOVE};
+    use windows::Win32::UI::Shell::Common::ITEMIDLIST;
+    use windows::Win32::UI::Shell::{
+        BHID_DataObject, IShellItemArray, SHCreateDataObject, SHCreateShellItemArrayFromIDLists,
+        SHDoDragDrop, SHParseDisplayName,
+    };
+
+    pub fn begin_drag(window: &winit::window::Window, payload: &DragPayload) -> Result<(), String> {
+        let handle = window.window_handle().map_err(|e| e.to_string())?;
+        let hwnd = match handle.as_raw() {
+            RawWindowHandle::Win32(h) => HWND(h.hwnd.get() as *mut std::ffi::c_void),
+            _ => return Err("Not a Win32 window".to_string()),
+        };
+
+        let data_object = match payload {
+            DragPayload::Files { paths } => files_data_object(paths)?,
+            DragPayload::Text { text } => text_data_object(text)?,
+        };
+
+        // SHDoDragDrop runs a modal drag loop and returns once the drop completes
+        let effect = unsafe {
+            SHDoDragDrop(hwnd, &data_object, None, DROPEFFECT_COPY | DROPEFFECT_MOVE | DROPEFFECT_LINK)
+                .map_err(|e| e.to_string())?
+        };
+
+        let result = if effect.contains(DROPEFFECT_MOVE) {
+            DragResult::Moved
+        } else if effect.contains(DROPEFFECT_COPY) {
+            DragResult::Copied
+        } else if effect.contains(DROPEFFECT_LINK) {
+            DragResult::Linked
+        } else {
+            DragResult::Cancelled
+        };
+        notify_drag_result(result);
+
+        Ok(())
+    }
+
+    /// Shell data object for a set of files (CF_HDROP and shell formats)
+    fn files_data_object(paths: &[String]) -> Result<IDataObject, String> {
+        let mut pidls: Vec<*mut ITEMIDLIST> = Vec::with_capacity(paths.len());
+        let result = unsafe {
+            let mut parse_error = None;
+            for path in paths {
+                let wide = HSTRING::from(path.as_str());
+                let mut pidl: *mut ITEMIDLIST = std::ptr::null_mut();
+                if let Err(e) = SHParseDisplayName(PCWSTR(wide.as_ptr()), None, &mut pidl, 0, None) {
+                    parse_error = Some(format!("{}: {}", path, e));
+                    break;
+                }
+                pidls.push(pidl);
+            }
+
+            match parse_error {
+                Some(e) => Err(e),
+                None => {
+                    let ids: Vec<*const ITEMIDLIST> = pidls.iter().map(|p| *p as *const ITEMIDLIST).collect();
+                    SHCreateShellItemArrayFromIDLists(&ids)
+                        .and_then(|items: IShellItemArray| items.BindToHandler(None, &BHID_DataObject))
+                        .map_err(|e| e.to_string())
+                }
+            }
+        };
+
+        for pidl in pidls {
+            unsafe { CoTaskMemFree(Some(pidl as *const std::ffi::c_void)) };
+        }
+
+        result
+    }
+
+    /// Data object carrying CF_UNICODETEXT
+    fn text_data_object(text: &str) -> Result<IDataObject, String> {
+        unsafe {
+            let data_object: IDataObject = SHCreateDataObject(None, None, None).map_err(|e| e.to_string())?;
+
+            let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+            let byte_len = wide.len() * std::mem::size_of::<u16>();
+            let hglobal = GlobalAlloc(GMEM_MOVEABLE, byte_len).map_err(|e| e.to_string())?;
+            let dest = GlobalLock(hglobal) as *mut u16;
+            if dest.is_null() {
+                return Err("GlobalLock failed".to_string());
+            }
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), dest, wide.len());
+            let _ = GlobalUnlock(hglobal);
+
+            let format = FORMATETC {
+                cfFormat: CF_UNICODETEXT.0,
+                ptd: std::ptr::null_mut(),
+                dwAspect: DVASPECT_CONTENT.0,
+                lindex: -1,
+                tymed: TYMED_HGLOBAL.0 as u32,
+            };
+            let medium = STGMEDIUM {
+                tymed: TYMED_HGLOBAL.0 as u32,
+                u: STGMEDIUM_0 { hGlobal: hglobal },
+                pUnkForRelease: std::mem::ManuallyDrop::new(None),
+            };
+            // fRelease = true hands ownership of the HGLOBAL to the data object
+            data_object.SetData(&format, &medium, true).map_err(|e| e.to_string())?;
+
+            Ok(data_object)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{notify_drag_result, DragPayload, DragResult};
+    use gtk::prelude::*;
+    use gtk::{gdk, glib};
+    use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+    use std::cell::{Cell, RefCell};
+    use std::os::raw::{c_int, c_ulong, c_void};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[link(name = "X11")]
+    extern "C" {
+        fn XUngrabPointer(display: *mut c_void, time: c_ulong) -> c_int;
+        fn XFlush(display: *mut c_void) -> c_int;
+    }
+
+    /// How long a drag may run before it's cancelled, so a drag whose end
+    /// GTK never sees can't stay in progress forever
+    const DRAG_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// The drag in progress: its off-screen source widget and the timeout
+    /// that cancels it
+    struct ActiveDrag {
+        source: gtk::Invisible,
+        timeout: Option<glib::SourceId>,
+    }
+
+    thread_local! {
+        static ACTIVE: RefCell<Option<ActiveDrag>> = const { RefCell::new(None) };
+    }
+
+    /// Whether a drag is running; the event loop keeps pumping GTK until it
+    /// ends
+    pub fn in_progress() -> bool {
+        ACTIVE.with(|active| active.borrow().is_some())
+    }
+
+    /// Forget the drag in progress, destroying its source widget
+    fn finish() {
+        if let Some(drag) = ACTIVE.with(|active| active.borrow_mut().take()) {
+            if let Some(timeout) = drag.timeout {
+                timeout.remove();
+            }
+            // Not from inside the widget's own signal handler
+            glib::idle_add_local_once(move || unsafe { drag.source.destroy() });
+        }
+    }
+
+    pub fn begin_drag(window: &winit::window::Window, payload: &DragPayload) -> Result<(), String> {
+        let handle = window.window_handle().map_err(|e| e.to_string())?;
+        if !matches!(handle.as_raw(), RawWindowHandle::Xlib(_)) {
+            return Err("Drag-out is only supported on X11".to_string());
+        }
+        let winit_display = match window.display_handle().map_err(|e| e.to_string())?.as_raw() {
+            RawDisplayHandle::Xlib(handle) => handle.display.ok_or("No Xlib display")?,
+            _ => return Err("Drag-out is only supported on X11".to_string()),
+        };
+        if !gtk::is_initialized_main_thread() {
+            return Err("GTK is not initialized".to_string());
+        }
+        if in_progress() {
+            return Err("A drag is already in progress".to_string());
+        }
+        // GTK has to talk to the same X server as the window
+        let display = gdk::Display::default().ok_or("No GDK display")?;
+        if !display.type_().name().starts_with("GdkX11") {
+            return Err("GTK is not running on X11".to_string());
+        }
+
+        // The button press gave winit's X connection an implicit pointer
+        // grab, which would make GTK's grab fail
+        // SAFETY: winit's display stays open for the window's lifetime
+        unsafe {
+            XUngrabPointer(winit_display.as_ptr(), 0); // CurrentTime
+            XFlush(winit_display.as_ptr());
+        }
+
+        let targets = gtk::TargetList::new(&[]);
+        match payload {
+            DragPayload::Files { .. } => targets.add_uri_targets(0),
+            DragPayload::Text { .. } => targets.add_text_targets(0),
+        }
+
+        // Off-screen widget that owns the drag
+        let source = gtk::Invisible::new();
+        source.show();
+
+        let data = payload.clone();
+        source.connect_drag_data_get(move |_, _, selection, _, _| match &data {
+            DragPayload::Files { paths } => {
+                let uris: Vec<String> = paths
+                    .iter()
+                    .filter_map(|path| glib::filename_to_uri(path, None).ok())
+                    .map(String::from)
+                    .collect();
+                selection.set_uris(&uris.iter().map(String::as_str).collect::<Vec<_>>());
+            }
+            DragPayload::Text { text } => {
+                selection.set_text(text);
+            }
+        });
+
+        let failed = Rc::new(Cell::new(false));
+        source.connect_drag_failed({
+            let failed = failed.clone();
+            move |_, _, _| {
+                failed.set(true);
+                // Skip GTK's animation of the icon flying back
+                glib::Propagation::Stop
+            }
+        });
+        source.connect_drag_end(move |_, context| {
+            let action = context.selected_action();
+            let result = if failed.get() {
+                DragResult::Cancelled
+            } else if action.contains(gdk::DragAction::MOVE) {
+                DragResult::Moved
+            } else if action.contains(gdk::DragAction::COPY) {
+                DragResult::Copied
+            } else if action.contains(gdk::DragAction::LINK) {
+                DragResult::Linked
+            } else {
+                DragResult::Cancelled
+            };
+            finish();
+            notify_drag_result(result);
+        });
+
+        // The press that started the gesture happened on winit's connection,
+        // so GTK never saw it; stand in for it with a press of the seat's
+        // pointer at the current time
+        let mut trigger = gdk::Event::new(gdk::EventType::ButtonPress);
+        trigger.set_device(display.default_seat().and_then(|seat| seat.pointer()).as_ref());
+
+        let actions = gdk::DragAction::COPY | gdk::DragAction::MOVE | gdk::DragAction::LINK;
+        let Some(context) = source.drag_begin_with_coordinates(&targets, actions, 1, Some(&trigger), -1, -1) else {
+            unsafe { source.destroy() };
+            return Err("GTK could not start the drag".to_string());
+        };
+
+        // The drag runs on the event loop, which pumps GTK while it's in
+        // progress; cancelling it ends it through drag-failed and drag-end
+        let timeout = glib::timeout_add_local_once(DRAG_TIMEOUT, move || {
+            // The timeout is spent, so finish() mustn't remove it
+            ACTIVE.with(|active| {
+                if let Some(drag) = active.borrow_mut().as_mut() {
+                    drag.timeout = None;
+                }
+            });
+            context.drag_cancel();
+        });
+        ACTIVE.with(|active| {
+            *active.borrow_mut() = Some(ActiveDrag { source, timeout: Some(timeout) });
+        });
+
+        Ok(())
+    }
+}
+
+/// Whether a drag session is running; the event loop has to keep
+/// dispatching GTK events until it ends
+#[cfg(target_os = "linux")]
+pub fn in_progress() -> bool {
+    linux::in_progress()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_files_payload() {
+        let payload = DragPayload::from_json(r#"{"type":"files","paths":["/tmp/a.txt","/tmp/b.png"]}"#).unwrap();
+        assert_eq!(
+            payload,
+            DragPayload::Files { paths: vec!["/tmp/a.txt".to_string(), "/tmp/b.png".to_string()] }
+        );
+    }
+
+    #[test]
+    fn test_parse_text_payload() {
+        let payload = DragPayload::from_json(r#"{"type":"text","text":"hello"}"#).unwrap();
+        assert_eq!(payload, DragPayload::Text { text: "hello".to_string() });
+    }
+
+    #[test]
+    fn test_parse_invalid_payloads() {
+        assert!(DragPayload::from_json(r#"{"type":"files","paths":[]}"#).is_err());
+        assert!(DragPayload::from_json(r#"{"type":"image","data":""}"#).is_err());
+        assert!(DragPayload::from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Files dragged into the window from the OS
+//!
+//! winit reports a drag of files as one `HoveredFile` per file when it
+//! enters the window, then one `DroppedFile` per file or a single
+//! `HoveredFileCancelled`. `FileDrop` groups those into one enter, drop or
+//! cancel for the app. No platform reports the cursor while a drag is over
+//! the window, so the cursor is polled for drag-over positions:
+//! - macOS: `mouseLocationOutsideOfEventStream`
+//! - Windows: `GetCursorPos`
+//! - X11: `QueryPointer`
+//! - Wayland: not available; events carry the last position the window saw
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often the cursor is polled while files are dragged over the window
+pub const CURSOR_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// What happened to a drag of files, positions in logical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileDropEvent {
+    /// Files were dragged into the window
+    Enter { paths: Vec<String>, x: f64, y: f64 },
+    /// The drag moved over the window
+    Over { x: f64, y: f64 },
+    /// The files were dropped on the window
+    Drop { paths: Vec<String>, x: f64, y: f64 },
+    /// The drag left the window or was cancelled
+    Cancel,
+}
+
+/// Collects winit's per-file drag events between event loop iterations
+#[derive(Debug, Clone, Default)]
+pub struct FileDrop {
+    hovered: Vec<PathBuf>,
+    dropped: Vec<PathBuf>,
+    cancelled: bool,
+    /// An Enter was reported and no Drop or Cancel since
+    active: bool,
+    /// Last known cursor position
+    position: (f64, f64),
+    /// Position of the last event reported
+    reported: (f64, f64),
+}
+
+impl FileDrop {
+    pub fn hovered(&mut self, path: PathBuf) {
+        self.hovered.push(path);
+    }
+
+    pub fn dropped(&mut self, path: PathBuf) {
+        self.dropped.push(path);
+    }
+
+    pub fn cancelled(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// The window saw the cursor at a logical position
+    pub fn cursor_moved(&mut self, x: f64, y: f64) {
+        self.position = (x, y);
+    }
+
+    /// Files are over the window, or about to be reported as such
+    pub fn is_dragging(&self) -> bool {
+        self.active || !self.hovered.is_empty()
+    }
+
+    /// The events that winit's events since the last call add up to
+    pub fn take_events(&mut self) -> Vec<FileDropEvent> {
+        let mut events = Vec::new();
+        let (x, y) = self.position;
+
+        if !self.active && !self.hovered.is_empty() {
+            self.active = true;
+            self.reported = self.position;
+            events.push(FileDropEvent::Enter { paths: path_strings(&self.hovered), x, y });
+        }
+        self.hovered.clear();
+
+        if !self.dropped.is_empty() {
+            events.push(FileDropEvent::Drop { paths: path_strings(&self.dropped), x, y });
+            self.dropped.clear();
+            self.active = false;
+        } else if self.cancelled {
+            if self.active {
+                events.push(FileDropEvent::Cancel);
+            }
+            self.active = false;
+        } else if self.active && self.position != self.reported {
+            self.reported = self.position;
+            events.push(FileDropEvent::Over { x, y });
+        }
+        self.cancelled = false;
+
+        events
+    }
+}
+
+fn path_strings(paths: &[PathBuf]) -> Vec<String> {
+    paths.iter().map(|path| path.to_string_lossy().into_owned()).collect()
+}
+
+/// Cursor position relative to the window's content in physical pixels,
+/// wherever the cursor is. None where the platform can't tell.
+pub fn cursor_position(window: &winit::window::Window) -> Option<(f64, f64)> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::cursor_position(window)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::cursor_position(window)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        x11::cursor_position(window)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = window;
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use cocoa::base::{id, nil, BOOL, NO};
+    use cocoa::foundation::{NSPoint, NSRect};
+    use objc::{msg_send, sel, sel_impl};
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    pub fn cursor_position(window: &winit::window::Window) -> Option<(f64, f64)> {
+        let handle = window.window_handle().ok()?;
+        let ns_view = match handle.as_raw() {
+            RawWindowHandle::AppKit(h) => h.ns_view.as_ptr() as id,
+            _ => return None,
+        };
+
+        unsafe {
+            let ns_window: id = msg_send![ns_view, window];
+            if ns_window == nil {
+                return None;
+            }
+            let location: NSPoint = msg_send![ns_window, mouseLocationOutsideOfEventStream];
+            let local: NSPoint = msg_send![ns_view, convertPoint: location fromView: nil];
+            let bounds: NSRect = msg_send![ns_view, bounds];
+            let flipped: BOOL = msg_send![ns_view, isFlipped];
+            let y = if flipped == NO { bounds.size.height - local.y } else { local.y };
+            let scale: f64 = msg_send![ns_window, backingScaleFactor];
+            Some((local.x * scale, y * scale))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use windows::Win32::Foundation::{HWND, POINT};
+    use windows::Win32::Graphics::Gdi::ScreenToClient;
+    use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    pub fn cursor_position(window: &winit::window::Window) -> Option<(f64, f64)> {
+        let handle = window.window_handle().ok()?;
+        let hwnd = match handle.as_raw() {
+            RawWindowHandle::Win32(h) => HWND(h.hwnd.get() as *mut std::ffi::c_void),
+            _ => return None,
+        };
+
+        let mut point = POINT::default();
+        unsafe {
+            GetCursorPos(&mut point).ok()?;
+            if !ScreenToClient(hwnd, &mut point).as_bool() {
+                return None;
+            }
+        }
+        Some((point.x as f64, point.y as f64))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use std::sync::OnceLock;
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::rust_connection::RustConnection;
+
+    /// Connection kept for polling; None if there is no X server (Wayland)
+    static CONNECTION: OnceLock<Option<RustConnection>> = OnceLock::new();
+
+    pub fn cursor_position(window: &winit::window::Window) -> Option<(f64, f64)> {
+        let handle = window.window_handle().ok()?;
+        let x11_window = match handle.as_raw() {
+            RawWindowHandle::Xlib(h) => h.window as u32,
+            RawWindowHandle::Xcb(h) => h.window.get(),
+            _ => return None,
+        };
+
+        let conn = CONNECTION.get_or_init(|| x11rb::connect(None).ok().map(|(conn, _)| conn)).as_ref()?;
+        let reply = conn.query_pointer(x11_window).ok()?.reply().ok()?;
+        reply.same_screen.then_some((reply.win_x as f64, reply.win_y as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_file_drop() {
+        let mut drop = FileDrop::default();
+        drop.cursor_moved(10.0, 20.0);
+        drop.hovered(PathBuf::from("/tmp/a.txt"));
+        drop.hovered(PathBuf::from("/tmp/b.png"));
+        assert!(drop.is_dragging());
+        assert_eq!(
+            drop.take_events(),
+            vec![FileDropEvent::Enter { paths: vec!["/tmp/a.txt".into(), "/tmp/b.png".into()], x: 10.0, y: 20.0 }]
+        );
+
+        // Moves while over the window
+        assert!(drop.take_events().is_empty());
+        drop.cursor_moved(30.0, 40.0);
+        assert_eq!(drop.take_events(), vec![FileDropEvent::Over { x: 30.0, y: 40.0 }]);
+
+        drop.dropped(PathBuf::from("/tmp/a.txt"));
+        drop.dropped(PathBuf::from("/tmp/b.png"));
+        assert_eq!(
+            drop.take_events(),
+            vec![FileDropEvent::Drop { paths: vec!["/tmp/a.txt".into(), "/tmp/b.png".into()], x: 30.0, y: 40.0 }]
+        );
+        assert!(!drop.is_dragging());
+    }
+
+    #[test]
+    fn test_cancel_only_after_enter() {
+        let mut drop = FileDrop::default();
+        drop.cancelled();
+        assert!(drop.take_events().is_empty());
+
+        drop.hovered(PathBuf::from("/tmp/a.txt"));
+        drop.cancelled();
+        let events = drop.take_events();
+        assert!(matches!(events[0], FileDropEvent::Enter { .. }));
+        assert_eq!(events[1], FileDropEvent::Cancel);
+        assert!(!drop.is_dragging());
+        assert!(drop.take_events().is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! Frame timing for RedrawRequested
+//!
+//! Each frame reports the seconds since the previous one, measured on the
+//! event loop's clock as the redraw starts. Optionally a fixed-timestep
+//! accumulator turns that variable delta into a whole number of fixed
+//! steps, so physics advances the same amount per second of wall-clock time
+//! however the frames fall: a dropped frame is made up by an extra step on
+//! the next one.
+
+use std::time::{Duration, Instant};
+
+/// Most fixed steps run for one frame. After a long stall (the app
+/// backgrounded, a breakpoint) the backlog is dropped rather than replayed
+/// all at once.
+pub const MAX_STEPS_PER_FRAME: u32 = 8;
+
+/// Timing of one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTiming {
+    /// Seconds since the previous frame started, 0 for the first frame
+    pub dt: f64,
+    /// Fixed steps that fell due, 0 outside fixed-timestep mode
+    pub fixed_steps: u32,
+}
+
+/// Clock and fixed-step accumulator for one window's frames
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    last_frame_at: Option<Instant>,
+    /// Fixed step length, None outside fixed-timestep mode
+    step: Option<Duration>,
+    /// Time not yet consumed by a fixed step
+    accumulator: Duration,
+}
+
+impl FramePacer {
+    /// Create a pacer; a `fixed_hz` of 0 leaves fixed-timestep mode off
+    pub fn new(fixed_hz: u32) -> Self {
+        Self {
+            last_frame_at: None,
+            step: (fixed_hz > 0).then(|| Duration::from_nanos(1_000_000_000 / fixed_hz as u64)),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Length of a fixed step in seconds, None outside fixed-timestep mode
+    pub fn step_seconds(&self) -> Option<f64> {
+        self.step.map(|step| step.as_secs_f64())
+    }
+
+    /// When the last frame started
+    pub fn last_frame_at(&self) -> Option<Instant> {
+        self.last_frame_at
+    }
+
+    /// Start a frame at `now`
+    pub fn begin_frame(&mut self, now: Instant) -> FrameTiming {
+        let elapsed = self
+            .last_frame_at
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_frame_at = Some(now);
+        let dt = elapsed.as_secs_f64();
+
+        let Some(step) = self.step else {
+            return FrameTiming { dt, fixed_steps: 0 };
+        };
+        // Count in whole nanoseconds so steps add up exactly over time
+        let step_ns = step.as_nanos();
+        let accumulated_ns = (self.accumulator + elapsed).as_nanos();
+        let due = accumulated_ns / step_ns;
+        let fixed_steps = due.min(MAX_STEPS_PER_FRAME as u128) as u32;
+        let left_ns = if due > MAX_STEPS_PER_FRAME as u128 {
+            // Keep only the fraction of a step, as if the stall never happened
+            accumulated_ns % step_ns
+        } else {
+            accumulated_ns - fixed_steps as u128 * step_ns
+        };
+        self.accumulator = Duration::from_nanos(left_ns as u64);
+        FrameTiming { dt, fixed_steps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn after(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_frame_dt() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(0);
+        assert_eq!(pacer.begin_frame(start), FrameTiming { dt: 0.0, fixed_steps: 0 });
+        let timing = pacer.begin_frame(after(start, 20));
+        assert!((timing.dt - 0.020).abs() < 1e-9);
+        assert_eq!(timing.fixed_steps, 0);
+        assert_eq!(pacer.last_frame_at(), Some(after(start, 20)));
+    }
+
+    #[test]
+    fn test_fixed_steps_follow_wall_clock() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(100);
+        pacer.begin_frame(start);
+
+        // Uneven frames over 100ms still add up to 10 steps of 10ms
+        let mut steps = 0;
+        for ms in [15, 40, 45, 100] {
+            steps += pacer.begin_frame(after(start, ms)).fixed_steps;
+        }
+        assert_eq!(steps, 10);
+
+        // A dropped frame is made up on the next one
+        assert_eq!(pacer.begin_frame(after(start, 130)).fixed_steps, 3);
+    }
+
+    #[test]
+    fn test_stall_drops_backlog() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(100);
+        pacer.begin_frame(start);
+        assert_eq!(pacer.begin_frame(after(start, 5_005)).fixed_steps, MAX_STEPS_PER_FRAME);
+        assert_eq!(pacer.begin_frame(after(start, 5_010)).fixed_steps, 1);
+    }
+}
@@ -872,6 +872,10 @@ declare_class!(
                 vsync: true,
                 low_power_gpu: false,
                 allow_software_fallback: false,
+                gamma_correct_text: false,
+                subpixel_text: false,
+                wide_gamut: false,
+                display_p3_colors: false,
             };
 
             match pollster::block_on(backend.init_with_window(&native_handle, config)) {
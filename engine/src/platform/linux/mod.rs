@@ -16,7 +16,7 @@ pub mod window_controls;
 
 pub use clipboard::LinuxClipboard;
 pub use dialogs::{open_file_dialog, save_file_dialog, open_folder_dialog, show_message_dialog, MessageLevel};
-pub use notifications::show_notification;
+pub use notifications::{show_notification, show_notification_handle};
 pub use portal::{is_dark_mode, get_accent_color, is_natural_scrolling, start_theme_listener};
 pub use tray::LinuxTrayIcon;
 pub use window_controls::{WindowControls, ButtonKind, ResizeEdge, detect_resize_edge, HEADER_HEIGHT, window_border_command, WINDOW_CORNER_RADIUS};
@@ -2,7 +2,7 @@
 //!
 //! Provides desktop notification functionality via D-Bus.
 
-use notify_rust::{Notification, Timeout, Urgency};
+use notify_rust::{Notification, NotificationHandle, Timeout, Urgency};
 
 /// Show a simple notification
 ///
@@ -32,6 +32,21 @@ pub fn show_notification_with_icon(title: &str, body: &str, icon: &str) {
         .show();
 }
 
+/// Show a notification and return the handle that closes it
+///
+/// # Arguments
+/// * `title` - Notification title
+/// * `body` - Notification body text
+/// * `icon` - Optional icon name (from icon theme) or path to icon file
+pub fn show_notification_handle(title: &str, body: &str, icon: Option<&str>) -> Result<NotificationHandle, String> {
+    let mut notification = Notification::new();
+    notification.summary(title).body(body).timeout(Timeout::Default);
+    if let Some(icon) = icon {
+        notification.icon(icon);
+    }
+    notification.show().map_err(|e| e.to_string())
+}
+
 /// Notification urgency level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NotificationUrgency {
@@ -295,8 +295,11 @@ impl WindowControls {
                     color: bg_color,
                     corner_radii: [BUTTON_RADIUS, BUTTON_RADIUS, BUTTON_RADIUS, BUTTON_RADIUS],
                     rotation: 0.0,
+                    pivot: None,
                     border: None,
                     gradient: None,
+                    inner_shadow: None,
+                    shadow: None,
                 });
             }
 
@@ -329,6 +332,7 @@ impl WindowControls {
                             vertical_align: VerticalAlign::Middle,
                             ..Default::default()
                         },
+                        gradient: None,
                     });
                 }
                 ButtonKind::Minimize => {
@@ -351,6 +355,7 @@ impl WindowControls {
                             vertical_align: VerticalAlign::Middle,
                             ..Default::default()
                         },
+                        gradient: None,
                     });
                 }
                 ButtonKind::Maximize => {
@@ -367,12 +372,11 @@ impl WindowControls {
                         color: 0x00000000, // Transparent fill
                         corner_radii: [1.0, 1.0, 1.0, 1.0],
                         rotation: 0.0,
-                        border: Some(crate::render::Border {
-                            width: 1.5,
-                            color: icon_color,
-                            style: crate::render::BorderStyle::Solid,
-                        }),
+                        pivot: None,
+                        border: Some(crate::render::Border::solid(1.5, icon_color)),
                         gradient: None,
+                        inner_shadow: None,
+                        shadow: None,
                     });
 
                     // If maximized, draw a second offset rectangle for restore icon
@@ -385,12 +389,11 @@ impl WindowControls {
                             color: 0x00000000,
                             corner_radii: [1.0, 1.0, 1.0, 1.0],
                             rotation: 0.0,
-                            border: Some(crate::render::Border {
-                                width: 1.5,
-                                color: icon_color,
-                                style: crate::render::BorderStyle::Solid,
-                            }),
+                            pivot: None,
+                            border: Some(crate::render::Border::solid(1.5, icon_color)),
                             gradient: None,
+                            inner_shadow: None,
+                            shadow: None,
                         });
                     }
                 }
@@ -415,7 +418,7 @@ impl WindowControls {
 /// Generate render command for window border
 /// This creates a rounded rectangle outline that visually defines the window bounds
 pub fn window_border_command(width: f32, height: f32, is_dark: bool) -> crate::render::RenderCommand {
-    use crate::render::{RenderCommand, Border, BorderStyle};
+    use crate::render::{RenderCommand, Border};
 
     let border_color = if is_dark {
         WINDOW_BORDER_COLOR_DARK
@@ -431,12 +434,11 @@ pub fn window_border_command(width: f32, height: f32, is_dark: bool) -> crate::r
         color: 0x00000000, // Transparent fill
         corner_radii: [WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS],
         rotation: 0.0,
-        border: Some(Border {
-            width: WINDOW_BORDER_WIDTH,
-            color: border_color,
-            style: BorderStyle::Solid,
-        }),
+        pivot: None,
+        border: Some(Border::solid(WINDOW_BORDER_WIDTH, border_color)),
         gradient: None,
+        inner_shadow: None,
+        shadow: None,
     }
 }
 
@@ -455,6 +457,7 @@ pub enum ResizeEdge {
 
 impl ResizeEdge {
     /// Convert to winit ResizeDirection
+    #[cfg(feature = "winit")]
     pub fn to_resize_direction(self) -> winit::window::ResizeDirection {
         use winit::window::ResizeDirection;
         match self {
@@ -475,6 +475,10 @@ impl super::backend::PlatformBackend for MacosBackend {
                 vsync: true,
                 low_power_gpu: false,
                 allow_software_fallback: false,
+                gamma_correct_text: false,
+                subpixel_text: false,
+                wide_gamut: false,
+                display_p3_colors: false,
             };
 
             // TODO: Initialize backend with metal layer
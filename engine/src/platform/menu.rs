@@ -0,0 +1,388 @@
+//! Application menu bar
+//!
+//! Lets the app describe its menus (File, Edit, ...) as JSON and install
+//! them as the system menu bar, with the OS handling their keyboard
+//! shortcuts:
+//! - macOS: the application's main menu. The first menu is the application
+//!   menu, which the system titles with the app's name.
+//! - Windows/Linux: not supported yet; `set_menu_bar` is a no-op
+//!
+//! Clicks, including ones made with an item's shortcut, are reported by id
+//! to the handler passed to `set_menu_bar`.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// A top-level menu of the menu bar
+///
+/// JSON form: `{"title":"File","items":[{"id":1,"title":"Open…","key":"o"},{"separator":true}]}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Menu {
+    pub title: String,
+    #[serde(default)]
+    pub items: Vec<MenuItem>,
+}
+
+/// An entry of a menu: a clickable item, a separator, or a submenu
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MenuItem {
+    /// Reported when the item is clicked; unique and non-zero for every
+    /// item that isn't a separator or a submenu
+    pub id: u32,
+    pub title: String,
+    /// Key of the item's shortcut: a character ("s", ",") or a named key
+    /// ("Enter", "Escape", "Up", "F5", ...; see `key_equivalent`)
+    pub key: Option<String>,
+    /// Modifiers held with `key`; Cmd alone when omitted
+    pub modifiers: Option<Vec<Modifier>>,
+    pub enabled: bool,
+    /// Show a checkmark next to the item
+    pub checked: bool,
+    pub separator: bool,
+    /// Entries of the submenu this item opens; empty for other items
+    pub items: Vec<MenuItem>,
+}
+
+impl Default for MenuItem {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            title: String::new(),
+            key: None,
+            modifiers: None,
+            enabled: true,
+            checked: false,
+            separator: false,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl MenuItem {
+    /// Modifiers of the item's shortcut
+    pub fn shortcut_modifiers(&self) -> &[Modifier] {
+        self.modifiers.as_deref().unwrap_or(&[Modifier::Cmd])
+    }
+}
+
+/// A modifier key of a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    /// Command on macOS
+    Cmd,
+    Shift,
+    /// Option on macOS
+    Alt,
+    Ctrl,
+}
+
+/// Parse and validate a menu bar from JSON (an array of menus)
+pub fn menus_from_json(json: &str) -> Result<Vec<Menu>, String> {
+    let menus: Vec<Menu> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let mut ids = HashSet::new();
+    for menu in &menus {
+        check_items(&menu.items, &mut ids)?;
+    }
+    Ok(menus)
+}
+
+fn check_items(items: &[MenuItem], ids: &mut HashSet<u32>) -> Result<(), String> {
+    for item in items {
+        if item.separator {
+            continue;
+        }
+        if !item.items.is_empty() {
+            check_items(&item.items, ids)?;
+            continue;
+        }
+        if item.id == 0 {
+            return Err(format!("Menu item \"{}\" has no id", item.title));
+        }
+        if !ids.insert(item.id) {
+            return Err(format!("Menu item id {} is used more than once", item.id));
+        }
+        if let Some(key) = &item.key {
+            if key_equivalent(key).is_none() {
+                return Err(format!("Unknown shortcut key \"{}\" for menu item {}", key, item.id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The character AppKit takes as the key equivalent for `key`: a single
+/// character (letters lowercased, as AppKit reads uppercase as Shift) or a
+/// named key. None for anything else.
+pub fn key_equivalent(key: &str) -> Option<String> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c.to_lowercase().collect());
+    }
+    let c = match key {
+        "Enter" => '\r',
+        "Tab" => '\t',
+        "Space" => ' ',
+        "Backspace" => '\u{8}',
+        "Escape" => '\u{1b}',
+        "Delete" => '\u{7f}',
+        // NSEvent function-key characters
+        "Up" => '\u{f700}',
+        "Down" => '\u{f701}',
+        "Left" => '\u{f702}',
+        "Right" => '\u{f703}',
+        "Home" => '\u{f729}',
+        "End" => '\u{f72b}',
+        "PageUp" => '\u{f72c}',
+        "PageDown" => '\u{f72d}',
+        _ => {
+            let n: u32 = key.strip_prefix('F')?.parse().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            char::from_u32(0xf704 + n - 1)?
+        }
+    };
+    Some(c.to_string())
+}
+
+/// Handler receiving the id of a clicked menu item
+static CLICK_HANDLER: Mutex<Option<fn(u32)>> = Mutex::new(None);
+
+/// Report a menu item click to the handler passed to `set_menu_bar`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn notify_click(id: u32) {
+    let handler = CLICK_HANDLER.lock().ok().and_then(|guard| *guard);
+    if let Some(handler) = handler {
+        handler(id);
+    }
+}
+
+/// Install `menus` as the menu bar, replacing the current one, and report
+/// clicks to `on_click`.
+///
+/// Must be called on the main thread, once the event loop is running.
+pub fn set_menu_bar(menus: &[Menu], on_click: fn(u32)) -> Result<(), String> {
+    if let Ok(mut guard) = CLICK_HANDLER.lock() {
+        *guard = Some(on_click);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos::set_menu_bar(menus)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = menus;
+        Ok(())
+    }
+}
+
+/// NSMenu building, shared by the menu bar and the tray icon's menu
+#[cfg(target_os = "macos")]
+pub(crate) mod macos {
+    use super::{key_equivalent, notify_click, Menu, MenuItem, Modifier};
+    use cocoa::base::{id, nil, NO, YES};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Class, Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::sync::Once;
+
+    // NSEventModifierFlags
+    const NS_SHIFT_KEY_MASK: usize = 1 << 17;
+    const NS_CONTROL_KEY_MASK: usize = 1 << 18;
+    const NS_ALTERNATE_KEY_MASK: usize = 1 << 19;
+    const NS_COMMAND_KEY_MASK: usize = 1 << 20;
+
+    static TARGET: Once = Once::new();
+    static mut TARGET_PTR: id = nil;
+
+    /// The object every item sends its action to (created once, never freed)
+    fn target() -> id {
+        TARGET.call_once(|| {
+            let superclass = class!(NSObject);
+            let mut decl = ClassDecl::new("CenteredMenuTarget", superclass)
+                .expect("Failed to create menu target class");
+            unsafe {
+                decl.add_method(
+                    sel!(menuItemClicked:),
+                    menu_item_clicked as extern "C" fn(&Object, Sel, id),
+                );
+            }
+            let cls: &Class = decl.register();
+            unsafe {
+                TARGET_PTR = msg_send![cls, new];
+            }
+        });
+
+        unsafe { TARGET_PTR }
+    }
+
+    extern "C" fn menu_item_clicked(_this: &Object, _sel: Sel, sender: id) {
+        let tag: isize = unsafe { msg_send![sender, tag] };
+        notify_click(tag as u32);
+    }
+
+    fn modifier_mask(modifiers: &[Modifier]) -> usize {
+        modifiers.iter().fold(0, |mask, modifier| {
+            mask | match modifier {
+                Modifier::Cmd => NS_COMMAND_KEY_MASK,
+                Modifier::Shift => NS_SHIFT_KEY_MASK,
+                Modifier::Alt => NS_ALTERNATE_KEY_MASK,
+                Modifier::Ctrl => NS_CONTROL_KEY_MASK,
+            }
+        })
+    }
+
+    /// An autoreleased NSString
+    unsafe fn ns_string(s: &str) -> id {
+        NSString::alloc(nil).init_str(s).autorelease()
+    }
+
+    /// A new, empty NSMenu (retained) whose items keep the enabled state
+    /// they're given rather than one worked out by AppKit
+    pub(crate) unsafe fn new_menu(title: &str) -> id {
+        let menu: id = msg_send![class!(NSMenu), alloc];
+        let menu: id = msg_send![menu, initWithTitle: ns_string(title)];
+        let _: () = msg_send![menu, setAutoenablesItems: NO];
+        menu
+    }
+
+    /// A new NSMenuItem (retained) with no action, `key` being its key
+    /// equivalent ("" for none)
+    pub(crate) unsafe fn new_item(title: &str, key: &str, enabled: bool, checked: bool) -> id {
+        let ns_item: id = msg_send![class!(NSMenuItem), alloc];
+        let ns_item: id = msg_send![ns_item, initWithTitle: ns_string(title)
+                                    action: nil
+                                    keyEquivalent: ns_string(key)];
+        let _: () = msg_send![ns_item, setEnabled: if enabled { YES } else { NO }];
+        if checked {
+            let _: () = msg_send![ns_item, setState: 1isize]; // NSControlStateValueOn
+        }
+        ns_item
+    }
+
+    /// A new separator item (retained)
+    pub(crate) unsafe fn new_separator() -> id {
+        let separator: id = msg_send![class!(NSMenuItem), separatorItem];
+        msg_send![separator, retain]
+    }
+
+    /// A new NSMenu holding `items`
+    unsafe fn build_menu(title: &str, items: &[MenuItem]) -> id {
+        let menu = new_menu(title);
+        for item in items {
+            let ns_item = build_item(item);
+            let _: () = msg_send![menu, addItem: ns_item];
+            let _: () = msg_send![ns_item, release];
+        }
+        menu
+    }
+
+    /// A new NSMenuItem for `item` (retained; a separator is retained too)
+    unsafe fn build_item(item: &MenuItem) -> id {
+        if item.separator {
+            return new_separator();
+        }
+
+        let key = item.key.as_deref().and_then(key_equivalent).unwrap_or_default();
+        let ns_item = new_item(&item.title, &key, item.enabled, item.checked);
+
+        if item.items.is_empty() {
+            let _: () = msg_send![ns_item, setAction: sel!(menuItemClicked:)];
+            let _: () = msg_send![ns_item, setTarget: target()];
+            let _: () = msg_send![ns_item, setTag: item.id as isize];
+            if !key.is_empty() {
+                let _: () = msg_send![ns_item, setKeyEquivalentModifierMask: modifier_mask(item.shortcut_modifiers())];
+            }
+        } else {
+            let submenu = build_menu(&item.title, &item.items);
+            let _: () = msg_send![ns_item, setSubmenu: submenu];
+            let _: () = msg_send![submenu, release];
+        }
+        ns_item
+    }
+
+    pub fn set_menu_bar(menus: &[Menu]) -> Result<(), String> {
+        unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            if app == nil {
+                return Err("No NSApplication".to_string());
+            }
+
+            // Each menu hangs off an item of the main menu
+            let main_menu = new_menu("");
+            for menu in menus {
+                let bar_item = build_item(&MenuItem {
+                    title: menu.title.clone(),
+                    items: menu.items.clone(),
+                    ..MenuItem::default()
+                });
+                let _: () = msg_send![main_menu, addItem: bar_item];
+                let _: () = msg_send![bar_item, release];
+            }
+            let _: () = msg_send![app, setMainMenu: main_menu];
+            let _: () = msg_send![main_menu, release];
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_menus() {
+        let menus = menus_from_json(
+            r#"[{"title": "File", "items": [
+                {"id": 1, "title": "Save", "key": "S"},
+                {"id": 2, "title": "Save As…", "key": "s", "modifiers": ["cmd", "shift"], "enabled": false},
+                {"separator": true},
+                {"title": "Export", "items": [{"id": 3, "title": "PNG", "checked": true}]}
+            ]}]"#,
+        )
+        .unwrap();
+        let items = &menus[0].items;
+        assert_eq!(menus[0].title, "File");
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].shortcut_modifiers(), &[Modifier::Cmd]);
+        assert!(items[0].enabled);
+        assert_eq!(items[1].shortcut_modifiers(), &[Modifier::Cmd, Modifier::Shift]);
+        assert!(!items[1].enabled);
+        assert!(items[2].separator);
+        assert_eq!(items[3].items[0].id, 3);
+        assert!(items[3].items[0].checked);
+    }
+
+    #[test]
+    fn test_invalid_menus_are_rejected() {
+        // Missing and duplicate ids, including across submenus
+        assert!(menus_from_json(r#"[{"title": "File", "items": [{"title": "Open"}]}]"#).is_err());
+        assert!(menus_from_json(
+            r#"[{"title": "File", "items": [{"id": 1, "title": "Open"}]},
+                {"title": "Edit", "items": [{"title": "More", "items": [{"id": 1, "title": "Undo"}]}]}]"#
+        )
+        .is_err());
+        assert!(menus_from_json(r#"[{"title": "File", "items": [{"id": 1, "title": "Open", "key": "Hyper"}]}]"#).is_err());
+        assert!(menus_from_json(r#"{"title": "File"}"#).is_err());
+        assert_eq!(menus_from_json("[]").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_key_equivalents() {
+        assert_eq!(key_equivalent("Q").as_deref(), Some("q"));
+        assert_eq!(key_equivalent(",").as_deref(), Some(","));
+        assert_eq!(key_equivalent("Enter").as_deref(), Some("\r"));
+        assert_eq!(key_equivalent("Up").as_deref(), Some("\u{f700}"));
+        assert_eq!(key_equivalent("F1").as_deref(), Some("\u{f704}"));
+        assert_eq!(key_equivalent("F12").as_deref(), Some("\u{f70f}"));
+        assert_eq!(key_equivalent("F13"), None);
+        assert_eq!(key_equivalent(""), None);
+        assert_eq!(key_equivalent("Hyper"), None);
+    }
+}
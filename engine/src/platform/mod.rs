@@ -9,10 +9,37 @@
 //!
 //! The wgpu backend handles actual GPU rendering on all platforms.
 
+pub mod aspect;
 pub mod backend;
+pub mod cursor;
+pub mod frame_pacing;
+pub mod pointer_capture;
+pub mod ready;
 pub mod wgpu_backend;
+pub mod window_state;
 pub mod window_styling;
 
+// Images on the system clipboard
+#[cfg(not(target_arch = "wasm32"))]
+pub mod clipboard_image;
+
+// System notifications
+#[cfg(not(target_arch = "wasm32"))]
+pub mod notifications;
+
+// Native drag sessions out of the window (desktop, winit-owned windows)
+#[cfg(all(not(target_arch = "wasm32"), feature = "winit"))]
+pub mod drag;
+
+// Files dragged into the window from the OS (desktop, winit-owned windows)
+#[cfg(all(not(target_arch = "wasm32"), feature = "winit"))]
+pub mod file_drop;
+
+// Application menu bar (desktop, winit-owned windows), and the NSMenu
+// building the macOS tray menu shares
+#[cfg(not(target_arch = "wasm32"))]
+pub mod menu;
+
 // Native platform backends (bypassing winit)
 // Currently iOS and Android use native backends - desktop uses winit
 #[cfg(target_os = "ios")]
@@ -40,7 +67,7 @@ pub mod windows;
 
 // Re-exports
 pub use backend::{AppConfig, EventCallback, EventResponse, PlatformBackend, PlatformEvent, SafeAreaInsets};
-pub use wgpu_backend::{SurfaceConfig, WgpuBackend};
+pub use wgpu_backend::{GpuLimits, RenderStats, SurfaceColorSpace, SurfaceConfig, WgpuBackend};
 pub use window_styling::{apply_window_style, WindowStyleOptions};
 
 // Platform-specific backend alias (iOS, Android, and Web use native backends, others use winit)
@@ -0,0 +1,380 @@
+//! System notifications
+//!
+//! Shows a title, body and optional icon in the system's notification UI:
+//! - macOS / iOS: UNUserNotificationCenter. Needs an app bundle; permission
+//!   is asked for with the first notification.
+//! - Windows: a balloon from a notification-area icon (Shell_NotifyIconW),
+//!   which Windows 10 and later show as a toast. One balloon is shown at a
+//!   time; a new one replaces the last.
+//! - Linux: notify-rust over D-Bus
+//!
+//! Every notification gets an ID that dismisses it again.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Why a notification couldn't be shown or dismissed
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationError {
+    /// No notifications on this platform
+    Unsupported,
+    /// The platform call failed
+    Failed(String),
+}
+
+impl std::fmt::Display for NotificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationError::Unsupported => write!(f, "Notifications are not supported on this platform"),
+            NotificationError::Failed(e) => write!(f, "Notification failed: {}", e),
+        }
+    }
+}
+
+/// IDs handed out by `show`, starting at 1
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Show a notification and return its ID
+///
+/// `icon_path` is an image file shown with the notification: any image
+/// format on macOS/iOS and Linux (which also takes icon theme names), an
+/// `.ico` file on Windows.
+pub fn show(title: &str, body: &str, icon_path: Option<&str>) -> Result<u32, NotificationError> {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+        apple::show(id, title, body, icon_path).map(|()| id)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::show(id, title, body, icon_path).map(|()| id)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let handle = crate::platform::linux::show_notification_handle(title, body, icon_path)
+            .map_err(NotificationError::Failed)?;
+        if let Ok(mut handles) = linux_handles().lock() {
+            handles.insert(id, handle);
+        }
+        Ok(id)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (id, title, body, icon_path);
+        Err(NotificationError::Unsupported)
+    }
+}
+
+/// Remove a notification shown by `show`. Dismissing one that is already
+/// gone does nothing.
+pub fn dismiss(id: u32) -> Result<(), NotificationError> {
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+        apple::dismiss(id)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_impl::dismiss(id)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let handle = linux_handles().lock().ok().and_then(|mut handles| handles.remove(&id));
+        if let Some(handle) = handle {
+            handle.close();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = id;
+        Err(NotificationError::Unsupported)
+    }
+}
+
+/// Handles of shown notifications, which close them. A handle is kept until
+/// its notification is dismissed, even if it expired on its own.
+#[cfg(target_os = "linux")]
+fn linux_handles() -> &'static std::sync::Mutex<std::collections::HashMap<u32, notify_rust::NotificationHandle>> {
+    static HANDLES: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<u32, notify_rust::NotificationHandle>>> =
+        std::sync::OnceLock::new();
+    HANDLES.get_or_init(Default::default)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod apple {
+    use super::NotificationError;
+    use block::{Block, ConcreteBlock};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Class, Object, Sel, BOOL};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::sync::Once;
+
+    #[link(name = "UserNotifications", kind = "framework")]
+    extern "C" {}
+
+    type Id = *mut Object;
+    const NIL: Id = std::ptr::null_mut();
+
+    // UNAuthorizationOptions
+    const AUTHORIZATION_SOUND: usize = 1 << 1;
+    const AUTHORIZATION_ALERT: usize = 1 << 2;
+
+    // UNNotificationPresentationOptions: Sound | Alert | List | Banner
+    const PRESENT_IN_FOREGROUND: usize = (1 << 1) | (1 << 2) | (1 << 3) | (1 << 4);
+
+    static SETUP: Once = Once::new();
+
+    /// Autoreleased NSString
+    unsafe fn ns_string(s: &str) -> Id {
+        let string: Id = msg_send![class!(NSString), alloc];
+        let string: Id = msg_send![string, initWithBytes: s.as_ptr() length: s.len() encoding: 4usize]; // NSUTF8StringEncoding
+        msg_send![string, autorelease]
+    }
+
+    /// Request identifier of notification `id`
+    unsafe fn identifier(id: u32) -> Id {
+        ns_string(&format!("centered-notification-{}", id))
+    }
+
+    /// Ask for permission and install the delegate that lets notifications
+    /// show while the app is in the foreground
+    unsafe fn setup(center: Id) {
+        SETUP.call_once(|| {
+            let superclass = class!(NSObject);
+            if let Some(mut decl) = ClassDecl::new("CenteredNotificationDelegate", superclass) {
+                decl.add_method(
+                    sel!(userNotificationCenter:willPresentNotification:withCompletionHandler:),
+                    will_present as extern "C" fn(&Object, Sel, Id, Id, *mut Block<(usize,), ()>),
+                );
+                decl.register();
+            }
+            if let Some(class) = Class::get("CenteredNotificationDelegate") {
+                // The center doesn't retain its delegate; this one lives forever
+                let delegate: Id = msg_send![class, new];
+                let _: () = msg_send![center, setDelegate: delegate];
+            }
+
+            let handler = ConcreteBlock::new(|_granted: BOOL, _error: Id| {}).copy();
+            let _: () = msg_send![center, requestAuthorizationWithOptions: AUTHORIZATION_ALERT | AUTHORIZATION_SOUND
+                                  completionHandler: &*handler];
+        });
+    }
+
+    extern "C" fn will_present(_this: &Object, _sel: Sel, _center: Id, _notification: Id, handler: *mut Block<(usize,), ()>) {
+        if let Some(handler) = unsafe { handler.as_ref() } {
+            unsafe { handler.call((PRESENT_IN_FOREGROUND,)) };
+        }
+    }
+
+    /// The notification center, or an error outside an app bundle (where
+    /// UNUserNotificationCenter throws)
+    unsafe fn center() -> Result<Id, NotificationError> {
+        let bundle: Id = msg_send![class!(NSBundle), mainBundle];
+        let bundle_id: Id = msg_send![bundle, bundleIdentifier];
+        if bundle_id == NIL {
+            return Err(NotificationError::Failed("Notifications need an app bundle".to_string()));
+        }
+        let center: Id = msg_send![class!(UNUserNotificationCenter), currentNotificationCenter];
+        setup(center);
+        Ok(center)
+    }
+
+    /// Attach a copy of the icon: the system moves attached files into its
+    /// own store
+    unsafe fn icon_attachment(id: u32, icon_path: &str) -> Result<Id, NotificationError> {
+        let source = std::path::Path::new(icon_path);
+        let extension = source.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        let copy = std::env::temp_dir().join(format!("centered-notification-{}.{}", id, extension));
+        std::fs::copy(source, &copy).map_err(|e| NotificationError::Failed(format!("{}: {}", icon_path, e)))?;
+
+        let url: Id = msg_send![class!(NSURL), fileURLWithPath: ns_string(&copy.to_string_lossy())];
+        let mut error: Id = NIL;
+        let attachment: Id = msg_send![class!(UNNotificationAttachment), attachmentWithIdentifier: ns_string("icon")
+                                       URL: url options: NIL error: &mut error];
+        if attachment == NIL {
+            let _ = std::fs::remove_file(&copy);
+            return Err(NotificationError::Failed(format!("Unsupported notification icon {}", icon_path)));
+        }
+        Ok(attachment)
+    }
+
+    pub fn show(id: u32, title: &str, body: &str, icon_path: Option<&str>) -> Result<(), NotificationError> {
+        unsafe {
+            let center = center()?;
+
+            let content: Id = msg_send![class!(UNMutableNotificationContent), new];
+            let _: () = msg_send![content, setTitle: ns_string(title)];
+            let _: () = msg_send![content, setBody: ns_string(body)];
+            if let Some(icon_path) = icon_path {
+                match icon_attachment(id, icon_path) {
+                    Ok(attachment) => {
+                        let attachments: Id = msg_send![class!(NSArray), arrayWithObject: attachment];
+                        let _: () = msg_send![content, setAttachments: attachments];
+                    }
+                    Err(e) => {
+                        let _: () = msg_send![content, release];
+                        return Err(e);
+                    }
+                }
+            }
+
+            // No trigger: deliver right away
+            let request: Id = msg_send![class!(UNNotificationRequest), requestWithIdentifier: identifier(id)
+                                        content: content trigger: NIL];
+            let _: () = msg_send![content, release];
+            let _: () = msg_send![center, addNotificationRequest: request withCompletionHandler: NIL];
+            Ok(())
+        }
+    }
+
+    pub fn dismiss(id: u32) -> Result<(), NotificationError> {
+        unsafe {
+            let center = center()?;
+            let identifiers: Id = msg_send![class!(NSArray), arrayWithObject: identifier(id)];
+            let _: () = msg_send![center, removePendingNotificationRequestsWithIdentifiers: identifiers];
+            let _: () = msg_send![center, removeDeliveredNotificationsWithIdentifiers: identifiers];
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use super::NotificationError;
+    use std::ffi::c_void;
+    use std::sync::Mutex;
+    use windows::core::{w, HSTRING};
+    use windows::Win32::Foundation::*;
+    use windows::Win32::UI::Shell::*;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    /// uID of the notification-area icon balloons are shown from
+    const ICON_ID: u32 = 1;
+
+    /// The hidden window owning the icon (as an address; HWND isn't Send),
+    /// and the notification its balloon is showing
+    struct Balloons {
+        hwnd: usize,
+        shown: Option<u32>,
+    }
+
+    static BALLOONS: Mutex<Option<Balloons>> = Mutex::new(None);
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// Copy `text` into a fixed-size, null-terminated UTF-16 field
+    fn wide<const N: usize>(text: &str) -> [u16; N] {
+        let mut field = [0u16; N];
+        for (i, unit) in text.encode_utf16().take(N - 1).enumerate() {
+            field[i] = unit;
+        }
+        field
+    }
+
+    fn icon_data(hwnd: usize) -> NOTIFYICONDATAW {
+        NOTIFYICONDATAW {
+            cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: HWND(hwnd as *mut c_void),
+            uID: ICON_ID,
+            ..Default::default()
+        }
+    }
+
+    /// Create the hidden window and add its icon
+    unsafe fn add_icon() -> Result<usize, NotificationError> {
+        let class_name = w!("CenteredNotificationWindow");
+        let wc = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(window_proc),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        // Fails harmlessly if the class is already registered
+        RegisterClassExW(&wc);
+
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!("Centered Notifications"),
+            WINDOW_STYLE::default(),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| NotificationError::Failed(format!("Failed to create notification window: {:?}", e)))?;
+
+        let mut nid = icon_data(hwnd.0 as usize);
+        nid.uFlags = NIF_ICON | NIF_TIP;
+        nid.hIcon = LoadIconW(None, IDI_APPLICATION).unwrap_or_default();
+        nid.szTip = wide("Notifications");
+        if !Shell_NotifyIconW(NIM_ADD, &nid).as_bool() {
+            let _ = DestroyWindow(hwnd);
+            return Err(NotificationError::Failed("Failed to add notification icon".to_string()));
+        }
+        nid.Anonymous.uVersion = NOTIFYICON_VERSION_4;
+        let _ = Shell_NotifyIconW(NIM_SETVERSION, &nid);
+        Ok(hwnd.0 as usize)
+    }
+
+    pub fn show(id: u32, title: &str, body: &str, icon_path: Option<&str>) -> Result<(), NotificationError> {
+        let mut balloons = BALLOONS.lock().map_err(|e| NotificationError::Failed(e.to_string()))?;
+        unsafe {
+            if balloons.is_none() {
+                *balloons = Some(Balloons { hwnd: add_icon()?, shown: None });
+            }
+            let state = balloons.as_mut().unwrap();
+
+            let mut nid = icon_data(state.hwnd);
+            nid.uFlags = NIF_INFO;
+            nid.szInfoTitle = wide(title);
+            // An empty body would hide the balloon instead
+            nid.szInfo = wide(if body.is_empty() { " " } else { body });
+            nid.dwInfoFlags = NIIF_INFO;
+
+            let icon = icon_path.and_then(|path| {
+                LoadImageW(None, &HSTRING::from(path), IMAGE_ICON, 0, 0, LR_LOADFROMFILE | LR_DEFAULTSIZE).ok()
+            });
+            if let Some(icon) = icon {
+                nid.hBalloonIcon = HICON(icon.0);
+                nid.dwInfoFlags = NIIF_USER | NIIF_LARGE_ICON;
+            }
+
+            let shown = Shell_NotifyIconW(NIM_MODIFY, &nid).as_bool();
+            if let Some(icon) = icon {
+                let _ = DestroyIcon(HICON(icon.0));
+            }
+            if !shown {
+                return Err(NotificationError::Failed("Shell_NotifyIconW refused the balloon".to_string()));
+            }
+            state.shown = Some(id);
+        }
+        Ok(())
+    }
+
+    pub fn dismiss(id: u32) -> Result<(), NotificationError> {
+        let mut balloons = BALLOONS.lock().map_err(|e| NotificationError::Failed(e.to_string()))?;
+        if let Some(state) = balloons.as_mut().filter(|state| state.shown == Some(id)) {
+            // An empty balloon text removes the balloon
+            let mut nid = icon_data(state.hwnd);
+            nid.uFlags = NIF_INFO;
+            unsafe {
+                let _ = Shell_NotifyIconW(NIM_MODIFY, &nid);
+            }
+            state.shown = None;
+        }
+        Ok(())
+    }
+}
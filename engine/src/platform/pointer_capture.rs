@@ -0,0 +1,154 @@
+//! Pointer capture
+//!
+//! While capture is on, the mouse keeps reporting moves and button releases
+//! after the cursor leaves the window, so a slider or scrollbar drag doesn't
+//! get stuck. Most systems already send a window the rest of a drag that
+//! started in it; where one doesn't, the window stops hearing about the
+//! cursor once it leaves, and the cursor is followed from the mouse's raw
+//! motion (winit device events) until a window event reports it again.
+//! Raw motion skips pointer acceleration on some systems, so positions
+//! followed that way are approximate.
+//!
+//! Buttons released outside the window are matched to the most recently
+//! pressed one; if the window reports the same release later, it is dropped.
+
+/// Capture state and the cursor and buttons it follows
+#[derive(Debug, Clone, Default)]
+pub struct PointerCapture {
+    enabled: bool,
+    /// Turn capture off once the last held button is released
+    auto_release: bool,
+    /// Last known cursor position in logical pixels
+    position: (f64, f64),
+    /// The cursor left the window and no window event has reported it since
+    lost: bool,
+    /// Buttons held (event button indices), in the order they were pressed
+    pressed: Vec<u32>,
+    /// Buttons whose release was reported from device events, so the
+    /// window's own release for them is a duplicate
+    released_early: Vec<u32>,
+}
+
+impl PointerCapture {
+    /// Turn capture on or off. With `auto_release` it turns itself off when
+    /// the last held button is released.
+    pub fn set(&mut self, enabled: bool, auto_release: bool) {
+        self.enabled = enabled;
+        self.auto_release = enabled && auto_release;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The window reported the cursor at a logical position
+    pub fn cursor_moved(&mut self, x: f64, y: f64) {
+        self.position = (x, y);
+        self.lost = false;
+    }
+
+    /// The window reported the cursor leaving it
+    pub fn cursor_left(&mut self) {
+        self.lost = true;
+    }
+
+    /// The window reported a button press or release. Returns whether to
+    /// deliver it: false for a release already reported from device events.
+    pub fn button(&mut self, button: u32, pressed: bool) -> bool {
+        if pressed {
+            self.released_early.retain(|&b| b != button);
+            if !self.pressed.contains(&button) {
+                self.pressed.push(button);
+            }
+            return true;
+        }
+        if let Some(i) = self.released_early.iter().position(|&b| b == button) {
+            self.released_early.remove(i);
+            return false;
+        }
+        self.pressed.retain(|&b| b != button);
+        self.auto_release_if_idle();
+        true
+    }
+
+    /// Raw mouse motion in logical pixels. Returns the cursor's new
+    /// position when it should be reported as a move: capture is on and
+    /// the window has lost track of the cursor.
+    pub fn device_motion(&mut self, dx: f64, dy: f64) -> Option<(f64, f64)> {
+        if !self.enabled || !self.lost {
+            return None;
+        }
+        self.position = (self.position.0 + dx, self.position.1 + dy);
+        Some(self.position)
+    }
+
+    /// A raw mouse button was released. Returns the button to report as
+    /// released when the window won't report it itself.
+    pub fn device_button_released(&mut self) -> Option<u32> {
+        if !self.enabled || !self.lost {
+            return None;
+        }
+        let button = self.pressed.pop()?;
+        self.released_early.push(button);
+        self.auto_release_if_idle();
+        Some(button)
+    }
+
+    fn auto_release_if_idle(&mut self) {
+        if self.auto_release && self.pressed.is_empty() {
+            self.set(false, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follows_cursor_only_when_lost() {
+        let mut capture = PointerCapture::default();
+        capture.cursor_moved(100.0, 50.0);
+        capture.cursor_left();
+        // Off: the window's events are all there is
+        assert_eq!(capture.device_motion(5.0, 0.0), None);
+
+        capture.set(true, false);
+        assert_eq!(capture.device_motion(5.0, -2.0), Some((105.0, 48.0)));
+        assert_eq!(capture.device_motion(5.0, -2.0), Some((110.0, 46.0)));
+
+        // The window reports the cursor again (implicit grab or re-entry)
+        capture.cursor_moved(120.0, 40.0);
+        assert_eq!(capture.device_motion(5.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_release_outside_window() {
+        let mut capture = PointerCapture::default();
+        capture.set(true, true);
+        assert!(capture.button(0, true));
+        capture.cursor_left();
+
+        assert_eq!(capture.device_button_released(), Some(0));
+        // Auto-release turned capture off with the last button up
+        assert!(!capture.is_enabled());
+        assert_eq!(capture.device_button_released(), None);
+        // The window's late report of the same release is dropped once
+        assert!(!capture.button(0, false));
+        assert!(capture.button(0, true));
+        assert!(capture.button(0, false));
+    }
+
+    #[test]
+    fn test_window_release_first() {
+        let mut capture = PointerCapture::default();
+        capture.set(true, false);
+        capture.button(1, true);
+        capture.cursor_left();
+        assert!(capture.button(1, false));
+        // Nothing left held, so the raw release reports nothing
+        assert_eq!(capture.device_button_released(), None);
+        // Without auto-release capture stays on
+        assert!(capture.is_enabled());
+    }
+}
@@ -0,0 +1,150 @@
+//! Deferred Ready delivery for native mobile backends
+//!
+//! On Android the window exists before the system has dispatched its window
+//! insets, so safe-area insets read at window creation are all zero. If Ready
+//! went out then, Go would lay out the first frame without the insets and the
+//! content would jump once they arrived. The gate holds Ready back until the
+//! insets are known (or a short timeout passes, for devices that genuinely
+//! have none), and turns any later inset change into a Resized event so Go
+//! lays out again.
+
+use super::backend::{PlatformEvent, SafeAreaInsets};
+
+/// Logical window size and scale factor reported with Ready/Resized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowMetrics {
+    pub width: f64,
+    pub height: f64,
+    pub scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GateState {
+    /// No window yet
+    Idle,
+    /// Window created, Ready held back until insets arrive
+    Waiting { polls_left: u32 },
+    /// Ready sent with these insets
+    Delivered { insets: SafeAreaInsets },
+}
+
+/// Decides when Ready (and follow-up Resized) events are delivered
+#[derive(Debug)]
+pub struct ReadyGate {
+    state: GateState,
+    metrics: WindowMetrics,
+    max_polls: u32,
+}
+
+impl ReadyGate {
+    /// A gate that waits at most `max_polls` polls for non-zero insets
+    pub fn new(max_polls: u32) -> Self {
+        Self {
+            state: GateState::Idle,
+            metrics: WindowMetrics { width: 0.0, height: 0.0, scale_factor: 1.0 },
+            max_polls,
+        }
+    }
+
+    /// Whether Ready is being held back
+    pub fn is_waiting(&self) -> bool {
+        matches!(self.state, GateState::Waiting { .. })
+    }
+
+    /// The window was created. Returns Ready if it can go out right away.
+    pub fn window_ready(&mut self, metrics: WindowMetrics, insets: SafeAreaInsets) -> Option<PlatformEvent> {
+        self.metrics = metrics;
+        self.state = GateState::Waiting { polls_left: self.max_polls };
+        self.poll(insets)
+    }
+
+    /// The window was resized and a Resized event with these insets is being
+    /// sent (after Ready), so a later poll needn't send another.
+    pub fn resized(&mut self, metrics: WindowMetrics, insets: SafeAreaInsets) {
+        self.metrics = metrics;
+        if let GateState::Delivered { .. } = self.state {
+            self.state = GateState::Delivered { insets };
+        }
+    }
+
+    /// The window was destroyed; the next window starts over
+    pub fn reset(&mut self) {
+        self.state = GateState::Idle;
+    }
+
+    /// Check the current insets, once per event loop iteration.
+    ///
+    /// Returns Ready once insets are non-zero or the wait times out, and
+    /// Resized when insets change after Ready.
+    pub fn poll(&mut self, insets: SafeAreaInsets) -> Option<PlatformEvent> {
+        match self.state {
+            GateState::Idle => None,
+            GateState::Waiting { polls_left } => {
+                if insets.is_zero() && polls_left > 0 {
+                    self.state = GateState::Waiting { polls_left: polls_left - 1 };
+                    return None;
+                }
+                self.state = GateState::Delivered { insets };
+                let WindowMetrics { width, height, scale_factor } = self.metrics;
+                Some(PlatformEvent::Ready { width, height, scale_factor })
+            }
+            GateState::Delivered { insets: delivered } => {
+                if insets == delivered {
+                    return None;
+                }
+                self.state = GateState::Delivered { insets };
+                let WindowMetrics { width, height, scale_factor } = self.metrics;
+                Some(PlatformEvent::Resized { width, height, scale_factor })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHONE: WindowMetrics = WindowMetrics { width: 412.0, height: 915.0, scale_factor: 2.625 };
+    const NOTCHED: SafeAreaInsets = SafeAreaInsets { top: 32.0, left: 0.0, bottom: 24.0, right: 0.0 };
+
+    fn is_ready(event: &Option<PlatformEvent>) -> bool {
+        matches!(event, Some(PlatformEvent::Ready { .. }))
+    }
+
+    #[test]
+    fn test_ready_waits_for_real_insets() {
+        let mut gate = ReadyGate::new(10);
+
+        // Insets aren't dispatched yet when the window is created
+        assert!(gate.window_ready(PHONE, SafeAreaInsets::default()).is_none());
+        assert!(gate.poll(SafeAreaInsets::default()).is_none());
+        assert!(gate.is_waiting());
+
+        // Ready goes out with the real insets, exactly once
+        let event = gate.poll(NOTCHED);
+        assert!(is_ready(&event));
+        assert!(matches!(event, Some(PlatformEvent::Ready { width, .. }) if width == 412.0));
+        assert!(gate.poll(NOTCHED).is_none());
+
+        // Known insets at creation deliver Ready immediately
+        let mut gate = ReadyGate::new(10);
+        assert!(is_ready(&gate.window_ready(PHONE, NOTCHED)));
+    }
+
+    #[test]
+    fn test_ready_times_out_without_insets() {
+        let mut gate = ReadyGate::new(2);
+        assert!(gate.window_ready(PHONE, SafeAreaInsets::default()).is_none());
+        assert!(gate.poll(SafeAreaInsets::default()).is_none());
+        assert!(is_ready(&gate.poll(SafeAreaInsets::default())));
+
+        // Insets that arrive late trigger a follow-up Resized
+        assert!(matches!(gate.poll(NOTCHED), Some(PlatformEvent::Resized { .. })));
+        assert!(gate.poll(NOTCHED).is_none());
+
+        // A resize that already carried the new insets isn't repeated
+        let rotated = SafeAreaInsets { top: 0.0, left: 32.0, bottom: 0.0, right: 24.0 };
+        gate.resized(PHONE, rotated);
+        assert!(gate.poll(rotated).is_none());
+    }
+}
@@ -25,3 +25,12 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     return input.color;
 }
+
+// Fragment shader for shapes antialiased by coverage (arcs). The texcoord
+// holds the pixel's signed distance to the shape's nearest edges in pixels,
+// positive inside; the half pixel either side of an edge is blended.
+@fragment
+fn fs_coverage(input: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = clamp(min(input.texcoord.x, input.texcoord.y) + 0.5, 0.0, 1.0);
+    return vec4<f32>(input.color.rgb, input.color.a * coverage);
+}
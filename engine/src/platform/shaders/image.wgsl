@@ -1,18 +1,21 @@
 // Image rendering shader
 //
 // This shader samples from an RGBA image texture and optionally tints it.
+// Textures hold straight or premultiplied alpha; the output is always
+// premultiplied, matching the pipeline's blend state.
 
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) color: vec4<f32>,
-    @location(3) use_texture_color: f32, // Unused for images, but required for vertex layout compatibility
+    @location(3) premultiplied: f32, // 1.0 if the texture holds premultiplied alpha
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) premultiplied: f32,
 }
 
 @group(0) @binding(0)
@@ -27,6 +30,7 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     output.position = vec4<f32>(input.position, 0.0, 1.0);
     output.tex_coords = input.tex_coords;
     output.color = input.color;
+    output.premultiplied = input.premultiplied;
     return output;
 }
 
@@ -34,10 +38,12 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Sample the image texture (full RGBA)
     let tex_color = textureSample(image_texture, image_sampler, input.tex_coords);
+    let rgb = select(tex_color.rgb * tex_color.a, tex_color.rgb, input.premultiplied > 0.5);
 
     // Multiply by vertex color for tinting (white = no tint)
     // Also apply vertex alpha for opacity control
-    let final_color = tex_color * input.color;
+    let tint = vec4<f32>(input.color.rgb * input.color.a, input.color.a);
+    let final_color = vec4<f32>(rgb, tex_color.a) * tint;
 
     return final_color;
 }
@@ -5,12 +5,17 @@
 //
 // For regular text: texture is white on transparent, vertex color provides the tint
 // For emojis: texture contains actual colors, use_texture_color flag tells shader to use them
+// For gradient text: the gradient is evaluated per fragment from the text-box position,
+// with its colors looked up in a row of the gradient ramp texture
 
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) color: vec4<f32>,
     @location(3) use_texture_color: f32, // 1.0 = use texture RGB (emoji), 0.0 = use vertex color (text)
+    @location(4) gradient_box: vec4<f32>, // position in the gradient's box (xy) and the box size (zw), in pixels
+    @location(5) gradient: vec4<f32>,     // kind (0 none, 1 linear, 2 radial, 3 conic) and its parameters
+    @location(6) gradient_row: f32,       // v coordinate of the gradient's row in the ramp
 }
 
 struct VertexOutput {
@@ -18,6 +23,9 @@ struct VertexOutput {
     @location(0) tex_coords: vec2<f32>,
     @location(1) color: vec4<f32>,
     @location(2) use_texture_color: f32,
+    @location(3) gradient_box: vec4<f32>,
+    @location(4) @interpolate(flat) gradient: vec4<f32>,
+    @location(5) @interpolate(flat) gradient_row: f32,
 }
 
 @group(0) @binding(0)
@@ -26,6 +34,9 @@ var atlas_texture: texture_2d<f32>;
 @group(0) @binding(1)
 var atlas_sampler: sampler;
 
+@group(0) @binding(2)
+var gradient_ramp: texture_2d<f32>;
+
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
@@ -36,23 +47,132 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     output.tex_coords = input.tex_coords;
     output.color = input.color;
     output.use_texture_color = input.use_texture_color;
+    output.gradient_box = input.gradient_box;
+    output.gradient = input.gradient;
+    output.gradient_row = input.gradient_row;
 
     return output;
 }
 
+// Position along a gradient at `local` in a `size` box; the same math as
+// the linear, radial and conic positions in geometry.rs
+fn gradient_position(kind: f32, params: vec3<f32>, local: vec2<f32>, size: vec2<f32>) -> f32 {
+    let norm = select(vec2<f32>(0.5), local / size, size > vec2<f32>(0.0));
+    if kind < 1.5 {
+        // Linear: params.x is the angle in degrees (CSS, 0 = up)
+        let angle = radians(params.x - 90.0);
+        return dot(norm - 0.5, vec2<f32>(cos(angle), sin(angle))) + 0.5;
+    }
+    if kind < 2.5 {
+        // Radial: params.xy is the center, reaching 1.0 at 1/sqrt(2)
+        return length(norm - params.xy) / 0.707;
+    }
+    // Conic: params.xy is the center, params.z the start angle in degrees
+    // clockwise from 12 o'clock, with angles measured in pixels
+    let d = local - params.xy * size;
+    let angle = degrees(atan2(d.x, -d.y));
+    return fract((angle - params.z) / 360.0);
+}
+
+// Width of the gradient ramp texture, in samples
+const GRADIENT_RAMP_WIDTH: f32 = 256.0;
+
+// Fill color at this fragment: the vertex color, or the gradient there
+fn paint(input: VertexOutput) -> vec4<f32> {
+    if input.gradient.x < 0.5 {
+        return input.color;
+    }
+    let t = clamp(gradient_position(input.gradient.x, input.gradient.yzw, input.gradient_box.xy, input.gradient_box.zw), 0.0, 1.0);
+    // Sample centers: position 0 is the first texel's center, 1 the last's
+    let u = (t * (GRADIENT_RAMP_WIDTH - 1.0) + 0.5) / GRADIENT_RAMP_WIDTH;
+    return textureSampleLevel(gradient_ramp, atlas_sampler, vec2<f32>(u, input.gradient_row), 0.0);
+}
+
+// Set when the target blends encoded (non-sRGB) values and text should be
+// blended in linear light: coverage is corrected so the encoded blend comes
+// out as the linear one would
+override correct_gamma: bool = false;
+
+fn srgb_to_linear(c: f32) -> f32 {
+    return select(pow((c + 0.055) / 1.055, 2.4), c / 12.92, c <= 0.04045);
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    return select(1.055 * pow(c, 1.0 / 2.4) - 0.055, c * 12.92, c <= 0.0031308);
+}
+
+// Coverage that, blended on encoded values, lands where blending `coverage`
+// in linear light would. The background is unknown, so the text is assumed
+// to be on the opposite extreme of its own luminance - dark text on white,
+// light text on black - which is where the difference shows most.
+fn linear_coverage(coverage: f32, color: vec3<f32>) -> f32 {
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let background = select(0.0, 1.0, luma < 0.5);
+    let blended = linear_to_srgb(mix(srgb_to_linear(background), srgb_to_linear(luma), coverage));
+    return clamp((blended - background) / (luma - background), 0.0, 1.0);
+}
+
+fn text_coverage(coverage: f32, color: vec3<f32>) -> f32 {
+    if correct_gamma {
+        return linear_coverage(coverage, color);
+    }
+    return coverage;
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Sample the atlas texture
     let atlas_sample = textureSample(atlas_texture, atlas_sampler, input.tex_coords);
 
-    // For emojis (use_texture_color > 0.5): use texture RGB directly
-    // For regular text: use vertex color RGB (tinting white text)
-    let is_emoji = input.use_texture_color > 0.5;
-    let final_rgb = select(input.color.rgb, atlas_sample.rgb, is_emoji);
+    // For emojis (use_texture_color > 0.5): use texture RGB and alpha directly
+    if input.use_texture_color > 0.5 {
+        return vec4<f32>(atlas_sample.rgb, input.color.a * atlas_sample.a);
+    }
+
+    // For regular text: fill color RGB (tinting white text), with the
+    // atlas alpha as the coverage mask
+    let color = paint(input);
+    let text_alpha = text_coverage(atlas_sample.a, color.rgb);
+    return vec4<f32>(color.rgb, color.a * text_alpha);
+}
+
+// LCD subpixel output for dual-source blending: `color` is premultiplied and
+// `coverage` holds how much of the background each channel lets through
+struct SubpixelOutput {
+    @location(0) color: vec4<f32>,
+    @location(0) @second_blend_source coverage: vec4<f32>,
+}
+
+// Horizontal distance between subpixel centers, in atlas texels (glyphs are
+// rasterized at one texel per physical pixel)
+const SUBPIXEL_OFFSET: f32 = 1.0 / 3.0;
+
+@fragment
+fn fs_subpixel(input: VertexOutput) -> SubpixelOutput {
+    var output: SubpixelOutput;
+    // Coverage at the red, green and blue stripes of the pixel (RGB order),
+    // with the grayscale glyph's filtering smoothing the color fringes
+    let step = vec2<f32>(SUBPIXEL_OFFSET / f32(textureDimensions(atlas_texture).x), 0.0);
+    let atlas_sample = textureSample(atlas_texture, atlas_sampler, input.tex_coords);
+    let red = textureSample(atlas_texture, atlas_sampler, input.tex_coords - step).a;
+    let blue = textureSample(atlas_texture, atlas_sampler, input.tex_coords + step).a;
+
+    if input.use_texture_color > 0.5 {
+        let alpha = input.color.a * atlas_sample.a;
+        output.color = vec4<f32>(atlas_sample.rgb * alpha, alpha);
+        output.coverage = vec4<f32>(alpha);
+        return output;
+    }
 
-    // Use atlas alpha as mask, modulated by vertex alpha
-    let text_alpha = atlas_sample.a;
-    let final_color = vec4<f32>(final_rgb, input.color.a * text_alpha);
+    let fill = paint(input);
+    let color = fill.rgb;
+    let coverage = vec3<f32>(
+        text_coverage(red, color),
+        text_coverage(atlas_sample.a, color),
+        text_coverage(blue, color),
+    ) * fill.a;
 
-    return final_color;
+    output.color = vec4<f32>(color * coverage, max(coverage.r, max(coverage.g, coverage.b)));
+    output.coverage = vec4<f32>(coverage, output.color.a);
+    return output;
 }
@@ -3,10 +3,14 @@
 //! This backend uses wgpu for cross-platform rendering (Metal, Vulkan, D3D12, WebGPU).
 //! It handles text rendering using our glyph atlas system.
 
-use crate::image::LoadedImage;
-use crate::render::RenderCommand;
+use crate::geometry::{Rect, Transform2D};
+use crate::image::{CompressedFormat, CompressionFamily, ImageUploadOptions, LoadedImage};
+use crate::logging::verbose;
+use crate::render::{image_uvs, nine_patch_pieces, validate_commands, RenderCommand, Sampling};
 use crate::text::atlas::{GlyphAtlas, GlyphRasterizer};
-use crate::text::{FontDescriptor, TextLayoutConfig, TextAlign, WhiteSpace, WordBreak, TextOverflow};
+use crate::text::{Direction, FontDescriptor, LayoutCluster, LayoutLine, RichTextLayout, TextAlign, TextAnchor, TextLayout, TextLayoutConfig, TextOverflow, TextRun, WhiteSpace, WordBreak};
+use crate::text::spacing::Spacing;
+use crate::text::{ShapingCache, ShapingCacheMetrics, ShapingKey};
 use std::collections::HashMap;
 use std::error::Error;
 use wgpu::util::DeviceExt;
@@ -24,6 +28,49 @@ use crate::text::atlas::LinuxGlyphRasterizer;
 use crate::text::atlas::WindowsGlyphRasterizer;
 
 
+/// The present mode for a vsync setting. The Auto modes fall back to a
+/// mode every surface supports, so either can be applied at any time.
+fn present_mode(vsync: bool) -> wgpu::PresentMode {
+    if vsync {
+        wgpu::PresentMode::AutoVsync
+    } else {
+        wgpu::PresentMode::AutoNoVsync
+    }
+}
+
+/// Color space of the swapchain the frame is presented in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SurfaceColorSpace {
+    /// 8-bit sRGB; colors outside the sRGB gamut are clipped
+    #[default]
+    Srgb,
+    /// Half-float extended-range linear sRGB (scRGB): components below 0
+    /// and above 1 reach colors outside sRGB, such as all of Display-P3.
+    /// Colors within sRGB look the same as on an sRGB surface.
+    ExtendedSrgb,
+}
+
+/// Swapchain format for the formats a surface offers, and the color space
+/// it puts the frame in.
+///
+/// Wide gamut takes a half-float format, which wgpu presents as extended
+/// linear sRGB (EDR on Metal, scRGB on DX12, EXTENDED_SRGB_LINEAR on
+/// Vulkan) and only offers where the display path supports it. Otherwise
+/// the first sRGB format is taken, or the first format at all.
+fn choose_surface_format(formats: &[wgpu::TextureFormat], wide_gamut: bool) -> (wgpu::TextureFormat, SurfaceColorSpace) {
+    if wide_gamut && formats.contains(&wgpu::TextureFormat::Rgba16Float) {
+        return (wgpu::TextureFormat::Rgba16Float, SurfaceColorSpace::ExtendedSrgb);
+    }
+    let format = formats.iter().find(|f| f.is_srgb()).copied().unwrap_or(formats[0]);
+    (format, SurfaceColorSpace::Srgb)
+}
+
+/// Whether a target stores linear values, so blending in it happens in
+/// linear light: sRGB formats encode on write, half-float ones don't encode
+fn blends_in_linear_light(format: wgpu::TextureFormat) -> bool {
+    format.is_srgb() || format == wgpu::TextureFormat::Rgba16Float
+}
+
 /// Surface configuration for wgpu
 pub struct SurfaceConfig {
     pub width: u32,
@@ -32,6 +79,178 @@ pub struct SurfaceConfig {
     pub vsync: bool,
     pub low_power_gpu: bool,
     pub allow_software_fallback: bool,
+    /// Blend text in linear light, as sRGB surfaces already do. Surfaces
+    /// without an sRGB format (such as DX12 swapchains) otherwise blend
+    /// encoded values, which makes dark text on light backgrounds look
+    /// heavier and blurrier. The background under text isn't known, so the
+    /// correction assumes it contrasts with the text; text over mid-tones
+    /// comes out slightly thinner than exact linear blending would draw it.
+    pub gamma_correct_text: bool,
+    /// LCD subpixel antialiasing for text, blended in linear light. Needs
+    /// dual-source blending and an opaque surface, and falls back to
+    /// grayscale antialiasing elsewhere. Sharper on RGB-striped LCDs, but
+    /// fringes colors on other panels, under rotation and in screenshots
+    /// scaled afterwards.
+    pub subpixel_text: bool,
+    /// Present in extended-range sRGB where the surface supports it, so
+    /// colors outside sRGB aren't clipped (see `SurfaceColorSpace`).
+    /// Falls back to sRGB; `WgpuBackend::surface_color_space` tells which
+    /// was chosen. Uses twice the swapchain memory.
+    pub wide_gamut: bool,
+    /// Take every color and image of the frame as Display-P3 rather than
+    /// sRGB, the way colors picked on a P3 display are meant. They are
+    /// converted when presented: to their full saturation on an extended
+    /// surface, clipped to the nearest sRGB color on an sRGB one.
+    pub display_p3_colors: bool,
+}
+
+/// Fragment entry point of the text pipeline for a target, and whether it
+/// corrects coverage for gamma.
+///
+/// sRGB and half-float targets already blend in linear light, so only
+/// targets that blend encoded values need the correction. Subpixel coverage can't be kept per
+/// channel in a translucent surface, so it needs an opaque one.
+fn text_shading(
+    gamma_correct: bool,
+    subpixel: bool,
+    format: wgpu::TextureFormat,
+    alpha_mode: wgpu::CompositeAlphaMode,
+    dual_source_blending: bool,
+) -> (&'static str, bool) {
+    let subpixel = subpixel && dual_source_blending && alpha_mode == wgpu::CompositeAlphaMode::Opaque;
+    let correct_gamma = (gamma_correct || subpixel) && !blends_in_linear_light(format);
+    (if subpixel { "fs_subpixel" } else { "fs_main" }, correct_gamma)
+}
+
+/// Device limits and optional adapter features, for callers that need to
+/// size resources up front (e.g. downscale images before upload)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuLimits {
+    /// Largest width or height of a 2D texture, in pixels
+    pub max_texture_dimension_2d: u32,
+    pub max_bind_groups: u32,
+    pub timestamp_queries: bool,
+    pub bc_compression: bool,
+    pub etc2_compression: bool,
+    pub astc_compression: bool,
+}
+
+/// What the last presented frame drew and how long it took, for
+/// performance tuning
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStats {
+    /// Draw calls issued, stencil clip masks included
+    pub draw_calls: u32,
+    /// Triangles drawn by those calls
+    pub triangles: u32,
+    /// Frame time in milliseconds: the GPU time of the frame's render pass
+    /// when timestamp queries are available, otherwise the wall-clock time
+    /// of submitting and presenting it
+    pub frame_time_ms: f32,
+    /// Whether `frame_time_ms` was measured with GPU timestamps
+    pub gpu_timed: bool,
+}
+
+/// Draw calls and triangles of a prepared frame
+fn count_draws(ops: &[PreparedOp]) -> (u32, u32) {
+    ops.iter().fold((0, 0), |(draw_calls, triangles), op| match op {
+        PreparedOp::SetScissor { .. } | PreparedOp::SetStencilRef { .. } => (draw_calls, triangles),
+        PreparedOp::DrawGeometry { index_count, .. }
+        | PreparedOp::DrawCoverage { index_count, .. }
+        | PreparedOp::DrawStencil { index_count, .. } => (draw_calls + 1, triangles + index_count / 3),
+        PreparedOp::DrawText { vertex_count, .. }
+        | PreparedOp::DrawImage { vertex_count, .. }
+        | PreparedOp::DrawLayer { vertex_count, .. } => (draw_calls + 1, triangles + vertex_count / 3),
+    })
+}
+
+/// Timestamp queries around the frame render pass. Results are read back
+/// without blocking, so the time reported lags a frame or two behind.
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    /// Set from resolving the queries until their readback has been read;
+    /// frames in between aren't timed
+    in_flight: bool,
+    /// Set by the map callback once the readback buffer can be read
+    ready: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// Last measured render pass time in milliseconds
+    last_ms: Option<f32>,
+}
+
+impl GpuTimer {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: 2 * std::mem::size_of::<u64>() as u64,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        Self {
+            query_set,
+            resolve_buffer: buffer("Timestamp Resolve Buffer", wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC),
+            readback_buffer: buffer("Timestamp Readback Buffer", wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ),
+            period: queue.get_timestamp_period(),
+            in_flight: false,
+            ready: Default::default(),
+            last_ms: None,
+        }
+    }
+
+    /// Timestamp writes for the frame render pass, or None while the last
+    /// measurement is still being read back
+    fn timestamp_writes(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        (!self.in_flight).then_some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        })
+    }
+
+    /// Copy the frame's timestamps to the readback buffer, after the timed pass
+    fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, self.readback_buffer.size());
+        self.in_flight = true;
+    }
+
+    /// Start reading the timestamps back, after the resolving commands are submitted
+    fn map(&self) {
+        let ready = self.ready.clone();
+        self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            if result.is_ok() {
+                ready.store(true, std::sync::atomic::Ordering::Release);
+            }
+        });
+    }
+
+    /// Pick up the measurement being read back if it has arrived
+    fn poll(&mut self, device: &wgpu::Device) {
+        if !self.in_flight {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+        if !self.ready.swap(false, std::sync::atomic::Ordering::Acquire) {
+            return;
+        }
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let ticks: &[u64] = bytemuck::cast_slice(&data);
+            self.last_ms = Some(ticks[1].saturating_sub(ticks[0]) as f32 * self.period / 1_000_000.0);
+        }
+        self.readback_buffer.unmap();
+        self.in_flight = false;
+    }
 }
 
 /// Scissor rect for clipping
@@ -68,6 +287,64 @@ fn clamp_scissor_to_viewport(rect: ScissorRect, viewport_width: u32, viewport_he
     }
 }
 
+/// Scissor rect around a rect (in physical pixels) after `transform`: the
+/// bounding box of its corners, rounded outwards and cut off at the origin
+fn transformed_scissor(transform: &Transform2D, x: f32, y: f32, width: f32, height: f32) -> ScissorRect {
+    let bounds = transform.bounds(Rect::new(x, y, width, height));
+    let left = bounds.x.floor().max(0.0);
+    let top = bounds.y.floor().max(0.0);
+    let right = (bounds.x + bounds.width).ceil().max(left);
+    let bottom = (bounds.y + bounds.height).ceil().max(top);
+    ScissorRect {
+        x: left as u32,
+        y: top as u32,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    }
+}
+
+/// An inner shadow with its lengths converted to physical pixels
+fn scale_inner_shadow(shadow: &crate::render::InnerShadow, scale: f32) -> crate::render::InnerShadow {
+    crate::render::InnerShadow {
+        blur: shadow.blur * scale,
+        spread: shadow.spread * scale,
+        offset_x: shadow.offset_x * scale,
+        offset_y: shadow.offset_y * scale,
+        color: shadow.color,
+    }
+}
+
+/// A box shadow with its lengths converted to physical pixels
+fn scale_box_shadow(shadow: &crate::render::BoxShadow, scale: f32) -> crate::render::BoxShadow {
+    crate::render::BoxShadow {
+        offset_x: shadow.offset_x * scale,
+        offset_y: shadow.offset_y * scale,
+        blur_radius: shadow.blur_radius * scale,
+        spread: shadow.spread * scale,
+        color: shadow.color,
+    }
+}
+
+/// Vertices with their alpha multiplied by the current `SetOpacity`
+fn with_opacity(mut vertices: Vec<crate::render::Vertex>, opacity: f32) -> Vec<crate::render::Vertex> {
+    if opacity < 1.0 {
+        for vertex in &mut vertices {
+            vertex.color[3] *= opacity;
+        }
+    }
+    vertices
+}
+
+/// A border with its lengths converted to physical pixels
+fn scale_border(border: &crate::render::Border, scale: f32) -> crate::render::Border {
+    crate::render::Border {
+        width: border.width * scale,
+        dash_length: border.dash_length * scale,
+        gap_length: border.gap_length * scale,
+        ..border.clone()
+    }
+}
+
 /// Scroll offset for scroll views (in logical pixels)
 #[derive(Debug, Clone, Copy)]
 struct ScrollOffset {
@@ -81,12 +358,104 @@ struct ScrollOffset {
     offset_y: f32,
 }
 
+/// Blink state of the caret drawn in the last frame
+#[derive(Debug, Clone, Copy)]
+struct CaretBlink {
+    /// Caret x, y and height (logical pixels); moving it restarts the blink
+    position: (f32, f32, f32),
+    blink_ms: u32,
+    /// Frame time (seconds) the current blink started
+    since: f32,
+}
+
+/// Optional compressed texture features, requested when the adapter has them
+/// Samples per gradient in the gradient ramp texture
+const GRADIENT_RAMP_WIDTH: u32 = 256;
+
+/// Gradients the ramp texture holds per frame, one per row; gradient text
+/// past these falls back to colors sampled at glyph corners
+const GRADIENT_RAMP_ROWS: u32 = 64;
+
+const COMPRESSED_TEXTURE_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_COMPRESSION_BC
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ETC2)
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
+fn compressed_texture_format(format: CompressedFormat) -> wgpu::TextureFormat {
+    use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+    match format {
+        CompressedFormat::Bc1RgbaUnormSrgb => TextureFormat::Bc1RgbaUnormSrgb,
+        CompressedFormat::Bc3RgbaUnormSrgb => TextureFormat::Bc3RgbaUnormSrgb,
+        CompressedFormat::Bc4RUnorm => TextureFormat::Bc4RUnorm,
+        CompressedFormat::Bc5RgUnorm => TextureFormat::Bc5RgUnorm,
+        CompressedFormat::Bc7RgbaUnormSrgb => TextureFormat::Bc7RgbaUnormSrgb,
+        CompressedFormat::Etc2Rgb8UnormSrgb => TextureFormat::Etc2Rgb8UnormSrgb,
+        CompressedFormat::Etc2Rgba8UnormSrgb => TextureFormat::Etc2Rgba8UnormSrgb,
+        CompressedFormat::Astc4x4RgbaUnormSrgb => TextureFormat::Astc { block: AstcBlock::B4x4, channel: AstcChannel::UnormSrgb },
+        CompressedFormat::Astc8x8RgbaUnormSrgb => TextureFormat::Astc { block: AstcBlock::B8x8, channel: AstcChannel::UnormSrgb },
+    }
+}
+
 /// GPU texture resource for loaded images
 struct GpuTexture {
     texture: wgpu::Texture,
-    bind_group: wgpu::BindGroup,
+    /// One bind group per `Sampling`, indexed by it
+    bind_groups: [wgpu::BindGroup; 3],
     width: u32,
     height: u32,
+    /// Whether the texture holds premultiplied alpha
+    premultiplied: bool,
+}
+
+impl GpuTexture {
+    /// The bind group drawing this texture with `sampling`
+    fn bind_group(&self, sampling: Sampling) -> &wgpu::BindGroup {
+        &self.bind_groups[sampling as usize]
+    }
+}
+
+/// Create the image samplers shared by every texture, indexed by `Sampling`
+fn create_image_samplers(device: &wgpu::Device) -> [wgpu::Sampler; 3] {
+    let sampler = |label, filter, mipmap_filter, lod_max_clamp| device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some(label),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter,
+        lod_max_clamp,
+        ..Default::default()
+    });
+    [
+        // Nearest never leaves the base level, so pixel art stays crisp
+        sampler("Image Sampler (Nearest)", wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest, 0.0),
+        sampler("Image Sampler (Linear)", wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest, 32.0),
+        sampler("Image Sampler (Trilinear)", wgpu::FilterMode::Linear, wgpu::FilterMode::Linear, 32.0),
+    ]
+}
+
+/// Create the bind groups drawing `view` with each of the image samplers
+fn image_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    samplers: &[wgpu::Sampler; 3],
+    view: &wgpu::TextureView,
+    label: &str,
+) -> [wgpu::BindGroup; 3] {
+    samplers.each_ref().map(|sampler| device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    }))
 }
 
 /// Stencil clip state for rounded corner clipping
@@ -260,11 +629,22 @@ enum PreparedOp {
         index_count: u32,
     },
 
-    /// Draw to stencil buffer for rounded clip
+    /// Draw geometry whose texcoords carry edge distances (arcs) using the
+    /// coverage pipeline, which antialiases the edges
+    DrawCoverage {
+        vertex_buffer_idx: usize,
+        index_buffer_idx: usize,
+        index_count: u32,
+    },
+
+    /// Draw a rounded clip mask to the stencil buffer, raising the pixels at
+    /// the current stencil reference one level (or lowering them back when
+    /// `pop` is set)
     DrawStencil {
         vertex_buffer_idx: usize,
         index_buffer_idx: usize,
         index_count: u32,
+        pop: bool,
     },
 
     /// Draw text using the text pipeline (non-indexed, vertex-only)
@@ -278,9 +658,47 @@ enum PreparedOp {
         vertex_buffer_idx: usize,
         vertex_count: u32,
         texture_id: u32,
+        sampling: Sampling,
+    },
+
+    /// Composite an opacity layer's target (indexed like
+    /// `PreparedFrame::layers`) using the image pipeline
+    DrawLayer {
+        layer: usize,
+        vertex_buffer_idx: usize,
+        vertex_count: u32,
     },
 }
 
+/// A clip region opened while preparing a frame, so PopClip knows what to undo
+enum OpenClip {
+    /// A PushClip scissor rect, on the scissor stack
+    Scissor,
+    /// A PushRoundedClip stencil mask, drawn again on pop to lower it
+    Rounded {
+        vertex_buffer_idx: usize,
+        index_buffer_idx: usize,
+        index_count: u32,
+    },
+}
+
+/// A PushOpacityLayer open while preparing a frame, holding the state of
+/// what it was pushed over until PopOpacityLayer restores it
+struct OpenLayer {
+    alpha: f32,
+    /// Ops of the enclosing pass, which the composite is added to
+    ops: Vec<PreparedOp>,
+    /// Clips of the enclosing pass; the layer starts with none, on a
+    /// cleared stencil
+    clip_stack: Vec<OpenClip>,
+    scissor_stack: Vec<ScissorRect>,
+}
+
+/// Stencil level of pixels inside every open rounded clip
+fn rounded_clip_depth(clip_stack: &[OpenClip]) -> u32 {
+    clip_stack.iter().filter(|clip| matches!(clip, OpenClip::Rounded { .. })).count() as u32
+}
+
 /// A prepared frame containing all draw operations and their buffers.
 /// This is created during the preparation phase (before the render pass)
 /// and executed during the render pass.
@@ -290,6 +708,10 @@ struct PreparedFrame {
 
     /// All prepared operations in order
     ops: Vec<PreparedOp>,
+
+    /// Ops of each opacity layer, rendered into their targets before `ops`.
+    /// Inner layers come before the layers they're composited into.
+    layers: Vec<Vec<PreparedOp>>,
 }
 
 /// A cached render region for regional re-rendering.
@@ -398,15 +820,24 @@ pub struct WgpuBackend {
     stencil_texture: Option<wgpu::Texture>,
     stencil_view: Option<wgpu::TextureView>,
     stencil_pipeline: Option<wgpu::RenderPipeline>,
+    /// Undoes a `stencil_pipeline` mask when its rounded clip is popped
+    stencil_pop_pipeline: Option<wgpu::RenderPipeline>,
     stencil_clip_state: StencilClipState,
 
     // Render pipeline for text
     text_pipeline: Option<wgpu::RenderPipeline>,
     text_bind_group: Option<wgpu::BindGroup>,
     atlas_texture: Option<wgpu::Texture>,
+    /// Colors of this frame's gradient text fills, one gradient per row
+    gradient_ramp: Option<wgpu::Texture>,
+    /// Rows of `gradient_ramp` used this frame
+    gradient_ramp_rows: u32,
 
     // Render pipeline for colored geometry (triangles, rectangles)
     geometry_pipeline: Option<wgpu::RenderPipeline>,
+    /// Geometry pipeline that antialiases edges by the distances in the
+    /// texcoords (arcs)
+    coverage_pipeline: Option<wgpu::RenderPipeline>,
 
     // Glyph atlas (platform-specific rasterizers)
     #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -429,11 +860,19 @@ pub struct WgpuBackend {
     #[cfg(target_os = "windows")]
     rasterizer: WindowsGlyphRasterizer,
 
+    // Laid-out DrawText and measured text, reused while the string, font
+    // and layout stay the same
+    shaping_cache: ShapingCache<VisibleText>,
+
     // Configuration
     width: u32,
     height: u32,
     scale_factor: f64,
 
+    // Set while the window is minimized / has a zero-area surface.
+    // width/height keep the last valid size so rendering resumes cleanly.
+    zero_sized: bool,
+
     // Clipping state - stack of scissor rects for nested clipping
     scissor_stack: Vec<ScissorRect>,
 
@@ -442,8 +881,12 @@ pub struct WgpuBackend {
 
     // Image textures - map from texture_id to GPU texture
     image_textures: HashMap<u32, GpuTexture>,
+    // Back buffers of double-buffered textures, keyed by the ID they alternate under
+    spare_textures: HashMap<u32, GpuTexture>,
     image_pipeline: Option<wgpu::RenderPipeline>,
     image_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    /// Samplers shared by all image textures, indexed by `Sampling`
+    image_samplers: Option<[wgpu::Sampler; 3]>,
     next_texture_id: u32,
 
     // Buffer pool for reusing GPU buffers across frames
@@ -456,6 +899,53 @@ pub struct WgpuBackend {
     // Frame counter for dirty tracking
     frame_counter: u64,
 
+    // Number of render pipelines compiled so far, counted at each
+    // create_render_pipeline (observability for prewarm)
+    pipelines_created: u32,
+
+    // Frame clock driving engine-managed animations (e.g. Spinner), set
+    // from `clock_now` at the start of each frame
+    #[cfg(not(target_arch = "wasm32"))]
+    clock_start: std::time::Instant,
+    frame_time: f32,
+
+    // Set by `init_headless`: the frame clock stays at 0, so a frame depends
+    // only on its commands
+    headless: bool,
+
+    // Set when the last prepared frame contained animated commands
+    continuous_redraw: bool,
+
+    // Blinking caret from the last prepared frame, if it had one
+    caret_blink: Option<CaretBlink>,
+
+    // PushTransform matrix in effect while a frame is prepared, in physical
+    // pixels; `screen_to_ndc` applies it to every vertex
+    transform: Transform2D,
+
+    // Frame-sized targets opacity layers render into, one per layer in the
+    // frame, kept between frames
+    opacity_layers: Vec<GpuTexture>,
+
+    // Set when cached glyphs or fonts were dropped; the next frame ignores
+    // its scissor rect so no stale text survives outside it
+    force_full_frame: bool,
+
+    // Text blending requested by the SurfaceConfig
+    gamma_correct_text: bool,
+    subpixel_text: bool,
+
+    // Color space the surface presents in, and whether the frame holds
+    // Display-P3 colors the blit converts to it
+    color_space: SurfaceColorSpace,
+    display_p3_colors: bool,
+
+    // Counts and timing of the last presented frame. GPU timing starts once
+    // the stats are first asked for, so it costs nothing until then.
+    last_stats: RenderStats,
+    stats_queried: std::sync::atomic::AtomicBool,
+    gpu_timer: Option<GpuTimer>,
+
     // Persistent frame texture for partial rendering optimization.
     // We render to this texture (with scissor for partial updates),
     // then blit to the swapchain. This avoids swapchain buffer issues
@@ -492,7 +982,10 @@ impl WgpuBackend {
             text_pipeline: None,
             text_bind_group: None,
             atlas_texture: None,
+            gradient_ramp: None,
+            gradient_ramp_rows: 0,
             geometry_pipeline: None,
+            coverage_pipeline: None,
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             glyph_atlas: GlyphAtlas::new(2048, 2048),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -509,23 +1002,45 @@ impl WgpuBackend {
             glyph_atlas: GlyphAtlas::new(2048, 2048),
             #[cfg(target_os = "windows")]
             rasterizer: WindowsGlyphRasterizer::new(),
+            shaping_cache: ShapingCache::new(crate::text::DEFAULT_SHAPING_CACHE_CAPACITY),
             width: 0,
             height: 0,
             scale_factor: 1.0,
+            zero_sized: false,
             scissor_stack: Vec::new(),
             scroll_offset_stack: Vec::new(),
             image_textures: HashMap::new(),
+            spare_textures: HashMap::new(),
             image_pipeline: None,
             image_bind_group_layout: None,
+            image_samplers: None,
             next_texture_id: 1,
             stencil_texture: None,
             stencil_view: None,
             stencil_pipeline: None,
+            stencil_pop_pipeline: None,
             stencil_clip_state: StencilClipState::default(),
             buffer_pool: BufferPool::new(),
             // 64MB budget for region textures (~4-6 full-screen textures at 1080p)
             region_cache: RegionCache::new(64 * 1024 * 1024),
             frame_counter: 0,
+            pipelines_created: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            clock_start: std::time::Instant::now(),
+            frame_time: 0.0,
+            headless: false,
+            continuous_redraw: false,
+            caret_blink: None,
+            transform: Transform2D::IDENTITY,
+            opacity_layers: Vec::new(),
+            force_full_frame: false,
+            gamma_correct_text: false,
+            subpixel_text: false,
+            color_space: SurfaceColorSpace::Srgb,
+            display_p3_colors: false,
+            last_stats: RenderStats::default(),
+            stats_queried: Default::default(),
+            gpu_timer: None,
             frame_texture: None,
             frame_texture_view: None,
             blit_pipeline: None,
@@ -535,11 +1050,47 @@ impl WgpuBackend {
         }
     }
 
+    /// Whether rendering is suspended because the surface has zero area
+    /// (window minimized). Frames submitted meanwhile are skipped.
+    pub fn is_zero_sized(&self) -> bool {
+        self.zero_sized
+    }
+
     /// Get the current scale factor (for HiDPI displays)
     pub fn scale_factor(&self) -> f64 {
         self.scale_factor
     }
 
+    /// Whether the last rendered frame contained engine-animated commands
+    /// (such as `Spinner`). Callers should keep requesting redraws while
+    /// this is true; it clears once a frame without them is rendered.
+    pub fn needs_continuous_redraw(&self) -> bool {
+        self.continuous_redraw
+    }
+
+    /// Milliseconds until the caret from the last rendered frame shows or
+    /// hides, or None if that frame had no blinking caret. Callers should
+    /// request a redraw after this long; no redraws are needed otherwise.
+    pub fn redraw_after_ms(&self) -> Option<u32> {
+        let caret = self.caret_blink?;
+        crate::render::caret_next_toggle_ms(caret.blink_ms, self.clock_now() - caret.since)
+    }
+
+    /// Seconds on the frame clock: since the backend was created, or on the
+    /// web (which has no `std::time::Instant`) since the page loaded
+    fn clock_now(&self) -> f32 {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.clock_start.elapsed().as_secs_f32()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            web_sys::window()
+                .and_then(|window| window.performance())
+                .map_or(self.frame_time, |performance| (performance.now() / 1000.0) as f32)
+        }
+    }
+
     /// Measure the width of a string using the rasterizer
     #[cfg(target_os = "windows")]
     pub fn measure_string(&mut self, text: &str, font: &crate::text::FontDescriptor) -> f32 {
@@ -566,7 +1117,22 @@ impl WgpuBackend {
             let surface = self.instance.create_surface(window)?;
             std::mem::transmute::<wgpu::Surface<'_>, wgpu::Surface<'static>>(surface)
         };
-        self.init_with_surface(surface, config).await
+        self.init_with_surface(surface, config).await?;
+
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        if self.color_space == SurfaceColorSpace::ExtendedSrgb {
+            if let Ok(handle) = raw_window_handle::HasWindowHandle::window_handle(window) {
+                let view = match handle.as_raw() {
+                    raw_window_handle::RawWindowHandle::AppKit(h) => Some(h.ns_view.as_ptr()),
+                    raw_window_handle::RawWindowHandle::UiKit(h) => Some(h.ui_view.as_ptr()),
+                    _ => None,
+                };
+                if let Some(view) = view {
+                    unsafe { tag_metal_layer_extended_srgb(view) };
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Initialize the backend with a surface
@@ -580,6 +1146,9 @@ impl WgpuBackend {
         self.width = config.width;
         self.height = config.height;
         self.scale_factor = config.scale_factor;
+        self.gamma_correct_text = config.gamma_correct_text;
+        self.subpixel_text = config.subpixel_text;
+        self.display_p3_colors = config.display_p3_colors;
 
         // Request adapter with configured power preference
         let power_preference = if config.low_power_gpu {
@@ -597,7 +1166,7 @@ impl WgpuBackend {
             .await
             .ok_or("Failed to find suitable GPU adapter. Set allow_software_fallback=true to enable software rendering.")?;
 
-        println!("wgpu adapter: {:?}", adapter.get_info());
+        verbose!("wgpu adapter: {:?}", adapter.get_info());
 
         // Request device and queue
         // Use downlevel limits for emulators (GLES), full limits for real devices (Vulkan)
@@ -615,11 +1184,18 @@ impl WgpuBackend {
             wgpu::Limits::default()
         };
 
+        // Subpixel text blends each channel's coverage separately; timestamp
+        // queries time frames for `last_frame_stats`
+        let mut optional_features = COMPRESSED_TEXTURE_FEATURES | wgpu::Features::TIMESTAMP_QUERY;
+        if config.subpixel_text {
+            optional_features |= wgpu::Features::DUAL_SOURCE_BLENDING;
+        }
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Centered Engine Device"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: adapter.features() & optional_features,
                     required_limits,
                     memory_hints: Default::default(),
                 },
@@ -629,10 +1205,9 @@ impl WgpuBackend {
 
         // Configure surface
         let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps.formats.iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
+        let (surface_format, color_space) = choose_surface_format(&surface_caps.formats, config.wide_gamut);
+        self.color_space = color_space;
+        verbose!("wgpu surface format: {:?} ({:?})", surface_format, color_space);
 
         // Prefer alpha modes that support transparency (PreMultiplied > PostMultiplied > Auto > Opaque)
         let alpha_mode = surface_caps.alpha_modes.iter()
@@ -647,11 +1222,7 @@ impl WgpuBackend {
             format: surface_format,
             width: config.width,
             height: config.height,
-            present_mode: if config.vsync {
-                wgpu::PresentMode::AutoVsync
-            } else {
-                wgpu::PresentMode::AutoNoVsync
-            },
+            present_mode: present_mode(config.vsync),
             alpha_mode,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -659,25 +1230,101 @@ impl WgpuBackend {
 
         surface.configure(&device, &surface_config);
 
+        self.init_render_resources(adapter, device, queue, surface_config)?;
+        self.surface = Some(surface);
+
+        Ok(())
+    }
+
+    /// Initialize the backend without a window, rendering into the frame
+    /// texture only (see `render_to_pixels`).
+    ///
+    /// Uses a GPU adapter when there is one and the platform's software
+    /// adapter otherwise, so it works on CI machines without a GPU. The
+    /// frame format is always RGBA8 sRGB and animations are frozen at their
+    /// first frame, so identical commands render identical pixels on a
+    /// given adapter.
+    pub async fn init_headless(&mut self, width: u32, height: u32) -> Result<(), Box<dyn Error>> {
+        let mut adapter = None;
+        for force_fallback_adapter in [false, true] {
+            adapter = self.instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter,
+                })
+                .await;
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter = adapter.ok_or("No GPU or software adapter available for headless rendering")?;
+        verbose!("wgpu headless adapter: {:?}", adapter.get_info());
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Centered Headless Device"),
+                    required_features: adapter.features() & COMPRESSED_TEXTURE_FEATURES,
+                    required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+                    memory_hints: Default::default(),
+                },
+                None,
+            )
+            .await?;
+
+        // Never configured on a surface; describes the frame texture
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self.headless = true;
+        self.init_render_resources(adapter, device, queue, surface_config)
+    }
+
+    /// Create the atlas, pipelines and size-dependent textures for a device.
+    ///
+    /// Shared by surface initialization and offscreen (test) setups; the
+    /// surface itself is configured and stored by the caller.
+    fn init_render_resources(
+        &mut self,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface_config: wgpu::SurfaceConfiguration,
+    ) -> Result<(), Box<dyn Error>> {
         // Create atlas texture
         let atlas_texture = self.create_atlas_texture(&device)?;
+        let gradient_ramp = self.create_gradient_ramp_texture(&device);
 
         // Create text rendering pipeline
         let (text_pipeline, text_bind_group) = self.create_text_pipeline(
             &device,
             &surface_config,
             &atlas_texture,
+            &gradient_ramp,
         )?;
 
-        // Create geometry rendering pipeline
-        let geometry_pipeline = self.create_geometry_pipeline(&device, &surface_config)?;
+        // Create geometry rendering pipelines, plain and antialiased by coverage
+        let geometry_pipeline = self.create_geometry_pipeline(&device, &surface_config, "fs_main")?;
+        let coverage_pipeline = self.create_geometry_pipeline(&device, &surface_config, "fs_coverage")?;
 
         // Create image rendering pipeline
         let (image_pipeline, image_bind_group_layout) = self.create_image_pipeline(&device, &surface_config)?;
+        let image_samplers = create_image_samplers(&device);
 
         // Create stencil texture and pipeline for rounded corner clipping
-        let (stencil_texture, stencil_view) = self.create_stencil_texture(&device, config.width, config.height);
-        let stencil_pipeline = self.create_stencil_pipeline(&device, &surface_config)?;
+        let (stencil_texture, stencil_view) = self.create_stencil_texture(&device, surface_config.width, surface_config.height);
+        let stencil_pipeline = self.create_stencil_pipeline(&device, &surface_config, wgpu::StencilOperation::IncrementClamp)?;
+        let stencil_pop_pipeline = self.create_stencil_pipeline(&device, &surface_config, wgpu::StencilOperation::DecrementClamp)?;
 
         // Create frame texture and blit pipeline for partial rendering optimization
         let (frame_texture, frame_texture_view) = self.create_frame_texture(&device, &surface_config);
@@ -687,17 +1334,20 @@ impl WgpuBackend {
         self.adapter = Some(adapter);
         self.device = Some(device);
         self.queue = Some(queue);
-        self.surface = Some(surface);
         self.surface_config = Some(surface_config);
         self.atlas_texture = Some(atlas_texture);
+        self.gradient_ramp = Some(gradient_ramp);
         self.text_pipeline = Some(text_pipeline);
         self.text_bind_group = Some(text_bind_group);
         self.geometry_pipeline = Some(geometry_pipeline);
+        self.coverage_pipeline = Some(coverage_pipeline);
         self.image_pipeline = Some(image_pipeline);
         self.image_bind_group_layout = Some(image_bind_group_layout);
+        self.image_samplers = Some(image_samplers);
         self.stencil_texture = Some(stencil_texture);
         self.stencil_view = Some(stencil_view);
         self.stencil_pipeline = Some(stencil_pipeline);
+        self.stencil_pop_pipeline = Some(stencil_pop_pipeline);
         self.frame_texture = Some(frame_texture);
         self.frame_texture_view = Some(frame_texture_view);
         self.blit_pipeline = Some(blit_pipeline);
@@ -727,6 +1377,25 @@ impl WgpuBackend {
         Ok(texture)
     }
 
+    /// Texture the text shader looks gradient colors up in: one row of
+    /// `GRADIENT_RAMP_WIDTH` samples per gradient
+    fn create_gradient_ramp_texture(&self, device: &wgpu::Device) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Gradient Ramp Texture"),
+            size: wgpu::Extent3d {
+                width: GRADIENT_RAMP_WIDTH,
+                height: GRADIENT_RAMP_ROWS,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
     /// Create or recreate the stencil texture for rounded corner clipping
     fn create_stencil_texture(&self, device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
@@ -749,11 +1418,17 @@ impl WgpuBackend {
         (texture, view)
     }
 
-    /// Create the stencil-write pipeline for drawing rounded rect masks
+    /// Create a stencil-write pipeline for drawing rounded rect masks
+    ///
+    /// Masks only touch pixels whose stencil equals the reference, i.e. the
+    /// pixels inside every enclosing rounded clip, and apply `pass_op` to
+    /// them: IncrementClamp to push a clip one level deeper, DecrementClamp
+    /// to pop it again.
     fn create_stencil_pipeline(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         surface_config: &wgpu::SurfaceConfiguration,
+        pass_op: wgpu::StencilOperation,
     ) -> Result<wgpu::RenderPipeline, Box<dyn Error>> {
         // Shader that outputs a dummy color (write_mask prevents actual writes)
         let shader_source = r#"
@@ -834,16 +1509,16 @@ impl WgpuBackend {
                 depth_compare: wgpu::CompareFunction::Always,
                 stencil: wgpu::StencilState {
                     front: wgpu::StencilFaceState {
-                        compare: wgpu::CompareFunction::Always,
+                        compare: wgpu::CompareFunction::Equal, // Inside the enclosing clips
                         fail_op: wgpu::StencilOperation::Keep,
                         depth_fail_op: wgpu::StencilOperation::Keep,
-                        pass_op: wgpu::StencilOperation::Replace, // Write reference value
+                        pass_op,
                     },
                     back: wgpu::StencilFaceState {
-                        compare: wgpu::CompareFunction::Always,
+                        compare: wgpu::CompareFunction::Equal,
                         fail_op: wgpu::StencilOperation::Keep,
                         depth_fail_op: wgpu::StencilOperation::Keep,
-                        pass_op: wgpu::StencilOperation::Replace,
+                        pass_op,
                     },
                     read_mask: 0xFF,
                     write_mask: 0xFF,
@@ -854,6 +1529,7 @@ impl WgpuBackend {
             multiview: None,
             cache: None,
         });
+        self.pipelines_created += 1;
 
         Ok(pipeline)
     }
@@ -877,7 +1553,7 @@ impl WgpuBackend {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: surface_config.format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
 
@@ -887,7 +1563,7 @@ impl WgpuBackend {
 
     /// Create the blit pipeline for copying frame texture to swapchain.
     fn create_blit_pipeline(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         surface_config: &wgpu::SurfaceConfiguration,
     ) -> Result<(wgpu::RenderPipeline, wgpu::BindGroupLayout, wgpu::Sampler), Box<dyn Error>> {
@@ -925,6 +1601,22 @@ impl WgpuBackend {
             fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
                 return textureSample(t_frame, s_frame, in.tex_coord);
             }
+
+            // Linear Display-P3 to linear sRGB (columns); P3 colors outside
+            // sRGB come out below 0 or above 1
+            const DISPLAY_P3_TO_SRGB = mat3x3<f32>(
+                vec3<f32>(1.2249401, -0.0420569, -0.0196376),
+                vec3<f32>(-0.2249404, 1.0420571, -0.0786361),
+                vec3<f32>(0.0, 0.0, 1.0982735),
+            );
+
+            // The frame holds Display-P3 colors. The conversion is linear,
+            // so premultiplied colors convert as they are.
+            @fragment
+            fn fs_display_p3(in: VertexOutput) -> @location(0) vec4<f32> {
+                let color = textureSample(t_frame, s_frame, in.tex_coord);
+                return vec4<f32>(DISPLAY_P3_TO_SRGB * color.rgb, color.a);
+            }
         "#;
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -982,7 +1674,7 @@ impl WgpuBackend {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point: if self.display_p3_colors { "fs_display_p3" } else { "fs_main" },
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
                     blend: None,
@@ -1004,6 +1696,7 @@ impl WgpuBackend {
             multiview: None,
             cache: None,
         });
+        self.pipelines_created += 1;
 
         Ok((pipeline, bind_group_layout, sampler))
     }
@@ -1033,13 +1726,38 @@ impl WgpuBackend {
     }
 
     fn create_text_pipeline(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         surface_config: &wgpu::SurfaceConfiguration,
         atlas_texture: &wgpu::Texture,
+        gradient_ramp: &wgpu::Texture,
     ) -> Result<(wgpu::RenderPipeline, wgpu::BindGroup), Box<dyn Error>> {
+        let (entry_point, correct_gamma) = text_shading(
+            self.gamma_correct_text,
+            self.subpixel_text,
+            surface_config.format,
+            surface_config.alpha_mode,
+            device.features().contains(wgpu::Features::DUAL_SOURCE_BLENDING),
+        );
+        // Subpixel output is premultiplied; its second source holds the
+        // per-channel coverage the background is scaled by
+        let blend = if entry_point == "fs_subpixel" {
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrc1,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            }
+        } else {
+            wgpu::BlendState::ALPHA_BLENDING
+        };
+        let constants = HashMap::from([("correct_gamma".to_string(), if correct_gamma { 1.0 } else { 0.0 })]);
+
         // Create texture view and sampler
         let texture_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let ramp_view = gradient_ramp.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("Glyph Atlas Sampler"),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -1070,6 +1788,16 @@ impl WgpuBackend {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -1086,6 +1814,10 @@ impl WgpuBackend {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&ramp_view),
+                },
             ],
         });
 
@@ -1118,19 +1850,25 @@ impl WgpuBackend {
                         1 => Float32x2,  // tex_coords
                         2 => Float32x4,  // color
                         3 => Float32,    // use_texture_color (1.0 for emoji, 0.0 for text)
+                        4 => Float32x4,  // gradient_box
+                        5 => Float32x4,  // gradient
+                        6 => Float32,    // gradient_row
                     ],
                 }],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point,
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
-                compilation_options: Default::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -1163,15 +1901,19 @@ impl WgpuBackend {
             multiview: None,
             cache: None,
         });
+        self.pipelines_created += 1;
 
         Ok((pipeline, bind_group))
     }
 
-    /// Create the geometry rendering pipeline for colored triangles and rectangles
+    /// Create a geometry rendering pipeline for colored triangles and
+    /// rectangles, shading fragments with `fragment_entry` from
+    /// geometry.wgsl
     fn create_geometry_pipeline(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         surface_config: &wgpu::SurfaceConfiguration,
+        fragment_entry: &str,
     ) -> Result<wgpu::RenderPipeline, Box<dyn Error>> {
         // Shader source
         let shader_source = include_str!("shaders/geometry.wgsl");
@@ -1207,7 +1949,7 @@ impl WgpuBackend {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point: fragment_entry,
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -1246,13 +1988,14 @@ impl WgpuBackend {
             multiview: None,
             cache: None,
         });
+        self.pipelines_created += 1;
 
         Ok(pipeline)
     }
 
     /// Create the image rendering pipeline
     fn create_image_pipeline(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         surface_config: &wgpu::SurfaceConfiguration,
     ) -> Result<(wgpu::RenderPipeline, wgpu::BindGroupLayout), Box<dyn Error>> {
@@ -1307,7 +2050,7 @@ impl WgpuBackend {
                         0 => Float32x2,  // position
                         1 => Float32x2,  // tex_coords
                         2 => Float32x4,  // color
-                        3 => Float32,    // use_texture_color (unused for images, but needed for struct alignment)
+                        3 => Float32,    // use_texture_color (1.0 if the texture is premultiplied)
                     ],
                 }],
                 compilation_options: Default::default(),
@@ -1317,7 +2060,8 @@ impl WgpuBackend {
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    // The shader outputs premultiplied color for both kinds of texture
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: Default::default(),
@@ -1353,25 +2097,51 @@ impl WgpuBackend {
             multiview: None,
             cache: None,
         });
+        self.pipelines_created += 1;
 
         Ok((pipeline, bind_group_layout))
     }
 
-    /// Load an image from bytes and return its texture ID
+    /// Load an image from bytes and return its texture ID. The pixels are
+    /// uploaded as decoded; see `load_image_with_options` to premultiply.
     pub fn load_image(&mut self, image: &LoadedImage) -> Result<u32, Box<dyn Error>> {
+        self.load_image_with_options(image, ImageUploadOptions::default())
+    }
+
+    /// `load_image`, optionally premultiplying straight-alpha data on upload
+    pub fn load_image_with_options(&mut self, image: &LoadedImage, options: ImageUploadOptions) -> Result<u32, Box<dyn Error>> {
+        if options.premultiply && !image.premultiplied {
+            let mut image = image.clone();
+            image.premultiply();
+            return self.load_image_with_options(&image, ImageUploadOptions { premultiply: false, ..options });
+        }
+
+        let mip_level_count = if options.mipmaps { image.mip_level_count() } else { 1 };
+        let gpu_texture = self.create_image_texture(image.width, image.height, mip_level_count, image.premultiplied)?;
+        self.write_image(&gpu_texture.texture, image)?;
+
+        // Assign texture ID and store
+        let texture_id = self.next_texture_id;
+        self.next_texture_id += 1;
+        self.image_textures.insert(texture_id, gpu_texture);
+
+        Ok(texture_id)
+    }
+
+    /// Create an empty RGBA image texture and its bind groups
+    fn create_image_texture(&self, width: u32, height: u32, mip_level_count: u32, premultiplied: bool) -> Result<GpuTexture, Box<dyn Error>> {
         let device = self.device.as_ref().ok_or("Device not initialized")?;
-        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
         let bind_group_layout = self.image_bind_group_layout.as_ref().ok_or("Image bind group layout not initialized")?;
+        let samplers = self.image_samplers.as_ref().ok_or("Image samplers not initialized")?;
 
-        // Create GPU texture
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Image Texture"),
             size: wgpu::Extent3d {
-                width: image.width,
-                height: image.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -1379,11 +2149,20 @@ impl WgpuBackend {
             view_formats: &[],
         });
 
-        // Upload pixel data
-        queue.write_texture(
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_groups = image_bind_groups(device, bind_group_layout, samplers, &texture_view, "Image Bind Group");
+
+        Ok(GpuTexture { texture, bind_groups, width, height, premultiplied })
+    }
+
+    /// Upload pixel data covering the whole of an RGBA image texture,
+    /// regenerating its smaller mip levels if it has any
+    fn write_image(&self, texture: &wgpu::Texture, image: &LoadedImage) -> Result<(), Box<dyn Error>> {
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+        let write_level = |level: u32, image: &LoadedImage| queue.write_texture(
             wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
+                texture,
+                mip_level: level,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
@@ -1400,43 +2179,102 @@ impl WgpuBackend {
             },
         );
 
-        // Create texture view and sampler
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Image Sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-
-        // Create bind group for this texture
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Image Bind Group"),
-            layout: bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
+        write_level(0, image);
+        if texture.mip_level_count() > 1 {
+            for (level, mip) in image.mip_chain().iter().enumerate() {
+                write_level(level as u32 + 1, mip);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the device was created with the feature `format` needs
+    pub fn supports_compressed_format(&self, format: CompressedFormat) -> bool {
+        let feature = match format.family() {
+            CompressionFamily::Bc => wgpu::Features::TEXTURE_COMPRESSION_BC,
+            CompressionFamily::Etc2 => wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+            CompressionFamily::Astc => wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+        };
+        self.device.as_ref().is_some_and(|device| device.features().contains(feature))
+    }
+
+    /// Upload block-compressed texture data as-is and return its texture ID.
+    ///
+    /// `data` holds `mip_levels` levels, largest first, each tightly packed
+    /// in block rows. Fails if the device lacks the format's feature.
+    pub fn load_compressed_texture(
+        &mut self,
+        format: CompressedFormat,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+    ) -> Result<u32, Box<dyn Error>> {
+        format.validate(data.len(), width, height, mip_levels)?;
+        if !self.supports_compressed_format(format) {
+            return Err(format!("{:?} textures are not supported by this GPU", format).into());
+        }
+
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+        let bind_group_layout = self.image_bind_group_layout.as_ref().ok_or("Image bind group layout not initialized")?;
+        let samplers = self.image_samplers.as_ref().ok_or("Image samplers not initialized")?;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Compressed Image Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: mip_levels,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: compressed_texture_format(format),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        // Upload each level; small levels still cover at least one whole block
+        let (block_width, block_height) = format.block_dimensions();
+        let mut offset = 0;
+        for level in 0..mip_levels {
+            let (blocks_x, blocks_y) = format.blocks((width >> level).max(1), (height >> level).max(1));
+            let level_size = (blocks_x * blocks_y * format.block_bytes()) as usize;
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
                 },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                &data[offset..offset + level_size],
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(blocks_x * format.block_bytes()),
+                    rows_per_image: Some(blocks_y),
                 },
-            ],
-        });
+                wgpu::Extent3d {
+                    width: blocks_x * block_width,
+                    height: blocks_y * block_height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            offset += level_size;
+        }
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_groups = image_bind_groups(device, bind_group_layout, samplers, &texture_view, "Compressed Image Bind Group");
 
-        // Assign texture ID and store
         let texture_id = self.next_texture_id;
         self.next_texture_id += 1;
 
         self.image_textures.insert(texture_id, GpuTexture {
             texture,
-            bind_group,
-            width: image.width,
-            height: image.height,
+            bind_groups,
+            width,
+            height,
+            premultiplied: false,
         });
 
         Ok(texture_id)
@@ -1445,45 +2283,57 @@ impl WgpuBackend {
     /// Unload an image texture
     pub fn unload_image(&mut self, texture_id: u32) {
         self.image_textures.remove(&texture_id);
+        self.spare_textures.remove(&texture_id);
     }
 
     /// Update an existing texture with new image data (for video/camera frames)
     /// This avoids the overhead of creating new textures for each frame.
     /// If the dimensions don't match, creates a new texture.
     pub fn update_texture(&mut self, texture_id: u32, image: &LoadedImage) -> Result<u32, Box<dyn Error>> {
-        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
-
         // Check if we can update in-place (same dimensions)
         if let Some(existing) = self.image_textures.get(&texture_id) {
             if existing.width == image.width && existing.height == image.height {
                 // Update existing texture in-place
-                queue.write_texture(
-                    wgpu::ImageCopyTexture {
-                        texture: &existing.texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    &image.data,
-                    wgpu::ImageDataLayout {
-                        offset: 0,
-                        bytes_per_row: Some(image.width * 4),
-                        rows_per_image: Some(image.height),
-                    },
-                    wgpu::Extent3d {
-                        width: image.width,
-                        height: image.height,
-                        depth_or_array_layers: 1,
-                    },
-                );
+                self.write_image(&existing.texture, image)?;
+                if let Some(existing) = self.image_textures.get_mut(&texture_id) {
+                    existing.premultiplied = image.premultiplied;
+                }
                 return Ok(texture_id);
             }
         }
 
         // Dimensions changed or texture doesn't exist - create new one
-        // (remove old one first if it exists)
-        self.image_textures.remove(&texture_id);
-        self.load_image(image)
+        // (remove old one first if it exists). Frames are replaced too often
+        // to be worth building mip chains for.
+        self.unload_image(texture_id);
+        self.load_image_with_options(image, ImageUploadOptions { mipmaps: false, ..Default::default() })
+    }
+
+    /// `update_texture` for content replaced every frame (a Go-drawn canvas).
+    ///
+    /// The texture ping-pongs between two backing textures: the upload goes
+    /// to the one the previous frame didn't sample, which then becomes the
+    /// texture `DrawImage` draws. An upload never has to wait for, or tear
+    /// under, the frame still using the other one. Like `update_texture`, a
+    /// size change replaces the texture and returns a new ID.
+    pub fn update_texture_double_buffered(&mut self, texture_id: u32, image: &LoadedImage) -> Result<u32, Box<dyn Error>> {
+        let same_size = |tex: &GpuTexture| tex.width == image.width && tex.height == image.height;
+        if !self.image_textures.get(&texture_id).is_some_and(same_size) {
+            return self.update_texture(texture_id, image);
+        }
+
+        let mut back = match self.spare_textures.remove(&texture_id) {
+            Some(spare) if same_size(&spare) => spare,
+            _ => self.create_image_texture(image.width, image.height, 1, image.premultiplied)?,
+        };
+        back.premultiplied = image.premultiplied;
+        self.write_image(&back.texture, image)?;
+
+        // Present the new content; the old front becomes the next back buffer
+        if let Some(front) = self.image_textures.insert(texture_id, back) {
+            self.spare_textures.insert(texture_id, front);
+        }
+        Ok(texture_id)
     }
 
     /// Get texture dimensions for a loaded image
@@ -1491,6 +2341,27 @@ impl WgpuBackend {
         self.image_textures.get(&texture_id).map(|tex| (tex.width, tex.height))
     }
 
+    /// Whether `texture_id` refers to a loaded texture
+    pub fn has_texture(&self, texture_id: u32) -> bool {
+        self.image_textures.contains_key(&texture_id)
+    }
+
+    /// Limits of the initialized device, and which optional features the
+    /// adapter supports. None before `init`.
+    pub fn gpu_limits(&self) -> Option<GpuLimits> {
+        let device = self.device.as_ref()?;
+        let limits = device.limits();
+        let features = self.adapter.as_ref().map(|a| a.features()).unwrap_or_else(|| device.features());
+        Some(GpuLimits {
+            max_texture_dimension_2d: limits.max_texture_dimension_2d,
+            max_bind_groups: limits.max_bind_groups,
+            timestamp_queries: features.contains(wgpu::Features::TIMESTAMP_QUERY),
+            bc_compression: features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+            etc2_compression: features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2),
+            astc_compression: features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC),
+        })
+    }
+
     /// Get the current window width in pixels
     pub fn get_width(&self) -> u32 {
         self.width
@@ -1508,6 +2379,7 @@ impl WgpuBackend {
     pub fn create_video_texture(&mut self, width: u32, height: u32) -> Result<u32, Box<dyn Error>> {
         let device = self.device.as_ref().ok_or("Device not initialized")?;
         let bind_group_layout = self.image_bind_group_layout.as_ref().ok_or("Image bind group layout not initialized")?;
+        let samplers = self.image_samplers.as_ref().ok_or("Image samplers not initialized")?;
 
         // Create GPU texture with COPY_DST for frequent updates
         let texture = device.create_texture(&wgpu::TextureDescriptor {
@@ -1525,33 +2397,9 @@ impl WgpuBackend {
             view_formats: &[],
         });
 
-        // Create texture view and sampler
+        // Create bind groups for this texture
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Video Sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-
-        // Create bind group for this texture
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Video Bind Group"),
-            layout: bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
+        let bind_groups = image_bind_groups(device, bind_group_layout, samplers, &texture_view, "Video Bind Group");
 
         // Assign texture ID and store
         let texture_id = self.next_texture_id;
@@ -1559,9 +2407,10 @@ impl WgpuBackend {
 
         self.image_textures.insert(texture_id, GpuTexture {
             texture,
-            bind_group,
+            bind_groups,
             width,
             height,
+            premultiplied: false,
         });
 
         Ok(texture_id)
@@ -1581,6 +2430,7 @@ impl WgpuBackend {
         let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
         let device = self.device.as_ref().ok_or("Device not initialized")?;
         let bind_group_layout = self.image_bind_group_layout.as_ref().ok_or("Image bind group layout not initialized")?;
+        let samplers = self.image_samplers.as_ref().ok_or("Image samplers not initialized")?;
 
         let gpu_texture = self.image_textures.get_mut(&texture_id)
             .ok_or("Texture not found")?;
@@ -1603,35 +2453,11 @@ impl WgpuBackend {
                 view_formats: &[],
             });
 
-            // Create new bind group
+            // Create new bind groups
             let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-                label: Some("Video Sampler"),
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                ..Default::default()
-            });
-
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Video Bind Group"),
-                layout: bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    },
-                ],
-            });
+            gpu_texture.bind_groups = image_bind_groups(device, bind_group_layout, samplers, &texture_view, "Video Bind Group");
 
             gpu_texture.texture = texture;
-            gpu_texture.bind_group = bind_group;
             gpu_texture.width = width;
             gpu_texture.height = height;
         }
@@ -1660,6 +2486,23 @@ impl WgpuBackend {
         Ok(())
     }
 
+    /// Forget loaded font faces and font lookups so fonts are resolved
+    /// again, e.g. after a font file is registered under an existing name
+    pub fn clear_font_caches(&mut self) {
+        #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows"))]
+        self.rasterizer.clear_font_cache();
+        self.shaping_cache.clear();
+        self.force_full_frame = true;
+    }
+
+    /// Drop all rasterized glyphs; text is rasterized again when next drawn
+    pub fn clear_glyph_atlas(&mut self) {
+        #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows"))]
+        self.glyph_atlas.clear();
+        self.shaping_cache.clear();
+        self.force_full_frame = true;
+    }
+
     /// Upload atlas texture to GPU if dirty
     fn upload_atlas_if_needed(&mut self) -> Result<(), Box<dyn Error>> {
         #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows"))]
@@ -1697,8 +2540,10 @@ impl WgpuBackend {
         Ok(())
     }
 
-    /// Convert screen coordinates to NDC (Normalized Device Coordinates)
+    /// Convert screen coordinates to NDC (Normalized Device Coordinates),
+    /// through the current transform
     fn screen_to_ndc(&self, x: f32, y: f32) -> [f32; 2] {
+        let (x, y) = self.transform.apply(x, y);
         let ndc_x = (x / self.width as f32) * 2.0 - 1.0;
         let ndc_y = 1.0 - (y / self.height as f32) * 2.0; // Y is flipped in NDC
         [ndc_x, ndc_y]
@@ -1739,7 +2584,7 @@ impl WgpuBackend {
         // If the frame size differs from our cached size, update our stored dimensions.
         // This ensures coordinate transformations use the correct values.
         if actual_width != self.width || actual_height != self.height {
-            println!("[wgpu] Frame texture size mismatch: cached {}x{}, actual {}x{} - updating",
+            verbose!("[wgpu] Frame texture size mismatch: cached {}x{}, actual {}x{} - updating",
                 self.width, self.height, actual_width, actual_height);
             self.width = actual_width;
             self.height = actual_height;
@@ -1821,6 +2666,9 @@ impl WgpuBackend {
             let full_height = actual_height;
             render_pass.set_scissor_rect(0, 0, full_width, full_height);
 
+            // Multiplier from SetOpacity
+            let mut opacity = 1.0f32;
+
             // Process render commands
             for cmd in commands {
                 match cmd {
@@ -1987,30 +2835,33 @@ impl WgpuBackend {
                         });
                         self.render_shadow(&mut render_pass, *x + scroll_dx, *y + scroll_dy, *width, *height, *blur, *color, *offset_x, *offset_y, *corner_radii)?;
                     }
-                    RenderCommand::DrawRect { x, y, width, height, color, corner_radii, rotation, border, gradient } => {
+                    RenderCommand::DrawRect { x, y, width, height, color, corner_radii, rotation, pivot, border, gradient, inner_shadow, shadow } => {
                         // Apply scroll offset
                         let (scroll_dx, scroll_dy) = self.scroll_offset_stack.iter().fold((0.0f32, 0.0f32), |(dx, dy), s| {
                             (dx - s.offset_x, dy - s.offset_y)
                         });
-                        self.render_rect(&mut render_pass, *x + scroll_dx, *y + scroll_dy, *width, *height, *color, *corner_radii, *rotation, border.as_ref(), gradient.as_ref())?;
+                        self.render_rect(&mut render_pass, *x + scroll_dx, *y + scroll_dy, *width, *height, *color, *corner_radii, *rotation, *pivot, border.as_ref(), gradient.as_ref(), inner_shadow.as_ref(), shadow.as_ref(), opacity)?;
                     }
                     RenderCommand::DrawTriangles { vertices, indices, .. } => {
                         // Note: DrawTriangles would need vertex transformation for scroll, skipping for now
                         self.render_triangles(&mut render_pass, vertices, indices)?;
                     }
-                    RenderCommand::DrawText { x, y, text, font, color, layout } => {
+                    RenderCommand::DrawText { x, y, text, font, color, layout, .. } => {
                         // Apply scroll offset
                         let (scroll_dx, scroll_dy) = self.scroll_offset_stack.iter().fold((0.0f32, 0.0f32), |(dx, dy), s| {
                             (dx - s.offset_x, dy - s.offset_y)
                         });
                         self.render_text(&mut render_pass, *x + scroll_dx, *y + scroll_dy, text, font, *color, layout)?;
                     }
-                    RenderCommand::DrawImage { x, y, width, height, texture_id, source_rect, corner_radii } => {
+                    RenderCommand::DrawImage { x, y, width, height, texture_id, source_rect, src, tint, corner_radii, opacity: image_opacity, rotation, pivot, sampling } => {
                         // Apply scroll offset
                         let (scroll_dx, scroll_dy) = self.scroll_offset_stack.iter().fold((0.0f32, 0.0f32), |(dx, dy), s| {
                             (dx - s.offset_x, dy - s.offset_y)
                         });
-                        self.render_image(&mut render_pass, *x + scroll_dx, *y + scroll_dy, *width, *height, *texture_id, source_rect.clone(), *corner_radii)?;
+                        self.render_image(&mut render_pass, *x + scroll_dx, *y + scroll_dy, *width, *height, *texture_id, *source_rect, *src, *tint, *corner_radii, opacity * image_opacity.clamp(0.0, 1.0), *rotation, *pivot, *sampling)?;
+                    }
+                    RenderCommand::SetOpacity(value) => {
+                        opacity = value.clamp(0.0, 1.0);
                     }
                     _ => {
                         // Ignore other commands for now
@@ -2028,18 +2879,31 @@ impl WgpuBackend {
     }
 
     /// Handle window resize - reconfigure surface with new dimensions
+    ///
+    /// A zero-area size (e.g. a minimized window on Windows) is not applied to
+    /// the surface. Instead rendering is skipped until a valid size arrives,
+    /// and the last valid size and resources are kept for the restore.
     pub fn resize(&mut self, width: u32, height: u32, scale_factor: f64) -> Result<(), Box<dyn Error>> {
+        if width == 0 || height == 0 {
+            self.zero_sized = true;
+            self.scale_factor = scale_factor;
+            return Ok(());
+        }
+        self.zero_sized = false;
+
         // Update stored dimensions
         self.width = width;
         self.height = height;
         self.scale_factor = scale_factor;
 
-        // Reconfigure the surface with new size
-        if let (Some(surface), Some(device), Some(config)) =
-            (&self.surface, &self.device, &mut self.surface_config) {
+        // Reconfigure the surface with new size (headless backends only
+        // have the config, which sizes the frame texture)
+        if let Some(config) = &mut self.surface_config {
             config.width = width.max(1);  // Ensure at least 1x1
             config.height = height.max(1);
-            surface.configure(device, config);
+            if let (Some(surface), Some(device)) = (&self.surface, &self.device) {
+                surface.configure(device, config);
+            }
         }
 
         // Recreate stencil texture with new dimensions
@@ -2065,6 +2929,43 @@ impl WgpuBackend {
         Ok(())
     }
 
+    /// Switch vsync on or off, reconfiguring the surface in place.
+    ///
+    /// Pipelines, textures and caches are kept. While there is no surface to
+    /// configure (minimized, or lost and not yet recreated) the setting is
+    /// stored and applied by the next reconfiguration.
+    pub fn set_present_mode(&mut self, vsync: bool) {
+        let Some(config) = self.surface_config.as_mut() else { return };
+        let mode = present_mode(vsync);
+        if config.present_mode == mode {
+            return;
+        }
+        config.present_mode = mode;
+        if self.zero_sized {
+            return;
+        }
+        if let (Some(surface), Some(device)) = (&self.surface, &self.device) {
+            surface.configure(device, config);
+        }
+    }
+
+    /// Acquire the next swapchain image, reconfiguring the surface and
+    /// retrying once if it was lost or outdated (e.g. after the app
+    /// returned from the background or the display mode changed)
+    fn acquire_frame(&self) -> Result<wgpu::SurfaceTexture, Box<dyn Error>> {
+        let surface = self.surface.as_ref().ok_or("Surface not initialized")?;
+        match surface.get_current_texture() {
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                let device = self.device.as_ref().ok_or("Device not initialized")?;
+                let config = self.surface_config.as_ref().ok_or("Surface not configured")?;
+                verbose!("[wgpu] Surface lost or outdated, reconfiguring");
+                surface.configure(device, config);
+                Ok(surface.get_current_texture()?)
+            }
+            result => Ok(result?),
+        }
+    }
+
     /// Prepare geometry vertices and indices for drawing.
     /// Returns (vertex_buffer_idx, index_buffer_idx, index_count).
     fn prepare_geometry(
@@ -2099,6 +3000,23 @@ impl WgpuBackend {
         (vertex_idx, index_idx, indices.len() as u32)
     }
 
+    /// Rotate screen-space vertices and convert them to NDC
+    fn rotated_ndc_vertices(
+        &self,
+        vertices: &[crate::render::Vertex],
+        rotation: crate::geometry::Rotation,
+    ) -> Vec<crate::render::Vertex> {
+        vertices.iter().map(|v| {
+            let (rx, ry) = rotation.apply(v.position[0], v.position[1]);
+            let ndc = self.screen_to_ndc(rx, ry);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect()
+    }
+
     /// Prepare a rectangle for drawing (handles scaling, rotation, borders).
     /// Returns prepared geometry indices.
     #[allow(clippy::too_many_arguments)]
@@ -2111,8 +3029,12 @@ impl WgpuBackend {
         color: u32,
         corner_radii: [f32; 4],
         rotation: f32,
+        pivot: Option<(f32, f32)>,
         border: Option<&crate::render::Border>,
         gradient: Option<&crate::render::Gradient>,
+        inner_shadow: Option<&crate::render::InnerShadow>,
+        shadow: Option<&crate::render::BoxShadow>,
+        opacity: f32,
     ) -> Vec<(usize, usize, u32)> {
         let scale = self.scale_factor as f32;
         let scaled_x = (x * scale).floor();
@@ -2128,6 +3050,24 @@ impl WgpuBackend {
 
         let mut results = Vec::new();
 
+        // Rotation about the pivot, shared by the fill, shadows and border
+        let rotation = crate::geometry::Rotation::about(
+            crate::geometry::Rect::new(scaled_x, scaled_y, scaled_width, scaled_height),
+            rotation, pivot.map(|(px, py)| (px * scale, py * scale)),
+        );
+
+        // Drop shadow behind everything else
+        if let Some(shadow) = shadow {
+            let (shadow_vertices, shadow_indices) = crate::geometry::box_shadow_rect(
+                scaled_x, scaled_y, scaled_width, scaled_height,
+                scaled_radii, &scale_box_shadow(shadow, scale),
+            );
+            if !shadow_indices.is_empty() {
+                let ndc_shadow_vertices = with_opacity(self.rotated_ndc_vertices(&shadow_vertices, rotation), opacity);
+                results.push(self.prepare_geometry(&ndc_shadow_vertices, &shadow_indices));
+            }
+        }
+
         // Generate fill geometry
         let (vertices, indices) = if let Some(gradient) = gradient {
             crate::geometry::gradient_rect(
@@ -2141,55 +3081,31 @@ impl WgpuBackend {
             )
         };
 
-        // Apply rotation and convert to NDC
-        let center_x = scaled_x + scaled_width / 2.0;
-        let center_y = scaled_y + scaled_height / 2.0;
-        let cos_r = rotation.cos();
-        let sin_r = rotation.sin();
+        let ndc_vertices = with_opacity(self.rotated_ndc_vertices(&vertices, rotation), opacity);
+        results.push(self.prepare_geometry(&ndc_vertices, &indices));
 
-        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
-            let (rx, ry) = if rotation.abs() > 0.0001 {
-                let dx = v.position[0] - center_x;
-                let dy = v.position[1] - center_y;
-                (center_x + dx * cos_r - dy * sin_r, center_y + dx * sin_r + dy * cos_r)
-            } else {
-                (v.position[0], v.position[1])
-            };
-            let ndc = self.screen_to_ndc(rx, ry);
-            crate::render::Vertex {
-                position: [ndc[0], ndc[1], 0.0],
-                texcoord: v.texcoord,
-                color: v.color,
+        // Inner shadow over the fill, under the border
+        if let Some(shadow) = inner_shadow {
+            let (shadow_vertices, shadow_indices) = crate::geometry::inner_shadow_rect(
+                scaled_x, scaled_y, scaled_width, scaled_height,
+                scaled_radii, &scale_inner_shadow(shadow, scale),
+            );
+            if !shadow_indices.is_empty() {
+                let ndc_shadow_vertices = with_opacity(self.rotated_ndc_vertices(&shadow_vertices, rotation), opacity);
+                results.push(self.prepare_geometry(&ndc_shadow_vertices, &shadow_indices));
             }
-        }).collect();
-
-        results.push(self.prepare_geometry(&ndc_vertices, &indices));
+        }
 
         // Generate border geometry if present
         if let Some(border) = border {
-            let scaled_border_width = border.width * scale;
-            let (border_vertices, border_indices) = crate::geometry::border_rect(
+            let (border_vertices, border_indices) = crate::geometry::styled_border(
                 scaled_x, scaled_y, scaled_width, scaled_height,
-                scaled_border_width, border.color, scaled_radii,
+                &scale_border(border, scale), scaled_radii,
             );
-
-            let ndc_border_vertices: Vec<crate::render::Vertex> = border_vertices.iter().map(|v| {
-                let (rx, ry) = if rotation.abs() > 0.0001 {
-                    let dx = v.position[0] - center_x;
-                    let dy = v.position[1] - center_y;
-                    (center_x + dx * cos_r - dy * sin_r, center_y + dx * sin_r + dy * cos_r)
-                } else {
-                    (v.position[0], v.position[1])
-                };
-                let ndc = self.screen_to_ndc(rx, ry);
-                crate::render::Vertex {
-                    position: [ndc[0], ndc[1], 0.0],
-                    texcoord: v.texcoord,
-                    color: v.color,
-                }
-            }).collect();
-
-            results.push(self.prepare_geometry(&ndc_border_vertices, &border_indices));
+            if !border_indices.is_empty() {
+                let ndc_border_vertices = with_opacity(self.rotated_ndc_vertices(&border_vertices, rotation), opacity);
+                results.push(self.prepare_geometry(&ndc_border_vertices, &border_indices));
+            }
         }
 
         results
@@ -2291,60 +3207,423 @@ impl WgpuBackend {
         self.prepare_geometry(&ndc_vertices, &indices)
     }
 
-    /// Prepare text for drawing, returning buffer index and vertex count.
-    /// Returns None if text is empty or preparation fails.
+    /// Prepare spinner arc geometry rotated by the current frame time
+    fn prepare_spinner(
+        &mut self,
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        thickness: f32,
+        color: u32,
+        speed: f32,
+    ) -> (usize, usize, u32) {
+        let scale = self.scale_factor as f32;
+        let thickness = if thickness > 0.0 { thickness } else { radius * 0.2 };
+        let rotation = crate::render::spinner_rotation(speed, self.frame_time);
+        let sweep = crate::render::SPINNER_SWEEP * std::f32::consts::TAU;
+
+        let (vertices, indices) = crate::geometry::arc(
+            center_x * scale, center_y * scale,
+            radius * scale, thickness * scale,
+            rotation, sweep, color, 32,
+        );
+
+        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
+            let ndc = self.screen_to_ndc(v.position[0], v.position[1]);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect();
+
+        self.prepare_geometry(&ndc_vertices, &indices)
+    }
+
+    /// Prepare an antialiased arc for the coverage pipeline. Returns None if
+    /// nothing is visible.
     #[allow(clippy::too_many_arguments)]
-    fn prepare_text(
+    fn prepare_arc(
         &mut self,
-        x: f32,
-        y: f32,
-        text: &str,
-        font: &FontDescriptor,
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        thickness: f32,
+        start_angle: f32,
+        end_angle: f32,
         color: u32,
-        layout: &TextLayoutConfig,
-    ) -> Option<(usize, u32)> {
-        if text.is_empty() {
+    ) -> Option<(usize, usize, u32)> {
+        let scale = self.scale_factor as f32;
+        let (vertices, indices) = crate::geometry::antialiased_arc(
+            cx * scale, cy * scale,
+            radius * scale, thickness * scale,
+            start_angle, end_angle, color,
+        );
+        if indices.is_empty() {
             return None;
         }
 
-        // Extract RGBA from u32 color
-        let r = ((color >> 24) & 0xFF) as f32 / 255.0;
-        let g = ((color >> 16) & 0xFF) as f32 / 255.0;
-        let b = ((color >> 8) & 0xFF) as f32 / 255.0;
-        let a = (color & 0xFF) as f32 / 255.0;
-        let text_color = [r, g, b, a];
+        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
+            let ndc = self.screen_to_ndc(v.position[0], v.position[1]);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect();
 
+        Some(self.prepare_geometry(&ndc_vertices, &indices))
+    }
+
+    /// Prepare a capped line. Returns None if nothing is visible.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_line(
+        &mut self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        width: f32,
+        color: u32,
+        cap: crate::render::LineCap,
+    ) -> Option<(usize, usize, u32)> {
         let scale = self.scale_factor as f32;
-        let font_size = font.size * scale;
-        let scaled_x = x * scale;
-        let scaled_y = y * scale;
-        let scaled_max_width = layout.max_width.map(|w| w * scale);
+        let (vertices, indices) = crate::geometry::capped_line(
+            x1 * scale, y1 * scale,
+            x2 * scale, y2 * scale,
+            width * scale, color, cap,
+        );
+        if indices.is_empty() {
+            return None;
+        }
 
-        let scaled_font = FontDescriptor {
-            source: font.source.clone(),
-            weight: font.weight,
-            style: font.style,
-            size: font_size,
-        };
+        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
+            let ndc = self.screen_to_ndc(v.position[0], v.position[1]);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect();
 
-        // Get font metrics
-        let (ascent, descent) = self.rasterizer.get_font_metrics(&scaled_font);
-        let actual_font_height = ascent + descent;
-        let line_height_px = actual_font_height * layout.line_height;
-        let letter_spacing_px = layout.letter_spacing * font_size;
-        let word_spacing_px = layout.word_spacing * font_size;
+        Some(self.prepare_geometry(&ndc_vertices, &indices))
+    }
 
-        // Compute font ID
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        let mut hasher = DefaultHasher::new();
+    /// Prepare a filled and/or stroked path, moved by (dx, dy) logical
+    /// pixels. Returns None if nothing is visible.
+    fn prepare_path(
+        &mut self,
+        segments: &[crate::render::PathSegment],
+        fill: Option<u32>,
+        fill_rule: crate::render::FillRule,
+        stroke: Option<&crate::render::Stroke>,
+        dx: f32,
+        dy: f32,
+    ) -> Option<(usize, usize, u32)> {
+        let scale = self.scale_factor as f32;
+        let (vertices, indices) = crate::geometry::tessellated_path(segments, fill, fill_rule, stroke, scale);
+        if indices.is_empty() {
+            return None;
+        }
+
+        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
+            let ndc = self.screen_to_ndc(v.position[0] + dx * scale, v.position[1] + dy * scale);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect();
+
+        Some(self.prepare_geometry(&ndc_vertices, &indices))
+    }
+
+    /// Prepare a dashed focus ring, marching its dashes by `speed` logical
+    /// pixels per second of frame time. Returns None if nothing is visible.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_focus_ring(
+        &mut self,
+        rect: crate::geometry::Rect,
+        corner_radii: [f32; 4],
+        color: u32,
+        thickness: f32,
+        dash: f32,
+        offset: f32,
+        speed: f32,
+    ) -> Option<(usize, usize, u32)> {
+        let scale = self.scale_factor as f32;
+        let thickness = if thickness > 0.0 { thickness } else { crate::render::FOCUS_RING_THICKNESS };
+        let dash = if dash > 0.0 { dash } else { crate::render::FOCUS_RING_DASH };
+        let offset = crate::render::focus_ring_dash_offset(offset, speed, self.frame_time);
+
+        let scaled = crate::geometry::Rect::new(
+            rect.x * scale, rect.y * scale,
+            rect.width * scale, rect.height * scale,
+        );
+        let (vertices, indices) = crate::geometry::dashed_outline(
+            scaled, corner_radii.map(|r| r * scale),
+            thickness * scale, dash * scale, offset * scale, color,
+        );
+        if indices.is_empty() {
+            return None;
+        }
+
+        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
+            let ndc = self.screen_to_ndc(v.position[0], v.position[1]);
+            crate::render::Vertex {
+                position: [ndc[0], ndc[1], 0.0],
+                texcoord: v.texcoord,
+                color: v.color,
+            }
+        }).collect();
+
+        Some(self.prepare_geometry(&ndc_vertices, &indices))
+    }
+
+    /// Prepare text for drawing, returning buffer index and vertex count.
+    /// Returns None if text is empty or preparation fails.
+    ///
+    /// A gradient spans `gradient_bounds` (logical pixels) when given,
+    /// otherwise the text's own box.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_text(
+        &mut self,
+        x: f32,
+        y: f32,
+        text: &str,
+        font: &FontDescriptor,
+        color: u32,
+        layout: &TextLayoutConfig,
+        gradient: Option<&crate::render::Gradient>,
+        gradient_bounds: Option<Rect>,
+    ) -> Option<(usize, u32)> {
+        let gradient = gradient.map(|gradient| TextGradient {
+            gradient,
+            bounds: gradient_bounds,
+            ramp_row: self.upload_gradient_ramp(gradient),
+        });
+        let vertices = self.layout_text_vertices(x, y, text, font, color, layout, gradient.as_ref())?;
+
+        // Upload to buffer pool
+        let device = self.device.as_ref()?;
+        let queue = self.queue.as_ref()?;
+        let vertex_idx = self.buffer_pool.prepare_vertex_buffer(
+            device,
+            queue,
+            bytemuck::cast_slice(&vertices),
+        );
+
+        Some((vertex_idx, vertices.len() as u32))
+    }
+
+    /// Write a gradient's colors into the next free row of the ramp
+    /// texture, returning the row. None once the rows for this frame run
+    /// out (or before the GPU is set up).
+    fn upload_gradient_ramp(&mut self, gradient: &crate::render::Gradient) -> Option<u32> {
+        if self.gradient_ramp_rows >= GRADIENT_RAMP_ROWS {
+            return None;
+        }
+        let queue = self.queue.as_ref()?;
+        let texture = self.gradient_ramp.as_ref()?;
+        let row = self.gradient_ramp_rows;
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: row, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &crate::geometry::gradient_ramp(gradient, GRADIENT_RAMP_WIDTH as usize),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(GRADIENT_RAMP_WIDTH * 4),
+                rows_per_image: Some(1),
+            },
+            wgpu::Extent3d {
+                width: GRADIENT_RAMP_WIDTH,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.gradient_ramp_rows += 1;
+        Some(row)
+    }
+
+    /// Lay out text and build its glyph quads (rasterizing glyphs into the atlas).
+    /// Returns None if text is empty or produces no glyphs.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_text_vertices(
+        &mut self,
+        x: f32,
+        y: f32,
+        text: &str,
+        font: &FontDescriptor,
+        color: u32,
+        layout: &TextLayoutConfig,
+        gradient: Option<&TextGradient>,
+    ) -> Option<Vec<TextVertex>> {
+        if text.is_empty() {
+            return None;
+        }
+
+        // Extract RGBA from u32 color
+        let r = ((color >> 24) & 0xFF) as f32 / 255.0;
+        let g = ((color >> 16) & 0xFF) as f32 / 255.0;
+        let b = ((color >> 8) & 0xFF) as f32 / 255.0;
+        let a = (color & 0xFF) as f32 / 255.0;
+        let text_color = [r, g, b, a];
+
+        let scale = self.scale_factor as f32;
+        let scaled_x = x * scale;
+        let scaled_y = y * scale;
+        let scaled_max_width = layout.max_width.map(|w| w * scale);
+
+        let VisibleText { lines, direction, font_size, ascent, descent, line_height_px } =
+            self.layout_visible_lines(text, font, layout)?;
+        let spacing = Spacing::from_layout(layout, font_size);
+
+        // Generate vertices
+        let mut vertices: Vec<TextVertex> = Vec::new();
+        let line_count = lines.len();
+        let first_baseline_y = scaled_y + layout.first_baseline_offset(ascent, descent, line_count, line_height_px);
+
+        // Box the gradient spans: its bounds, or the text box - layout width
+        // (or widest line) by all lines
+        let (box_left, box_top, box_width, box_height) = match gradient.and_then(|g| g.bounds) {
+            Some(bounds) => (bounds.x * scale, bounds.y * scale, bounds.width * scale, bounds.height * scale),
+            None => (
+                scaled_x,
+                first_baseline_y - ascent,
+                scaled_max_width.unwrap_or_else(|| lines.iter().map(|l| l.width).fold(0.0, f32::max)),
+                ascent + descent + line_count.saturating_sub(1) as f32 * line_height_px,
+            ),
+        };
+        let paint = |px: f32, py: f32| -> [f32; 4] {
+            match gradient {
+                Some(g) => crate::geometry::compute_gradient_color(
+                    g.gradient, px - box_left, py - box_top, box_width, box_height,
+                ),
+                None => text_color,
+            }
+        };
+        // Evaluated per fragment from the ramp when it has a row
+        let (gradient_params, gradient_row) = match gradient {
+            Some(TextGradient { gradient, ramp_row: Some(row), .. }) => {
+                (TextVertex::gradient_params(gradient), (*row as f32 + 0.5) / GRADIENT_RAMP_ROWS as f32)
+            }
+            _ => ([0.0; 4], 0.0),
+        };
+
+        for (line_idx, line) in lines.iter().enumerate() {
+            let is_last_line = line_idx == line_count - 1;
+            let line_baseline_y = first_baseline_y + (line_idx as f32 * line_height_px);
+
+            let (line_offset, justify_extra_space) = line.alignment_offset(layout, direction, scaled_max_width, is_last_line);
+
+            let mut current_x = scaled_x + line_offset;
+            for (glyph_idx, glyph_info) in line.glyphs.iter().enumerate() {
+                let entry = glyph_info.entry;
+
+                let glyph_x = current_x + entry.bearing_x;
+                let glyph_y = line_baseline_y - entry.bearing_y;
+                let glyph_width = entry.width as f32;
+                let glyph_height = entry.height as f32;
+
+                // Corner colors: flat color, or the gradient sampled at each corner
+                // of the glyph quad in text-box space (interpolated per fragment),
+                // for when the gradient isn't evaluated per fragment
+                let [c_tl, c_tr, c_bl, c_br] = if glyph_info.is_emoji {
+                    [[1.0, 1.0, 1.0, a]; 4]
+                } else {
+                    [
+                        paint(glyph_x, glyph_y),
+                        paint(glyph_x + glyph_width, glyph_y),
+                        paint(glyph_x, glyph_y + glyph_height),
+                        paint(glyph_x + glyph_width, glyph_y + glyph_height),
+                    ]
+                };
+
+                let top_left = self.screen_to_ndc(glyph_x, glyph_y);
+                let top_right = self.screen_to_ndc(glyph_x + glyph_width, glyph_y);
+                let bottom_left = self.screen_to_ndc(glyph_x, glyph_y + glyph_height);
+                let bottom_right = self.screen_to_ndc(glyph_x + glyph_width, glyph_y + glyph_height);
+
+                let use_texture_color = if glyph_info.is_emoji { 1.0 } else { 0.0 };
+                let glyph_gradient = if glyph_info.is_emoji { [0.0; 4] } else { gradient_params };
+                let vertex = |position, tex_coords, color, px: f32, py: f32| TextVertex {
+                    position,
+                    tex_coords,
+                    color,
+                    use_texture_color,
+                    gradient_box: [px - box_left, py - box_top, box_width, box_height],
+                    gradient: glyph_gradient,
+                    gradient_row,
+                };
+                let (right, bottom) = (glyph_x + glyph_width, glyph_y + glyph_height);
+
+                // Triangle 1
+                vertices.push(vertex(top_left, [entry.u0, entry.v0], c_tl, glyph_x, glyph_y));
+                vertices.push(vertex(bottom_left, [entry.u0, entry.v1], c_bl, glyph_x, bottom));
+                vertices.push(vertex(top_right, [entry.u1, entry.v0], c_tr, right, glyph_y));
+                // Triangle 2
+                vertices.push(vertex(top_right, [entry.u1, entry.v0], c_tr, right, glyph_y));
+                vertices.push(vertex(bottom_left, [entry.u0, entry.v1], c_bl, glyph_x, bottom));
+                vertices.push(vertex(bottom_right, [entry.u1, entry.v1], c_br, right, bottom));
+
+                current_x += line.glyph_advance(glyph_idx, spacing, justify_extra_space);
+            }
+        }
+
+        if vertices.is_empty() {
+            return None;
+        }
+
+        Some(vertices)
+    }
+
+    /// Lay out text into the lines that are actually drawn.
+    ///
+    /// Applies `max_lines` and `max_height`; with `TextOverflow::Ellipsis`
+    /// the last visible line ends in "…" when lines were cut. Sizes in the
+    /// result are physical pixels.
+    fn layout_visible_lines(
+        &mut self,
+        text: &str,
+        font: &FontDescriptor,
+        layout: &TextLayoutConfig,
+    ) -> Option<VisibleText> {
+        let scale = self.scale_factor as f32;
+        let cache_key = ShapingKey::new(text, font, layout, scale);
+        if let Some(visible) = self.cached_visible_lines(&cache_key) {
+            return Some(visible);
+        }
+        let font_size = font.size * scale;
+        let scaled_max_width = layout.max_width.map(|w| w * scale);
+
+        let scaled_font = FontDescriptor {
+            source: font.source.clone(),
+            weight: font.weight,
+            style: font.style,
+            size: font_size,
+        };
+
+        // Get font metrics
+        let (ascent, descent) = self.rasterizer.get_font_metrics(&scaled_font);
+        let actual_font_height = ascent + descent;
+        let line_height_px = actual_font_height * layout.line_height;
+        let spacing = Spacing::from_layout(layout, font_size);
+
+        // Compute font ID
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
         scaled_font.cache_key().hash(&mut hasher);
         let font_id = hasher.finish();
 
         // Layout text into lines
         let all_lines = self.layout_text_lines(
             text, &scaled_font, font_id, font_size, scaled_max_width,
-            letter_spacing_px, word_spacing_px, layout, scale
+            spacing, layout, scale
         ).ok()?;
 
         // Calculate max lines
@@ -2372,139 +3651,417 @@ impl WgpuBackend {
         let needs_ellipsis = needs_line_ellipsis || needs_width_ellipsis;
 
         // Apply ellipsis if needed
-        let lines: Vec<TextLine> = if needs_ellipsis && max_lines > 0 {
+        let mut lines: Vec<TextLine> = if needs_ellipsis && max_lines > 0 {
             let mut truncated_lines: Vec<_> = all_lines.into_iter().take(max_lines).collect();
             if let Some(last_line) = truncated_lines.last_mut() {
-                let ellipsis_glyphs = self.rasterize_text_segment("…", &scaled_font, font_id, font_size).ok()?;
-                let ellipsis_width: f32 = ellipsis_glyphs.iter().map(|g| g.entry.advance).sum();
-
-                if let Some(max_w) = scaled_max_width {
-                    let target_width = max_w - ellipsis_width;
-                    if target_width > 0.0 {
-                        let mut current_width = 0.0;
-                        let mut truncate_index = 0;
-                        for (i, glyph_info) in last_line.glyphs.iter().enumerate() {
-                            let next_width = current_width + glyph_info.entry.advance;
-                            if next_width > target_width {
-                                truncate_index = i;
-                                break;
-                            }
-                            current_width = next_width;
-                            truncate_index = i + 1;
-                        }
-                        last_line.glyphs.truncate(truncate_index);
-                        last_line.glyphs.extend(ellipsis_glyphs);
-                        last_line.width = current_width + ellipsis_width;
-                    }
-                } else {
-                    last_line.glyphs.extend(ellipsis_glyphs);
-                    last_line.width += ellipsis_width;
-                }
+                self.append_ellipsis(text, last_line, scaled_max_width, &scaled_font, font_id, font_size, spacing).ok()?;
             }
             truncated_lines
         } else {
             all_lines.into_iter().take(max_lines).collect()
         };
 
-        // Generate vertices
-        let mut vertices: Vec<TextVertex> = Vec::new();
-        let line_count = lines.len();
+        // Reorder each line for display, now that truncation (which works on
+        // the text as written) is done
+        let direction = layout.base_direction.resolve(text);
+        let bidi = crate::text::bidi::BidiText::new(text, direction);
+        for line in &mut lines {
+            let offsets: Vec<usize> = line.glyphs.iter().map(|g| g.byte_index).collect();
+            let order = bidi.visual_order(line.range.clone(), &offsets);
+            let mut logical: Vec<Option<GlyphInfo>> = line.glyphs.drain(..).map(Some).collect();
+            line.glyphs.extend(order.into_iter().filter_map(|i| logical[i].take()));
+        }
 
-        for (line_idx, line) in lines.iter().enumerate() {
-            let is_last_line = line_idx == line_count - 1;
-            let line_baseline_y = scaled_y + ascent + (line_idx as f32 * line_height_px);
+        let visible = VisibleText { lines, direction, font_size, ascent, descent, line_height_px };
+        self.shaping_cache.insert(cache_key, visible.clone());
+        Some(visible)
+    }
 
-            let (line_x, justify_extra_space) = match layout.alignment {
-                TextAlign::Left => (scaled_x, 0.0),
-                TextAlign::Center => {
-                    let x = if let Some(max_w) = scaled_max_width {
-                        scaled_x + (max_w - line.width) / 2.0
-                    } else {
-                        scaled_x
-                    };
-                    (x, 0.0)
-                }
-                TextAlign::Right => {
-                    let x = if let Some(max_w) = scaled_max_width {
-                        scaled_x + max_w - line.width
-                    } else {
-                        scaled_x
-                    };
-                    (x, 0.0)
+    /// A cached layout of the text, with its glyphs looked up again so the
+    /// atlas counts them as used this frame. None, and the entry dropped,
+    /// if the atlas has since evicted one of them.
+    fn cached_visible_lines(&mut self, key: &ShapingKey) -> Option<VisibleText> {
+        let atlas = &mut self.glyph_atlas;
+        self.shaping_cache.get_refreshed(key, |visible| {
+            for glyph in visible.lines.iter_mut().flat_map(|line| line.glyphs.iter_mut()) {
+                let Some(entry) = atlas.get(&glyph.key) else { return false };
+                glyph.entry = *entry;
+            }
+            true
+        })
+    }
+
+    /// Keep up to `capacity` laid-out texts for reuse; 0 turns the cache off
+    pub fn set_shaping_cache_capacity(&mut self, capacity: usize) {
+        self.shaping_cache.set_capacity(capacity);
+    }
+
+    /// Drop all laid-out texts; text is laid out again when next drawn
+    pub fn clear_shaping_cache(&mut self) {
+        self.shaping_cache.clear();
+    }
+
+    /// Hits and misses of the laid-out text cache
+    pub fn shaping_cache_metrics(&self) -> &ShapingCacheMetrics {
+        self.shaping_cache.metrics()
+    }
+
+    /// End a line that was cut short with an ellipsis.
+    ///
+    /// The ellipsis is measured in the line's font ("..." if the font has no
+    /// "…"). With a max width the line is cut at a cluster boundary so it
+    /// and the ellipsis fit; if not even the ellipsis fits, the line is
+    /// left empty.
+    #[allow(clippy::too_many_arguments)]
+    fn append_ellipsis(
+        &mut self,
+        text: &str,
+        line: &mut TextLine,
+        max_width: Option<f32>,
+        scaled_font: &FontDescriptor,
+        font_id: u64,
+        font_size: f32,
+        spacing: Spacing,
+    ) -> Result<(), Box<dyn Error>> {
+        let has_ellipsis = scaled_font.fallback_chain().iter().any(|font| self.rasterizer.has_glyph('…', font));
+        let ellipsis = if has_ellipsis { "…" } else { "..." };
+        let ellipsis_glyphs = self.rasterize_text_segment(ellipsis, 0, scaled_font, font_id, font_size)?;
+        let ellipsis_width = spacing.extra_width(ellipsis)
+            + ellipsis_glyphs.iter().map(|g| g.entry.advance).sum::<f32>();
+        // Kept clusters are no longer last, so each gets its letter spacing
+        let advance = |g: &GlyphInfo| g.entry.advance + spacing.after_cluster(g.character, false);
+
+        if let Some(max_w) = max_width {
+            let clusters = line.glyphs.iter().map(|g| (&text[g.byte_index..cluster_end(text, g)], advance(g)));
+            let Some(keep) = crate::text::ellipsis::ellipsis_cut(clusters, ellipsis_width, max_w) else {
+                line.glyphs.clear();
+                line.width = 0.0;
+                line.range.end = line.range.start;
+                return Ok(());
+            };
+            line.glyphs.truncate(keep);
+            line.range.end = line.glyphs.last().map_or(line.range.start, |g| cluster_end(text, g));
+        }
+        line.width = line.glyphs.iter().map(advance).sum::<f32>() + ellipsis_width;
+        line.glyphs.extend(ellipsis_at_cut(text, &line.glyphs, ellipsis_glyphs));
+        Ok(())
+    }
+
+    /// Size of the box text occupies once laid out, in logical pixels.
+    ///
+    /// Width is the widest visible line; height spans the visible lines from
+    /// the first line's ascent to the last line's descent.
+    pub fn measure_text_box(&mut self, text: &str, font: &FontDescriptor, layout: &TextLayoutConfig) -> Option<(f32, f32)> {
+        self.measure_text_layout(text, font, layout).map(|m| (m.width, m.height))
+    }
+
+    /// `measure_text_box` plus the number of lines drawn, after wrapping,
+    /// explicit newlines and `max_lines`/`max_height` truncation.
+    pub fn measure_text_layout(&mut self, text: &str, font: &FontDescriptor, layout: &TextLayoutConfig) -> Option<TextMeasurement> {
+        let visible = self.layout_visible_lines(text, font, layout)?;
+        let (width, height) = visible.box_size();
+        let scale = self.scale_factor as f32;
+        Some(TextMeasurement { width: width / scale, height: height / scale, line_count: visible.lines.len() as u32 })
+    }
+
+    /// Lay out a `DrawRichText` paragraph in logical pixels, measuring each
+    /// text run the way DrawText draws it.
+    pub fn layout_rich_text(&mut self, runs: &[TextRun], layout: &TextLayoutConfig) -> RichTextLayout {
+        let scale = self.scale_factor as f32;
+        let scaled_font = |font: &FontDescriptor| FontDescriptor {
+            source: font.source.clone(),
+            weight: font.weight,
+            style: font.style,
+            size: font.size * scale,
+        };
+
+        let metrics: Vec<(f32, f32)> = runs.iter().map(|run| match run {
+            TextRun::Text { font, .. } => {
+                let (ascent, descent) = self.rasterizer.get_font_metrics(&scaled_font(font));
+                (ascent / scale, descent / scale)
+            }
+            TextRun::Image { .. } => (0.0, 0.0),
+        }).collect();
+
+        let rasterizer = &mut self.rasterizer;
+        crate::text::layout_rich_text(
+            runs,
+            layout,
+            |run, text| match &runs[run] {
+                TextRun::Text { font, .. } => {
+                    let font = scaled_font(font);
+                    let spacing = Spacing::from_layout(layout, font.size);
+                    (rasterizer.measure_string(text, &font) + spacing.extra_width(text)) / scale
                 }
-                TextAlign::Justify => {
-                    if is_last_line {
-                        (scaled_x, 0.0)
-                    } else if let Some(max_w) = scaled_max_width {
-                        let space_count = line.glyphs.iter().filter(|g| g.character == ' ').count();
-                        if space_count > 0 {
-                            let extra_space = max_w - line.width;
-                            let extra_per_space = extra_space / space_count as f32;
-                            (scaled_x, extra_per_space)
-                        } else {
-                            (scaled_x, 0.0)
+                TextRun::Image { .. } => 0.0,
+            },
+            |run| metrics[run],
+        )
+    }
+
+    /// Replace each `DrawRichText` with the DrawText and DrawImage commands
+    /// that draw it. Returns None for frames without rich text, so they
+    /// aren't copied.
+    ///
+    /// A rich text gradient spans the whole paragraph, so the lowered
+    /// DrawTexts carrying it come with the paragraph box (logical pixels),
+    /// keyed by their index in the lowered commands.
+    fn lower_rich_text(&mut self, commands: &[RenderCommand]) -> Option<(Vec<RenderCommand>, HashMap<usize, Rect>)> {
+        if !commands.iter().any(|cmd| matches!(cmd, RenderCommand::DrawRichText { .. })) {
+            return None;
+        }
+
+        let mut lowered = Vec::with_capacity(commands.len());
+        let mut gradient_bounds = HashMap::new();
+        for command in commands {
+            let RenderCommand::DrawRichText { x, y, runs, layout, gradient } = command else {
+                lowered.push(command.clone());
+                continue;
+            };
+            // Each piece is one unwrapped line drawn on its line's baseline
+            let piece_layout = TextLayoutConfig {
+                letter_spacing: layout.letter_spacing,
+                word_spacing: layout.word_spacing,
+                white_space: WhiteSpace::NoWrap,
+                anchor: TextAnchor::Baseline,
+                ..TextLayoutConfig::default()
+            };
+            let rich = self.layout_rich_text(runs, layout);
+            let paragraph = Rect::new(*x, *y, layout.max_width.unwrap_or(rich.width), rich.height);
+            for line in rich.lines {
+                for piece in line.pieces {
+                    match &runs[piece.run] {
+                        TextRun::Text { text, color, font } if !piece.range.is_empty() => {
+                            if gradient.is_some() {
+                                gradient_bounds.insert(lowered.len(), paragraph);
+                            }
+                            lowered.push(RenderCommand::DrawText {
+                                x: x + piece.x,
+                                y: y + line.baseline,
+                                text: text[piece.range].to_string(),
+                                font: font.clone(),
+                                color: *color,
+                                layout: piece_layout.clone(),
+                                gradient: gradient.clone(),
+                            });
                         }
-                    } else {
-                        (scaled_x, 0.0)
+                        TextRun::Image { image_texture_id, width, height, baseline_offset } => {
+                            lowered.push(RenderCommand::DrawImage {
+                                x: x + piece.x,
+                                y: y + line.baseline + baseline_offset - height,
+                                width: *width,
+                                height: *height,
+                                texture_id: *image_texture_id,
+                                source_rect: None,
+                                src: None,
+                                tint: None,
+                                corner_radii: [0.0; 4],
+                                opacity: 1.0,
+                                rotation: 0.0,
+                                pivot: None,
+                                sampling: Sampling::default(),
+                            });
+                        }
+                        TextRun::Text { .. } => {}
                     }
                 }
-            };
+            }
+        }
+        Some((lowered, gradient_bounds))
+    }
+
+    /// Where each cluster of a `DrawText` at `x` is drawn, in logical pixels.
+    ///
+    /// Uses the same layout as drawing (wrapping, alignment, spacing,
+    /// truncation), so a caret placed at a position's `x` lines up with the
+    /// drawn text. Positions are in visual order; an ellipsis maps to the
+    /// byte index where the cut text starts.
+    pub fn text_glyph_positions(
+        &mut self,
+        text: &str,
+        font: &FontDescriptor,
+        x: f32,
+        layout: &TextLayoutConfig,
+    ) -> Option<Vec<GlyphPosition>> {
+        let visible = self.layout_visible_lines(text, font, layout)?;
+        let scale = self.scale_factor as f32;
+        let scaled_max_width = layout.max_width.map(|w| w * scale);
+        let spacing = Spacing::from_layout(layout, visible.font_size);
+
+        let mut positions = Vec::new();
+        let line_count = visible.lines.len();
+        for (line_idx, line) in visible.lines.iter().enumerate() {
+            let (line_offset, justify_extra_space) =
+                line.alignment_offset(layout, visible.direction, scaled_max_width, line_idx + 1 == line_count);
+            let mut current_x = x * scale + line_offset;
+            for (glyph_idx, glyph) in line.glyphs.iter().enumerate() {
+                let advance = line.glyph_advance(glyph_idx, spacing, justify_extra_space);
+                positions.push(GlyphPosition {
+                    byte_index: glyph.byte_index,
+                    x: current_x / scale,
+                    advance: advance / scale,
+                    line: line_idx as u32,
+                });
+                current_x += advance;
+            }
+        }
+        Some(positions)
+    }
 
+    /// Selection geometry of a `DrawText` at (x, y), in logical pixels.
+    ///
+    /// Built from the same layout as drawing, with each line's row starting
+    /// at its ascent and running to the next line's (one line height), so
+    /// the rows of consecutive lines touch.
+    pub fn text_layout(
+        &mut self,
+        text: &str,
+        font: &FontDescriptor,
+        x: f32,
+        y: f32,
+        layout: &TextLayoutConfig,
+    ) -> Option<TextLayout> {
+        let visible = self.layout_visible_lines(text, font, layout)?;
+        let scale = self.scale_factor as f32;
+        let scaled_max_width = layout.max_width.map(|w| w * scale);
+        let spacing = Spacing::from_layout(layout, visible.font_size);
+        let bidi = crate::text::bidi::BidiText::new(text, visible.direction);
+
+        let line_count = visible.lines.len();
+        let first_baseline = y * scale
+            + layout.first_baseline_offset(visible.ascent, visible.descent, line_count, visible.line_height_px);
+        let row_height = visible.line_height_px.max(visible.ascent + visible.descent);
+
+        let lines = visible.lines.iter().enumerate().map(|(line_idx, line)| {
+            let (line_offset, justify_extra_space) =
+                line.alignment_offset(layout, visible.direction, scaled_max_width, line_idx + 1 == line_count);
+            let line_x = x * scale + line_offset;
             let mut current_x = line_x;
-            for glyph_info in &line.glyphs {
-                let entry = glyph_info.entry;
-                let glyph_color = if glyph_info.is_emoji {
-                    [1.0, 1.0, 1.0, a]
+            let clusters = line.glyphs.iter().enumerate().map(|(glyph_idx, glyph)| {
+                let advance = line.glyph_advance(glyph_idx, spacing, justify_extra_space);
+                let range = if line.range.contains(&glyph.byte_index) {
+                    glyph.byte_index..cluster_end(text, glyph)
                 } else {
-                    text_color
+                    glyph.byte_index..glyph.byte_index
                 };
+                let cluster = LayoutCluster {
+                    rtl: bidi.is_rtl(glyph.byte_index),
+                    range,
+                    x: current_x / scale,
+                    advance: advance / scale,
+                };
+                current_x += advance;
+                cluster
+            }).collect();
+            let baseline = first_baseline + line_idx as f32 * visible.line_height_px;
+            LayoutLine {
+                range: line.range.clone(),
+                x: line_x / scale,
+                top: (baseline - visible.ascent) / scale,
+                height: row_height / scale,
+                clusters,
+            }
+        }).collect();
 
-                let glyph_x = current_x + entry.bearing_x;
-                let glyph_y = line_baseline_y - entry.bearing_y;
-                let glyph_width = entry.width as f32;
-                let glyph_height = entry.height as f32;
+        Some(TextLayout { lines, direction: visible.direction })
+    }
 
-                let top_left = self.screen_to_ndc(glyph_x, glyph_y);
-                let top_right = self.screen_to_ndc(glyph_x + glyph_width, glyph_y);
-                let bottom_left = self.screen_to_ndc(glyph_x, glyph_y + glyph_height);
-                let bottom_right = self.screen_to_ndc(glyph_x + glyph_width, glyph_y + glyph_height);
+    /// Lay out and rasterize text once into its own RGBA texture.
+    ///
+    /// For static labels: draw the texture with `DrawImage` every frame
+    /// instead of re-shaping the text, and free it with `unload_image`. The
+    /// texture is sized tightly to the text box at the current scale factor
+    /// (physical pixels), so draw it at its size divided by the scale factor.
+    /// Lines are aligned within the widest line; justification is not applied.
+    pub fn create_text_texture(
+        &mut self,
+        text: &str,
+        font: &FontDescriptor,
+        color: u32,
+        layout: &TextLayoutConfig,
+    ) -> Result<u32, Box<dyn Error>> {
+        let image = self.rasterize_text_image(text, font, color, layout).ok_or("Text has no visible glyphs")?;
+        self.load_image(&image)
+    }
 
-                let use_texture_color = if glyph_info.is_emoji { 1.0 } else { 0.0 };
+    /// Composite laid-out glyphs from the atlas into an unpremultiplied RGBA image
+    fn rasterize_text_image(
+        &mut self,
+        text: &str,
+        font: &FontDescriptor,
+        color: u32,
+        layout: &TextLayoutConfig,
+    ) -> Option<LoadedImage> {
+        if text.is_empty() {
+            return None;
+        }
+        let visible = self.layout_visible_lines(text, font, layout)?;
+        let (box_width, box_height) = visible.box_size();
+        let width = box_width.ceil() as u32;
+        let height = box_height.ceil() as u32;
+        if width == 0 || height == 0 {
+            return None;
+        }
 
-                // Triangle 1
-                vertices.push(TextVertex { position: top_left, tex_coords: [entry.u0, entry.v0], color: glyph_color, use_texture_color });
-                vertices.push(TextVertex { position: bottom_left, tex_coords: [entry.u0, entry.v1], color: glyph_color, use_texture_color });
-                vertices.push(TextVertex { position: top_right, tex_coords: [entry.u1, entry.v0], color: glyph_color, use_texture_color });
-                // Triangle 2
-                vertices.push(TextVertex { position: top_right, tex_coords: [entry.u1, entry.v0], color: glyph_color, use_texture_color });
-                vertices.push(TextVertex { position: bottom_left, tex_coords: [entry.u0, entry.v1], color: glyph_color, use_texture_color });
-                vertices.push(TextVertex { position: bottom_right, tex_coords: [entry.u1, entry.v1], color: glyph_color, use_texture_color });
+        let spacing = Spacing::from_layout(layout, visible.font_size);
+        let text_rgb = [(color >> 24) as u8, (color >> 16) as u8, (color >> 8) as u8];
+        let text_alpha = (color & 0xFF) as f32 / 255.0;
+        let (atlas_width, _) = self.glyph_atlas.dimensions();
+        let atlas = self.glyph_atlas.texture_data();
+        let mut data = vec![0u8; (width * height * 4) as usize];
+
+        for (line_idx, line) in visible.lines.iter().enumerate() {
+            let baseline = visible.ascent + line_idx as f32 * visible.line_height_px;
+            let mut pen_x = match layout.alignment.resolve(visible.direction) {
+                TextAlign::Center => (box_width - line.width) / 2.0,
+                TextAlign::Right => box_width - line.width,
+                TextAlign::Justify if visible.direction == Direction::Rtl => box_width - line.width,
+                TextAlign::Left | TextAlign::Justify | TextAlign::Start | TextAlign::End => 0.0,
+            };
 
-                let mut advance = entry.advance + letter_spacing_px;
-                if glyph_info.character == ' ' {
-                    advance += word_spacing_px + justify_extra_space;
+            for (glyph_idx, glyph) in line.glyphs.iter().enumerate() {
+                let entry = glyph.entry;
+                let left = (pen_x + entry.bearing_x).round() as i64;
+                let top = (baseline - entry.bearing_y).round() as i64;
+
+                for gy in 0..entry.height {
+                    let py = top + gy as i64;
+                    if py < 0 || py >= height as i64 {
+                        continue;
+                    }
+                    for gx in 0..entry.width {
+                        let px = left + gx as i64;
+                        if px < 0 || px >= width as i64 {
+                            continue;
+                        }
+                        let src = (((entry.y + gy) * atlas_width + entry.x + gx) * 4) as usize;
+                        let coverage = atlas[src + 3] as f32 / 255.0 * text_alpha;
+                        if coverage <= 0.0 {
+                            continue;
+                        }
+                        let rgb = if glyph.is_emoji { [atlas[src], atlas[src + 1], atlas[src + 2]] } else { text_rgb };
+
+                        // Source-over, unpremultiplied
+                        let dst = ((py as u32 * width + px as u32) * 4) as usize;
+                        let dst_alpha = data[dst + 3] as f32 / 255.0;
+                        let out_alpha = coverage + dst_alpha * (1.0 - coverage);
+                        for c in 0..3 {
+                            let blended = (rgb[c] as f32 * coverage + data[dst + c] as f32 * dst_alpha * (1.0 - coverage)) / out_alpha;
+                            data[dst + c] = blended.round() as u8;
+                        }
+                        data[dst + 3] = (out_alpha * 255.0).round() as u8;
+                    }
                 }
-                current_x += advance;
-            }
-        }
 
-        if vertices.is_empty() {
-            return None;
+                pen_x += line.glyph_advance(glyph_idx, spacing, 0.0);
+            }
         }
 
-        // Upload to buffer pool
-        let device = self.device.as_ref()?;
-        let queue = self.queue.as_ref()?;
-        let vertex_idx = self.buffer_pool.prepare_vertex_buffer(
-            device,
-            queue,
-            bytemuck::cast_slice(&vertices),
-        );
-
-        Some((vertex_idx, vertices.len() as u32))
+        Some(LoadedImage { width, height, data, premultiplied: false, frames: Vec::new(), loop_count: 0 })
     }
 
     /// Prepare an image for drawing, returning buffer index and vertex count.
+    #[allow(clippy::too_many_arguments)]
     fn prepare_image(
         &mut self,
         x: f32,
@@ -2513,21 +4070,33 @@ impl WgpuBackend {
         height: f32,
         texture_id: u32,
         source_rect: Option<(f32, f32, f32, f32)>,
+        src: Option<Rect>,
+        tint: Option<u32>,
         corner_radii: [f32; 4],
+        opacity: f32,
+        rotation: f32,
+        pivot: Option<(f32, f32)>,
     ) -> Option<(usize, u32)> {
         // Check if texture exists
-        if !self.image_textures.contains_key(&texture_id) {
-            return None;
-        }
+        let texture = self.image_textures.get(&texture_id)?;
+        let (premultiplied, texture_width, texture_height) = (texture.premultiplied, texture.width, texture.height);
 
         let scale = self.scale_factor as f32;
         let scaled_x = x * scale;
         let scaled_y = y * scale;
         let scaled_w = width * scale;
         let scaled_h = height * scale;
+        let rotation = crate::geometry::Rotation::about(
+            crate::geometry::Rect::new(scaled_x, scaled_y, scaled_w, scaled_h),
+            rotation, pivot.map(|(px, py)| (px * scale, py * scale)),
+        );
 
-        let (u0, v0, u1, v1) = source_rect.unwrap_or((0.0, 0.0, 1.0, 1.0));
-        let color = [1.0f32, 1.0, 1.0, 1.0];
+        let (u0, v0, u1, v1) = image_uvs(source_rect, src, texture_width, texture_height);
+        // The image shader multiplies the texture by the vertex color
+        let [r, g, b, a] = tint.map_or([1.0; 4], crate::geometry::color_to_rgba);
+        let color = [r, g, b, a * opacity];
+        // The image shader reads use_texture_color as the premultiplied flag
+        let use_texture_color = if premultiplied { 1.0 } else { 0.0 };
 
         let has_rounded = corner_radii.iter().any(|&r| r > 0.5);
 
@@ -2537,6 +4106,8 @@ impl WgpuBackend {
                 corner_radii.map(|r| r * scale),
                 u0, v0, u1, v1,
                 color,
+                use_texture_color,
+                rotation,
             )
         } else {
             let left = scaled_x;
@@ -2544,18 +4115,22 @@ impl WgpuBackend {
             let top = scaled_y;
             let bottom = scaled_y + scaled_h;
 
-            let tl = self.screen_to_ndc(left, top);
-            let tr = self.screen_to_ndc(right, top);
-            let bl = self.screen_to_ndc(left, bottom);
-            let br = self.screen_to_ndc(right, bottom);
+            let to_ndc = |px: f32, py: f32| {
+                let (rx, ry) = rotation.apply(px, py);
+                self.screen_to_ndc(rx, ry)
+            };
+            let tl = to_ndc(left, top);
+            let tr = to_ndc(right, top);
+            let bl = to_ndc(left, bottom);
+            let br = to_ndc(right, bottom);
 
             vec![
-                TextVertex { position: tl, tex_coords: [u0, v0], color, use_texture_color: 1.0 },
-                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color: 1.0 },
-                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color: 1.0 },
-                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color: 1.0 },
-                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color: 1.0 },
-                TextVertex { position: br, tex_coords: [u1, v1], color, use_texture_color: 1.0 },
+                TextVertex { position: tl, tex_coords: [u0, v0], color, use_texture_color, ..Default::default() },
+                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color, ..Default::default() },
+                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color, ..Default::default() },
+                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color, ..Default::default() },
+                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color, ..Default::default() },
+                TextVertex { position: br, tex_coords: [u1, v1], color, use_texture_color, ..Default::default() },
             ]
         };
 
@@ -2575,8 +4150,29 @@ impl WgpuBackend {
     /// This walks all commands, uploads buffers, and returns a PreparedFrame
     /// that can be executed during the render pass.
     fn prepare_frame(&mut self, commands: &[RenderCommand]) -> PreparedFrame {
-        // Reset buffer pool for this frame
+        // Reset buffer pool and gradient ramp rows for this frame
         self.buffer_pool.reset();
+        self.gradient_ramp_rows = 0;
+
+        // Advance the frame clock for animated commands
+        if !self.headless {
+            self.frame_time = self.clock_now();
+        }
+        self.continuous_redraw = commands.iter().any(|cmd| cmd.is_animated());
+        self.update_caret_blink(commands);
+        // Glyphs drawn from here on are pinned in the atlas until the next frame
+        #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows"))]
+        self.glyph_atlas.begin_frame();
+
+        // Mistakes the renderer silently tolerates (stray pops, unloaded
+        // textures) are worth a trace while developing
+        if crate::logging::is_verbose() {
+            for issue in validate_commands(commands, |id| self.has_texture(id)) {
+                verbose!("[wgpu] Command {}: {}", issue.index, issue.message);
+            }
+        }
+        let (lowered, gradient_bounds) = self.lower_rich_text(commands).unzip();
+        let commands = lowered.as_deref().unwrap_or(commands);
 
         let scale = self.scale_factor as f32;
         let full_width = self.width;
@@ -2585,7 +4181,18 @@ impl WgpuBackend {
         // Track scroll state during preparation (mirrors render-time state)
         let mut scroll_offset_stack: Vec<ScrollOffset> = Vec::new();
         let mut scissor_stack: Vec<ScissorRect> = Vec::new();
-        let mut stencil_active = false;
+        // Open PushClip/PushRoundedClip regions, innermost last, so PopClip
+        // undoes the right kind; the stencil reference is the number of
+        // rounded ones
+        let mut clip_stack: Vec<OpenClip> = Vec::new();
+        // Transforms enclosing the current one, restored by PopTransform
+        let mut transform_stack: Vec<Transform2D> = Vec::new();
+        self.transform = Transform2D::IDENTITY;
+        // Multiplier from SetOpacity (applied to images and rects)
+        let mut opacity = 1.0f32;
+        // Open opacity layers, innermost last, and the ops of closed ones
+        let mut layer_stack: Vec<OpenLayer> = Vec::new();
+        let mut layers: Vec<Vec<PreparedOp>> = Vec::new();
 
         // Determine clear color
         let clear_color = commands.iter()
@@ -2612,16 +4219,27 @@ impl WgpuBackend {
             height: full_height,
         });
 
-        for cmd in commands {
+        // Layers left open at the end are composited as if popped there
+        let unclosed_layers = commands.iter().fold(0usize, |depth, cmd| match cmd {
+            RenderCommand::PushOpacityLayer(_) => depth + 1,
+            RenderCommand::PopOpacityLayer {} => depth.saturating_sub(1),
+            _ => depth,
+        });
+        let pop_layer = RenderCommand::PopOpacityLayer {};
+
+        for (index, cmd) in commands.iter().chain(std::iter::repeat(&pop_layer).take(unclosed_layers)).enumerate() {
             match cmd {
                 RenderCommand::Clear(_) => {
                     // Clear is handled by render pass load op
                 }
                 RenderCommand::PushClip { x, y, width, height } => {
-                    let clip_x = (*x * scale) as u32;
-                    let clip_y = (*y * scale) as u32;
-                    let clip_w = (*width * scale) as u32;
-                    let clip_h = (*height * scale) as u32;
+                    clip_stack.push(OpenClip::Scissor);
+                    let (clip_x, clip_y, clip_w, clip_h) = if self.transform.is_identity() {
+                        ((*x * scale) as u32, (*y * scale) as u32, (*width * scale) as u32, (*height * scale) as u32)
+                    } else {
+                        let rect = transformed_scissor(&self.transform, *x * scale, *y * scale, *width * scale, *height * scale);
+                        (rect.x, rect.y, rect.width, rect.height)
+                    };
 
                     let new_rect = if let Some(parent) = scissor_stack.last() {
                         let int_x = clip_x.max(parent.x);
@@ -2650,9 +4268,10 @@ impl WgpuBackend {
                     });
                 }
                 RenderCommand::PopClip {} => {
-                    if stencil_active {
-                        ops.push(PreparedOp::SetStencilRef { value: 0 });
-                        stencil_active = false;
+                    if let Some(OpenClip::Rounded { vertex_buffer_idx, index_buffer_idx, index_count }) = clip_stack.pop() {
+                        // Lower the mask's pixels back to the enclosing level
+                        ops.push(PreparedOp::DrawStencil { vertex_buffer_idx, index_buffer_idx, index_count, pop: true });
+                        ops.push(PreparedOp::SetStencilRef { value: rounded_clip_depth(&clip_stack) });
                     } else {
                         scissor_stack.pop();
                         if let Some(parent) = scissor_stack.last() {
@@ -2672,13 +4291,20 @@ impl WgpuBackend {
                 }
                 RenderCommand::PushRoundedClip { x, y, width, height, corner_radii } => {
                     let (v_idx, i_idx, i_count) = self.prepare_stencil_mask(*x, *y, *width, *height, *corner_radii);
+                    // Only pixels inside every enclosing rounded clip are
+                    // raised, so nested clips intersect
                     ops.push(PreparedOp::DrawStencil {
                         vertex_buffer_idx: v_idx,
                         index_buffer_idx: i_idx,
                         index_count: i_count,
+                        pop: false,
+                    });
+                    clip_stack.push(OpenClip::Rounded {
+                        vertex_buffer_idx: v_idx,
+                        index_buffer_idx: i_idx,
+                        index_count: i_count,
                     });
-                    ops.push(PreparedOp::SetStencilRef { value: 1 });
-                    stencil_active = true;
+                    ops.push(PreparedOp::SetStencilRef { value: rounded_clip_depth(&clip_stack) });
                 }
                 RenderCommand::BeginScrollView { x, y, width, height, scroll_x, scroll_y, .. } => {
                     // Calculate parent scroll offset
@@ -2696,16 +4322,24 @@ impl WgpuBackend {
                     let adjusted_x = *x + parent_scroll_dx;
                     let adjusted_y = *y + parent_scroll_dy;
 
-                    let (clip_x, clip_w) = if adjusted_x < 0.0 {
-                        (0u32, ((*width + adjusted_x) * scale).max(0.0) as u32)
-                    } else {
-                        ((adjusted_x * scale) as u32, (*width * scale) as u32)
-                    };
-
-                    let (clip_y, clip_h) = if adjusted_y < 0.0 {
-                        (0u32, ((*height + adjusted_y) * scale).max(0.0) as u32)
+                    let (clip_x, clip_y, clip_w, clip_h) = if self.transform.is_identity() {
+                        let (clip_x, clip_w) = if adjusted_x < 0.0 {
+                            (0u32, ((*width + adjusted_x) * scale).max(0.0) as u32)
+                        } else {
+                            ((adjusted_x * scale) as u32, (*width * scale) as u32)
+                        };
+                        let (clip_y, clip_h) = if adjusted_y < 0.0 {
+                            (0u32, ((*height + adjusted_y) * scale).max(0.0) as u32)
+                        } else {
+                            ((adjusted_y * scale) as u32, (*height * scale) as u32)
+                        };
+                        (clip_x, clip_y, clip_w, clip_h)
                     } else {
-                        ((adjusted_y * scale) as u32, (*height * scale) as u32)
+                        let rect = transformed_scissor(
+                            &self.transform,
+                            adjusted_x * scale, adjusted_y * scale, *width * scale, *height * scale,
+                        );
+                        (rect.x, rect.y, rect.width, rect.height)
                     };
 
                     let new_rect = if let Some(parent) = scissor_stack.last() {
@@ -2747,6 +4381,48 @@ impl WgpuBackend {
                         });
                     }
                 }
+                RenderCommand::DrawLine { x1, y1, x2, y2, width, color, cap } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    if let Some((v_idx, i_idx, i_count)) = self.prepare_line(
+                        *x1 + scroll_dx, *y1 + scroll_dy,
+                        *x2 + scroll_dx, *y2 + scroll_dy,
+                        *width, *color, *cap,
+                    ) {
+                        ops.push(PreparedOp::DrawGeometry {
+                            vertex_buffer_idx: v_idx,
+                            index_buffer_idx: i_idx,
+                            index_count: i_count,
+                        });
+                    }
+                }
+                RenderCommand::DrawArc { cx, cy, radius, start_angle, end_angle, thickness, color } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    if let Some((v_idx, i_idx, i_count)) = self.prepare_arc(
+                        *cx + scroll_dx, *cy + scroll_dy,
+                        *radius, *thickness, *start_angle, *end_angle, *color,
+                    ) {
+                        ops.push(PreparedOp::DrawCoverage {
+                            vertex_buffer_idx: v_idx,
+                            index_buffer_idx: i_idx,
+                            index_count: i_count,
+                        });
+                    }
+                }
+                RenderCommand::DrawPath { segments, fill, fill_rule, stroke } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    if let Some((v_idx, i_idx, i_count)) = self.prepare_path(
+                        segments, *fill, *fill_rule, stroke.as_ref(), scroll_dx, scroll_dy,
+                    ) {
+                        ops.push(PreparedOp::DrawGeometry {
+                            vertex_buffer_idx: v_idx,
+                            index_buffer_idx: i_idx,
+                            index_count: i_count,
+                        });
+                    }
+                }
                 RenderCommand::DrawShadow { x, y, width, height, blur, color, offset_x, offset_y, corner_radii } => {
                     let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
                         .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
@@ -2761,13 +4437,13 @@ impl WgpuBackend {
                         index_count: i_count,
                     });
                 }
-                RenderCommand::DrawRect { x, y, width, height, color, corner_radii, rotation, border, gradient } => {
+                RenderCommand::DrawRect { x, y, width, height, color, corner_radii, rotation, pivot, border, gradient, inner_shadow, shadow } => {
                     let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
                         .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
                     let prepared = self.prepare_rect(
                         *x + scroll_dx, *y + scroll_dy,
-                        *width, *height, *color, *corner_radii, *rotation,
-                        border.as_ref(), gradient.as_ref(),
+                        *width, *height, *color, *corner_radii, *rotation, *pivot,
+                        border.as_ref(), gradient.as_ref(), inner_shadow.as_ref(), shadow.as_ref(), opacity,
                     );
                     for (v_idx, i_idx, i_count) in prepared {
                         ops.push(PreparedOp::DrawGeometry {
@@ -2785,12 +4461,15 @@ impl WgpuBackend {
                         index_count: i_count,
                     });
                 }
-                RenderCommand::DrawText { x, y, text, font, color, layout } => {
+                RenderCommand::DrawText { x, y, text, font, color, layout, gradient } => {
                     let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
                         .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    let bounds = gradient_bounds.as_ref()
+                        .and_then(|bounds| bounds.get(&index))
+                        .map(|b| Rect::new(b.x + scroll_dx, b.y + scroll_dy, b.width, b.height));
                     if let Some((v_idx, v_count)) = self.prepare_text(
                         *x + scroll_dx, *y + scroll_dy,
-                        text, font, *color, layout,
+                        text, font, *color, layout, gradient.as_ref(), bounds,
                     ) {
                         ops.push(PreparedOp::DrawText {
                             vertex_buffer_idx: v_idx,
@@ -2798,27 +4477,226 @@ impl WgpuBackend {
                         });
                     }
                 }
-                RenderCommand::DrawImage { x, y, width, height, texture_id, source_rect, corner_radii } => {
+                RenderCommand::DrawImage { x, y, width, height, texture_id, source_rect, src, tint, corner_radii, opacity: image_opacity, rotation, pivot, sampling } => {
                     let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
                         .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
                     if let Some((v_idx, v_count)) = self.prepare_image(
                         *x + scroll_dx, *y + scroll_dy,
-                        *width, *height, *texture_id, *source_rect, *corner_radii,
+                        *width, *height, *texture_id, *source_rect, *src, *tint, *corner_radii,
+                        opacity * image_opacity.clamp(0.0, 1.0), *rotation, *pivot,
                     ) {
                         ops.push(PreparedOp::DrawImage {
                             vertex_buffer_idx: v_idx,
                             vertex_count: v_count,
                             texture_id: *texture_id,
+                            sampling: *sampling,
+                        });
+                    }
+                }
+                RenderCommand::DrawNinePatch { texture_id, dest_rect, insets } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    let texture_size = self.image_textures.get(texture_id).map(|t| (t.width, t.height));
+                    if let Some((texture_width, texture_height)) = texture_size {
+                        // Each piece is an image quad sampling its own region
+                        for (dest, src) in nine_patch_pieces(*dest_rect, *insets, texture_width, texture_height) {
+                            if let Some((v_idx, v_count)) = self.prepare_image(
+                                dest.x + scroll_dx, dest.y + scroll_dy,
+                                dest.width, dest.height, *texture_id, None, Some(src), None, [0.0; 4],
+                                opacity, 0.0, None,
+                            ) {
+                                ops.push(PreparedOp::DrawImage {
+                                    vertex_buffer_idx: v_idx,
+                                    vertex_count: v_count,
+                                    texture_id: *texture_id,
+                                    sampling: Sampling::default(),
+                                });
+                            }
+                        }
+                    }
+                }
+                RenderCommand::Spinner { center_x, center_y, radius, color, speed, thickness } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    let (v_idx, i_idx, i_count) = self.prepare_spinner(
+                        *center_x + scroll_dx, *center_y + scroll_dy,
+                        *radius, *thickness, *color, *speed,
+                    );
+                    ops.push(PreparedOp::DrawGeometry {
+                        vertex_buffer_idx: v_idx,
+                        index_buffer_idx: i_idx,
+                        index_count: i_count,
+                    });
+                }
+                RenderCommand::FocusRing { rect, corner_radii, color, thickness, dash, offset, speed } => {
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    let rect = crate::geometry::Rect::new(
+                        rect.x + scroll_dx, rect.y + scroll_dy, rect.width, rect.height,
+                    );
+                    if let Some((v_idx, i_idx, i_count)) = self.prepare_focus_ring(
+                        rect, *corner_radii, *color, *thickness, *dash, *offset, *speed,
+                    ) {
+                        ops.push(PreparedOp::DrawGeometry {
+                            vertex_buffer_idx: v_idx,
+                            index_buffer_idx: i_idx,
+                            index_count: i_count,
                         });
                     }
                 }
+                RenderCommand::Caret { x, y, width, height, color, blink_ms } => {
+                    let elapsed = self.caret_blink.map_or(0.0, |caret| self.frame_time - caret.since);
+                    if crate::render::caret_visible(*blink_ms, elapsed) {
+                        let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                            .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                        let width = if *width > 0.0 { *width } else { 1.0 };
+                        let prepared = self.prepare_rect(
+                            *x + scroll_dx, *y + scroll_dy,
+                            width, *height, *color, [0.0; 4], 0.0, None, None, None, None, None, 1.0,
+                        );
+                        for (v_idx, i_idx, i_count) in prepared {
+                            ops.push(PreparedOp::DrawGeometry {
+                                vertex_buffer_idx: v_idx,
+                                index_buffer_idx: i_idx,
+                                index_count: i_count,
+                            });
+                        }
+                    }
+                }
+                RenderCommand::PushTransform { matrix } => {
+                    // Draws add the scroll offset to their coordinates before
+                    // they're transformed; wrapping the matrix in that offset
+                    // keeps an enclosing scroll from being scaled with them
+                    let (scroll_dx, scroll_dy) = scroll_offset_stack.iter()
+                        .fold((0.0f32, 0.0f32), |(dx, dy), s| (dx - s.offset_x, dy - s.offset_y));
+                    let local = Transform2D::translate(scroll_dx, scroll_dy)
+                        .then(&Transform2D(*matrix))
+                        .then(&Transform2D::translate(-scroll_dx, -scroll_dy));
+                    transform_stack.push(self.transform);
+                    self.transform = self.transform.then(&local.scaled(scale));
+                }
+                RenderCommand::PopTransform {} => {
+                    if let Some(parent) = transform_stack.pop() {
+                        self.transform = parent;
+                    }
+                }
+                RenderCommand::SetOpacity(value) => {
+                    opacity = value.clamp(0.0, 1.0);
+                }
+                RenderCommand::PushOpacityLayer(alpha) => {
+                    // The layer's pass starts clipped to the enclosing scissor
+                    let scissor = scissor_stack.last().copied()
+                        .unwrap_or(ScissorRect { x: 0, y: 0, width: full_width, height: full_height });
+                    let layer_ops = vec![PreparedOp::SetScissor {
+                        x: scissor.x, y: scissor.y,
+                        width: scissor.width.max(1),
+                        height: scissor.height.max(1),
+                    }];
+                    layer_stack.push(OpenLayer {
+                        alpha: alpha.clamp(0.0, 1.0),
+                        ops: std::mem::replace(&mut ops, layer_ops),
+                        clip_stack: std::mem::take(&mut clip_stack),
+                        scissor_stack: scissor_stack.clone(),
+                    });
+                }
+                RenderCommand::PopOpacityLayer {} => {
+                    if let Some(open) = layer_stack.pop() {
+                        // Clips left open inside the layer close with it; the
+                        // enclosing pass's scissor and stencil were never changed
+                        layers.push(std::mem::replace(&mut ops, open.ops));
+                        clip_stack = open.clip_stack;
+                        scissor_stack = open.scissor_stack;
+                        if let Some((v_idx, v_count)) = self.prepare_layer_composite(open.alpha) {
+                            ops.push(PreparedOp::DrawLayer {
+                                layer: layers.len() - 1,
+                                vertex_buffer_idx: v_idx,
+                                vertex_count: v_count,
+                            });
+                        }
+                    }
+                }
                 _ => {
                     // Other commands ignored
                 }
             }
         }
+        self.transform = Transform2D::IDENTITY;
+        self.ensure_opacity_layers(layers.len());
+
+        PreparedFrame { clear_color, ops, layers }
+    }
 
-        PreparedFrame { clear_color, ops }
+    /// Prepare a quad covering the frame that draws an opacity layer's
+    /// target at `alpha`
+    fn prepare_layer_composite(&mut self, alpha: f32) -> Option<(usize, u32)> {
+        // Layer targets hold premultiplied color, drawn 1:1 and untransformed
+        let color = [1.0, 1.0, 1.0, alpha];
+        let vertex = |position, tex_coords| TextVertex { position, tex_coords, color, use_texture_color: 1.0, ..Default::default() };
+        let (tl, tr) = (vertex([-1.0, 1.0], [0.0, 0.0]), vertex([1.0, 1.0], [1.0, 0.0]));
+        let (bl, br) = (vertex([-1.0, -1.0], [0.0, 1.0]), vertex([1.0, -1.0], [1.0, 1.0]));
+        let vertices = [tl, bl, tr, tr, bl, br];
+
+        let device = self.device.as_ref()?;
+        let queue = self.queue.as_ref()?;
+        let vertex_idx = self.buffer_pool.prepare_vertex_buffer(
+            device,
+            queue,
+            bytemuck::cast_slice(&vertices),
+        );
+        Some((vertex_idx, vertices.len() as u32))
+    }
+
+    /// Make sure there are at least `count` opacity layer targets the size
+    /// of the frame being prepared
+    fn ensure_opacity_layers(&mut self, count: usize) {
+        let (width, height) = (self.width, self.height);
+        if self.opacity_layers.first().is_some_and(|layer| (layer.width, layer.height) != (width, height)) {
+            self.opacity_layers.clear();
+        }
+        if self.opacity_layers.len() >= count || width == 0 || height == 0 {
+            return;
+        }
+        let (Some(device), Some(surface_config), Some(layout), Some(samplers)) = (
+            self.device.as_ref(),
+            self.surface_config.as_ref(),
+            self.image_bind_group_layout.as_ref(),
+            self.image_samplers.as_ref(),
+        ) else {
+            return;
+        };
+        while self.opacity_layers.len() < count {
+            // Same format as the frame texture, so the render pipelines can draw into it
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Opacity Layer Texture"),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: surface_config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_groups = image_bind_groups(device, layout, samplers, &view, "Opacity Layer Bind Group");
+            // Blending into a transparent target leaves premultiplied color
+            self.opacity_layers.push(GpuTexture { texture, bind_groups, width, height, premultiplied: true });
+        }
+    }
+
+    /// Track the frame's caret so it blinks from when it last appeared or
+    /// moved - a caret that follows typing stays solid while keys are pressed.
+    fn update_caret_blink(&mut self, commands: &[RenderCommand]) {
+        let caret = commands.iter().rev().find_map(|cmd| match cmd {
+            RenderCommand::Caret { x, y, height, blink_ms, .. } => Some(((*x, *y, *height), *blink_ms)),
+            _ => None,
+        });
+        self.caret_blink = caret.map(|(position, blink_ms)| {
+            let since = match self.caret_blink {
+                Some(previous) if previous.position == position && previous.blink_ms == blink_ms => previous.since,
+                _ => self.frame_time,
+            };
+            CaretBlink { position, blink_ms, since }
+        });
     }
 
     /// Execute a prepared frame during the render pass.
@@ -2826,10 +4704,12 @@ impl WgpuBackend {
     fn execute_prepared_frame(
         &self,
         render_pass: &mut wgpu::RenderPass,
-        prepared: &PreparedFrame,
+        ops: &[PreparedOp],
     ) {
         let geometry_pipeline = self.geometry_pipeline.as_ref().expect("Geometry pipeline not initialized");
+        let coverage_pipeline = self.coverage_pipeline.as_ref().expect("Coverage pipeline not initialized");
         let stencil_pipeline = self.stencil_pipeline.as_ref().expect("Stencil pipeline not initialized");
+        let stencil_pop_pipeline = self.stencil_pop_pipeline.as_ref().expect("Stencil pipeline not initialized");
         let text_pipeline = self.text_pipeline.as_ref().expect("Text pipeline not initialized");
         let text_bind_group = self.text_bind_group.as_ref().expect("Text bind group not initialized");
         let image_pipeline = self.image_pipeline.as_ref().expect("Image pipeline not initialized");
@@ -2839,16 +4719,18 @@ impl WgpuBackend {
         enum CurrentPipeline {
             None,
             Geometry,
+            Coverage,
             Stencil,
+            StencilPop,
             Text,
             Image,
         }
 
         let mut current_pipeline = CurrentPipeline::None;
-        let mut current_image_texture: Option<u32> = None;
+        let mut current_image_texture: Option<(u32, Sampling)> = None;
         let mut text_bind_group_set = false;
 
-        for op in &prepared.ops {
+        for op in ops {
             match op {
                 PreparedOp::SetScissor { x, y, width, height } => {
                     render_pass.set_scissor_rect(*x, *y, *width, *height);
@@ -2867,13 +4749,25 @@ impl WgpuBackend {
                     render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
                     render_pass.draw_indexed(0..*index_count, 0, 0..1);
                 }
-                PreparedOp::DrawStencil { vertex_buffer_idx, index_buffer_idx, index_count } => {
+                PreparedOp::DrawCoverage { vertex_buffer_idx, index_buffer_idx, index_count } => {
                     let vertex_buffer = self.buffer_pool.get_vertex_buffer(*vertex_buffer_idx);
                     let index_buffer = self.buffer_pool.get_index_buffer(*index_buffer_idx);
-                    render_pass.set_stencil_reference(1);
-                    if current_pipeline != CurrentPipeline::Stencil {
-                        render_pass.set_pipeline(stencil_pipeline);
-                        current_pipeline = CurrentPipeline::Stencil;
+                    if current_pipeline != CurrentPipeline::Coverage {
+                        render_pass.set_pipeline(coverage_pipeline);
+                        current_pipeline = CurrentPipeline::Coverage;
+                    }
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..*index_count, 0, 0..1);
+                }
+                PreparedOp::DrawStencil { vertex_buffer_idx, index_buffer_idx, index_count, pop } => {
+                    let vertex_buffer = self.buffer_pool.get_vertex_buffer(*vertex_buffer_idx);
+                    let index_buffer = self.buffer_pool.get_index_buffer(*index_buffer_idx);
+                    // The reference is still the level the mask starts from
+                    let pipeline = if *pop { CurrentPipeline::StencilPop } else { CurrentPipeline::Stencil };
+                    if current_pipeline != pipeline {
+                        render_pass.set_pipeline(if *pop { stencil_pop_pipeline } else { stencil_pipeline });
+                        current_pipeline = pipeline;
                     }
                     render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
                     render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
@@ -2890,22 +4784,36 @@ impl WgpuBackend {
                         render_pass.set_bind_group(0, text_bind_group, &[]);
                         text_bind_group_set = true;
                     }
-                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                    render_pass.draw(0..*vertex_count, 0..1);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.draw(0..*vertex_count, 0..1);
+                }
+                PreparedOp::DrawImage { vertex_buffer_idx, vertex_count, texture_id, sampling } => {
+                    if let Some(gpu_texture) = self.image_textures.get(texture_id) {
+                        let vertex_buffer = self.buffer_pool.get_vertex_buffer(*vertex_buffer_idx);
+                        if current_pipeline != CurrentPipeline::Image {
+                            render_pass.set_pipeline(image_pipeline);
+                            current_pipeline = CurrentPipeline::Image;
+                            current_image_texture = None; // Force bind group update on pipeline switch
+                        }
+                        // Only update bind group if texture or sampling changed
+                        if current_image_texture != Some((*texture_id, *sampling)) {
+                            render_pass.set_bind_group(0, gpu_texture.bind_group(*sampling), &[]);
+                            current_image_texture = Some((*texture_id, *sampling));
+                        }
+                        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                        render_pass.draw(0..*vertex_count, 0..1);
+                    }
                 }
-                PreparedOp::DrawImage { vertex_buffer_idx, vertex_count, texture_id } => {
-                    if let Some(gpu_texture) = self.image_textures.get(texture_id) {
+                PreparedOp::DrawLayer { layer, vertex_buffer_idx, vertex_count } => {
+                    if let Some(target) = self.opacity_layers.get(*layer) {
                         let vertex_buffer = self.buffer_pool.get_vertex_buffer(*vertex_buffer_idx);
                         if current_pipeline != CurrentPipeline::Image {
                             render_pass.set_pipeline(image_pipeline);
                             current_pipeline = CurrentPipeline::Image;
-                            current_image_texture = None; // Force bind group update on pipeline switch
-                        }
-                        // Only update bind group if texture changed
-                        if current_image_texture != Some(*texture_id) {
-                            render_pass.set_bind_group(0, &gpu_texture.bind_group, &[]);
-                            current_image_texture = Some(*texture_id);
                         }
+                        // Drawn 1:1, so nearest sampling is exact
+                        render_pass.set_bind_group(0, target.bind_group(Sampling::Nearest), &[]);
+                        current_image_texture = None;
                         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
                         render_pass.draw(0..*vertex_count, 0..1);
                     }
@@ -2928,11 +4836,14 @@ impl WgpuBackend {
         commands: &[RenderCommand],
         scissor: Option<(u32, u32, u32, u32)>,
     ) -> Result<(), Box<dyn Error>> {
+        // Nothing to present while minimized; resume once resized to a valid size
+        if self.zero_sized {
+            return Ok(());
+        }
+        let scissor = if std::mem::take(&mut self.force_full_frame) { None } else { scissor };
+
         // First, get the surface texture to check for size changes
-        let frame = {
-            let surface = self.surface.as_ref().ok_or("Surface not initialized")?;
-            surface.get_current_texture()?
-        };
+        let frame = self.acquire_frame()?;
 
         let actual_width = frame.texture.width();
         let actual_height = frame.texture.height();
@@ -2971,6 +4882,9 @@ impl WgpuBackend {
 
         // Phase 1: Prepare all draw operations and upload buffers
         let prepared = self.prepare_frame(commands);
+        let (draw_calls, triangles) = prepared.layers.iter().map(|ops| count_draws(ops))
+            .fold(count_draws(&prepared.ops), |(calls, tris), (layer_calls, layer_tris)| (calls + layer_calls, tris + layer_tris));
+        self.poll_gpu_timer();
 
         // Upload text atlas if any glyphs were rasterized during text preparation
         let _ = self.upload_atlas_if_needed();
@@ -2978,8 +4892,6 @@ impl WgpuBackend {
         // Phase 2: Render to our persistent frame texture
         let device = self.device.as_ref().ok_or("Device not initialized")?;
         let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
-        let stencil_view = self.stencil_view.as_ref().ok_or("Stencil view not initialized")?;
-        let frame_texture_view = self.frame_texture_view.as_ref().ok_or("Frame texture not initialized")?;
         let blit_pipeline = self.blit_pipeline.as_ref().ok_or("Blit pipeline not initialized")?;
         let blit_bind_group = self.blit_bind_group.as_ref().ok_or("Blit bind group not initialized")?;
 
@@ -2990,20 +4902,403 @@ impl WgpuBackend {
         });
 
         // Render to frame texture (not swapchain)
+        let timestamp_writes = self.gpu_timer.as_ref().and_then(GpuTimer::timestamp_writes);
+        let timed = timestamp_writes.is_some();
+        self.encode_frame_pass(&mut encoder, &prepared, scissor, timestamp_writes)?;
+
+        // Blit frame texture to swapchain
+        encode_blit_pass(&mut encoder, &swapchain_view, blit_pipeline, blit_bind_group);
+
+        if timed {
+            if let Some(timer) = self.gpu_timer.as_mut() {
+                timer.resolve(&mut encoder);
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let submitted = std::time::Instant::now();
+        queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+        #[cfg(not(target_arch = "wasm32"))]
+        let wall_clock_ms = submitted.elapsed().as_secs_f32() * 1000.0;
+        #[cfg(target_arch = "wasm32")]
+        let wall_clock_ms = 0.0;
+
+        if timed {
+            if let Some(timer) = self.gpu_timer.as_ref() {
+                timer.map();
+            }
+        }
+        let gpu_ms = self.gpu_timer.as_ref().and_then(|timer| timer.last_ms);
+        self.last_stats = RenderStats {
+            draw_calls,
+            triangles,
+            frame_time_ms: gpu_ms.unwrap_or(wall_clock_ms),
+            gpu_timed: gpu_ms.is_some(),
+        };
+
+        Ok(())
+    }
+
+    /// Present the last rendered frame again without re-rendering.
+    ///
+    /// Blits the persistent frame texture to a new swapchain image, to keep
+    /// the window showing content while a frame is delayed (see the callback
+    /// watchdog). Does nothing while minimized.
+    pub fn present_last_frame(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.zero_sized {
+            return Ok(());
+        }
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+        let blit_pipeline = self.blit_pipeline.as_ref().ok_or("Blit pipeline not initialized")?;
+        let blit_bind_group = self.blit_bind_group.as_ref().ok_or("Blit bind group not initialized")?;
+
+        let frame = self.acquire_frame()?;
+        let swapchain_view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Re-present Encoder"),
+        });
+        encode_blit_pass(&mut encoder, &swapchain_view, blit_pipeline, blit_bind_group);
+        queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+        Ok(())
+    }
+
+    /// Render commands into the persistent frame texture without presenting.
+    fn render_offscreen(&mut self, commands: &[RenderCommand]) -> Result<(), Box<dyn Error>> {
+        let prepared = self.prepare_frame(commands);
+        let _ = self.upload_atlas_if_needed();
+
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Render Encoder"),
+        });
+        self.encode_frame_pass(&mut encoder, &prepared, None, None)?;
+        queue.submit(std::iter::once(encoder.finish()));
+
+        Ok(())
+    }
+
+    /// Render commands at `width` x `height` pixels and read the frame back
+    /// as tightly packed RGBA8 (sRGB) rows, top row first.
+    ///
+    /// Meant for headless backends (`init_headless`), e.g. golden-image
+    /// tests; the frame texture is resized to match. Nothing is presented.
+    pub fn render_to_pixels(&mut self, commands: &[RenderCommand], width: u32, height: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+        if width == 0 || height == 0 {
+            return Err(format!("Invalid frame size {width}x{height}").into());
+        }
+        if (width, height) != (self.width, self.height) {
+            self.resize(width, height, self.scale_factor)?;
+        }
+        self.render_offscreen(commands)?;
+        self.read_frame_pixels()
+    }
+
+    /// Copy the frame texture back from the GPU as tightly packed rows
+    fn read_frame_pixels(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+        let texture = self.frame_texture.as_ref().ok_or("Frame texture not initialized")?;
+        let (width, height) = (texture.width(), texture.height());
+
+        // Buffer rows are padded to the copy alignment
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Readback Buffer"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let data = buffer.slice(..).get_mapped_range();
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        for row in data.chunks_exact(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        Ok(pixels)
+    }
+
+    /// Render commands into a new texture and return its id for `DrawImage`.
+    ///
+    /// `width` and `height` are in logical pixels, like the commands; the
+    /// texture is that size times the scale factor, so it draws 1:1 as a
+    /// `width` x `height` image. Without a `Clear` command the texture starts
+    /// transparent and composites over whatever it's drawn on. The window's
+    /// frame is untouched. Free the texture with `unload_image`.
+    pub fn render_to_texture(&mut self, commands: &[RenderCommand], width: u32, height: u32) -> Result<u32, Box<dyn Error>> {
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let format = self.surface_config.as_ref().ok_or("Surface not initialized")?.format;
+        let scale = self.scale_factor as f32;
+        let pixel_width = (width as f32 * scale).ceil() as u32;
+        let pixel_height = (height as f32 * scale).ceil() as u32;
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        if pixel_width == 0 || pixel_height == 0 || pixel_width > max_dimension || pixel_height > max_dimension {
+            return Err(format!("Invalid texture size {pixel_width}x{pixel_height} (max {max_dimension})").into());
+        }
+
+        // Same format as the frame texture, so the render pipelines can draw into it
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render Target Texture"),
+            size: wgpu::Extent3d {
+                width: pixel_width,
+                height: pixel_height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let (_stencil_texture, stencil_view) = self.create_stencil_texture(device, pixel_width, pixel_height);
+
+        // Prepare against the texture's size, keeping the window's frame state
+        let window_size = (self.width, self.height);
+        let window_state = (self.continuous_redraw, self.caret_blink);
+        (self.width, self.height) = (pixel_width, pixel_height);
+        let mut prepared = self.prepare_frame(commands);
+        (self.width, self.height) = window_size;
+        (self.continuous_redraw, self.caret_blink) = window_state;
+        if !commands.iter().any(|cmd| matches!(cmd, RenderCommand::Clear(_))) {
+            prepared.clear_color = wgpu::Color::TRANSPARENT;
+        }
+        let _ = self.upload_atlas_if_needed();
+
+        let device = self.device.as_ref().ok_or("Device not initialized")?;
+        let queue = self.queue.as_ref().ok_or("Queue not initialized")?;
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render To Texture Encoder"),
+        });
+        self.encode_pass(&mut encoder, &prepared, &view, &stencil_view, None, None);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let bind_group_layout = self.image_bind_group_layout.as_ref().ok_or("Image bind group layout not initialized")?;
+        let samplers = self.image_samplers.as_ref().ok_or("Image samplers not initialized")?;
+        let bind_groups = image_bind_groups(device, bind_group_layout, samplers, &view, "Render Target Bind Group");
+
+        // Blending into a transparent target leaves premultiplied color
+        let texture_id = self.next_texture_id;
+        self.next_texture_id += 1;
+        self.image_textures.insert(texture_id, GpuTexture {
+            texture,
+            bind_groups,
+            width: pixel_width,
+            height: pixel_height,
+            premultiplied: true,
+        });
+
+        Ok(texture_id)
+    }
+
+    /// Pre-warm pipelines, buffers and the glyph atlas to avoid a first-frame hitch.
+    ///
+    /// All pipelines are compiled during init, but drivers often defer the real
+    /// shader compilation until the first draw that uses them. This renders one
+    /// off-screen frame that touches every pipeline (geometry for plain, rounded,
+    /// bordered and gradient rects and shadows, stencil clipping, text and
+    /// images) and rasterizes printable ASCII for the default font. Nothing is
+    /// presented. Blend modes share the pipelines above, so they need no extra
+    /// work. Call once after init.
+    pub fn prewarm(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.device.is_none() {
+            return Err("Device not initialized".into());
+        }
+
+        // Rasterize common glyphs for the default font at the current scale
+        let default_font = FontDescriptor::default();
+        let scaled_font = FontDescriptor {
+            size: default_font.size * self.scale_factor as f32,
+            ..default_font.clone()
+        };
+        self.glyph_atlas.warm_cache(&mut self.rasterizer, &scaled_font);
+
+        // 1x1 white texture so the image pipeline and its bind group get used
+        let texture_id = self.load_image(&LoadedImage {
+            width: 1,
+            height: 1,
+            data: vec![255, 255, 255, 255],
+            premultiplied: false,
+            frames: Vec::new(),
+            loop_count: 0,
+        })?;
+
+        let warmup = vec![
+            RenderCommand::Clear(crate::style::Color { r: 0, g: 0, b: 0, a: 0 }),
+            RenderCommand::DrawShadow {
+                x: 0.0, y: 0.0, width: 8.0, height: 8.0,
+                blur: 4.0, color: 0x00000001,
+                offset_x: 0.0, offset_y: 1.0,
+                corner_radii: [2.0; 4],
+            },
+            RenderCommand::DrawRect {
+                x: 0.0, y: 0.0, width: 8.0, height: 8.0,
+                color: 0x00000001,
+                corner_radii: [0.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: None,
+                gradient: None,
+                inner_shadow: None,
+                shadow: None,
+            },
+            RenderCommand::DrawRect {
+                x: 0.0, y: 0.0, width: 8.0, height: 8.0,
+                color: 0x00000001,
+                corner_radii: [2.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: Some(crate::render::Border::solid(1.0, 0x00000001)),
+                gradient: Some(crate::render::Gradient::vertical(0x00000001, 0x00000001)),
+                inner_shadow: None,
+                shadow: None,
+            },
+            RenderCommand::DrawArc {
+                cx: 4.0, cy: 4.0, radius: 4.0,
+                start_angle: 0.0, end_angle: 1.0,
+                thickness: 1.0,
+                color: 0x00000001,
+            },
+            RenderCommand::PushRoundedClip {
+                x: 0.0, y: 0.0, width: 8.0, height: 8.0,
+                corner_radii: [2.0; 4],
+            },
+            RenderCommand::DrawText {
+                x: 0.0, y: 0.0,
+                text: "Aa".to_string(),
+                font: default_font,
+                color: 0x00000001,
+                layout: TextLayoutConfig::default(),
+                gradient: None,
+            },
+            RenderCommand::DrawImage {
+                x: 0.0, y: 0.0, width: 1.0, height: 1.0,
+                texture_id,
+                source_rect: None,
+                src: None,
+                tint: None,
+                corner_radii: [0.0; 4],
+                opacity: 1.0,
+                rotation: 0.0,
+                pivot: None,
+                sampling: Sampling::default(),
+            },
+            RenderCommand::PopClip {},
+        ];
+
+        let result = self.render_offscreen(&warmup);
+        self.unload_image(texture_id);
+        result
+    }
+
+    /// Number of render pipelines compiled so far, counted as each one is
+    /// created. Stays constant after init and prewarm; rendering never
+    /// compiles pipelines lazily.
+    pub fn pipeline_creation_count(&self) -> u32 {
+        self.pipelines_created
+    }
+
+    /// Color space the surface presents in: extended sRGB when the
+    /// SurfaceConfig asked for wide gamut and the surface supports it,
+    /// sRGB otherwise (and for headless backends)
+    pub fn surface_color_space(&self) -> SurfaceColorSpace {
+        self.color_space
+    }
+
+    /// Draw calls, triangles and frame time of the last presented frame.
+    ///
+    /// GPU timing starts with the first call: until a frame timed after it
+    /// has been read back, `frame_time_ms` is the wall-clock time of submit
+    /// and present. Devices without timestamp queries always report that.
+    pub fn last_frame_stats(&self) -> RenderStats {
+        self.stats_queried.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.last_stats
+    }
+
+    /// Start GPU frame timing once stats have been asked for, and pick up
+    /// the last measurement
+    fn poll_gpu_timer(&mut self) {
+        let Some(device) = self.device.as_ref() else { return };
+        if self.gpu_timer.is_none()
+            && self.stats_queried.load(std::sync::atomic::Ordering::Relaxed)
+            && device.features().contains(wgpu::Features::TIMESTAMP_QUERY)
         {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Frame Render Pass"),
+            if let Some(queue) = self.queue.as_ref() {
+                self.gpu_timer = Some(GpuTimer::new(device, queue));
+            }
+        }
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.poll(device);
+        }
+    }
+
+    /// Encode the main render pass for a prepared frame into the persistent
+    /// frame texture. The caller decides whether to blit and present it.
+    fn encode_frame_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        prepared: &PreparedFrame,
+        scissor: Option<(u32, u32, u32, u32)>,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+    ) -> Result<(), Box<dyn Error>> {
+        let stencil_view = self.stencil_view.as_ref().ok_or("Stencil view not initialized")?;
+        let frame_texture_view = self.frame_texture_view.as_ref().ok_or("Frame texture not initialized")?;
+        self.encode_pass(encoder, prepared, frame_texture_view, stencil_view, scissor, timestamp_writes);
+        Ok(())
+    }
+
+    /// Encode a render pass drawing a prepared frame into `target`, using
+    /// `stencil_view` (the same size) for rounded clips, and optionally
+    /// timing it with `timestamp_writes`.
+    fn encode_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        prepared: &PreparedFrame,
+        target: &wgpu::TextureView,
+        stencil_view: &wgpu::TextureView,
+        scissor: Option<(u32, u32, u32, u32)>,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+    ) {
+        // Opacity layers render first, each in its own pass over a
+        // transparent target, so the passes that composite them can sample them
+        for (ops, layer) in prepared.layers.iter().zip(&self.opacity_layers) {
+            let view = layer.texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let mut layer_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Opacity Layer Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: frame_texture_view,
+                    view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        // LoadOp::Load preserves previous frame content for partial updates
-                        // LoadOp::Clear for full redraw
-                        load: if scissor.is_some() {
-                            wgpu::LoadOp::Load
-                        } else {
-                            wgpu::LoadOp::Clear(prepared.clear_color)
-                        },
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -3018,48 +5313,51 @@ impl WgpuBackend {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-
-            // Apply scissor rect if provided (partial update)
-            // Clamp to frame texture dimensions to avoid validation errors
-            if let Some((x, y, w, h)) = scissor {
-                let clamped = clamp_scissor_to_viewport(
-                    ScissorRect { x, y, width: w, height: h },
-                    self.width,
-                    self.height,
-                );
-                render_pass.set_scissor_rect(clamped.x, clamped.y, clamped.width, clamped.height);
-            }
-
-            render_pass.set_stencil_reference(0);
-            self.execute_prepared_frame(&mut render_pass, &prepared);
+            layer_pass.set_stencil_reference(0);
+            self.execute_prepared_frame(&mut layer_pass, ops);
         }
 
-        // Blit frame texture to swapchain
-        {
-            let mut blit_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Blit Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &swapchain_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Frame Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    // LoadOp::Load preserves previous frame content for partial updates
+                    // LoadOp::Clear for full redraw
+                    load: if scissor.is_some() {
+                        wgpu::LoadOp::Load
+                    } else {
+                        wgpu::LoadOp::Clear(prepared.clear_color)
                     },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: stencil_view,
+                depth_ops: None,
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Store,
+                }),
+            }),
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
 
-            blit_pass.set_pipeline(blit_pipeline);
-            blit_pass.set_bind_group(0, blit_bind_group, &[]);
-            blit_pass.draw(0..3, 0..1); // Fullscreen triangle
+        // Apply scissor rect if provided (partial update)
+        // Clamp to frame texture dimensions to avoid validation errors
+        if let Some((x, y, w, h)) = scissor {
+            let clamped = clamp_scissor_to_viewport(
+                ScissorRect { x, y, width: w, height: h },
+                self.width,
+                self.height,
+            );
+            render_pass.set_scissor_rect(clamped.x, clamped.y, clamped.width, clamped.height);
         }
 
-        queue.submit(std::iter::once(encoder.finish()));
-        frame.present();
-
-        Ok(())
+        render_pass.set_stencil_reference(0);
+        self.execute_prepared_frame(&mut render_pass, &prepared.ops);
     }
 
     /// Render raw triangles with custom vertices
@@ -3163,8 +5461,8 @@ impl WgpuBackend {
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        // Set stencil reference to 1 - this is what gets written to the stencil buffer
-        render_pass.set_stencil_reference(1);
+        // Increment pixels still at 0 to 1, the reference content tests against
+        render_pass.set_stencil_reference(0);
 
         // Use stencil pipeline and draw
         render_pass.set_pipeline(pipeline);
@@ -3175,7 +5473,7 @@ impl WgpuBackend {
         Ok(())
     }
 
-    /// Render a rectangle with optional rounded corners, border, gradient, and rotation
+    /// Render a rectangle with optional rounded corners, border, gradient, shadows, and rotation
     #[allow(clippy::too_many_arguments)]
     fn render_rect(
         &mut self,
@@ -3187,8 +5485,12 @@ impl WgpuBackend {
         color: u32,
         corner_radii: [f32; 4],
         rotation: f32,
+        pivot: Option<(f32, f32)>,
         border: Option<&crate::render::Border>,
         gradient: Option<&crate::render::Gradient>,
+        inner_shadow: Option<&crate::render::InnerShadow>,
+        shadow: Option<&crate::render::BoxShadow>,
+        opacity: f32,
     ) -> Result<(), Box<dyn Error>> {
         // Scale coordinates for HiDPI
         // Floor positions to align with pixel boundaries (matches scissor rect truncation)
@@ -3206,6 +5508,29 @@ impl WgpuBackend {
             corner_radii[3] * scale,
         ];
 
+        // Rotate about the pivot (the center unless one is given)
+        let rotation = crate::geometry::Rotation::about(
+            crate::geometry::Rect::new(scaled_x, scaled_y, scaled_width, scaled_height),
+            rotation,
+            pivot.map(|(px, py)| (px * scale, py * scale)),
+        );
+
+        // Render the drop shadow first, behind the fill
+        if let Some(shadow) = shadow {
+            let (shadow_vertices, shadow_indices) = crate::geometry::box_shadow_rect(
+                scaled_x,
+                scaled_y,
+                scaled_width,
+                scaled_height,
+                scaled_radii,
+                &scale_box_shadow(shadow, scale),
+            );
+            if !shadow_indices.is_empty() {
+                let ndc_shadow_vertices = with_opacity(self.rotated_ndc_vertices(&shadow_vertices, rotation), opacity);
+                self.render_triangles(render_pass, &ndc_shadow_vertices, &shadow_indices)?;
+            }
+        }
+
         // Generate geometry - use gradient if present, otherwise solid color
         // Both functions support rounded corners via the radii parameter
         let (vertices, indices) = if let Some(gradient) = gradient {
@@ -3228,68 +5553,42 @@ impl WgpuBackend {
             )
         };
 
-        // Compute center for rotation
-        let center_x = scaled_x + scaled_width / 2.0;
-        let center_y = scaled_y + scaled_height / 2.0;
-        let cos_r = rotation.cos();
-        let sin_r = rotation.sin();
-
-        // Convert screen-space vertices to NDC, applying rotation around center
-        let ndc_vertices: Vec<crate::render::Vertex> = vertices.iter().map(|v| {
-            // Rotate around center if rotation is non-zero
-            let (rx, ry) = if rotation.abs() > 0.0001 {
-                let dx = v.position[0] - center_x;
-                let dy = v.position[1] - center_y;
-                let rotated_x = center_x + dx * cos_r - dy * sin_r;
-                let rotated_y = center_y + dx * sin_r + dy * cos_r;
-                (rotated_x, rotated_y)
-            } else {
-                (v.position[0], v.position[1])
-            };
-            let ndc = self.screen_to_ndc(rx, ry);
-            crate::render::Vertex {
-                position: [ndc[0], ndc[1], 0.0],
-                texcoord: v.texcoord,
-                color: v.color,
-            }
-        }).collect();
+        // Convert screen-space vertices to NDC, applying rotation
+        let ndc_vertices = with_opacity(self.rotated_ndc_vertices(&vertices, rotation), opacity);
 
         // Render the fill
         self.render_triangles(render_pass, &ndc_vertices, &indices)?;
 
+        // Render inner shadow if present (over the fill, under the border)
+        if let Some(shadow) = inner_shadow {
+            let (shadow_vertices, shadow_indices) = crate::geometry::inner_shadow_rect(
+                scaled_x,
+                scaled_y,
+                scaled_width,
+                scaled_height,
+                scaled_radii,
+                &scale_inner_shadow(shadow, scale),
+            );
+            if !shadow_indices.is_empty() {
+                let ndc_shadow_vertices = with_opacity(self.rotated_ndc_vertices(&shadow_vertices, rotation), opacity);
+                self.render_triangles(render_pass, &ndc_shadow_vertices, &shadow_indices)?;
+            }
+        }
+
         // Render border if present
         if let Some(border) = border {
-            let scaled_border_width = border.width * scale;
-            let (border_vertices, border_indices) = crate::geometry::border_rect(
+            let (border_vertices, border_indices) = crate::geometry::styled_border(
                 scaled_x,
                 scaled_y,
                 scaled_width,
                 scaled_height,
-                scaled_border_width,
-                border.color,
+                &scale_border(border, scale),
                 scaled_radii,
             );
-
-            let ndc_border_vertices: Vec<crate::render::Vertex> = border_vertices.iter().map(|v| {
-                // Rotate around center if rotation is non-zero
-                let (rx, ry) = if rotation.abs() > 0.0001 {
-                    let dx = v.position[0] - center_x;
-                    let dy = v.position[1] - center_y;
-                    let rotated_x = center_x + dx * cos_r - dy * sin_r;
-                    let rotated_y = center_y + dx * sin_r + dy * cos_r;
-                    (rotated_x, rotated_y)
-                } else {
-                    (v.position[0], v.position[1])
-                };
-                let ndc = self.screen_to_ndc(rx, ry);
-                crate::render::Vertex {
-                    position: [ndc[0], ndc[1], 0.0],
-                    texcoord: v.texcoord,
-                    color: v.color,
-                }
-            }).collect();
-
-            self.render_triangles(render_pass, &ndc_border_vertices, &border_indices)?;
+            if !border_indices.is_empty() {
+                let ndc_border_vertices = with_opacity(self.rotated_ndc_vertices(&border_vertices, rotation), opacity);
+                self.render_triangles(render_pass, &ndc_border_vertices, &border_indices)?;
+            }
         }
 
         Ok(())
@@ -3399,10 +5698,8 @@ impl WgpuBackend {
         // Calculate line height based on actual font metrics, not font_size
         let line_height_px = actual_font_height * layout.line_height;
 
-        // Calculate letter and word spacing (em units -> pixels)
-        // letter_spacing applies to every character, word_spacing applies additionally to spaces
-        let letter_spacing_px = layout.letter_spacing * font_size;
-        let word_spacing_px = layout.word_spacing * font_size;
+        // Letter spacing goes between clusters, word spacing at word separators (em -> pixels)
+        let spacing = Spacing::from_layout(layout, font_size);
 
         // Pre-compute font ID for glyph cache lookups
         use std::collections::hash_map::DefaultHasher;
@@ -3414,7 +5711,7 @@ impl WgpuBackend {
         // Break text into lines based on layout config
         let all_lines = self.layout_text_lines(
             text, &scaled_font, font_id, font_size, scaled_max_width,
-            letter_spacing_px, word_spacing_px, layout, scale
+            spacing, layout, scale
         )?;
 
         // Calculate max lines based on max_lines setting and max_height
@@ -3454,42 +5751,7 @@ impl WgpuBackend {
 
             // Truncate the last line and add ellipsis
             if let Some(last_line) = truncated_lines.last_mut() {
-                // Rasterize ellipsis
-                let ellipsis_glyphs = self.rasterize_text_segment("…", &scaled_font, font_id, font_size)?;
-                let ellipsis_width: f32 = ellipsis_glyphs.iter().map(|g| g.entry.advance).sum();
-
-                // If we have a max_width, we need to truncate the line to fit ellipsis
-                if let Some(max_w) = scaled_max_width {
-                    let target_width = max_w - ellipsis_width;
-                    if target_width > 0.0 {
-                        // Truncate glyphs until we fit
-                        let mut current_width = 0.0f32;
-                        let mut truncate_at = 0;
-
-                        for (i, glyph) in last_line.glyphs.iter().enumerate() {
-                            if current_width + glyph.entry.advance > target_width {
-                                break;
-                            }
-                            current_width += glyph.entry.advance;
-                            truncate_at = i + 1;
-                        }
-
-                        last_line.glyphs.truncate(truncate_at);
-                        // Trim trailing spaces before ellipsis
-                        while last_line.glyphs.last().map(|g| g.character == ' ').unwrap_or(false) {
-                            last_line.glyphs.pop();
-                        }
-                        last_line.width = last_line.glyphs.iter().map(|g| g.entry.advance).sum();
-                    } else {
-                        // Not enough room even for ellipsis - just use ellipsis
-                        last_line.glyphs.clear();
-                        last_line.width = 0.0;
-                    }
-                }
-
-                // Add ellipsis glyphs
-                last_line.glyphs.extend(ellipsis_glyphs);
-                last_line.width += ellipsis_width;
+                self.append_ellipsis(text, last_line, scaled_max_width, &scaled_font, font_id, font_size, spacing)?;
             }
 
             truncated_lines
@@ -3516,8 +5778,8 @@ impl WgpuBackend {
 
             // Calculate X offset for alignment and justify spacing
             let is_last_line = line_index == lines.len() - 1;
-            let (line_x, justify_extra_space) = match layout.alignment {
-                TextAlign::Left => (scaled_x, 0.0),
+            let (line_x, justify_extra_space) = match layout.alignment.resolve(layout.base_direction.resolve(text)) {
+                TextAlign::Left | TextAlign::Start | TextAlign::End => (scaled_x, 0.0),
                 TextAlign::Center => {
                     let x = if let Some(max_w) = scaled_max_width {
                         scaled_x + (max_w - line.width) / 2.0
@@ -3562,7 +5824,8 @@ impl WgpuBackend {
 
             // Render each glyph in the line
             let mut current_x = line_x;
-            for glyph_info in &line.glyphs {
+            let glyph_count = line.glyphs.len();
+            for (glyph_idx, glyph_info) in line.glyphs.iter().enumerate() {
                 let entry = glyph_info.entry;
 
                 // For emojis, use white color (no tint) so they render with native colors
@@ -3595,18 +5858,21 @@ impl WgpuBackend {
                     tex_coords: [entry.u0, entry.v0],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
                 vertices.push(TextVertex {
                     position: bottom_left,
                     tex_coords: [entry.u0, entry.v1],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
                 vertices.push(TextVertex {
                     position: top_right,
                     tex_coords: [entry.u1, entry.v0],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
 
                 // Triangle 2: top-right, bottom-left, bottom-right
@@ -3615,24 +5881,27 @@ impl WgpuBackend {
                     tex_coords: [entry.u1, entry.v0],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
                 vertices.push(TextVertex {
                     position: bottom_left,
                     tex_coords: [entry.u0, entry.v1],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
                 vertices.push(TextVertex {
                     position: bottom_right,
                     tex_coords: [entry.u1, entry.v1],
                     color: glyph_color,
                     use_texture_color,
+                    ..Default::default()
                 });
 
                 // Advance cursor with letter spacing (and word spacing + justify for spaces)
-                let mut advance = entry.advance + letter_spacing_px;
+                let mut advance = entry.advance + spacing.after_cluster(glyph_info.character, glyph_idx + 1 == glyph_count);
                 if glyph_info.character == ' ' {
-                    advance += word_spacing_px + justify_extra_space;
+                    advance += justify_extra_space;
                 }
                 current_x += advance;
             }
@@ -3667,19 +5936,11 @@ impl WgpuBackend {
     }
 
     /// Calculate the width of glyphs including letter and word spacing
-    /// Note: letter_spacing is added BETWEEN letters, not after the last one
-    fn calculate_glyphs_width(glyphs: &[GlyphInfo], letter_spacing: f32, word_spacing: f32) -> f32 {
+    /// Note: letter_spacing is added BETWEEN clusters, not after the last one
+    fn calculate_glyphs_width(glyphs: &[GlyphInfo], spacing: Spacing) -> f32 {
         let len = glyphs.len();
         glyphs.iter().enumerate().map(|(i, g)| {
-            let mut advance = g.entry.advance;
-            // Add letter_spacing between letters (not after the last one)
-            if i < len - 1 {
-                advance += letter_spacing;
-            }
-            if g.character == ' ' {
-                advance += word_spacing;
-            }
-            advance
+            g.entry.advance + spacing.after_cluster(g.character, i + 1 == len)
         }).sum()
     }
 
@@ -3692,8 +5953,7 @@ impl WgpuBackend {
         font_id: u64,
         font_size: f32,
         max_width: Option<f32>,
-        _letter_spacing: f32,
-        _word_spacing: f32,
+        spacing: Spacing,
         layout: &TextLayoutConfig,
         scale: f32,
     ) -> Result<Vec<TextLine>, Box<dyn Error>> {
@@ -3710,83 +5970,43 @@ impl WgpuBackend {
             text.split('\n').collect()
         };
 
+        let mut next_paragraph_start = 0;
         for paragraph in paragraphs {
+            let paragraph_start = next_paragraph_start;
+            next_paragraph_start += paragraph.len() + 1;
+
             if paragraph.is_empty() {
                 // Empty line (from double newline or trailing newline)
-                lines.push(TextLine { glyphs: Vec::new(), width: 0.0 });
+                lines.push(TextLine { glyphs: Vec::new(), width: 0.0, range: paragraph_start..paragraph_start });
                 continue;
             }
 
             if !should_wrap || max_width.is_none() {
                 // No wrapping - render entire paragraph as one line
-                let glyphs = self.rasterize_text_segment(paragraph, scaled_font, font_id, font_size)?;
-                let width = self.rasterizer.measure_string(paragraph, scaled_font);
-                lines.push(TextLine { glyphs, width });
+                let glyphs = self.rasterize_text_segment(paragraph, paragraph_start, scaled_font, font_id, font_size)?;
+                let width = self.rasterizer.measure_string(paragraph, scaled_font) + spacing.extra_width(paragraph);
+                let range = paragraph_start..paragraph_start + paragraph.len();
+                lines.push(TextLine { glyphs, width, range });
             } else {
-                // Character-by-character wrapping to match Go's algorithm exactly
-                // This ensures wrap decisions are identical between Go layout and Rust rendering
+                // Cluster-by-cluster wrapping at UAX #14 break opportunities;
+                // Go wraps through the same engine call so layout and
+                // rendering agree on where lines end
                 let max_w = max_width.unwrap();
                 // Go uses 1.0 logical pixel tolerance, so we need scale * 1.0 physical pixels
                 let overflow_tolerance = scale;
 
-                let chars: Vec<char> = paragraph.chars().collect();
-                let mut line_start = 0;
-                let mut last_word_end = 0; // Position after last space (word boundary)
-                let mut i = 0;
-
-                while i < chars.len() {
-                    let ch = chars[i];
-
-                    // Track word boundaries
-                    if ch.is_whitespace() {
-                        last_word_end = i + 1;
-                    }
-
-                    // Measure text from line_start to i+1 (inclusive of current char)
-                    let line_text: String = chars[line_start..=i].iter().collect();
-                    let line_width = self.rasterizer.measure_string(&line_text, scaled_font);
-
-                    if line_width > max_w + overflow_tolerance && i > line_start {
-                        // Need to wrap - find break point
-                        let break_point = if last_word_end > line_start {
-                            // Break at last word boundary
-                            last_word_end
-                        } else {
-                            // No word boundary found, break at current character
-                            i
-                        };
-
-                        // Create line from line_start to break_point
-                        let final_line_text: String = chars[line_start..break_point].iter().collect();
-                        let final_line_width = self.rasterizer.measure_string(&final_line_text, scaled_font);
-                        let line_glyphs = self.rasterize_text_segment(&final_line_text, scaled_font, font_id, font_size)?;
-                        lines.push(TextLine {
-                            glyphs: line_glyphs,
-                            width: final_line_width,
-                        });
-
-                        // Skip whitespace at start of next line (matching Go behavior)
-                        line_start = break_point;
-                        while line_start < chars.len() && chars[line_start] == ' ' {
-                            line_start += 1;
-                        }
-                        i = line_start;
-                        last_word_end = line_start;
-                        continue;
-                    }
-
-                    i += 1;
-                }
-
-                // Add remaining text as final line
-                if line_start < chars.len() {
-                    let final_line_text: String = chars[line_start..].iter().collect();
-                    let final_line_width = self.rasterizer.measure_string(&final_line_text, scaled_font);
-                    let line_glyphs = self.rasterize_text_segment(&final_line_text, scaled_font, font_id, font_size)?;
-                    lines.push(TextLine {
-                        glyphs: line_glyphs,
-                        width: final_line_width,
-                    });
+                let ranges = crate::text::linebreak::wrap_paragraph(
+                    paragraph, layout.word_break, max_w + overflow_tolerance,
+                    |line| self.rasterizer.measure_string(line, scaled_font) + spacing.extra_width(line),
+                );
+                for range in ranges {
+                    let line_text = &paragraph[range.clone()];
+                    let width = self.rasterizer.measure_string(line_text, scaled_font) + spacing.extra_width(line_text);
+                    let glyphs = self.rasterize_text_segment(
+                        line_text, paragraph_start + range.start, scaled_font, font_id, font_size,
+                    )?;
+                    let range = paragraph_start + range.start..paragraph_start + range.end;
+                    lines.push(TextLine { glyphs, width, range });
                 }
             }
         }
@@ -3797,8 +6017,8 @@ impl WgpuBackend {
     /// Tokenize text into words (including trailing spaces)
     fn tokenize_text(&self, text: &str, preserve_whitespace: bool) -> Vec<String> {
         if preserve_whitespace {
-            // Keep all whitespace as-is, split into individual characters
-            text.chars().map(|c| c.to_string()).collect()
+            // Keep all whitespace as-is, split into individual grapheme clusters
+            crate::text::grapheme::graphemes(text).map(|(_, g)| g.to_string()).collect()
         } else {
             // Split by whitespace but keep space attached to previous word for proper measuring
             let mut words = Vec::new();
@@ -3839,26 +6059,34 @@ impl WgpuBackend {
         }
     }
 
-    /// Rasterize a text segment and return glyph info
+    /// Rasterize a text segment and return glyph info.
+    ///
+    /// `start` is the segment's byte offset in the full text, so glyph byte
+    /// indices refer to the full text.
     #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows"))]
     fn rasterize_text_segment(
         &mut self,
         text: &str,
+        start: usize,
         scaled_font: &FontDescriptor,
         font_id: u64,
         font_size: f32,
     ) -> Result<Vec<GlyphInfo>, Box<dyn Error>> {
         let mut glyphs = Vec::new();
 
-        for ch in text.chars() {
-            let glyph_key = crate::text::GlyphKey::new(font_id, ch as u32, font_size);
+        // One glyph per grapheme cluster so emoji ZWJ sequences stay whole
+        for (offset, grapheme) in crate::text::grapheme::graphemes(text) {
+            let Some(ch) = crate::text::grapheme::base_char(grapheme) else { continue };
+            let glyph_key = crate::text::GlyphKey::for_grapheme(font_id, grapheme, font_size);
 
             // Get or rasterize glyph
             let entry = if let Some(e) = self.glyph_atlas.get(&glyph_key) {
                 *e
             } else {
-                // Rasterize the glyph with full font descriptor
-                if let Some(bitmap) = self.rasterizer.rasterize_glyph(ch, scaled_font) {
+                // Rasterize the cluster with the first font in the fallback
+                // chain that has it
+                let cluster_font = self.rasterizer.font_for_grapheme(grapheme, scaled_font);
+                if let Some(bitmap) = self.rasterizer.rasterize_grapheme(grapheme, &cluster_font) {
                     self.glyph_atlas.insert(glyph_key, bitmap)
                         .ok_or_else(|| "Failed to insert glyph into atlas")?
                 } else {
@@ -3867,7 +6095,7 @@ impl WgpuBackend {
                 }
             };
 
-            glyphs.push(GlyphInfo { character: ch, entry, is_emoji: is_emoji(ch) });
+            glyphs.push(GlyphInfo { character: ch, byte_index: start + offset, key: glyph_key, entry, is_emoji: is_emoji(ch) });
         }
 
         Ok(glyphs)
@@ -3889,6 +6117,7 @@ impl WgpuBackend {
     }
 
     /// Render an image at the given position
+    #[allow(clippy::too_many_arguments)]
     fn render_image(
         &self,
         render_pass: &mut wgpu::RenderPass,
@@ -3898,7 +6127,13 @@ impl WgpuBackend {
         height: f32,
         texture_id: u32,
         source_rect: Option<(f32, f32, f32, f32)>,
+        src: Option<Rect>,
+        tint: Option<u32>,
         corner_radii: [f32; 4],
+        opacity: f32,
+        rotation: f32,
+        pivot: Option<(f32, f32)>,
+        sampling: Sampling,
     ) -> Result<(), Box<dyn Error>> {
         let gpu_texture = self.image_textures.get(&texture_id)
             .ok_or_else(|| format!("Texture {} not found", texture_id))?;
@@ -3913,12 +6148,18 @@ impl WgpuBackend {
         let scaled_y = y * scale;
         let scaled_w = width * scale;
         let scaled_h = height * scale;
+        let rotation = crate::geometry::Rotation::about(
+            crate::geometry::Rect::new(scaled_x, scaled_y, scaled_w, scaled_h),
+            rotation, pivot.map(|(px, py)| (px * scale, py * scale)),
+        );
 
         // Texture coordinates (source rect or full texture)
-        let (u0, v0, u1, v1) = source_rect.unwrap_or((0.0, 0.0, 1.0, 1.0));
+        let (u0, v0, u1, v1) = image_uvs(source_rect, src, gpu_texture.width, gpu_texture.height);
 
-        // White color = no tint, full opacity
-        let color = [1.0f32, 1.0, 1.0, 1.0];
+        // White color = no tint
+        let [r, g, b, a] = tint.map_or([1.0; 4], crate::geometry::color_to_rgba);
+        let color = [r, g, b, a * opacity];
+        let use_texture_color = if gpu_texture.premultiplied { 1.0 } else { 0.0 };
 
         // Check if we have rounded corners
         let has_rounded = corner_radii.iter().any(|&r| r > 0.5);
@@ -3930,6 +6171,8 @@ impl WgpuBackend {
                 corner_radii.map(|r| r * scale),
                 u0, v0, u1, v1,
                 color,
+                use_texture_color,
+                rotation,
             )
         } else {
             // Simple quad - 2 triangles, 6 vertices
@@ -3939,20 +6182,24 @@ impl WgpuBackend {
             let bottom = scaled_y + scaled_h;
 
             // Convert to NDC
-            let tl = self.screen_to_ndc(left, top);
-            let tr = self.screen_to_ndc(right, top);
-            let bl = self.screen_to_ndc(left, bottom);
-            let br = self.screen_to_ndc(right, bottom);
+            let to_ndc = |px: f32, py: f32| {
+                let (rx, ry) = rotation.apply(px, py);
+                self.screen_to_ndc(rx, ry)
+            };
+            let tl = to_ndc(left, top);
+            let tr = to_ndc(right, top);
+            let bl = to_ndc(left, bottom);
+            let br = to_ndc(right, bottom);
 
             vec![
-                // Triangle 1 - images always use texture color directly
-                TextVertex { position: tl, tex_coords: [u0, v0], color, use_texture_color: 1.0 },
-                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color: 1.0 },
-                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color: 1.0 },
+                // Triangle 1
+                TextVertex { position: tl, tex_coords: [u0, v0], color, use_texture_color, ..Default::default() },
+                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color, ..Default::default() },
+                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color, ..Default::default() },
                 // Triangle 2
-                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color: 1.0 },
-                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color: 1.0 },
-                TextVertex { position: br, tex_coords: [u1, v1], color, use_texture_color: 1.0 },
+                TextVertex { position: tr, tex_coords: [u1, v0], color, use_texture_color, ..Default::default() },
+                TextVertex { position: bl, tex_coords: [u0, v1], color, use_texture_color, ..Default::default() },
+                TextVertex { position: br, tex_coords: [u1, v1], color, use_texture_color, ..Default::default() },
             ]
         };
 
@@ -3965,7 +6212,7 @@ impl WgpuBackend {
 
         // Set pipeline and bind group for this texture
         render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, &gpu_texture.bind_group, &[]);
+        render_pass.set_bind_group(0, gpu_texture.bind_group(sampling), &[]);
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.draw(0..vertices.len() as u32, 0..1);
 
@@ -3973,6 +6220,7 @@ impl WgpuBackend {
     }
 
     /// Generate vertices for a rounded rectangle with proper UV mapping for images
+    #[allow(clippy::too_many_arguments)]
     fn generate_rounded_image_vertices(
         &self,
         x: f32,
@@ -3985,21 +6233,23 @@ impl WgpuBackend {
         u1: f32,
         v1: f32,
         color: [f32; 4],
+        use_texture_color: f32,
+        rotation: crate::geometry::Rotation,
     ) -> Vec<TextVertex> {
         use std::f32::consts::PI;
         const CORNER_SEGMENTS: usize = 8;
 
         // Clamp radii to half the smallest dimension
-        let max_radius = (width.min(height)) / 2.0;
-        let radii = [
-            radii[0].min(max_radius),
-            radii[1].min(max_radius),
-            radii[2].min(max_radius),
-            radii[3].min(max_radius),
-        ];
+        let radii = crate::geometry::clamp_radii(radii, width, height);
 
         let mut vertices = Vec::new();
 
+        // Helper to convert screen position to rotated NDC
+        let to_ndc = |px: f32, py: f32| {
+            let (rx, ry) = rotation.apply(px, py);
+            self.screen_to_ndc(rx, ry)
+        };
+
         // Helper to convert screen position to UV
         let pos_to_uv = |px: f32, py: f32| -> [f32; 2] {
             let u = u0 + (px - x) / width * (u1 - u0);
@@ -4010,7 +6260,7 @@ impl WgpuBackend {
         // Center point for fan triangulation
         let center_x = x + width / 2.0;
         let center_y = y + height / 2.0;
-        let center_ndc = self.screen_to_ndc(center_x, center_y);
+        let center_ndc = to_ndc(center_x, center_y);
         let center_uv = pos_to_uv(center_x, center_y);
 
         // Generate vertices along the perimeter and create triangles to center
@@ -4057,31 +6307,204 @@ impl WgpuBackend {
             let p1 = perimeter_points[i];
             let p2 = perimeter_points[(i + 1) % num_points];
 
-            let p1_ndc = self.screen_to_ndc(p1.0, p1.1);
-            let p2_ndc = self.screen_to_ndc(p2.0, p2.1);
+            let p1_ndc = to_ndc(p1.0, p1.1);
+            let p2_ndc = to_ndc(p2.0, p2.1);
             let p1_uv = pos_to_uv(p1.0, p1.1);
             let p2_uv = pos_to_uv(p2.0, p2.1);
 
             // Triangle: center, p1, p2 - images always use texture color directly
-            vertices.push(TextVertex { position: center_ndc, tex_coords: center_uv, color, use_texture_color: 1.0 });
-            vertices.push(TextVertex { position: p1_ndc, tex_coords: p1_uv, color, use_texture_color: 1.0 });
-            vertices.push(TextVertex { position: p2_ndc, tex_coords: p2_uv, color, use_texture_color: 1.0 });
+            vertices.push(TextVertex { position: center_ndc, tex_coords: center_uv, color, use_texture_color, ..Default::default() });
+            vertices.push(TextVertex { position: p1_ndc, tex_coords: p1_uv, color, use_texture_color, ..Default::default() });
+            vertices.push(TextVertex { position: p2_ndc, tex_coords: p2_uv, color, use_texture_color, ..Default::default() });
         }
 
         vertices
     }
 }
 
+/// Tag the CAMetalLayer wgpu presents to with extended linear sRGB, the
+/// space half-float swapchains hold. wgpu turns on EDR for such layers but
+/// leaves their color space unset, which skips color matching.
+///
+/// On macOS wgpu makes the view's own layer the CAMetalLayer; on iOS the
+/// view's layer may instead hold it as a sublayer.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe fn tag_metal_layer_extended_srgb(view: *mut std::ffi::c_void) {
+    use core_graphics::color_space::kCGColorSpaceExtendedLinearSRGB;
+    use objc::runtime::{Object, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    extern "C" {
+        fn CGColorSpaceCreateWithName(name: core_foundation::string::CFStringRef) -> *mut std::ffi::c_void;
+        fn CGColorSpaceRelease(space: *mut std::ffi::c_void);
+    }
+
+    let view = view as *mut Object;
+    let main_layer: *mut Object = msg_send![view, layer];
+    if main_layer.is_null() {
+        return;
+    }
+    let is_metal = |layer: *mut Object| -> bool {
+        let is_metal: BOOL = msg_send![layer, isKindOfClass: class!(CAMetalLayer)];
+        is_metal == YES
+    };
+    let mut metal_layer = is_metal(main_layer).then_some(main_layer);
+    if metal_layer.is_none() {
+        let sublayers: *mut Object = msg_send![main_layer, sublayers];
+        let count: usize = if sublayers.is_null() { 0 } else { msg_send![sublayers, count] };
+        metal_layer = (0..count)
+            .map(|i| -> *mut Object { msg_send![sublayers, objectAtIndex: i] })
+            .find(|&layer| is_metal(layer));
+    }
+
+    if let Some(layer) = metal_layer {
+        let space = CGColorSpaceCreateWithName(kCGColorSpaceExtendedLinearSRGB);
+        if !space.is_null() {
+            let _: () = msg_send![layer, setColorspace: space];
+            CGColorSpaceRelease(space);
+        }
+    }
+}
+
+/// Blit the persistent frame texture to a swapchain image (fullscreen triangle)
+fn encode_blit_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    target: &wgpu::TextureView,
+    blit_pipeline: &wgpu::RenderPipeline,
+    blit_bind_group: &wgpu::BindGroup,
+) {
+    let mut blit_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Blit Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    blit_pass.set_pipeline(blit_pipeline);
+    blit_pass.set_bind_group(0, blit_bind_group, &[]);
+    blit_pass.draw(0..3, 0..1);
+}
+
 /// Information about a laid-out line of text
+#[derive(Clone)]
 struct TextLine {
     glyphs: Vec<GlyphInfo>,
     width: f32,
+    /// Bytes of the text the line was laid out from, without an ellipsis
+    range: std::ops::Range<usize>,
+}
+
+impl TextLine {
+    /// Offset of the line from the text origin for its alignment, and the
+    /// extra advance each space gets when justified. Start and End, and
+    /// lines that aren't justified, follow the paragraph `direction`.
+    fn alignment_offset(&self, layout: &TextLayoutConfig, direction: Direction, max_width: Option<f32>, is_last_line: bool) -> (f32, f32) {
+        let Some(max_w) = max_width else { return (0.0, 0.0) };
+        match layout.alignment.resolve(direction) {
+            TextAlign::Left | TextAlign::Start | TextAlign::End => (0.0, 0.0),
+            TextAlign::Center => ((max_w - self.width) / 2.0, 0.0),
+            TextAlign::Right => (max_w - self.width, 0.0),
+            TextAlign::Justify => {
+                let space_count = self.glyphs.iter().filter(|g| g.character == ' ').count();
+                if is_last_line || space_count == 0 {
+                    let start = if direction == Direction::Rtl { max_w - self.width } else { 0.0 };
+                    (start, 0.0)
+                } else {
+                    (0.0, (max_w - self.width) / space_count as f32)
+                }
+            }
+        }
+    }
+
+    /// Pen advance after glyph `index`, including spacing and justification
+    fn glyph_advance(&self, index: usize, spacing: Spacing, justify_extra_space: f32) -> f32 {
+        let glyph = &self.glyphs[index];
+        let mut advance = glyph.entry.advance + spacing.after_cluster(glyph.character, index + 1 == self.glyphs.len());
+        if glyph.character == ' ' {
+            advance += justify_extra_space;
+        }
+        advance
+    }
+}
+
+/// Byte offset just past the cluster `glyph` was laid out from
+fn cluster_end(text: &str, glyph: &GlyphInfo) -> usize {
+    text.get(glyph.byte_index..)
+        .and_then(|rest| crate::text::grapheme::graphemes(rest).next())
+        .map_or(text.len(), |(_, cluster)| glyph.byte_index + cluster.len())
+}
+
+/// Ellipsis glyphs stand in for the cut text, so they map to where the cut
+/// starts: just past the last glyph kept on the line
+fn ellipsis_at_cut(text: &str, kept: &[GlyphInfo], mut ellipsis: Vec<GlyphInfo>) -> Vec<GlyphInfo> {
+    let cut = kept.last().map_or(text.len(), |g| cluster_end(text, g));
+    for glyph in &mut ellipsis {
+        glyph.byte_index = cut;
+    }
+    ellipsis
+}
+
+/// Where a cluster of a DrawText command is drawn, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphPosition {
+    /// Byte offset of the cluster in the text
+    pub byte_index: usize,
+    /// Left edge of the cluster's advance (absolute, like the command's x)
+    pub x: f32,
+    /// Pen advance to the next cluster
+    pub advance: f32,
+    /// Visible line the cluster is on
+    pub line: u32,
+}
+
+/// Size of a DrawText command's laid-out text, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextMeasurement {
+    /// Widest visible line
+    pub width: f32,
+    /// First line's ascent to last line's descent
+    pub height: f32,
+    /// Visible lines
+    pub line_count: u32,
+}
+
+/// Visible lines of a DrawText command with the metrics used to place them
+#[derive(Clone)]
+struct VisibleText {
+    lines: Vec<TextLine>,
+    /// Paragraph direction the lines are ordered and aligned in
+    direction: Direction,
+    font_size: f32,
+    ascent: f32,
+    descent: f32,
+    line_height_px: f32,
+}
+
+impl VisibleText {
+    /// Widest line by first ascent to last descent, in physical pixels
+    fn box_size(&self) -> (f32, f32) {
+        let width = self.lines.iter().map(|l| l.width).fold(0.0, f32::max);
+        let height = self.ascent + self.descent + self.lines.len().saturating_sub(1) as f32 * self.line_height_px;
+        (width, height)
+    }
 }
 
 /// Information about a single glyph for layout
 #[derive(Clone, Copy)]
 struct GlyphInfo {
     character: char,
+    /// Byte offset of the glyph's cluster in the laid-out text
+    byte_index: usize,
+    /// Atlas key of the glyph, to find `entry` again for a cached layout
+    key: crate::text::GlyphKey,
     entry: crate::text::AtlasEntry,
     is_emoji: bool,
 }
@@ -4122,7 +6545,7 @@ fn is_emoji(c: char) -> bool {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 struct TextVertex {
     position: [f32; 2],
     tex_coords: [f32; 2],
@@ -4130,6 +6553,37 @@ struct TextVertex {
     /// 1.0 = use texture RGB directly (for emojis)
     /// 0.0 = use vertex color RGB (for regular text)
     use_texture_color: f32,
+    /// Position in the gradient's box from its top-left, and the box's
+    /// width and height, in pixels
+    gradient_box: [f32; 4],
+    /// Gradient evaluated per fragment: kind (0 = none, use `color`;
+    /// 1 = linear, 2 = radial, 3 = conic) and its parameters
+    gradient: [f32; 4],
+    /// Ramp texture v coordinate of the gradient's colors
+    gradient_row: f32,
+}
+
+impl TextVertex {
+    /// `gradient` field for a gradient: its kind and parameters
+    fn gradient_params(gradient: &crate::render::Gradient) -> [f32; 4] {
+        match *gradient {
+            crate::render::Gradient::Linear { angle, .. } => [1.0, angle, 0.0, 0.0],
+            crate::render::Gradient::Radial { center_x, center_y, .. } => [2.0, center_x, center_y, 0.0],
+            crate::render::Gradient::Conic { center_x, center_y, start_angle, .. } => {
+                [3.0, center_x, center_y, start_angle]
+            }
+        }
+    }
+}
+
+/// Gradient fill for `layout_text_vertices`
+struct TextGradient<'a> {
+    gradient: &'a crate::render::Gradient,
+    /// Box the gradient spans, in logical pixels; None = the text's own box
+    bounds: Option<Rect>,
+    /// Ramp texture row holding the gradient's colors; None = no row left
+    /// this frame, so the gradient is sampled at glyph corners instead
+    ramp_row: Option<u32>,
 }
 
 #[repr(C)]
@@ -4139,3 +6593,1208 @@ struct GeometryVertex {
     texcoord: [f32; 2],
     color: [f32; 4],
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Headless backend (no surface), or None without any adapter, in
+    /// which case the calling GPU test returns early (see `no_gpu`)
+    pub(crate) fn offscreen_backend(width: u32, height: u32) -> Option<WgpuBackend> {
+        let mut backend = WgpuBackend::new();
+        match pollster::block_on(backend.init_headless(width, height)) {
+            Ok(()) => Some(backend),
+            Err(e) => {
+                no_gpu(e);
+                None
+            }
+        }
+    }
+
+    /// A GPU test can't run because there is no GPU or software adapter.
+    ///
+    /// Fails the test when `REQUIRE_GPU` is set (CI runners that should have
+    /// an adapter), otherwise reports the skip so a test that checked
+    /// nothing doesn't pass silently.
+    pub(crate) fn no_gpu(error: impl std::fmt::Display) {
+        if std::env::var_os("REQUIRE_GPU").is_some_and(|value| !value.is_empty()) {
+            panic!("REQUIRE_GPU is set but no GPU adapter is available: {}", error);
+        }
+        eprintln!("skipping GPU test, no adapter: {}", error);
+    }
+
+    /// Read one pixel of the frame texture back from the GPU
+    fn read_frame_pixel(backend: &WgpuBackend, x: u32, y: u32) -> [u8; 4] {
+        let device = backend.device.as_ref().unwrap();
+        let queue = backend.queue.as_ref().unwrap();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Test Readback Buffer"),
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: backend.frame_texture.as_ref().unwrap(),
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+        buffer.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+        let data = buffer.slice(..).get_mapped_range();
+        [data[0], data[1], data[2], data[3]]
+    }
+
+    #[test]
+    fn test_image_opacity_blends_over_background() {
+        let Some(mut backend) = offscreen_backend(16, 16) else { return };
+        let solid = |rgb: [u8; 3]| LoadedImage { width: 1, height: 1, data: vec![rgb[0], rgb[1], rgb[2], 255], premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let red = backend.load_image(&solid([255, 0, 0])).unwrap();
+        let blue = backend.load_image(&solid([0, 0, 255])).unwrap();
+        let image = |texture_id, opacity| RenderCommand::DrawImage {
+            x: 0.0, y: 0.0, width: 16.0, height: 16.0,
+            texture_id,
+            source_rect: None,
+            src: None,
+            tint: None,
+            corner_radii: [0.0; 4],
+            opacity,
+            rotation: 0.0,
+            pivot: None,
+            sampling: Sampling::default(),
+        };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+
+        // The frame is sRGB, so blending happens on linear values
+        let encode = |linear: [f32; 3]| linear.map(|c| {
+            let srgb = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+            (srgb * 255.0).round() as i32
+        });
+        let assert_pixel = |pixel: [u8; 4], expected: [i32; 3]| {
+            for c in 0..3 {
+                assert!((pixel[c] as i32 - expected[c]).abs() <= 2, "got {:?}, expected {:?}", pixel, expected);
+            }
+        };
+
+        // Cross-fade midpoint: red then blue, each at 0.5, over white
+        backend.render_offscreen(&[white.clone(), image(red, 0.5), image(blue, 0.5)]).unwrap();
+        assert_pixel(read_frame_pixel(&backend, 8, 8), encode([0.5, 0.25, 0.75]));
+
+        // Image opacity multiplies with SetOpacity
+        backend.render_offscreen(&[white, RenderCommand::SetOpacity(0.5), image(red, 1.0)]).unwrap();
+        assert_pixel(read_frame_pixel(&backend, 8, 8), encode([1.0, 0.5, 0.5]));
+    }
+
+    #[test]
+    fn test_atlas_region_samples_only_its_cell_and_tints() {
+        let Some(mut backend) = offscreen_backend(16, 16) else { return };
+        // A 4x1 atlas of two 2px cells: white then black
+        let data = [[255u8; 4], [255; 4], [0, 0, 0, 255], [0, 0, 0, 255]].concat();
+        let atlas = backend.load_image(&LoadedImage { width: 4, height: 1, data, premultiplied: false, frames: Vec::new(), loop_count: 0 }).unwrap();
+        let cell = |src_x, tint| RenderCommand::DrawImage {
+            x: 0.0, y: 0.0, width: 16.0, height: 16.0,
+            texture_id: atlas,
+            source_rect: None,
+            src: Some(Rect::new(src_x, 0.0, 2.0, 1.0)),
+            tint,
+            corner_radii: [0.0; 4],
+            opacity: 1.0,
+            rotation: 0.0,
+            pivot: None,
+            sampling: Sampling::default(),
+        };
+        let assert_pixel = |pixel: [u8; 4], expected: [i32; 3]| {
+            for c in 0..3 {
+                assert!((pixel[c] as i32 - expected[c]).abs() <= 2, "got {:?}, expected {:?}", pixel, expected);
+            }
+        };
+
+        backend.render_offscreen(&[cell(0.0, None)]).unwrap();
+        assert_pixel(read_frame_pixel(&backend, 8, 8), [255, 255, 255]);
+        backend.render_offscreen(&[cell(2.0, None)]).unwrap();
+        assert_pixel(read_frame_pixel(&backend, 8, 8), [0, 0, 0]);
+
+        // The tint multiplies the white cell
+        backend.render_offscreen(&[cell(0.0, Some(0x00FF00FF))]).unwrap();
+        assert_pixel(read_frame_pixel(&backend, 8, 8), [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_minified_image_sampling() {
+        let Some(mut backend) = offscreen_backend(16, 16) else { return };
+        // 8x8 black with a white 2x2 center, drawn into a single pixel
+        let data = (0..64).flat_map(|i| {
+            let center = (3..5).contains(&(i % 8)) && (3..5).contains(&(i / 8));
+            if center { [255u8; 4] } else { [0, 0, 0, 255] }
+        }).collect();
+        let image = LoadedImage { width: 8, height: 8, data, premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let mipmapped = backend.load_image(&image).unwrap();
+        let base_only = backend.load_image_with_options(&image, ImageUploadOptions { mipmaps: false, ..Default::default() }).unwrap();
+        let mut draw_sampled = |texture_id, sampling| {
+            backend.render_offscreen(&[RenderCommand::DrawImage {
+                x: 4.0, y: 4.0, width: 1.0, height: 1.0,
+                texture_id,
+                source_rect: None,
+                src: None,
+                tint: None,
+                corner_radii: [0.0; 4],
+                opacity: 1.0,
+                rotation: 0.0,
+                pivot: None,
+                sampling,
+            }]).unwrap();
+            read_frame_pixel(&backend, 4, 4)[0] as i32
+        };
+
+        // Trilinear reads the 1x1 level: 4 of 64 texels white is linear
+        // 0.0625, sRGB 71. Linear and Nearest stay on the base level and
+        // only see the center.
+        assert!((draw_sampled(mipmapped, Sampling::Trilinear) - 71).abs() <= 3);
+        assert!(draw_sampled(mipmapped, Sampling::Linear) >= 250);
+        assert!(draw_sampled(mipmapped, Sampling::Nearest) >= 250);
+        // Without mipmaps, even Trilinear only has the base level
+        assert!(draw_sampled(base_only, Sampling::Trilinear) >= 250);
+    }
+
+    #[test]
+    fn test_half_alpha_image_composites_to_mid_gray() {
+        let Some(mut backend) = offscreen_backend(16, 16) else { return };
+        let image = |texture_id| RenderCommand::DrawImage {
+            x: 0.0, y: 0.0, width: 16.0, height: 16.0,
+            texture_id,
+            source_rect: None,
+            src: None,
+            tint: None,
+            corner_radii: [0.0; 4],
+            opacity: 1.0,
+            rotation: 0.0,
+            pivot: None,
+            sampling: Sampling::default(),
+        };
+        let clear = |v: u8| RenderCommand::Clear(crate::style::Color { r: v, g: v, b: v, a: 255 });
+        let assert_gray = |pixel: [u8; 4], expected: i32| {
+            for c in 0..3 {
+                assert!((pixel[c] as i32 - expected).abs() <= 2, "got {:?}, expected {}", pixel, expected);
+            }
+        };
+
+        // 50% white over black is half of linear white (sRGB 188), both when
+        // uploaded straight and when premultiplied on upload
+        let half_white = LoadedImage { width: 1, height: 1, data: vec![255, 255, 255, 128], premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let straight = backend.load_image(&half_white).unwrap();
+        let premultiply = ImageUploadOptions { premultiply: true, ..Default::default() };
+        let premultiplied = backend.load_image_with_options(&half_white, premultiply).unwrap();
+        for texture_id in [straight, premultiplied] {
+            backend.render_offscreen(&[clear(0), image(texture_id)]).unwrap();
+            assert_gray(read_frame_pixel(&backend, 8, 8), 188);
+        }
+
+        // An antialiased edge: an opaque white texel next to a transparent
+        // black one. Over white, the filtered edge must stay white; filtering
+        // straight alpha mixes in the black and leaves a dark halo.
+        let edge = LoadedImage { width: 2, height: 1, data: vec![255, 255, 255, 255, 0, 0, 0, 0], premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let straight = backend.load_image(&edge).unwrap();
+        let premultiplied = backend.load_image_with_options(&edge, premultiply).unwrap();
+        backend.render_offscreen(&[clear(255), image(premultiplied)]).unwrap();
+        assert_gray(read_frame_pixel(&backend, 8, 0), 255);
+        backend.render_offscreen(&[clear(255), image(straight)]).unwrap();
+        assert!(read_frame_pixel(&backend, 8, 0)[0] < 250);
+    }
+
+    #[test]
+    fn test_double_buffered_updates_draw_latest_content() {
+        let Some(mut backend) = offscreen_backend(16, 16) else { return };
+        let solid = |rgb: [u8; 3]| LoadedImage { width: 1, height: 1, data: vec![rgb[0], rgb[1], rgb[2], 255], premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let image = |texture_id| RenderCommand::DrawImage {
+            x: 0.0, y: 0.0, width: 16.0, height: 16.0,
+            texture_id,
+            source_rect: None,
+            src: None,
+            tint: None,
+            corner_radii: [0.0; 4],
+            opacity: 1.0,
+            rotation: 0.0,
+            pivot: None,
+            sampling: Sampling::default(),
+        };
+
+        let canvas = backend.load_image(&solid([255, 0, 0])).unwrap();
+        backend.render_offscreen(&[image(canvas)]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 8, 8)[..3], [255, 0, 0]);
+
+        // Two updates back to back, with no frame or device poll in between,
+        // keep the ID and land in alternate backing textures
+        assert_eq!(backend.update_texture_double_buffered(canvas, &solid([0, 255, 0])).unwrap(), canvas);
+        assert_eq!(backend.update_texture_double_buffered(canvas, &solid([0, 0, 255])).unwrap(), canvas);
+        assert!(backend.spare_textures.contains_key(&canvas));
+        backend.render_offscreen(&[image(canvas)]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 8, 8)[..3], [0, 0, 255]);
+
+        // A size change replaces the texture and drops its back buffer
+        let wide = LoadedImage { width: 2, height: 1, data: vec![255; 8], premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let resized = backend.update_texture_double_buffered(canvas, &wide).unwrap();
+        assert_ne!(resized, canvas);
+        assert!(!backend.spare_textures.contains_key(&canvas));
+    }
+
+    #[test]
+    fn test_render_to_texture_composites_with_draw_image() {
+        let Some(mut backend) = offscreen_backend(32, 32) else { return };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+        let rect = |x, color| RenderCommand::DrawRect {
+            x, y: 0.0, width: 8.0, height: 16.0,
+            color,
+            corner_radii: [0.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: None,
+        };
+        backend.render_offscreen(&[white.clone()]).unwrap();
+
+        // Red left half, translucent blue at the right edge, transparent between
+        let cached = backend.render_to_texture(&[rect(0.0, 0xFF0000FF), rect(12.0, 0x0000FF80)], 16, 16).unwrap();
+        assert_eq!(backend.get_texture_size(cached), Some((16, 16)));
+        // The window's frame is untouched
+        assert_eq!(read_frame_pixel(&backend, 4, 8)[..3], [255, 255, 255]);
+
+        backend.render_offscreen(&[
+            white,
+            RenderCommand::DrawImage {
+                x: 16.0, y: 16.0, width: 16.0, height: 16.0,
+                texture_id: cached,
+                source_rect: None,
+                src: None,
+                tint: None,
+                corner_radii: [0.0; 4],
+                opacity: 1.0,
+                rotation: 0.0,
+                pivot: None,
+                sampling: Sampling::default(),
+            },
+        ]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 20, 24)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 26, 24)[..3], [255, 255, 255]);
+        // Half-alpha blue over white keeps half of linear red and green (sRGB 188)
+        let tinted = read_frame_pixel(&backend, 30, 24);
+        assert!(tinted[2] == 255 && (184..=192).contains(&tinted[0]), "blend {:?}", tinted);
+        assert_eq!(read_frame_pixel(&backend, 4, 4)[..3], [255, 255, 255]);
+
+        backend.unload_image(cached);
+        assert!(!backend.has_texture(cached));
+        assert!(backend.render_to_texture(&[], 0, 16).is_err());
+    }
+
+    #[test]
+    fn test_inner_shadow_darkens_edges_not_center() {
+        let Some(mut backend) = offscreen_backend(64, 64) else { return };
+        let field = RenderCommand::DrawRect {
+            x: 0.0, y: 0.0, width: 64.0, height: 64.0,
+            color: 0xFFFFFFFF,
+            corner_radii: [8.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: Some(crate::render::InnerShadow {
+                blur: 12.0, spread: 4.0, offset_x: 0.0, offset_y: 0.0, color: 0x000000FF,
+            }),
+            shadow: None,
+        };
+        backend.render_offscreen(&[field]).unwrap();
+
+        let edge = read_frame_pixel(&backend, 32, 1);
+        assert!(edge[0] < 128, "edge pixel {:?}", edge);
+        assert_eq!(read_frame_pixel(&backend, 32, 32)[..3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_box_shadow_draws_behind_rect_with_opacity_and_clip() {
+        let Some(mut backend) = offscreen_backend(64, 64) else { return };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+        let card = RenderCommand::DrawRect {
+            x: 16.0, y: 8.0, width: 32.0, height: 32.0,
+            color: 0xFFFFFFFF,
+            corner_radii: [4.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: Some(crate::render::BoxShadow {
+                offset_x: 0.0, offset_y: 8.0, blur_radius: 4.0, spread: 0.0, color: 0x000000FF,
+            }),
+        };
+
+        // Dark below the card, untouched above it, covered by the fill
+        backend.render_offscreen(&[white.clone(), card.clone()]).unwrap();
+        let below = read_frame_pixel(&backend, 32, 44);
+        assert!(below[0] < 64, "shadow pixel {:?}", below);
+        assert_eq!(read_frame_pixel(&backend, 32, 2)[..3], [255, 255, 255]);
+        assert_eq!(read_frame_pixel(&backend, 32, 24)[..3], [255, 255, 255]);
+
+        // SetOpacity fades the shadow with the rect
+        backend.render_offscreen(&[white.clone(), RenderCommand::SetOpacity(0.5), card.clone()]).unwrap();
+        let faded = read_frame_pixel(&backend, 32, 44);
+        assert!(faded[0] > below[0] + 64 && faded[0] < 255, "faded pixel {:?}", faded);
+
+        // A clip around the card cuts the shadow off
+        backend.render_offscreen(&[
+            white,
+            RenderCommand::PushClip { x: 16.0, y: 8.0, width: 32.0, height: 32.0 },
+            card,
+            RenderCommand::PopClip {},
+        ]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 32, 44)[..3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_scale_transform_covers_scaled_pixels() {
+        let Some(mut backend) = offscreen_backend(32, 32) else { return };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+        let rect = |x: f32, y: f32, size: f32| RenderCommand::DrawRect {
+            x, y, width: size, height: size,
+            color: 0xFF0000FF,
+            corner_radii: [0.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: None,
+        };
+        let is_red = |pixel: [u8; 4]| pixel[..3] == [255, 0, 0];
+
+        // A 4x4 rect at (4, 4) under 2x covers 8..16; the rect after the pop
+        // is drawn at its own size
+        backend.render_offscreen(&[
+            white.clone(),
+            RenderCommand::PushTransform { matrix: [2.0, 0.0, 0.0, 2.0, 0.0, 0.0] },
+            rect(4.0, 4.0, 4.0),
+            RenderCommand::PopTransform {},
+            rect(24.0, 24.0, 4.0),
+        ]).unwrap();
+        for (x, y) in [(8, 8), (12, 12), (15, 15)] {
+            assert!(is_red(read_frame_pixel(&backend, x, y)), "({}, {}) not covered", x, y);
+        }
+        for (x, y) in [(7, 7), (16, 12), (12, 16), (23, 23)] {
+            assert!(!is_red(read_frame_pixel(&backend, x, y)), "({}, {}) covered", x, y);
+        }
+        assert!(is_red(read_frame_pixel(&backend, 27, 27)));
+
+        // Nested transforms compose, and a clip inside is scaled with them
+        backend.render_offscreen(&[
+            white,
+            RenderCommand::PushTransform { matrix: [2.0, 0.0, 0.0, 2.0, 0.0, 0.0] },
+            RenderCommand::PushTransform { matrix: [1.0, 0.0, 0.0, 1.0, 2.0, 2.0] },
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 4.0, height: 4.0 },
+            rect(0.0, 0.0, 8.0),
+            RenderCommand::PopClip {},
+            RenderCommand::PopTransform {},
+            RenderCommand::PopTransform {},
+        ]).unwrap();
+        assert!(is_red(read_frame_pixel(&backend, 4, 4)));
+        assert!(is_red(read_frame_pixel(&backend, 11, 11)));
+        assert!(!is_red(read_frame_pixel(&backend, 3, 3)));
+        assert!(!is_red(read_frame_pixel(&backend, 12, 12)));
+    }
+
+    #[test]
+    fn test_round_capped_line_draws_past_endpoints() {
+        let Some(mut backend) = offscreen_backend(32, 32) else { return };
+        let line = |x2, cap| RenderCommand::DrawLine {
+            x1: 8.0, y1: 16.0, x2, y2: 16.0,
+            width: 6.0,
+            color: 0xFF0000FF,
+            cap,
+        };
+        backend.render_offscreen(&[
+            RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 }),
+            line(24.0, crate::render::LineCap::Round),
+        ]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 16, 16)[..3], [255, 0, 0]);
+        // The round caps reach 3px past each endpoint
+        assert_eq!(read_frame_pixel(&backend, 6, 16)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 25, 16)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 16, 10)[..3], [255, 255, 255]);
+
+        // A zero-length line with round caps is a dot
+        backend.render_offscreen(&[
+            RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 }),
+            line(8.0, crate::render::LineCap::Round),
+        ]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 8, 16)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 16, 16)[..3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_arc_draws_ring_and_wedge() {
+        let Some(mut backend) = offscreen_backend(32, 32) else { return };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+        let arc = |end_angle, thickness| RenderCommand::DrawArc {
+            cx: 16.0, cy: 16.0, radius: 12.0,
+            start_angle: 0.0, end_angle,
+            thickness,
+            color: 0xFF0000FF,
+        };
+
+        // A full turn 4px thick is a ring with a hole in the middle
+        backend.render_offscreen(&[white.clone(), arc(std::f32::consts::TAU, 4.0)]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 26, 16)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 16, 5)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 16, 16)[..3], [255, 255, 255]);
+        assert_eq!(read_frame_pixel(&backend, 30, 16)[..3], [255, 255, 255]);
+        // The outer edge is blended rather than aliased
+        let edge = read_frame_pixel(&backend, 24, 24);
+        assert!(edge[1] > 20 && edge[1] < 235, "edge pixel {:?}", edge);
+
+        // Filled from 3 o'clock clockwise to 6 o'clock: the bottom-right quarter
+        backend.render_offscreen(&[white, arc(std::f32::consts::FRAC_PI_2, 0.0)]).unwrap();
+        assert_eq!(read_frame_pixel(&backend, 20, 20)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 11, 20)[..3], [255, 255, 255]);
+        assert_eq!(read_frame_pixel(&backend, 20, 11)[..3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_text_shading_falls_back_where_unsupported() {
+        use wgpu::{CompositeAlphaMode, TextureFormat};
+        let opaque = CompositeAlphaMode::Opaque;
+
+        // Grayscale, uncorrected text by default on every target
+        assert_eq!(text_shading(false, false, TextureFormat::Bgra8Unorm, opaque, true), ("fs_main", false));
+        // sRGB targets already blend in linear light
+        assert_eq!(text_shading(true, false, TextureFormat::Bgra8UnormSrgb, opaque, true), ("fs_main", false));
+        assert_eq!(text_shading(true, false, TextureFormat::Bgra8Unorm, opaque, false), ("fs_main", true));
+        // ...as do half-float (extended sRGB) targets
+        assert_eq!(text_shading(true, false, TextureFormat::Rgba16Float, opaque, false), ("fs_main", false));
+
+        // Subpixel text is blended in linear light too
+        assert_eq!(text_shading(false, true, TextureFormat::Bgra8Unorm, opaque, true), ("fs_subpixel", true));
+        // ...but needs dual-source blending and an opaque surface
+        assert_eq!(text_shading(false, true, TextureFormat::Bgra8Unorm, opaque, false), ("fs_main", false));
+        assert_eq!(
+            text_shading(true, true, TextureFormat::Bgra8Unorm, CompositeAlphaMode::PreMultiplied, true),
+            ("fs_main", true),
+        );
+    }
+
+    #[test]
+    fn test_surface_format_falls_back_to_srgb() {
+        use wgpu::TextureFormat::{Bgra8Unorm, Bgra8UnormSrgb, Rgba16Float};
+
+        // Wide gamut takes the half-float format when the surface offers it
+        assert_eq!(
+            choose_surface_format(&[Bgra8Unorm, Bgra8UnormSrgb, Rgba16Float], true),
+            (Rgba16Float, SurfaceColorSpace::ExtendedSrgb),
+        );
+        assert_eq!(choose_surface_format(&[Bgra8Unorm, Bgra8UnormSrgb], true), (Bgra8UnormSrgb, SurfaceColorSpace::Srgb));
+        // ...and only when asked for
+        assert_eq!(choose_surface_format(&[Rgba16Float, Bgra8UnormSrgb], false), (Bgra8UnormSrgb, SurfaceColorSpace::Srgb));
+        // Surfaces without an sRGB format (DX12 flip models) use their first
+        assert_eq!(choose_surface_format(&[Bgra8Unorm, Rgba16Float], false), (Bgra8Unorm, SurfaceColorSpace::Srgb));
+    }
+
+    #[test]
+    fn test_nine_patch_stretches_middle_only() {
+        let Some(mut backend) = offscreen_backend(32, 32) else { return };
+        // 3x3: a one-texel red border around a green center
+        let mut data = Vec::new();
+        for i in 0..9 {
+            data.extend_from_slice(if i == 4 { &[0, 255, 0, 255] } else { &[255, 0, 0, 255] });
+        }
+        let bubble = LoadedImage { width: 3, height: 3, data, premultiplied: false, frames: Vec::new(), loop_count: 0 };
+        let texture_id = backend.load_image(&bubble).unwrap();
+
+        backend.render_offscreen(&[RenderCommand::DrawNinePatch {
+            texture_id,
+            dest_rect: crate::geometry::Rect::new(0.0, 0.0, 32.0, 32.0),
+            insets: [1.0; 4],
+        }]).unwrap();
+
+        // The border stays one pixel wide; only the center grows
+        assert_eq!(read_frame_pixel(&backend, 16, 16)[..3], [0, 255, 0]);
+        assert_eq!(read_frame_pixel(&backend, 16, 0)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 0, 16)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 31, 31)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 3, 16)[..3], [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_focus_ring_draws_outside_rect() {
+        let Some(mut backend) = offscreen_backend(64, 64) else { return };
+        let background = RenderCommand::DrawRect {
+            x: 0.0, y: 0.0, width: 64.0, height: 64.0,
+            color: 0xFFFFFFFF,
+            corner_radii: [0.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: None,
+        };
+        // One dash long enough to cover the first half of the ring: the left
+        // corner, the top edge and down the right side
+        let ring = |speed| RenderCommand::FocusRing {
+            rect: crate::geometry::Rect::new(16.0, 16.0, 32.0, 32.0),
+            corner_radii: [4.0; 4],
+            color: 0xFF0000FF,
+            thickness: 4.0,
+            dash: 1000.0,
+            offset: 0.0,
+            speed,
+        };
+        backend.render_offscreen(&[background.clone(), ring(0.0)]).unwrap();
+        assert!(!backend.needs_continuous_redraw());
+
+        // Drawn in the 4px band above the top edge, not inside or beyond it
+        assert_eq!(read_frame_pixel(&backend, 32, 14)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 32, 12)[..3], [255, 0, 0]);
+        assert_eq!(read_frame_pixel(&backend, 32, 17)[..3], [255, 255, 255]);
+        assert_eq!(read_frame_pixel(&backend, 32, 10)[..3], [255, 255, 255]);
+        // The bottom edge falls in the gap
+        assert_eq!(read_frame_pixel(&backend, 32, 50)[..3], [255, 255, 255]);
+
+        // A moving ring keeps the loop redrawing
+        backend.render_offscreen(&[background, ring(30.0)]).unwrap();
+        assert!(backend.needs_continuous_redraw());
+    }
+
+    #[test]
+    fn test_cleared_glyph_atlas_rasterizes_text_again() {
+        let Some(mut backend) = offscreen_backend(64, 32) else { return };
+        let text = RenderCommand::DrawText {
+            x: 0.0, y: 0.0,
+            text: "Hello".to_string(),
+            font: FontDescriptor::default(),
+            color: 0x000000FF,
+            layout: TextLayoutConfig::default(),
+            gradient: None,
+        };
+        let rasterized = |backend: &WgpuBackend| backend.glyph_atlas.metrics().glyphs_rasterized;
+
+        backend.render_offscreen(std::slice::from_ref(&text)).unwrap();
+        let first = rasterized(&backend);
+        assert!(first > 0);
+
+        // Cached: drawing the same text again rasterizes nothing
+        backend.render_offscreen(std::slice::from_ref(&text)).unwrap();
+        assert_eq!(rasterized(&backend), first);
+
+        backend.clear_font_caches();
+        backend.clear_glyph_atlas();
+        assert_eq!(backend.glyph_atlas.glyph_count(), 0);
+        backend.render_offscreen(std::slice::from_ref(&text)).unwrap();
+        assert_eq!(rasterized(&backend), first * 2);
+        assert!(backend.glyph_atlas.glyph_count() > 0);
+    }
+
+    #[test]
+    fn test_prewarm_then_render_creates_no_pipelines() {
+        let Some(mut backend) = offscreen_backend(64, 64) else { return };
+
+        // Every pipeline is compiled by init; prewarm only fills caches
+        let count = backend.pipeline_creation_count();
+        assert!(count > 0);
+        backend.prewarm().unwrap();
+        assert_eq!(backend.pipeline_creation_count(), count);
+
+        let primitives = vec![
+            RenderCommand::DrawRect {
+                x: 0.0, y: 0.0, width: 10.0, height: 10.0,
+                color: 0xFF0000FF,
+                corner_radii: [4.0; 4],
+                rotation: 0.3,
+                pivot: None,
+                border: Some(crate::render::Border::solid(1.0, 0x000000FF)),
+                gradient: None,
+                inner_shadow: None,
+                shadow: None,
+            },
+            RenderCommand::DrawRect {
+                x: 0.0, y: 0.0, width: 10.0, height: 10.0,
+                color: 0xFF0000FF,
+                corner_radii: [0.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: None,
+                gradient: Some(crate::render::Gradient::horizontal(0xFF0000FF, 0x0000FFFF)),
+                inner_shadow: None,
+                shadow: None,
+            },
+            RenderCommand::DrawShadow {
+                x: 0.0, y: 0.0, width: 10.0, height: 10.0,
+                blur: 4.0, color: 0x00000080,
+                offset_x: 0.0, offset_y: 2.0,
+                corner_radii: [2.0; 4],
+            },
+            RenderCommand::DrawText {
+                x: 0.0, y: 0.0,
+                text: "Hello".to_string(),
+                font: FontDescriptor::default(),
+                color: 0x000000FF,
+                layout: TextLayoutConfig::default(),
+                gradient: None,
+            },
+            RenderCommand::PushRoundedClip {
+                x: 0.0, y: 0.0, width: 10.0, height: 10.0,
+                corner_radii: [4.0; 4],
+            },
+        ];
+
+        for cmd in primitives {
+            backend.render_offscreen(&[cmd]).unwrap();
+            assert_eq!(backend.pipeline_creation_count(), count);
+        }
+    }
+
+    #[test]
+    fn test_frame_stats_count_draws_not_state_changes() {
+        let ops = [
+            PreparedOp::SetScissor { x: 0, y: 0, width: 10, height: 10 },
+            PreparedOp::DrawStencil { vertex_buffer_idx: 0, index_buffer_idx: 0, index_count: 30, pop: false },
+            PreparedOp::SetStencilRef { value: 1 },
+            PreparedOp::DrawGeometry { vertex_buffer_idx: 1, index_buffer_idx: 1, index_count: 6 },
+            PreparedOp::DrawText { vertex_buffer_idx: 2, vertex_count: 12 },
+            PreparedOp::DrawImage { vertex_buffer_idx: 3, vertex_count: 6, texture_id: 1, sampling: Sampling::Linear },
+        ];
+        assert_eq!(count_draws(&ops), (4, 10 + 2 + 4 + 2));
+        assert_eq!(count_draws(&[]), (0, 0));
+
+        // Nothing has been presented yet
+        assert_eq!(WgpuBackend::new().last_frame_stats(), RenderStats::default());
+    }
+
+    #[test]
+    fn test_zero_size_resize_skips_rendering_and_restores() {
+        let Some(mut backend) = offscreen_backend(80, 60) else { return };
+        assert!(!backend.is_zero_sized());
+
+        // Minimizing reports a zero-area size; rendering is skipped, not an error
+        backend.resize(80, 0, 2.0).unwrap();
+        assert!(backend.is_zero_sized());
+        assert_eq!((backend.get_width(), backend.get_height()), (80, 60));
+        assert!(backend.render_frame(&[]).is_ok());
+
+        // Restoring resumes rendering at the new valid size
+        backend.resize(128, 96, 2.0).unwrap();
+        assert!(!backend.is_zero_sized());
+        assert_eq!((backend.get_width(), backend.get_height()), (128, 96));
+        let green = RenderCommand::Clear(crate::style::Color { r: 0, g: 255, b: 0, a: 255 });
+        assert!(backend.render_offscreen(&[green]).is_ok());
+        let frame = backend.frame_texture.as_ref().unwrap();
+        assert_eq!((frame.width(), frame.height()), (128, 96));
+        // Outside the pre-minimize size, so only a frame at the new size has it
+        assert_eq!(read_frame_pixel(&backend, 120, 90), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_present_mode_kept_without_surface() {
+        // Without a surface (as after losing it) the mode is stored for the
+        // next reconfiguration, and nothing else is rebuilt
+        let Some(mut backend) = offscreen_backend(8, 8) else { return };
+        backend.set_present_mode(false);
+        assert_eq!(backend.surface_config.as_ref().unwrap().present_mode, wgpu::PresentMode::AutoNoVsync);
+        backend.resize(16, 16, 1.0).unwrap();
+        assert_eq!(backend.surface_config.as_ref().unwrap().present_mode, wgpu::PresentMode::AutoNoVsync);
+        backend.set_present_mode(true);
+        assert_eq!(backend.surface_config.as_ref().unwrap().present_mode, wgpu::PresentMode::AutoVsync);
+        assert!(backend.render_offscreen(&[]).is_ok());
+
+        // Before init there is nothing to configure
+        WgpuBackend::new().set_present_mode(false);
+    }
+
+    #[test]
+    fn test_headless_frames_are_deterministic() {
+        let Some(mut backend) = offscreen_backend(8, 8) else { return };
+        let commands = [
+            RenderCommand::Clear(crate::style::Color { r: 0, g: 0, b: 255, a: 255 }),
+            RenderCommand::DrawRect {
+                x: 0.0, y: 0.0, width: 4.0, height: 2.0, color: 0xFF0000FF,
+                corner_radii: [0.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: None,
+                gradient: None,
+                inner_shadow: None,
+                shadow: None,
+            },
+            RenderCommand::Spinner { center_x: 10.0, center_y: 4.0, radius: 3.0, color: 0x00FF00FF, speed: 1.0, thickness: 1.0 },
+        ];
+
+        // Tightly packed rows at the requested size, rect in the top-left
+        let first = backend.render_to_pixels(&commands, 16, 4).unwrap();
+        assert_eq!(first.len(), 16 * 4 * 4);
+        let pixel = |x: usize, y: usize| &first[(y * 16 + x) * 4..(y * 16 + x) * 4 + 4];
+        assert!(pixel(0, 0)[0] > 250 && pixel(0, 0)[2] < 5, "got {:?}", pixel(0, 0));
+        assert_eq!(pixel(0, 3), &[0, 0, 255, 255]);
+
+        // Animations don't advance between headless frames
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(backend.render_to_pixels(&commands, 16, 4).unwrap(), first);
+        assert!(backend.render_to_pixels(&commands, 0, 4).is_err());
+    }
+
+    #[test]
+    fn test_nested_rounded_clips_intersect_and_pop() {
+        let Some(mut backend) = offscreen_backend(16, 4) else { return };
+        let rect = |x: f32, width: f32, color: u32| RenderCommand::DrawRect {
+            x, y: 0.0, width, height: 4.0, color,
+            corner_radii: [0.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: None,
+        };
+        let clip = |x: f32| RenderCommand::PushRoundedClip { x, y: 0.0, width: 10.0, height: 4.0, corner_radii: [0.0; 4] };
+        let commands = [
+            RenderCommand::Clear(crate::style::Color { r: 0, g: 0, b: 255, a: 255 }),
+            clip(0.0),
+            clip(6.0),
+            // Only x 6..10 is inside both
+            rect(0.0, 16.0, 0xFF0000FF),
+            RenderCommand::PopClip {},
+            // Back to the outer clip alone
+            rect(0.0, 4.0, 0x00FF00FF),
+            RenderCommand::PopClip {},
+            // Unclipped again, including where the masks were
+            rect(8.0, 1.0, 0xFFFFFFFF),
+        ];
+
+        let pixels = backend.render_to_pixels(&commands, 16, 4).unwrap();
+        let pixel = |x: usize| [pixels[(16 + x) * 4], pixels[(16 + x) * 4 + 1], pixels[(16 + x) * 4 + 2]];
+        assert_eq!(pixel(2), [0, 255, 0]);
+        assert_eq!(pixel(5), [0, 0, 255]);
+        assert_eq!(pixel(7), [255, 0, 0]);
+        assert_eq!(pixel(8), [255, 255, 255]);
+        assert_eq!(pixel(12), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_opacity_layer_composites_as_a_unit() {
+        let Some(mut backend) = offscreen_backend(16, 4) else { return };
+        let rect = |x: f32, width: f32| RenderCommand::DrawRect {
+            x, y: 0.0, width, height: 4.0, color: 0xFF0000FF,
+            corner_radii: [0.0; 4],
+            rotation: 0.0,
+            pivot: None,
+            border: None,
+            gradient: None,
+            inner_shadow: None,
+            shadow: None,
+        };
+        let white = RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 });
+        // Red at 50% over white, and at 75% where two 50% draws overlap
+        // (blended in linear light, stored as sRGB)
+        let (half, double_blended) = ([255, 188, 188], [255, 137, 137]);
+        let assert_pixel = |pixels: &[u8], x: usize, expected: [i32; 3]| {
+            let pixel = &pixels[(16 + x) * 4..(16 + x) * 4 + 3];
+            for c in 0..3 {
+                assert!((pixel[c] as i32 - expected[c]).abs() <= 2, "x {}: got {:?}, expected {:?}", x, pixel, expected);
+            }
+        };
+
+        // Rects overlapping at x 6..10
+        let overlapping = [rect(0.0, 10.0), rect(6.0, 10.0)];
+
+        // SetOpacity fades each rect, so the overlap is darker
+        let per_draw = [vec![white.clone(), RenderCommand::SetOpacity(0.5)], overlapping.to_vec()].concat();
+        let pixels = backend.render_to_pixels(&per_draw, 16, 4).unwrap();
+        assert_pixel(&pixels, 3, half);
+        assert_pixel(&pixels, 8, double_blended);
+
+        // A layer fades the group as one
+        let grouped = [
+            vec![white.clone(), RenderCommand::PushOpacityLayer(0.5)],
+            overlapping.to_vec(),
+            vec![RenderCommand::PopOpacityLayer {}],
+        ].concat();
+        let pixels = backend.render_to_pixels(&grouped, 16, 4).unwrap();
+        for x in [3, 8, 13] {
+            assert_pixel(&pixels, x, half);
+        }
+
+        // A clip around the layer clips the composite; one inside it closes
+        // with the layer, and nested layers multiply
+        let clipped = [
+            white,
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 12.0, height: 4.0 },
+            RenderCommand::PushOpacityLayer(1.0),
+            RenderCommand::PushOpacityLayer(0.5),
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 4.0, height: 4.0 },
+            rect(0.0, 16.0),
+            RenderCommand::PopOpacityLayer {},
+            rect(6.0, 16.0),
+            RenderCommand::PopOpacityLayer {},
+            RenderCommand::PopClip {},
+        ];
+        let pixels = backend.render_to_pixels(&clipped, 16, 4).unwrap();
+        assert_pixel(&pixels, 2, half);
+        assert_pixel(&pixels, 5, [255, 255, 255]);
+        assert_pixel(&pixels, 8, [255, 0, 0]);
+        assert_pixel(&pixels, 13, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_gpu_limits_match_adapter() {
+        assert!(WgpuBackend::new().gpu_limits().is_none());
+
+        let Some(backend) = offscreen_backend(64, 64) else { return };
+        let limits = backend.gpu_limits().unwrap();
+        let adapter_limits = backend.adapter.as_ref().unwrap().limits();
+
+        assert!(limits.max_texture_dimension_2d >= 2048);
+        // Real adapters report 2^n (4096, 8192, 16384...)
+        assert_eq!(limits.max_texture_dimension_2d % 1024, 0);
+        assert_eq!(limits.max_texture_dimension_2d, adapter_limits.max_texture_dimension_2d);
+        assert!(limits.max_bind_groups >= 4);
+    }
+
+    #[test]
+    fn test_compressed_texture_upload_checks_device_support() {
+        let Some(mut backend) = offscreen_backend(64, 64) else { return };
+
+        let formats = [
+            CompressedFormat::Bc7RgbaUnormSrgb,
+            CompressedFormat::Etc2Rgba8UnormSrgb,
+            CompressedFormat::Astc4x4RgbaUnormSrgb,
+        ];
+
+        // A format without its device feature is rejected, not decompressed
+        if let Some(&unsupported) = formats.iter().find(|&&f| !backend.supports_compressed_format(f)) {
+            let data = vec![0u8; unsupported.data_size(16, 16, 1)];
+            assert!(backend.load_compressed_texture(unsupported, &data, 16, 16, 1).is_err());
+        }
+
+        if let Some(&supported) = formats.iter().find(|&&f| backend.supports_compressed_format(f)) {
+            // Full mip chain down to 1x1
+            let data = vec![0u8; supported.data_size(16, 16, 5)];
+            let id = backend.load_compressed_texture(supported, &data, 16, 16, 5).unwrap();
+            assert_eq!(backend.get_texture_size(id), Some((16, 16)));
+
+            // Data that doesn't match the size is rejected before upload
+            assert!(backend.load_compressed_texture(supported, &data[1..], 16, 16, 5).is_err());
+        }
+    }
+
+    #[test]
+    fn test_gradient_text_spans_text_box() {
+        let mut backend = WgpuBackend::new();
+        backend.width = 400;
+        backend.height = 100;
+
+        // Red on the left edge of the text box, blue on the right
+        let gradient = crate::render::Gradient::Linear {
+            angle: 90.0,
+            stops: vec![
+                crate::render::GradientStop { position: 0.0, color: 0xFF0000FF },
+                crate::render::GradientStop { position: 1.0, color: 0x0000FFFF },
+            ],
+        };
+        let layout = TextLayoutConfig { max_width: Some(200.0), ..Default::default() };
+        let text_gradient = |bounds, ramp_row| TextGradient { gradient: &gradient, bounds, ramp_row };
+
+        // Skip when no system font can be rasterized
+        let Some(vertices) = backend.layout_text_vertices(
+            0.0, 0.0, "MMMM", &FontDescriptor::default(), 0x000000FF, &layout, Some(&text_gradient(None, None)),
+        ) else { return };
+
+        // Six vertices per glyph: compare the first and last glyph's top-left corner
+        let left = vertices[0].color;
+        let right = vertices[vertices.len() - 6].color;
+        assert!(left[0] > left[2], "left glyph should be red-dominant: {:?}", left);
+        assert!(right[0] < left[0] && right[2] > left[2], "right glyph should be bluer: {:?} vs {:?}", right, left);
+        // Without a ramp row the shader uses those corner colors
+        assert!(vertices.iter().all(|v| v.gradient == [0.0; 4]));
+
+        // With one, each vertex carries the gradient and its place in the box
+        let scale = backend.scale_factor as f32;
+        let vertices = backend.layout_text_vertices(
+            0.0, 0.0, "MMMM", &FontDescriptor::default(), 0x000000FF, &layout, Some(&text_gradient(None, Some(3))),
+        ).unwrap();
+        assert!(vertices.iter().all(|v| v.gradient == [1.0, 90.0, 0.0, 0.0]));
+        assert_eq!(vertices[0].gradient_row, 3.5 / GRADIENT_RAMP_ROWS as f32);
+        assert_eq!(vertices[0].gradient_box[2], 200.0 * scale);
+        assert!(vertices[0].gradient_box[0] < vertices[vertices.len() - 6].gradient_box[0]);
+
+        // Given bounds, the gradient spans them instead of the text box
+        let bounds = Rect::new(-100.0, 0.0, 1000.0, 50.0);
+        let vertices = backend.layout_text_vertices(
+            0.0, 0.0, "MMMM", &FontDescriptor::default(), 0x000000FF, &layout, Some(&text_gradient(Some(bounds), Some(0))),
+        ).unwrap();
+        assert_eq!(vertices[0].gradient_box[2..], [1000.0 * scale, 50.0 * scale]);
+        assert!(vertices[0].gradient_box[0] >= 100.0 * scale);
+
+        // A flat color is unaffected
+        let flat = backend.layout_text_vertices(
+            0.0, 0.0, "MMMM", &FontDescriptor::default(), 0x00FF00FF, &layout, None,
+        ).unwrap();
+        assert!(flat.iter().all(|v| v.color == [0.0, 1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_gradient_text_evaluated_per_fragment() {
+        let Some(mut backend) = offscreen_backend(96, 64) else { return };
+
+        // Red, green and blue across the text box: sampled at the corners of
+        // the one glyph it would be red and blue with purple between
+        let stop = |position, color| crate::render::GradientStop { position, color };
+        let commands = [
+            RenderCommand::Clear(crate::style::Color { r: 0, g: 0, b: 0, a: 255 }),
+            RenderCommand::DrawText {
+                x: 8.0,
+                y: 4.0,
+                text: "H".to_string(),
+                font: FontDescriptor { size: 48.0, ..Default::default() },
+                color: 0xFFFFFFFF,
+                layout: TextLayoutConfig::default(),
+                gradient: Some(crate::render::Gradient::Linear {
+                    angle: 90.0,
+                    stops: vec![stop(0.0, 0xFF0000FF), stop(0.5, 0x00FF00FF), stop(1.0, 0x0000FFFF)],
+                }),
+            },
+        ];
+        let pixels = backend.render_to_pixels(&commands, 96, 64).unwrap();
+        let pixel = |x: usize, y: usize| &pixels[(y * 96 + x) * 4..][..4];
+        let ink = |px: &[u8]| px[0] as u32 + px[1] as u32 + px[2] as u32;
+
+        // Skip when no system font can be rasterized
+        let inked: Vec<usize> = (0..96).filter(|&x| (0..64).any(|y| ink(pixel(x, y)) > 128)).collect();
+        let (Some(&first), Some(&last)) = (inked.first(), inked.last()) else { return };
+
+        // The crossbar at the middle of the glyph is green
+        let middle = (first + last) / 2;
+        let brightest = (0..64).map(|y| pixel(middle, y)).max_by_key(|px| ink(px)).unwrap();
+        assert!(
+            brightest[1] > brightest[0] && brightest[1] > brightest[2],
+            "middle of the text should be green: {:?}",
+            brightest
+        );
+        assert_eq!(backend.gradient_ramp_rows, 1);
+    }
+
+    #[test]
+    fn test_rich_text_gradient_spans_paragraph() {
+        let mut backend = WgpuBackend::new();
+        let gradient = crate::render::Gradient::Linear {
+            angle: 90.0,
+            stops: vec![crate::render::GradientStop { position: 0.0, color: 0xFF0000FF }],
+        };
+        let run = |text: &str| TextRun::Text { text: text.to_string(), color: 0x000000FF, font: FontDescriptor::default() };
+        let commands = [RenderCommand::DrawRichText {
+            x: 10.0,
+            y: 20.0,
+            runs: vec![run("Hello "), run("world")],
+            layout: TextLayoutConfig { max_width: Some(300.0), ..Default::default() },
+            gradient: Some(gradient.clone()),
+        }];
+
+        let (lowered, bounds) = backend.lower_rich_text(&commands).unwrap();
+        let texts: Vec<usize> = (0..lowered.len())
+            .filter(|&i| matches!(lowered[i], RenderCommand::DrawText { .. }))
+            .collect();
+        // Skip when no system font can be rasterized
+        if texts.len() < 2 {
+            return;
+        }
+
+        // Every piece carries the gradient over the same paragraph box
+        for &i in &texts {
+            let RenderCommand::DrawText { gradient: piece_gradient, .. } = &lowered[i] else { unreachable!() };
+            assert_eq!(piece_gradient.as_ref(), Some(&gradient));
+            assert_eq!(bounds[&i].x, 10.0);
+            assert_eq!(bounds[&i].y, 20.0);
+            assert_eq!(bounds[&i].width, 300.0);
+        }
+        assert_eq!(bounds.len(), texts.len());
+    }
+
+    #[test]
+    fn test_max_lines_ellipsizes_last_visible_line() {
+        let mut backend = WgpuBackend::new();
+        let layout = TextLayoutConfig {
+            max_lines: Some(2),
+            overflow: TextOverflow::Ellipsis,
+            ..Default::default()
+        };
+
+        // Skip when no system font can be rasterized
+        let Some(visible) = backend.layout_visible_lines(
+            "one\ntwo\nthree\nfour\nfive", &FontDescriptor::default(), &layout,
+        ) else { return };
+        if visible.lines.iter().all(|l| l.glyphs.is_empty()) {
+            return;
+        }
+
+        assert_eq!(visible.lines.len(), 2);
+        let chars: Vec<char> = visible.lines[1].glyphs.iter().map(|g| g.character).collect();
+        assert_eq!(chars.last(), Some(&'…'), "last line should end in an ellipsis: {:?}", chars);
+        assert_eq!(chars[0], 't');
+
+        // Without max_lines every line is drawn
+        let all = backend.layout_visible_lines(
+            "one\ntwo\nthree\nfour\nfive", &FontDescriptor::default(), &TextLayoutConfig::default(),
+        ).unwrap();
+        assert_eq!(all.lines.len(), 5);
+    }
+
+    #[test]
+    fn test_repeated_text_reuses_cached_layout() {
+        let mut backend = WgpuBackend::new();
+        let font = FontDescriptor::default();
+        let layout = TextLayoutConfig { max_width: Some(80.0), ..Default::default() };
+
+        // Skip when no system font can be rasterized
+        let Some(first) = backend.layout_visible_lines("Save changes", &font, &layout) else { return };
+        if first.lines.iter().all(|l| l.glyphs.is_empty()) {
+            return;
+        }
+        assert_eq!(backend.shaping_cache_metrics().misses, 1);
+
+        // Drawing and measuring the same text again skip the layout
+        let again = backend.layout_visible_lines("Save changes", &font, &layout).unwrap();
+        assert_eq!(again.lines.len(), first.lines.len());
+        assert!(backend.measure_text_layout("Save changes", &font, &layout).is_some());
+        assert_eq!(backend.shaping_cache_metrics().hits, 2);
+        assert_eq!(backend.shaping_cache_metrics().misses, 1);
+
+        // A layout whose glyphs left the atlas is laid out again
+        backend.glyph_atlas.clear();
+        let relaid = backend.layout_visible_lines("Save changes", &font, &layout).unwrap();
+        assert_eq!(relaid.lines.len(), first.lines.len());
+        assert_eq!(backend.shaping_cache_metrics().misses, 2);
+    }
+
+    #[test]
+    fn test_baseline_anchor_puts_baseline_at_y() {
+        let Some(mut backend) = offscreen_backend(64, 48) else { return };
+        let y = 30.0;
+        let mut bottom_row = |anchor| {
+            let commands = [
+                RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 }),
+                RenderCommand::DrawText {
+                    x: 4.0,
+                    y,
+                    text: "HH".to_string(),
+                    font: FontDescriptor::default(),
+                    color: 0x000000FF,
+                    layout: TextLayoutConfig { anchor, ..Default::default() },
+                    gradient: None,
+                },
+            ];
+            let pixels = backend.render_to_pixels(&commands, 64, 48).unwrap();
+            // Lowest row with ink; "H" has no descender, so it ends at the baseline
+            pixels.chunks(64 * 4).rposition(|row| row.chunks(4).any(|px| px[0] < 128))
+        };
+
+        // Skip when no system font can be rasterized
+        let Some(baseline_bottom) = bottom_row(TextAnchor::Baseline) else { return };
+        assert!((y as usize - 2..=y as usize).contains(&baseline_bottom), "ink ends at row {}", baseline_bottom);
+
+        // The default anchor puts y at the top, so the same text sits lower
+        let top_bottom = bottom_row(TextAnchor::TopLeft).unwrap();
+        assert!(top_bottom > baseline_bottom + 5, "{} vs {}", top_bottom, baseline_bottom);
+    }
+
+    #[test]
+    fn test_glyph_positions_increase_within_each_line() {
+        let mut backend = WgpuBackend::new();
+        let text = "The quick brown fox jumps over the lazy dog";
+        let layout = TextLayoutConfig { max_width: Some(120.0), letter_spacing: 0.05, ..Default::default() };
+
+        // Skip when no system font can be rasterized
+        let Some(positions) = backend.text_glyph_positions(text, &FontDescriptor::default(), 10.0, &layout) else { return };
+        if positions.is_empty() {
+            return;
+        }
+
+        let lines = positions.last().unwrap().line + 1;
+        assert!(lines > 1, "text should wrap at 120px: {:?}", positions);
+        for line in 0..lines {
+            let on_line: Vec<_> = positions.iter().filter(|p| p.line == line).collect();
+            assert_eq!(on_line[0].x, 10.0, "left-aligned lines start at the command's x");
+            for pair in on_line.windows(2) {
+                assert!(pair[1].x > pair[0].x, "x must increase within line {}: {:?}", line, pair);
+                assert!(pair[1].byte_index > pair[0].byte_index);
+                assert!((pair[0].x + pair[0].advance - pair[1].x).abs() < 1e-3);
+            }
+        }
+        assert!(positions.iter().all(|p| text.is_char_boundary(p.byte_index)));
+    }
+
+    #[test]
+    fn test_measured_lines_follow_newlines_and_wrapping() {
+        let Some(mut backend) = offscreen_backend(200, 100) else { return };
+        let font = FontDescriptor::default();
+        let unconstrained = TextLayoutConfig::default();
+
+        // Skip when no system font can be rasterized
+        let Some(one) = backend.measure_text_layout("Open recent", &font, &unconstrained) else { return };
+        if one.width == 0.0 {
+            return;
+        }
+        assert_eq!(one.line_count, 1);
+
+        let two = backend.measure_text_layout("File\nOpen recent", &font, &unconstrained).unwrap();
+        assert_eq!(two.line_count, 2);
+        assert_eq!(two.width, one.width);
+        assert!(two.height > one.height);
+
+        // Wrapping to a narrow width adds lines, each no wider than the limit
+        let narrow = TextLayoutConfig { max_width: Some(one.width * 0.6), ..Default::default() };
+        let wrapped = backend.measure_text_layout("Open recent", &font, &narrow).unwrap();
+        assert_eq!(wrapped.line_count, 2);
+        assert!(wrapped.width <= one.width * 0.6);
+        assert_eq!(wrapped.height, two.height);
+
+        let capped = TextLayoutConfig { max_lines: Some(1), ..narrow };
+        assert_eq!(backend.measure_text_layout("Open recent", &font, &capped).unwrap().line_count, 1);
+    }
+
+    #[test]
+    fn test_text_texture_matches_measured_size() {
+        let Some(mut backend) = offscreen_backend(200, 100) else { return };
+        let font = FontDescriptor::default();
+        let layout = TextLayoutConfig::default();
+
+        // Skip when no system font can be rasterized
+        let Some((width, height)) = backend.measure_text_box("File\nOpen recent", &font, &layout) else { return };
+        if width == 0.0 {
+            return;
+        }
+
+        let id = backend.create_text_texture("File\nOpen recent", &font, 0x000000FF, &layout).unwrap();
+        let scale = backend.scale_factor() as f32;
+        assert_eq!(
+            backend.get_texture_size(id),
+            Some(((width * scale).ceil() as u32, (height * scale).ceil() as u32)),
+        );
+
+        // The label has ink, drawn in the text color
+        let image = backend.rasterize_text_image("File\nOpen recent", &font, 0x000000FF, &layout).unwrap();
+        assert!(image.data.chunks(4).any(|px| px[3] > 0));
+        assert!(image.data.chunks(4).filter(|px| px[3] > 0).all(|px| px[..3] == [0, 0, 0]));
+
+        backend.unload_image(id);
+        assert_eq!(backend.get_texture_size(id), None);
+        assert!(backend.create_text_texture("", &font, 0x000000FF, &layout).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Window focus, minimize and occlusion changes
+//!
+//! winit reports these piecemeal and not always as transitions: `Focused`
+//! may repeat, minimizing shows up as a zero-size `Resized` on Windows and
+//! as `Occluded(true)` on macOS, and mobile apps only hear that they were
+//! suspended or resumed. `WindowState` remembers what was last reported so
+//! the app gets each change once, the same way on every platform.
+
+/// A change in window state to report to the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowStateChange {
+    FocusGained,
+    FocusLost,
+    Minimized,
+    Restored,
+    /// The window became hidden (true) or visible again (false) without
+    /// being minimized, e.g. covered by other windows or on another space
+    Occluded(bool),
+}
+
+/// What the app was last told about the window
+#[derive(Debug, Clone)]
+pub struct WindowState {
+    focused: bool,
+    minimized: bool,
+    occluded: bool,
+}
+
+impl Default for WindowState {
+    /// A freshly shown window: focused, visible and not minimized
+    fn default() -> Self {
+        Self { focused: true, minimized: false, occluded: false }
+    }
+}
+
+impl WindowState {
+    /// The window gained or lost keyboard focus
+    pub fn focused(&mut self, focused: bool) -> Option<WindowStateChange> {
+        if focused == self.focused {
+            return None;
+        }
+        self.focused = focused;
+        Some(if focused { WindowStateChange::FocusGained } else { WindowStateChange::FocusLost })
+    }
+
+    /// The window is (or is no longer) minimized
+    pub fn minimized(&mut self, minimized: bool) -> Option<WindowStateChange> {
+        if minimized == self.minimized {
+            return None;
+        }
+        self.minimized = minimized;
+        Some(if minimized { WindowStateChange::Minimized } else { WindowStateChange::Restored })
+    }
+
+    /// The window was resized; `minimized` is what the platform says, if
+    /// it can tell, otherwise a zero size counts as minimized
+    pub fn resized(&mut self, width: u32, height: u32, minimized: Option<bool>) -> Option<WindowStateChange> {
+        self.minimized(minimized.unwrap_or(width == 0 || height == 0))
+    }
+
+    /// The window became hidden or visible. Being minimized also hides the
+    /// window, so that's reported as Minimized/Restored instead when
+    /// `minimized` says so.
+    pub fn occluded(&mut self, occluded: bool, minimized: Option<bool>) -> Option<WindowStateChange> {
+        if let Some(minimized) = minimized {
+            if minimized || self.minimized {
+                self.occluded = occluded;
+                return self.minimized(minimized);
+            }
+        }
+        if occluded == self.occluded {
+            return None;
+        }
+        self.occluded = occluded;
+        Some(WindowStateChange::Occluded(occluded))
+    }
+
+    /// A mobile app went to the background: like a desktop window that
+    /// lost focus and was minimized
+    pub fn suspended(&mut self) -> Vec<WindowStateChange> {
+        self.focused(false).into_iter().chain(self.minimized(true)).collect()
+    }
+
+    /// A mobile app came back to the foreground
+    pub fn resumed(&mut self) -> Vec<WindowStateChange> {
+        self.minimized(false).into_iter().chain(self.focused(true)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_reported_once() {
+        let mut state = WindowState::default();
+        assert_eq!(state.focused(true), None);
+        assert_eq!(state.focused(false), Some(WindowStateChange::FocusLost));
+        assert_eq!(state.focused(false), None);
+        assert_eq!(state.focused(true), Some(WindowStateChange::FocusGained));
+
+        // Windows minimizes to a zero-size resize
+        assert_eq!(state.resized(0, 0, None), Some(WindowStateChange::Minimized));
+        assert_eq!(state.resized(0, 0, None), None);
+        assert_eq!(state.resized(800, 600, Some(false)), Some(WindowStateChange::Restored));
+
+        assert_eq!(state.occluded(true, Some(false)), Some(WindowStateChange::Occluded(true)));
+        assert_eq!(state.occluded(false, None), Some(WindowStateChange::Occluded(false)));
+    }
+
+    #[test]
+    fn test_minimize_hides_window_without_occluded() {
+        // macOS minimizes with Occluded(true) and no resize
+        let mut state = WindowState::default();
+        assert_eq!(state.occluded(true, Some(true)), Some(WindowStateChange::Minimized));
+        assert_eq!(state.occluded(false, Some(false)), Some(WindowStateChange::Restored));
+        assert_eq!(state.occluded(false, Some(false)), None);
+    }
+
+    #[test]
+    fn test_mobile_suspend_and_resume() {
+        let mut state = WindowState::default();
+        assert_eq!(state.suspended(), vec![WindowStateChange::FocusLost, WindowStateChange::Minimized]);
+        assert!(state.suspended().is_empty());
+        assert_eq!(state.resumed(), vec![WindowStateChange::Restored, WindowStateChange::FocusGained]);
+    }
+}
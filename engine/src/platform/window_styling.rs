@@ -4,6 +4,7 @@
 //! - Border radius (rounded corners) for frameless windows
 //! - Native window control buttons (traffic lights on macOS)
 //! - Title bar transparency and appearance
+//! - Window opacity and blur-behind (vibrancy) for any window
 
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
@@ -18,6 +19,11 @@ pub struct WindowStyleOptions {
     pub enable_minimize: bool,
     /// Enable the maximize/zoom button (only used if show_native_controls is true)
     pub enable_maximize: bool,
+    /// Blur what's behind the window's transparent areas (see
+    /// [`set_window_blur_behind`])
+    pub blur_behind: bool,
+    /// Opacity of the whole window, 0.0 (invisible) to 1.0 (opaque)
+    pub opacity: f32,
 }
 
 impl Default for WindowStyleOptions {
@@ -27,6 +33,8 @@ impl Default for WindowStyleOptions {
             show_native_controls: true,
             enable_minimize: true,
             enable_maximize: true,
+            blur_behind: false,
+            opacity: 1.0,
         }
     }
 }
@@ -64,6 +72,102 @@ pub fn apply_window_style<W: HasWindowHandle>(
     }
 }
 
+/// Apply the blur-behind and opacity settings from `options`
+///
+/// Unlike [`apply_window_style`], this applies to decorated windows too.
+/// Default options (no blur, fully opaque) leave the window untouched.
+pub fn apply_window_effects<W: HasWindowHandle>(
+    window: &W,
+    options: WindowStyleOptions,
+) -> Result<(), String> {
+    if options.blur_behind {
+        set_window_blur_behind(window, true)?;
+    }
+    if options.opacity < 1.0 {
+        set_window_opacity(window, options.opacity)?;
+    }
+    Ok(())
+}
+
+/// Turn blur-behind (vibrancy) on or off for a window
+///
+/// The blur only shows through where the window is transparent, so the
+/// window must be created transparent and cleared with a translucent color.
+///
+/// - macOS: an `NSVisualEffectView` behind the content view
+/// - Windows 11: the acrylic system backdrop; Windows 10 falls back to
+///   `DwmEnableBlurBehindWindow`
+/// - X11: the `_KDE_NET_WM_BLUR_BEHIND_REGION` hint, which KWin (and picom
+///   with blur enabled) honors. Other window managers, GNOME included, and
+///   sessions without a compositor ignore it.
+/// - Wayland: not handled here; winit's `Window::set_blur` covers KDE's
+///   blur protocol, and other compositors have none.
+pub fn set_window_blur_behind<W: HasWindowHandle>(
+    window: &W,
+    enabled: bool,
+) -> Result<(), String> {
+    let handle = window
+        .window_handle()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+
+    match handle.as_raw() {
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::AppKit(appkit_handle) => {
+            macos::set_blur_behind(appkit_handle, enabled)
+        }
+        #[cfg(target_os = "windows")]
+        RawWindowHandle::Win32(win32_handle) => {
+            windows::set_blur_behind(win32_handle, enabled)
+        }
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xlib(xlib_handle) => {
+            x11::set_blur_behind(xlib_handle.window as u32, enabled)
+        }
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xcb(xcb_handle) => {
+            x11::set_blur_behind(xcb_handle.window.get(), enabled)
+        }
+        _ => {
+            // No blur on this platform - silently succeed
+            Ok(())
+        }
+    }
+}
+
+/// Set the opacity of a whole window, from 0.0 (invisible) to 1.0 (opaque)
+///
+/// - macOS: `NSWindow.alphaValue`
+/// - Windows: a layered window with a constant alpha
+/// - X11: the `_NET_WM_WINDOW_OPACITY` hint, applied by the compositor;
+///   a no-op without one
+/// - Wayland: a no-op; there is no protocol for it
+pub fn set_window_opacity<W: HasWindowHandle>(window: &W, opacity: f32) -> Result<(), String> {
+    let handle = window
+        .window_handle()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    let opacity = opacity.clamp(0.0, 1.0);
+
+    match handle.as_raw() {
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::AppKit(appkit_handle) => macos::set_opacity(appkit_handle, opacity),
+        #[cfg(target_os = "windows")]
+        RawWindowHandle::Win32(win32_handle) => windows::set_opacity(win32_handle, opacity),
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xlib(xlib_handle) => {
+            x11::set_opacity(xlib_handle.window as u32, opacity)
+        }
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xcb(xcb_handle) => x11::set_opacity(xcb_handle.window.get(), opacity),
+        _ => Ok(()),
+    }
+}
+
+/// `_NET_WM_WINDOW_OPACITY` value for an opacity, scaled so 1.0 is `u32::MAX`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn x11_opacity_cardinal(opacity: f32) -> u32 {
+    (opacity.clamp(0.0, 1.0) as f64 * u32::MAX as f64).round() as u32
+}
+
 // Platform-specific implementations
 #[cfg(target_os = "macos")]
 mod macos {
@@ -213,16 +317,93 @@ mod macos {
 
         Ok(())
     }
+
+    pub fn set_blur_behind(handle: AppKitWindowHandle, enabled: bool) -> Result<(), String> {
+        use cocoa::foundation::NSRect;
+        use objc::{class, msg_send, sel, sel_impl, runtime::Object};
+
+        unsafe {
+            let ns_view = handle.ns_view.as_ptr() as *mut Object;
+            if ns_view.is_null() {
+                return Err("NSView handle is null".to_string());
+            }
+
+            // Remove the effect view from an earlier call (subviews is a copy,
+            // so it's safe to remove while iterating)
+            let subviews: *mut Object = msg_send![ns_view, subviews];
+            let count: usize = msg_send![subviews, count];
+            for i in 0..count {
+                let subview: *mut Object = msg_send![subviews, objectAtIndex: i];
+                let is_effect_view: bool =
+                    msg_send![subview, isKindOfClass: class!(NSVisualEffectView)];
+                if is_effect_view {
+                    let _: () = msg_send![subview, removeFromSuperview];
+                }
+            }
+
+            if !enabled {
+                return Ok(());
+            }
+
+            let bounds: NSRect = msg_send![ns_view, bounds];
+            let effect_view: *mut Object = msg_send![class!(NSVisualEffectView), alloc];
+            let effect_view: *mut Object = msg_send![effect_view, initWithFrame: bounds];
+            if effect_view.is_null() {
+                return Err("Failed to create NSVisualEffectView".to_string());
+            }
+
+            // NSViewWidthSizable | NSViewHeightSizable
+            let _: () = msg_send![effect_view, setAutoresizingMask: 18u64];
+            // NSVisualEffectBlendingModeBehindWindow = 0
+            let _: () = msg_send![effect_view, setBlendingMode: 0i64];
+            // NSVisualEffectMaterialUnderWindowBackground = 21
+            let _: () = msg_send![effect_view, setMaterial: 21i64];
+            // NSVisualEffectStateActive = 1, so it doesn't go flat when unfocused
+            let _: () = msg_send![effect_view, setState: 1i64];
+
+            // NSWindowBelow = -1: behind the Metal layer
+            let nil: *mut Object = std::ptr::null_mut();
+            let _: () = msg_send![ns_view, addSubview: effect_view positioned: -1i64 relativeTo: nil];
+            let _: () = msg_send![effect_view, release];
+
+            Ok(())
+        }
+    }
+
+    pub fn set_opacity(handle: AppKitWindowHandle, opacity: f32) -> Result<(), String> {
+        use objc::{msg_send, sel, sel_impl, runtime::Object};
+
+        unsafe {
+            let ns_view = handle.ns_view.as_ptr() as *mut Object;
+            if ns_view.is_null() {
+                return Err("NSView handle is null".to_string());
+            }
+
+            let ns_window: *mut Object = msg_send![ns_view, window];
+            if ns_window.is_null() {
+                return Err("NSWindow is null".to_string());
+            }
+
+            let _: () = msg_send![ns_window, setAlphaValue: opacity as f64];
+            Ok(())
+        }
+    }
 }
 
 #[cfg(target_os = "windows")]
 mod windows {
     use super::WindowStyleOptions;
     use raw_window_handle::Win32WindowHandle;
-    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Foundation::{COLORREF, HWND};
     use windows::Win32::Graphics::Dwm::{
-        DwmExtendFrameIntoClientArea, DwmSetWindowAttribute, DWMWA_SYSTEMBACKDROP_TYPE,
-        DWMWA_USE_IMMERSIVE_DARK_MODE, DWMWA_WINDOW_CORNER_PREFERENCE,
+        DwmEnableBlurBehindWindow, DwmExtendFrameIntoClientArea, DwmSetWindowAttribute,
+        DWMWA_SYSTEMBACKDROP_TYPE, DWMWA_USE_IMMERSIVE_DARK_MODE, DWMWA_WINDOW_CORNER_PREFERENCE,
+        DWM_BB_ENABLE, DWM_BLURBEHIND,
+    };
+    use windows::Win32::Graphics::Gdi::HRGN;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+        LWA_ALPHA, WS_EX_LAYERED,
     };
     use windows::Win32::UI::Controls::MARGINS;
 
@@ -307,4 +488,115 @@ mod windows {
             Ok(())
         }
     }
+
+    pub fn set_blur_behind(handle: Win32WindowHandle, enabled: bool) -> Result<(), String> {
+        unsafe {
+            let hwnd = HWND(handle.hwnd.get() as *mut std::ffi::c_void);
+
+            // Windows 11: acrylic backdrop, which shows through the client
+            // area once the frame is extended over it
+            let backdrop_type = if enabled { DWMSBT_TRANSIENTWINDOW } else { DWMSBT_AUTO };
+            let result = DwmSetWindowAttribute(
+                hwnd,
+                DWMWA_SYSTEMBACKDROP_TYPE,
+                &backdrop_type as *const i32 as *const std::ffi::c_void,
+                std::mem::size_of::<i32>() as u32,
+            );
+
+            if result.is_ok() {
+                if enabled {
+                    let margins = MARGINS {
+                        cxLeftWidth: -1,
+                        cxRightWidth: -1,
+                        cyTopHeight: -1,
+                        cyBottomHeight: -1,
+                    };
+                    DwmExtendFrameIntoClientArea(hwnd, &margins)
+                        .map_err(|e| format!("Failed to extend frame into client area: {}", e))?;
+                }
+                return Ok(());
+            }
+
+            // Windows 10: the older blur-behind over the whole client area
+            let blur_behind = DWM_BLURBEHIND {
+                dwFlags: DWM_BB_ENABLE,
+                fEnable: enabled.into(),
+                hRgnBlur: HRGN::default(),
+                fTransitionOnMaximized: false.into(),
+            };
+            DwmEnableBlurBehindWindow(hwnd, &blur_behind)
+                .map_err(|e| format!("Failed to enable blur behind window: {}", e))
+        }
+    }
+
+    pub fn set_opacity(handle: Win32WindowHandle, opacity: f32) -> Result<(), String> {
+        unsafe {
+            let hwnd = HWND(handle.hwnd.get() as *mut std::ffi::c_void);
+
+            // Constant alpha needs a layered window
+            let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            if ex_style & WS_EX_LAYERED.0 as isize == 0 {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED.0 as isize);
+            }
+
+            let alpha = (opacity * 255.0).round() as u8;
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA)
+                .map_err(|e| format!("Failed to set window opacity: {}", e))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, PropMode};
+    use x11rb::wrapper::ConnectionExt as _;
+
+    pub fn set_blur_behind(window: u32, enabled: bool) -> Result<(), String> {
+        // An empty region blurs behind the whole window
+        let region: &[u32] = &[];
+        set_cardinal_property(window, "_KDE_NET_WM_BLUR_BEHIND_REGION", enabled.then_some(region))
+    }
+
+    pub fn set_opacity(window: u32, opacity: f32) -> Result<(), String> {
+        // No property means fully opaque
+        let value = [super::x11_opacity_cardinal(opacity)];
+        set_cardinal_property(window, "_NET_WM_WINDOW_OPACITY", (opacity < 1.0).then_some(&value[..]))
+    }
+
+    /// Set (or with `None`, delete) a CARDINAL property on a window
+    fn set_cardinal_property(window: u32, name: &str, value: Option<&[u32]>) -> Result<(), String> {
+        let (conn, _) =
+            x11rb::connect(None).map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let atom = conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("Failed to intern {}: {}", name, e))?
+            .atom;
+
+        match value {
+            Some(data) => {
+                conn.change_property32(PropMode::REPLACE, window, atom, AtomEnum::CARDINAL, data)
+                    .map_err(|e| e.to_string())?;
+            }
+            None => {
+                conn.delete_property(window, atom).map_err(|e| e.to_string())?;
+            }
+        }
+        conn.flush().map_err(|e| format!("Failed to flush X connection: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x11_opacity_cardinal() {
+        assert_eq!(x11_opacity_cardinal(1.0), u32::MAX);
+        assert_eq!(x11_opacity_cardinal(0.0), 0);
+        assert_eq!(x11_opacity_cardinal(0.5), 0x8000_0000);
+        assert_eq!(x11_opacity_cardinal(2.0), u32::MAX);
+    }
 }
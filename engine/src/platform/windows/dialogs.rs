@@ -0,0 +1,181 @@
+//! Windows native file dialogs using IFileOpenDialog / IFileSaveDialog
+//!
+//! The common item dialogs are COM objects that want a single-threaded
+//! apartment, so each call initializes COM for the calling thread (and
+//! uninitializes it afterwards) unless the thread already has an apartment.
+
+use std::path::PathBuf;
+
+use windows::core::{HSTRING, PCWSTR, PWSTR};
+use windows::Win32::Foundation::{HWND, RPC_E_CHANGED_MODE};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_INPROC_SERVER,
+    COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE,
+};
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::Shell::Common::COMDLG_FILTERSPEC;
+use windows::Win32::UI::Shell::{
+    FileOpenDialog, FileSaveDialog, IFileDialog, IFileOpenDialog, IFileSaveDialog, IShellItem,
+    SHCreateItemFromParsingName, FOS_ALLOWMULTISELECT, FOS_FILEMUSTEXIST, FOS_FORCEFILESYSTEM,
+    FOS_OVERWRITEPROMPT, FOS_PATHMUSTEXIST, SIGDN_FILESYSPATH,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+/// COM initialized for the current thread, undone on drop if we did it
+struct ComApartment {
+    initialized: bool,
+}
+
+impl ComApartment {
+    fn enter() -> Option<Self> {
+        let hr = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE) };
+        if hr.is_ok() {
+            // S_OK or S_FALSE: either way this call must be balanced
+            Some(Self { initialized: true })
+        } else if hr == RPC_E_CHANGED_MODE {
+            // The thread is already in the multithreaded apartment; the
+            // dialogs still work there, and it isn't ours to uninitialize
+            Some(Self { initialized: false })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// Show a file open dialog.
+///
+/// # Arguments
+/// * `title` - Dialog title, or None for the system default
+/// * `directory` - Starting directory
+/// * `extensions` - Allowed extensions without the dot (e.g. ["png", "jpg"]); empty for all files
+/// * `multiple` - Allow selecting more than one file
+///
+/// Returns the selected paths, or None if the dialog was cancelled or failed.
+pub fn open_files_dialog(
+    title: Option<&str>,
+    directory: Option<&str>,
+    extensions: &[&str],
+    multiple: bool,
+) -> Option<Vec<PathBuf>> {
+    let _com = ComApartment::enter()?;
+    unsafe {
+        let dialog: IFileOpenDialog = CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER).ok()?;
+        let filters = FilterSpec::new(extensions);
+        configure(&dialog, title, directory, &filters)?;
+
+        let mut options = dialog.GetOptions().ok()? | FOS_FORCEFILESYSTEM | FOS_FILEMUSTEXIST | FOS_PATHMUSTEXIST;
+        if multiple {
+            options |= FOS_ALLOWMULTISELECT;
+        }
+        dialog.SetOptions(options).ok()?;
+
+        // Cancelling reports ERROR_CANCELLED as an error
+        dialog.Show(owner_window()).ok()?;
+
+        let items = dialog.GetResults().ok()?;
+        let count = items.GetCount().ok()?;
+        let paths: Vec<PathBuf> = (0..count)
+            .filter_map(|i| items.GetItemAt(i).ok())
+            .filter_map(|item| item_path(&item))
+            .collect();
+        (!paths.is_empty()).then_some(paths)
+    }
+}
+
+/// Show a file save dialog.
+///
+/// Takes the same arguments as `open_files_dialog`. The first extension is
+/// appended to a typed name that has none, and choosing an existing file
+/// asks before overwriting it.
+pub fn save_file_dialog(title: Option<&str>, directory: Option<&str>, extensions: &[&str]) -> Option<PathBuf> {
+    let _com = ComApartment::enter()?;
+    unsafe {
+        let dialog: IFileSaveDialog = CoCreateInstance(&FileSaveDialog, None, CLSCTX_INPROC_SERVER).ok()?;
+        let filters = FilterSpec::new(extensions);
+        configure(&dialog, title, directory, &filters)?;
+
+        if let Some(ext) = extensions.first() {
+            dialog.SetDefaultExtension(&HSTRING::from(*ext)).ok()?;
+        }
+        let options = dialog.GetOptions().ok()? | FOS_FORCEFILESYSTEM | FOS_OVERWRITEPROMPT | FOS_PATHMUSTEXIST;
+        dialog.SetOptions(options).ok()?;
+
+        dialog.Show(owner_window()).ok()?;
+        item_path(&dialog.GetResult().ok()?)
+    }
+}
+
+/// The "*.png;*.jpg" filter the dialogs show, kept alive while they're open
+struct FilterSpec {
+    name: HSTRING,
+    spec: HSTRING,
+    empty: bool,
+}
+
+impl FilterSpec {
+    fn new(extensions: &[&str]) -> Self {
+        let spec = extensions.iter().map(|ext| format!("*.{ext}")).collect::<Vec<_>>().join(";");
+        Self { name: HSTRING::from(format!("Files ({spec})")), spec: HSTRING::from(spec), empty: extensions.is_empty() }
+    }
+
+    fn specs(&self) -> Vec<COMDLG_FILTERSPEC> {
+        if self.empty {
+            return Vec::new();
+        }
+        vec![COMDLG_FILTERSPEC { pszName: PCWSTR(self.name.as_ptr()), pszSpec: PCWSTR(self.spec.as_ptr()) }]
+    }
+}
+
+/// Title, starting folder and file types shared by both dialogs
+unsafe fn configure(
+    dialog: &IFileDialog,
+    title: Option<&str>,
+    directory: Option<&str>,
+    filters: &FilterSpec,
+) -> Option<()> {
+    if let Some(title) = title {
+        dialog.SetTitle(&HSTRING::from(title)).ok()?;
+    }
+    if let Some(directory) = directory {
+        // A missing directory isn't fatal: the dialog opens in its default
+        let folder: windows::core::Result<IShellItem> = SHCreateItemFromParsingName(&HSTRING::from(directory), None);
+        if let Ok(folder) = folder {
+            dialog.SetFolder(&folder).ok()?;
+        }
+    }
+    let specs = filters.specs();
+    if !specs.is_empty() {
+        dialog.SetFileTypes(&specs).ok()?;
+    }
+    Some(())
+}
+
+/// The foreground window if it's one of ours, so the dialog is modal to it
+fn owner_window() -> HWND {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id as *mut u32));
+        if process_id == GetCurrentProcessId() {
+            hwnd
+        } else {
+            HWND(std::ptr::null_mut())
+        }
+    }
+}
+
+/// File system path of a shell item
+unsafe fn item_path(item: &IShellItem) -> Option<PathBuf> {
+    let name: PWSTR = item.GetDisplayName(SIGDN_FILESYSPATH).ok()?;
+    let path = name.to_string().ok();
+    CoTaskMemFree(Some(name.0 as *const _));
+    path.map(PathBuf::from)
+}
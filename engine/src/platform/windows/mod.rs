@@ -2,13 +2,16 @@
 //!
 //! Provides Windows-specific functionality for desktop integration:
 //! - System tray icons (Shell_NotifyIconW)
+//! - File dialogs (IFileOpenDialog / IFileSaveDialog)
 //! - Notifications (Toast notifications)
 //! - Scroll direction detection
 //! - Frameless window controls
 
+pub mod dialogs;
 pub mod tray;
 pub mod window_controls;
 
+pub use dialogs::{open_files_dialog, save_file_dialog};
 pub use tray::WindowsTrayIcon;
 pub use window_controls::{WindowControls, ButtonKind, ResizeEdge, detect_resize_edge, HEADER_HEIGHT, window_border_command, WINDOW_CORNER_RADIUS};
 
@@ -272,8 +272,11 @@ impl WindowControls {
                     color: bg_color,
                     corner_radii: [0.0, 0.0, 0.0, 0.0],
                     rotation: 0.0,
+                    pivot: None,
                     border: None,
                     gradient: None,
+                    inner_shadow: None,
+                    shadow: None,
                 });
             }
 
@@ -298,8 +301,11 @@ impl WindowControls {
                         color: icon_color,
                         corner_radii: [0.0, 0.0, 0.0, 0.0],
                         rotation: rotation_45,
+                        pivot: None,
                         border: None,
                         gradient: None,
+                        inner_shadow: None,
+                        shadow: None,
                     });
 
                     // Second diagonal (rotated -45 degrees = -π/4 radians)
@@ -311,8 +317,11 @@ impl WindowControls {
                         color: icon_color,
                         corner_radii: [0.0, 0.0, 0.0, 0.0],
                         rotation: -rotation_45,
+                        pivot: None,
                         border: None,
                         gradient: None,
+                        inner_shadow: None,
+                        shadow: None,
                     });
                 }
                 ButtonKind::Minimize => {
@@ -328,8 +337,11 @@ impl WindowControls {
                         color: icon_color,
                         corner_radii: [0.0, 0.0, 0.0, 0.0],
                         rotation: 0.0,
+                        pivot: None,
                         border: None,
                         gradient: None,
+                        inner_shadow: None,
+                        shadow: None,
                     });
                 }
                 ButtonKind::Maximize => {
@@ -351,12 +363,11 @@ impl WindowControls {
                             color: 0x00000000,
                             corner_radii: [1.5, 1.5, 1.5, 1.5],
                             rotation: 0.0,
-                            border: Some(crate::render::Border {
-                                width: 1.0,
-                                color: icon_color,
-                                style: crate::render::BorderStyle::Solid,
-                            }),
+                            pivot: None,
+                            border: Some(crate::render::Border::solid(1.0, icon_color)),
                             gradient: None,
+                            inner_shadow: None,
+                            shadow: None,
                         });
 
                         // Front rectangle
@@ -368,12 +379,11 @@ impl WindowControls {
                             color: 0x00000000,
                             corner_radii: [1.5, 1.5, 1.5, 1.5],
                             rotation: 0.0,
-                            border: Some(crate::render::Border {
-                                width: 1.0,
-                                color: icon_color,
-                                style: crate::render::BorderStyle::Solid,
-                            }),
+                            pivot: None,
+                            border: Some(crate::render::Border::solid(1.0, icon_color)),
                             gradient: None,
+                            inner_shadow: None,
+                            shadow: None,
                         });
                     } else {
                         // Maximize icon - single rectangle
@@ -385,12 +395,11 @@ impl WindowControls {
                             color: 0x00000000,
                             corner_radii: [1.5, 1.5, 1.5, 1.5],
                             rotation: 0.0,
-                            border: Some(crate::render::Border {
-                                width: 1.0,
-                                color: icon_color,
-                                style: crate::render::BorderStyle::Solid,
-                            }),
+                            pivot: None,
+                            border: Some(crate::render::Border::solid(1.0, icon_color)),
                             gradient: None,
+                            inner_shadow: None,
+                            shadow: None,
                         });
                     }
                 }
@@ -412,7 +421,7 @@ impl WindowControls {
 
 /// Generate render command for window border
 pub fn window_border_command(width: f32, height: f32, is_dark: bool) -> crate::render::RenderCommand {
-    use crate::render::{RenderCommand, Border, BorderStyle};
+    use crate::render::{RenderCommand, Border};
 
     let border_color = if is_dark {
         WINDOW_BORDER_COLOR_DARK
@@ -428,12 +437,11 @@ pub fn window_border_command(width: f32, height: f32, is_dark: bool) -> crate::r
         color: 0x00000000,
         corner_radii: [WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS, WINDOW_CORNER_RADIUS],
         rotation: 0.0,
-        border: Some(Border {
-            width: WINDOW_BORDER_WIDTH,
-            color: border_color,
-            style: BorderStyle::Solid,
-        }),
+        pivot: None,
+        border: Some(Border::solid(WINDOW_BORDER_WIDTH, border_color)),
         gradient: None,
+        inner_shadow: None,
+        shadow: None,
     }
 }
 
@@ -452,6 +460,7 @@ pub enum ResizeEdge {
 
 impl ResizeEdge {
     /// Convert to winit ResizeDirection
+    #[cfg(feature = "winit")]
     pub fn to_resize_direction(self) -> winit::window::ResizeDirection {
         use winit::window::ResizeDirection;
         match self {
@@ -1,6 +1,7 @@
 //! Rendering module - supports both immediate and retained mode rendering
 
-use crate::text::{FontDescriptor, TextLayoutConfig};
+use crate::geometry::Rect;
+use crate::text::{FontDescriptor, TextLayoutConfig, TextRun};
 use serde::{Deserialize, Serialize};
 
 /// Rendering mode for the engine
@@ -41,14 +42,22 @@ impl CommandBuffer {
 // ===== Supporting Types (must be defined before RenderCommand) =====
 
 /// Border specification for rectangles
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Border {
     /// Border width in pixels
     pub width: f32,
     /// Border color (0xRRGGBBAA)
     pub color: u32,
-    /// Border style
+    /// Border style (Solid when omitted)
+    #[serde(default)]
     pub style: BorderStyle,
+    /// Length of each dash in pixels (0 = 3x the border width)
+    #[serde(default)]
+    pub dash_length: f32,
+    /// Space between dashes or dots in pixels (0 = the dash length for
+    /// Dashed, the border width for Dotted)
+    #[serde(default)]
+    pub gap_length: f32,
 }
 
 impl Border {
@@ -58,29 +67,195 @@ impl Border {
             width,
             color,
             style: BorderStyle::Solid,
+            dash_length: 0.0,
+            gap_length: 0.0,
         }
     }
 }
 
 /// Border style
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BorderStyle {
+    #[default]
     Solid,
     Dashed,
     Dotted,
 }
 
+/// How the ends of a line are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineCap {
+    /// Ends flat at the endpoints
+    #[default]
+    Butt,
+    /// Ends in a half circle past each endpoint
+    Round,
+    /// Ends flat, half the line width past each endpoint
+    Square,
+}
+
+impl From<u8> for LineCap {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => LineCap::Round,
+            2 => LineCap::Square,
+            _ => LineCap::Butt,
+        }
+    }
+}
+
+/// How the corners between stroked path segments are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineJoin {
+    /// Sharp corners, beveled where the miter would be longer than four
+    /// times the stroke width
+    #[default]
+    Miter,
+    /// Rounded corners
+    Round,
+    /// Corners cut off flat
+    Bevel,
+}
+
+impl From<u8> for LineJoin {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => LineJoin::Round,
+            2 => LineJoin::Bevel,
+            _ => LineJoin::Miter,
+        }
+    }
+}
+
+/// Which parts of a self-intersecting or nested path are filled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillRule {
+    /// Points the outline winds around in total at least once; a star's
+    /// center is filled
+    #[default]
+    NonZero,
+    /// Points inside an odd number of outlines; a star's center is a hole
+    EvenOdd,
+}
+
+/// How an image's texels are filtered when it is drawn larger or smaller
+/// than the texture
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sampling {
+    /// The nearest texel of the full-size texture, for crisp pixel art
+    Nearest,
+    /// Texels blended within the nearest mip level
+    Linear,
+    /// Texels blended within and between the two nearest mip levels;
+    /// smoothest when scaled down, the default for photos
+    #[default]
+    Trilinear,
+}
+
+impl From<u8> for Sampling {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Sampling::Nearest,
+            1 => Sampling::Linear,
+            _ => Sampling::Trilinear,
+        }
+    }
+}
+
+/// One step of a path outline, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PathSegment {
+    /// Start a new subpath at (x, y)
+    MoveTo { x: f32, y: f32 },
+    /// Straight line to (x, y)
+    LineTo { x: f32, y: f32 },
+    /// Quadratic bezier to (x, y) with control point (cx, cy)
+    QuadTo { cx: f32, cy: f32, x: f32, y: f32 },
+    /// Cubic bezier to (x, y) with control points (c1x, c1y) and (c2x, c2y)
+    CubicTo { c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32 },
+    /// Close the subpath with a line back to its start
+    Close {},
+}
+
+impl PathSegment {
+    /// All coordinates of the segment, control points first
+    pub fn coordinates(&self) -> Vec<f32> {
+        match *self {
+            PathSegment::MoveTo { x, y } | PathSegment::LineTo { x, y } => vec![x, y],
+            PathSegment::QuadTo { cx, cy, x, y } => vec![cx, cy, x, y],
+            PathSegment::CubicTo { c1x, c1y, c2x, c2y, x, y } => vec![c1x, c1y, c2x, c2y, x, y],
+            PathSegment::Close {} => vec![],
+        }
+    }
+}
+
+/// Outline drawn along a path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stroke {
+    /// Stroke width in logical pixels, centered on the outline
+    pub width: f32,
+    /// Stroke color (0xRRGGBBAA)
+    pub color: u32,
+    /// Ends of open subpaths
+    #[serde(default)]
+    pub cap: LineCap,
+    /// Corners between segments
+    #[serde(default)]
+    pub join: LineJoin,
+}
+
+/// Shadow cast inward from the edges of a rect, for inset controls
+/// (pressed buttons, input fields)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InnerShadow {
+    /// Blur radius in pixels
+    #[serde(default)]
+    pub blur: f32,
+    /// How far in from the rect's edges the shadow's edge sits; the blur is
+    /// centered on it
+    #[serde(default)]
+    pub spread: f32,
+    /// Offset of the shadow; (0, 4) shades the top edge most
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+    /// Shadow color (0xRRGGBBAA)
+    pub color: u32,
+}
+
+/// Shadow cast outward from a rect, like CSS `box-shadow`, for raised
+/// surfaces (cards, menus, dialogs)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoxShadow {
+    /// Offset of the shadow; (0, 4) drops it below the rect
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+    /// Blur radius in pixels (larger = softer edge)
+    #[serde(default)]
+    pub blur_radius: f32,
+    /// How far the shadow grows past the rect's edges before blurring;
+    /// negative values shrink it
+    #[serde(default)]
+    pub spread: f32,
+    /// Shadow color (0xRRGGBBAA)
+    pub color: u32,
+}
+
 /// Gradient color stop
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GradientStop {
-    /// Position along gradient (0.0 to 1.0)
+    /// Position along gradient (0.0 to 1.0, clamped). Stops needn't be
+    /// sorted; two stops at the same position make a hard edge.
     pub position: f32,
     /// Color at this position (0xRRGGBBAA)
     pub color: u32,
 }
 
 /// Gradient specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Gradient {
     /// Linear gradient with angle in degrees (0 = right, 90 = down)
     Linear {
@@ -95,6 +270,17 @@ pub enum Gradient {
         center_y: f32,
         stops: Vec<GradientStop>,
     },
+    /// Conic gradient sweeping clockwise around a center (pie charts,
+    /// progress rings). Stop positions are fractions of a full turn.
+    Conic {
+        /// Center X (0.0 to 1.0, relative to rect)
+        center_x: f32,
+        /// Center Y (0.0 to 1.0, relative to rect)
+        center_y: f32,
+        /// Where position 0.0 is, in degrees clockwise from 12 o'clock
+        start_angle: f32,
+        stops: Vec<GradientStop>,
+    },
 }
 
 impl Gradient {
@@ -136,8 +322,114 @@ pub enum BlendMode {
 
 // ===== Render Commands =====
 
+/// Fraction of a full turn covered by the spinner's arc
+pub const SPINNER_SWEEP: f32 = 0.75;
+
+/// Rotation angle (radians, wrapped to 0..2π) of a spinner turning at
+/// `speed` revolutions per second after `elapsed` seconds of frame time.
+pub fn spinner_rotation(speed: f32, elapsed: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    (speed * elapsed * tau).rem_euclid(tau)
+}
+
+/// Default focus ring line thickness in logical pixels
+pub const FOCUS_RING_THICKNESS: f32 = 2.0;
+
+/// Default focus ring dash (and gap) length in logical pixels
+pub const FOCUS_RING_DASH: f32 = 4.0;
+
+/// How far along its outline a focus ring's dash pattern has moved after
+/// `elapsed` seconds of frame time, marching at `speed` pixels per second
+/// from its starting `offset`.
+pub fn focus_ring_dash_offset(offset: f32, speed: f32, elapsed: f32) -> f32 {
+    offset + speed * elapsed
+}
+
+/// Whether a caret blinking every `blink_ms` milliseconds is shown `elapsed`
+/// seconds after its blink started. It starts visible; 0 never blinks.
+pub fn caret_visible(blink_ms: u32, elapsed: f32) -> bool {
+    if blink_ms == 0 {
+        return true;
+    }
+    let phase = (elapsed.max(0.0) * 1000.0 / blink_ms as f32) as u64;
+    phase % 2 == 0
+}
+
+/// Milliseconds from `elapsed` until the caret next shows or hides, or None
+/// if it doesn't blink
+pub fn caret_next_toggle_ms(blink_ms: u32, elapsed: f32) -> Option<u32> {
+    if blink_ms == 0 {
+        return None;
+    }
+    let into_phase = (elapsed.max(0.0) * 1000.0).rem_euclid(blink_ms as f32);
+    Some(((blink_ms as f32 - into_phase).ceil() as u32).max(1))
+}
+
+/// Texture coordinates (u0, v0, u1, v1) a DrawImage samples from a
+/// `texture_width` x `texture_height` texture: the pixel `src` region if set,
+/// else the normalized `source_rect`, else the whole texture
+pub fn image_uvs(
+    source_rect: Option<(f32, f32, f32, f32)>,
+    src: Option<Rect>,
+    texture_width: u32,
+    texture_height: u32,
+) -> (f32, f32, f32, f32) {
+    if let Some(src) = src {
+        let (w, h) = (texture_width.max(1) as f32, texture_height.max(1) as f32);
+        return (src.x / w, src.y / h, (src.x + src.width) / w, (src.y + src.height) / h);
+    }
+    let (x, y, w, h) = source_rect.unwrap_or((0.0, 0.0, 1.0, 1.0));
+    (x, y, x + w, y + h)
+}
+
+/// The quads a DrawNinePatch draws, as (destination, source) pairs: the
+/// destination in logical pixels, the source in texture pixels. Corners keep
+/// their size, edges stretch along their length and the middle stretches
+/// both ways. Empty pieces - a zero inset, or a middle squeezed out - are
+/// left out.
+pub fn nine_patch_pieces(dest: Rect, insets: [f32; 4], texture_width: u32, texture_height: u32) -> Vec<(Rect, Rect)> {
+    let [top, right, bottom, left] = insets.map(|inset| inset.max(0.0));
+    let columns = nine_patch_spans(dest.x, dest.width, left, right, texture_width as f32);
+    let rows = nine_patch_spans(dest.y, dest.height, top, bottom, texture_height as f32);
+    rows.iter()
+        .flat_map(|row| columns.iter().map(move |col| {
+            (Rect::new(col.0, row.0, col.1, row.1), Rect::new(col.2, row.2, col.3, row.3))
+        }))
+        .filter(|(dest, src)| !dest.is_empty() && !src.is_empty())
+        .collect()
+}
+
+/// One axis of a nine-patch as three (dest start, dest length, source
+/// start, source length) spans: fixed start border, stretched middle, fixed
+/// end border.
+///
+/// Borders that don't fit - in the texture or in the destination - shrink
+/// in proportion to fill it exactly, leaving the middle zero length rather
+/// than letting them overlap.
+fn nine_patch_spans(start: f32, length: f32, inset_start: f32, inset_end: f32, texture_length: f32) -> [(f32, f32, f32, f32); 3] {
+    let fit = |a: f32, b: f32, room: f32| {
+        if a + b > room {
+            let scale = room.max(0.0) / (a + b);
+            (a * scale, b * scale)
+        } else {
+            (a, b)
+        }
+    };
+    let (src_start, src_end) = fit(inset_start, inset_end, texture_length);
+    let (dest_start, dest_end) = fit(src_start, src_end, length);
+    [
+        (start, dest_start, 0.0, src_start),
+        (start + dest_start, length - dest_start - dest_end, src_start, texture_length - src_start - src_end),
+        (start + length - dest_end, dest_end, texture_length - src_end, src_end),
+    ]
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
 /// Individual render command
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RenderCommand {
     // ===== High-Level Commands (Web UI) =====
 
@@ -152,13 +444,25 @@ pub enum RenderCommand {
         /// Corner radii [top-left, top-right, bottom-right, bottom-left]
         /// Use [r, r, r, r] for uniform radius
         corner_radii: [f32; 4],
-        /// Rotation angle in radians (around center), defaults to 0
+        /// Rotation angle in radians (around `pivot`), defaults to 0
         #[serde(default)]
         rotation: f32,
+        /// Point to rotate about, relative to the rect's top-left corner
+        /// (e.g. (0, 0) for a hinge on that corner); defaults to the center
+        #[serde(default)]
+        pivot: Option<(f32, f32)>,
         /// Optional border
         border: Option<Border>,
         /// Optional gradient (overrides solid color if present)
         gradient: Option<Gradient>,
+        /// Optional shadow inside the rect, drawn over the fill and under
+        /// the border
+        #[serde(default)]
+        inner_shadow: Option<InnerShadow>,
+        /// Optional shadow outside the rect, drawn behind it with the same
+        /// corners and rotation
+        #[serde(default)]
+        shadow: Option<BoxShadow>,
     },
 
     /// Draw text with full font and layout control
@@ -169,6 +473,29 @@ pub enum RenderCommand {
         font: FontDescriptor,
         color: u32,
         layout: TextLayoutConfig,
+        /// Optional gradient fill (overrides `color`). The gradient spans the
+        /// laid-out text box - the layout width (or widest line) by all lines -
+        /// rather than restarting on each glyph. Color emoji are not tinted.
+        #[serde(default)]
+        gradient: Option<Gradient>,
+    },
+
+    /// Draw a paragraph of differently colored and styled text runs with
+    /// inline images, wrapped together and sharing each line's baseline.
+    /// `x, y` is the paragraph's top-left; `layout` supplies the wrap width,
+    /// line height, alignment and line limit. Plain text should use DrawText,
+    /// which is cheaper.
+    DrawRichText {
+        x: f32,
+        y: f32,
+        runs: Vec<TextRun>,
+        #[serde(default)]
+        layout: TextLayoutConfig,
+        /// Optional gradient fill for the text runs (overrides their
+        /// colors). It spans the paragraph box - the layout width (or
+        /// widest line) by all lines - across runs. Images are not tinted.
+        #[serde(default)]
+        gradient: Option<Gradient>,
     },
 
     /// Draw an image from a loaded texture asset
@@ -181,9 +508,47 @@ pub enum RenderCommand {
         texture_id: u32,
         /// Optional source rect for sprite sheets (x, y, w, h in texture coords 0-1)
         source_rect: Option<(f32, f32, f32, f32)>,
+        /// Region of the texture to draw, in texture pixels (e.g. one entry
+        /// of a sprite atlas). Takes precedence over `source_rect`.
+        #[serde(default)]
+        src: Option<Rect>,
+        /// Color (0xRRGGBBAA) multiplied with each sampled texel, e.g. to
+        /// recolor a white monochrome icon
+        #[serde(default)]
+        tint: Option<u32>,
         /// Corner radii [top-left, top-right, bottom-right, bottom-left]
         #[serde(default)]
         corner_radii: [f32; 4],
+        /// Opacity 0.0-1.0, multiplied with the current `SetOpacity`. For a
+        /// cross-fade draw the outgoing image at `1 - t` and the incoming at `t`.
+        #[serde(default = "default_opacity")]
+        opacity: f32,
+        /// Rotation angle in radians (around `pivot`), defaults to 0
+        #[serde(default)]
+        rotation: f32,
+        /// Point to rotate about, relative to the image's top-left corner;
+        /// defaults to the center
+        #[serde(default)]
+        pivot: Option<(f32, f32)>,
+        /// Texel filtering; mip levels only exist for images uploaded with
+        /// mipmaps
+        #[serde(default)]
+        sampling: Sampling,
+    },
+
+    /// Draw a texture stretched over `dest_rect` with its borders kept at
+    /// their own size - chat bubbles, buttons and panels from one small
+    /// image. Corners are drawn unscaled, edges stretch along their length
+    /// and the middle fills the rest.
+    ///
+    /// `insets` are the fixed borders [top, right, bottom, left] in texture
+    /// pixels, drawn at the same size in logical pixels. A destination
+    /// smaller than its borders shrinks them to fit and drops the middle,
+    /// so corners never overlap.
+    DrawNinePatch {
+        texture_id: u32,
+        dest_rect: Rect,
+        insets: [f32; 4],
     },
 
     /// Draw a sprite from a sprite sheet
@@ -198,6 +563,62 @@ pub enum RenderCommand {
         sprite_index: u32,
     },
 
+    /// Draw a straight line between two points
+    DrawLine {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        /// Line width in pixels
+        width: f32,
+        /// Line color (0xRRGGBBAA)
+        color: u32,
+        /// End cap style, defaults to Butt. A zero-length line with round
+        /// or square caps still draws a dot.
+        #[serde(default)]
+        cap: LineCap,
+    },
+
+    /// Draw an arc of a circle with antialiased edges: a ring segment for
+    /// spinners and gauges, or a filled wedge for pie slices. A full turn
+    /// draws a whole ring or circle.
+    ///
+    /// Angles are in radians clockwise from 3 o'clock; the arc covers the
+    /// sweep from `start_angle` to `end_angle` in either direction.
+    DrawArc {
+        /// Center X position
+        cx: f32,
+        /// Center Y position
+        cy: f32,
+        /// Outer radius
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        /// Stroke width inside the radius; 0 fills the wedge
+        #[serde(default)]
+        thickness: f32,
+        /// Arc color (0xRRGGBBAA)
+        color: u32,
+    },
+
+    /// Fill and/or stroke an outline of lines and bezier curves - speech
+    /// bubble tails, signature strokes, vector icons.
+    ///
+    /// Curves are flattened and the shape tessellated into triangles each
+    /// frame, so very long paths are better split or drawn once into an
+    /// image. The fill is drawn first, then the stroke over it. Subpaths
+    /// left open are filled as if closed but stroked open.
+    DrawPath {
+        segments: Vec<PathSegment>,
+        /// Fill color (0xRRGGBBAA); None draws only the stroke
+        #[serde(default)]
+        fill: Option<u32>,
+        #[serde(default)]
+        fill_rule: FillRule,
+        #[serde(default)]
+        stroke: Option<Stroke>,
+    },
+
     /// Draw a shadow (typically rendered before the element)
     /// Shadows should be drawn BEFORE the element they're shadowing
     DrawShadow {
@@ -239,6 +660,86 @@ pub enum RenderCommand {
         colors: Option<Vec<u32>>,
     },
 
+    // ===== Animated Commands =====
+
+    /// Draw an indeterminate progress spinner animated by the engine.
+    ///
+    /// The rotation is derived from the backend's frame clock, so Go does not
+    /// need to recompute it every frame. While a spinner is present in the
+    /// submitted commands the backend reports that it needs continuous
+    /// redraws, and the app loop keeps requesting frames until the spinner is
+    /// removed.
+    Spinner {
+        /// Center X position
+        center_x: f32,
+        /// Center Y position
+        center_y: f32,
+        /// Outer radius of the spinner ring
+        radius: f32,
+        /// Arc color (0xRRGGBBAA)
+        color: u32,
+        /// Rotation speed in revolutions per second
+        speed: f32,
+        /// Ring thickness, defaults to a fifth of the radius when 0
+        #[serde(default)]
+        thickness: f32,
+    },
+
+    /// Draw a text caret that blinks on the engine's frame clock.
+    ///
+    /// The caret is solid when it appears or moves and then alternates
+    /// between shown and hidden every `blink_ms`. Unlike a spinner it doesn't
+    /// need continuous redraws: the backend reports when the next toggle is
+    /// due (`WgpuBackend::redraw_after_ms`) and the app loop wakes up for it.
+    Caret {
+        /// Left edge X position
+        x: f32,
+        /// Top Y position
+        y: f32,
+        /// Caret width, defaults to 1 logical pixel when 0
+        #[serde(default)]
+        width: f32,
+        /// Caret height (usually the line height)
+        height: f32,
+        /// Caret color (0xRRGGBBAA)
+        color: u32,
+        /// Time shown (and then hidden) per blink in milliseconds; 0 keeps the
+        /// caret solid
+        #[serde(default)]
+        blink_ms: u32,
+    },
+
+    /// Draw a dashed focus ring around a rect, with "marching ants" animated
+    /// by the engine.
+    ///
+    /// The ring sits just outside the rect, following its corner radii.
+    /// Dashes and equal gaps run clockwise from the top-left corner, sized
+    /// to tile the outline evenly. With a non-zero `speed` the dashes march
+    /// along the outline on the frame clock and, like a spinner, keep the
+    /// app loop redrawing while the ring is submitted.
+    FocusRing {
+        /// The focused rect the ring surrounds
+        rect: Rect,
+        /// Corner radii of the rect [top-left, top-right, bottom-right, bottom-left]
+        #[serde(default)]
+        corner_radii: [f32; 4],
+        /// Ring color (0xRRGGBBAA)
+        color: u32,
+        /// Line thickness, defaults to 2 logical pixels when 0
+        #[serde(default)]
+        thickness: f32,
+        /// Length of each dash and gap, defaults to 4 logical pixels when 0
+        #[serde(default)]
+        dash: f32,
+        /// Distance along the outline the dash pattern is shifted by
+        #[serde(default)]
+        offset: f32,
+        /// Speed the dashes march clockwise in logical pixels per second;
+        /// 0 draws a static ring
+        #[serde(default)]
+        speed: f32,
+    },
+
     // ===== State Commands =====
 
     /// Begin a rectangular clip region (scissor-based, fast)
@@ -289,9 +790,31 @@ pub enum RenderCommand {
     /// Restores the previous clip and offset state
     EndScrollView {},
 
+    /// Transform subsequent drawing until the matching PopTransform.
+    /// `matrix` is `[a, b, c, d, e, f]` in CSS `matrix()` order, in logical
+    /// pixels. Transforms nest, each composing with the one around it, and
+    /// apply to text and to clips pushed inside them (a rectangular clip
+    /// becomes the bounding box of its transformed rect).
+    PushTransform {
+        matrix: [f32; 6],
+    },
+
+    /// End the current transform, restoring the enclosing one
+    PopTransform {},
+
     /// Set opacity for subsequent draws
     SetOpacity(f32),
 
+    /// Draw everything until the matching PopOpacityLayer as one group
+    /// faded to `alpha` (0.0-1.0). Unlike SetOpacity, which fades each draw
+    /// on its own, overlapping draws in the group don't show through each
+    /// other. Layers nest and multiply. Clips pushed outside the layer clip
+    /// the composited group; clips inside it close with it.
+    PushOpacityLayer(f32),
+
+    /// Composite the current opacity layer onto what it was pushed over
+    PopOpacityLayer {},
+
     /// Set blend mode for subsequent draws
     SetBlendMode(BlendMode),
 
@@ -299,9 +822,308 @@ pub enum RenderCommand {
     Clear(crate::style::Color),
 }
 
+impl RenderCommand {
+    /// Whether this command animates on the engine's frame clock and
+    /// therefore requires continuous redraws while it is being submitted.
+    pub fn is_animated(&self) -> bool {
+        match self {
+            RenderCommand::Spinner { .. } => true,
+            RenderCommand::FocusRing { speed, .. } => *speed != 0.0,
+            _ => false,
+        }
+    }
+
+    /// Name of the first NaN or infinite coordinate, size or angle, if any
+    fn non_finite_field(&self) -> Option<&'static str> {
+        let fields: Vec<(&'static str, f32)> = match self {
+            RenderCommand::DrawRect { x, y, width, height, rotation, pivot, corner_radii, border, inner_shadow, shadow, .. } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height), ("rotation", *rotation),
+                ("pivot", pivot.map_or(0.0, |(x, y)| first_non_finite([x, y]))),
+                ("corner_radii", first_non_finite(*corner_radii)),
+                ("border", border.as_ref().map_or(0.0, |b| first_non_finite([b.width, b.dash_length, b.gap_length]))),
+                ("inner_shadow", inner_shadow.map_or(0.0, |s| first_non_finite([s.blur, s.spread, s.offset_x, s.offset_y]))),
+                ("shadow", shadow.map_or(0.0, |s| first_non_finite([s.offset_x, s.offset_y, s.blur_radius, s.spread]))),
+            ],
+            RenderCommand::DrawText { x, y, font, layout, .. } => vec![
+                ("x", *x), ("y", *y), ("font.size", font.size),
+                ("layout.max_width", layout.max_width.unwrap_or(0.0)),
+            ],
+            RenderCommand::DrawRichText { x, y, runs, layout, .. } => vec![
+                ("x", *x), ("y", *y),
+                ("layout.max_width", layout.max_width.unwrap_or(0.0)),
+                ("runs", first_non_finite(runs.iter().flat_map(|run| match run {
+                    TextRun::Text { font, .. } => vec![font.size],
+                    TextRun::Image { width, height, baseline_offset, .. } => vec![*width, *height, *baseline_offset],
+                }))),
+            ],
+            RenderCommand::DrawImage { x, y, width, height, corner_radii, opacity, source_rect, src, rotation, pivot, .. } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height), ("rotation", *rotation),
+                ("pivot", pivot.map_or(0.0, |(x, y)| first_non_finite([x, y]))),
+                ("corner_radii", first_non_finite(*corner_radii)), ("opacity", *opacity),
+                ("source_rect", source_rect.map_or(0.0, |(x, y, w, h)| first_non_finite([x, y, w, h]))),
+                ("src", src.map_or(0.0, |r| first_non_finite([r.x, r.y, r.width, r.height]))),
+            ],
+            RenderCommand::DrawNinePatch { dest_rect, insets, .. } => vec![
+                ("dest_rect", first_non_finite([dest_rect.x, dest_rect.y, dest_rect.width, dest_rect.height])),
+                ("insets", first_non_finite(*insets)),
+            ],
+            RenderCommand::DrawSprite { x, y, width, height, .. } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height),
+            ],
+            RenderCommand::DrawLine { x1, y1, x2, y2, width, .. } => vec![
+                ("x1", *x1), ("y1", *y1), ("x2", *x2), ("y2", *y2), ("width", *width),
+            ],
+            RenderCommand::DrawArc { cx, cy, radius, start_angle, end_angle, thickness, .. } => vec![
+                ("cx", *cx), ("cy", *cy), ("radius", *radius),
+                ("start_angle", *start_angle), ("end_angle", *end_angle), ("thickness", *thickness),
+            ],
+            RenderCommand::DrawPath { segments, stroke, .. } => vec![
+                ("segments", first_non_finite(segments.iter().flat_map(|s| s.coordinates()))),
+                ("stroke", stroke.map_or(0.0, |s| s.width)),
+            ],
+            RenderCommand::DrawShadow { x, y, width, height, blur, offset_x, offset_y, corner_radii, .. } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height), ("blur", *blur),
+                ("offset_x", *offset_x), ("offset_y", *offset_y), ("corner_radii", first_non_finite(*corner_radii)),
+            ],
+            RenderCommand::DrawTriangles { vertices, .. } => vec![
+                ("vertices", first_non_finite(vertices.iter().flat_map(|v| v.position))),
+            ],
+            RenderCommand::DrawInstanced { transforms, .. } => vec![
+                ("transforms", first_non_finite(transforms.iter().flatten().copied())),
+            ],
+            RenderCommand::Spinner { center_x, center_y, radius, speed, thickness, .. } => vec![
+                ("center_x", *center_x), ("center_y", *center_y), ("radius", *radius),
+                ("speed", *speed), ("thickness", *thickness),
+            ],
+            RenderCommand::Caret { x, y, width, height, .. } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height),
+            ],
+            RenderCommand::FocusRing { rect, corner_radii, thickness, dash, offset, speed, .. } => vec![
+                ("rect", first_non_finite([rect.x, rect.y, rect.width, rect.height])),
+                ("corner_radii", first_non_finite(*corner_radii)),
+                ("thickness", *thickness), ("dash", *dash), ("offset", *offset), ("speed", *speed),
+            ],
+            RenderCommand::PushClip { x, y, width, height } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height),
+            ],
+            RenderCommand::PushRoundedClip { x, y, width, height, corner_radii } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height),
+                ("corner_radii", first_non_finite(*corner_radii)),
+            ],
+            RenderCommand::BeginScrollView { x, y, width, height, scroll_x, scroll_y, content_width, content_height } => vec![
+                ("x", *x), ("y", *y), ("width", *width), ("height", *height),
+                ("scroll_x", *scroll_x), ("scroll_y", *scroll_y),
+                ("content_width", content_width.unwrap_or(0.0)),
+                ("content_height", content_height.unwrap_or(0.0)),
+            ],
+            RenderCommand::PushTransform { matrix } => vec![("matrix", first_non_finite(*matrix))],
+            RenderCommand::SetOpacity(opacity) => vec![("opacity", *opacity)],
+            RenderCommand::PushOpacityLayer(alpha) => vec![("alpha", *alpha)],
+            RenderCommand::PopClip {}
+            | RenderCommand::EndScrollView {}
+            | RenderCommand::PopTransform {}
+            | RenderCommand::PopOpacityLayer {}
+            | RenderCommand::SetBlendMode(_)
+            | RenderCommand::Clear(_) => vec![],
+        };
+        fields.into_iter().find(|(_, value)| !value.is_finite()).map(|(name, _)| name)
+    }
+}
+
+// ===== Validation =====
+
+/// Category of a problem found by `validate_commands`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A PopClip/EndScrollView/PopTransform/PopOpacityLayer with nothing to
+    /// close, one that closes another kind of region, or a region still open
+    /// at the end of the frame
+    UnbalancedClip,
+    /// A texture id that isn't loaded; the renderer skips the draw
+    UnknownTexture,
+    /// A NaN or infinite coordinate or size
+    NonFinite,
+    /// DrawText with an empty string, or DrawRichText with no runs
+    EmptyText,
+    /// An opacity or gradient stop outside 0.0-1.0, or (from JSON) a color
+    /// that doesn't fit in 0xRRGGBBAA
+    OutOfRange,
+    /// A JSON command that doesn't parse
+    InvalidCommand,
+}
+
+/// A problem with one command in a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandIssue {
+    /// Index of the offending command in the frame
+    pub index: usize,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// Open clip region, for matching pushes with pops
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClipRegion {
+    Clip,
+    ScrollView,
+    Transform,
+    OpacityLayer,
+}
+
+/// Check a frame for mistakes without rendering it.
+///
+/// `texture_exists` reports whether a texture id is loaded. The frame is
+/// still renderable with issues present - the renderer skips unknown
+/// textures and tolerates stray pops - but the output is rarely what was
+/// meant.
+pub fn validate_commands(commands: &[RenderCommand], texture_exists: impl Fn(u32) -> bool) -> Vec<CommandIssue> {
+    let mut issues = Vec::new();
+    let mut open: Vec<(usize, ClipRegion)> = Vec::new();
+    let mut issue = |index: usize, kind: IssueKind, message: String| {
+        issues.push(CommandIssue { index, kind, message });
+    };
+
+    for (index, command) in commands.iter().enumerate() {
+        if let Some(field) = command.non_finite_field() {
+            issue(index, IssueKind::NonFinite, format!("{} is not finite", field));
+        }
+
+        match command {
+            RenderCommand::DrawRect { gradient: Some(gradient), .. } => {
+                check_gradient(gradient, index, &mut issue);
+            }
+            RenderCommand::DrawText { text, gradient, .. } => {
+                if text.is_empty() {
+                    issue(index, IssueKind::EmptyText, "DrawText has no text".into());
+                }
+                if let Some(gradient) = gradient {
+                    check_gradient(gradient, index, &mut issue);
+                }
+            }
+            RenderCommand::DrawRichText { runs, .. } => {
+                if runs.is_empty() {
+                    issue(index, IssueKind::EmptyText, "DrawRichText has no runs".into());
+                }
+                for run in runs {
+                    if let TextRun::Image { image_texture_id, .. } = run {
+                        if !texture_exists(*image_texture_id) {
+                            issue(index, IssueKind::UnknownTexture, format!("texture {} is not loaded", image_texture_id));
+                        }
+                    }
+                }
+            }
+            RenderCommand::DrawImage { texture_id, opacity, .. } => {
+                if !texture_exists(*texture_id) {
+                    issue(index, IssueKind::UnknownTexture, format!("texture {} is not loaded", texture_id));
+                }
+                check_unit(*opacity, "opacity", index, &mut issue);
+            }
+            RenderCommand::DrawNinePatch { texture_id, .. } if !texture_exists(*texture_id) => {
+                issue(index, IssueKind::UnknownTexture, format!("texture {} is not loaded", texture_id));
+            }
+            RenderCommand::DrawTriangles { texture_id: Some(texture_id), .. } if !texture_exists(*texture_id) => {
+                issue(index, IssueKind::UnknownTexture, format!("texture {} is not loaded", texture_id));
+            }
+            RenderCommand::SetOpacity(opacity) => {
+                check_unit(*opacity, "opacity", index, &mut issue);
+            }
+            RenderCommand::PushClip { .. } | RenderCommand::PushRoundedClip { .. } => {
+                open.push((index, ClipRegion::Clip));
+            }
+            RenderCommand::BeginScrollView { .. } => {
+                open.push((index, ClipRegion::ScrollView));
+            }
+            RenderCommand::PushTransform { .. } => {
+                open.push((index, ClipRegion::Transform));
+            }
+            RenderCommand::PushOpacityLayer(alpha) => {
+                check_unit(*alpha, "alpha", index, &mut issue);
+                open.push((index, ClipRegion::OpacityLayer));
+            }
+            RenderCommand::PopClip {}
+            | RenderCommand::EndScrollView {}
+            | RenderCommand::PopTransform {}
+            | RenderCommand::PopOpacityLayer {} => {
+                let (closes, name) = match command {
+                    RenderCommand::PopClip {} => (ClipRegion::Clip, "PopClip"),
+                    RenderCommand::PopTransform {} => (ClipRegion::Transform, "PopTransform"),
+                    RenderCommand::PopOpacityLayer {} => (ClipRegion::OpacityLayer, "PopOpacityLayer"),
+                    _ => (ClipRegion::ScrollView, "EndScrollView"),
+                };
+                match open.pop() {
+                    None => issue(index, IssueKind::UnbalancedClip, format!("{} with no open region", name)),
+                    Some((opened, region)) if region != closes => issue(
+                        index,
+                        IssueKind::UnbalancedClip,
+                        format!("{} closes the {:?} opened at command {}", name, region, opened),
+                    ),
+                    Some(_) => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (index, region) in open {
+        issue(index, IssueKind::UnbalancedClip, format!("{:?} is never closed", region));
+    }
+    issues
+}
+
+/// `validate_commands` for a JSON frame. Commands are parsed one at a time,
+/// so one malformed command is reported as `InvalidCommand` rather than
+/// hiding the issues in the rest. Errors only if `json` isn't an array.
+pub fn validate_commands_json(json: &str, texture_exists: impl Fn(u32) -> bool) -> Result<Vec<CommandIssue>, serde_json::Error> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(json)?;
+    let mut commands = Vec::with_capacity(values.len());
+    // Index in `values` of each parsed command
+    let mut indices = Vec::with_capacity(values.len());
+    let mut issues = Vec::new();
+
+    for (index, value) in values.into_iter().enumerate() {
+        match serde_json::from_value::<RenderCommand>(value) {
+            Ok(command) => {
+                commands.push(command);
+                indices.push(index);
+            }
+            Err(e) => {
+                // An integer color too big for u32 is the usual out-of-range case
+                let kind = if e.to_string().starts_with("invalid value: integer") { IssueKind::OutOfRange } else { IssueKind::InvalidCommand };
+                issues.push(CommandIssue { index, kind, message: e.to_string() });
+            }
+        }
+    }
+
+    issues.extend(validate_commands(&commands, texture_exists).into_iter().map(|mut issue| {
+        issue.index = indices[issue.index];
+        issue
+    }));
+    issues.sort_by_key(|issue| issue.index);
+    Ok(issues)
+}
+
+/// The first NaN or infinity in `values`, or 0.0 if they are all finite
+fn first_non_finite(values: impl IntoIterator<Item = f32>) -> f32 {
+    values.into_iter().find(|v| !v.is_finite()).unwrap_or(0.0)
+}
+
+fn check_unit(value: f32, name: &str, index: usize, issue: &mut impl FnMut(usize, IssueKind, String)) {
+    if value.is_finite() && !(0.0..=1.0).contains(&value) {
+        issue(index, IssueKind::OutOfRange, format!("{} {} is outside 0.0-1.0", name, value));
+    }
+}
+
+fn check_gradient(gradient: &Gradient, index: usize, issue: &mut impl FnMut(usize, IssueKind, String)) {
+    let (Gradient::Linear { stops, .. } | Gradient::Radial { stops, .. } | Gradient::Conic { stops, .. }) = gradient;
+    for stop in stops {
+        check_unit(stop.position, "gradient stop position", index, issue);
+    }
+}
+
 /// Vertex structure for low-level rendering
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
     /// Position (x, y, z)
     pub position: [f32; 3],
@@ -332,6 +1154,9 @@ impl Vertex {
 pub struct Renderer {
     mode: RenderMode,
     command_buffer: CommandBuffer,
+    /// Windowless backend for `render_offscreen`, created on first use
+    #[cfg(not(target_arch = "wasm32"))]
+    headless: Option<Box<crate::platform::wgpu_backend::WgpuBackend>>,
 }
 
 impl Renderer {
@@ -339,6 +1164,8 @@ impl Renderer {
         Self {
             mode,
             command_buffer: CommandBuffer::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            headless: None,
         }
     }
 
@@ -358,6 +1185,23 @@ impl Renderer {
         &self.command_buffer
     }
 
+    /// Render `commands` without a window into `width` x `height` RGBA8
+    /// pixels (sRGB, rows top to bottom, no padding), in either mode.
+    ///
+    /// Uses the GPU when available and a software adapter otherwise.
+    /// Animations are frozen at their first frame, so the same commands give
+    /// the same bytes on a given machine - suitable for golden-image tests.
+    /// Fails if the platform has no adapter at all.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_offscreen(&mut self, commands: &[RenderCommand], width: u32, height: u32) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if self.headless.is_none() {
+            let mut backend = crate::platform::wgpu_backend::WgpuBackend::new();
+            pollster::block_on(backend.init_headless(width, height))?;
+            self.headless = Some(Box::new(backend));
+        }
+        self.headless.as_mut().unwrap().render_to_pixels(commands, width, height)
+    }
+
     /// Execute rendering (platform implementation will override this)
     pub fn render(&mut self) {
         // Platform-specific implementation will be provided by Go layer
@@ -381,11 +1225,287 @@ mod tests {
                 color: 0xFF0000FF,
                 corner_radii: [0.0, 0.0, 0.0, 0.0],
                 rotation: 0.0,
+                pivot: None,
                 border: None,
                 gradient: None,
+                inner_shadow: None,
+                shadow: None,
             },
         ];
         renderer.submit_frame(commands);
         assert_eq!(renderer.command_buffer().commands().len(), 1);
     }
+
+    #[test]
+    fn test_render_offscreen_is_repeatable() {
+        let mut renderer = Renderer::new(RenderMode::Retained);
+        let commands = vec![
+            RenderCommand::Clear(crate::style::Color { r: 255, g: 255, b: 255, a: 255 }),
+            RenderCommand::DrawRect {
+                x: 2.0,
+                y: 2.0,
+                width: 8.0,
+                height: 4.0,
+                color: 0x3B82F6FF,
+                corner_radii: [2.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: None,
+                gradient: None,
+                inner_shadow: None,
+                shadow: None,
+            },
+        ];
+        let first = match renderer.render_offscreen(&commands, 12, 8) {
+            Ok(pixels) => pixels,
+            Err(e) => return crate::platform::wgpu_backend::tests::no_gpu(e),
+        };
+        assert_eq!(first.len(), 12 * 8 * 4);
+        assert_eq!(renderer.render_offscreen(&commands, 12, 8).unwrap(), first);
+        assert_eq!(renderer.render_offscreen(&commands, 6, 4).unwrap().len(), 6 * 4 * 4);
+    }
+
+    #[test]
+    fn test_spinner_rotation_advances_with_frame_time() {
+        let start = spinner_rotation(1.0, 0.0);
+        let later = spinner_rotation(1.0, 0.25);
+        assert_eq!(start, 0.0);
+        assert!((later - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert!(spinner_rotation(1.0, 0.5) > later);
+
+        // Faster spinners cover more of the turn in the same time
+        assert!(spinner_rotation(2.0, 0.1) > spinner_rotation(1.0, 0.1));
+
+        // Rotation wraps after a full revolution
+        assert!(spinner_rotation(1.0, 1.25) < std::f32::consts::TAU);
+        assert!((spinner_rotation(1.0, 1.25) - later).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_spinner_is_animated() {
+        let spinner = RenderCommand::Spinner {
+            center_x: 50.0,
+            center_y: 50.0,
+            radius: 16.0,
+            color: 0x3B82F6FF,
+            speed: 1.0,
+            thickness: 0.0,
+        };
+        assert!(spinner.is_animated());
+        assert!(!RenderCommand::PopClip {}.is_animated());
+    }
+
+    #[test]
+    fn test_border_style_defaults_to_solid() {
+        let solid: Border = serde_json::from_str(r#"{"width": 1, "color": 255}"#).unwrap();
+        assert_eq!(solid.style, BorderStyle::Solid);
+        assert_eq!((solid.dash_length, solid.gap_length), (0.0, 0.0));
+
+        let dashed: Border =
+            serde_json::from_str(r#"{"width": 2, "color": 255, "style": "Dashed", "dash_length": 6, "gap_length": 2}"#).unwrap();
+        assert_eq!(dashed.style, BorderStyle::Dashed);
+        assert_eq!((dashed.dash_length, dashed.gap_length), (6.0, 2.0));
+    }
+
+    #[test]
+    fn test_draw_line_json_round_trip() {
+        // The shape centered_backend_render_frame parses, cap defaulting to Butt
+        let json = r#"[{"DrawLine": {"x1": 0, "y1": 10, "x2": 100, "y2": 10, "width": 2, "color": 255}},
+                       {"DrawLine": {"x1": 5, "y1": 5, "x2": 5, "y2": 5, "width": 4, "color": 255, "cap": "Round"}}]"#;
+        let commands: Vec<RenderCommand> = serde_json::from_str(json).unwrap();
+        assert!(matches!(commands[0], RenderCommand::DrawLine { x2, cap: LineCap::Butt, .. } if x2 == 100.0));
+        assert!(matches!(commands[1], RenderCommand::DrawLine { cap: LineCap::Round, .. }));
+
+        let reparsed: Vec<RenderCommand> = serde_json::from_str(&serde_json::to_string(&commands).unwrap()).unwrap();
+        assert!(matches!(reparsed[1], RenderCommand::DrawLine { width, cap: LineCap::Round, .. } if width == 4.0));
+    }
+
+    #[test]
+    fn test_focus_ring_dashes_march_with_frame_time() {
+        // Dashes advance at `speed` pixels per second from the start offset
+        assert_eq!(focus_ring_dash_offset(3.0, 20.0, 0.0), 3.0);
+        assert!((focus_ring_dash_offset(3.0, 20.0, 0.5) - 13.0).abs() < 1e-4);
+        assert!(focus_ring_dash_offset(0.0, 20.0, 0.2) > focus_ring_dash_offset(0.0, 20.0, 0.1));
+
+        // Only a moving ring needs continuous redraws
+        let ring = |speed| RenderCommand::FocusRing {
+            rect: Rect::new(10.0, 10.0, 80.0, 30.0),
+            corner_radii: [6.0; 4],
+            color: 0x3B82F6FF,
+            thickness: 2.0,
+            dash: 4.0,
+            offset: 0.0,
+            speed,
+        };
+        assert!(ring(20.0).is_animated());
+        assert!(!ring(0.0).is_animated());
+
+        // The rect is a nested object in JSON
+        let json = r#"{"FocusRing": {"rect": {"x": 10, "y": 10, "width": 80, "height": 30}, "color": 255, "speed": 20}}"#;
+        let parsed: RenderCommand = serde_json::from_str(json).unwrap();
+        assert!(matches!(parsed, RenderCommand::FocusRing { rect, dash, .. } if rect.width == 80.0 && dash == 0.0));
+    }
+
+    #[test]
+    fn test_caret_blinks_at_configured_interval() {
+        // Simulated 60fps frames over two seconds with a 500ms blink
+        let frames: Vec<(f32, bool)> = (0..120)
+            .map(|frame| {
+                let elapsed = frame as f32 / 60.0;
+                (elapsed, caret_visible(500, elapsed))
+            })
+            .collect();
+        let toggles: Vec<f32> = frames
+            .windows(2)
+            .filter(|pair| pair[0].1 != pair[1].1)
+            .map(|pair| pair[1].0)
+            .collect();
+        assert!(frames[0].1, "caret starts visible");
+        assert_eq!(toggles.len(), 3);
+        for (toggle, expected) in toggles.iter().zip([0.5, 1.0, 1.5]) {
+            assert!((toggle - expected).abs() < 1.0 / 60.0, "toggled at {}", toggle);
+        }
+
+        // Waking up when told to lands on the next toggle every time
+        let mut elapsed = 0.0;
+        let mut visible = caret_visible(500, elapsed);
+        for _ in 0..6 {
+            elapsed += caret_next_toggle_ms(500, elapsed).unwrap() as f32 / 1000.0;
+            assert_ne!(caret_visible(500, elapsed), visible);
+            visible = !visible;
+        }
+        assert_eq!(caret_next_toggle_ms(500, 0.2), Some(300));
+
+        // A solid caret never schedules a redraw, and none needs continuous redraws
+        assert!(caret_visible(0, 12.3));
+        assert_eq!(caret_next_toggle_ms(0, 0.2), None);
+        let caret = RenderCommand::Caret { x: 10.0, y: 4.0, width: 0.0, height: 18.0, color: 0x000000FF, blink_ms: 500 };
+        assert!(!caret.is_animated());
+    }
+
+    #[test]
+    fn test_nine_patch_pieces_keep_corners_fixed() {
+        // A 30x30 bubble with 10-pixel borders stretched to 100x50
+        let pieces = nine_patch_pieces(Rect::new(5.0, 5.0, 100.0, 50.0), [10.0; 4], 30, 30);
+        assert_eq!(pieces.len(), 9);
+        assert_eq!(pieces[0], (Rect::new(5.0, 5.0, 10.0, 10.0), Rect::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(pieces[4], (Rect::new(15.0, 15.0, 80.0, 30.0), Rect::new(10.0, 10.0, 10.0, 10.0)));
+        assert_eq!(pieces[8], (Rect::new(95.0, 45.0, 10.0, 10.0), Rect::new(20.0, 20.0, 10.0, 10.0)));
+
+        // Narrower than its borders: they share the width and the middle
+        // column goes, so the corners meet without overlapping
+        let pieces = nine_patch_pieces(Rect::new(0.0, 0.0, 12.0, 50.0), [10.0, 5.0, 10.0, 15.0], 30, 30);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces[0], (Rect::new(0.0, 0.0, 9.0, 10.0), Rect::new(0.0, 0.0, 15.0, 10.0)));
+        assert_eq!(pieces[1], (Rect::new(9.0, 0.0, 3.0, 10.0), Rect::new(25.0, 0.0, 5.0, 10.0)));
+
+        // Zero insets are a plain stretched image
+        let pieces = nine_patch_pieces(Rect::new(0.0, 0.0, 64.0, 64.0), [0.0; 4], 16, 16);
+        assert_eq!(pieces, vec![(Rect::new(0.0, 0.0, 64.0, 64.0), Rect::new(0.0, 0.0, 16.0, 16.0))]);
+    }
+
+    #[test]
+    fn test_image_uvs_for_atlas_sub_rect() {
+        // The 32x16 cell at (64, 32) of a 256x128 atlas
+        let cell = Rect::new(64.0, 32.0, 32.0, 16.0);
+        assert_eq!(image_uvs(None, Some(cell), 256, 128), (0.25, 0.25, 0.375, 0.375));
+        // A pixel region wins over a normalized one
+        assert_eq!(image_uvs(Some((0.5, 0.5, 0.5, 0.5)), Some(cell), 256, 128), (0.25, 0.25, 0.375, 0.375));
+        // Normalized source rects are x, y, width, height
+        assert_eq!(image_uvs(Some((0.5, 0.25, 0.25, 0.5)), None, 256, 128), (0.5, 0.25, 0.75, 0.75));
+        assert_eq!(image_uvs(None, None, 256, 128), (0.0, 0.0, 1.0, 1.0));
+
+        let json = r#"{"DrawImage":{"x":0,"y":0,"width":32,"height":16,"texture_id":1,"source_rect":null,
+            "src":{"x":64,"y":32,"width":32,"height":16},"tint":4278190335}}"#;
+        match serde_json::from_str::<RenderCommand>(json).unwrap() {
+            RenderCommand::DrawImage { src, tint, sampling, .. } => {
+                assert_eq!(src, Some(cell));
+                assert_eq!(tint, Some(0xFF0000FF));
+                assert_eq!(sampling, Sampling::Trilinear);
+            }
+            other => panic!("expected DrawImage, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_flags_unknown_texture() {
+        let image = |texture_id| RenderCommand::DrawImage {
+            x: 0.0,
+            y: 0.0,
+            width: 64.0,
+            height: 64.0,
+            texture_id,
+            source_rect: None,
+            src: None,
+            tint: None,
+            corner_radii: [0.0; 4],
+            opacity: 1.0,
+            rotation: 0.0,
+            pivot: None,
+            sampling: Sampling::default(),
+        };
+        let commands = vec![
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 100.0, height: 100.0 },
+            image(1),
+            image(7),
+            RenderCommand::PopClip {},
+        ];
+        let issues = validate_commands(&commands, |id| id == 1);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].index, 2);
+        assert_eq!(issues[0].kind, IssueKind::UnknownTexture);
+    }
+
+    #[test]
+    fn test_validate_json_reports_each_problem() {
+        let json = r#"[
+            {"PushClip": {"x": 0, "y": 0, "width": 100, "height": 100}},
+            {"BeginScrollView": {"x": 0, "y": 0, "width": 100, "height": 100, "scroll_x": 0, "scroll_y": 1e40,
+                "content_width": null, "content_height": null}},
+            {"PopClip": {}},
+            {"SetOpacity": 1.5},
+            {"DrawRect": {"x": 0, "y": 0, "width": 10, "height": 10, "color": 4294967296,
+                "corner_radii": [0, 0, 0, 0], "border": null, "gradient": null}},
+            {"PopClip": {}},
+            {"PopClip": {}},
+            {"PushTransform": {"matrix": [2, 0, 0, 2, 0, 0]}},
+            {"PopClip": {}},
+            {"PopTransform": {}}
+        ]"#;
+        let issues = validate_commands_json(json, |_| true).unwrap();
+        let found: Vec<_> = issues.iter().map(|issue| (issue.index, issue.kind)).collect();
+        assert_eq!(found, vec![
+            (1, IssueKind::NonFinite),
+            (2, IssueKind::UnbalancedClip),
+            (3, IssueKind::OutOfRange),
+            (4, IssueKind::OutOfRange),
+            (6, IssueKind::UnbalancedClip),
+            (8, IssueKind::UnbalancedClip),
+            (9, IssueKind::UnbalancedClip),
+        ]);
+
+        assert!(validate_commands_json("{}", |_| true).is_err());
+    }
+
+    #[test]
+    fn test_validate_opacity_layers() {
+        let commands = vec![
+            RenderCommand::PushOpacityLayer(0.5),
+            RenderCommand::PushClip { x: 0.0, y: 0.0, width: 10.0, height: 10.0 },
+            RenderCommand::PopOpacityLayer {},
+            RenderCommand::PushOpacityLayer(2.0),
+        ];
+        let issues = validate_commands(&commands, |_| true);
+        let found: Vec<_> = issues.iter().map(|issue| (issue.index, issue.kind)).collect();
+        assert_eq!(found, vec![
+            (2, IssueKind::UnbalancedClip),
+            (3, IssueKind::OutOfRange),
+            (0, IssueKind::UnbalancedClip),
+            (3, IssueKind::UnbalancedClip),
+        ]);
+        assert_eq!(issues[0].message, "PopOpacityLayer closes the Clip opened at command 1");
+
+        let json = r#"[{"PushOpacityLayer": 0.5}, {"PopOpacityLayer": {}}]"#;
+        assert!(validate_commands_json(json, |_| true).unwrap().is_empty());
+    }
 }
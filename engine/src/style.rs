@@ -7,6 +7,12 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Condvar, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
 /// Color representation (RGBA)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -140,8 +146,14 @@ pub enum StyleRule {
 }
 
 /// Theme configuration loaded from TOML
+///
+/// Values in `[variables]` can be referenced from `[colors]` and from other
+/// variables as `$name` or `var(name)`; references are replaced with the
+/// values they name when the theme is loaded.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ThemeConfig {
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
     #[serde(default)]
     pub colors: HashMap<String, String>,
     #[serde(default)]
@@ -180,6 +192,7 @@ impl Default for ThemeConfig {
         }
 
         Self {
+            variables: HashMap::new(),
             colors,
             spacing,
             custom_classes: HashMap::new(),
@@ -187,6 +200,126 @@ impl Default for ThemeConfig {
     }
 }
 
+impl ThemeConfig {
+    /// Replace variable references in `variables` and `colors` with the
+    /// values they name
+    fn resolve_variables(&mut self) -> Result<(), String> {
+        let mut names: Vec<&String> = self.variables.keys().collect();
+        names.sort();
+        let mut resolved = HashMap::with_capacity(names.len());
+        for name in names {
+            let value = resolve_variable(&self.variables, name, &mut Vec::new())?;
+            resolved.insert(name.clone(), value);
+        }
+
+        for (color, value) in &mut self.colors {
+            if let Some(name) = variable_reference(value) {
+                *value = resolved
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| format!("color `{}` references undefined variable `{}`", color, name))?;
+            }
+        }
+        self.variables = resolved;
+        Ok(())
+    }
+}
+
+/// Theme keys a reload changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThemeChanges {
+    /// Changed, added and removed keys as "section.key" (e.g.
+    /// "colors.primary"), sorted. Colors that reference a variable change
+    /// along with it.
+    pub keys: Vec<String>,
+}
+
+impl ThemeChanges {
+    fn between(old: &ThemeConfig, new: &ThemeConfig) -> Self {
+        let mut keys = Vec::new();
+        changed_keys("variables", &old.variables, &new.variables, &mut keys);
+        changed_keys("colors", &old.colors, &new.colors, &mut keys);
+        changed_keys("spacing", &old.spacing, &new.spacing, &mut keys);
+        changed_keys("custom_classes", &old.custom_classes, &new.custom_classes, &mut keys);
+        keys.sort();
+        Self { keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether `key` of `section` changed
+    pub fn contains(&self, section: &str, key: &str) -> bool {
+        self.keys.iter().any(|changed| {
+            changed.strip_prefix(section).and_then(|rest| rest.strip_prefix('.')) == Some(key)
+        })
+    }
+}
+
+/// Append the keys of one theme section whose values differ between `old`
+/// and `new`, including keys only one of them has
+fn changed_keys<V: PartialEq>(section: &str, old: &HashMap<String, V>, new: &HashMap<String, V>, keys: &mut Vec<String>) {
+    for (key, value) in old {
+        if new.get(key) != Some(value) {
+            keys.push(format!("{}.{}", section, key));
+        }
+    }
+    for key in new.keys().filter(|key| !old.contains_key(*key)) {
+        keys.push(format!("{}.{}", section, key));
+    }
+}
+
+/// The variable named by a `$name` or `var(name)` reference
+fn variable_reference(value: &str) -> Option<&str> {
+    let value = value.trim();
+    value
+        .strip_prefix('$')
+        .or_else(|| value.strip_prefix("var(").and_then(|rest| rest.strip_suffix(')')))
+        .map(str::trim)
+}
+
+/// Follow a chain of variable references to a concrete value. `chain` holds
+/// the variables that led to `name`, to report cycles.
+fn resolve_variable(variables: &HashMap<String, String>, name: &str, chain: &mut Vec<String>) -> Result<String, String> {
+    if chain.iter().any(|seen| seen == name) {
+        chain.push(name.to_string());
+        return Err(format!("cyclic variable reference: {}", chain.join(" -> ")));
+    }
+    let Some(value) = variables.get(name) else {
+        return Err(match chain.last() {
+            Some(from) => format!("variable `{}` references undefined variable `{}`", from, name),
+            None => format!("undefined variable `{}`", name),
+        });
+    };
+    match variable_reference(value) {
+        Some(next) => {
+            chain.push(name.to_string());
+            resolve_variable(variables, next, chain)
+        }
+        None => Ok(value.clone()),
+    }
+}
+
+/// Parse a hex color string like "#RRGGBB" or "#RRGGBBAA"
+fn parse_hex_color(hex_str: &str) -> Option<Color> {
+    let hex_str = hex_str.trim_start_matches('#');
+    if hex_str.len() == 6 {
+        let r = u8::from_str_radix(&hex_str[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex_str[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex_str[4..6], 16).ok()?;
+        Some(Color::new(r, g, b, 255))
+    } else if hex_str.len() == 8 {
+        let r = u8::from_str_radix(&hex_str[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex_str[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex_str[4..6], 16).ok()?;
+        let a = u8::from_str_radix(&hex_str[6..8], 16).ok()?;
+        Some(Color::new(r, g, b, a))
+    } else {
+        None
+    }
+}
+
 /// Main style system
 pub struct StyleSystem {
     theme: ThemeConfig,
@@ -203,13 +336,65 @@ impl StyleSystem {
     }
 
     /// Load a theme configuration from TOML
+    ///
+    /// Fails on an undefined or cyclic variable reference, leaving the
+    /// current theme in place.
     pub fn load_theme(&mut self, toml_str: &str) -> Result<(), String> {
-        let theme: ThemeConfig = toml::from_str(toml_str)
+        self.reload_theme(toml_str).map(|_| ())
+    }
+
+    /// Replace the theme with a new version of it, returning the keys that
+    /// differ from the current one.
+    ///
+    /// For live theme editing: pass the result to `classes_affected` to find
+    /// the widgets to redraw. Fails like `load_theme`, keeping the current
+    /// theme.
+    pub fn reload_theme(&mut self, toml_str: &str) -> Result<ThemeChanges, String> {
+        let mut theme: ThemeConfig = toml::from_str(toml_str)
             .map_err(|e| format!("Failed to parse theme TOML: {}", e))?;
+        theme.resolve_variables()?;
 
+        let changes = ThemeChanges::between(&self.theme, &theme);
         self.theme = theme;
         self.class_cache.clear();
-        Ok(())
+        Ok(changes)
+    }
+
+    /// Whether a class string uses any of the changed theme keys: a color
+    /// or variable named by a `text-`, `bg-` or `border-` class, or a
+    /// custom class (or any class it expands to). Checked against the
+    /// current theme.
+    pub fn classes_affected(&self, class_str: &str, changes: &ThemeChanges) -> bool {
+        !changes.is_empty() && class_str.split_whitespace().any(|class| self.class_affected(class, changes))
+    }
+
+    fn class_affected(&self, class: &str, changes: &ThemeChanges) -> bool {
+        if changes.contains("custom_classes", class) {
+            return true;
+        }
+        if let Some(expanded) = self.theme.custom_classes.get(class) {
+            return expanded.iter().any(|expanded_class| self.class_affected(expanded_class, changes));
+        }
+        ["text-", "bg-", "border-"]
+            .iter()
+            .filter_map(|prefix| class.strip_prefix(prefix))
+            .any(|name| changes.contains("colors", name) || changes.contains("variables", name))
+    }
+
+    /// Call `on_change` with a theme file's contents whenever they change.
+    ///
+    /// Polls the file from a background thread (`on_change` runs there)
+    /// until the returned watcher is dropped; the contents it starts with
+    /// aren't reported. Pass them to `reload_theme`. An editor replacing the
+    /// file on save is picked up, and a missing file is waited for.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch_file(path: impl Into<PathBuf>, on_change: impl FnMut(String) + Send + 'static) -> ThemeWatcher {
+        ThemeWatcher::spawn(path.into(), THEME_POLL_INTERVAL, on_change)
+    }
+
+    /// Forget parsed classes so they are resolved against the theme again
+    pub fn clear_cache(&mut self) {
+        self.class_cache.clear();
     }
 
     /// Parse a class string and return computed styles
@@ -320,24 +505,22 @@ impl StyleSystem {
 
     /// Parse a color name to Color
     fn parse_color(&self, color_name: &str) -> Option<Color> {
-        self.theme.colors.get(color_name).and_then(|hex_str| {
-            // Parse hex color string like "#RRGGBB" or "#RRGGBBAA"
-            let hex_str = hex_str.trim_start_matches('#');
-            if hex_str.len() == 6 {
-                let r = u8::from_str_radix(&hex_str[0..2], 16).ok()?;
-                let g = u8::from_str_radix(&hex_str[2..4], 16).ok()?;
-                let b = u8::from_str_radix(&hex_str[4..6], 16).ok()?;
-                Some(Color::new(r, g, b, 255))
-            } else if hex_str.len() == 8 {
-                let r = u8::from_str_radix(&hex_str[0..2], 16).ok()?;
-                let g = u8::from_str_radix(&hex_str[2..4], 16).ok()?;
-                let b = u8::from_str_radix(&hex_str[4..6], 16).ok()?;
-                let a = u8::from_str_radix(&hex_str[6..8], 16).ok()?;
-                Some(Color::new(r, g, b, a))
-            } else {
-                None
-            }
-        })
+        self.resolve_color(color_name).ok()
+    }
+
+    /// Look up a color of the theme by its name in `[colors]` or
+    /// `[variables]`, or by a `$name` / `var(name)` variable reference
+    pub fn resolve_color(&self, name: &str) -> Result<Color, String> {
+        let value = match variable_reference(name) {
+            Some(variable) => self.theme.variables
+                .get(variable)
+                .ok_or_else(|| format!("undefined variable `{}`", variable))?,
+            None => self.theme.colors
+                .get(name)
+                .or_else(|| self.theme.variables.get(name))
+                .ok_or_else(|| format!("undefined color `{}`", name))?,
+        };
+        parse_hex_color(value).ok_or_else(|| format!("`{}` is not a color: {}", name, value))
     }
 
     /// Apply a style rule to computed styles
@@ -370,6 +553,56 @@ impl Default for StyleSystem {
     }
 }
 
+/// How often `watch_file` checks the file
+#[cfg(not(target_arch = "wasm32"))]
+const THEME_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Watches a theme file for `StyleSystem::watch_file`; stops when dropped
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ThemeWatcher {
+    /// Set on drop, with the condvar waking the thread to exit
+    stop: Arc<(Mutex<bool>, Condvar)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ThemeWatcher {
+    fn spawn(path: PathBuf, interval: Duration, mut on_change: impl FnMut(String) + Send + 'static) -> Self {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+        std::thread::Builder::new()
+            .name("centered-theme-watch".into())
+            .spawn(move || {
+                let mut last = std::fs::read_to_string(&path).ok();
+                let (stopped, wake) = &*thread_stop;
+                let mut guard = stopped.lock().unwrap();
+                loop {
+                    guard = wake.wait_timeout(guard, interval).unwrap().0;
+                    if *guard {
+                        return;
+                    }
+                    // Unreadable mid-save: try again next time
+                    let Ok(contents) = std::fs::read_to_string(&path) else { continue };
+                    if last.as_ref() != Some(&contents) {
+                        last = Some(contents.clone());
+                        drop(guard);
+                        on_change(contents);
+                        guard = stopped.lock().unwrap();
+                    }
+                }
+            })
+            .expect("failed to spawn theme watcher thread");
+        Self { stop }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ThemeWatcher {
+    fn drop(&mut self) {
+        *self.stop.0.lock().unwrap() = true;
+        self.stop.1.notify_one();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -397,4 +630,141 @@ mod tests {
         assert!(computed.text_color.is_some());
         assert!(computed.background_color.is_some());
     }
+
+    #[test]
+    fn test_theme_variables_resolve_at_load() {
+        let mut system = StyleSystem::new();
+        system.load_theme(r##"
+            [variables]
+            brand = "#3B82F6"
+            accent = "$brand"
+            danger = "#EF4444CC"
+
+            [colors]
+            primary = "var(accent)"
+            link = "$accent"
+            error = "var( danger )"
+            white = "#FFFFFF"
+        "##).unwrap();
+
+        let blue = Color::new(0x3B, 0x82, 0xF6, 255);
+        assert_eq!(system.resolve_color("primary"), Ok(blue));
+        assert_eq!(system.resolve_color("link"), Ok(blue));
+        assert_eq!(system.resolve_color("$accent"), Ok(blue));
+        assert_eq!(system.resolve_color("var(brand)"), Ok(blue));
+        assert_eq!(system.resolve_color("error"), Ok(Color::new(0xEF, 0x44, 0x44, 0xCC)));
+        assert_eq!(system.parse_classes("bg-primary text-accent").background_color, Some(blue));
+        assert_eq!(system.parse_classes("text-accent").text_color, Some(blue));
+
+        assert!(system.resolve_color("missing").unwrap_err().contains("missing"));
+        assert!(system.resolve_color("$missing").unwrap_err().contains("undefined variable"));
+    }
+
+    #[test]
+    fn test_undefined_and_cyclic_variables_fail_to_load() {
+        let mut system = StyleSystem::new();
+
+        let err = system.load_theme("[colors]\nprimary = \"$accent\"\n").unwrap_err();
+        assert!(err.contains("undefined variable `accent`"), "{}", err);
+
+        let err = system.load_theme("[variables]\na = \"$b\"\nb = \"var(c)\"\nc = \"$a\"\n").unwrap_err();
+        assert_eq!(err, "cyclic variable reference: a -> b -> c -> a");
+
+        let err = system.load_theme("[variables]\na = \"$a\"\n").unwrap_err();
+        assert_eq!(err, "cyclic variable reference: a -> a");
+
+        // A failed load keeps the previous theme
+        assert!(system.parse_classes("bg-white").background_color.is_some());
+    }
+
+    #[test]
+    fn test_flat_theme_loads_unchanged() {
+        let mut system = StyleSystem::new();
+        system.load_theme("[colors]\nsurface = \"#11223344\"\n[spacing]\n4 = 16.0\n").unwrap();
+        assert_eq!(system.resolve_color("surface"), Ok(Color::new(0x11, 0x22, 0x33, 0x44)));
+        assert!(system.parse_classes("bg-white").background_color.is_none());
+    }
+
+    #[test]
+    fn test_reload_reports_changed_keys() {
+        let mut system = StyleSystem::new();
+        system.load_theme(r##"
+            [variables]
+            accent = "#3B82F6"
+            [colors]
+            primary = "$accent"
+            surface = "#FFFFFF"
+            [custom_classes]
+            btn = ["bg-primary", "rounded"]
+            card = ["bg-surface"]
+        "##).unwrap();
+
+        let changes = system.reload_theme(r##"
+            [variables]
+            accent = "#EF4444"
+            [colors]
+            primary = "$accent"
+            surface = "#FFFFFF"
+            muted = "#9CA3AF"
+            [custom_classes]
+            btn = ["bg-primary", "rounded"]
+            card = ["bg-surface"]
+        "##).unwrap();
+        assert_eq!(changes.keys, vec!["colors.muted", "colors.primary", "variables.accent"]);
+        assert_eq!(system.resolve_color("primary"), Ok(Color::new(0xEF, 0x44, 0x44, 255)));
+
+        // Classes using a changed color, directly, through a variable or
+        // through a custom class
+        assert!(system.classes_affected("p-4 bg-primary", &changes));
+        assert!(system.classes_affected("text-accent", &changes));
+        assert!(system.classes_affected("btn", &changes));
+        assert!(!system.classes_affected("card text-lg", &changes));
+
+        // Reloading the same theme changes nothing
+        let same = system.reload_theme(r##"
+            [variables]
+            accent = "#EF4444"
+            [colors]
+            primary = "$accent"
+            surface = "#FFFFFF"
+            muted = "#9CA3AF"
+            [custom_classes]
+            btn = ["bg-primary", "rounded"]
+            card = ["bg-surface"]
+        "##).unwrap();
+        assert!(same.is_empty());
+        assert!(!system.classes_affected("btn", &same));
+
+        // A removed custom class counts as changed
+        let removed = system.reload_theme("[colors]\nsurface = \"#FFFFFF\"\n").unwrap();
+        assert!(removed.contains("custom_classes", "card"));
+        assert!(system.classes_affected("card", &removed));
+    }
+
+    #[test]
+    fn test_watch_file_reports_edits() {
+        let path = std::env::temp_dir().join(format!("centered-theme-watch-{}.toml", std::process::id()));
+        std::fs::write(&path, "[colors]\nprimary = \"#000000\"\n").unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let watcher = ThemeWatcher::spawn(path.clone(), Duration::from_millis(10), move |contents| {
+            let _ = sender.send(contents);
+        });
+
+        // The starting contents aren't reported
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+
+        std::fs::write(&path, "[colors]\nprimary = \"#FFFFFF\"\n").unwrap();
+        let contents = receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+        let mut system = StyleSystem::new();
+        assert!(system.reload_theme(&contents).unwrap().contains("colors", "primary"));
+        assert_eq!(system.resolve_color("primary"), Ok(Color::white()));
+
+        // No reports after the watcher is dropped
+        drop(watcher);
+        std::thread::sleep(Duration::from_millis(30));
+        std::fs::write(&path, "[colors]\n").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -5,8 +5,16 @@
 //! Go layer (already resolved from Tailwind classes).
 
 pub mod atlas;
+pub mod bidi;
+pub mod ellipsis;
 pub mod font_manager;
+pub mod grapheme;
+pub mod layout;
+pub mod linebreak;
+pub mod rich;
 pub mod shaper;
+pub mod shaping_cache;
+pub mod spacing;
 
 use serde::{Deserialize, Serialize};
 
@@ -14,11 +22,20 @@ use serde::{Deserialize, Serialize};
 pub use atlas::{AtlasEntry, AtlasMetrics, GlyphAtlas, GlyphBitmap, GlyphKey, GlyphRasterizer, PlatformGlyphRasterizer};
 
 // Re-export font manager types
-pub use font_manager::{Font, FontError, FontManager, GlyphMetrics};
+pub use font_manager::{list_system_fonts, Font, FontError, FontFamilyInfo, FontManager, FontOrigin, GlyphMetrics, ResolvedFont, DEFAULT_FONT_FAMILY};
+
+// Re-export text layout types
+pub use layout::{LayoutCluster, LayoutLine, TextLayout};
+
+// Re-export rich text types
+pub use rich::{layout_rich_text, RichLine, RichPiece, RichTextLayout, TextRun};
 
 // Re-export shaper types
 pub use shaper::{ShapedGlyph, ShapedLine, ShapedText, ShaperError, TextShaper, PlatformTextShaper};
 
+// Re-export shaping cache types
+pub use shaping_cache::{ShapingCache, ShapingCacheMetrics, ShapingKey, DEFAULT_SHAPING_CACHE_CAPACITY};
+
 /// Complete font specification with exact values
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FontDescriptor {
@@ -50,6 +67,11 @@ pub enum FontSource {
         name: String,
         data_hash: u64,  // Hash of font data for cache key
     },
+
+    /// Ordered list of system families, primary first (e.g. `["Inter",
+    /// "Noto Sans CJK JP", "Noto Color Emoji"]`). Each grapheme cluster is
+    /// drawn with the first family that has a glyph for it.
+    SystemWithFallback(Vec<String>),
 }
 
 impl FontDescriptor {
@@ -73,6 +95,43 @@ impl FontDescriptor {
         }
     }
 
+    /// Create a font descriptor with an ordered chain of system families
+    pub fn system_with_fallback(names: &[&str], weight: u16, style: FontStyle, size: f32) -> Self {
+        Self {
+            source: FontSource::SystemWithFallback(names.iter().map(|name| name.to_string()).collect()),
+            weight,
+            style,
+            size,
+        }
+    }
+
+    /// The fonts to try for a glyph, in order.
+    ///
+    /// A fallback source expands to one system font per family, all with
+    /// this descriptor's weight, style and size; an empty chain means the
+    /// system font. Any other source is a chain of one.
+    pub fn fallback_chain(&self) -> Vec<FontDescriptor> {
+        match &self.source {
+            FontSource::SystemWithFallback(names) if names.is_empty() => {
+                vec![FontDescriptor { source: FontSource::System("system".to_string()), ..self.clone() }]
+            }
+            FontSource::SystemWithFallback(names) => names
+                .iter()
+                .map(|name| FontDescriptor { source: FontSource::System(name.clone()), ..self.clone() })
+                .collect(),
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// The first font of [`fallback_chain`](Self::fallback_chain), used for
+    /// line metrics and anything else that needs a single face
+    pub fn primary(&self) -> FontDescriptor {
+        match &self.source {
+            FontSource::SystemWithFallback(_) => self.fallback_chain().swap_remove(0),
+            _ => self.clone(),
+        }
+    }
+
     /// Create a cache key for this font (for font manager cache)
     pub fn cache_key(&self) -> String {
         format!(
@@ -113,6 +172,18 @@ impl From<u8> for FontStyle {
     }
 }
 
+/// Resolve the face actually used for a font request.
+///
+/// Missing system families fall back to a bundled font registered for the
+/// same family, then the engine default, then tofu. Uses the process-wide
+/// font manager, so bundled families registered there are taken into account.
+pub fn resolve_font(desc: &FontDescriptor) -> ResolvedFont {
+    let mut manager = font_manager::shared_font_manager()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    manager.resolve_font(desc)
+}
+
 /// Text layout configuration with exact values
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TextLayoutConfig {
@@ -148,6 +219,15 @@ pub struct TextLayoutConfig {
 
     /// Whitespace handling
     pub white_space: WhiteSpace,
+
+    /// How the DrawText y coordinate is interpreted (defaults to top-left)
+    #[serde(default)]
+    pub anchor: TextAnchor,
+
+    /// Paragraph direction for bidi reordering and Start/End alignment
+    /// (defaults to the text's first strong character)
+    #[serde(default)]
+    pub base_direction: TextDirection,
 }
 
 impl Default for TextLayoutConfig {
@@ -164,6 +244,22 @@ impl Default for TextLayoutConfig {
             word_break: WordBreak::Normal,
             overflow: TextOverflow::Wrap,
             white_space: WhiteSpace::Normal,
+            anchor: TextAnchor::TopLeft,
+            base_direction: TextDirection::Auto,
+        }
+    }
+}
+
+impl TextLayoutConfig {
+    /// Offset from the DrawText y to the first line's baseline.
+    ///
+    /// `VerticalAlign::Baseline` puts the first baseline at y whatever the
+    /// anchor, so text in different sizes drawn at one y shares a baseline;
+    /// otherwise the anchor decides (see [`TextAnchor::baseline_offset`]).
+    pub fn first_baseline_offset(&self, ascent: f32, descent: f32, line_count: usize, line_height: f32) -> f32 {
+        match self.vertical_align {
+            VerticalAlign::Baseline => 0.0,
+            _ => self.anchor.baseline_offset(ascent, descent, line_count, line_height),
         }
     }
 }
@@ -176,6 +272,10 @@ pub enum TextAlign {
     Center = 1,
     Right = 2,
     Justify = 3,
+    /// Left in LTR text, right in RTL text
+    Start = 4,
+    /// Right in LTR text, left in RTL text
+    End = 5,
 }
 
 impl From<u8> for TextAlign {
@@ -184,11 +284,25 @@ impl From<u8> for TextAlign {
             1 => TextAlign::Center,
             2 => TextAlign::Right,
             3 => TextAlign::Justify,
+            4 => TextAlign::Start,
+            5 => TextAlign::End,
             _ => TextAlign::Left,
         }
     }
 }
 
+impl TextAlign {
+    /// Map Start and End to Left or Right for a paragraph's direction; other
+    /// alignments are returned as they are
+    pub fn resolve(self, direction: Direction) -> TextAlign {
+        match (self, direction) {
+            (TextAlign::Start, Direction::Ltr) | (TextAlign::End, Direction::Rtl) => TextAlign::Left,
+            (TextAlign::Start, Direction::Rtl) | (TextAlign::End, Direction::Ltr) => TextAlign::Right,
+            (alignment, _) => alignment,
+        }
+    }
+}
+
 /// Vertical text alignment
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -210,13 +324,13 @@ impl From<u8> for VerticalAlign {
     }
 }
 
-/// Word breaking behavior
+/// Word breaking behavior (see `linebreak` for the rules)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum WordBreak {
-    Normal = 0,      // Break at word boundaries
+    Normal = 0,      // Break at word boundaries and between CJK characters
     BreakAll = 1,    // Break anywhere
-    KeepAll = 2,     // No breaks (CJK)
+    KeepAll = 2,     // No breaks inside words, CJK runs included
     BreakWord = 3,   // Break long words if needed
 }
 
@@ -271,6 +385,113 @@ impl From<u8> for WhiteSpace {
     }
 }
 
+/// Vertical anchor for the DrawText y coordinate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum TextAnchor {
+    /// y is the top of the first line (default, matches DrawRect's y)
+    #[default]
+    TopLeft = 0,
+    /// y is the baseline of the first line
+    Baseline = 1,
+    /// y is the vertical center of the whole text block (button labels)
+    Center = 2,
+}
+
+impl From<u8> for TextAnchor {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => TextAnchor::Baseline,
+            2 => TextAnchor::Center,
+            _ => TextAnchor::TopLeft,
+        }
+    }
+}
+
+impl TextAnchor {
+    /// Offset from the anchor y to the first line's baseline.
+    ///
+    /// `line_height` is the distance between consecutive baselines; the block
+    /// height used for centering is one font height plus that distance for
+    /// every additional line.
+    pub fn baseline_offset(self, ascent: f32, descent: f32, line_count: usize, line_height: f32) -> f32 {
+        match self {
+            TextAnchor::TopLeft => ascent,
+            TextAnchor::Baseline => 0.0,
+            TextAnchor::Center => {
+                let extra_lines = line_count.saturating_sub(1) as f32;
+                let block_height = ascent + descent + extra_lines * line_height;
+                ascent - block_height / 2.0
+            }
+        }
+    }
+}
+
+/// Base (paragraph) text direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Direction {
+    Ltr = 0,
+    Rtl = 1,
+}
+
+impl From<u8> for Direction {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Direction::Rtl,
+            _ => Direction::Ltr,
+        }
+    }
+}
+
+/// Requested paragraph direction of a text layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum TextDirection {
+    /// From the first strong character, LTR if there is none
+    #[default]
+    Auto = 0,
+    Ltr = 1,
+    Rtl = 2,
+}
+
+impl From<u8> for TextDirection {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => TextDirection::Ltr,
+            2 => TextDirection::Rtl,
+            _ => TextDirection::Auto,
+        }
+    }
+}
+
+impl TextDirection {
+    /// The direction `text` is laid out in
+    pub fn resolve(self, text: &str) -> Direction {
+        match self {
+            TextDirection::Auto => base_direction(text),
+            TextDirection::Ltr => Direction::Ltr,
+            TextDirection::Rtl => Direction::Rtl,
+        }
+    }
+}
+
+/// Detect the base direction of a string using the first-strong-character
+/// rule (Unicode bidi rules P2/P3), as `unicode_bidi` resolves it for the
+/// first paragraph, so it agrees with how `BidiText` reorders the text.
+///
+/// The first character with a strong direction decides the result; digits,
+/// punctuation, whitespace, combining marks and isolated runs are skipped.
+/// Strings with no strong character (empty, digits-only, punctuation-only)
+/// default to `Direction::Ltr`.
+pub fn base_direction(s: &str) -> Direction {
+    let info = unicode_bidi::BidiInfo::new(s, None);
+    match info.paragraphs.first() {
+        Some(paragraph) if paragraph.level.is_rtl() => Direction::Rtl,
+        _ => Direction::Ltr,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,6 +510,23 @@ mod tests {
         assert!(matches!(font.source, FontSource::Bundled(_)));
     }
 
+    #[test]
+    fn test_fallback_chain_keeps_weight_and_style() {
+        let font = FontDescriptor::system_with_fallback(&["Inter", "Noto Sans CJK JP"], 700, FontStyle::Italic, 14.0);
+        let chain = font.fallback_chain();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].source, FontSource::System("Inter".to_string()));
+        assert_eq!(chain[1].source, FontSource::System("Noto Sans CJK JP".to_string()));
+        assert!(chain.iter().all(|f| f.weight == 700 && f.style == FontStyle::Italic && f.size == 14.0));
+        assert_eq!(font.primary(), chain[0]);
+
+        // An empty chain is the system font; other sources are a chain of one
+        let empty = FontDescriptor::system_with_fallback(&[], 400, FontStyle::Normal, 16.0);
+        assert_eq!(empty.fallback_chain(), vec![FontDescriptor::default()]);
+        let single = FontDescriptor::system("Arial", 400, FontStyle::Normal, 16.0);
+        assert_eq!(single.fallback_chain(), vec![single.clone()]);
+    }
+
     #[test]
     fn test_font_cache_key() {
         let font1 = FontDescriptor::system("Arial", 400, FontStyle::Normal, 16.0);
@@ -315,5 +553,87 @@ mod tests {
         assert_eq!(TextAlign::from(1), TextAlign::Center);
 
         assert_eq!(WordBreak::from(3), WordBreak::BreakWord);
+
+        assert_eq!(TextAnchor::from(0), TextAnchor::TopLeft);
+        assert_eq!(TextAnchor::from(2), TextAnchor::Center);
+
+        assert_eq!(Direction::from(0), Direction::Ltr);
+        assert_eq!(Direction::from(1), Direction::Rtl);
+
+        assert_eq!(TextDirection::from(0), TextDirection::Auto);
+        assert_eq!(TextDirection::from(2), TextDirection::Rtl);
+    }
+
+    #[test]
+    fn test_start_and_end_follow_direction() {
+        assert_eq!(TextAlign::from(4), TextAlign::Start);
+        assert_eq!(TextAlign::Start.resolve(Direction::Ltr), TextAlign::Left);
+        assert_eq!(TextAlign::Start.resolve(Direction::Rtl), TextAlign::Right);
+        assert_eq!(TextAlign::End.resolve(Direction::Rtl), TextAlign::Left);
+        assert_eq!(TextAlign::Center.resolve(Direction::Rtl), TextAlign::Center);
+
+        let arabic = "\u{645}\u{631}\u{62D}\u{628}\u{627} hello";
+        assert_eq!(TextDirection::Auto.resolve(arabic), Direction::Rtl);
+        assert_eq!(TextDirection::Auto.resolve("hello"), Direction::Ltr);
+        assert_eq!(TextDirection::Ltr.resolve(arabic), Direction::Ltr);
+    }
+
+    #[test]
+    fn test_text_anchor_baseline_offsets() {
+        let (ascent, descent, line_height) = (14.0, 4.0, 27.0);
+
+        let top_left = TextAnchor::TopLeft.baseline_offset(ascent, descent, 1, line_height);
+        let baseline = TextAnchor::Baseline.baseline_offset(ascent, descent, 1, line_height);
+        assert_eq!(top_left - baseline, ascent);
+
+        // Single line centered on y: baseline sits below center by half (ascent - descent)
+        let center = TextAnchor::Center.baseline_offset(ascent, descent, 1, line_height);
+        assert_eq!(center, (ascent - descent) / 2.0);
+
+        // Multi-line blocks move the first baseline up to keep the block centered
+        let center_two = TextAnchor::Center.baseline_offset(ascent, descent, 2, line_height);
+        assert_eq!(center - center_two, line_height / 2.0);
+
+        assert_eq!(TextLayoutConfig::default().anchor, TextAnchor::TopLeft);
+    }
+
+    #[test]
+    fn test_vertical_align_baseline_offset() {
+        let (ascent, descent, line_height) = (14.0, 4.0, 27.0);
+
+        let top = TextLayoutConfig::default();
+        assert_eq!(top.first_baseline_offset(ascent, descent, 2, line_height), ascent);
+
+        // Baseline alignment wins over the anchor
+        let baseline = TextLayoutConfig {
+            vertical_align: VerticalAlign::Baseline,
+            anchor: TextAnchor::Center,
+            ..Default::default()
+        };
+        assert_eq!(baseline.first_baseline_offset(ascent, descent, 2, line_height), 0.0);
+    }
+
+    #[test]
+    fn test_base_direction_arabic_is_rtl() {
+        assert_eq!(base_direction("مرحبا بالعالم"), Direction::Rtl);
+        // Leading neutrals are skipped before the first strong character
+        assert_eq!(base_direction("123 - مرحبا Hello"), Direction::Rtl);
+        assert_eq!(base_direction("שלום"), Direction::Rtl);
+    }
+
+    #[test]
+    fn test_base_direction_english_is_ltr() {
+        assert_eq!(base_direction("Hello مرحبا"), Direction::Ltr);
+        assert_eq!(base_direction("  (42) world"), Direction::Ltr);
+        // Text in an isolate doesn't count, as in the bidi algorithm
+        assert_eq!(base_direction("\u{2067}שלום\u{2069} world"), Direction::Ltr);
+    }
+
+    #[test]
+    fn test_base_direction_defaults_to_ltr() {
+        assert_eq!(base_direction("1234567890"), Direction::Ltr);
+        assert_eq!(base_direction("٠١٢٣"), Direction::Ltr); // Arabic-Indic digits are weak
+        assert_eq!(base_direction(""), Direction::Ltr);
+        assert_eq!(base_direction("!?. "), Direction::Ltr);
     }
 }
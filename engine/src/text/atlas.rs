@@ -6,8 +6,8 @@
 //!
 //! Features:
 //! - Dynamic texture packing using shelf algorithm
-//! - Automatic atlas growth when full
-//! - LRU eviction (TODO) for very large glyph sets
+//! - LRU eviction: when the atlas is full, the shelf whose glyphs were used
+//!   least recently is emptied and reused
 //! - SDF (Signed Distance Field) rendering (TODO) for crisp scaling
 
 use std::collections::HashMap;
@@ -63,6 +63,27 @@ impl GlyphKey {
         }
     }
 
+    /// Create a glyph key for a grapheme cluster.
+    ///
+    /// Single-char clusters use the char as the glyph ID, matching [`new`](Self::new).
+    /// Multi-code-point clusters hash to an ID above the Unicode range so they
+    /// never collide with a single char.
+    pub fn for_grapheme(font_id: u64, grapheme: &str, size_px: f32) -> Self {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut chars = grapheme.chars();
+        let glyph_id = match (chars.next(), chars.next()) {
+            (Some(ch), None) => ch as u32,
+            _ => {
+                let mut hasher = DefaultHasher::new();
+                grapheme.hash(&mut hasher);
+                0x8000_0000 | (hasher.finish() as u32 & 0x7FFF_FFFF)
+            }
+        };
+        Self::new(font_id, glyph_id, size_px)
+    }
+
     /// Create a glyph key with subpixel positioning (x is fractional pixel position)
     pub fn with_subpixel(font_id: u64, glyph_id: u32, size_px: f32, x: f32) -> Self {
         // Quantize to 4 subpixel positions (0, 0.25, 0.5, 0.75)
@@ -154,19 +175,19 @@ impl ShelfPacker {
         }
     }
 
-    /// Try to pack a rectangle, returns position if successful
-    fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+    /// Try to pack a rectangle, returns position and shelf index if successful
+    fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32, usize)> {
         let padded_width = width + self.padding * 2;
         let padded_height = height + self.padding * 2;
 
         // Try to fit in existing shelves
-        for shelf in &mut self.shelves {
+        for (index, shelf) in self.shelves.iter_mut().enumerate() {
             if shelf.height >= padded_height &&
                shelf.x + padded_width <= self.width {
                 let x = shelf.x;
                 let y = shelf.y;
                 shelf.x += padded_width;
-                return Some((x + self.padding, y + self.padding));
+                return Some((x + self.padding, y + self.padding, index));
             }
         }
 
@@ -182,11 +203,24 @@ impl ShelfPacker {
                 x: padded_width,
             };
             self.shelves.push(shelf);
-            Some((self.padding, current_y + self.padding))
+            Some((self.padding, current_y + self.padding, self.shelves.len() - 1))
         } else {
             None
         }
     }
+
+    /// Empty a shelf so it can be packed again, returning its rows (y,
+    /// height). Empty shelves at the bottom are removed, returning their
+    /// space for shelves of any height.
+    fn free_shelf(&mut self, index: usize) -> (u32, u32) {
+        let shelf = &mut self.shelves[index];
+        shelf.x = 0;
+        let rows = (shelf.y, shelf.height);
+        while self.shelves.last().is_some_and(|shelf| shelf.x == 0) {
+            self.shelves.pop();
+        }
+        rows
+    }
 }
 
 /// Performance metrics for the glyph atlas
@@ -209,6 +243,9 @@ pub struct AtlasMetrics {
 
     /// Total bytes uploaded to GPU
     pub bytes_uploaded: u64,
+
+    /// Glyphs evicted to make room in a full atlas
+    pub evictions: u64,
 }
 
 impl AtlasMetrics {
@@ -226,6 +263,16 @@ impl AtlasMetrics {
     }
 }
 
+/// A cached glyph with what eviction needs to know about it
+#[derive(Debug, Clone, Copy)]
+struct CachedGlyph {
+    entry: AtlasEntry,
+    /// Shelf the glyph is packed on
+    shelf: usize,
+    /// Frame the glyph was last drawn in
+    last_used: u64,
+}
+
 /// Glyph atlas - manages GPU texture cache for rendered glyphs
 pub struct GlyphAtlas {
     /// Atlas texture width
@@ -238,7 +285,10 @@ pub struct GlyphAtlas {
     texture_data: Vec<u8>,
 
     /// Cache mapping glyph keys to atlas entries
-    cache: HashMap<GlyphKey, AtlasEntry>,
+    cache: HashMap<GlyphKey, CachedGlyph>,
+
+    /// Current frame number, advanced by `begin_frame`
+    frame: u64,
 
     /// Texture packer
     packer: ShelfPacker,
@@ -260,32 +310,48 @@ impl GlyphAtlas {
             height,
             texture_data,
             cache: HashMap::new(),
+            frame: 0,
             packer: ShelfPacker::new(width, height, 1), // 1px padding
             dirty: false,
             metrics: AtlasMetrics::default(),
         }
     }
 
+    /// Start a new frame. Glyphs looked up or inserted since the previous
+    /// call are never evicted, as that frame's vertices already sample them.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
     /// Get a glyph from the cache, or None if not cached
     pub fn get(&mut self, key: &GlyphKey) -> Option<&AtlasEntry> {
         self.metrics.cache_lookups += 1;
 
-        if let Some(entry) = self.cache.get(key) {
+        if let Some(glyph) = self.cache.get_mut(key) {
             self.metrics.cache_hits += 1;
-            Some(entry)
+            glyph.last_used = self.frame;
+            Some(&glyph.entry)
         } else {
             self.metrics.cache_misses += 1;
             None
         }
     }
 
-    /// Add a glyph bitmap to the atlas
+    /// Add a glyph bitmap to the atlas.
+    ///
+    /// If the atlas is full, least recently used glyphs are evicted to make
+    /// room. Returns None if it can't fit even then.
     pub fn insert(&mut self, key: GlyphKey, bitmap: GlyphBitmap) -> Option<AtlasEntry> {
         // Track rasterization
         self.metrics.glyphs_rasterized += 1;
 
-        // Try to pack the bitmap
-        let (x, y) = self.packer.pack(bitmap.width, bitmap.height)?;
+        // Try to pack the bitmap, evicting until it fits
+        let (x, y, shelf) = loop {
+            if let Some(packed) = self.packer.pack(bitmap.width, bitmap.height) {
+                break packed;
+            }
+            self.evict_least_recently_used_shelf()?;
+        };
 
         // Copy bitmap data into atlas texture
         self.copy_bitmap_to_atlas(&bitmap, x, y);
@@ -319,12 +385,38 @@ impl GlyphAtlas {
             advance: bitmap.advance,
         };
 
-        self.cache.insert(key, entry);
+        self.cache.insert(key, CachedGlyph { entry, shelf, last_used: self.frame });
         self.dirty = true;
 
         Some(entry)
     }
 
+    /// Evict every glyph on the shelf used least recently, skipping shelves
+    /// with glyphs in use this frame. Returns None if there is none to evict.
+    fn evict_least_recently_used_shelf(&mut self) -> Option<()> {
+        let mut shelf_last_used: HashMap<usize, u64> = HashMap::new();
+        for glyph in self.cache.values() {
+            let last_used = shelf_last_used.entry(glyph.shelf).or_default();
+            *last_used = (*last_used).max(glyph.last_used);
+        }
+        let (shelf, _) = shelf_last_used.into_iter()
+            .filter(|&(_, last_used)| last_used < self.frame)
+            .min_by_key(|&(shelf, last_used)| (last_used, shelf))?;
+
+        let before = self.cache.len();
+        self.cache.retain(|_, glyph| glyph.shelf != shelf);
+        self.metrics.evictions += (before - self.cache.len()) as u64;
+
+        // Clear the rows so stale pixels can't bleed into new neighbours
+        let (y, height) = self.packer.free_shelf(shelf);
+        let row_bytes = (self.width * 4) as usize;
+        let start = y as usize * row_bytes;
+        let end = ((y + height).min(self.height)) as usize * row_bytes;
+        self.texture_data[start..end].fill(0);
+        self.dirty = true;
+        Some(())
+    }
+
     /// Copy a glyph bitmap into the atlas texture
     fn copy_bitmap_to_atlas(&mut self, bitmap: &GlyphBitmap, x: u32, y: u32) {
         #[cfg(debug_assertions)]
@@ -420,6 +512,15 @@ impl GlyphAtlas {
         self.metrics.bytes_uploaded += (self.width * self.height * 4) as u64;
     }
 
+    /// Drop every cached glyph so each is rasterized again on next use,
+    /// e.g. after the app font changes
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.packer = ShelfPacker::new(self.width, self.height, 1);
+        self.texture_data.fill(0);
+        self.dirty = true;
+    }
+
     /// Get number of cached glyphs
     pub fn glyph_count(&self) -> usize {
         self.cache.len()
@@ -439,7 +540,7 @@ impl GlyphAtlas {
     pub fn utilization(&self) -> f32 {
         let total_pixels = (self.width * self.height) as f32;
         let used_pixels: u32 = self.cache.values()
-            .map(|entry| entry.width * entry.height)
+            .map(|glyph| glyph.entry.width * glyph.entry.height)
             .sum();
         used_pixels as f32 / total_pixels
     }
@@ -491,6 +592,79 @@ pub trait GlyphRasterizer {
         character: char,
         font: &super::FontDescriptor,
     ) -> Option<GlyphBitmap>;
+
+    /// Render a grapheme cluster (e.g. an emoji ZWJ sequence) as one bitmap.
+    ///
+    /// The default renders the cluster's first char, so a sequence never
+    /// breaks into several glyphs. Platforms that can draw whole sequences
+    /// with native text APIs provide their own `rasterize_grapheme`.
+    fn rasterize_grapheme(
+        &mut self,
+        grapheme: &str,
+        font: &super::FontDescriptor,
+    ) -> Option<GlyphBitmap> {
+        let character = grapheme.chars().next()?;
+        self.rasterize_glyph(character, font)
+    }
+
+    /// Whether `font` has a glyph for `character`.
+    ///
+    /// Used to walk fallback chains. The default claims every glyph, so on
+    /// platforms without a coverage query the primary font is always used.
+    fn has_glyph(&mut self, _character: char, _font: &super::FontDescriptor) -> bool {
+        true
+    }
+
+    /// The font a grapheme cluster is drawn with: the first font of the
+    /// fallback chain that has a glyph for the cluster's base character, or
+    /// the primary font if none does (so it shows as the primary's tofu).
+    fn font_for_grapheme(&mut self, grapheme: &str, font: &super::FontDescriptor) -> super::FontDescriptor {
+        let chain = font.fallback_chain();
+        if chain.len() > 1 {
+            if let Some(character) = super::grapheme::base_char(grapheme) {
+                if let Some(found) = chain.iter().find(|candidate| self.has_glyph(character, candidate)) {
+                    return found.clone();
+                }
+            }
+        }
+        chain.into_iter().next().unwrap_or_else(|| font.clone())
+    }
+
+    /// Split `text` into runs of consecutive grapheme clusters that
+    /// [`font_for_grapheme`](Self::font_for_grapheme) draws with the same font
+    fn fallback_runs<'a>(&mut self, text: &'a str, font: &super::FontDescriptor) -> Vec<(&'a str, super::FontDescriptor)> {
+        let mut runs: Vec<(&'a str, super::FontDescriptor)> = Vec::new();
+        let mut run_start = 0;
+        for (offset, grapheme) in super::grapheme::graphemes(text) {
+            let end = offset + grapheme.len();
+            let chosen = self.font_for_grapheme(grapheme, font);
+            match runs.last_mut() {
+                Some((run, run_font)) if *run_font == chosen => *run = &text[run_start..end],
+                _ => {
+                    run_start = offset;
+                    runs.push((&text[offset..end], chosen));
+                }
+            }
+        }
+        runs
+    }
+
+    /// Forget loaded fonts and font lookups so they are resolved again on
+    /// next use. Platforms without font caches needn't implement this.
+    fn clear_font_cache(&mut self) {}
+}
+
+/// Whether `font` is a system family that only draws as a stand-in: not
+/// installed and with no bundled font registered for it, so
+/// [`resolve_font`](super::resolve_font) falls through to the engine default.
+/// It has no glyphs of its own, so fallback chains move past it.
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "linux"))]
+fn is_missing_family(font: &super::FontDescriptor) -> bool {
+    let super::FontSource::System(name) = &font.source else { return false };
+    if name.is_empty() || name == super::DEFAULT_FONT_FAMILY {
+        return false;
+    }
+    matches!(super::resolve_font(font).origin, super::FontOrigin::Default | super::FontOrigin::Tofu)
 }
 
 /// Platform-specific glyph rasterizer
@@ -556,12 +730,12 @@ mod tests {
         // Pack first glyph
         let pos1 = packer.pack(32, 32);
         assert!(pos1.is_some());
-        assert_eq!(pos1.unwrap(), (1, 1)); // With 1px padding
+        assert_eq!(pos1.unwrap(), (1, 1, 0)); // With 1px padding
 
         // Pack second glyph (should go on same shelf)
         let pos2 = packer.pack(32, 32);
         assert!(pos2.is_some());
-        assert_eq!(pos2.unwrap(), (35, 1)); // 1 + 32 + 1 + 1
+        assert_eq!(pos2.unwrap(), (35, 1, 0)); // 1 + 32 + 1 + 1
     }
 
     #[test]
@@ -575,7 +749,7 @@ mod tests {
         // Pack second glyph (won't fit on same shelf, needs new shelf)
         let pos2 = packer.pack(32, 32);
         assert!(pos2.is_some());
-        assert_eq!(pos2.unwrap(), (1, 35)); // New shelf at y=35 (34 padded height + 1 padding)
+        assert_eq!(pos2.unwrap(), (1, 35, 1)); // New shelf at y=35 (34 padded height + 1 padding)
     }
 
     #[test]
@@ -610,4 +784,108 @@ mod tests {
         let cached = atlas.get(&key);
         assert!(cached.is_some());
     }
+
+    fn square_bitmap(size: u32) -> GlyphBitmap {
+        GlyphBitmap {
+            data: vec![255u8; (size * size * 4) as usize],
+            width: size,
+            height: size,
+            bearing_x: 0.0,
+            bearing_y: size as f32,
+            advance: size as f32,
+        }
+    }
+
+    #[test]
+    fn test_full_atlas_evicts_least_recently_used_shelf() {
+        // Two shelves of two 30px glyphs (32px padded) fill a 64x64 atlas
+        let mut atlas = GlyphAtlas::new(64, 64);
+        let key = |ch: char| GlyphKey::new(1, ch as u32, 16.0);
+        atlas.begin_frame();
+        for ch in ['a', 'b', 'c', 'd'] {
+            assert!(atlas.insert(key(ch), square_bitmap(30)).is_some());
+        }
+
+        // Next frame only 'a' is drawn again, so the c/d shelf is the oldest
+        atlas.begin_frame();
+        assert!(atlas.get(&key('a')).is_some());
+        atlas.begin_frame();
+        atlas.get(&key('a'));
+        let e = atlas.insert(key('e'), square_bitmap(30)).unwrap();
+        assert_eq!((e.x, e.y), (1, 33));
+        assert_eq!(atlas.metrics().evictions, 2);
+        assert!(atlas.get(&key('c')).is_none() && atlas.get(&key('d')).is_none());
+        assert!(atlas.get(&key('b')).is_some());
+        // The evicted rows were cleared before 'e' was copied in
+        assert_eq!(atlas.texture_data()[(40 * 64 + 40) * 4 + 3], 0);
+
+        // Glyphs drawn in the current frame are never evicted
+        atlas.begin_frame();
+        for ch in ['a', 'b', 'e'] {
+            atlas.get(&key(ch));
+        }
+        assert!(atlas.insert(key('f'), square_bitmap(30)).is_some());
+        assert!(atlas.insert(key('g'), square_bitmap(30)).is_none());
+        assert_eq!(atlas.glyph_count(), 4);
+    }
+
+    #[test]
+    fn test_second_frame_reuses_cached_glyphs() {
+        let mut atlas = GlyphAtlas::new(256, 256);
+        let draw_frame = |atlas: &mut GlyphAtlas| {
+            atlas.begin_frame();
+            for ch in "hello, hello world".chars() {
+                let key = GlyphKey::with_subpixel(7, ch as u32, 14.0, 0.5);
+                if atlas.get(&key).is_none() {
+                    atlas.insert(key, square_bitmap(8)).unwrap();
+                }
+            }
+        };
+
+        draw_frame(&mut atlas);
+        let first = atlas.metrics().glyphs_rasterized;
+        assert_eq!(first, 9); // distinct chars only
+        atlas.mark_clean();
+
+        draw_frame(&mut atlas);
+        assert_eq!(atlas.metrics().glyphs_rasterized, first);
+        assert!(!atlas.is_dirty(), "nothing new to upload");
+    }
+
+    /// Latin-only primary font with a CJK fallback
+    struct CoverageRasterizer;
+
+    impl GlyphRasterizer for CoverageRasterizer {
+        fn rasterize_glyph(&mut self, _character: char, _font: &crate::text::FontDescriptor) -> Option<GlyphBitmap> {
+            None
+        }
+
+        fn has_glyph(&mut self, character: char, font: &crate::text::FontDescriptor) -> bool {
+            match &font.source {
+                crate::text::FontSource::System(name) if name == "Latin" => character.is_ascii(),
+                crate::text::FontSource::System(name) if name == "CJK" => !character.is_ascii(),
+                _ => false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_for_grapheme_walks_fallback_chain() {
+        use crate::text::{FontDescriptor, FontSource, FontStyle};
+
+        let mut rasterizer = CoverageRasterizer;
+        let font = FontDescriptor::system_with_fallback(&["Latin", "CJK"], 700, FontStyle::Italic, 16.0);
+
+        assert_eq!(rasterizer.font_for_grapheme("a", &font).source, FontSource::System("Latin".to_string()));
+        let fallback = rasterizer.font_for_grapheme("漢", &font);
+        assert_eq!(fallback.source, FontSource::System("CJK".to_string()));
+        assert_eq!((fallback.weight, fallback.style), (700, FontStyle::Italic));
+
+        // No font covers it: the primary draws tofu
+        let none = FontDescriptor::system_with_fallback(&["Latin", "Other"], 400, FontStyle::Normal, 16.0);
+        assert_eq!(rasterizer.font_for_grapheme("漢", &none).source, FontSource::System("Latin".to_string()));
+
+        let runs: Vec<&str> = rasterizer.fallback_runs("ab漢字c", &font).into_iter().map(|(run, _)| run).collect();
+        assert_eq!(runs, vec!["ab", "漢字", "c"]);
+    }
 }
@@ -6,7 +6,7 @@
 #![cfg(target_os = "android")]
 
 use super::{GlyphBitmap, GlyphRasterizer};
-use crate::text::{FontDescriptor, FontSource, FontStyle};
+use crate::text::{FontDescriptor, FontOrigin, FontSource, FontStyle};
 use jni::objects::{GlobalRef, JObject, JValue};
 use jni::JNIEnv;
 use std::collections::HashMap;
@@ -52,6 +52,7 @@ pub struct AndroidGlyphRasterizer {
     /// Cache of created Paint objects (font name + style -> GlobalRef<Paint>)
     paint_cache: HashMap<String, GlobalRef>,
     /// Cache of loaded Typeface objects for bundled fonts (path -> GlobalRef<Typeface>)
+    /// and memory fonts ("memory:<data hash>" -> GlobalRef<Typeface>)
     typeface_cache: HashMap<String, GlobalRef>,
     /// Global reference to a reusable Bitmap for rasterization
     /// We create this lazily and resize as needed
@@ -234,11 +235,30 @@ impl AndroidGlyphRasterizer {
         let typeface_class = self.typeface_class.as_ref()?;
         let typeface_jclass = unsafe { jni::objects::JClass::from_raw(typeface_class.as_raw()) };
 
+        // The font manager can't tell an installed family from one
+        // Typeface.create substitutes on Android, so of the resolution order
+        // only a bundled stand-in changes what a system family draws with
+        if let FontSource::System(_) = &font.source {
+            let resolved = crate::text::resolve_font(font);
+            if resolved.origin == FontOrigin::Bundled {
+                return self.create_typeface(env, &resolved.descriptor);
+            }
+        }
+
+        if let FontSource::Memory { data_hash, .. } = &font.source {
+            if let Some(typeface) = self.create_typeface_from_memory(env, *data_hash) {
+                return Some(typeface);
+            }
+        }
+
         match &font.source {
             FontSource::Bundled(path) => {
                 // For bundled fonts, use Typeface.createFromFile()
                 self.create_typeface_from_file(env, path)
             }
+            FontSource::SystemWithFallback(_) => self.create_typeface(env, &font.primary()),
+            // Memory fonts whose bytes are gone (or that need Android 10) try
+            // an installed family of that name
             FontSource::System(name) | FontSource::Memory { name, .. } => {
                 // For system fonts, use Typeface.create(family, style)
                 let family_name = name.as_str();
@@ -303,6 +323,77 @@ impl AndroidGlyphRasterizer {
         self.create_fallback_typeface(env)
     }
 
+    /// Create a Typeface from bytes registered with the shared font manager.
+    /// Needs Android 10 (API 29), where fonts can be built from a buffer.
+    fn create_typeface_from_memory<'a>(&mut self, env: &mut JNIEnv<'a>, data_hash: u64) -> Option<JObject<'a>> {
+        let key = format!("memory:{:x}", data_hash);
+
+        // Copy the bytes into a Java array while the manager is locked
+        let j_bytes = {
+            let manager = crate::text::font_manager::shared_font_manager()
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let Some(bytes) = manager.font_data(data_hash) else {
+                // Unregistered since it was loaded
+                self.typeface_cache.remove(&key);
+                return None;
+            };
+            if let Some(cached) = self.typeface_cache.get(&key) {
+                return env.new_local_ref(cached.as_obj()).ok();
+            }
+            env.byte_array_from_slice(bytes).ok()?
+        };
+        let len = env.get_array_length(&j_bytes).ok()?;
+
+        let build = |env: &mut JNIEnv<'a>| -> jni::errors::Result<JObject<'a>> {
+            // Font.Builder reads a direct buffer
+            let buffer = env
+                .call_static_method("java/nio/ByteBuffer", "allocateDirect", "(I)Ljava/nio/ByteBuffer;", &[JValue::Int(len)])?
+                .l()?;
+            env.call_method(&buffer, "put", "([B)Ljava/nio/ByteBuffer;", &[JValue::Object(&j_bytes)])?;
+            env.call_method(&buffer, "rewind", "()Ljava/nio/Buffer;", &[])?;
+
+            let font_builder = env.new_object(
+                "android/graphics/fonts/Font$Builder",
+                "(Ljava/nio/ByteBuffer;)V",
+                &[JValue::Object(&buffer)],
+            )?;
+            let font = env.call_method(&font_builder, "build", "()Landroid/graphics/fonts/Font;", &[])?.l()?;
+
+            let family_builder = env.new_object(
+                "android/graphics/fonts/FontFamily$Builder",
+                "(Landroid/graphics/fonts/Font;)V",
+                &[JValue::Object(&font)],
+            )?;
+            let family = env
+                .call_method(&family_builder, "build", "()Landroid/graphics/fonts/FontFamily;", &[])?
+                .l()?;
+
+            let typeface_builder = env.new_object(
+                "android/graphics/Typeface$CustomFallbackBuilder",
+                "(Landroid/graphics/fonts/FontFamily;)V",
+                &[JValue::Object(&family)],
+            )?;
+            env.call_method(&typeface_builder, "build", "()Landroid/graphics/Typeface;", &[])?.l()
+        };
+
+        match build(env) {
+            Ok(typeface) if !typeface.is_null() => {
+                if let Ok(global) = env.new_global_ref(&typeface) {
+                    self.typeface_cache.insert(key, global);
+                }
+                Some(typeface)
+            }
+            result => {
+                if env.exception_check().unwrap_or(false) {
+                    let _ = env.exception_clear();
+                }
+                log::error!("Failed to load memory font {:#x}: {:?}", data_hash, result.err());
+                None
+            }
+        }
+    }
+
     /// Try to create a Typeface from Android assets folder
     fn create_typeface_from_assets<'a>(&self, env: &mut JNIEnv<'a>, path: &str) -> Option<JObject<'a>> {
         let typeface_class = self.typeface_class.as_ref()?;
@@ -844,6 +935,27 @@ impl GlyphRasterizer for AndroidGlyphRasterizer {
             advance,
         })
     }
+
+    fn has_glyph(&mut self, character: char, font: &FontDescriptor) -> bool {
+        let Some(vm) = get_java_vm() else { return false };
+        let Ok(mut env) = vm.attach_current_thread() else { return false };
+        let Some(paint) = self.create_paint(&mut env, font) else { return false };
+        let Ok(j_text) = env.new_string(character.to_string()) else { return false };
+
+        let found = env
+            .call_method(&paint, "hasGlyph", "(Ljava/lang/String;)Z", &[JValue::Object(&j_text)])
+            .and_then(|value| value.z())
+            .unwrap_or(false);
+        if env.exception_check().unwrap_or(false) {
+            let _ = env.exception_clear();
+        }
+        found
+    }
+
+    fn clear_font_cache(&mut self) {
+        self.paint_cache.clear();
+        self.typeface_cache.clear();
+    }
 }
 
 impl AndroidGlyphRasterizer {
@@ -980,6 +1092,19 @@ pub fn measure_text_width(text: &str, font: &FontDescriptor) -> Option<f32> {
     };
 
     let mut rasterizer = AndroidGlyphRasterizer::new();
+
+    // Each run with the font from the chain that draws it
+    if let FontSource::SystemWithFallback(_) = &font.source {
+        return rasterizer
+            .fallback_runs(text, font)
+            .iter()
+            .map(|(run, run_font)| {
+                let paint = rasterizer.create_paint(&mut env, run_font)?;
+                rasterizer.measure_text_advance(&mut env, &paint, run)
+            })
+            .sum();
+    }
+
     let paint = match rasterizer.create_paint(&mut env, font) {
         Some(p) => p,
         None => {
@@ -4,7 +4,7 @@
 //! Supports font weights and styles via fontconfig font matching.
 
 use super::{GlyphBitmap, GlyphRasterizer};
-use crate::text::{FontDescriptor, FontSource, FontStyle};
+use crate::text::{FontDescriptor, FontOrigin, FontSource, FontStyle};
 use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_SLANT, FC_WEIGHT};
 use fontconfig::{
     FC_SLANT_ITALIC, FC_SLANT_ROMAN, FC_WEIGHT_BLACK, FC_WEIGHT_BOLD, FC_WEIGHT_EXTRALIGHT,
@@ -42,10 +42,19 @@ fn is_emoji(c: char) -> bool {
     )
 }
 
+/// Where a face is loaded from
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum FaceSource {
+    /// Font file path
+    File(String),
+    /// Bytes registered with the shared font manager (data hash)
+    Memory(u64),
+}
+
 /// Cache key for loaded fonts
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct FontCacheKey {
-    path: String,
+    source: FaceSource,
     size_px: u32,
 }
 
@@ -61,10 +70,10 @@ struct FontPathCacheKey {
 pub struct LinuxGlyphRasterizer {
     /// FreeType library handle
     library: Library,
-    /// Cache of loaded faces (path+size -> Face)
+    /// Cache of loaded faces (source+size -> Face)
     face_cache: HashMap<FontCacheKey, freetype::Face>,
-    /// Cache of font paths (family+weight+italic -> path) to avoid fontconfig lookups
-    font_path_cache: HashMap<FontPathCacheKey, Option<String>>,
+    /// Cache of face sources (family+weight+italic -> source) to avoid fontconfig lookups
+    font_path_cache: HashMap<FontPathCacheKey, Option<FaceSource>>,
     /// Cache of resolved bundled font paths
     bundled_path_cache: HashMap<String, Option<String>>,
 }
@@ -130,24 +139,47 @@ impl LinuxGlyphRasterizer {
         matched.filename().map(PathBuf::from)
     }
 
-    /// Load a FreeType face from a file path at the given size
-    fn load_face(&mut self, path: &str, size_px: f32) -> Option<&freetype::Face> {
+    /// Load a FreeType face from a file path or registered bytes at the given size
+    fn load_face(&mut self, source: &FaceSource, size_px: f32) -> Option<&freetype::Face> {
         use std::collections::hash_map::Entry;
 
         let cache_key = FontCacheKey {
-            path: path.to_string(),
+            source: source.clone(),
             size_px: size_px.round() as u32,
         };
 
+        // Bytes of a memory font, copied only when its face is created
+        let mut data = None;
+        if let FaceSource::Memory(data_hash) = source {
+            let manager = crate::text::font_manager::shared_font_manager()
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let Some(bytes) = manager.font_data(*data_hash) else {
+                // Unregistered since it was loaded; families that resolved
+                // to it are resolved again
+                drop(manager);
+                self.face_cache.retain(|key, _| key.source != *source);
+                self.font_path_cache.retain(|_, cached| cached.as_ref() != Some(source));
+                return None;
+            };
+            if !self.face_cache.contains_key(&cache_key) {
+                data = Some(bytes.to_vec());
+            }
+        }
+
         // Use entry API for single lookup
         match self.face_cache.entry(cache_key) {
             Entry::Occupied(entry) => Some(entry.into_mut()),
             Entry::Vacant(entry) => {
                 // Load the face
-                let face = match self.library.new_face(path, 0) {
+                let loaded = match source {
+                    FaceSource::File(path) => self.library.new_face(path, 0),
+                    FaceSource::Memory(_) => self.library.new_memory_face(data.unwrap_or_default(), 0),
+                };
+                let face = match loaded {
                     Ok(f) => f,
                     Err(e) => {
-                        eprintln!("Failed to load font '{}': {:?}", path, e);
+                        eprintln!("Failed to load font {:?}: {:?}", source, e);
                         return None;
                     }
                 };
@@ -164,14 +196,15 @@ impl LinuxGlyphRasterizer {
         }
     }
 
-    /// Get the font file path from a FontDescriptor (with caching)
-    fn get_font_path(&mut self, font: &FontDescriptor) -> Option<String> {
+    /// Get the face source from a FontDescriptor (with caching)
+    fn get_face_source(&mut self, font: &FontDescriptor) -> Option<FaceSource> {
         match &font.source {
             FontSource::System(name) => {
+                let italic = font.style == FontStyle::Italic;
                 let cache_key = FontPathCacheKey {
                     family: name.clone(),
                     weight: font.weight,
-                    italic: font.style == FontStyle::Italic,
+                    italic,
                 };
 
                 // Check cache first
@@ -179,9 +212,17 @@ impl LinuxGlyphRasterizer {
                     return cached.clone();
                 }
 
-                // Not cached - do fontconfig lookup
-                let result = Self::find_font_path(name, font.weight, font.style == FontStyle::Italic)
-                    .map(|p| p.to_string_lossy().to_string());
+                // Not cached - resolve the family first, since fontconfig
+                // substitutes some font for any name it doesn't know
+                let resolved = crate::text::resolve_font(font);
+                let result = match resolved.origin {
+                    FontOrigin::System | FontOrigin::Default => {
+                        Self::find_font_path(&resolved.family, font.weight, italic)
+                            .map(|p| FaceSource::File(p.to_string_lossy().to_string()))
+                    }
+                    FontOrigin::Bundled => self.get_face_source(&resolved.descriptor),
+                    FontOrigin::Tofu => None,
+                };
 
                 // Cache the result (even if None, to avoid repeated failed lookups)
                 self.font_path_cache.insert(cache_key, result.clone());
@@ -190,7 +231,7 @@ impl LinuxGlyphRasterizer {
             FontSource::Bundled(path) => {
                 // Check cache first
                 if let Some(cached) = self.bundled_path_cache.get(path) {
-                    return cached.clone();
+                    return cached.clone().map(FaceSource::File);
                 }
 
                 // Not cached - resolve the path
@@ -198,13 +239,10 @@ impl LinuxGlyphRasterizer {
 
                 // Cache the result
                 self.bundled_path_cache.insert(path.clone(), result.clone());
-                result
-            }
-            FontSource::Memory { .. } => {
-                // Memory fonts not yet supported in Linux rasterizer
-                eprintln!("Memory fonts not yet supported on Linux");
-                None
+                result.map(FaceSource::File)
             }
+            FontSource::Memory { data_hash, .. } => Some(FaceSource::Memory(*data_hash)),
+            FontSource::SystemWithFallback(_) => self.get_face_source(&font.primary()),
         }
     }
 
@@ -243,18 +281,25 @@ impl LinuxGlyphRasterizer {
             return 0.0;
         }
 
-        let font_path = match self.get_font_path(font) {
-            Some(p) => p,
+        // Each run with the font from the chain that draws it
+        if let FontSource::SystemWithFallback(_) = &font.source {
+            return self.fallback_runs(text, font).iter().map(|(run, run_font)| self.measure_string(run, run_font)).sum();
+        }
+
+        let face_source = match self.get_face_source(font) {
+            Some(source) => source,
             None => return 0.0,
         };
 
-        let face = match self.load_face(&font_path, font.size) {
+        let face = match self.load_face(&face_source, font.size) {
             Some(f) => f,
             None => return 0.0,
         };
 
+        // One advance per grapheme cluster, matching how clusters are rendered
         let mut width = 0.0;
-        for ch in text.chars() {
+        for (_, grapheme) in crate::text::grapheme::graphemes(text) {
+            let Some(ch) = crate::text::grapheme::base_char(grapheme) else { continue };
             if let Some(glyph_index) = face.get_char_index(ch as usize) {
                 if face.load_glyph(glyph_index, LoadFlag::DEFAULT).is_ok() {
                     // Advance is in 26.6 fixed-point format
@@ -268,12 +313,12 @@ impl LinuxGlyphRasterizer {
     /// Get font metrics (ascent, descent) for a font descriptor
     /// Returns (ascent, descent) in pixels, or (0.0, 0.0) on error
     pub fn get_font_metrics(&mut self, font: &FontDescriptor) -> (f32, f32) {
-        let font_path = match self.get_font_path(font) {
-            Some(p) => p,
+        let face_source = match self.get_face_source(font) {
+            Some(source) => source,
             None => return (0.0, 0.0),
         };
 
-        let face = match self.load_face(&font_path, font.size) {
+        let face = match self.load_face(&face_source, font.size) {
             Some(f) => f,
             None => return (0.0, 0.0),
         };
@@ -309,8 +354,8 @@ impl GlyphRasterizer for LinuxGlyphRasterizer {
         character: char,
         font: &FontDescriptor,
     ) -> Option<GlyphBitmap> {
-        let font_path = self.get_font_path(font)?;
-        let face = self.load_face(&font_path, font.size)?;
+        let face_source = self.get_face_source(font)?;
+        let face = self.load_face(&face_source, font.size)?;
 
         // Handle whitespace characters - no visual glyph needed
         if character.is_whitespace() {
@@ -395,6 +440,21 @@ impl GlyphRasterizer for LinuxGlyphRasterizer {
             advance,
         })
     }
+
+    fn has_glyph(&mut self, character: char, font: &FontDescriptor) -> bool {
+        if super::is_missing_family(font) {
+            return false;
+        }
+        let Some(face_source) = self.get_face_source(font) else { return false };
+        self.load_face(&face_source, font.size)
+            .is_some_and(|face| face.get_char_index(character as usize).is_some())
+    }
+
+    fn clear_font_cache(&mut self) {
+        self.face_cache.clear();
+        self.font_path_cache.clear();
+        self.bundled_path_cache.clear();
+    }
 }
 
 #[cfg(test)]
@@ -427,4 +487,36 @@ mod tests {
             assert!(bitmap.height > 0, "Glyph should have height");
         }
     }
+
+    #[test]
+    fn test_rasterize_glyph_from_memory_font() {
+        // Any installed font file will do as registered bytes
+        let Some(path) = LinuxGlyphRasterizer::find_font_path("sans-serif", 400, false) else { return };
+        let Ok(data) = std::fs::read(&path) else { return };
+        let manager = crate::text::font_manager::shared_font_manager();
+        let data_hash = manager.lock().unwrap().register_font_data("", data);
+
+        let mut rasterizer = LinuxGlyphRasterizer::new();
+        let font = FontDescriptor {
+            source: FontSource::Memory { name: "Memory Test Font".to_string(), data_hash },
+            weight: 400,
+            style: FontStyle::Normal,
+            size: 16.0,
+        };
+        let bitmap = rasterizer.rasterize_glyph('A', &font).expect("registered bytes should rasterize");
+        assert!(bitmap.width > 0, "Glyph should have width");
+        assert!(bitmap.height > 0, "Glyph should have height");
+
+        // The face is dropped with the bytes
+        assert!(manager.lock().unwrap().unregister_font_data(data_hash));
+        assert!(rasterizer.rasterize_glyph('A', &font).is_none());
+    }
+
+    #[test]
+    fn test_missing_family_has_no_glyphs() {
+        // fontconfig would substitute an installed font for this name
+        let mut rasterizer = LinuxGlyphRasterizer::new();
+        let font = FontDescriptor::system("No Such Font Family", 400, FontStyle::Normal, 16.0);
+        assert!(!rasterizer.has_glyph('A', &font));
+    }
 }
@@ -4,9 +4,10 @@
 //! Supports font weights and styles via Core Text font creation APIs.
 
 use super::{GlyphBitmap, GlyphRasterizer};
-use crate::text::{FontDescriptor, FontSource, FontStyle};
+use crate::text::{FontDescriptor, FontOrigin, FontSource, FontStyle};
 use core_foundation::attributed_string::CFMutableAttributedString;
 use core_foundation::base::{CFRange, TCFType};
+use core_foundation::data::{CFData, CFDataRef};
 use core_foundation::string::CFString;
 use core_foundation::url::CFURL;
 use core_graphics::base::CGFloat;
@@ -83,12 +84,20 @@ extern "C" {
         matrix: *const std::ffi::c_void,
         attributes: *const std::ffi::c_void,
     ) -> CTFontRef;
+
+    fn CTFontGetGlyphsForCharacters(
+        font: CTFontRef,
+        characters: *const u16,
+        glyphs: *mut u16,
+        count: isize,
+    ) -> bool;
 }
 
 #[link(name = "CoreGraphics", kind = "framework")]
 extern "C" {
     fn CGFontCreateWithDataProvider(provider: CGDataProviderRef) -> CGFontRef;
     fn CGDataProviderCreateWithURL(url: CFURLRef) -> CGDataProviderRef;
+    fn CGDataProviderCreateWithCFData(data: CFDataRef) -> CGDataProviderRef;
     fn CGDataProviderRelease(provider: CGDataProviderRef);
     fn CGFontRelease(font: CGFontRef);
 }
@@ -103,7 +112,8 @@ unsafe impl Sync for SendableCGFont {}
 
 /// macOS glyph rasterizer using Core Graphics
 pub struct MacOSGlyphRasterizer {
-    /// Cache of loaded CGFonts from file paths (path -> CGFontRef)
+    /// Cache of loaded CGFonts from file paths (path -> CGFontRef), and from
+    /// memory fonts ("memory:<data hash>" -> CGFontRef)
     /// We keep raw pointers since CGFont doesn't have a safe wrapper
     loaded_fonts: HashMap<String, SendableCGFont>,
 }
@@ -185,6 +195,46 @@ impl MacOSGlyphRasterizer {
         }
     }
 
+    /// Load a CGFont from bytes registered with the shared font manager
+    fn load_font_from_memory(&mut self, data_hash: u64) -> Option<CGFontRef> {
+        let key = format!("memory:{:x}", data_hash);
+        let manager = crate::text::font_manager::shared_font_manager()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(bytes) = manager.font_data(data_hash) else {
+            // Unregistered since it was loaded
+            if let Some(font) = self.loaded_fonts.remove(&key) {
+                unsafe { CGFontRelease(font.0) };
+            }
+            return None;
+        };
+        if let Some(font) = self.loaded_fonts.get(&key) {
+            return Some(font.0);
+        }
+        let data = CFData::from_buffer(bytes);
+        drop(manager);
+
+        unsafe {
+            // The provider retains the CFData, which owns a copy of the bytes
+            let provider = CGDataProviderCreateWithCFData(data.as_concrete_TypeRef());
+            if provider.is_null() {
+                eprintln!("Failed to create data provider for memory font {:#x}", data_hash);
+                return None;
+            }
+
+            let cg_font = CGFontCreateWithDataProvider(provider);
+            CGDataProviderRelease(provider);
+
+            if cg_font.is_null() {
+                eprintln!("Failed to create CGFont from memory font {:#x}", data_hash);
+                return None;
+            }
+
+            self.loaded_fonts.insert(key, SendableCGFont(cg_font));
+            Some(cg_font)
+        }
+    }
+
     /// Get the font name for a given weight (for system fonts)
     /// San Francisco (system font) has named variants for different weights
     fn get_system_font_name_for_weight(weight: u16) -> &'static str {
@@ -206,10 +256,16 @@ impl MacOSGlyphRasterizer {
     fn create_font(&mut self, font: &FontDescriptor) -> Option<CTFont> {
         let size = font.size as f64;
 
-        // Handle bundled fonts specially - load from file
-        if let FontSource::Bundled(path) = &font.source {
-            let cg_font = self.load_font_from_file(path)?;
-
+        // Handle bundled fonts specially - load from file, or from the bytes
+        // registered for a memory font (by name if they are gone)
+        let graphics_font = match &font.source {
+            FontSource::Bundled(path) => Some((self.load_font_from_file(path)?, path.as_str())),
+            FontSource::Memory { name, data_hash } => {
+                self.load_font_from_memory(*data_hash).map(|cg_font| (cg_font, name.as_str()))
+            }
+            _ => None,
+        };
+        if let Some((cg_font, path)) = graphics_font {
             unsafe {
                 // Create CTFont from CGFont
                 let ct_font_ref = CTFontCreateWithGraphicsFont(
@@ -232,16 +288,24 @@ impl MacOSGlyphRasterizer {
 
         // Get font name from source (system or memory fonts)
         let font_name = match &font.source {
+            // CTFontCreateWithName substitutes for unknown names, so take the
+            // face the resolution order picks
             FontSource::System(name) => {
-                if name == "system" || name.is_empty() {
+                let resolved = crate::text::resolve_font(font);
+                match resolved.origin {
+                    // Installed; the name may be a PostScript name for one face
+                    FontOrigin::System => name.clone(),
+                    FontOrigin::Bundled => return self.create_font(&resolved.descriptor),
                     // Use weight-specific system font variant
-                    Self::get_system_font_name_for_weight(font.weight).to_string()
-                } else {
-                    name.clone()
+                    FontOrigin::Default | FontOrigin::Tofu => {
+                        Self::get_system_font_name_for_weight(font.weight).to_string()
+                    }
                 }
             }
             FontSource::Bundled(_) => unreachable!(), // Handled above
+            // Bytes no longer registered; try an installed family of that name
             FontSource::Memory { name, .. } => name.clone(),
+            FontSource::SystemWithFallback(_) => return self.create_font(&font.primary()),
         };
 
         unsafe {
@@ -358,6 +422,11 @@ impl MacOSGlyphRasterizer {
             return 0.0;
         }
 
+        // Each run with the font from the chain that draws it
+        if let FontSource::SystemWithFallback(_) = &font.source {
+            return self.fallback_runs(text, font).iter().map(|(run, run_font)| self.measure_string(run, run_font)).sum();
+        }
+
         // Create font with proper weight and style
         let ct_font = match self.create_font(font) {
             Some(f) => f,
@@ -539,6 +608,28 @@ impl GlyphRasterizer for MacOSGlyphRasterizer {
             advance: bounds.width as f32,
         })
     }
+
+    fn has_glyph(&mut self, character: char, font: &FontDescriptor) -> bool {
+        if super::is_missing_family(font) {
+            return false;
+        }
+        let Some(ct_font) = self.create_font(font) else { return false };
+        let mut utf16 = [0u16; 2];
+        let units = character.encode_utf16(&mut utf16);
+        let mut glyphs = [0u16; 2];
+        unsafe {
+            CTFontGetGlyphsForCharacters(
+                ct_font.as_concrete_TypeRef() as CTFontRef,
+                units.as_ptr(),
+                glyphs.as_mut_ptr(),
+                units.len() as isize,
+            )
+        }
+    }
+
+    fn clear_font_cache(&mut self) {
+        self.loaded_fonts.clear();
+    }
 }
 
 /// Flip a bitmap vertically (Core Graphics uses bottom-left origin)
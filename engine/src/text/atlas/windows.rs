@@ -2,16 +2,17 @@
 //!
 //! Renders glyphs to RGBA bitmaps using DirectWrite GDI interop.
 //! Supports font weights and styles via DirectWrite font creation APIs.
-//! Bundled fonts are loaded using AddFontResourceExW for private process access.
+//! Bundled fonts are loaded using AddFontResourceExW for private process access,
+//! and memory fonts using AddFontMemResourceEx and an in-memory DirectWrite loader.
 
 use super::{GlyphBitmap, GlyphRasterizer};
-use crate::text::{FontDescriptor, FontSource, FontStyle};
+use crate::text::{FontDescriptor, FontOrigin, FontSource, FontStyle};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::OnceLock;
 
-use windows::core::PCWSTR;
-use windows::Win32::Foundation::{BOOL, COLORREF, RECT};
+use windows::core::{Interface, IUnknown, PCWSTR};
+use windows::Win32::Foundation::{BOOL, COLORREF, HANDLE, RECT};
 use windows::Win32::Graphics::DirectWrite::*;
 use windows::Win32::Graphics::Gdi::*;
 use windows::Win32::System::Com::*;
@@ -97,14 +98,26 @@ struct BundledFontInfo {
     loaded: bool,
 }
 
+/// Key of a memory font in the bundled and loaded font caches
+fn memory_font_key(data_hash: u64) -> String {
+    format!("memory:{:x}", data_hash)
+}
+
 /// Windows glyph rasterizer using DirectWrite for metrics and GDI for rendering
 pub struct WindowsGlyphRasterizer {
     /// DirectWrite factory (for text metrics)
     dwrite_factory: IDWriteFactory,
-    /// Cache of loaded font files from file paths (for DirectWrite font faces)
+    /// Cache of loaded font files from file paths, and from memory fonts
+    /// ("memory:<data hash>"), for DirectWrite font faces
     loaded_fonts: HashMap<String, LoadedFontFile>,
-    /// Cache of bundled font paths to their family names
+    /// Cache of bundled font paths (and memory font keys) to their family names
     bundled_fonts: HashMap<String, BundledFontInfo>,
+    /// Loader for DirectWrite font files over registered bytes, registered
+    /// with the factory on first use
+    memory_font_loader: Option<IDWriteInMemoryFontFileLoader>,
+    /// AddFontMemResourceEx handles of memory fonts added to GDI (data hash
+    /// -> handle), kept as integers since HANDLE isn't Send
+    memory_font_handles: HashMap<u64, isize>,
 }
 
 // External function for adding fonts
@@ -126,6 +139,8 @@ impl WindowsGlyphRasterizer {
                 dwrite_factory,
                 loaded_fonts: HashMap::new(),
                 bundled_fonts: HashMap::new(),
+                memory_font_loader: None,
+                memory_font_handles: HashMap::new(),
             }
         }
     }
@@ -194,6 +209,75 @@ impl WindowsGlyphRasterizer {
         }
     }
 
+    /// Load font bytes registered with the shared font manager and return
+    /// their family name. GDI gets a private copy of the bytes, and
+    /// DirectWrite an in-memory font file for metrics.
+    fn load_memory_font(&mut self, data_hash: u64) -> Option<String> {
+        let key = memory_font_key(data_hash);
+        let manager = crate::text::font_manager::shared_font_manager()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(bytes) = manager.font_data(data_hash) else {
+            // Unregistered since it was loaded
+            drop(manager);
+            self.remove_memory_font(data_hash);
+            return None;
+        };
+
+        // Check cache first
+        if let Some(info) = self.bundled_fonts.get(&key) {
+            return info.loaded.then(|| info.font_name.clone());
+        }
+        let data = bytes.to_vec();
+        drop(manager);
+
+        unsafe {
+            // Add a private copy of the bytes for GDI
+            let mut num_fonts = 0u32;
+            let handle = AddFontMemResourceEx(
+                data.as_ptr() as *const std::ffi::c_void,
+                data.len() as u32,
+                None,
+                &mut num_fonts,
+            );
+            if handle.is_invalid() {
+                eprintln!("Failed to add memory font {:#x}", data_hash);
+                self.bundled_fonts.insert(key, BundledFontInfo {
+                    font_name: String::new(),
+                    resolved_path: String::new(),
+                    loaded: false,
+                });
+                return None;
+            }
+            self.memory_font_handles.insert(data_hash, handle.0 as isize);
+
+            let font_name = self.parse_font_family_name(&data).unwrap_or_else(|| "Segoe UI".to_string());
+
+            // Also load the font into DirectWrite for proper metrics
+            self.load_font_from_memory(&key, &data);
+
+            self.bundled_fonts.insert(key.clone(), BundledFontInfo {
+                font_name: font_name.clone(),
+                resolved_path: key,
+                loaded: true,
+            });
+
+            Some(font_name)
+        }
+    }
+
+    /// Forget a memory font and remove it from GDI
+    fn remove_memory_font(&mut self, data_hash: u64) {
+        let key = memory_font_key(data_hash);
+        self.loaded_fonts.remove(&key);
+        self.bundled_fonts.remove(&key);
+        if let Some(handle) = self.memory_font_handles.remove(&data_hash) {
+            unsafe {
+                let _ = RemoveFontMemResourceEx(HANDLE(handle as *mut std::ffi::c_void));
+            }
+        }
+    }
+
     /// Verify that GDI can find the font and return the actual name it uses
     fn verify_gdi_font_name(&self, expected_name: &str, font_path: &str) -> String {
         unsafe {
@@ -287,6 +371,22 @@ impl WindowsGlyphRasterizer {
         self.bundled_fonts.get(path).map(|info| info.resolved_path.clone())
     }
 
+    /// Key of the DirectWrite face loaded for a bundled or memory font,
+    /// loading the font first if needed
+    fn loaded_font_key(&mut self, font: &FontDescriptor) -> Option<String> {
+        match &font.source {
+            FontSource::Bundled(path) => {
+                self.load_bundled_font(path)?;
+                self.get_bundled_font_path(path)
+            }
+            FontSource::Memory { data_hash, .. } => {
+                self.load_memory_font(*data_hash)?;
+                Some(memory_font_key(*data_hash))
+            }
+            _ => None,
+        }
+    }
+
     /// Resolve a font path to an absolute path
     fn resolve_font_path(&self, path: &str) -> Option<String> {
         if Path::new(path).is_absolute() && Path::new(path).exists() {
@@ -488,6 +588,46 @@ impl WindowsGlyphRasterizer {
                 }
             };
 
+            self.load_font_file(path, &resolved_path, font_file)
+        }
+    }
+
+    /// Load font bytes as a DirectWrite font face, cached under `key`
+    fn load_font_from_memory(&mut self, key: &str, data: &[u8]) -> Option<&LoadedFontFile> {
+        unsafe {
+            let loader = match &self.memory_font_loader {
+                Some(loader) => loader.clone(),
+                None => {
+                    let factory5: IDWriteFactory5 = self.dwrite_factory.cast().ok()?;
+                    let loader = factory5.CreateInMemoryFontFileLoader().ok()?;
+                    self.dwrite_factory.RegisterFontFileLoader(&loader).ok()?;
+                    self.memory_font_loader = Some(loader.clone());
+                    loader
+                }
+            };
+
+            // Without an owner object the loader keeps its own copy of the bytes
+            let font_file = match loader.CreateInMemoryFontFileReference(
+                &self.dwrite_factory,
+                data.as_ptr() as *const std::ffi::c_void,
+                data.len() as u32,
+                None::<&IUnknown>,
+            ) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("Failed to create in-memory font file for {}: {:?}", key, e);
+                    return None;
+                }
+            };
+
+            self.load_font_file(key, key, font_file)
+        }
+    }
+
+    /// Create a font face from a DirectWrite font file and cache it under
+    /// `key`; `label` names the font in errors
+    fn load_font_file(&mut self, key: &str, label: &str, font_file: IDWriteFontFile) -> Option<&LoadedFontFile> {
+        unsafe {
             // Check if the font is supported
             let mut is_supported = BOOL::default();
             let mut file_type = DWRITE_FONT_FILE_TYPE_UNKNOWN;
@@ -500,12 +640,12 @@ impl WindowsGlyphRasterizer {
                 Some(&mut face_type as *mut _),
                 &mut num_faces as *mut _,
             ).is_err() {
-                eprintln!("Failed to analyze font file: {}", resolved_path);
+                eprintln!("Failed to analyze font file: {}", label);
                 return None;
             }
 
             if !is_supported.as_bool() {
-                eprintln!("Unsupported font file format: {}", resolved_path);
+                eprintln!("Unsupported font file format: {}", label);
                 return None;
             }
 
@@ -519,7 +659,7 @@ impl WindowsGlyphRasterizer {
             ) {
                 Ok(f) => f,
                 Err(e) => {
-                    eprintln!("Failed to create font face for {}: {:?}", resolved_path, e);
+                    eprintln!("Failed to create font face for {}: {:?}", label, e);
                     return None;
                 }
             };
@@ -529,13 +669,30 @@ impl WindowsGlyphRasterizer {
                 font_file,
                 font_face,
             };
-            self.loaded_fonts.insert(path.to_string(), loaded);
-            self.loaded_fonts.get(path)
+            self.loaded_fonts.insert(key.to_string(), loaded);
+            self.loaded_fonts.get(key)
+        }
+    }
+
+    /// The font to draw a system family with: the installed family, else a
+    /// bundled font registered for it, else Segoe UI (the engine default),
+    /// rather than whatever CreateTextFormat and GDI substitute for a name
+    /// they don't know
+    fn resolve_system_font(font: &FontDescriptor) -> FontDescriptor {
+        let FontSource::System(_) = &font.source else { return font.clone() };
+        let resolved = crate::text::resolve_font(font);
+        match resolved.origin {
+            FontOrigin::System | FontOrigin::Default => FontDescriptor {
+                source: FontSource::System(resolved.family),
+                ..font.clone()
+            },
+            FontOrigin::Bundled | FontOrigin::Tofu => resolved.descriptor,
         }
     }
 
     /// Create a text format with the specified font settings
     fn create_text_format(&mut self, font: &FontDescriptor) -> Option<IDWriteTextFormat> {
+        let font = &Self::resolve_system_font(font);
         let size = font.size;
         let weight = map_weight_to_dwrite(font.weight);
         let style = if font.style == FontStyle::Italic {
@@ -557,7 +714,10 @@ impl WindowsGlyphRasterizer {
                 // Load the bundled font and get its family name
                 self.load_bundled_font(path).unwrap_or_else(|| "Segoe UI".to_string())
             }
-            FontSource::Memory { name, .. } => name.clone(),
+            FontSource::Memory { data_hash, .. } => {
+                self.load_memory_font(*data_hash).unwrap_or_else(|| "Segoe UI".to_string())
+            }
+            FontSource::SystemWithFallback(_) => return self.create_text_format(&font.primary()),
         };
 
         unsafe {
@@ -765,27 +925,22 @@ impl WindowsGlyphRasterizer {
     /// Returns (ascent, descent) in pixels. Both values are positive.
     /// Height = ascent + descent.
     pub fn get_font_metrics(&mut self, font: &FontDescriptor) -> (f32, f32) {
-        // Ensure bundled font is loaded first
-        if let FontSource::Bundled(path) = &font.source {
-            let _ = self.load_bundled_font(path);
-        }
+        let font = &Self::resolve_system_font(font);
 
-        // Try to get metrics from DirectWrite for bundled fonts
-        if let FontSource::Bundled(path) = &font.source {
-            if let Some(resolved_path) = self.get_bundled_font_path(path) {
-                if let Some(loaded) = self.loaded_fonts.get(&resolved_path) {
-                    unsafe {
-                        let mut metrics = DWRITE_FONT_METRICS::default();
-                        loaded.font_face.GetMetrics(&mut metrics);
+        // Try to get metrics from DirectWrite for bundled and memory fonts
+        if let Some(key) = self.loaded_font_key(font) {
+            if let Some(loaded) = self.loaded_fonts.get(&key) {
+                unsafe {
+                    let mut metrics = DWRITE_FONT_METRICS::default();
+                    loaded.font_face.GetMetrics(&mut metrics);
 
-                        let design_units_per_em = metrics.designUnitsPerEm as f32;
-                        let scale = font.size / design_units_per_em;
+                    let design_units_per_em = metrics.designUnitsPerEm as f32;
+                    let scale = font.size / design_units_per_em;
 
-                        let ascent = metrics.ascent as f32 * scale;
-                        let descent = metrics.descent as f32 * scale;
+                    let ascent = metrics.ascent as f32 * scale;
+                    let descent = metrics.descent as f32 * scale;
 
-                        return (ascent, descent);
-                    }
+                    return (ascent, descent);
                 }
             }
         }
@@ -830,6 +985,12 @@ impl WindowsGlyphRasterizer {
             return 0.0;
         }
 
+        // Each run with the font from the chain that draws it
+        if let FontSource::SystemWithFallback(_) = &font.source {
+            return self.fallback_runs(text, font).iter().map(|(run, run_font)| self.measure_string(run, run_font)).sum();
+        }
+        let font = &Self::resolve_system_font(font);
+
         // Get the font name to use for GDI measurement (verified during load_bundled_font)
         let font_name = match &font.source {
             FontSource::Bundled(path) => {
@@ -846,7 +1007,10 @@ impl WindowsGlyphRasterizer {
                     name.clone()
                 }
             }
-            FontSource::Memory { name, .. } => name.clone(),
+            FontSource::Memory { data_hash, .. } => {
+                self.load_memory_font(*data_hash).unwrap_or_else(|| "Segoe UI".to_string())
+            }
+            FontSource::SystemWithFallback(_) => unreachable!(), // Handled above
         };
 
         // Use GDI for all fonts to match rendering
@@ -860,21 +1024,23 @@ impl WindowsGlyphRasterizer {
         font: &FontDescriptor,
         _is_emoji: bool,
     ) -> Option<(Vec<u8>, u32, u32, f32, f32, f32)> {
+        if let FontSource::SystemWithFallback(_) = &font.source {
+            return self.render_to_bitmap(text, &font.primary(), _is_emoji);
+        }
+        let font = &Self::resolve_system_font(font);
+
         // CRITICAL: Use measure_string for the advance to ensure consistency
         // between layout measurement and glyph rendering
         let advance = self.measure_string(text, font);
 
         // Get dimensions for the bitmap
         let (width, height, ascent, descent) = match &font.source {
-            FontSource::Bundled(path) => {
+            FontSource::Bundled(_) | FontSource::Memory { .. } => {
                 // Ensure the font is loaded first
-                if self.load_bundled_font(path).is_none() {
-                    return None;
-                }
+                let key = self.loaded_font_key(font)?;
 
                 // Get height metrics from DirectWrite font face
-                let resolved_path = self.get_bundled_font_path(path)?;
-                if let Some(loaded) = self.loaded_fonts.get(&resolved_path) {
+                if let Some(loaded) = self.loaded_fonts.get(&key) {
                     let font_face = &loaded.font_face;
 
                     unsafe {
@@ -984,6 +1150,12 @@ impl WindowsGlyphRasterizer {
                         "Segoe UI".to_string()
                     }
                 }
+                // Loaded by measure_string already
+                FontSource::Memory { data_hash, .. } => self
+                    .bundled_fonts
+                    .get(&memory_font_key(*data_hash))
+                    .filter(|info| info.loaded)
+                    .map_or_else(|| "Segoe UI".to_string(), |info| info.font_name.clone()),
                 _ => "Segoe UI".to_string(),
             };
 
@@ -1129,6 +1301,56 @@ impl GlyphRasterizer for WindowsGlyphRasterizer {
             advance,
         })
     }
+
+    fn has_glyph(&mut self, character: char, font: &FontDescriptor) -> bool {
+        // Fallback chains are made of system families; other sources draw as
+        // given, and so does a bundled font registered for a missing family
+        let FontSource::System(name) = &font.source else { return true };
+        if crate::text::resolve_font(font).origin == FontOrigin::Bundled {
+            return true;
+        }
+        let family_name = if name == "system" || name.is_empty() { "Segoe UI" } else { name.as_str() };
+        let weight = map_weight_to_dwrite(font.weight);
+        let style = if font.style == FontStyle::Italic {
+            DWRITE_FONT_STYLE_ITALIC
+        } else {
+            DWRITE_FONT_STYLE_NORMAL
+        };
+
+        unsafe {
+            let mut collection: Option<IDWriteFontCollection> = None;
+            if self.dwrite_factory.GetSystemFontCollection(&mut collection as *mut _, false).is_err() {
+                return false;
+            }
+            let Some(collection) = collection else { return false };
+
+            let wide_name: Vec<u16> = family_name.encode_utf16().chain(std::iter::once(0)).collect();
+            let mut index = 0u32;
+            let mut exists = BOOL::default();
+            if collection.FindFamilyName(PCWSTR::from_raw(wide_name.as_ptr()), &mut index, &mut exists).is_err()
+                || !exists.as_bool()
+            {
+                return false;
+            }
+
+            collection
+                .GetFontFamily(index)
+                .and_then(|family| family.GetFirstMatchingFont(weight, DWRITE_FONT_STRETCH_NORMAL, style))
+                .and_then(|matched| matched.HasCharacter(character as u32))
+                .is_ok_and(|found| found.as_bool())
+        }
+    }
+
+    fn clear_font_cache(&mut self) {
+        // Memory fonts leave GDI now; they and bundled fonts are registered
+        // again on next use
+        let memory_fonts: Vec<u64> = self.memory_font_handles.keys().copied().collect();
+        for data_hash in memory_fonts {
+            self.remove_memory_font(data_hash);
+        }
+        self.loaded_fonts.clear();
+        self.bundled_fonts.clear();
+    }
 }
 
 #[cfg(test)]
@@ -1193,6 +1415,26 @@ mod tests {
         assert!(bold_b.width > 0);
     }
 
+    #[test]
+    fn test_rasterize_glyph_from_memory_font() {
+        let Ok(data) = std::fs::read("C:\\Windows\\Fonts\\segoeui.ttf") else { return };
+        let manager = crate::text::font_manager::shared_font_manager();
+        let data_hash = manager.lock().unwrap().register_font_data("", data);
+
+        let mut rasterizer = WindowsGlyphRasterizer::new();
+        let font = FontDescriptor {
+            source: FontSource::Memory { name: "Memory Test Font".to_string(), data_hash },
+            weight: 400,
+            style: FontStyle::Normal,
+            size: 16.0,
+        };
+        let bitmap = rasterizer.rasterize_glyph('A', &font).expect("registered bytes should rasterize");
+        assert!(bitmap.width > 0);
+        assert!(bitmap.height > 0);
+
+        assert!(manager.lock().unwrap().unregister_font_data(data_hash));
+    }
+
     #[test]
     fn test_measure_string() {
         let mut rasterizer = WindowsGlyphRasterizer::new();
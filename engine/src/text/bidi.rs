@@ -0,0 +1,122 @@
+//! Bidirectional text
+//!
+//! Lines are laid out in logical order (wrapping and ellipsis truncation
+//! work on the text as written) and then reordered for display with the
+//! Unicode Bidirectional Algorithm (UAX #9), so Arabic or Hebrew runs read
+//! right to left inside left-to-right text and vice versa. Reordering moves
+//! whole grapheme clusters, the unit the text renderer draws; it doesn't
+//! mirror brackets or shape glyphs.
+
+use std::ops::Range;
+
+use unicode_bidi::{BidiInfo, Level};
+
+use super::Direction;
+
+/// Embedding levels of a text, resolved once and shared by its lines
+pub struct BidiText<'text> {
+    info: BidiInfo<'text>,
+}
+
+impl<'text> BidiText<'text> {
+    /// Resolve the levels of `text`, every paragraph in it having the
+    /// `base` direction
+    pub fn new(text: &'text str, base: Direction) -> Self {
+        let level = match base {
+            Direction::Ltr => Level::ltr(),
+            Direction::Rtl => Level::rtl(),
+        };
+        Self { info: BidiInfo::new(text, Some(level)) }
+    }
+
+    /// Display order of the clusters of one line.
+    ///
+    /// `line` is the line's byte range in the text and `clusters` the byte
+    /// offsets of its clusters in logical order. Returns indices into
+    /// `clusters`, left to right. A cluster outside `line` (an ellipsis
+    /// appended to a cut line) takes the paragraph's level, so it stays at
+    /// the paragraph's end: the right for LTR, the left for RTL.
+    pub fn visual_order(&self, line: Range<usize>, clusters: &[usize]) -> Vec<usize> {
+        let Some(para) = self.info.paragraphs.iter()
+            .find(|p| p.range.contains(&line.start))
+            .filter(|_| self.info.has_rtl())
+        else {
+            return (0..clusters.len()).collect();
+        };
+
+        let levels = self.info.reordered_levels(para, line.clone());
+        let cluster_levels: Vec<Level> = clusters
+            .iter()
+            .map(|offset| if line.contains(offset) { levels[*offset] } else { para.level })
+            .collect();
+        BidiInfo::reorder_visual(&cluster_levels)
+    }
+
+    /// Whether the character at byte `offset` is in a right-to-left run.
+    /// Offsets past the text take the paragraph direction of the text's end.
+    pub fn is_rtl(&self, offset: usize) -> bool {
+        match self.info.levels.get(offset) {
+            Some(level) => level.is_rtl(),
+            None => self.info.paragraphs.last().is_some_and(|p| p.level.is_rtl()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::grapheme;
+
+    /// The clusters of `text` as a whole line, in display order
+    fn visual(text: &str, base: Direction) -> String {
+        let clusters: Vec<(usize, &str)> = grapheme::graphemes(text).collect();
+        let offsets: Vec<usize> = clusters.iter().map(|&(offset, _)| offset).collect();
+        BidiText::new(text, base)
+            .visual_order(0..text.len(), &offsets)
+            .into_iter()
+            .map(|i| clusters[i].1)
+            .collect()
+    }
+
+    #[test]
+    fn test_mixed_arabic_and_latin() {
+        // "abc", then alef-beh-jeem, then "def"
+        let text = "abc \u{627}\u{628}\u{62C} def";
+
+        // In an LTR paragraph only the Arabic word flips
+        assert_eq!(visual(text, Direction::Ltr), "abc \u{62C}\u{628}\u{627} def");
+        // In an RTL paragraph the words run right to left, but each Latin
+        // word still reads left to right
+        assert_eq!(visual(text, Direction::Rtl), "def \u{62C}\u{628}\u{627} abc");
+
+        // Digits inside Arabic keep their own order
+        let text = "\u{627}\u{628} 123 \u{62C}";
+        assert_eq!(visual(text, Direction::Rtl), "\u{62C} 123 \u{628}\u{627}");
+
+        // Latin-only text is left alone, whatever the base
+        assert_eq!(visual("hello world", Direction::Ltr), "hello world");
+        assert_eq!(visual("hello world", Direction::Rtl), "hello world");
+    }
+
+    #[test]
+    fn test_lines_and_appended_ellipsis() {
+        let text = "\u{627}\u{628}\u{62C} \u{62F}\u{647}";
+        let bidi = BidiText::new(text, Direction::Rtl);
+
+        // The first word as its own line, with an ellipsis appended at the
+        // cut: the ellipsis ends the line on the left
+        let first_word = 0..6;
+        let clusters = [0, 2, 4, 6];
+        assert_eq!(bidi.visual_order(first_word, &clusters), vec![3, 2, 1, 0]);
+
+        // Combining marks move with their base letter
+        let text = "ab \u{628}\u{650}\u{62A}";
+        assert_eq!(visual(text, Direction::Ltr), "ab \u{62A}\u{628}\u{650}");
+
+        // Run directions, for placing carets
+        let bidi = BidiText::new(text, Direction::Ltr);
+        assert!(!bidi.is_rtl(0));
+        assert!(bidi.is_rtl(3));
+        assert!(!bidi.is_rtl(text.len()));
+    }
+}
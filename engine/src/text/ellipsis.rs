@@ -0,0 +1,90 @@
+//! Ellipsis truncation
+//!
+//! A line cut short by `TextOverflow::Ellipsis` ends at a grapheme cluster
+//! boundary, so an emoji ZWJ sequence, a flag or a letter with combining
+//! marks is either kept whole or dropped whole - never split into broken
+//! glyphs. Whitespace before the cut is dropped so the ellipsis sits against
+//! the last word.
+
+/// How many of a line's clusters to keep before an ellipsis.
+///
+/// `clusters` are the line's grapheme clusters with their advances (spacing
+/// included), in order; `ellipsis_width` is the ellipsis measured in the
+/// line's font. The kept clusters plus the ellipsis fit in `max_width`.
+/// Returns None when not even the ellipsis fits, in which case the line
+/// draws nothing.
+pub fn ellipsis_cut<'a>(
+    clusters: impl IntoIterator<Item = (&'a str, f32)>,
+    ellipsis_width: f32,
+    max_width: f32,
+) -> Option<usize> {
+    if ellipsis_width > max_width {
+        return None;
+    }
+    let available = max_width - ellipsis_width;
+
+    let mut width = 0.0;
+    let mut keep = 0;
+    for (i, (cluster, advance)) in clusters.into_iter().enumerate() {
+        width += advance;
+        if width > available {
+            break;
+        }
+        if !cluster.chars().all(char::is_whitespace) {
+            keep = i + 1;
+        }
+    }
+    Some(keep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::grapheme;
+
+    /// What's left of `text` before the ellipsis, with clusters measured by
+    /// `advance`
+    fn cut(text: &str, max_width: f32, ellipsis_width: f32, advance: impl Fn(&str) -> f32) -> Option<&str> {
+        let clusters: Vec<(usize, &str)> = grapheme::graphemes(text).collect();
+        let keep = ellipsis_cut(clusters.iter().map(|&(_, c)| (c, advance(c))), ellipsis_width, max_width)?;
+        Some(&text[..clusters.get(keep).map_or(text.len(), |&(offset, _)| offset)])
+    }
+
+    #[test]
+    fn test_emoji_sequences_are_kept_or_dropped_whole() {
+        // Each cluster is 10 wide, however many code points it has
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        let text = format!("ab{family}cd");
+        assert_eq!(cut(&text, 40.0, 10.0, |_| 10.0), Some(format!("ab{family}").as_str()));
+        assert_eq!(cut(&text, 39.0, 10.0, |_| 10.0), Some("ab"));
+
+        // Flags and skin tones too
+        let text = "go \u{1F1EF}\u{1F1F5}\u{1F44B}\u{1F3FD}!";
+        assert_eq!(cut(text, 50.0, 10.0, |_| 10.0), Some("go \u{1F1EF}\u{1F1F5}"));
+
+        // A combining accent stays with its letter
+        let text = "cafe\u{301} noir";
+        assert_eq!(cut(text, 50.0, 10.0, |_| 10.0), Some("cafe\u{301}"));
+        assert_eq!(cut(text, 49.0, 10.0, |_| 10.0), Some("caf"));
+    }
+
+    #[test]
+    fn test_cjk_and_spaces() {
+        // Full-width ideographs are 20 wide here
+        let wide = |c: &str| if c.is_ascii() { 10.0 } else { 20.0 };
+        assert_eq!(cut("日本語のテキスト", 75.0, 10.0, wide), Some("日本語"));
+        assert_eq!(cut("日本語 text", 85.0, 10.0, wide), Some("日本語"));
+
+        // Whitespace before the cut is dropped
+        assert_eq!(cut("hello world", 70.0, 10.0, |_| 10.0), Some("hello"));
+        // The whole line when it fits (cut by max_lines rather than width)
+        assert_eq!(cut("hi", 100.0, 10.0, |_| 10.0), Some("hi"));
+    }
+
+    #[test]
+    fn test_nothing_when_ellipsis_does_not_fit() {
+        assert_eq!(cut("hello", 8.0, 10.0, |_| 10.0), None);
+        // Room for the ellipsis alone
+        assert_eq!(cut("hello", 15.0, 10.0, |_| 10.0), Some(""));
+    }
+}
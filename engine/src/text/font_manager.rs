@@ -15,7 +15,9 @@
 //! See `macos.rs` for a reference implementation using Core Text.
 
 use super::{FontDescriptor, FontSource, FontStyle};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
 
 // Platform-specific font manager implementations
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -79,6 +81,14 @@ mod stub {
                 "Text rendering not supported on this platform".to_string()
             ))
         }
+
+        fn match_system_family(&mut self, _name: &str) -> Option<SystemFontMatch> {
+            None
+        }
+
+        fn list_system_families(&mut self) -> Vec<FontFamilyInfo> {
+            Vec::new()
+        }
     }
 }
 
@@ -134,6 +144,65 @@ pub trait PlatformFontManagerTrait {
         style: FontStyle,
         size: f32,
     ) -> Result<Box<dyn Font>, FontError>;
+
+    /// Check whether a font family is installed, without substitution.
+    ///
+    /// Most platform APIs quietly return a fallback face for unknown names;
+    /// implementations must detect that and return `None` instead. "system"
+    /// or an empty name matches the platform's default UI font when present.
+    fn match_system_family(&mut self, name: &str) -> Option<SystemFontMatch>;
+
+    /// Enumerate the installed font families.
+    ///
+    /// This walks every installed face and can take hundreds of
+    /// milliseconds; callers should go through [`list_system_fonts`], which
+    /// caches the result. Implementations may use [`group_system_faces`].
+    fn list_system_families(&mut self) -> Vec<FontFamilyInfo>;
+}
+
+/// An installed system font family, as reported by the platform
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemFontMatch {
+    /// Family name of the matched face
+    pub family: String,
+
+    /// Font file path, when the platform exposes it
+    pub path: Option<String>,
+}
+
+/// Family name of the engine default font (the platform UI font)
+pub const DEFAULT_FONT_FAMILY: &str = "system";
+
+/// Which step of the resolution order produced a font
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontOrigin {
+    /// The requested system family is installed
+    System,
+
+    /// A bundled font registered for the requested family
+    Bundled,
+
+    /// The engine default font ([`DEFAULT_FONT_FAMILY`])
+    Default,
+
+    /// Nothing could be loaded; glyphs render as missing-glyph boxes
+    Tofu,
+}
+
+/// The face actually used for a font request, for debugging missing fonts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedFont {
+    /// Descriptor that is loaded in place of the requested one
+    pub descriptor: FontDescriptor,
+
+    /// Family name of the chosen face
+    pub family: String,
+
+    /// Font file path, when known
+    pub path: Option<String>,
+
+    /// Resolution step that produced this face
+    pub origin: FontOrigin,
 }
 
 /// Glyph metrics for a single character
@@ -172,6 +241,12 @@ pub trait Font: Send + Sync {
     /// Get the font's line height (recommended line spacing)
     fn line_height(&self) -> f32;
 
+    /// Get the font's line gap (extra spacing recommended between lines,
+    /// on top of ascent and descent)
+    fn line_gap(&self) -> f32 {
+        (self.line_height() - self.ascent() - self.descent().abs()).max(0.0)
+    }
+
     /// Get the font's cap height (height of capital letters)
     fn cap_height(&self) -> f32;
 
@@ -181,15 +256,23 @@ pub trait Font: Send + Sync {
     /// Get the font size in points
     fn size(&self) -> f32;
 
-    /// Measure the width of a string
+    /// Measure the width of a string (one advance per grapheme cluster)
     fn measure_text(&self, text: &str) -> f32 {
-        text.chars()
+        super::grapheme::graphemes(text)
+            .filter_map(|(_, g)| super::grapheme::base_char(g))
             .filter_map(|c| self.glyph_metrics(c))
             .map(|m| m.advance)
             .sum()
     }
 }
 
+/// Font bytes registered from memory, shared by identical registrations
+struct RegisteredFontData {
+    data: Vec<u8>,
+    /// Registrations not yet unregistered; the bytes are freed at zero
+    registrations: usize,
+}
+
 /// Font Manager - loads and caches fonts
 pub struct FontManager {
     /// Platform-specific font manager
@@ -198,8 +281,15 @@ pub struct FontManager {
     /// Font cache (cache_key → Font)
     cache: HashMap<String, Box<dyn Font>>,
 
-    /// Font data cache for bundled/memory fonts
-    font_data_cache: HashMap<u64, Vec<u8>>,
+    /// Font data cache for bundled/memory fonts (data hash → bytes)
+    font_data_cache: HashMap<u64, RegisteredFontData>,
+
+    /// Bundled fonts registered as stand-ins for a family (lowercase family → source)
+    bundled_families: HashMap<String, FontSource>,
+
+    /// Platform family lookups (family → match), so rasterizers can resolve
+    /// fonts per glyph without asking the platform each time
+    system_matches: HashMap<String, Option<SystemFontMatch>>,
 }
 
 impl FontManager {
@@ -209,6 +299,8 @@ impl FontManager {
             platform: PlatformFontManager::new(),
             cache: HashMap::new(),
             font_data_cache: HashMap::new(),
+            bundled_families: HashMap::new(),
+            system_matches: HashMap::new(),
         }
     }
 
@@ -221,8 +313,24 @@ impl FontManager {
             return Ok(self.cache.get(&cache_key).unwrap().as_ref());
         }
 
+        // Missing system families fall back deterministically
+        let resolved;
+        let source = match &descriptor.source {
+            FontSource::System(_) | FontSource::SystemWithFallback(_) => {
+                resolved = self.resolve_font(descriptor);
+                if resolved.origin == FontOrigin::Tofu {
+                    return Err(FontError::NotFound(format!(
+                        "{:?} is not installed and no default font is available",
+                        descriptor.source
+                    )));
+                }
+                &resolved.descriptor.source
+            }
+            source => source,
+        };
+
         // Load font based on source
-        let font: Box<dyn Font> = match &descriptor.source {
+        let font: Box<dyn Font> = match source {
             FontSource::System(name) => {
                 self.platform.load_system_font(name, descriptor.weight, descriptor.style, descriptor.size)?
             }
@@ -235,13 +343,15 @@ impl FontManager {
                 self.platform.load_font_from_data(&font_data, descriptor.weight, descriptor.style, descriptor.size)?
             }
 
-            FontSource::Memory { data_hash, .. } => {
+            FontSource::Memory { name, data_hash } => {
                 // Get font data from cache
                 let font_data = self.font_data_cache.get(data_hash)
-                    .ok_or_else(|| FontError::LoadFailed("Font data not found in cache".to_string()))?;
+                    .ok_or_else(|| FontError::NotFound(format!("no font data registered for '{}' ({:#x})", name, data_hash)))?;
 
-                self.platform.load_font_from_data(font_data, descriptor.weight, descriptor.style, descriptor.size)?
+                self.platform.load_font_from_data(&font_data.data, descriptor.weight, descriptor.style, descriptor.size)?
             }
+
+            FontSource::SystemWithFallback(_) => unreachable!("fallback chains resolve to a single source"),
         };
 
         // Cache the font
@@ -250,23 +360,176 @@ impl FontManager {
         Ok(self.cache.get(&cache_key).unwrap().as_ref())
     }
 
-    /// Register embedded font data (for Memory fonts)
+    /// Register embedded font data (for Memory fonts) for family `name`.
+    ///
+    /// Returns the `data_hash` for [`FontSource::Memory`]. It is a hash of the
+    /// bytes alone, so registering identical bytes again returns the same
+    /// hash and stores them once; each registration needs its own
+    /// [`unregister_font_data`](Self::unregister_font_data). The data also
+    /// stands in for the system family `name` when that isn't installed.
     pub fn register_font_data(&mut self, name: &str, data: Vec<u8>) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
         let mut hasher = DefaultHasher::new();
-        name.hash(&mut hasher);
         data.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        self.font_data_cache.insert(hash, data);
+        // 0 is the FFI's failure value
+        let hash = hasher.finish().max(1);
+
+        self.font_data_cache
+            .entry(hash)
+            .or_insert(RegisteredFontData { data, registrations: 0 })
+            .registrations += 1;
+        if !name.is_empty() {
+            self.register_bundled_family(name, FontSource::Memory { name: name.to_string(), data_hash: hash });
+        }
         hash
     }
 
+    /// Drop one registration of font data. The bytes, the fonts loaded from
+    /// them and the families they stand in for are freed with the last one.
+    /// Returns false if `data_hash` isn't registered.
+    pub fn unregister_font_data(&mut self, data_hash: u64) -> bool {
+        let Some(font_data) = self.font_data_cache.get_mut(&data_hash) else { return false };
+        font_data.registrations -= 1;
+        if font_data.registrations == 0 {
+            self.font_data_cache.remove(&data_hash);
+            self.bundled_families.retain(|_, source| {
+                !matches!(source, FontSource::Memory { data_hash: hash, .. } if *hash == data_hash)
+            });
+            self.cache.clear();
+        }
+        true
+    }
+
+    /// Bytes registered under `data_hash`, if any
+    pub fn font_data(&self, data_hash: u64) -> Option<&[u8]> {
+        self.font_data_cache.get(&data_hash).map(|font_data| font_data.data.as_slice())
+    }
+
+    /// Register a bundled font to stand in for `family` when it isn't installed.
+    ///
+    /// `source` is usually [`FontSource::Bundled`] or a [`FontSource::Memory`]
+    /// handle from [`register_font_data`](Self::register_font_data).
+    pub fn register_bundled_family(&mut self, family: &str, source: FontSource) {
+        self.bundled_families.insert(family.to_lowercase(), source);
+        // Cached fallbacks for this family are stale now
+        self.cache.clear();
+    }
+
+    /// Resolve the face that will be used for `descriptor`.
+    ///
+    /// System fonts resolve in a fixed order: the requested system family,
+    /// then a bundled font registered for that family, then the engine
+    /// default ([`DEFAULT_FONT_FAMILY`]), then tofu. A fallback chain tries
+    /// each of its families that way before the default, and resolves to the
+    /// first one found. Bundled and memory sources are used as given.
+    pub fn resolve_font(&mut self, descriptor: &FontDescriptor) -> ResolvedFont {
+        match &descriptor.source {
+            FontSource::System(name) => {
+                if let Some(found) = self.resolve_family(name, descriptor) {
+                    return found;
+                }
+            }
+            FontSource::SystemWithFallback(_) => {
+                for font in descriptor.fallback_chain() {
+                    let FontSource::System(name) = &font.source else { continue };
+                    if name.is_empty() || name == DEFAULT_FONT_FAMILY {
+                        break;
+                    }
+                    if let Some(found) = self.resolve_family(name, &font) {
+                        return found;
+                    }
+                }
+            }
+            FontSource::Bundled(path) => {
+                return ResolvedFont {
+                    descriptor: descriptor.clone(),
+                    family: std::path::Path::new(path)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.clone()),
+                    path: Some(path.clone()),
+                    origin: FontOrigin::Bundled,
+                };
+            }
+            FontSource::Memory { name, .. } => {
+                return ResolvedFont {
+                    descriptor: descriptor.clone(),
+                    family: name.clone(),
+                    path: None,
+                    origin: FontOrigin::Bundled,
+                };
+            }
+        }
+
+        // 3. Engine default, 4. tofu
+        let default_descriptor = FontDescriptor {
+            source: FontSource::System(DEFAULT_FONT_FAMILY.to_string()),
+            ..descriptor.clone()
+        };
+        match self.match_system_family(DEFAULT_FONT_FAMILY) {
+            Some(found) => ResolvedFont {
+                descriptor: default_descriptor,
+                family: found.family,
+                path: found.path,
+                origin: FontOrigin::Default,
+            },
+            None => ResolvedFont {
+                descriptor: default_descriptor,
+                family: DEFAULT_FONT_FAMILY.to_string(),
+                path: None,
+                origin: FontOrigin::Tofu,
+            },
+        }
+    }
+
+    /// Steps 1 and 2 of [`resolve_font`](Self::resolve_font) for one system
+    /// family: installed, or a registered bundled stand-in
+    fn resolve_family(&mut self, name: &str, descriptor: &FontDescriptor) -> Option<ResolvedFont> {
+        if name.is_empty() || name == DEFAULT_FONT_FAMILY {
+            return None;
+        }
+
+        // 1. Requested system family
+        if let Some(found) = self.match_system_family(name) {
+            return Some(ResolvedFont {
+                descriptor: descriptor.clone(),
+                family: found.family,
+                path: found.path,
+                origin: FontOrigin::System,
+            });
+        }
+
+        // 2. Bundled font registered for the same family
+        let source = self.bundled_families.get(&name.to_lowercase())?;
+        let path = match source {
+            FontSource::Bundled(path) => Some(path.clone()),
+            _ => None,
+        };
+        Some(ResolvedFont {
+            descriptor: FontDescriptor { source: source.clone(), ..descriptor.clone() },
+            family: name.to_string(),
+            path,
+            origin: FontOrigin::Bundled,
+        })
+    }
+
+    /// The platform's match for an installed family, remembered until
+    /// [`clear_cache`](Self::clear_cache)
+    fn match_system_family(&mut self, name: &str) -> Option<SystemFontMatch> {
+        if let Some(found) = self.system_matches.get(name) {
+            return found.clone();
+        }
+        let found = self.platform.match_system_family(name);
+        self.system_matches.insert(name.to_string(), found.clone());
+        found
+    }
+
     /// Clear the font cache
     pub fn clear_cache(&mut self) {
         self.cache.clear();
+        self.system_matches.clear();
     }
 
     /// Get cache statistics
@@ -284,6 +547,56 @@ impl Default for FontManager {
     }
 }
 
+/// An installed font family, as listed by [`list_system_fonts`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FontFamilyInfo {
+    /// Family name, as passed to `FontDescriptor::system`
+    pub family: String,
+
+    /// Weights of the installed faces on the CSS scale (100-900), ascending
+    pub weights: Vec<u16>,
+
+    /// Whether any face is italic or oblique
+    pub has_italic: bool,
+}
+
+/// Group installed faces, given as (family, CSS weight, italic), into
+/// families sorted by name. Hidden families (a leading '.', like Apple's
+/// private system faces) are skipped.
+pub(crate) fn group_system_faces(faces: impl IntoIterator<Item = (String, u16, bool)>) -> Vec<FontFamilyInfo> {
+    let mut families: BTreeMap<String, FontFamilyInfo> = BTreeMap::new();
+    for (family, weight, italic) in faces {
+        if family.is_empty() || family.starts_with('.') {
+            continue;
+        }
+        let info = families.entry(family.to_lowercase()).or_insert_with(|| FontFamilyInfo {
+            family,
+            weights: Vec::new(),
+            has_italic: false,
+        });
+        if let Err(index) = info.weights.binary_search(&weight) {
+            info.weights.insert(index, weight);
+        }
+        info.has_italic |= italic;
+    }
+    families.into_values().collect()
+}
+
+/// List the installed system font families.
+///
+/// Enumeration is slow, so the first call's result is cached for the life
+/// of the process; fonts installed afterwards are not picked up.
+pub fn list_system_fonts() -> Vec<FontFamilyInfo> {
+    static SYSTEM_FONTS: OnceLock<Vec<FontFamilyInfo>> = OnceLock::new();
+    SYSTEM_FONTS.get_or_init(|| PlatformFontManager::new().list_system_families()).clone()
+}
+
+/// Process-wide font manager shared by text measurement and font resolution
+pub fn shared_font_manager() -> &'static Mutex<FontManager> {
+    static SHARED: OnceLock<Mutex<FontManager>> = OnceLock::new();
+    SHARED.get_or_init(|| Mutex::new(FontManager::new()))
+}
+
 /// Font cache statistics
 #[derive(Debug, Clone)]
 pub struct FontCacheStats {
@@ -348,6 +661,118 @@ mod tests {
         assert_eq!(stats.embedded_fonts, 1);
     }
 
+    #[test]
+    fn test_identical_font_data_is_stored_once() {
+        let mut manager = FontManager::new();
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        let first = manager.register_font_data("No Such Brand 7f3a", bytes.clone());
+        let second = manager.register_font_data("No Such Alias 7f3a", bytes.clone());
+        assert_eq!(first, second);
+        assert_eq!(manager.cache_stats().embedded_fonts, 1);
+        assert_ne!(manager.register_font_data("Other", vec![1, 2, 3]), first);
+
+        // Both families stand in through the shared bytes
+        let descriptor = FontDescriptor::system("no such alias 7f3a", 400, FontStyle::Normal, 16.0);
+        assert_eq!(
+            manager.resolve_font(&descriptor).descriptor.source,
+            FontSource::Memory { name: "No Such Alias 7f3a".to_string(), data_hash: first }
+        );
+
+        // The bytes live until every registration is dropped
+        assert!(manager.unregister_font_data(first));
+        assert_eq!(manager.font_data(first), Some(bytes.as_slice()));
+        assert!(manager.unregister_font_data(first));
+        assert_eq!(manager.font_data(first), None);
+        assert!(!manager.unregister_font_data(first));
+        assert_eq!(manager.cache_stats().embedded_fonts, 1);
+
+        // Its families no longer resolve to it
+        let source = manager.resolve_font(&descriptor).descriptor.source;
+        assert!(!matches!(source, FontSource::Memory { .. }));
+        let memory = FontSource::Memory { name: "No Such Brand 7f3a".to_string(), data_hash: first };
+        let missing = FontDescriptor { source: memory, ..descriptor };
+        assert!(matches!(manager.load_font(&missing), Err(FontError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_group_system_faces() {
+        let face = |family: &str, weight, italic| (family.to_string(), weight, italic);
+        let families = group_system_faces([
+            face("Zapfino", 400, false),
+            face("Arial", 700, false),
+            face(".SF NS", 400, false),
+            face("Arial", 400, true),
+            face("arial", 400, false),
+            face("", 400, false),
+        ]);
+        assert_eq!(families, vec![
+            FontFamilyInfo { family: "Arial".to_string(), weights: vec![400, 700], has_italic: true },
+            FontFamilyInfo { family: "Zapfino".to_string(), weights: vec![400], has_italic: false },
+        ]);
+
+        // Cached after the first enumeration
+        assert_eq!(list_system_fonts(), list_system_fonts());
+    }
+
+    #[test]
+    fn test_missing_family_resolves_to_default() {
+        let mut manager = FontManager::new();
+        let descriptor = FontDescriptor::system("No Such Family 7f3a", 700, FontStyle::Italic, 16.0);
+
+        let resolved = manager.resolve_font(&descriptor);
+        assert_eq!(resolved.descriptor.source, FontSource::System(DEFAULT_FONT_FAMILY.to_string()));
+        assert_eq!(resolved.descriptor.weight, 700);
+        assert_eq!(resolved.descriptor.style, FontStyle::Italic);
+        assert!(matches!(resolved.origin, FontOrigin::Default | FontOrigin::Tofu));
+
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        assert_eq!(resolved.origin, FontOrigin::Default);
+    }
+
+    #[test]
+    fn test_missing_family_prefers_registered_bundled_font() {
+        let mut manager = FontManager::new();
+        manager.register_bundled_family(
+            "No Such Family 7f3a",
+            FontSource::Bundled("fonts/NoSuchFamily-Regular.ttf".to_string()),
+        );
+        let descriptor = FontDescriptor::system("no such family 7f3a", 400, FontStyle::Normal, 16.0);
+
+        let resolved = manager.resolve_font(&descriptor);
+        assert_eq!(resolved.origin, FontOrigin::Bundled);
+        assert_eq!(resolved.path.as_deref(), Some("fonts/NoSuchFamily-Regular.ttf"));
+        assert_eq!(
+            resolved.descriptor.source,
+            FontSource::Bundled("fonts/NoSuchFamily-Regular.ttf".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fallback_chain_resolves_first_available_family() {
+        let mut manager = FontManager::new();
+        manager.register_bundled_family(
+            "No Such CJK 7f3a",
+            FontSource::Bundled("fonts/NoSuchCJK-Regular.ttf".to_string()),
+        );
+        let descriptor = FontDescriptor::system_with_fallback(
+            &["No Such Family 7f3a", "No Such CJK 7f3a"],
+            700,
+            FontStyle::Normal,
+            16.0,
+        );
+
+        let resolved = manager.resolve_font(&descriptor);
+        assert_eq!(resolved.origin, FontOrigin::Bundled);
+        assert_eq!(resolved.family, "No Such CJK 7f3a");
+        assert_eq!(resolved.descriptor.weight, 700);
+
+        // Nothing in the chain is available: the engine default
+        let missing = FontDescriptor::system_with_fallback(&["No Such Family 7f3a"], 400, FontStyle::Normal, 16.0);
+        let resolved = manager.resolve_font(&missing);
+        assert_eq!(resolved.descriptor.source, FontSource::System(DEFAULT_FONT_FAMILY.to_string()));
+    }
+
     #[test]
     #[cfg(target_os = "macos")]
     fn test_load_system_font() {
@@ -3,9 +3,9 @@
 //! Uses FreeType for font loading and glyph metrics,
 //! fontconfig for system font discovery.
 
-use super::{Font, FontError, GlyphMetrics, PlatformFontManagerTrait};
+use super::{group_system_faces, Font, FontError, FontFamilyInfo, GlyphMetrics, PlatformFontManagerTrait, SystemFontMatch};
 use crate::text::FontStyle;
-use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_SLANT, FC_WEIGHT};
+use fontconfig::{list_fonts, Fontconfig, ObjectSet, Pattern, FC_FAMILY, FC_SLANT, FC_WEIGHT};
 use fontconfig::{
     FC_SLANT_ITALIC, FC_SLANT_ROMAN, FC_WEIGHT_BLACK, FC_WEIGHT_BOLD, FC_WEIGHT_EXTRALIGHT,
     FC_WEIGHT_EXTRABOLD, FC_WEIGHT_LIGHT, FC_WEIGHT_MEDIUM, FC_WEIGHT_REGULAR,
@@ -145,6 +145,22 @@ impl LinuxFontManager {
         }
     }
 
+    /// Convert a fontconfig weight to the nearest CSS font weight (100-900)
+    fn fc_weight_to_css(weight: i32) -> u16 {
+        const WEIGHTS: [(i32, u16); 9] = [
+            (FC_WEIGHT_THIN, 100),
+            (FC_WEIGHT_EXTRALIGHT, 200),
+            (FC_WEIGHT_LIGHT, 300),
+            (FC_WEIGHT_REGULAR, 400),
+            (FC_WEIGHT_MEDIUM, 500),
+            (FC_WEIGHT_SEMIBOLD, 600),
+            (FC_WEIGHT_BOLD, 700),
+            (FC_WEIGHT_EXTRABOLD, 800),
+            (FC_WEIGHT_BLACK, 900),
+        ];
+        WEIGHTS.iter().min_by_key(|(fc, _)| (fc - weight).abs()).map_or(400, |&(_, css)| css)
+    }
+
     /// Find font file path using fontconfig
     fn find_font_path(family: &str, weight: u16, italic: bool) -> Option<PathBuf> {
         let fc = Fontconfig::new()?;
@@ -178,6 +194,39 @@ impl LinuxFontManager {
         let font = pattern.font_match();
         font.filename().map(PathBuf::from)
     }
+
+    /// Look up an installed family without accepting fontconfig's substitutes.
+    ///
+    /// fontconfig always returns *some* face, so the matched family is
+    /// compared against the request. Generic aliases ("sans-serif",
+    /// "monospace", ...) are resolved by fontconfig and always match.
+    fn match_family(family: &str) -> Option<SystemFontMatch> {
+        let fc = Fontconfig::new()?;
+
+        let is_default = family == "system" || family.is_empty();
+        let family_name = if is_default { "sans-serif" } else { family };
+        let is_alias = is_default
+            || matches!(
+                family_name.to_ascii_lowercase().as_str(),
+                "sans-serif" | "sans" | "serif" | "monospace" | "mono" | "cursive" | "fantasy"
+            );
+
+        let family_cstr = CString::new(family_name).ok()?;
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FAMILY, &family_cstr);
+
+        let font = pattern.font_match();
+        let matched_family = font.get_string(FC_FAMILY)?.to_string();
+
+        if !is_alias && !matched_family.eq_ignore_ascii_case(family_name) {
+            return None;
+        }
+
+        Some(SystemFontMatch {
+            family: matched_family,
+            path: font.filename().map(str::to_string),
+        })
+    }
 }
 
 impl PlatformFontManagerTrait for LinuxFontManager {
@@ -210,6 +259,31 @@ impl PlatformFontManagerTrait for LinuxFontManager {
         Ok(Box::new(LinuxFont::new(face, size)))
     }
 
+    fn match_system_family(&mut self, name: &str) -> Option<SystemFontMatch> {
+        Self::match_family(name)
+    }
+
+    fn list_system_families(&mut self) -> Vec<FontFamilyInfo> {
+        let Some(fc) = Fontconfig::new() else {
+            return Vec::new();
+        };
+
+        // An empty pattern matches every installed face
+        let pattern = Pattern::new(&fc);
+        let mut objects = ObjectSet::new(&fc);
+        objects.add(FC_FAMILY);
+        objects.add(FC_WEIGHT);
+        objects.add(FC_SLANT);
+        let faces = list_fonts(&pattern, Some(&objects));
+
+        group_system_faces(faces.iter().filter_map(|face| {
+            let family = face.get_string(FC_FAMILY)?.to_string();
+            let weight = Self::fc_weight_to_css(face.get_int(FC_WEIGHT).unwrap_or(FC_WEIGHT_REGULAR));
+            let italic = face.get_int(FC_SLANT).is_some_and(|slant| slant != FC_SLANT_ROMAN);
+            Some((family, weight, italic))
+        }))
+    }
+
     fn load_font_from_data(
         &mut self,
         data: &[u8],
@@ -236,6 +310,17 @@ mod tests {
         let _manager = LinuxFontManager::new();
     }
 
+    #[test]
+    fn test_fc_weight_to_css() {
+        assert_eq!(LinuxFontManager::fc_weight_to_css(FC_WEIGHT_REGULAR), 400);
+        assert_eq!(LinuxFontManager::fc_weight_to_css(FC_WEIGHT_BOLD), 700);
+        // Book (75) sits between light and regular, nearest regular
+        assert_eq!(LinuxFontManager::fc_weight_to_css(75), 400);
+        for css in (100..=900).step_by(100) {
+            assert_eq!(LinuxFontManager::fc_weight_to_css(LinuxFontManager::css_weight_to_fc(css)), css);
+        }
+    }
+
     #[test]
     fn test_find_system_font() {
         let path = LinuxFontManager::find_font_path("sans-serif", 400, false);
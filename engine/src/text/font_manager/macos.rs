@@ -3,15 +3,17 @@
 //! Uses Apple's Core Text framework for font loading, glyph metrics,
 //! and text shaping on macOS and iOS.
 
-use super::{Font, FontError, GlyphMetrics, PlatformFontManagerTrait};
+use super::{group_system_faces, Font, FontError, FontFamilyInfo, GlyphMetrics, PlatformFontManagerTrait, SystemFontMatch};
 use crate::text::FontStyle;
 use core_foundation::attributed_string::CFMutableAttributedString;
 use core_foundation::base::TCFType;
+use core_foundation::number::CFNumber;
 use core_foundation::string::CFString;
 use core_graphics::data_provider::CGDataProvider;
 use core_graphics::font::CGFont;
 use core_text::font::CTFont;
-use core_text::font_descriptor::CTFontDescriptorRef;
+use core_text::font_collection;
+use core_text::font_descriptor::{kCTFontSymbolicTrait, kCTFontWeightTrait, CTFontDescriptor, CTFontDescriptorRef};
 use core_text::line::CTLine;
 use core_text::string_attributes;
 use std::sync::Arc;
@@ -155,6 +157,35 @@ impl MacOSFontManager {
             _ => ".AppleSystemUIFontBlack",  // 850+
         }
     }
+
+    /// Convert a Core Text weight trait (-1.0 to 1.0) to the nearest CSS
+    /// font weight, using the values of the named `NSFontWeight`s
+    fn ct_weight_to_css(weight: f64) -> u16 {
+        const WEIGHTS: [(f64, u16); 9] = [
+            (-0.8, 100),
+            (-0.6, 200),
+            (-0.4, 300),
+            (0.0, 400),
+            (0.23, 500),
+            (0.3, 600),
+            (0.4, 700),
+            (0.56, 800),
+            (0.62, 900),
+        ];
+        WEIGHTS
+            .iter()
+            .min_by(|a, b| (a.0 - weight).abs().total_cmp(&(b.0 - weight).abs()))
+            .map_or(400, |&(_, css)| css)
+    }
+
+    /// Family, CSS weight and italic-ness of an installed face
+    fn describe_face(descriptor: &CTFontDescriptor) -> (String, u16, bool) {
+        let traits = descriptor.traits();
+        let number = |key: core_foundation::string::CFStringRef| traits.find(key).and_then(|value| value.downcast::<CFNumber>());
+        let weight = number(unsafe { kCTFontWeightTrait }).and_then(|n| n.to_f64()).unwrap_or(0.0);
+        let symbolic = number(unsafe { kCTFontSymbolicTrait }).and_then(|n| n.to_i64()).unwrap_or(0) as CTFontSymbolicTraits;
+        (descriptor.family_name(), Self::ct_weight_to_css(weight), (symbolic & kCTFontTraitItalic) != 0)
+    }
 }
 
 impl PlatformFontManagerTrait for MacOSFontManager {
@@ -239,6 +270,31 @@ impl PlatformFontManagerTrait for MacOSFontManager {
         Ok(Box::new(MacOSFont::new(ct_font, size)))
     }
 
+    fn match_system_family(&mut self, name: &str) -> Option<SystemFontMatch> {
+        let is_default = name == "system" || name.is_empty();
+        let lookup = if is_default { Self::get_system_font_name_for_weight(400) } else { name };
+
+        // CTFontCreateWithName substitutes a fallback (usually Helvetica) for
+        // unknown names, so compare the family we actually got.
+        let ct_font = core_text::font::new_from_name(lookup, 12.0).ok()?;
+        let family = ct_font.family_name();
+        if !is_default
+            && !family.eq_ignore_ascii_case(name)
+            && !ct_font.postscript_name().eq_ignore_ascii_case(name)
+        {
+            return None;
+        }
+
+        Some(SystemFontMatch { family, path: None })
+    }
+
+    fn list_system_families(&mut self) -> Vec<FontFamilyInfo> {
+        let Some(descriptors) = font_collection::create_for_all_families().get_descriptors() else {
+            return Vec::new();
+        };
+        group_system_faces(descriptors.iter().map(|descriptor| Self::describe_face(&descriptor)))
+    }
+
     fn load_font_from_data(
         &mut self,
         data: &[u8],
@@ -280,6 +336,17 @@ mod tests {
         assert!(font.descent() > 0.0);
     }
 
+    #[test]
+    fn test_list_system_families() {
+        let families = MacOSFontManager::new().list_system_families();
+        let helvetica = families.iter().find(|f| f.family == "Helvetica").expect("Helvetica is installed");
+        assert!(helvetica.weights.contains(&400));
+        assert!(helvetica.weights.contains(&700));
+        assert!(helvetica.has_italic);
+        assert_eq!(MacOSFontManager::ct_weight_to_css(0.0), 400);
+        assert_eq!(MacOSFontManager::ct_weight_to_css(0.4), 700);
+    }
+
     #[test]
     fn test_glyph_metrics() {
         let mut manager = MacOSFontManager::new();
@@ -3,7 +3,7 @@
 //! Uses Microsoft's DirectWrite framework for font loading, glyph metrics,
 //! and text shaping on Windows.
 
-use super::{Font, FontError, GlyphMetrics, PlatformFontManagerTrait};
+use super::{group_system_faces, Font, FontError, FontFamilyInfo, GlyphMetrics, PlatformFontManagerTrait, SystemFontMatch};
 use crate::text::FontStyle;
 use std::sync::OnceLock;
 
@@ -40,6 +40,27 @@ fn map_weight_to_dwrite(weight: u16) -> DWRITE_FONT_WEIGHT {
     })
 }
 
+/// Map a DirectWrite font weight (1-999) to the nearest CSS weight (100-900)
+fn map_dwrite_weight_to_css(weight: DWRITE_FONT_WEIGHT) -> u16 {
+    (((weight.0 + 50) / 100) * 100).clamp(100, 900) as u16
+}
+
+/// A family's name, in US English when it has one, else its first name
+unsafe fn family_name(family: &IDWriteFontFamily) -> Option<String> {
+    let names = family.GetFamilyNames().ok()?;
+    let locale: Vec<u16> = "en-us".encode_utf16().chain(std::iter::once(0)).collect();
+    let mut index = 0u32;
+    let mut exists = BOOL::default();
+    names.FindLocaleName(PCWSTR::from_raw(locale.as_ptr()), &mut index, &mut exists).ok()?;
+    if !exists.as_bool() {
+        index = 0;
+    }
+    let length = names.GetStringLength(index).ok()?;
+    let mut buffer = vec![0u16; length as usize + 1];
+    names.GetString(index, &mut buffer).ok()?;
+    Some(String::from_utf16_lossy(&buffer[..length as usize]))
+}
+
 /// Windows font implementation using DirectWrite
 pub struct WindowsFont {
     factory: IDWriteFactory,
@@ -299,6 +320,59 @@ impl PlatformFontManagerTrait for WindowsFontManager {
         }
     }
 
+    fn match_system_family(&mut self, name: &str) -> Option<SystemFontMatch> {
+        let family_name = if name == "system" || name.is_empty() {
+            "Segoe UI"
+        } else {
+            name
+        };
+
+        unsafe {
+            let mut collection: Option<IDWriteFontCollection> = None;
+            self.factory.GetSystemFontCollection(&mut collection as *mut _, false).ok()?;
+            let collection = collection?;
+
+            // FindFamilyName is an exact lookup, unlike CreateTextFormat which
+            // silently falls back for unknown families.
+            let wide_name: Vec<u16> = family_name.encode_utf16().chain(std::iter::once(0)).collect();
+            let mut index = 0u32;
+            let mut exists = BOOL::default();
+            collection
+                .FindFamilyName(PCWSTR::from_raw(wide_name.as_ptr()), &mut index, &mut exists)
+                .ok()?;
+
+            if !exists.as_bool() {
+                return None;
+            }
+        }
+
+        Some(SystemFontMatch { family: family_name.to_string(), path: None })
+    }
+
+    fn list_system_families(&mut self) -> Vec<FontFamilyInfo> {
+        let mut faces = Vec::new();
+        unsafe {
+            let mut collection: Option<IDWriteFontCollection> = None;
+            if self.factory.GetSystemFontCollection(&mut collection as *mut _, false).is_err() {
+                return Vec::new();
+            }
+            let Some(collection) = collection else {
+                return Vec::new();
+            };
+
+            for family_index in 0..collection.GetFontFamilyCount() {
+                let Ok(family) = collection.GetFontFamily(family_index) else { continue };
+                let Some(name) = family_name(&family) else { continue };
+                for font_index in 0..family.GetFontCount() {
+                    let Ok(font) = family.GetFont(font_index) else { continue };
+                    let italic = font.GetStyle() != DWRITE_FONT_STYLE_NORMAL;
+                    faces.push((name.clone(), map_dwrite_weight_to_css(font.GetWeight()), italic));
+                }
+            }
+        }
+        group_system_faces(faces)
+    }
+
     fn load_font_from_data(
         &mut self,
         data: &[u8],
@@ -405,6 +479,16 @@ mod tests {
         assert!(width > width2);
     }
 
+    #[test]
+    fn test_list_system_families() {
+        let families = WindowsFontManager::new().list_system_families();
+        let segoe = families.iter().find(|f| f.family == "Segoe UI").expect("Segoe UI is installed");
+        assert!(segoe.weights.contains(&400));
+        assert!(segoe.weights.contains(&700));
+        assert_eq!(map_dwrite_weight_to_css(DWRITE_FONT_WEIGHT(350)), 400);
+        assert_eq!(map_dwrite_weight_to_css(DWRITE_FONT_WEIGHT(950)), 900);
+    }
+
     #[test]
     fn test_font_weights() {
         let mut manager = WindowsFontManager::new();
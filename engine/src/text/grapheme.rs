@@ -0,0 +1,130 @@
+//! Grapheme cluster boundaries
+//!
+//! Text is shaped, measured and edited in extended grapheme clusters
+//! (UAX #29), not chars. A ZWJ emoji sequence such as a family emoji, a
+//! flag, or a skin-tone modified emoji is several code points but a single
+//! user-perceived character: it maps to one glyph, and the caret steps over
+//! it in one move.
+//!
+//! Offsets here are byte offsets into UTF-8 text unless noted otherwise.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Iterate over extended grapheme clusters with their byte offsets
+pub fn graphemes(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.grapheme_indices(true)
+}
+
+/// Number of extended grapheme clusters in `text`
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// The char that selects the glyph for a cluster (its first code point)
+pub fn base_char(grapheme: &str) -> Option<char> {
+    grapheme.chars().next()
+}
+
+/// Byte offset of the next grapheme boundary after `offset`.
+///
+/// Returns `text.len()` at the end of the text. An offset inside a cluster
+/// moves to the end of that cluster.
+pub fn next_grapheme_boundary(text: &str, offset: usize) -> usize {
+    graphemes(text)
+        .map(|(start, g)| start + g.len())
+        .find(|&end| end > offset)
+        .unwrap_or(text.len())
+}
+
+/// Byte offset of the previous grapheme boundary before `offset`.
+///
+/// Returns 0 at the start of the text. An offset inside a cluster moves to
+/// the start of that cluster.
+pub fn prev_grapheme_boundary(text: &str, offset: usize) -> usize {
+    graphemes(text)
+        .map(|(start, _)| start)
+        .take_while(|&start| start < offset)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Move a caret by `delta` grapheme clusters, in char (code point) indices.
+///
+/// Editors that store text as code points (like the Go text buffer) use
+/// this so a ZWJ sequence is stepped over, selected and deleted as a unit.
+/// The result is clamped to `[0, char count]`.
+pub fn move_caret_chars(text: &str, char_index: usize, delta: i32) -> usize {
+    // Char index of every cluster boundary, including both ends
+    let mut boundaries = Vec::with_capacity(text.len() + 1);
+    let mut chars_seen = 0;
+    for (_, g) in graphemes(text) {
+        boundaries.push(chars_seen);
+        chars_seen += g.chars().count();
+    }
+    boundaries.push(chars_seen);
+
+    let char_index = char_index.min(chars_seen);
+    let last = boundaries.len() - 1;
+    if delta == 0 {
+        // Snap into place: a caret inside a cluster moves to its start
+        let i = boundaries.iter().rposition(|&b| b <= char_index).unwrap_or(0);
+        boundaries[i]
+    } else if delta > 0 {
+        // First boundary after char_index, then step forward
+        let first = boundaries.iter().position(|&b| b > char_index).unwrap_or(last);
+        boundaries[(first + delta as usize - 1).min(last)]
+    } else {
+        // Last boundary before char_index, then step back
+        let steps = delta.unsigned_abs() as usize;
+        match boundaries.iter().rposition(|&b| b < char_index) {
+            Some(last) => boundaries[last.saturating_sub(steps - 1)],
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Man, ZWJ, woman, ZWJ, girl, ZWJ, boy
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+
+    #[test]
+    fn test_zwj_family_is_one_cluster() {
+        assert_eq!(FAMILY.chars().count(), 7);
+        assert_eq!(grapheme_count(FAMILY), 1);
+
+        let text = format!("a{}b", FAMILY);
+        let clusters: Vec<_> = graphemes(&text).collect();
+        assert_eq!(clusters, vec![(0, "a"), (1, FAMILY), (1 + FAMILY.len(), "b")]);
+        assert_eq!(base_char(FAMILY), Some('\u{1F468}'));
+    }
+
+    #[test]
+    fn test_skin_tone_is_one_cluster() {
+        // Waving hand + medium skin tone modifier
+        assert_eq!(grapheme_count("\u{1F44B}\u{1F3FD}"), 1);
+    }
+
+    #[test]
+    fn test_caret_steps_over_zwj_sequence() {
+        let text = format!("a{}b", FAMILY);
+
+        // Byte offsets
+        assert_eq!(next_grapheme_boundary(&text, 1), 1 + FAMILY.len());
+        assert_eq!(prev_grapheme_boundary(&text, 1 + FAMILY.len()), 1);
+        assert_eq!(next_grapheme_boundary(&text, text.len()), text.len());
+        assert_eq!(prev_grapheme_boundary(&text, 0), 0);
+
+        // Char indices: the family is 7 code points but one caret step
+        assert_eq!(move_caret_chars(&text, 1, 1), 8);
+        assert_eq!(move_caret_chars(&text, 8, -1), 1);
+        assert_eq!(move_caret_chars(&text, 0, 2), 8);
+        assert_eq!(move_caret_chars(&text, 9, -2), 1);
+        assert_eq!(move_caret_chars(&text, 9, 1), 9);
+        assert_eq!(move_caret_chars(&text, 0, -1), 0);
+        // A caret stranded inside the sequence snaps to its start
+        assert_eq!(move_caret_chars(&text, 4, 0), 1);
+    }
+}
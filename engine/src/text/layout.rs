@@ -0,0 +1,290 @@
+//! Geometry of laid-out text for carets and selection
+//!
+//! A `TextLayout` is the result of laying out a DrawText command: its
+//! visible lines with each cluster's position, in the coordinates the text
+//! is drawn at. It maps a point to the byte index a caret goes to, a byte
+//! index to the caret's rectangle, and a byte range to the rectangles that
+//! highlight it - the pieces a selectable text field needs.
+//!
+//! Clusters are stored left to right as drawn, so bidi text works: in a
+//! right-to-left cluster the caret before it is on its right edge. Byte
+//! indices handed back are always cluster boundaries.
+//!
+//! Layout here doesn't depend on a renderer; the backend fills it in from
+//! the same line layout it draws with.
+
+use std::ops::Range;
+
+use super::Direction;
+use crate::geometry::Rect;
+
+/// One grapheme cluster of a line, as drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutCluster {
+    /// Bytes of the cluster in the text; empty for an ellipsis, which sits
+    /// at the byte index where the cut text starts
+    pub range: Range<usize>,
+    /// Left edge of the cluster's advance
+    pub x: f32,
+    /// Pen advance to the next cluster, spacing included
+    pub advance: f32,
+    /// Whether the cluster is in a right-to-left run
+    pub rtl: bool,
+}
+
+impl LayoutCluster {
+    /// Byte index of the caret on the cluster's left edge
+    fn left_index(&self) -> usize {
+        if self.rtl { self.range.end } else { self.range.start }
+    }
+
+    /// Byte index of the caret on the cluster's right edge
+    fn right_index(&self) -> usize {
+        if self.rtl { self.range.start } else { self.range.end }
+    }
+}
+
+/// One visible line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLine {
+    /// Bytes of the text on the line, without the newline that ends it
+    pub range: Range<usize>,
+    /// Where the line starts when it has no clusters (an empty line)
+    pub x: f32,
+    /// Top of the line's row
+    pub top: f32,
+    /// Row height: the distance to the next line's top
+    pub height: f32,
+    /// Clusters left to right
+    pub clusters: Vec<LayoutCluster>,
+}
+
+impl LayoutLine {
+    fn left(&self) -> f32 {
+        self.clusters.first().map_or(self.x, |c| c.x)
+    }
+
+    fn right(&self) -> f32 {
+        self.clusters.last().map_or(self.x, |c| c.x + c.advance)
+    }
+
+    /// The cluster the line's text ends with, in logical order
+    fn logical_last(&self) -> Option<&LayoutCluster> {
+        self.clusters.iter().filter(|c| !c.range.is_empty()).max_by_key(|c| c.range.end)
+    }
+}
+
+/// Laid-out text, in the coordinates it is drawn at
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    /// Visible lines, top to bottom
+    pub lines: Vec<LayoutLine>,
+    /// Paragraph direction
+    pub direction: Direction,
+}
+
+impl TextLayout {
+    /// Byte index a click at (x, y) puts the caret at.
+    ///
+    /// Picks the line whose row contains y (the first or last line above or
+    /// below the text), then the nearer edge of the cluster under x. A click
+    /// past either end of a line goes to that end: the line's logical end
+    /// on the side the paragraph runs towards, its start on the other. On a
+    /// wrapped line the caret stays before the cluster the line ends with,
+    /// since the index after it is the next line's start.
+    pub fn hit_test(&self, x: f32, y: f32) -> usize {
+        let Some(line_idx) = self.line_at(y) else { return 0 };
+        let line = &self.lines[line_idx];
+        if line.clusters.is_empty() {
+            return line.range.start;
+        }
+
+        let index = if x < line.left() {
+            line.clusters[0].left_index()
+        } else if x >= line.right() {
+            line.clusters[line.clusters.len() - 1].right_index()
+        } else {
+            let cluster = line.clusters.iter()
+                .find(|c| x < c.x + c.advance)
+                .unwrap_or(&line.clusters[line.clusters.len() - 1]);
+            if x < cluster.x + cluster.advance / 2.0 { cluster.left_index() } else { cluster.right_index() }
+        };
+
+        let wraps = self.lines.get(line_idx + 1).is_some_and(|next| next.range.start == line.range.end);
+        match line.logical_last() {
+            Some(last) if wraps && index == line.range.end => last.range.start,
+            _ => index,
+        }
+    }
+
+    /// Caret rectangle for a byte index: zero width, as tall as its line.
+    ///
+    /// The index where a wrapped line ends is drawn at the start of the next
+    /// line, and one past the end of the text at the end of the last line.
+    /// Indices inside a cluster snap to its start.
+    pub fn cursor_rect(&self, byte_index: usize) -> Rect {
+        let Some(line) = self.line_for_index(byte_index) else {
+            return Rect::default();
+        };
+        let x = line.clusters.iter()
+            .filter(|c| !c.range.is_empty())
+            .find(|c| c.range.contains(&byte_index))
+            .map(|c| if c.rtl { c.x + c.advance } else { c.x })
+            .or_else(|| {
+                line.logical_last()
+                    .filter(|c| byte_index >= c.range.end)
+                    .map(|c| if c.rtl { c.x } else { c.x + c.advance })
+            })
+            .unwrap_or_else(|| match self.direction {
+                Direction::Ltr => line.left(),
+                Direction::Rtl => line.right(),
+            });
+        Rect::new(x, line.top, 0.0, line.height)
+    }
+
+    /// Rectangles highlighting the bytes `start..end` (in either order), one
+    /// per visible line the range covers.
+    ///
+    /// Each spans the selected clusters of its line from the leftmost to the
+    /// rightmost, so on a line mixing directions it also covers unselected
+    /// clusters drawn between them. Lines with nothing selected, empty lines
+    /// included, get no rectangle.
+    pub fn selection_rects(&self, start: usize, end: usize) -> Vec<Rect> {
+        let selected = start.min(end)..start.max(end);
+        self.lines
+            .iter()
+            .filter_map(|line| {
+                let (left, right) = line.clusters.iter()
+                    .filter(|c| !c.range.is_empty() && c.range.start < selected.end && selected.start < c.range.end)
+                    .fold(None, |span: Option<(f32, f32)>, c| {
+                        let (left, right) = span.unwrap_or((c.x, c.x + c.advance));
+                        Some((left.min(c.x), right.max(c.x + c.advance)))
+                    })?;
+                Some(Rect::new(left, line.top, right - left, line.height))
+            })
+            .collect()
+    }
+
+    /// Line whose row contains y, clamped to the first and last lines
+    fn line_at(&self, y: f32) -> Option<usize> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        Some(self.lines.iter().position(|line| y < line.top + line.height).unwrap_or(self.lines.len() - 1))
+    }
+
+    /// Line a caret at `byte_index` is drawn on
+    fn line_for_index(&self, byte_index: usize) -> Option<&LayoutLine> {
+        self.lines
+            .iter()
+            .find(|line| line.range.contains(&byte_index))
+            .or_else(|| self.lines.iter().rev().find(|line| line.range.end <= byte_index))
+            .or_else(|| self.lines.first())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A line with a 10-pixel cluster per (range, rtl) pair, in drawing order
+    fn line(range: Range<usize>, top: f32, clusters: &[(Range<usize>, bool)]) -> LayoutLine {
+        LayoutLine {
+            range,
+            x: 0.0,
+            top,
+            height: 20.0,
+            clusters: clusters
+                .iter()
+                .enumerate()
+                .map(|(i, (range, rtl))| LayoutCluster { range: range.clone(), x: i as f32 * 10.0, advance: 10.0, rtl: *rtl })
+                .collect(),
+        }
+    }
+
+    /// One-byte left-to-right clusters
+    fn ltr(range: Range<usize>) -> Vec<(Range<usize>, bool)> {
+        range.map(|i| (i..i + 1, false)).collect()
+    }
+
+    #[test]
+    fn test_hit_test_picks_nearer_edge_and_line_ends() {
+        // "hello " wrapped before "world"
+        let layout = TextLayout {
+            lines: vec![line(0..6, 0.0, &ltr(0..6)), line(6..11, 20.0, &ltr(6..11))],
+            direction: Direction::Ltr,
+        };
+
+        assert_eq!(layout.hit_test(12.0, 5.0), 1);
+        assert_eq!(layout.hit_test(16.0, 5.0), 2);
+        assert_eq!(layout.hit_test(-5.0, 25.0), 6);
+        // Past the end of the last line is the end of the text
+        assert_eq!(layout.hit_test(500.0, 25.0), 11);
+        // Past the end of a wrapped line stays on it, before the space
+        assert_eq!(layout.hit_test(500.0, 5.0), 5);
+        // Above and below the text clamp to the first and last lines
+        assert_eq!(layout.hit_test(1.0, -40.0), 0);
+        assert_eq!(layout.hit_test(1.0, 400.0), 6);
+
+        // Carets round-trip: the index a click gives is drawn where it was
+        assert_eq!(layout.cursor_rect(2), Rect::new(20.0, 0.0, 0.0, 20.0));
+        assert_eq!(layout.cursor_rect(6), Rect::new(0.0, 20.0, 0.0, 20.0));
+        assert_eq!(layout.cursor_rect(11), Rect::new(50.0, 20.0, 0.0, 20.0));
+        assert_eq!(layout.cursor_rect(99), Rect::new(50.0, 20.0, 0.0, 20.0));
+    }
+
+    #[test]
+    fn test_rtl_runs_mirror_caret_edges() {
+        // "ab " then three two-byte Arabic letters, drawn as "ab " + reversed
+        let mut clusters = ltr(0..3);
+        clusters.extend([(7..9, true), (5..7, true), (3..5, true)]);
+        let layout = TextLayout { lines: vec![line(0..9, 0.0, &clusters)], direction: Direction::Ltr };
+
+        // The first Arabic letter is drawn rightmost: clicking its right half
+        // puts the caret before it, its left half after it
+        assert_eq!(layout.hit_test(58.0, 5.0), 3);
+        assert_eq!(layout.hit_test(51.0, 5.0), 5);
+        assert_eq!(layout.cursor_rect(3).x, 60.0);
+        assert_eq!(layout.cursor_rect(5).x, 50.0);
+        // The end of the text is the left edge of the last Arabic letter
+        assert_eq!(layout.cursor_rect(9).x, 30.0);
+
+        // Selecting the first Arabic letter and the space before it
+        assert_eq!(layout.selection_rects(2, 5), vec![Rect::new(20.0, 0.0, 40.0, 20.0)]);
+
+        // In an RTL paragraph, clicks past the left end go to the end
+        let arabic = vec![(4..6, true), (2..4, true), (0..2, true)];
+        let layout = TextLayout { lines: vec![line(0..6, 0.0, &arabic)], direction: Direction::Rtl };
+        assert_eq!(layout.hit_test(-10.0, 5.0), 6);
+        assert_eq!(layout.hit_test(100.0, 5.0), 0);
+        assert_eq!(layout.cursor_rect(0).x, 30.0);
+    }
+
+    #[test]
+    fn test_selection_rects_per_line() {
+        let mut layout = TextLayout {
+            lines: vec![
+                line(0..6, 0.0, &ltr(0..6)),
+                line(7..7, 20.0, &[]),
+                line(8..12, 40.0, &ltr(8..12)),
+            ],
+            direction: Direction::Ltr,
+        };
+
+        assert_eq!(
+            layout.selection_rects(10, 3),
+            vec![Rect::new(30.0, 0.0, 30.0, 20.0), Rect::new(0.0, 40.0, 20.0, 20.0)],
+        );
+        assert!(layout.selection_rects(4, 4).is_empty());
+
+        // The empty line has no clusters but still takes a caret
+        assert_eq!(layout.cursor_rect(7), Rect::new(0.0, 20.0, 0.0, 20.0));
+        assert_eq!(layout.hit_test(30.0, 25.0), 7);
+
+        // An ellipsis maps to the cut and is never selected
+        layout.lines[2].clusters[3] = LayoutCluster { range: 11..11, x: 30.0, advance: 10.0, rtl: false };
+        layout.lines[2].range = 8..11;
+        assert_eq!(layout.hit_test(38.0, 45.0), 11);
+        assert_eq!(layout.selection_rects(8, 12), vec![Rect::new(0.0, 40.0, 30.0, 20.0)]);
+    }
+}
@@ -0,0 +1,187 @@
+//! Line break opportunities
+//!
+//! Where a line may wrap follows the Unicode line breaking algorithm
+//! (UAX #14) rather than splitting on ASCII spaces: CJK text can break
+//! between ideographs while Latin words stay whole, a no-break space
+//! (U+00A0) glues its neighbours together, and a zero-width space (U+200B)
+//! allows a break without drawing anything. `WordBreak` adjusts the result
+//! the way CSS `word-break` does. Breaks inside URLs are dropped, so a link
+//! is only split when it can't fit on a line by itself.
+//!
+//! Offsets are byte offsets into UTF-8 text; a break at offset `i` ends the
+//! line before byte `i`.
+
+use std::ops::Range;
+
+use unicode_linebreak::linebreaks;
+
+use super::{grapheme, WordBreak};
+
+/// Byte offsets where `text` may wrap, in increasing order.
+///
+/// Excludes 0 and `text.len()`; every offset is a grapheme boundary.
+pub fn break_opportunities(text: &str, word_break: WordBreak) -> Vec<usize> {
+    let mut breaks: Vec<usize> = match word_break {
+        WordBreak::BreakAll => grapheme::graphemes(text)
+            .map(|(start, _)| start)
+            .filter(|&i| i > 0 && !is_glued(text, i))
+            .collect(),
+        WordBreak::Normal | WordBreak::KeepAll | WordBreak::BreakWord => linebreaks(text)
+            .map(|(i, _)| i)
+            .filter(|&i| i < text.len())
+            .collect(),
+    };
+    if word_break == WordBreak::KeepAll {
+        breaks.retain(|&i| !between_letters(text, i));
+    }
+
+    let urls = url_ranges(text);
+    let boundaries: Vec<usize> = grapheme::graphemes(text).map(|(start, _)| start).collect();
+    breaks.retain(|&i| {
+        !urls.iter().any(|url| url.start < i && i < url.end) && boundaries.binary_search(&i).is_ok()
+    });
+    breaks
+}
+
+/// Greedy wrapping of one paragraph (text without newlines).
+///
+/// Each line grows a grapheme cluster at a time until `measure` of it
+/// exceeds `max_width`, then ends at its last break opportunity - or just
+/// before the cluster that overflowed if it has none, so a word or URL
+/// longer than a line is still split. A space before a break may hang past
+/// the edge, and spaces at the start of the next line are skipped.
+///
+/// Returns the byte range of each line.
+pub fn wrap_paragraph(
+    text: &str,
+    word_break: WordBreak,
+    max_width: f32,
+    mut measure: impl FnMut(&str) -> f32,
+) -> Vec<Range<usize>> {
+    let (offsets, clusters): (Vec<usize>, Vec<&str>) = grapheme::graphemes(text).unzip();
+    let breaks = break_opportunities(text, word_break);
+    // breakable[i]: a line may end before cluster i (or at the end of text)
+    let breakable: Vec<bool> = (0..=clusters.len())
+        .map(|i| i == clusters.len() || breaks.binary_search(&offsets[i]).is_ok())
+        .collect();
+    let offset_of = |i: usize| offsets.get(i).copied().unwrap_or(text.len());
+
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut last_break = 0;
+    let mut i = 0;
+    while i < clusters.len() {
+        if i > line_start && breakable[i] {
+            last_break = i;
+        }
+        if clusters[i].chars().all(char::is_whitespace) && breakable[i + 1] {
+            last_break = i + 1;
+        }
+
+        let width = measure(&text[offset_of(line_start)..offset_of(i + 1)]);
+        if width > max_width && i > line_start {
+            let break_point = if last_break > line_start { last_break } else { i };
+            lines.push(offset_of(line_start)..offset_of(break_point));
+
+            line_start = break_point;
+            while line_start < clusters.len() && clusters[line_start] == " " {
+                line_start += 1;
+            }
+            i = line_start;
+            last_break = line_start;
+            continue;
+        }
+        i += 1;
+    }
+
+    if line_start < clusters.len() {
+        lines.push(offset_of(line_start)..text.len());
+    }
+    lines
+}
+
+/// Whether the characters around `i` are joined by a no-break character
+fn is_glued(text: &str, i: usize) -> bool {
+    const GLUE: [char; 4] = ['\u{00A0}', '\u{202F}', '\u{2060}', '\u{FEFF}'];
+    let before = text[..i].chars().next_back();
+    let after = text[i..].chars().next();
+    before.is_some_and(|c| GLUE.contains(&c)) || after.is_some_and(|c| GLUE.contains(&c))
+}
+
+/// Whether `i` falls between two letters or digits (of any script)
+fn between_letters(text: &str, i: usize) -> bool {
+    let before = text[..i].chars().next_back();
+    let after = text[i..].chars().next();
+    before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric)
+}
+
+/// Byte ranges of the URL-like words in `text`
+fn url_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                let word = &text[s..i];
+                if word.contains("://") || word.starts_with("www.") {
+                    ranges.push(s..i);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrap with every cluster one unit wide
+    fn wrap(text: &str, word_break: WordBreak, max_width: f32) -> Vec<&str> {
+        wrap_paragraph(text, word_break, max_width, |line| grapheme::grapheme_count(line) as f32)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_cjk_breaks_between_ideographs_not_inside_words() {
+        assert_eq!(wrap("我爱Rust编程", WordBreak::Normal, 4.0), vec!["我爱", "Rust", "编程"]);
+        assert_eq!(wrap("日本語のテキスト", WordBreak::Normal, 3.0), vec!["日本語", "のテキ", "スト"]);
+
+        // keep-all holds CJK runs together, like Latin words
+        assert_eq!(wrap("日本語 テキスト", WordBreak::KeepAll, 5.0), vec!["日本語 ", "テキスト"]);
+        // break-all splits Latin words too
+        assert_eq!(wrap("abcdef", WordBreak::BreakAll, 4.0), vec!["abcd", "ef"]);
+    }
+
+    #[test]
+    fn test_no_break_space_prevents_break() {
+        assert_eq!(wrap("go to page", WordBreak::Normal, 7.0), vec!["go to ", "page"]);
+        assert_eq!(wrap("go to\u{A0}page", WordBreak::Normal, 7.0), vec!["go ", "to\u{A0}page"]);
+
+        // Even break-all doesn't split at a no-break space
+        let text = "go to\u{A0}page";
+        let nbsp = text.find('\u{A0}').unwrap();
+        let breaks = break_opportunities(text, WordBreak::BreakAll);
+        assert!(!breaks.contains(&nbsp) && !breaks.contains(&(nbsp + '\u{A0}'.len_utf8())));
+
+        // A zero-width space is a break opportunity
+        assert_eq!(wrap("foo\u{200B}bar", WordBreak::Normal, 4.0), vec!["foo\u{200B}", "bar"]);
+    }
+
+    #[test]
+    fn test_urls_break_only_when_necessary() {
+        let text = "see https://example.com/a-b/c";
+        let url = text.find("https").unwrap();
+        assert!(break_opportunities(text, WordBreak::Normal).iter().all(|&i| i <= url));
+        assert_eq!(wrap(text, WordBreak::Normal, 30.0), vec![text]);
+        assert_eq!(wrap(text, WordBreak::Normal, 26.0), vec!["see ", "https://example.com/a-b/c"]);
+
+        // Too long for any line: split where it overflows
+        assert_eq!(wrap(text, WordBreak::Normal, 20.0), vec!["see ", "https://example.com/", "a-b/c"]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Rich text: styled text runs and inline images in one paragraph
+//!
+//! A `DrawRichText` paragraph is a list of runs - text with its own color
+//! and font, or an image that sits in the line like a glyph. The runs flow
+//! together: wrapping follows the same UAX #14 rules as DrawText across run
+//! boundaries (to the line breaker an image is U+FFFC, the object
+//! replacement character), and everything on a line shares one baseline.
+//!
+//! Layout here doesn't depend on a renderer. The caller measures text and
+//! reports font metrics, then draws each positioned piece as a plain
+//! DrawText or DrawImage, so single-style text keeps the DrawText fast path.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::{linebreak, Direction, FontDescriptor, TextAlign, TextLayoutConfig, WhiteSpace};
+
+/// What an inline image stands in for when finding line breaks
+const OBJECT_REPLACEMENT: char = '\u{FFFC}';
+
+/// One run of a rich text paragraph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextRun {
+    /// Text in one color and font
+    Text {
+        text: String,
+        #[serde(default)]
+        color: u32,
+        font: FontDescriptor,
+    },
+    /// An inline image drawn `width` x `height` logical pixels
+    Image {
+        image_texture_id: u32,
+        width: f32,
+        height: f32,
+        /// How far the image's bottom edge sits below the baseline; 0 rests
+        /// it on the baseline like a capital letter
+        #[serde(default)]
+        baseline_offset: f32,
+    },
+}
+
+/// A run's share of one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct RichPiece {
+    /// Index of the run
+    pub run: usize,
+    /// Byte range of the run's text on this line (empty for an image)
+    pub range: Range<usize>,
+    /// Left edge, relative to the paragraph's x
+    pub x: f32,
+    pub width: f32,
+}
+
+/// One laid-out line of a rich text paragraph
+#[derive(Debug, Clone, PartialEq)]
+pub struct RichLine {
+    pub pieces: Vec<RichPiece>,
+    /// Baseline, relative to the paragraph's top
+    pub baseline: f32,
+    /// Width without trailing spaces
+    pub width: f32,
+    /// Tallest extent above the baseline (font ascent or image top)
+    pub ascent: f32,
+    /// Deepest extent below the baseline (font descent or image bottom)
+    pub descent: f32,
+}
+
+/// A laid-out rich text paragraph, in the caller's units
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RichTextLayout {
+    pub lines: Vec<RichLine>,
+    /// Widest line
+    pub width: f32,
+    /// From the top of the first line to the bottom of the last
+    pub height: f32,
+}
+
+/// Lay out `runs` as one paragraph.
+///
+/// `measure(run, text)` is the advance of `text` in text run `run`'s font,
+/// and `metrics(run)` its (ascent, descent). Uses `max_width` (when
+/// `white_space` wraps), `max_lines`, `line_height`, `alignment` and
+/// `word_break` from `layout`; Start and End follow `base_direction`, and
+/// Justify lays out like Start. Runs aren't reordered for bidi text.
+///
+/// Lines are filled greedily a word at a time, where a word runs between
+/// break opportunities and may span several runs. Spaces at the end of a
+/// line hang past the edge; a word wider than a line overflows it. `\n`
+/// ends a line. Baselines are spaced so that single-style text lines up
+/// with DrawText: the first sits one ascent below the top, and each line
+/// adds the leading `line_height` gives its fonts below its descent.
+pub fn layout_rich_text(
+    runs: &[TextRun],
+    layout: &TextLayoutConfig,
+    mut measure: impl FnMut(usize, &str) -> f32,
+    mut metrics: impl FnMut(usize) -> (f32, f32),
+) -> RichTextLayout {
+    // The paragraph as the line breaker sees it, and where each run starts
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(runs.len());
+    for run in runs {
+        starts.push(text.len());
+        match run {
+            TextRun::Text { text: run_text, .. } => text.push_str(run_text),
+            TextRun::Image { .. } => text.push(OBJECT_REPLACEMENT),
+        }
+    }
+    if text.is_empty() {
+        return RichTextLayout::default();
+    }
+    let run_at = |offset: usize| starts.partition_point(|&start| start <= offset) - 1;
+
+    let wrap_width = match layout.white_space {
+        WhiteSpace::Normal | WhiteSpace::PreWrap => layout.max_width.unwrap_or(f32::INFINITY),
+        WhiteSpace::NoWrap | WhiteSpace::Pre => f32::INFINITY,
+    };
+
+    let mut cuts = vec![0];
+    cuts.extend(linebreak::break_opportunities(&text, layout.word_break));
+    cuts.push(text.len());
+
+    let mut lines: Vec<Vec<RichPiece>> = Vec::new();
+    let mut widths: Vec<f32> = Vec::new();
+    let mut pieces: Vec<RichPiece> = Vec::new();
+    let mut x = 0.0;
+    let mut line_width = 0.0;
+
+    for word in cuts.windows(2).map(|w| w[0]..w[1]) {
+        let hard_break = text[word.clone()].ends_with('\n');
+
+        // Split the word at run boundaries, dropping a trailing newline
+        let mut segments: Vec<(usize, Range<usize>, f32)> = Vec::new();
+        let mut start = word.start;
+        while start < word.end {
+            let run = run_at(start);
+            let end = starts.get(run + 1).copied().unwrap_or(text.len()).min(word.end);
+            let local = start - starts[run]..end - starts[run];
+            let segment = match &runs[run] {
+                TextRun::Text { text: run_text, .. } => {
+                    let kept = run_text[local.clone()].trim_end_matches(['\n', '\r']).len();
+                    let local = local.start..local.start + kept;
+                    let width = measure(run, &run_text[local.clone()]);
+                    (run, local, width)
+                }
+                TextRun::Image { width, .. } => (run, 0..0, *width),
+            };
+            segments.push(segment);
+            start = end;
+        }
+
+        let word_width: f32 = segments.iter().map(|(_, _, width)| width).sum();
+        // Trailing spaces may hang past the edge, so fit without them
+        let hanging = match segments.last() {
+            Some((run, range, width)) => match &runs[*run] {
+                TextRun::Text { text: run_text, .. } if run_text[range.clone()].ends_with(char::is_whitespace) => {
+                    width - measure(*run, run_text[range.clone()].trim_end())
+                }
+                _ => 0.0,
+            },
+            None => 0.0,
+        };
+
+        if !pieces.is_empty() && x + word_width - hanging > wrap_width {
+            lines.push(std::mem::take(&mut pieces));
+            widths.push(line_width);
+            x = 0.0;
+        }
+
+        for (run, range, width) in segments {
+            match pieces.last_mut() {
+                Some(last) if last.run == run && last.range.end == range.start => {
+                    last.range.end = range.end;
+                    last.width += width;
+                }
+                _ => pieces.push(RichPiece { run, range, x, width }),
+            }
+            x += width;
+        }
+        line_width = x - hanging;
+
+        if hard_break {
+            lines.push(std::mem::take(&mut pieces));
+            widths.push(line_width);
+            x = 0.0;
+            line_width = 0.0;
+        }
+    }
+    if !pieces.is_empty() {
+        lines.push(pieces);
+        widths.push(line_width);
+    }
+    if let Some(max_lines) = layout.max_lines {
+        lines.truncate(max_lines);
+    }
+
+    // Each text run's font metrics, fetched once
+    let font_metrics: Vec<Option<(f32, f32)>> = runs
+        .iter()
+        .enumerate()
+        .map(|(i, run)| matches!(run, TextRun::Text { .. }).then(|| metrics(i)))
+        .collect();
+
+    let direction = layout.base_direction.resolve(&text);
+    let box_width = layout.max_width
+        .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+    let mut result = RichTextLayout::default();
+    let mut previous_bottom: Option<f32> = None;
+    for (pieces, width) in lines.into_iter().zip(widths) {
+        let (mut ascent, mut descent, mut leading) = (0.0f32, 0.0f32, 0.0f32);
+        for piece in &pieces {
+            match (&runs[piece.run], font_metrics[piece.run]) {
+                (TextRun::Text { .. }, Some((font_ascent, font_descent))) => {
+                    ascent = ascent.max(font_ascent);
+                    descent = descent.max(font_descent);
+                    leading = leading.max((font_ascent + font_descent) * (layout.line_height - 1.0));
+                }
+                (TextRun::Image { height, baseline_offset, .. }, _) => {
+                    ascent = ascent.max(height - baseline_offset);
+                    descent = descent.max(*baseline_offset);
+                }
+                _ => {}
+            }
+        }
+
+        let baseline = previous_bottom.unwrap_or(0.0) + ascent;
+        previous_bottom = Some(baseline + descent + leading);
+
+        let shift = match layout.alignment.resolve(direction) {
+            TextAlign::Center => (box_width - width) / 2.0,
+            TextAlign::Right => box_width - width,
+            TextAlign::Justify if direction == Direction::Rtl => box_width - width,
+            TextAlign::Left | TextAlign::Justify | TextAlign::Start | TextAlign::End => 0.0,
+        };
+        let pieces = pieces
+            .into_iter()
+            .map(|piece| RichPiece { x: piece.x + shift, ..piece })
+            .collect();
+
+        result.width = result.width.max(width);
+        result.height = baseline + descent;
+        result.lines.push(RichLine { pieces, baseline, width, ascent, descent });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_run(text: &str, color: u32) -> TextRun {
+        TextRun::Text { text: text.to_string(), color, font: FontDescriptor::default() }
+    }
+
+    /// Lay out with every char 10 wide and fonts 8 up, 2 down
+    fn layout(runs: &[TextRun], layout: &TextLayoutConfig) -> RichTextLayout {
+        layout_rich_text(runs, layout, |_, text| text.chars().count() as f32 * 10.0, |_| (8.0, 2.0))
+    }
+
+    #[test]
+    fn test_runs_and_image_wrap_on_a_shared_baseline() {
+        let runs = vec![
+            text_run("Hello ", 0xFF0000FF),
+            TextRun::Image { image_texture_id: 7, width: 12.0, height: 12.0, baseline_offset: 2.0 },
+            text_run(" world wide", 0x0000FFFF),
+        ];
+        let config = TextLayoutConfig { max_width: Some(100.0), line_height: 1.5, ..Default::default() };
+        let result = layout(&runs, &config);
+        assert_eq!(result.lines.len(), 2);
+
+        // "Hello " [image] " " | "world wide": the space after the image
+        // hangs, and the blue run continues as one piece on the next line
+        let first = &result.lines[0];
+        let spans: Vec<(usize, Range<usize>, f32)> =
+            first.pieces.iter().map(|p| (p.run, p.range.clone(), p.x)).collect();
+        assert_eq!(spans, vec![(0, 0..6, 0.0), (1, 0..0, 60.0), (2, 0..1, 72.0)]);
+        assert_eq!(first.width, 72.0);
+        let second = &result.lines[1];
+        assert_eq!(second.pieces, vec![RichPiece { run: 2, range: 1..11, x: 0.0, width: 100.0 }]);
+
+        // The image rises 10 above the baseline, so the first line's text
+        // sits lower than a text-only line would; the image's top is the
+        // paragraph's top
+        assert_eq!((first.baseline, first.ascent, first.descent), (10.0, 10.0, 2.0));
+        assert_eq!(first.baseline - (12.0 - 2.0), 0.0);
+        // Next baseline: descent 2 + leading (8 + 2) * 0.5 + ascent 8
+        assert_eq!(second.baseline, 25.0);
+        assert_eq!((result.width, result.height), (100.0, 27.0));
+
+        // Centering shifts whole lines
+        let centered = layout(&runs, &TextLayoutConfig { alignment: TextAlign::Center, ..config.clone() });
+        let xs: Vec<f32> = centered.lines[0].pieces.iter().map(|p| p.x).collect();
+        assert_eq!(xs, vec![14.0, 74.0, 86.0]);
+
+        // A newline ends the line; an empty line still takes its font's height
+        let runs = vec![text_run("a\n\n", 0x000000FF), text_run("b", 0x000000FF)];
+        let result = layout(&runs, &TextLayoutConfig { line_height: 1.0, ..Default::default() });
+        let baselines: Vec<f32> = result.lines.iter().map(|l| l.baseline).collect();
+        assert_eq!(baselines, vec![8.0, 18.0, 28.0]);
+        assert_eq!(result.lines[0].pieces[0].range, 0..1);
+    }
+}
@@ -7,6 +7,9 @@
 //! - Line spacing and letter spacing
 //! - Bidirectional text (TODO)
 //! - Complex scripts (handled by platform - Core Text, HarfBuzz, DirectWrite)
+//!
+//! Glyphs are produced per extended grapheme cluster, so emoji ZWJ sequences
+//! and skin-tone modifiers map to a single glyph (see [`crate::text::grapheme`]).
 
 use super::{FontDescriptor, TextLayoutConfig, TextAlign, WordBreak, TextOverflow};
 use crate::text::font_manager::{Font, FontError, FontManager};
@@ -35,9 +38,13 @@ pub struct ShapedGlyph {
     /// Glyph ID (for atlas lookup)
     pub glyph_id: u32,
 
-    /// Character this glyph represents (for debugging/fallback)
+    /// Character this glyph represents (for debugging/fallback).
+    /// For multi-code-point clusters this is the cluster's first char.
     pub character: char,
 
+    /// Byte offset of the grapheme cluster this glyph renders
+    pub cluster: usize,
+
     /// X position relative to text origin
     pub x: f32,
 
@@ -197,6 +204,7 @@ mod tests {
         let glyph = ShapedGlyph {
             glyph_id: 42,
             character: 'A',
+            cluster: 0,
             x: 10.0,
             y: 20.0,
             advance: 12.0,
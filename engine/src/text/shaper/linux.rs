@@ -5,6 +5,8 @@
 
 use super::{ShapedGlyph, ShapedLine, ShapedText, ShaperError, TextShaper};
 use crate::text::font_manager::Font;
+use crate::text::grapheme;
+use crate::text::spacing::Spacing;
 use crate::text::{TextAlign, TextLayoutConfig, WordBreak};
 
 /// Linux text shaper
@@ -23,35 +25,18 @@ impl LinuxTextShaper {
         Self
     }
 
-    /// Shape a single line of text
-    fn shape_line(
-        &self,
-        text: &str,
-        font: &dyn Font,
-        baseline_y: f32,
-        alignment: TextAlign,
-        max_width: f32,
-    ) -> ShapedLine {
-        // Calculate line width
-        let line_width = font.measure_text(text);
-
-        // Calculate X offset based on alignment
-        let x_offset = match alignment {
-            TextAlign::Left => 0.0,
-            TextAlign::Center => ((max_width - line_width) / 2.0).max(0.0),
-            TextAlign::Right => (max_width - line_width).max(0.0),
-            TextAlign::Justify => 0.0, // TODO: Implement justify
-        };
-
-        // Shape glyphs
+    /// Shape glyphs for a run of text from x = 0, before spacing
+    fn shape_glyphs(&self, text: &str, font: &dyn Font, baseline_y: f32) -> Vec<ShapedGlyph> {
         let mut glyphs = Vec::new();
-        let mut current_x = x_offset;
+        let mut current_x = 0.0;
 
-        for ch in text.chars() {
+        for (cluster, g) in grapheme::graphemes(text) {
+            let Some(ch) = grapheme::base_char(g) else { continue };
             if let Some(metrics) = font.glyph_metrics(ch) {
                 glyphs.push(ShapedGlyph {
                     glyph_id: metrics.glyph_id,
                     character: ch,
+                    cluster,
                     x: current_x,
                     y: baseline_y,
                     advance: metrics.advance,
@@ -66,6 +51,41 @@ impl LinuxTextShaper {
                 }
             }
         }
+        glyphs
+    }
+
+    /// Width of a run of text with spacing, measured from its shaped glyphs
+    /// so it matches what `shape_line` draws
+    fn measure(&self, text: &str, font: &dyn Font, spacing: Spacing) -> f32 {
+        spacing.measure(&self.shape_glyphs(text, font, 0.0), text)
+    }
+
+    /// Shape a single line of text
+    fn shape_line(
+        &self,
+        text: &str,
+        font: &dyn Font,
+        baseline_y: f32,
+        alignment: TextAlign,
+        max_width: f32,
+        spacing: Spacing,
+    ) -> ShapedLine {
+        // Shape glyphs and measure the line from them
+        let mut glyphs = self.shape_glyphs(text, font, baseline_y);
+        let line_width = spacing.measure(&glyphs, text);
+        spacing.apply(&mut glyphs, text);
+
+        // Calculate X offset based on alignment
+        let x_offset = match alignment {
+            // Start and End are resolved by the caller
+            TextAlign::Left | TextAlign::Start | TextAlign::End => 0.0,
+            TextAlign::Center => ((max_width - line_width) / 2.0).max(0.0),
+            TextAlign::Right => (max_width - line_width).max(0.0),
+            TextAlign::Justify => 0.0, // TODO: Implement justify
+        };
+        for glyph in &mut glyphs {
+            glyph.x += x_offset;
+        }
 
         ShapedLine {
             glyphs,
@@ -78,7 +98,7 @@ impl LinuxTextShaper {
     }
 
     /// Break text into lines based on max_width and word break rules
-    fn break_lines(&self, text: &str, font: &dyn Font, max_width: f32, word_break: WordBreak) -> Vec<String> {
+    fn break_lines(&self, text: &str, font: &dyn Font, max_width: f32, word_break: WordBreak, spacing: Spacing) -> Vec<String> {
         if max_width <= 0.0 || max_width == f32::MAX {
             // No wrapping, but split on explicit newlines
             return text.lines().map(String::from).collect();
@@ -95,8 +115,9 @@ impl LinuxTextShaper {
                 WordBreak::Normal | WordBreak::BreakWord => {
                     // Break at word boundaries, and break long words if needed (BreakWord)
                     for word in paragraph.split_whitespace() {
-                        let word_width = font.measure_text(word);
-                        let space_width = font.measure_text(" ");
+                        let word_width = self.measure(word, font, spacing);
+                        // Once joined, the space sits between two letter-spacing gaps
+                        let space_width = self.measure(" ", font, spacing) + 2.0 * spacing.letter;
 
                         if current_width + word_width <= max_width {
                             if !current_line.is_empty() {
@@ -107,12 +128,12 @@ impl LinuxTextShaper {
                             current_width += word_width;
                         } else if word_break == WordBreak::BreakWord && word_width > max_width {
                             // Word is too long, break it character by character
-                            for ch in word.chars() {
-                                let char_str = ch.to_string();
-                                let char_width = font.measure_text(&char_str);
+                            for (_, cluster) in grapheme::graphemes(word) {
+                                let char_str = cluster.to_string();
+                                let char_width = self.measure(&char_str, font, spacing) + spacing.letter;
 
                                 if current_width + char_width <= max_width {
-                                    current_line.push(ch);
+                                    current_line.push_str(cluster);
                                     current_width += char_width;
                                 } else {
                                     if !current_line.is_empty() {
@@ -133,13 +154,13 @@ impl LinuxTextShaper {
                     }
                 }
                 WordBreak::BreakAll => {
-                    // Break at any character
-                    for ch in paragraph.chars() {
-                        let char_str = ch.to_string();
-                        let char_width = font.measure_text(&char_str);
+                    // Break at any grapheme cluster
+                    for (_, cluster) in grapheme::graphemes(paragraph) {
+                        let char_str = cluster.to_string();
+                        let char_width = self.measure(&char_str, font, spacing) + spacing.letter;
 
                         if current_width + char_width <= max_width {
-                            current_line.push(ch);
+                            current_line.push_str(cluster);
                             current_width += char_width;
                         } else {
                             if !current_line.is_empty() {
@@ -188,7 +209,8 @@ impl TextShaper for LinuxTextShaper {
 
         // Break text into lines
         let max_width = config.max_width.unwrap_or(f32::MAX);
-        let line_strings = self.break_lines(text, font, max_width, config.word_break);
+        let spacing = Spacing::from_layout(config, font.size());
+        let line_strings = self.break_lines(text, font, max_width, config.word_break, spacing);
 
         // Shape each line
         let mut shaped_lines = Vec::new();
@@ -200,8 +222,9 @@ impl TextShaper for LinuxTextShaper {
                 line_text,
                 font,
                 current_y,
-                config.alignment,
+                config.alignment.resolve(config.base_direction.resolve(text)),
                 max_width,
+                spacing,
             );
 
             shaped_lines.push(shaped_line);
@@ -298,6 +321,27 @@ mod tests {
         assert_eq!(shaped.lines[0].glyphs.len(), 5);
     }
 
+    #[test]
+    fn test_zwj_sequence_shapes_as_one_cluster() {
+        let shaper = LinuxTextShaper::new();
+        let font = MockFont::new(8.0);
+        let config = TextLayoutConfig::default();
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        let text = format!("a{}b", family);
+
+        let shaped = shaper.shape_text(&text, &font, &config).unwrap();
+        let glyphs = &shaped.lines[0].glyphs;
+        assert_eq!(glyphs.len(), 3);
+        assert_eq!(glyphs[1].cluster, 1);
+        assert_eq!(glyphs[1].character, '\u{1F468}');
+        assert_eq!(glyphs[2].cluster, 1 + family.len());
+        assert!((shaped.lines[0].width - 24.0).abs() < 0.01);
+
+        // The caret steps from before the family to after it in one move
+        let after = crate::text::grapheme::next_grapheme_boundary(&text, glyphs[1].cluster);
+        assert_eq!(after, glyphs[2].cluster);
+    }
+
     #[test]
     fn test_line_breaking() {
         let shaper = LinuxTextShaper::new();
@@ -4,6 +4,8 @@
 
 use super::{ShapedGlyph, ShapedLine, ShapedText, ShaperError, TextShaper};
 use crate::text::font_manager::Font;
+use crate::text::grapheme;
+use crate::text::spacing::Spacing;
 use crate::text::{TextAlign, TextLayoutConfig, WordBreak};
 use core_foundation::attributed_string::CFMutableAttributedString;
 use core_foundation::base::{CFRange, TCFType};
@@ -38,7 +40,38 @@ impl MacOSTextShaper {
         attr_string
     }
 
+    /// Shape glyphs for a run of text from x = 0, before spacing
+    fn shape_glyphs(&self, text: &str, font: &dyn Font, baseline_y: f32) -> Vec<ShapedGlyph> {
+        let mut glyphs = Vec::new();
+        let mut current_x = 0.0;
+
+        for (cluster, g) in grapheme::graphemes(text) {
+            let Some(ch) = grapheme::base_char(g) else { continue };
+            if let Some(metrics) = font.glyph_metrics(ch) {
+                glyphs.push(ShapedGlyph {
+                    glyph_id: metrics.glyph_id,
+                    character: ch,
+                    cluster,
+                    x: current_x,
+                    y: baseline_y,
+                    advance: metrics.advance,
+                    width: metrics.width,
+                    height: metrics.height,
+                });
+                current_x += metrics.advance;
+            }
+        }
+        glyphs
+    }
+
+    /// Width of a run of text with spacing, measured from its shaped glyphs
+    /// so it matches what `shape_line` draws
+    fn measure(&self, text: &str, font: &dyn Font, spacing: Spacing) -> f32 {
+        spacing.measure(&self.shape_glyphs(text, font, 0.0), text)
+    }
+
     /// Shape a single line of text
+    #[allow(clippy::too_many_arguments)]
     fn shape_line(
         &self,
         text: &str,
@@ -47,39 +80,27 @@ impl MacOSTextShaper {
         baseline_y: f32,
         alignment: TextAlign,
         max_width: f32,
+        spacing: Spacing,
     ) -> ShapedLine {
         let attr_string = self.create_attributed_string(text, ct_font);
         let line = CTLine::new_with_attributed_string(attr_string.as_concrete_TypeRef());
 
-        // Get typographic bounds
+        // Shape glyphs; spacing goes between the clusters they were shaped
+        // into, so the width counts the same gaps `apply` inserts
+        let mut glyphs = self.shape_glyphs(text, font, baseline_y);
         let bounds = line.get_typographic_bounds();
-        let line_width = bounds.width as f32;
+        let line_width = bounds.width as f32 + spacing.apply(&mut glyphs, text);
 
         // Calculate X offset based on alignment
         let x_offset = match alignment {
-            TextAlign::Left => 0.0,
+            // Start and End are resolved by the caller
+            TextAlign::Left | TextAlign::Start | TextAlign::End => 0.0,
             TextAlign::Center => (max_width - line_width) / 2.0,
             TextAlign::Right => max_width - line_width,
             TextAlign::Justify => 0.0, // TODO: Use CTLine justification
         };
-
-        // Shape glyphs
-        let mut glyphs = Vec::new();
-        let mut current_x = x_offset;
-
-        for ch in text.chars() {
-            if let Some(metrics) = font.glyph_metrics(ch) {
-                glyphs.push(ShapedGlyph {
-                    glyph_id: metrics.glyph_id,
-                    character: ch,
-                    x: current_x,
-                    y: baseline_y,
-                    advance: metrics.advance,
-                    width: metrics.width,
-                    height: metrics.height,
-                });
-                current_x += metrics.advance;
-            }
+        for glyph in &mut glyphs {
+            glyph.x += x_offset;
         }
 
         ShapedLine {
@@ -93,7 +114,7 @@ impl MacOSTextShaper {
     }
 
     /// Break text into lines based on max_width and word break rules
-    fn break_lines(&self, text: &str, font: &dyn Font, max_width: f32, word_break: WordBreak) -> Vec<String> {
+    fn break_lines(&self, text: &str, font: &dyn Font, max_width: f32, word_break: WordBreak, spacing: Spacing) -> Vec<String> {
         if max_width <= 0.0 {
             // No wrapping, return entire text as single line
             return vec![text.to_string()];
@@ -107,8 +128,9 @@ impl MacOSTextShaper {
             WordBreak::Normal | WordBreak::BreakWord => {
                 // Break at word boundaries, and break long words if needed (BreakWord)
                 for word in text.split_whitespace() {
-                    let word_width = font.measure_text(word);
-                    let space_width = font.measure_text(" ");
+                    let word_width = self.measure(word, font, spacing);
+                    // Once joined, the space sits between two letter-spacing gaps
+                    let space_width = self.measure(" ", font, spacing) + 2.0 * spacing.letter;
 
                     if current_width + word_width <= max_width {
                         if !current_line.is_empty() {
@@ -119,12 +141,12 @@ impl MacOSTextShaper {
                         current_width += word_width;
                     } else if word_break == WordBreak::BreakWord && word_width > max_width {
                         // Word is too long, break it character by character
-                        for ch in word.chars() {
-                            let char_str = ch.to_string();
-                            let char_width = font.measure_text(&char_str);
+                        for (_, cluster) in grapheme::graphemes(word) {
+                            let char_str = cluster.to_string();
+                            let char_width = self.measure(&char_str, font, spacing) + spacing.letter;
 
                             if current_width + char_width <= max_width {
-                                current_line.push(ch);
+                                current_line.push_str(cluster);
                                 current_width += char_width;
                             } else {
                                 if !current_line.is_empty() {
@@ -145,13 +167,13 @@ impl MacOSTextShaper {
                 }
             }
             WordBreak::BreakAll => {
-                // Break at any character
-                for ch in text.chars() {
-                    let char_str = ch.to_string();
-                    let char_width = font.measure_text(&char_str);
+                // Break at any grapheme cluster
+                for (_, cluster) in grapheme::graphemes(text) {
+                    let char_str = cluster.to_string();
+                    let char_width = self.measure(&char_str, font, spacing) + spacing.letter;
 
                     if current_width + char_width <= max_width {
-                        current_line.push(ch);
+                        current_line.push_str(cluster);
                         current_width += char_width;
                     } else {
                         if !current_line.is_empty() {
@@ -201,7 +223,8 @@ impl TextShaper for MacOSTextShaper {
 
         // Break text into lines
         let max_width = config.max_width.unwrap_or(f32::MAX);
-        let line_strings = self.break_lines(text, font, max_width, config.word_break);
+        let spacing = Spacing::from_layout(config, font.size());
+        let line_strings = self.break_lines(text, font, max_width, config.word_break, spacing);
 
         // Shape each line
         let mut shaped_lines = Vec::new();
@@ -214,8 +237,9 @@ impl TextShaper for MacOSTextShaper {
                 font,
                 &ct_font,
                 current_y,
-                config.alignment,
+                config.alignment.resolve(config.base_direction.resolve(text)),
                 max_width,
+                spacing,
             );
 
             let effective_line_height = font_size * line_height_multiplier;
@@ -0,0 +1,248 @@
+//! Cache of laid-out text
+//!
+//! Labels and button captions draw the same strings every frame. Laying
+//! them out (shaping clusters, measuring, breaking lines, truncating and
+//! reordering for bidi) is the bulk of DrawText's cost, so the result is
+//! kept per (text, font, layout-affecting fields) and reused until it falls
+//! out of the least-recently-used cap. Text measurement goes through the
+//! same layout, so it shares the cache.
+//!
+//! Fields that only move the laid-out text (position, color, alignment,
+//! vertical anchor) are not part of the key.
+
+use super::{FontDescriptor, TextLayoutConfig};
+use std::collections::HashMap;
+
+/// Entries kept by default
+pub const DEFAULT_SHAPING_CACHE_CAPACITY: usize = 512;
+
+/// What a cached layout depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShapingKey {
+    text: String,
+    /// `FontDescriptor::cache_key` of the font at its logical size
+    font: String,
+    /// Scale factor the layout is in physical pixels for (bits)
+    scale: u32,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    max_lines: Option<usize>,
+    line_height: u32,
+    letter_spacing: u32,
+    word_spacing: u32,
+    word_break: u8,
+    overflow: u8,
+    white_space: u8,
+    base_direction: u8,
+}
+
+impl ShapingKey {
+    pub fn new(text: &str, font: &FontDescriptor, layout: &TextLayoutConfig, scale: f32) -> Self {
+        Self {
+            text: text.to_string(),
+            font: font.cache_key(),
+            scale: scale.to_bits(),
+            max_width: layout.max_width.map(f32::to_bits),
+            max_height: layout.max_height.map(f32::to_bits),
+            max_lines: layout.max_lines,
+            line_height: layout.line_height.to_bits(),
+            letter_spacing: layout.letter_spacing.to_bits(),
+            word_spacing: layout.word_spacing.to_bits(),
+            word_break: layout.word_break as u8,
+            overflow: layout.overflow as u8,
+            white_space: layout.white_space as u8,
+            base_direction: layout.base_direction as u8,
+        }
+    }
+}
+
+/// Hit and miss counts of a shaping cache
+#[derive(Debug, Clone, Default)]
+pub struct ShapingCacheMetrics {
+    /// Lookups answered from the cache
+    pub hits: u64,
+
+    /// Lookups that had to lay the text out
+    pub misses: u64,
+
+    /// Entries dropped to stay within the capacity
+    pub evictions: u64,
+}
+
+impl ShapingCacheMetrics {
+    /// Get cache hit rate (0.0 - 1.0)
+    pub fn hit_rate(&self) -> f32 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f32 / lookups as f32
+    }
+}
+
+struct CachedLayout<V> {
+    layout: V,
+    /// Lookup tick of the last hit or insert
+    last_used: u64,
+}
+
+/// Least-recently-used map from `ShapingKey` to a laid-out text
+pub struct ShapingCache<V> {
+    entries: HashMap<ShapingKey, CachedLayout<V>>,
+    capacity: usize,
+    tick: u64,
+    metrics: ShapingCacheMetrics,
+}
+
+impl<V: Clone> ShapingCache<V> {
+    /// Create a cache holding up to `capacity` layouts; 0 disables it
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            tick: 0,
+            metrics: ShapingCacheMetrics::default(),
+        }
+    }
+
+    /// The cached layout for `key`, counted as a hit or a miss
+    pub fn get(&mut self, key: &ShapingKey) -> Option<V> {
+        self.get_refreshed(key, |_| true)
+    }
+
+    /// Like `get`, but `refresh` updates the copy returned, or returns
+    /// false for a layout that went stale, which drops it as a miss
+    pub fn get_refreshed(&mut self, key: &ShapingKey, refresh: impl FnOnce(&mut V) -> bool) -> Option<V> {
+        self.tick += 1;
+        let Some(entry) = self.entries.get_mut(key) else {
+            self.metrics.misses += 1;
+            return None;
+        };
+        let mut layout = entry.layout.clone();
+        if !refresh(&mut layout) {
+            self.entries.remove(key);
+            self.metrics.misses += 1;
+            return None;
+        }
+        entry.last_used = self.tick;
+        self.metrics.hits += 1;
+        Some(layout)
+    }
+
+    /// Cache a layout, dropping the least recently used one when full
+    pub fn insert(&mut self, key: ShapingKey, layout: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_least_recently_used();
+        }
+        self.entries.insert(key, CachedLayout { layout, last_used: self.tick });
+    }
+
+    /// Change how many layouts are kept, dropping the least recently used
+    /// ones beyond it; 0 disables the cache
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict_least_recently_used();
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every cached layout
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn metrics(&self) -> &ShapingCacheMetrics {
+        &self.metrics
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self.entries.iter().min_by_key(|(_, entry)| entry.last_used).map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+            self.metrics.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(text: &str) -> ShapingKey {
+        ShapingKey::new(text, &FontDescriptor::default(), &TextLayoutConfig::default(), 1.0)
+    }
+
+    #[test]
+    fn test_key_ignores_placement_only_fields() {
+        let layout = TextLayoutConfig::default();
+        let font = FontDescriptor::default();
+        let base = ShapingKey::new("Save", &font, &layout, 2.0);
+
+        let aligned = TextLayoutConfig { alignment: crate::text::TextAlign::Center, ..layout.clone() };
+        assert_eq!(ShapingKey::new("Save", &font, &aligned, 2.0), base);
+
+        let wrapped = TextLayoutConfig { max_width: Some(40.0), ..layout.clone() };
+        assert_ne!(ShapingKey::new("Save", &font, &wrapped, 2.0), base);
+        let bigger = FontDescriptor { size: font.size + 1.0, ..font.clone() };
+        assert_ne!(ShapingKey::new("Save", &bigger, &layout, 2.0), base);
+        assert_ne!(ShapingKey::new("Save", &font, &layout, 1.0), base);
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let mut cache = ShapingCache::new(2);
+        cache.insert(key("a"), 1);
+        cache.insert(key("b"), 2);
+        assert_eq!(cache.get(&key("a")), Some(1));
+
+        // "b" was used least recently
+        cache.insert(key("c"), 3);
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        assert_eq!(cache.get(&key("c")), Some(3));
+        assert_eq!(cache.metrics().hits, 3);
+        assert_eq!(cache.metrics().misses, 1);
+        assert_eq!(cache.metrics().evictions, 1);
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key("c")), Some(3));
+    }
+
+    #[test]
+    fn test_stale_layout_is_dropped() {
+        let mut cache = ShapingCache::new(4);
+        cache.insert(key("a"), 1);
+        assert_eq!(cache.get_refreshed(&key("a"), |v| { *v += 1; true }), Some(2));
+        // Refreshing changes the copy, not the cached layout
+        assert_eq!(cache.get(&key("a")), Some(1));
+
+        assert_eq!(cache.get_refreshed(&key("a"), |_| false), None);
+        assert!(cache.is_empty());
+        assert_eq!(cache.metrics().hits, 2);
+        assert_eq!(cache.metrics().misses, 1);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables() {
+        let mut cache = ShapingCache::new(0);
+        cache.insert(key("a"), 1);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&key("a")), None);
+    }
+}
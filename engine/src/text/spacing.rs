@@ -0,0 +1,180 @@
+//! Letter and word spacing
+//!
+//! Spacing follows CSS: letter-spacing is added between grapheme clusters,
+//! never inside one, so a ligature or an emoji sequence stays intact, and
+//! there is none after the last cluster of a line. Word-spacing is added only
+//! at word separator characters.
+
+use super::grapheme;
+use super::shaper::ShapedGlyph;
+use super::TextLayoutConfig;
+
+/// CSS word separator characters (space, no-break space, Ethiopic and
+/// Aegean/Ugaritic/Phoenician word separators)
+pub fn is_word_separator(c: char) -> bool {
+    matches!(c, ' ' | '\u{00A0}' | '\u{1361}' | '\u{10100}' | '\u{10101}' | '\u{1039F}' | '\u{1091F}')
+}
+
+/// Letter and word spacing in the same units as glyph advances
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spacing {
+    pub letter: f32,
+    pub word: f32,
+}
+
+impl Spacing {
+    pub fn new(letter: f32, word: f32) -> Self {
+        Self { letter, word }
+    }
+
+    /// Spacing from a layout config (em units) at a font size
+    pub fn from_layout(config: &TextLayoutConfig, font_size: f32) -> Self {
+        Self::new(config.letter_spacing * font_size, config.word_spacing * font_size)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.letter == 0.0 && self.word == 0.0
+    }
+
+    /// Extra advance after a cluster whose first char is `base`
+    pub fn after_cluster(&self, base: char, is_last: bool) -> f32 {
+        let mut extra = if is_last { 0.0 } else { self.letter };
+        if is_word_separator(base) {
+            extra += self.word;
+        }
+        extra
+    }
+
+    /// Extra width spacing adds to a run of text drawn one glyph per
+    /// grapheme cluster. Shaped glyphs can merge clusters into ligatures;
+    /// measure those with `measure` instead.
+    pub fn extra_width(&self, text: &str) -> f32 {
+        if self.is_zero() {
+            return 0.0;
+        }
+        let count = grapheme::grapheme_count(text);
+        grapheme::graphemes(text)
+            .enumerate()
+            .filter_map(|(i, (_, g))| grapheme::base_char(g).map(|c| self.after_cluster(c, i + 1 == count)))
+            .sum()
+    }
+
+    /// Width of shaped glyphs with spacing applied: their advances plus
+    /// exactly what `apply` adds to them
+    pub fn measure(&self, glyphs: &[ShapedGlyph], text: &str) -> f32 {
+        glyphs.iter().map(|g| g.advance).sum::<f32>() + self.shaped_extra_width(glyphs, text)
+    }
+
+    /// Extra width `apply` would add to shaped glyphs, without moving them
+    pub fn shaped_extra_width(&self, glyphs: &[ShapedGlyph], text: &str) -> f32 {
+        (0..glyphs.len()).map(|i| self.after_glyph(glyphs, i, text)).sum()
+    }
+
+    /// Shift shaped glyphs of one line to apply spacing, returning the
+    /// added width.
+    ///
+    /// Glyphs sharing a `cluster` (a ligature's components, or the parts of
+    /// a cluster the font draws with several glyphs) move together.
+    pub fn apply(&self, glyphs: &mut [ShapedGlyph], text: &str) -> f32 {
+        let mut shift = 0.0;
+        for i in 0..glyphs.len() {
+            glyphs[i].x += shift;
+            shift += self.after_glyph(glyphs, i, text);
+        }
+        shift
+    }
+
+    /// Spacing after glyph `i`: none inside a cluster, the cluster's
+    /// spacing after its last glyph
+    fn after_glyph(&self, glyphs: &[ShapedGlyph], i: usize, text: &str) -> f32 {
+        let last_in_cluster = glyphs.get(i + 1).is_none_or(|next| next.cluster != glyphs[i].cluster);
+        if !last_in_cluster {
+            return 0.0;
+        }
+        let base = text.get(glyphs[i].cluster..).and_then(|t| t.chars().next()).unwrap_or(glyphs[i].character);
+        self.after_cluster(base, i + 1 == glyphs.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(character: char, cluster: usize, x: f32, advance: f32) -> ShapedGlyph {
+        ShapedGlyph { glyph_id: 0, character, cluster, x, y: 0.0, advance, width: advance, height: 10.0 }
+    }
+
+    /// One glyph per char at a fixed advance, except a run shaped as a ligature
+    fn shape(text: &str, ligature: Option<(usize, usize)>) -> Vec<ShapedGlyph> {
+        let mut glyphs = Vec::new();
+        let mut x = 0.0;
+        for (i, c) in text.char_indices() {
+            if let Some((start, end)) = ligature {
+                if i > start && i < end {
+                    continue;
+                }
+            }
+            glyphs.push(glyph(c, i, x, 10.0));
+            x += 10.0;
+        }
+        glyphs
+    }
+
+    fn width(glyphs: &[ShapedGlyph]) -> f32 {
+        glyphs.last().map(|g| g.x + g.advance).unwrap_or(0.0)
+    }
+
+    #[test]
+    fn test_letter_spacing_between_clusters() {
+        let spacing = Spacing::new(2.0, 0.0);
+
+        let mut glyphs = shape("word", None);
+        let base = width(&glyphs);
+        let added = spacing.apply(&mut glyphs, "word");
+        assert_eq!(added, 3.0 * 2.0);
+        assert_eq!(width(&glyphs), base + (4.0 - 1.0) * 2.0);
+        assert_eq!(spacing.extra_width("word"), 6.0);
+
+        // "office" with "ffi" as one ligature glyph: 4 glyphs, 3 gaps, and
+        // nothing inserted inside the ligature
+        let mut glyphs = shape("office", Some((1, 4)));
+        assert_eq!(glyphs.len(), 4);
+        let added = spacing.apply(&mut glyphs, "office");
+        assert_eq!(added, 3.0 * 2.0);
+        assert_eq!(glyphs[1].x, 10.0 + 2.0);
+        assert_eq!(glyphs[2].x, 20.0 + 4.0);
+    }
+
+    #[test]
+    fn test_measure_matches_applied_width_for_ligatures() {
+        // Grapheme counting would give "office" 5 gaps; shaped with an "ffi"
+        // ligature it only has 3, and measuring must agree with drawing
+        let spacing = Spacing::new(2.0, 5.0);
+        for text in ["office", "an office"] {
+            let start = text.find("ffi").unwrap();
+            let mut glyphs = shape(text, Some((start, start + 3)));
+            let measured = spacing.measure(&glyphs, text);
+            spacing.apply(&mut glyphs, text);
+            assert_eq!(measured, width(&glyphs), "{:?}", text);
+        }
+        assert_eq!(spacing.measure(&shape("office", Some((1, 4))), "office"), 4.0 * 10.0 + 3.0 * 2.0);
+    }
+
+    #[test]
+    fn test_word_spacing_only_at_separators() {
+        let spacing = Spacing::new(0.0, 5.0);
+
+        assert_eq!(spacing.extra_width("onetwo"), 0.0);
+        assert_eq!(spacing.extra_width("one two three"), 2.0 * 5.0);
+        assert_eq!(spacing.extra_width("a\u{00A0}b"), 5.0);
+
+        let text = "ab cd";
+        let mut glyphs = shape(text, None);
+        spacing.apply(&mut glyphs, text);
+        // Glyphs before the gap stay put, the second word moves by one gap
+        assert_eq!(glyphs[1].x, 10.0);
+        assert_eq!(glyphs[2].x, 20.0);
+        assert_eq!(glyphs[3].x, 30.0 + 5.0);
+        assert_eq!(glyphs[4].x, 40.0 + 5.0);
+    }
+}
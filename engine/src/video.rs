@@ -7,7 +7,7 @@
 //! - Windows: Media Foundation (planned)
 //!
 //! Supports:
-//! - Video playback from files and URLs
+//! - Video playback from files and URLs, with the audio track kept in sync
 //! - Camera capture with device enumeration
 //! - Multiple simultaneous video inputs
 
@@ -111,7 +111,7 @@ impl Default for VideoInfo {
 pub enum PlaybackState {
     /// No video loaded
     Idle = 0,
-    /// Video is loading/buffering
+    /// Video is loading
     Loading = 1,
     /// Video is playing
     Playing = 2,
@@ -121,6 +121,8 @@ pub enum PlaybackState {
     Ended = 4,
     /// Error occurred
     Error = 5,
+    /// Playback is stalled mid-stream, waiting for more data
+    Buffering = 6,
 }
 
 /// Video error types
@@ -171,6 +173,54 @@ pub trait VideoDecoder: Send {
 
     /// Get current playback position
     fn current_time_ms(&self) -> u64;
+
+    /// Position up to which the media is downloaded, counting on from the
+    /// playback position. Local sources have all of it.
+    fn buffered_ms(&self) -> u64 {
+        self.info().duration_ms
+    }
+
+    /// Whether playback is stalled waiting for data to arrive
+    fn is_buffering(&self) -> bool {
+        false
+    }
+
+    /// Audio track sharing the decoder's own playback, for decoders that
+    /// play the media themselves. The player then uses it in place of
+    /// opening the audio separately.
+    fn audio_track(&self) -> Option<Box<dyn AudioTrack>> {
+        None
+    }
+}
+
+/// Audio track of a video, played alongside its frames.
+///
+/// While a track is playing, its position is the clock the player shows
+/// frames against, so picture follows sound rather than the other way round.
+pub trait AudioTrack: Send {
+    /// Start or resume the audio
+    fn play(&mut self);
+
+    /// Pause the audio
+    fn pause(&mut self);
+
+    /// Seek the audio to a timestamp
+    fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError>;
+
+    /// Set volume (0.0 - 1.0)
+    fn set_volume(&mut self, volume: f32);
+
+    /// Mute or unmute without losing the volume
+    fn set_muted(&mut self, muted: bool);
+
+    /// Poll the backend for position and end of stream
+    fn update(&mut self);
+
+    /// Current audio position, the player's clock
+    fn current_time_ms(&self) -> u64;
+
+    /// Whether the last sample has been played
+    fn ended(&self) -> bool;
 }
 
 #[cfg(test)]
@@ -194,5 +244,6 @@ mod tests {
         assert_eq!(PlaybackState::Paused as i32, 3);
         assert_eq!(PlaybackState::Ended as i32, 4);
         assert_eq!(PlaybackState::Error as i32, 5);
+        assert_eq!(PlaybackState::Buffering as i32, 6);
     }
 }
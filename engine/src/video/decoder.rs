@@ -2,12 +2,17 @@
 //!
 //! Provides a common interface for platform-specific video decoders.
 
-use super::{VideoDecoder, VideoError, VideoFrame, VideoInfo};
+use super::{AudioTrack, VideoDecoder, VideoError, VideoFrame, VideoInfo};
 
 /// Create a decoder for the current platform
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub fn create_decoder_from_url(url: &str) -> Result<Box<dyn VideoDecoder>, VideoError> {
-    use super::macos::MacOSVideoDecoder;
+    use super::macos::{MacOSStreamingDecoder, MacOSVideoDecoder};
+    // AVAssetReader needs the whole file; AVPlayer streams remote ones
+    if url.starts_with("http://") || url.starts_with("https://") {
+        let decoder = MacOSStreamingDecoder::from_url(url)?;
+        return Ok(Box::new(decoder));
+    }
     let decoder = MacOSVideoDecoder::from_url(url)?;
     Ok(Box::new(decoder))
 }
@@ -74,6 +79,112 @@ pub fn create_decoder_from_file(_path: &str) -> Result<Box<dyn VideoDecoder>, Vi
     Err(VideoError::UnsupportedPlatform)
 }
 
+/// Open the audio track of a video on macOS/iOS, played by an AVPlayer
+///
+/// Returns None when the video has no audio or it can't be opened; the
+/// video then plays silently on the wall clock.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn create_audio_track_from_url(url: &str) -> Option<Box<dyn AudioTrack>> {
+    use super::macos::AVPlayerAudioTrack;
+    AVPlayerAudioTrack::from_url(url).map(|track| Box::new(track) as Box<dyn AudioTrack>)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn create_audio_track_from_file(path: &str) -> Option<Box<dyn AudioTrack>> {
+    use super::macos::AVPlayerAudioTrack;
+    AVPlayerAudioTrack::from_file(path).map(|track| Box::new(track) as Box<dyn AudioTrack>)
+}
+
+/// Open the audio track of a video elsewhere, through the `audio` module
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "windows"))]
+pub fn create_audio_track_from_url(url: &str) -> Option<Box<dyn AudioTrack>> {
+    let mut player = crate::audio::player::AudioPlayer::new();
+    player.load_url(url).ok()?;
+    Some(Box::new(AudioPlayerTrack::new(player)))
+}
+
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "windows"))]
+pub fn create_audio_track_from_file(path: &str) -> Option<Box<dyn AudioTrack>> {
+    let mut player = crate::audio::player::AudioPlayer::new();
+    player.load_file(path).ok()?;
+    Some(Box::new(AudioPlayerTrack::new(player)))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows")))]
+pub fn create_audio_track_from_url(_url: &str) -> Option<Box<dyn AudioTrack>> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "android", target_os = "linux", target_os = "windows")))]
+pub fn create_audio_track_from_file(_path: &str) -> Option<Box<dyn AudioTrack>> {
+    None
+}
+
+/// Audio track played by the `audio` module's player
+///
+/// The audio backends have no mute, so muting sets their volume to zero
+/// and unmuting restores it.
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "windows"))]
+pub struct AudioPlayerTrack {
+    player: crate::audio::player::AudioPlayer,
+    volume: f32,
+    muted: bool,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "windows"))]
+impl AudioPlayerTrack {
+    pub fn new(player: crate::audio::player::AudioPlayer) -> Self {
+        let volume = player.volume();
+        Self { player, volume, muted: false }
+    }
+
+    fn apply_volume(&mut self) {
+        let volume = if self.muted { 0.0 } else { self.volume };
+        self.player.set_volume(volume);
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "windows"))]
+impl AudioTrack for AudioPlayerTrack {
+    fn play(&mut self) {
+        if let Err(e) = self.player.play() {
+            eprintln!("Failed to play video audio: {}", e);
+        }
+    }
+
+    fn pause(&mut self) {
+        let _ = self.player.pause();
+    }
+
+    fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+        self.player
+            .seek(timestamp_ms)
+            .map_err(|e| VideoError::SeekError(e.to_string()))
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        self.apply_volume();
+    }
+
+    fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volume();
+    }
+
+    fn update(&mut self) {
+        self.player.update();
+    }
+
+    fn current_time_ms(&self) -> u64 {
+        self.player.current_time_ms()
+    }
+
+    fn ended(&self) -> bool {
+        self.player.state() == crate::audio::PlaybackState::Ended
+    }
+}
+
 /// A simple frame buffer decoder for raw frame input (video meetings, etc.)
 pub struct FrameBufferDecoder {
     info: VideoInfo,
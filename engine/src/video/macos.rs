@@ -2,10 +2,15 @@
 //!
 //! Uses AVAsset, AVAssetReader, and AVAssetReaderTrackOutput for video decoding.
 //! This provides hardware-accelerated decoding via VideoToolbox for H.264, HEVC, etc.
+//! The audio track plays through an AVPlayer, whose clock the frames follow.
+//!
+//! AVAssetReader only reads local files, so remote URLs instead play through
+//! an AVPlayer with an AVPlayerItemVideoOutput. AVPlayer streams over HTTP
+//! with range requests, starting as soon as enough is buffered.
 
-use super::{VideoDecoder, VideoError, VideoFrame, VideoInfo};
+use super::{AudioTrack, VideoDecoder, VideoError, VideoFrame, VideoInfo};
 use core_foundation::base::CFRelease;
-use objc::runtime::{Object, BOOL, NO};
+use objc::runtime::{Object, BOOL, NO, YES};
 use objc::{msg_send, sel, sel_impl};
 use std::ffi::c_void;
 use std::ptr;
@@ -13,8 +18,9 @@ use std::ptr;
 // AVFoundation types
 #[link(name = "AVFoundation", kind = "framework")]
 extern "C" {
-    // AVMediaTypeVideo string constant
+    // AVMediaTypeVideo and AVMediaTypeAudio string constants
     static AVMediaTypeVideo: *mut Object;
+    static AVMediaTypeAudio: *mut Object;
 }
 
 #[link(name = "CoreMedia", kind = "framework")]
@@ -45,6 +51,14 @@ struct CMTime {
     epoch: i64,
 }
 
+/// Core Media time range, as found in an item's loadedTimeRanges
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct CMTimeRange {
+    start: CMTime,
+    duration: CMTime,
+}
+
 impl CMTime {
     fn invalid() -> Self {
         CMTime {
@@ -85,20 +99,17 @@ pub struct MacOSVideoDecoder {
 unsafe impl Send for MacOSVideoDecoder {}
 
 impl MacOSVideoDecoder {
-    /// Create decoder from a URL (file:// or http://)
+    /// Create decoder from a file:// URL
+    ///
+    /// Remote URLs stream through `MacOSStreamingDecoder` instead.
     pub fn from_url(url: &str) -> Result<Self, VideoError> {
-        unsafe {
-            // For remote URLs, we need to download the video first
-            // AVAssetReader doesn't support streaming - it needs a local file
-            if url.starts_with("http://") || url.starts_with("https://") {
-                // Download the video to a temp file
-                // AVAssetReader doesn't support streaming - it needs a local file
-                let temp_path = Self::download_to_temp(url)?;
-                let ns_url = Self::create_file_url(&temp_path)?;
-                return Self::from_nsurl(ns_url);
-            }
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Err(VideoError::LoadError(
+                "AVAssetReader can't read remote URLs; use MacOSStreamingDecoder".into(),
+            ));
+        }
 
-            // Local file URL
+        unsafe {
             let path = if url.starts_with("file://") {
                 &url[7..]
             } else {
@@ -114,46 +125,6 @@ impl MacOSVideoDecoder {
         }
     }
 
-    /// Download a remote URL to a temporary file
-    fn download_to_temp(url: &str) -> Result<String, VideoError> {
-        use std::io::Write;
-
-        // Create temp file path
-        let temp_dir = std::env::temp_dir();
-        let file_name = format!("centered_video_{}.mp4", std::process::id());
-        let temp_path = temp_dir.join(file_name);
-        let temp_path_str = temp_path.to_string_lossy().to_string();
-
-        unsafe {
-            let ns_url = Self::create_http_url(url)?;
-
-            // Use NSData's dataWithContentsOfURL - synchronous but works on both macOS and iOS
-            // This is simpler than NSURLSession with blocks and works reliably
-            let data: *mut Object = msg_send![class!(NSData), dataWithContentsOfURL: ns_url];
-
-            if data.is_null() {
-                return Err(VideoError::LoadError(format!("Failed to download video from: {}", url)));
-            }
-
-            // Get data bytes
-            let length: usize = msg_send![data, length];
-            let bytes: *const u8 = msg_send![data, bytes];
-
-            if length == 0 || bytes.is_null() {
-                return Err(VideoError::LoadError("Downloaded empty data".into()));
-            }
-
-            // Write to temp file
-            let data_slice = std::slice::from_raw_parts(bytes, length);
-            let mut file = std::fs::File::create(&temp_path)
-                .map_err(|e| VideoError::LoadError(format!("Failed to create temp file: {}", e)))?;
-            file.write_all(data_slice)
-                .map_err(|e| VideoError::LoadError(format!("Failed to write temp file: {}", e)))?;
-
-            Ok(temp_path_str)
-        }
-    }
-
     unsafe fn create_http_url(url: &str) -> Result<*mut Object, VideoError> {
         let ns_string: *mut Object = msg_send![class!(NSString), alloc];
         let ns_string: *mut Object = msg_send![ns_string, initWithBytes:url.as_ptr()
@@ -202,7 +173,12 @@ impl MacOSVideoDecoder {
         Ok(ns_url)
     }
 
-    unsafe fn from_nsurl(ns_url: *mut Object) -> Result<Self, VideoError> {
+    /// Open an asset and wait for its tracks and duration to load
+    ///
+    /// Returns the retained asset, its retained first video track, and the
+    /// video's metadata. For remote URLs this only fetches what describes
+    /// the media, not the media itself.
+    unsafe fn load_asset(ns_url: *mut Object) -> Result<(*mut Object, *mut Object, VideoInfo), VideoError> {
         // Create AVURLAsset (better for remote URLs)
         let asset: *mut Object = msg_send![class!(AVURLAsset), URLAssetWithURL:ns_url options:ptr::null::<Object>()];
         if asset.is_null() {
@@ -299,6 +275,12 @@ impl MacOSVideoDecoder {
             is_live: false,
         };
 
+        Ok((asset, video_track, info))
+    }
+
+    unsafe fn from_nsurl(ns_url: *mut Object) -> Result<Self, VideoError> {
+        let (asset, video_track, info) = Self::load_asset(ns_url)?;
+
         // Create asset reader
         let mut error: *mut Object = ptr::null_mut();
         let asset_reader: *mut Object = msg_send![class!(AVAssetReader), alloc];
@@ -383,6 +365,39 @@ impl MacOSVideoDecoder {
         })
     }
 
+    /// Copy a BGRA CVPixelBuffer into an RGBA frame
+    unsafe fn frame_from_pixel_buffer(image_buffer: *const c_void, timestamp_ms: u64) -> Option<VideoFrame> {
+        // Lock the pixel buffer
+        let lock_result = CVPixelBufferLockBaseAddress(image_buffer, 0);
+        if lock_result != 0 {
+            return None;
+        }
+
+        // Get pixel data
+        let width = CVPixelBufferGetWidth(image_buffer);
+        let height = CVPixelBufferGetHeight(image_buffer);
+        let bytes_per_row = CVPixelBufferGetBytesPerRow(image_buffer);
+        let base_address = CVPixelBufferGetBaseAddress(image_buffer);
+
+        let frame = if !base_address.is_null() {
+            let data_len = bytes_per_row * height;
+            let bgra_data = std::slice::from_raw_parts(base_address, data_len);
+            let rgba_data = Self::bgra_to_rgba(bgra_data, width, height, bytes_per_row);
+
+            Some(VideoFrame {
+                width: width as u32,
+                height: height as u32,
+                data: rgba_data,
+                timestamp_ms,
+            })
+        } else {
+            None
+        };
+
+        CVPixelBufferUnlockBaseAddress(image_buffer, 0);
+        frame
+    }
+
     /// Convert BGRA pixel buffer to RGBA
     fn bgra_to_rgba(bgra: &[u8], width: usize, height: usize, stride: usize) -> Vec<u8> {
         let mut rgba = vec![0u8; width * height * 4];
@@ -479,36 +494,7 @@ impl VideoDecoder for MacOSVideoDecoder {
                 return None;
             }
 
-            // Lock the pixel buffer
-            let lock_result = CVPixelBufferLockBaseAddress(image_buffer, 0);
-            if lock_result != 0 {
-                CFRelease(sample_buffer as *const c_void);
-                return None;
-            }
-
-            // Get pixel data
-            let width = CVPixelBufferGetWidth(image_buffer);
-            let height = CVPixelBufferGetHeight(image_buffer);
-            let bytes_per_row = CVPixelBufferGetBytesPerRow(image_buffer);
-            let base_address = CVPixelBufferGetBaseAddress(image_buffer);
-
-            let frame = if !base_address.is_null() {
-                let data_len = bytes_per_row * height;
-                let bgra_data = std::slice::from_raw_parts(base_address, data_len);
-                let rgba_data = Self::bgra_to_rgba(bgra_data, width, height, bytes_per_row);
-
-                Some(VideoFrame {
-                    width: width as u32,
-                    height: height as u32,
-                    data: rgba_data,
-                    timestamp_ms: self.current_time_ms,
-                })
-            } else {
-                None
-            };
-
-            // Unlock and release
-            CVPixelBufferUnlockBaseAddress(image_buffer, 0);
+            let frame = Self::frame_from_pixel_buffer(image_buffer, self.current_time_ms);
             CFRelease(sample_buffer as *const c_void);
 
             frame
@@ -579,6 +565,358 @@ impl VideoDecoder for MacOSVideoDecoder {
     }
 }
 
+/// Decoder for remote videos, streamed by an AVPlayer
+///
+/// Frames come from an AVPlayerItemVideoOutput at the player's current
+/// time, so they follow the AVPlayer rather than being read ahead. The same
+/// AVPlayer plays the sound and is handed to the video player as its audio
+/// track, which makes it the clock and the thing play/pause/seek control.
+pub struct MacOSStreamingDecoder {
+    /// AVURLAsset being streamed
+    asset: *mut Object,
+    /// AVPlayerItem for the asset
+    item: *mut Object,
+    /// AVPlayerItemVideoOutput attached to the item
+    video_output: *mut Object,
+    /// AVPlayer playing the item
+    player: *mut Object,
+    /// Video metadata
+    info: VideoInfo,
+    /// Timestamp of the last frame handed out
+    current_time_ms: u64,
+    /// Whether the player has reached the end of the item
+    ended: bool,
+}
+
+// Safety: AVPlayer and its item output may be used from any thread
+unsafe impl Send for MacOSStreamingDecoder {}
+
+impl MacOSStreamingDecoder {
+    /// Start streaming an http:// or https:// URL
+    ///
+    /// Only the asset's metadata is loaded before this returns; the media
+    /// itself is fetched in ranges as it plays.
+    pub fn from_url(url: &str) -> Result<Self, VideoError> {
+        unsafe {
+            let ns_url = MacOSVideoDecoder::create_http_url(url)?;
+            let (asset, video_track, info) = MacOSVideoDecoder::load_asset(ns_url)?;
+            // The output takes frames from the whole item, not a track
+            let _: () = msg_send![video_track, release];
+
+            // Same BGRA format as the file reader
+            let pixel_format_key: *mut Object =
+                msg_send![class!(NSString), stringWithUTF8String: b"PixelFormatType\0".as_ptr()];
+            let pixel_format_value: *mut Object = msg_send![class!(NSNumber), numberWithInt: 0x42475241i32];
+            let attributes: *mut Object = msg_send![class!(NSDictionary),
+                dictionaryWithObject:pixel_format_value
+                forKey:pixel_format_key];
+
+            let video_output: *mut Object = msg_send![class!(AVPlayerItemVideoOutput), alloc];
+            let video_output: *mut Object = msg_send![video_output, initWithPixelBufferAttributes: attributes];
+            if video_output.is_null() {
+                let _: () = msg_send![asset, release];
+                return Err(VideoError::LoadError("Failed to create video output".into()));
+            }
+
+            let item: *mut Object = msg_send![class!(AVPlayerItem), playerItemWithAsset: asset];
+            if item.is_null() {
+                let _: () = msg_send![video_output, release];
+                let _: () = msg_send![asset, release];
+                return Err(VideoError::LoadError("Failed to create AVPlayerItem".into()));
+            }
+            let _: () = msg_send![item, retain];
+            let _: () = msg_send![item, addOutput: video_output];
+
+            let player: *mut Object = msg_send![class!(AVPlayer), playerWithPlayerItem: item];
+            if player.is_null() {
+                let _: () = msg_send![item, release];
+                let _: () = msg_send![video_output, release];
+                let _: () = msg_send![asset, release];
+                return Err(VideoError::LoadError("Failed to create AVPlayer".into()));
+            }
+            let _: () = msg_send![player, retain];
+
+            Ok(Self {
+                asset,
+                item,
+                video_output,
+                player,
+                info,
+                current_time_ms: 0,
+                ended: false,
+            })
+        }
+    }
+
+    /// The item's current playback position
+    fn item_time(&self) -> CMTime {
+        unsafe { msg_send![self.item, currentTime] }
+    }
+}
+
+impl Drop for MacOSStreamingDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.player, pause];
+            let _: () = msg_send![self.item, removeOutput: self.video_output];
+            let _: () = msg_send![self.video_output, release];
+            let _: () = msg_send![self.player, release];
+            let _: () = msg_send![self.item, release];
+            let _: () = msg_send![self.asset, release];
+        }
+    }
+}
+
+impl VideoDecoder for MacOSStreamingDecoder {
+    fn info(&self) -> &VideoInfo {
+        &self.info
+    }
+
+    fn next_frame(&mut self) -> Option<VideoFrame> {
+        if self.ended {
+            return None;
+        }
+
+        unsafe {
+            let time = self.item_time();
+            let has_new: BOOL = msg_send![self.video_output, hasNewPixelBufferForItemTime: time];
+            if has_new == NO {
+                // Nothing new at the end of the item means there's nothing left
+                let rate: f32 = msg_send![self.player, rate];
+                let duration_ms = self.info.duration_ms;
+                if rate == 0.0 && duration_ms > 0 && time.to_milliseconds() + 100 >= duration_ms {
+                    self.ended = true;
+                }
+                return None;
+            }
+
+            let mut display_time = CMTime::invalid();
+            let pixel_buffer: *const c_void =
+                msg_send![self.video_output, copyPixelBufferForItemTime:time itemTimeForDisplay:&mut display_time];
+            if pixel_buffer.is_null() {
+                return None;
+            }
+
+            self.current_time_ms = display_time.to_milliseconds();
+            let frame = MacOSVideoDecoder::frame_from_pixel_buffer(pixel_buffer, self.current_time_ms);
+            CFRelease(pixel_buffer);
+            frame
+        }
+    }
+
+    fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+        unsafe {
+            // The audio track seeks the same player; seeking here as well keeps
+            // the decoder usable on its own, and AVPlayer drops the superseded seek
+            let time = CMTimeMake(timestamp_ms as i64, 1000);
+            let zero = CMTimeMake(0, 1);
+            let _: () = msg_send![self.player, seekToTime:time toleranceBefore:zero toleranceAfter:zero];
+        }
+        self.current_time_ms = timestamp_ms;
+        self.ended = false;
+        Ok(())
+    }
+
+    fn has_more_frames(&self) -> bool {
+        !self.ended
+    }
+
+    fn current_time_ms(&self) -> u64 {
+        self.current_time_ms
+    }
+
+    fn buffered_ms(&self) -> u64 {
+        unsafe {
+            let now_ms = self.item_time().to_milliseconds();
+            let ranges: *mut Object = msg_send![self.item, loadedTimeRanges];
+            if ranges.is_null() {
+                return now_ms;
+            }
+
+            // The loaded range the playhead is in; others were left by seeking
+            let count: usize = msg_send![ranges, count];
+            let mut buffered_ms = now_ms;
+            for i in 0..count {
+                let value: *mut Object = msg_send![ranges, objectAtIndex: i];
+                let range: CMTimeRange = msg_send![value, CMTimeRangeValue];
+                let start_ms = range.start.to_milliseconds();
+                let end_ms = start_ms + range.duration.to_milliseconds();
+                if start_ms <= now_ms && now_ms <= end_ms {
+                    buffered_ms = buffered_ms.max(end_ms);
+                }
+            }
+            buffered_ms
+        }
+    }
+
+    fn is_buffering(&self) -> bool {
+        // AVPlayerTimeControlStatusWaitingToPlayAtSpecifiedRate: asked to
+        // play, but stalled until enough is buffered to keep up
+        let status: i64 = unsafe { msg_send![self.player, timeControlStatus] };
+        status == 1
+    }
+
+    fn audio_track(&self) -> Option<Box<dyn AudioTrack>> {
+        Some(Box::new(AVPlayerAudioTrack::with_player(self.player, self.info.duration_ms)))
+    }
+}
+
+/// Audio track of a video, played by an AVPlayer
+///
+/// AVPlayer streams remote URLs itself, so unlike the decoder it doesn't
+/// need the file downloaded first.
+pub struct AVPlayerAudioTrack {
+    /// AVPlayer playing the asset
+    player: *mut Object,
+    /// Asset duration, where the audio ends
+    duration_ms: u64,
+    /// Whether play was called and the audio hasn't ended since
+    playing: bool,
+    /// Whether the last sample has been played
+    ended: bool,
+}
+
+// Safety: AVPlayer may be controlled from any thread
+unsafe impl Send for AVPlayerAudioTrack {}
+
+impl AVPlayerAudioTrack {
+    /// Open the audio of a URL (file:// or http://), None if it has none
+    pub fn from_url(url: &str) -> Option<Self> {
+        unsafe {
+            let ns_url = if url.starts_with("http://") || url.starts_with("https://") {
+                MacOSVideoDecoder::create_http_url(url).ok()?
+            } else {
+                let path = url.strip_prefix("file://").unwrap_or(url);
+                MacOSVideoDecoder::create_file_url(path).ok()?
+            };
+            Self::from_nsurl(ns_url)
+        }
+    }
+
+    /// Open the audio of a file, None if it has none
+    pub fn from_file(path: &str) -> Option<Self> {
+        unsafe {
+            let ns_url = MacOSVideoDecoder::create_file_url(path).ok()?;
+            Self::from_nsurl(ns_url)
+        }
+    }
+
+    /// Share an AVPlayer that's already playing a video, such as a
+    /// streaming decoder's, so its sound and clock drive the video player
+    pub(crate) fn with_player(player: *mut Object, duration_ms: u64) -> Self {
+        unsafe {
+            let _: () = msg_send![player, retain];
+        }
+        Self {
+            player,
+            duration_ms,
+            playing: false,
+            ended: false,
+        }
+    }
+
+    unsafe fn from_nsurl(ns_url: *mut Object) -> Option<Self> {
+        let asset: *mut Object = msg_send![class!(AVURLAsset), URLAssetWithURL:ns_url options:ptr::null::<Object>()];
+        if asset.is_null() {
+            return None;
+        }
+
+        // Video without sound plays silently on the wall clock
+        let tracks: *mut Object = msg_send![asset, tracksWithMediaType: AVMediaTypeAudio];
+        let track_count: usize = msg_send![tracks, count];
+        if track_count == 0 {
+            return None;
+        }
+
+        let duration: CMTime = msg_send![asset, duration];
+        let item: *mut Object = msg_send![class!(AVPlayerItem), playerItemWithAsset: asset];
+        let player: *mut Object = msg_send![class!(AVPlayer), playerWithPlayerItem: item];
+        if player.is_null() {
+            return None;
+        }
+        let _: () = msg_send![player, retain];
+
+        Some(Self {
+            player,
+            duration_ms: duration.to_milliseconds(),
+            playing: false,
+            ended: false,
+        })
+    }
+}
+
+impl Drop for AVPlayerAudioTrack {
+    fn drop(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.player, pause];
+            let _: () = msg_send![self.player, release];
+        }
+    }
+}
+
+impl AudioTrack for AVPlayerAudioTrack {
+    fn play(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.player, play];
+        }
+        self.playing = true;
+    }
+
+    fn pause(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.player, pause];
+        }
+        self.playing = false;
+    }
+
+    fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+        unsafe {
+            // Zero tolerance so the audio lands where the frames will be
+            let time = CMTimeMake(timestamp_ms as i64, 1000);
+            let zero = CMTimeMake(0, 1);
+            let _: () = msg_send![self.player, seekToTime:time toleranceBefore:zero toleranceAfter:zero];
+        }
+        self.ended = false;
+        Ok(())
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        unsafe {
+            let _: () = msg_send![self.player, setVolume: volume];
+        }
+    }
+
+    fn set_muted(&mut self, muted: bool) {
+        unsafe {
+            let _: () = msg_send![self.player, setMuted: if muted { YES } else { NO }];
+        }
+    }
+
+    fn update(&mut self) {
+        if !self.playing {
+            return;
+        }
+
+        // AVPlayer drops its rate to zero when it runs out of item to play
+        let rate: f32 = unsafe { msg_send![self.player, rate] };
+        if rate == 0.0 && self.current_time_ms() + 100 >= self.duration_ms {
+            self.playing = false;
+            self.ended = true;
+        }
+    }
+
+    fn current_time_ms(&self) -> u64 {
+        unsafe {
+            let time: CMTime = msg_send![self.player, currentTime];
+            time.to_milliseconds()
+        }
+    }
+
+    fn ended(&self) -> bool {
+        self.ended
+    }
+}
+
 #[repr(C)]
 struct CGSize {
     width: f64,
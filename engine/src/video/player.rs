@@ -2,13 +2,17 @@
 //!
 //! The player coordinates between the decoder (which extracts frames) and
 //! the GPU texture (which displays frames). It handles:
-//! - Frame timing and synchronization
+//! - Frame timing and synchronization, following the audio track's clock
+//!   while it plays
 //! - Play/pause/seek controls
 //! - Looping behavior
 //! - Texture management
 
-use super::decoder::{create_decoder_from_file, create_decoder_from_url, FrameBufferDecoder};
-use super::{PlaybackState, VideoDecoder, VideoError, VideoFrame, VideoInfo};
+use super::decoder::{
+    create_audio_track_from_file, create_audio_track_from_url, create_decoder_from_file,
+    create_decoder_from_url, FrameBufferDecoder,
+};
+use super::{AudioTrack, PlaybackState, VideoDecoder, VideoError, VideoFrame, VideoInfo};
 use std::time::Instant;
 
 /// Video player that manages playback and texture streaming
@@ -19,6 +23,13 @@ pub struct VideoPlayer {
     /// Frame buffer decoder for raw frame input
     frame_buffer: Option<FrameBufferDecoder>,
 
+    /// The video's audio track, if it has one
+    audio: Option<Box<dyn AudioTrack>>,
+
+    /// Whether the decoder has run out of frames; playback ends once the
+    /// audio has too
+    frames_done: bool,
+
     /// GPU texture ID for the current frame
     texture_id: Option<u32>,
 
@@ -59,6 +70,8 @@ impl VideoPlayer {
         Self {
             decoder: None,
             frame_buffer: None,
+            audio: None,
+            frames_done: false,
             texture_id: None,
             state: PlaybackState::Idle,
             current_time_ms: 0,
@@ -80,10 +93,8 @@ impl VideoPlayer {
 
         match create_decoder_from_url(url) {
             Ok(decoder) => {
-                self.decoder = Some(decoder);
-                self.state = PlaybackState::Paused;
-                // Decode first frame for thumbnail
-                self.decode_next_frame();
+                let audio = decoder.audio_track().or_else(|| create_audio_track_from_url(url));
+                self.load_decoder(decoder, audio);
                 Ok(())
             }
             Err(e) => {
@@ -101,10 +112,8 @@ impl VideoPlayer {
 
         match create_decoder_from_file(path) {
             Ok(decoder) => {
-                self.decoder = Some(decoder);
-                self.state = PlaybackState::Paused;
-                // Decode first frame for thumbnail
-                self.decode_next_frame();
+                let audio = decoder.audio_track().or_else(|| create_audio_track_from_file(path));
+                self.load_decoder(decoder, audio);
                 Ok(())
             }
             Err(e) => {
@@ -115,6 +124,20 @@ impl VideoPlayer {
         }
     }
 
+    /// Load video from a custom decoder, with an optional audio track
+    pub fn load_decoder(&mut self, decoder: Box<dyn VideoDecoder>, audio: Option<Box<dyn AudioTrack>>) {
+        self.reset();
+        self.decoder = Some(decoder);
+        self.audio = audio;
+        if let Some(audio) = &mut self.audio {
+            audio.set_volume(self.volume);
+            audio.set_muted(self.muted);
+        }
+        self.state = PlaybackState::Paused;
+        // Decode first frame for thumbnail
+        self.decode_next_frame();
+    }
+
     /// Initialize for raw frame input (video meetings)
     pub fn init_frame_buffer(&mut self, width: u32, height: u32) {
         self.reset();
@@ -144,19 +167,25 @@ impl VideoPlayer {
             self.state = PlaybackState::Playing;
             self.playback_start = Some(Instant::now());
             self.playback_start_pos = self.current_time_ms;
+            if let Some(audio) = &mut self.audio {
+                audio.play();
+            }
         }
     }
 
     /// Pause playback
     pub fn pause(&mut self) {
-        if self.state == PlaybackState::Playing {
-            self.state = PlaybackState::Paused;
-            // Update current time before stopping
-            if let Some(start) = self.playback_start {
-                self.current_time_ms =
-                    self.playback_start_pos + start.elapsed().as_millis() as u64;
+        if self.state == PlaybackState::Playing || self.state == PlaybackState::Buffering {
+            // Update current time before stopping; a stalled clock is
+            // already where it stopped
+            if self.state == PlaybackState::Playing {
+                self.sync_clock();
             }
+            self.state = PlaybackState::Paused;
             self.playback_start = None;
+            if let Some(audio) = &mut self.audio {
+                audio.pause();
+            }
         }
     }
 
@@ -164,9 +193,15 @@ impl VideoPlayer {
     pub fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
         if let Some(decoder) = &mut self.decoder {
             decoder.seek(timestamp_ms)?;
+            if let Some(audio) = &mut self.audio {
+                if let Err(e) = audio.seek(timestamp_ms) {
+                    eprintln!("Failed to seek video audio: {}", e);
+                }
+            }
+            self.frames_done = false;
             self.current_time_ms = timestamp_ms;
             self.playback_start_pos = timestamp_ms;
-            if self.state == PlaybackState::Playing {
+            if self.state == PlaybackState::Playing || self.state == PlaybackState::Buffering {
                 self.playback_start = Some(Instant::now());
             }
             // Decode frame at new position
@@ -185,25 +220,35 @@ impl VideoPlayer {
     /// Set muted state
     pub fn set_muted(&mut self, muted: bool) {
         self.muted = muted;
+        if let Some(audio) = &mut self.audio {
+            audio.set_muted(muted);
+        }
     }
 
     /// Set volume (0.0 - 1.0)
     pub fn set_volume(&mut self, volume: f32) {
         self.volume = volume.clamp(0.0, 1.0);
+        if let Some(audio) = &mut self.audio {
+            audio.set_volume(self.volume);
+        }
     }
 
     /// Update playback state (call each frame)
     ///
     /// Returns true if a new frame is ready for upload
     pub fn update(&mut self) -> bool {
-        if self.state != PlaybackState::Playing {
+        if self.state != PlaybackState::Playing && self.state != PlaybackState::Buffering {
             return self.frame_dirty;
         }
 
         // Calculate current playback position
-        if let Some(start) = self.playback_start {
-            self.current_time_ms = self.playback_start_pos + start.elapsed().as_millis() as u64;
+        if let Some(audio) = &mut self.audio {
+            audio.update();
         }
+        if self.poll_buffering() {
+            return self.frame_dirty;
+        }
+        self.sync_clock();
 
         // Check if we need a new frame
         if let Some(decoder) = &self.decoder {
@@ -213,15 +258,19 @@ impl VideoPlayer {
                 33 // ~30fps default
             };
 
-            // Decode frames until we catch up to current time
-            let current_frame_time = self
-                .current_frame
-                .as_ref()
-                .map(|f| f.timestamp_ms)
-                .unwrap_or(0);
-            if self.current_time_ms >= current_frame_time + frame_time {
-                self.decode_next_frame();
+            // Decode frames until we catch up to current time, dropping
+            // any the audio clock has already passed
+            loop {
+                let current_frame_time = self
+                    .current_frame
+                    .as_ref()
+                    .map(|f| f.timestamp_ms)
+                    .unwrap_or(0);
+                if self.current_time_ms < current_frame_time + frame_time || !self.decode_next_frame() {
+                    break;
+                }
             }
+            self.finish_if_done();
         }
 
         // Handle frame buffer (live streams)
@@ -278,6 +327,15 @@ impl VideoPlayer {
         self.current_time_ms
     }
 
+    /// Get the position up to which the video is downloaded
+    pub fn buffered_ms(&self) -> u64 {
+        if let Some(decoder) = &self.decoder {
+            decoder.buffered_ms()
+        } else {
+            self.current_time_ms
+        }
+    }
+
     /// Get error message
     pub fn error_message(&self) -> Option<&str> {
         self.error_message.as_deref()
@@ -298,31 +356,78 @@ impl VideoPlayer {
         self.volume
     }
 
+    /// Advance the playback position: the audio's while it plays, the wall
+    /// clock's otherwise
+    fn sync_clock(&mut self) {
+        match &self.audio {
+            Some(audio) if !audio.ended() => {
+                self.current_time_ms = audio.current_time_ms();
+                // Re-anchor the wall clock so it carries on from here if the
+                // audio ends before the frames do
+                self.playback_start = Some(Instant::now());
+                self.playback_start_pos = self.current_time_ms;
+            }
+            _ => {
+                if let Some(start) = self.playback_start {
+                    self.current_time_ms = self.playback_start_pos + start.elapsed().as_millis() as u64;
+                }
+            }
+        }
+    }
+
+    /// Move between Playing and Buffering as the decoder stalls and
+    /// recovers
+    ///
+    /// Returns true while stalled, when the clock should stand still
+    fn poll_buffering(&mut self) -> bool {
+        let buffering = self.decoder.as_ref().is_some_and(|decoder| decoder.is_buffering());
+        if buffering {
+            if self.state == PlaybackState::Playing {
+                self.sync_clock();
+                self.state = PlaybackState::Buffering;
+            }
+        } else if self.state == PlaybackState::Buffering {
+            // Carry on from where the stall froze the clock
+            self.state = PlaybackState::Playing;
+            self.playback_start = Some(Instant::now());
+            self.playback_start_pos = self.current_time_ms;
+        }
+        buffering
+    }
+
     /// Decode the next frame from the decoder
-    fn decode_next_frame(&mut self) {
+    ///
+    /// Returns true if a frame was decoded
+    fn decode_next_frame(&mut self) -> bool {
         if let Some(decoder) = &mut self.decoder {
             if let Some(frame) = decoder.next_frame() {
                 self.current_frame = Some(frame);
                 self.frame_dirty = true;
+                return true;
             } else if !decoder.has_more_frames() {
-                // End of video
-                if self.looping {
-                    // Seek to beginning and continue
-                    if decoder.seek(0).is_ok() {
-                        self.current_time_ms = 0;
-                        self.playback_start_pos = 0;
-                        self.playback_start = Some(Instant::now());
-                        // Try to get first frame
-                        if let Some(frame) = decoder.next_frame() {
-                            self.current_frame = Some(frame);
-                            self.frame_dirty = true;
-                        }
-                    }
-                } else {
-                    self.state = PlaybackState::Ended;
-                    self.playback_start = None;
+                self.frames_done = true;
+            }
+        }
+        false
+    }
+
+    /// End or loop playback once the last frame is shown and the last
+    /// audio sample played
+    fn finish_if_done(&mut self) {
+        if !self.frames_done || self.audio.as_ref().is_some_and(|audio| !audio.ended()) {
+            return;
+        }
+
+        if self.looping {
+            // Seek to beginning and continue
+            if self.seek(0).is_ok() {
+                if let Some(audio) = &mut self.audio {
+                    audio.play();
                 }
             }
+        } else {
+            self.state = PlaybackState::Ended;
+            self.playback_start = None;
         }
     }
 
@@ -330,6 +435,8 @@ impl VideoPlayer {
     fn reset(&mut self) {
         self.decoder = None;
         self.frame_buffer = None;
+        self.audio = None;
+        self.frames_done = false;
         self.texture_id = None;
         self.state = PlaybackState::Idle;
         self.current_time_ms = 0;
@@ -346,3 +453,193 @@ impl Default for VideoPlayer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Decoder of `count` black frames at 30fps, stalled while the test
+    /// says so
+    struct MockDecoder {
+        info: VideoInfo,
+        count: u64,
+        next: u64,
+        stalled: Arc<Mutex<bool>>,
+    }
+
+    impl MockDecoder {
+        fn new(count: u64) -> Self {
+            let info = VideoInfo { width: 2, height: 2, duration_ms: count * 33, ..Default::default() };
+            Self { info, count, next: 0, stalled: Arc::default() }
+        }
+    }
+
+    impl VideoDecoder for MockDecoder {
+        fn info(&self) -> &VideoInfo {
+            &self.info
+        }
+
+        fn next_frame(&mut self) -> Option<VideoFrame> {
+            if self.next == self.count {
+                return None;
+            }
+            let mut frame = VideoFrame::black(2, 2);
+            frame.timestamp_ms = self.next * 33;
+            self.next += 1;
+            Some(frame)
+        }
+
+        fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+            self.next = (timestamp_ms / 33).min(self.count);
+            Ok(())
+        }
+
+        fn has_more_frames(&self) -> bool {
+            self.next < self.count
+        }
+
+        fn current_time_ms(&self) -> u64 {
+            self.next * 33
+        }
+
+        fn is_buffering(&self) -> bool {
+            *self.stalled.lock().unwrap()
+        }
+    }
+
+    /// What a mock audio track was told, and the clock the test drives it by
+    #[derive(Default)]
+    struct AudioState {
+        time_ms: u64,
+        ended: bool,
+        playing: bool,
+        muted: bool,
+        volume: f32,
+        seeks: Vec<u64>,
+    }
+
+    struct MockAudio(Arc<Mutex<AudioState>>);
+
+    impl AudioTrack for MockAudio {
+        fn play(&mut self) {
+            self.0.lock().unwrap().playing = true;
+        }
+
+        fn pause(&mut self) {
+            self.0.lock().unwrap().playing = false;
+        }
+
+        fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+            let mut state = self.0.lock().unwrap();
+            state.time_ms = timestamp_ms;
+            state.ended = false;
+            state.seeks.push(timestamp_ms);
+            Ok(())
+        }
+
+        fn set_volume(&mut self, volume: f32) {
+            self.0.lock().unwrap().volume = volume;
+        }
+
+        fn set_muted(&mut self, muted: bool) {
+            self.0.lock().unwrap().muted = muted;
+        }
+
+        fn update(&mut self) {}
+
+        fn current_time_ms(&self) -> u64 {
+            self.0.lock().unwrap().time_ms
+        }
+
+        fn ended(&self) -> bool {
+            self.0.lock().unwrap().ended
+        }
+    }
+
+    #[test]
+    fn test_ended_waits_for_last_audio_sample() {
+        let audio = Arc::new(Mutex::new(AudioState::default()));
+        let mut player = VideoPlayer::new();
+        player.load_decoder(Box::new(MockDecoder::new(3)), Some(Box::new(MockAudio(audio.clone()))));
+        player.set_volume(0.5);
+        player.set_muted(true);
+        player.play();
+        assert!(audio.lock().unwrap().playing);
+        assert!(audio.lock().unwrap().muted);
+        assert_eq!(audio.lock().unwrap().volume, 0.5);
+
+        // Frames follow the audio clock, skipping straight to the one it's at
+        audio.lock().unwrap().time_ms = 70;
+        assert!(player.update());
+        assert_eq!(player.take_frame().map(|f| f.timestamp_ms), Some(66));
+
+        // Past the last frame the audio is still going
+        audio.lock().unwrap().time_ms = 150;
+        player.update();
+        assert_eq!(player.state(), PlaybackState::Playing);
+        assert_eq!(player.current_time_ms(), 150);
+
+        audio.lock().unwrap().ended = true;
+        player.update();
+        assert_eq!(player.state(), PlaybackState::Ended);
+
+        // Seeking takes the audio along
+        player.seek(33).unwrap();
+        assert_eq!(audio.lock().unwrap().seeks, vec![33]);
+        assert_eq!(player.take_frame().map(|f| f.timestamp_ms), Some(33));
+    }
+
+    #[test]
+    fn test_looping_restarts_audio_with_frames() {
+        let audio = Arc::new(Mutex::new(AudioState::default()));
+        let mut player = VideoPlayer::new();
+        player.load_decoder(Box::new(MockDecoder::new(2)), Some(Box::new(MockAudio(audio.clone()))));
+        player.set_looping(true);
+        player.play();
+
+        audio.lock().unwrap().time_ms = 100;
+        player.update();
+        {
+            let mut state = audio.lock().unwrap();
+            state.ended = true;
+            state.playing = false;
+        }
+        player.update();
+        assert_eq!(player.state(), PlaybackState::Playing);
+        assert_eq!(player.current_time_ms(), 0);
+        let state = audio.lock().unwrap();
+        assert_eq!(state.seeks, vec![0]);
+        assert!(state.playing && !state.ended);
+    }
+
+    #[test]
+    fn test_stall_buffers_without_advancing() {
+        let decoder = MockDecoder::new(100);
+        let stalled = decoder.stalled.clone();
+        let audio = Arc::new(Mutex::new(AudioState::default()));
+        let mut player = VideoPlayer::new();
+        player.load_decoder(Box::new(decoder), Some(Box::new(MockAudio(audio.clone()))));
+        player.play();
+        assert_eq!(player.buffered_ms(), 3300);
+
+        audio.lock().unwrap().time_ms = 100;
+        *stalled.lock().unwrap() = true;
+        player.update();
+        assert_eq!(player.state(), PlaybackState::Buffering);
+        assert_eq!(player.current_time_ms(), 100);
+
+        *stalled.lock().unwrap() = false;
+        audio.lock().unwrap().time_ms = 200;
+        player.update();
+        assert_eq!(player.state(), PlaybackState::Playing);
+        assert_eq!(player.current_time_ms(), 200);
+
+        // Pausing mid-stall pauses, and doesn't move the clock
+        *stalled.lock().unwrap() = true;
+        player.update();
+        player.pause();
+        assert_eq!(player.state(), PlaybackState::Paused);
+        assert!(!audio.lock().unwrap().playing);
+    }
+}
@@ -0,0 +1,152 @@
+//! Frame callback watchdog
+//!
+//! The app loop calls into Go for every event, and if that call deadlocks or
+//! runs long (a GC pause, a blocking call) the UI freezes with nothing in the
+//! logs. The watchdog times each call from a background thread. When one
+//! runs past the threshold it reports the stall once, then again every
+//! threshold interval until the call returns, so the host can log it and
+//! re-present the last frame. It never interrupts the call.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A call that has run past the threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stall {
+    /// Time since the call started
+    pub elapsed: Duration,
+    /// First report for this call (later reports repeat every threshold)
+    pub first: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Call {
+    id: u64,
+    started: Instant,
+}
+
+struct State {
+    /// None disables the watchdog
+    threshold: Option<Duration>,
+    call: Option<Call>,
+    next_id: u64,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+/// Times calls made through `watch` and reports the ones that stall
+pub struct Watchdog {
+    shared: Arc<Shared>,
+}
+
+impl Watchdog {
+    /// Start the watchdog thread. `on_stall` runs on that thread.
+    pub fn spawn(threshold: Option<Duration>, on_stall: impl Fn(Stall) + Send + 'static) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { threshold, call: None, next_id: 0, shutdown: false }),
+            wake: Condvar::new(),
+        });
+        let thread_shared = shared.clone();
+        thread::Builder::new()
+            .name("centered-watchdog".into())
+            .spawn(move || run(&thread_shared, on_stall))
+            .expect("failed to spawn watchdog thread");
+        Self { shared }
+    }
+
+    /// Change the threshold; None turns the watchdog off
+    pub fn set_threshold(&self, threshold: Option<Duration>) {
+        self.shared.state.lock().unwrap().threshold = threshold;
+        self.shared.wake.notify_one();
+    }
+
+    /// Run `f`, reporting it if it stalls
+    pub fn watch<R>(&self, f: impl FnOnce() -> R) -> R {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.next_id += 1;
+            state.call = Some(Call { id: state.next_id, started: Instant::now() });
+        }
+        self.shared.wake.notify_one();
+        let result = f();
+        self.shared.state.lock().unwrap().call = None;
+        result
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.wake.notify_one();
+    }
+}
+
+fn run(shared: &Shared, on_stall: impl Fn(Stall)) {
+    // Reports made so far for the call with this id
+    let mut reported: (u64, u32) = (0, 0);
+    let mut state = shared.state.lock().unwrap();
+    loop {
+        if state.shutdown {
+            return;
+        }
+        let (Some(call), Some(threshold)) = (state.call, state.threshold) else {
+            state = shared.wake.wait(state).unwrap();
+            continue;
+        };
+
+        let reports = if reported.0 == call.id { reported.1 } else { 0 };
+        let deadline = call.started + threshold * (reports + 1);
+        let now = Instant::now();
+        if now < deadline {
+            state = shared.wake.wait_timeout(state, deadline - now).unwrap().0;
+            continue;
+        }
+
+        reported = (call.id, reports + 1);
+        drop(state);
+        on_stall(Stall { elapsed: now - call.started, first: reports == 0 });
+        state = shared.state.lock().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_stalled_callback_warns_once() {
+        let warnings = Arc::new(AtomicU32::new(0));
+        let counter = warnings.clone();
+        let watchdog = Watchdog::spawn(Some(Duration::from_millis(20)), move |stall| {
+            if stall.first {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // Fast calls are never reported
+        for _ in 0..5 {
+            watchdog.watch(|| thread::sleep(Duration::from_millis(1)));
+        }
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+
+        // A call sleeping several thresholds long warns exactly once
+        let value = watchdog.watch(|| {
+            thread::sleep(Duration::from_millis(120));
+            42
+        });
+        assert_eq!(value, 42);
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+
+        // Disabled: even a slow call isn't reported
+        watchdog.set_threshold(None);
+        watchdog.watch(|| thread::sleep(Duration::from_millis(60)));
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+    }
+}
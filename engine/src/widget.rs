@@ -1,13 +1,23 @@
 //! Widget system - mode-agnostic widget tree management
 //!
+//! In retained mode Go sends a `WidgetDelta` each frame instead of the whole
+//! command list. Each widget carries its bounds and the commands that draw
+//! it, so the tree can produce the frame itself and knows which rects a
+//! delta touched.
+//!
 //! Performance considerations:
-//! - SlotMap for O(1) lookups and cache-friendly iteration
+//! - Dense secondary map for O(1) lookups and cache-friendly iteration,
+//!   which also accepts ids chosen by Go
 //! - Minimal allocations during tree traversal
 //! - Efficient dirty tracking for retained mode
 
+use crate::geometry::Rect;
 use crate::layout::LayoutNodeId;
+use crate::render::RenderCommand;
 use serde::{Deserialize, Serialize};
-use slotmap::{new_key_type, SlotMap};
+use slotmap::{new_key_type, Key, KeyData, SecondaryMap};
+
+pub mod accessibility;
 
 new_key_type! {
     /// Unique identifier for widgets
@@ -64,7 +74,7 @@ impl WidgetState {
 }
 
 /// Widget data (properties that can be set from Go)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WidgetData {
     /// Widget type
     pub kind: WidgetKind,
@@ -74,6 +84,21 @@ pub struct WidgetData {
     pub text: Option<String>,
     /// Custom data (JSON blob for app-specific data)
     pub custom_data: Option<String>,
+    /// Name screen readers announce where `text` isn't it (icon buttons,
+    /// text fields)
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Value screen readers announce where `text` isn't it (slider
+    /// position, checked state)
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Area the widget draws in, in window coordinates (logical pixels)
+    #[serde(default)]
+    pub bounds: Rect,
+    /// Commands that draw the widget, in window coordinates, before its
+    /// children
+    #[serde(default)]
+    pub commands: Vec<RenderCommand>,
 }
 
 /// Widget node in the tree
@@ -102,6 +127,10 @@ impl Widget {
                 classes: String::new(),
                 text: None,
                 custom_data: None,
+                label: None,
+                value: None,
+                bounds: Rect::default(),
+                commands: Vec::new(),
             },
             parent: None,
             children: Vec::new(),
@@ -117,30 +146,58 @@ impl Widget {
         self.dirty = true;
         self.generation += 1;
     }
+
+    /// A widget for data that arrived in a delta
+    fn from_data(data: WidgetData) -> Self {
+        Self { data, ..Self::new(WidgetKind::Container) }
+    }
 }
 
+/// Damage covering more than this fraction of the root widget redraws the
+/// whole frame instead; a scissor that large saves little
+const FULL_REDRAW_DAMAGE_FRACTION: f32 = 0.5;
+
 /// Widget tree - central data structure for the widget system
 pub struct WidgetTree {
     /// Widget storage
-    widgets: SlotMap<WidgetId, Widget>,
+    widgets: SecondaryMap<WidgetId, Widget>,
+    /// Slot index the next `create_widget` tries
+    next_index: u32,
     /// Root widget
     root: Option<WidgetId>,
     /// Current generation (for change tracking)
     generation: u64,
+    /// Whole frame must be redrawn regardless of widget dirty flags
+    /// (first frame, resize, running animations)
+    frame_invalidated: bool,
+    /// Union of the rects changed since the damage was last taken
+    damage: Option<Rect>,
 }
 
 impl WidgetTree {
     pub fn new() -> Self {
         Self {
-            widgets: SlotMap::with_key(),
+            widgets: SecondaryMap::new(),
+            next_index: 0,
             root: None,
             generation: 0,
+            frame_invalidated: true,
+            damage: None,
         }
     }
 
     /// Create a new widget
     pub fn create_widget(&mut self, kind: WidgetKind) -> WidgetId {
-        self.widgets.insert(Widget::new(kind))
+        // Skip slots a delta has already filled
+        let id = loop {
+            let id = WidgetId::from(KeyData::from_ffi((1 << 32) | u64::from(self.next_index)));
+            self.next_index += 1;
+            if !self.widgets.contains_key(id) {
+                break id;
+            }
+        };
+        self.widgets.insert(id, Widget::new(kind));
+        id
     }
 
     /// Get a widget by ID
@@ -217,11 +274,222 @@ impl WidgetTree {
         }
     }
 
+    /// Mark a widget, everything under it and its ancestors as dirty
+    pub fn mark_subtree_dirty(&mut self, id: WidgetId) {
+        for descendant in self.subtree(id) {
+            if let Some(widget) = self.widgets.get_mut(descendant) {
+                widget.mark_dirty();
+            }
+        }
+        self.mark_dirty(id);
+    }
+
+    /// `id` and every widget under it, parents before children
+    fn subtree(&self, id: WidgetId) -> Vec<WidgetId> {
+        let mut ids = Vec::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(widget) = self.widgets.get(id) {
+                ids.push(id);
+                stack.extend(widget.children.iter().rev());
+            }
+        }
+        ids
+    }
+
+    /// Mark the widgets whose class string `affected` returns true for, and
+    /// their ancestors, dirty. Returns how many widgets matched.
+    pub fn mark_dirty_by_classes(&mut self, affected: impl Fn(&str) -> bool) -> usize {
+        let matched: Vec<WidgetId> = self.widgets
+            .iter()
+            .filter(|(_, widget)| affected(&widget.data.classes))
+            .map(|(id, _)| id)
+            .collect();
+        for &id in &matched {
+            self.mark_dirty(id);
+            self.add_damage(self.widgets[id].data.bounds);
+        }
+        matched.len()
+    }
+
+    /// Apply a retained mode delta, marking changed subtrees dirty.
+    ///
+    /// Removals go first, then updates, then reparenting, so one delta can
+    /// add a widget and attach it. An update for an id the tree doesn't
+    /// know adds the widget, unattached; the first widget added to a tree
+    /// without a root becomes the root. Updates whose data matches the
+    /// current widget are ignored, so a delta that changes nothing leaves
+    /// the tree clean. Reparenting entries are `(parent, child)` pairs and
+    /// move the child's whole subtree; moves that would make a cycle, move
+    /// the root, or name unknown ids are skipped.
+    ///
+    /// Returns the rects that must be redrawn: the old and new bounds of
+    /// each changed widget and the bounds of every widget in a removed or
+    /// moved subtree. Empty rects and duplicates are left out. They are
+    /// also added to the damage `take_damage` hands out.
+    pub fn apply_delta(&mut self, delta: WidgetDelta) -> Vec<Rect> {
+        let mut changed = false;
+        let mut dirty_rects = Vec::new();
+
+        for id in delta.removals {
+            let Some(parent) = self.widgets.get(id).map(|w| w.parent) else {
+                continue;
+            };
+            dirty_rects.extend(self.subtree(id).into_iter().map(|id| self.widgets[id].data.bounds));
+            if let Some(parent_id) = parent {
+                self.remove_child(parent_id, id);
+                self.mark_dirty(parent_id);
+            }
+            if self.root == Some(id) {
+                self.root = None;
+                self.frame_invalidated = true;
+            }
+            self.remove_widget(id);
+            changed = true;
+        }
+
+        for (id, data) in delta.updates {
+            if id.is_null() {
+                continue;
+            }
+            match self.widgets.get_mut(id) {
+                Some(widget) if widget.data == data => {}
+                Some(widget) => {
+                    dirty_rects.extend([widget.data.bounds, data.bounds]);
+                    widget.data = data;
+                    self.mark_dirty(id);
+                    changed = true;
+                }
+                None => {
+                    dirty_rects.push(data.bounds);
+                    self.widgets.insert(id, Widget::from_data(data));
+                    if self.root.is_none() {
+                        self.root = Some(id);
+                    }
+                    changed = true;
+                }
+            }
+        }
+
+        for (parent_id, child_id) in delta.reparenting {
+            if !self.widgets.contains_key(parent_id) || self.root == Some(child_id) {
+                continue;
+            }
+            let Some(old_parent) = self.widgets.get(child_id).map(|w| w.parent) else {
+                continue;
+            };
+            let moved = self.subtree(child_id);
+            if old_parent == Some(parent_id) || moved.contains(&parent_id) {
+                continue;
+            }
+            if let Some(old_parent_id) = old_parent {
+                self.remove_child(old_parent_id, child_id);
+                self.mark_dirty(old_parent_id);
+            }
+            self.add_child(parent_id, child_id);
+            self.mark_subtree_dirty(child_id);
+            dirty_rects.extend(moved.into_iter().map(|id| self.widgets[id].data.bounds));
+            changed = true;
+        }
+
+        if changed {
+            self.generation += 1;
+        }
+        dirty_rects.retain(|rect| !rect.is_empty());
+        let mut unique = Vec::with_capacity(dirty_rects.len());
+        for rect in dirty_rects {
+            if !unique.contains(&rect) {
+                self.add_damage(rect);
+                unique.push(rect);
+            }
+        }
+        unique
+    }
+
+    /// Take the region changed since the last call, for scissoring the
+    /// next frame to it.
+    ///
+    /// None means redraw everything: the frame was invalidated (first frame,
+    /// resize, animations), nothing was damaged, or the damage covers more
+    /// than half the root widget, where a scissor saves little. Check
+    /// `needs_render` first; a frame with nothing dirty needs no redraw.
+    pub fn take_damage(&mut self) -> Option<Rect> {
+        let damage = self.damage.take()?;
+        if self.frame_invalidated {
+            return None;
+        }
+        let screen = self.root.and_then(|id| self.widgets.get(id)).map(|w| w.data.bounds).unwrap_or_default();
+        if !screen.is_empty() && damage.width * damage.height > FULL_REDRAW_DAMAGE_FRACTION * screen.width * screen.height {
+            return None;
+        }
+        Some(damage)
+    }
+
+    fn add_damage(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        self.damage = Some(self.damage.map_or(rect, |damage| damage.union(&rect)));
+    }
+
+    /// Commands that draw the tree: each widget's commands, parents before
+    /// children, siblings in order. Hidden widgets are skipped along with
+    /// everything under them.
+    pub fn render_commands(&self) -> Vec<RenderCommand> {
+        let mut commands = Vec::new();
+        let mut stack: Vec<WidgetId> = self.root.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            let Some(widget) = self.widgets.get(id) else {
+                continue;
+            };
+            if !widget.state.visible {
+                continue;
+            }
+            commands.extend(widget.data.commands.iter().cloned());
+            stack.extend(widget.children.iter().rev());
+        }
+        commands
+    }
+
+    /// Force the next frame to render even if no widget is dirty.
+    ///
+    /// Used for the first frame, resizes, scale factor changes, running
+    /// animations and hybrid-mode overlays, where the presented frame is
+    /// stale without a tree change.
+    pub fn invalidate_frame(&mut self) {
+        self.frame_invalidated = true;
+    }
+
+    /// True if a widget in the subtree rooted at `id` needs re-rendering.
+    ///
+    /// Dirty flags propagate to ancestors, so this only checks `id` itself.
+    pub fn is_subtree_dirty(&self, id: WidgetId) -> bool {
+        self.widgets.get(id).is_some_and(|w| w.dirty)
+    }
+
+    /// True if the next frame must be rendered.
+    ///
+    /// When false, the previously presented frame is still correct and the
+    /// render commands for it can be skipped entirely.
+    pub fn needs_render(&self) -> bool {
+        self.frame_invalidated || self.widgets.values().any(|w| w.dirty)
+    }
+
+    /// Clear all dirty flags after a frame has been rendered
+    pub fn mark_rendered(&mut self) {
+        self.frame_invalidated = false;
+        for widget in self.widgets.values_mut() {
+            widget.dirty = false;
+        }
+    }
+
     /// Clear the entire tree
     pub fn clear(&mut self) {
         self.widgets.clear();
         self.root = None;
+        self.damage = None;
         self.generation += 1;
+        self.frame_invalidated = true;
     }
 
     /// Increment generation counter
@@ -366,4 +634,254 @@ mod tests {
         assert_eq!(ids.len(), 3);
         assert_eq!(ids[0], root);
     }
+
+    #[test]
+    fn test_noop_deltas_render_once() {
+        let mut tree = WidgetTree::new();
+        let root = tree.create_widget(WidgetKind::VStack);
+        let label = tree.create_widget(WidgetKind::Text);
+        tree.set_root(root);
+        tree.add_child(root, label);
+
+        let mut renders = 0;
+        let mut frame = |tree: &mut WidgetTree, delta: &WidgetDelta| {
+            tree.apply_delta(delta.clone());
+            if tree.needs_render() {
+                renders += 1;
+                tree.mark_rendered();
+            }
+        };
+
+        // The first frame always renders; the same data again is a no-op
+        let same = WidgetDelta {
+            updates: vec![(label, tree.get_widget(label).unwrap().data.clone())],
+            ..WidgetDelta::new()
+        };
+        frame(&mut tree, &WidgetDelta::new());
+        frame(&mut tree, &same);
+        frame(&mut tree, &WidgetDelta::new());
+        assert_eq!(renders, 1);
+
+        // A real change dirties the label and its ancestors
+        let mut data = tree.get_widget(label).unwrap().data.clone();
+        data.text = Some("changed".to_string());
+        data.bounds = Rect::new(0.0, 0.0, 80.0, 20.0);
+        assert_eq!(
+            tree.apply_delta(WidgetDelta { updates: vec![(label, data)], ..WidgetDelta::new() }),
+            vec![Rect::new(0.0, 0.0, 80.0, 20.0)]
+        );
+        assert!(tree.is_subtree_dirty(label));
+        assert!(tree.is_subtree_dirty(root));
+        assert!(tree.needs_render());
+        tree.mark_rendered();
+
+        // Resize invalidates the frame without dirtying widgets
+        tree.invalidate_frame();
+        assert!(tree.needs_render());
+        assert!(!tree.is_subtree_dirty(root));
+    }
+
+    /// An id as Go would send it
+    fn go_id(index: u64) -> WidgetId {
+        WidgetId::from(KeyData::from_ffi((1 << 32) | index))
+    }
+
+    /// A widget that fills `bounds` with `color`
+    fn filled(kind: WidgetKind, bounds: Rect, color: u32) -> WidgetData {
+        WidgetData {
+            kind,
+            classes: String::new(),
+            text: None,
+            custom_data: None,
+            label: None,
+            value: None,
+            bounds,
+            commands: vec![RenderCommand::DrawRect {
+                x: bounds.x, y: bounds.y, width: bounds.width, height: bounds.height,
+                color,
+                corner_radii: [0.0; 4],
+                rotation: 0.0,
+                pivot: None,
+                border: None,
+                gradient: None,
+                inner_shadow: None,
+                shadow: None,
+            }],
+        }
+    }
+
+    /// The fill colors of the tree's commands, in draw order
+    fn drawn_colors(tree: &WidgetTree) -> Vec<u32> {
+        tree.render_commands().iter().filter_map(|cmd| match cmd {
+            RenderCommand::DrawRect { color, .. } => Some(*color),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_delta_inserts_widgets_and_draws_them() {
+        let mut tree = WidgetTree::new();
+        let (root, a, b) = (go_id(1), go_id(2), go_id(3));
+        let dirty = tree.apply_delta(WidgetDelta {
+            updates: vec![
+                (root, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 100.0, 100.0), 1)),
+                (a, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 100.0, 20.0), 2)),
+                (b, filled(WidgetKind::Button, Rect::new(0.0, 20.0, 100.0, 20.0), 3)),
+            ],
+            removals: vec![],
+            reparenting: vec![(root, a), (root, b)],
+        });
+
+        // The first widget added becomes the root
+        assert_eq!(tree.root(), Some(root));
+        assert_eq!(tree.get_widget(root).unwrap().children, vec![a, b]);
+        assert_eq!(dirty.len(), 3);
+        assert_eq!(drawn_colors(&tree), vec![1, 2, 3]);
+
+        // Local widgets never reuse an id Go picked
+        let local = tree.create_widget(WidgetKind::Label);
+        assert!(![root, a, b].contains(&local));
+        assert_eq!(tree.widget_count(), 4);
+    }
+
+    #[test]
+    fn test_delta_removes_subtrees() {
+        let mut tree = WidgetTree::new();
+        let (root, list, item) = (go_id(1), go_id(2), go_id(3));
+        tree.apply_delta(WidgetDelta {
+            updates: vec![
+                (root, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 100.0, 100.0), 1)),
+                (list, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 50.0, 50.0), 2)),
+                (item, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 50.0, 10.0), 3)),
+            ],
+            removals: vec![],
+            reparenting: vec![(root, list), (list, item)],
+        });
+        tree.mark_rendered();
+
+        let dirty = tree.apply_delta(WidgetDelta { removals: vec![list], ..WidgetDelta::new() });
+        assert_eq!(dirty, vec![Rect::new(0.0, 0.0, 50.0, 50.0), Rect::new(0.0, 0.0, 50.0, 10.0)]);
+        assert!(tree.get_widget(list).is_none() && tree.get_widget(item).is_none());
+        assert!(tree.get_widget(root).unwrap().children.is_empty());
+        assert!(tree.is_subtree_dirty(root));
+        assert_eq!(drawn_colors(&tree), vec![1]);
+
+        // Removing an unknown id is a no-op
+        tree.mark_rendered();
+        assert!(tree.apply_delta(WidgetDelta { removals: vec![list], ..WidgetDelta::new() }).is_empty());
+        assert!(!tree.needs_render());
+    }
+
+    #[test]
+    fn test_delta_reparents_subtrees() {
+        let mut tree = WidgetTree::new();
+        let (root, left, right, item) = (go_id(1), go_id(2), go_id(3), go_id(4));
+        tree.apply_delta(WidgetDelta {
+            updates: vec![
+                (root, filled(WidgetKind::HStack, Rect::new(0.0, 0.0, 100.0, 50.0), 1)),
+                (left, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 50.0, 50.0), 2)),
+                (right, filled(WidgetKind::VStack, Rect::new(50.0, 0.0, 50.0, 50.0), 3)),
+                (item, filled(WidgetKind::Text, Rect::new(10.0, 10.0, 20.0, 10.0), 4)),
+            ],
+            removals: vec![],
+            reparenting: vec![(root, left), (root, right), (left, item)],
+        });
+        assert_eq!(drawn_colors(&tree), vec![1, 2, 4, 3]);
+        tree.mark_rendered();
+
+        let dirty = tree.apply_delta(WidgetDelta { reparenting: vec![(right, item)], ..WidgetDelta::new() });
+        assert_eq!(dirty, vec![Rect::new(10.0, 10.0, 20.0, 10.0)]);
+        assert!(tree.get_widget(left).unwrap().children.is_empty());
+        assert_eq!(tree.get_widget(item).unwrap().parent, Some(right));
+        assert!(tree.is_subtree_dirty(item) && tree.is_subtree_dirty(root));
+        assert_eq!(drawn_colors(&tree), vec![1, 2, 3, 4]);
+        tree.mark_rendered();
+
+        // A widget can't move under itself or its own descendants
+        let cycle = WidgetDelta { reparenting: vec![(item, right), (right, right)], ..WidgetDelta::new() };
+        assert!(tree.apply_delta(cycle).is_empty());
+        assert_eq!(tree.get_widget(right).unwrap().parent, Some(root));
+        assert!(!tree.needs_render());
+    }
+
+    #[test]
+    fn test_delta_property_update_dirties_only_its_path() {
+        let mut tree = WidgetTree::new();
+        let (root, a, b) = (go_id(1), go_id(2), go_id(3));
+        tree.apply_delta(WidgetDelta {
+            updates: vec![
+                (root, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 100.0, 100.0), 1)),
+                (a, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 100.0, 20.0), 2)),
+                (b, filled(WidgetKind::Text, Rect::new(0.0, 20.0, 100.0, 20.0), 3)),
+            ],
+            removals: vec![],
+            reparenting: vec![(root, a), (root, b)],
+        });
+        tree.mark_rendered();
+
+        // Same bounds, new color: one dirty rect, and b stays clean
+        let dirty = tree.apply_delta(WidgetDelta {
+            updates: vec![(a, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 100.0, 20.0), 5))],
+            ..WidgetDelta::new()
+        });
+        assert_eq!(dirty, vec![Rect::new(0.0, 0.0, 100.0, 20.0)]);
+        assert!(tree.is_subtree_dirty(a) && tree.is_subtree_dirty(root));
+        assert!(!tree.is_subtree_dirty(b));
+        assert_eq!(drawn_colors(&tree), vec![1, 5, 3]);
+
+        // Moving it dirties where it was and where it is now
+        let dirty = tree.apply_delta(WidgetDelta {
+            updates: vec![(a, filled(WidgetKind::Text, Rect::new(0.0, 60.0, 100.0, 20.0), 5))],
+            ..WidgetDelta::new()
+        });
+        assert_eq!(dirty, vec![Rect::new(0.0, 0.0, 100.0, 20.0), Rect::new(0.0, 60.0, 100.0, 20.0)]);
+    }
+
+    #[test]
+    fn test_damage_accumulates_until_taken() {
+        let mut tree = WidgetTree::new();
+        let (root, a, b) = (go_id(1), go_id(2), go_id(3));
+        tree.apply_delta(WidgetDelta {
+            updates: vec![
+                (root, filled(WidgetKind::VStack, Rect::new(0.0, 0.0, 400.0, 300.0), 1)),
+                (a, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 100.0, 20.0), 2)),
+                (b, filled(WidgetKind::Text, Rect::new(0.0, 20.0, 100.0, 20.0), 3)),
+            ],
+            removals: vec![],
+            reparenting: vec![(root, a), (root, b)],
+        });
+
+        // The first frame is drawn whole
+        assert_eq!(tree.take_damage(), None);
+        tree.mark_rendered();
+
+        // Two deltas before a frame: the old and new bounds of both changes
+        tree.apply_delta(WidgetDelta {
+            updates: vec![(a, filled(WidgetKind::Text, Rect::new(0.0, 50.0, 100.0, 20.0), 2))],
+            ..WidgetDelta::new()
+        });
+        tree.apply_delta(WidgetDelta {
+            updates: vec![(b, filled(WidgetKind::Text, Rect::new(0.0, 20.0, 100.0, 20.0), 4))],
+            ..WidgetDelta::new()
+        });
+        assert_eq!(tree.take_damage(), Some(Rect::new(0.0, 0.0, 100.0, 70.0)));
+        assert_eq!(tree.take_damage(), None);
+        tree.mark_rendered();
+
+        // Damage over most of the screen is a full redraw
+        tree.apply_delta(WidgetDelta {
+            updates: vec![(b, filled(WidgetKind::Text, Rect::new(0.0, 0.0, 400.0, 200.0), 4))],
+            ..WidgetDelta::new()
+        });
+        assert_eq!(tree.take_damage(), None);
+        tree.mark_rendered();
+
+        // So is an invalidated frame, whatever changed
+        tree.apply_delta(WidgetDelta {
+            updates: vec![(a, filled(WidgetKind::Text, Rect::new(0.0, 50.0, 100.0, 20.0), 6))],
+            ..WidgetDelta::new()
+        });
+        tree.invalidate_frame();
+        assert_eq!(tree.take_damage(), None);
+    }
 }
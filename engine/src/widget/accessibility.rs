@@ -0,0 +1,273 @@
+//! Accessibility tree - what screen readers see of the widget tree
+//!
+//! Each visible widget becomes a node with a role, a label, a value and its
+//! bounds. The app's tree is published after every widget delta, so the FFI
+//! and the platform accessibility APIs (called on their own schedule) read
+//! a consistent snapshot instead of the live tree.
+
+use super::{WidgetId, WidgetKind, WidgetTree};
+use crate::geometry::{Point2, Rect};
+use raw_window_handle::HasWindowHandle;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+/// What a node is, for screen readers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AccessibilityRole {
+    /// Containers and custom widgets
+    Group,
+    ScrollArea,
+    StaticText,
+    Heading,
+    Button,
+    TextField,
+    TextArea,
+    CheckBox,
+    RadioButton,
+    Slider,
+}
+
+impl AccessibilityRole {
+    pub fn from_kind(kind: &WidgetKind) -> Self {
+        match kind {
+            WidgetKind::VStack
+            | WidgetKind::HStack
+            | WidgetKind::ZStack
+            | WidgetKind::Container
+            | WidgetKind::Custom(_) => AccessibilityRole::Group,
+            WidgetKind::ScrollView => AccessibilityRole::ScrollArea,
+            WidgetKind::Text | WidgetKind::Label => AccessibilityRole::StaticText,
+            WidgetKind::Heading => AccessibilityRole::Heading,
+            WidgetKind::Button => AccessibilityRole::Button,
+            WidgetKind::TextField => AccessibilityRole::TextField,
+            WidgetKind::TextArea => AccessibilityRole::TextArea,
+            WidgetKind::Checkbox => AccessibilityRole::CheckBox,
+            WidgetKind::Radio => AccessibilityRole::RadioButton,
+            WidgetKind::Slider => AccessibilityRole::Slider,
+        }
+    }
+
+    /// Roles whose text is their name (a button's title) rather than their
+    /// value (a text field's contents)
+    fn text_is_label(self) -> bool {
+        matches!(
+            self,
+            AccessibilityRole::StaticText
+                | AccessibilityRole::Heading
+                | AccessibilityRole::Button
+                | AccessibilityRole::CheckBox
+                | AccessibilityRole::RadioButton
+        )
+    }
+}
+
+/// One widget as screen readers see it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccessibilityNode {
+    /// The widget, in the same form widget deltas use
+    pub id: WidgetId,
+    pub role: AccessibilityRole,
+    /// Name to announce: the widget's `label`, else its text for roles
+    /// named by their text
+    pub label: Option<String>,
+    /// Value to announce: the widget's `value`, else a text field's text
+    pub value: Option<String>,
+    /// Window coordinates, logical pixels
+    pub bounds: Rect,
+    pub focused: bool,
+    pub disabled: bool,
+    pub children: Vec<AccessibilityNode>,
+}
+
+impl AccessibilityNode {
+    /// Build the node for `id` and everything under it; None if the widget
+    /// is unknown or hidden
+    fn from_widget(widgets: &WidgetTree, id: WidgetId) -> Option<Self> {
+        let widget = widgets.get_widget(id)?;
+        if !widget.state.visible {
+            return None;
+        }
+        let data = &widget.data;
+        let role = AccessibilityRole::from_kind(&data.kind);
+        let text_is_label = role.text_is_label();
+        let text_is_value = matches!(role, AccessibilityRole::TextField | AccessibilityRole::TextArea);
+
+        Some(Self {
+            id,
+            role,
+            label: data.label.clone().or_else(|| data.text.clone().filter(|_| text_is_label)),
+            value: data.value.clone().or_else(|| data.text.clone().filter(|_| text_is_value)),
+            bounds: data.bounds,
+            focused: widget.state.focused,
+            disabled: widget.state.disabled,
+            children: widget
+                .children
+                .iter()
+                .filter_map(|&child| Self::from_widget(widgets, child))
+                .collect(),
+        })
+    }
+}
+
+/// Snapshot of the widget tree for screen readers
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AccessibilityTree {
+    /// None when the widget tree is empty (immediate mode apps)
+    pub root: Option<AccessibilityNode>,
+}
+
+impl AccessibilityTree {
+    pub fn from_widgets(widgets: &WidgetTree) -> Self {
+        Self {
+            root: widgets.root().and_then(|id| AccessibilityNode::from_widget(widgets, id)),
+        }
+    }
+
+    /// The deepest labeled node under `point` (window coordinates, logical
+    /// pixels). Later siblings draw on top, so they are checked first.
+    pub fn hit_test(&self, point: Point2) -> Option<&AccessibilityNode> {
+        fn visit(node: &AccessibilityNode, point: Point2) -> Option<&AccessibilityNode> {
+            if !node.bounds.contains(point) {
+                return None;
+            }
+            node.children
+                .iter()
+                .rev()
+                .find_map(|child| visit(child, point))
+                .or_else(|| node.label.is_some().then_some(node))
+        }
+        self.root.as_ref().and_then(|root| visit(root, point))
+    }
+
+    /// Nodes with a label, parents before children
+    pub fn labeled_nodes(&self) -> Vec<&AccessibilityNode> {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<&AccessibilityNode> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.label.is_some() {
+                nodes.push(node);
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        nodes
+    }
+}
+
+struct Published {
+    /// Bumped on every change, so readers can tell a new snapshot apart
+    generation: u64,
+    /// `WidgetTree::generation` the snapshot was built from
+    widget_generation: Option<u64>,
+    tree: AccessibilityTree,
+}
+
+fn published() -> &'static Mutex<Published> {
+    static PUBLISHED: OnceLock<Mutex<Published>> = OnceLock::new();
+    PUBLISHED.get_or_init(|| {
+        Mutex::new(Published { generation: 0, widget_generation: None, tree: AccessibilityTree::default() })
+    })
+}
+
+/// Rebuild the published tree from `widgets` if it changed since the last
+/// call. Cheap when nothing changed, so call it after every delta.
+pub fn publish(widgets: &WidgetTree) {
+    let mut published = published().lock().unwrap();
+    if published.widget_generation == Some(widgets.generation()) {
+        return;
+    }
+    published.widget_generation = Some(widgets.generation());
+    published.generation += 1;
+    published.tree = AccessibilityTree::from_widgets(widgets);
+}
+
+/// The last published tree
+pub fn current_tree() -> AccessibilityTree {
+    published().lock().unwrap().tree.clone()
+}
+
+/// Run `f` on the last published tree and its generation without copying it
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn with_current_tree<R>(f: impl FnOnce(u64, &AccessibilityTree) -> R) -> R {
+    let published = published().lock().unwrap();
+    f(published.generation, &published.tree)
+}
+
+/// Expose the published tree through the platform accessibility API
+///
+/// - macOS: labeled nodes become `NSAccessibilityElement` children of the
+///   window's view, found by VoiceOver's hit testing
+/// - Other platforms: not yet supported, a no-op
+pub fn install<W: HasWindowHandle>(window: &W) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let handle = window
+            .window_handle()
+            .map_err(|e| format!("Failed to get window handle: {}", e))?;
+        if let raw_window_handle::RawWindowHandle::AppKit(appkit_handle) = handle.as_raw() {
+            return macos::install(appkit_handle);
+        }
+    }
+    let _ = window;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{WidgetData, WidgetDelta};
+
+    fn widget(kind: WidgetKind, bounds: Rect, text: Option<&str>) -> WidgetData {
+        WidgetData {
+            kind,
+            classes: String::new(),
+            text: text.map(str::to_string),
+            custom_data: None,
+            label: None,
+            value: None,
+            bounds,
+            commands: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_tree_from_widgets() {
+        let mut widgets = WidgetTree::new();
+        let root = widgets.create_widget(WidgetKind::VStack);
+        let button = widgets.create_widget(WidgetKind::Button);
+        let field = widgets.create_widget(WidgetKind::TextField);
+        let hidden = widgets.create_widget(WidgetKind::Text);
+        widgets.set_root(root);
+
+        let mut delta = WidgetDelta::new();
+        delta.updates.push((root, widget(WidgetKind::VStack, Rect::new(0.0, 0.0, 200.0, 100.0), None)));
+        delta.updates.push((button, widget(WidgetKind::Button, Rect::new(10.0, 10.0, 80.0, 30.0), Some("Save"))));
+        let mut field_data = widget(WidgetKind::TextField, Rect::new(10.0, 50.0, 180.0, 30.0), Some("draft"));
+        field_data.label = Some("Name".to_string());
+        delta.updates.push((field, field_data));
+        delta.reparenting.extend([(root, button), (root, field), (root, hidden)]);
+        widgets.apply_delta(delta);
+        widgets.get_widget_mut(hidden).unwrap().state.visible = false;
+
+        let tree = AccessibilityTree::from_widgets(&widgets);
+        let root_node = tree.root.as_ref().unwrap();
+        assert_eq!(root_node.role, AccessibilityRole::Group);
+        assert_eq!(root_node.children.len(), 2);
+
+        let button_node = &root_node.children[0];
+        assert_eq!(button_node.role, AccessibilityRole::Button);
+        assert_eq!(button_node.label.as_deref(), Some("Save"));
+        assert_eq!(button_node.value, None);
+
+        let field_node = &root_node.children[1];
+        assert_eq!(field_node.label.as_deref(), Some("Name"));
+        assert_eq!(field_node.value.as_deref(), Some("draft"));
+
+        assert_eq!(tree.labeled_nodes().len(), 2);
+        assert_eq!(tree.hit_test(Point2::new(20.0, 20.0)).map(|n| n.id), Some(button));
+        // Inside the root but over no labeled node
+        assert!(tree.hit_test(Point2::new(150.0, 20.0)).is_none());
+    }
+}
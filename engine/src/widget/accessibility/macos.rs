@@ -0,0 +1,169 @@
+//! VoiceOver support for the published accessibility tree
+//!
+//! The window's view gets an `NSAccessibilityElement` child for each
+//! labeled node, rebuilt when a new tree is published, and a hit test that
+//! finds the node under the pointer. winit owns the view's class, so both
+//! methods are replaced on it at runtime.
+
+use super::{with_current_tree, AccessibilityNode, AccessibilityRole};
+use crate::geometry::{Point2, Rect};
+use crate::widget::WidgetId;
+use cocoa::base::{id, nil, BOOL, NO};
+use cocoa::foundation::{NSArray, NSPoint, NSRect, NSSize, NSString};
+use objc::runtime::{object_getClass, Class, Imp, Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use raw_window_handle::AppKitWindowHandle;
+use std::cell::RefCell;
+use std::ffi::c_char;
+
+#[link(name = "objc")]
+extern "C" {
+    fn class_replaceMethod(cls: *const Class, name: Sel, imp: Imp, types: *const c_char) -> Option<Imp>;
+}
+
+/// Elements made for one published tree
+struct Elements {
+    generation: u64,
+    view: id,
+    /// Retained until the next rebuild
+    elements: Vec<(WidgetId, id)>,
+}
+
+thread_local! {
+    // AppKit only asks on the main thread
+    static ELEMENTS: RefCell<Option<Elements>> = const { RefCell::new(None) };
+}
+
+fn ax_role(role: AccessibilityRole) -> &'static str {
+    match role {
+        AccessibilityRole::Group => "AXGroup",
+        AccessibilityRole::ScrollArea => "AXScrollArea",
+        AccessibilityRole::StaticText => "AXStaticText",
+        AccessibilityRole::Heading => "AXHeading",
+        AccessibilityRole::Button => "AXButton",
+        AccessibilityRole::TextField => "AXTextField",
+        AccessibilityRole::TextArea => "AXTextArea",
+        AccessibilityRole::CheckBox => "AXCheckBox",
+        AccessibilityRole::RadioButton => "AXRadioButton",
+        AccessibilityRole::Slider => "AXSlider",
+    }
+}
+
+unsafe fn ns_string(text: &str) -> id {
+    let string = NSString::alloc(nil).init_str(text);
+    msg_send![string, autorelease]
+}
+
+/// Height for flipping y, or None if the view is already top-down
+unsafe fn flip_height(view: id) -> Option<f64> {
+    let flipped: BOOL = msg_send![view, isFlipped];
+    if flipped != NO {
+        return None;
+    }
+    let bounds: NSRect = msg_send![view, bounds];
+    Some(bounds.size.height)
+}
+
+/// Window rect (logical pixels, top-down) in the view's coordinates
+unsafe fn view_rect(view: id, rect: Rect) -> NSRect {
+    let y = match flip_height(view) {
+        Some(height) => height - (rect.y + rect.height) as f64,
+        None => rect.y as f64,
+    };
+    NSRect::new(NSPoint::new(rect.x as f64, y), NSSize::new(rect.width as f64, rect.height as f64))
+}
+
+unsafe fn create_element(view: id, node: &AccessibilityNode) -> id {
+    let element: id = msg_send![class!(NSAccessibilityElement), new];
+    let _: () = msg_send![element, setAccessibilityRole: ns_string(ax_role(node.role))];
+    if let Some(label) = &node.label {
+        let _: () = msg_send![element, setAccessibilityLabel: ns_string(label)];
+    }
+    if let Some(value) = &node.value {
+        let _: () = msg_send![element, setAccessibilityValue: ns_string(value)];
+    }
+    let _: () = msg_send![element, setAccessibilityEnabled: !node.disabled];
+    let _: () = msg_send![element, setAccessibilityFocused: node.focused];
+    let _: () = msg_send![element, setAccessibilityParent: view];
+    let _: () = msg_send![element, setAccessibilityFrameInParentSpace: view_rect(view, node.bounds)];
+    element
+}
+
+/// The view's elements for the current tree, rebuilt if a new one was
+/// published since they were made
+unsafe fn elements(view: id) -> Vec<(WidgetId, id)> {
+    with_current_tree(|generation, tree| {
+        ELEMENTS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let stale = !matches!(&*cache, Some(c) if c.generation == generation && c.view == view);
+            if stale {
+                if let Some(old) = cache.take() {
+                    for (_, element) in old.elements {
+                        let _: () = msg_send![element, release];
+                    }
+                }
+                let elements = tree
+                    .labeled_nodes()
+                    .into_iter()
+                    .map(|node| (node.id, create_element(view, node)))
+                    .collect();
+                *cache = Some(Elements { generation, view, elements });
+            }
+            cache.as_ref().map(|c| c.elements.clone()).unwrap_or_default()
+        })
+    })
+}
+
+extern "C" fn accessibility_children(this: &Object, _sel: Sel) -> id {
+    unsafe {
+        let view = this as *const Object as id;
+        let elements: Vec<id> = elements(view).into_iter().map(|(_, element)| element).collect();
+        NSArray::arrayWithObjects(nil, &elements)
+    }
+}
+
+extern "C" fn accessibility_hit_test(this: &Object, _sel: Sel, point: NSPoint) -> id {
+    unsafe {
+        let view = this as *const Object as id;
+        let window: id = msg_send![view, window];
+        if window == nil {
+            return view;
+        }
+
+        // Screen point to window coordinates, top-down
+        let in_window: NSPoint = msg_send![window, convertPointFromScreen: point];
+        let in_view: NSPoint = msg_send![view, convertPoint: in_window fromView: nil];
+        let y = flip_height(view).map_or(in_view.y, |height| height - in_view.y);
+        let point = Point2::new(in_view.x as f32, y as f32);
+
+        let hit = with_current_tree(|_, tree| tree.hit_test(point).map(|node| node.id));
+        hit.and_then(|hit| elements(view).into_iter().find(|(node, _)| *node == hit))
+            .map_or(view, |(_, element)| element)
+    }
+}
+
+pub fn install(handle: AppKitWindowHandle) -> Result<(), String> {
+    unsafe {
+        let view = handle.ns_view.as_ptr() as id;
+        if view == nil {
+            return Err("NSView handle is null".to_string());
+        }
+
+        let class = object_getClass(view);
+        let children: extern "C" fn(&Object, Sel) -> id = accessibility_children;
+        let hit_test: extern "C" fn(&Object, Sel, NSPoint) -> id = accessibility_hit_test;
+        class_replaceMethod(
+            class,
+            sel!(accessibilityChildren),
+            std::mem::transmute::<extern "C" fn(&Object, Sel) -> id, Imp>(children),
+            b"@@:\0".as_ptr() as *const c_char,
+        );
+        class_replaceMethod(
+            class,
+            sel!(accessibilityHitTest:),
+            std::mem::transmute::<extern "C" fn(&Object, Sel, NSPoint) -> id, Imp>(hit_test),
+            b"@@:{CGPoint=dd}\0".as_ptr() as *const c_char,
+        );
+    }
+    Ok(())
+}
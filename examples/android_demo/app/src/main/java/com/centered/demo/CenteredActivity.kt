@@ -2,18 +2,24 @@ package com.centered.demo
 
 import android.app.NativeActivity
 import android.content.Context
+import android.content.Intent
+import android.net.Uri
 import android.os.Build
 import android.os.Bundle
 import android.os.VibrationEffect
 import android.os.Vibrator
 import android.os.VibratorManager
 import android.text.Editable
+import android.text.Selection
 import android.text.TextWatcher
 import android.view.KeyEvent
 import android.view.View
 import android.view.WindowInsets
+import android.view.inputmethod.BaseInputConnection
 import android.view.inputmethod.EditorInfo
+import android.provider.DocumentsContract
 import android.view.inputmethod.InputMethodManager
+import android.webkit.MimeTypeMap
 import android.widget.EditText
 import android.widget.FrameLayout
 import androidx.annotation.Keep
@@ -34,6 +40,7 @@ import androidx.core.view.WindowInsetsCompat
  * - Safe area insets (display cutouts, navigation bar)
  * - Haptic feedback
  * - Scale factor (DPI)
+ * - Document pickers (Storage Access Framework)
  */
 class CenteredActivity : NativeActivity() {
 
@@ -46,18 +53,29 @@ class CenteredActivity : NativeActivity() {
         }
 
         private const val TAG = "CenteredActivity"
+
+        // First startActivityForResult request code used for document pickers
+        private const val FILE_DIALOG_REQUEST_CODE = 0xCE00
     }
 
     private lateinit var imm: InputMethodManager
     private var vibrator: Vibrator? = null
     private lateinit var hiddenEditText: EditText
     private var isProcessingTextChange = false
+    private var composingText = ""
     private var lastKeyboardHeight: Float = 0f
 
+    // Open document pickers: request code -> request ID from Rust
+    private val fileDialogRequests = HashMap<Int, Int>()
+    private var nextFileDialogRequestCode = FILE_DIALOG_REQUEST_CODE
+
     // Native method declarations - implemented in Rust
     private external fun nativeOnTextInput(text: String)
+    private external fun nativeOnImePreedit(text: String, cursorStart: Int, cursorEnd: Int)
+    private external fun nativeOnImeCommit(text: String)
     private external fun nativeOnKeyEvent(keyCode: Int, action: Int): Boolean
     private external fun nativeOnKeyboardHeightChanged(height: Float, animationDurationMs: Int)
+    private external fun nativeOnFileDialogResult(requestId: Int, uris: Array<String>?)
 
     override fun onCreate(savedInstanceState: Bundle?) {
         super.onCreate(savedInstanceState)
@@ -125,28 +143,46 @@ class CenteredActivity : NativeActivity() {
             hiddenEditText.addTextChangedListener(object : TextWatcher {
                 override fun beforeTextChanged(s: CharSequence?, start: Int, count: Int, after: Int) {}
 
-                override fun onTextChanged(s: CharSequence?, start: Int, before: Int, count: Int) {
-                    if (isProcessingTextChange) return
+                override fun onTextChanged(s: CharSequence?, start: Int, before: Int, count: Int) {}
 
-                    val newText = s?.toString() ?: ""
+                override fun afterTextChanged(s: Editable?) {
+                    if (isProcessingTextChange || s == null) return
+
+                    try {
+                        val composeStart = BaseInputConnection.getComposingSpanStart(s)
+                        val composeEnd = BaseInputConnection.getComposingSpanEnd(s)
+                        if (composeStart >= 0 && composeEnd > composeStart) {
+                            // IME is composing: report the marked text and leave it
+                            // in the field until the IME commits it
+                            composingText = s.substring(composeStart, composeEnd)
+                            val cursorStart = Selection.getSelectionStart(s) - composeStart
+                            val cursorEnd = Selection.getSelectionEnd(s) - composeStart
+                            val visible = cursorStart in 0..composingText.length &&
+                                cursorEnd in 0..composingText.length
+                            nativeOnImePreedit(
+                                composingText,
+                                if (visible) cursorStart else -1,
+                                if (visible) cursorEnd else -1
+                            )
+                            return
+                        }
 
-                    // Detect what was added
-                    if (count > 0 && before == 0) {
-                        // Text was added
-                        val addedText = newText.substring(start, start + count)
-                        if (addedText.isNotEmpty()) {
-                            try {
-                                nativeOnTextInput(addedText)
-                            } catch (e: Exception) {
-                                android.util.Log.e(TAG, "nativeOnTextInput failed: ${e.message}")
+                        if (composingText.isNotEmpty()) {
+                            // Composition ended; whatever is left was committed
+                            composingText = ""
+                            nativeOnImePreedit("", -1, -1)
+                            if (s.isNotEmpty()) {
+                                nativeOnImeCommit(s.toString())
                             }
+                        } else if (s.isNotEmpty()) {
+                            nativeOnTextInput(s.toString())
                         }
+                    } catch (e: Exception) {
+                        android.util.Log.e(TAG, "text input failed: ${e.message}")
                     }
-                }
 
-                override fun afterTextChanged(s: Editable?) {
                     // Clear the edit text after processing to keep it simple
-                    if (!isProcessingTextChange && !s.isNullOrEmpty()) {
+                    if (s.isNotEmpty()) {
                         isProcessingTextChange = true
                         s.clear()
                         isProcessingTextChange = false
@@ -226,6 +262,73 @@ class CenteredActivity : NativeActivity() {
         }
     }
 
+    @Deprecated("Deprecated in Java")
+    override fun onActivityResult(requestCode: Int, resultCode: Int, data: Intent?) {
+        val requestId = fileDialogRequests.remove(requestCode)
+        if (requestId == null) {
+            @Suppress("DEPRECATION")
+            super.onActivityResult(requestCode, resultCode, data)
+            return
+        }
+
+        var uris: Array<String>? = null
+        if (resultCode == RESULT_OK && data != null) {
+            val clipData = data.clipData
+            uris = if (clipData != null) {
+                Array(clipData.itemCount) { clipData.getItemAt(it).uri.toString() }
+            } else {
+                data.data?.let { arrayOf(it.toString()) }
+            }
+        }
+
+        try {
+            nativeOnFileDialogResult(requestId, uris)
+        } catch (e: Exception) {
+            android.util.Log.e(TAG, "nativeOnFileDialogResult failed: ${e.message}")
+        }
+    }
+
+    /**
+     * Configure a document picker intent: MIME types from comma-separated
+     * file extensions (any file when empty) and the location to start in.
+     * Returns the MIME types.
+     */
+    private fun configureDocumentIntent(intent: Intent, extensions: String, initialUri: String?): List<String> {
+        intent.addCategory(Intent.CATEGORY_OPENABLE)
+        val mimeTypes = extensions.split(',')
+            .map { it.trim().lowercase() }
+            .filter { it.isNotEmpty() }
+            .map { MimeTypeMap.getSingleton().getMimeTypeFromExtension(it) ?: "application/octet-stream" }
+            .distinct()
+        when (mimeTypes.size) {
+            0 -> intent.type = "*/*"
+            1 -> intent.type = mimeTypes[0]
+            else -> {
+                intent.type = "*/*"
+                intent.putExtra(Intent.EXTRA_MIME_TYPES, mimeTypes.toTypedArray())
+            }
+        }
+        if (initialUri != null && Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            intent.putExtra(DocumentsContract.EXTRA_INITIAL_URI, Uri.parse(initialUri))
+        }
+        return mimeTypes
+    }
+
+    private fun startDocumentPicker(requestId: Int, intent: Intent) {
+        runOnUiThread {
+            val requestCode = nextFileDialogRequestCode++
+            fileDialogRequests[requestCode] = requestId
+            try {
+                @Suppress("DEPRECATION")
+                startActivityForResult(intent, requestCode)
+            } catch (e: Exception) {
+                android.util.Log.e(TAG, "Failed to start document picker: ${e.message}")
+                fileDialogRequests.remove(requestCode)
+                nativeOnFileDialogResult(requestId, null)
+            }
+        }
+    }
+
     /**
      * Check current keyboard height and notify Rust if it changed.
      */
@@ -386,6 +489,31 @@ class CenteredActivity : NativeActivity() {
         }
     }
 
+    /**
+     * Show the system picker for opening documents. The picked content URIs
+     * are reported to Rust through nativeOnFileDialogResult.
+     */
+    @Keep
+    fun openDocument(requestId: Int, extensions: String, multiple: Boolean, initialUri: String?) {
+        val intent = Intent(Intent.ACTION_OPEN_DOCUMENT)
+        configureDocumentIntent(intent, extensions, initialUri)
+        intent.putExtra(Intent.EXTRA_ALLOW_MULTIPLE, multiple)
+        startDocumentPicker(requestId, intent)
+    }
+
+    /**
+     * Show the system picker for creating a document. The new document's
+     * content URI is reported to Rust through nativeOnFileDialogResult.
+     */
+    @Keep
+    fun createDocument(requestId: Int, extensions: String, initialUri: String?) {
+        val intent = Intent(Intent.ACTION_CREATE_DOCUMENT)
+        val mimeTypes = configureDocumentIntent(intent, extensions, initialUri)
+        // A new document has exactly one type
+        intent.type = mimeTypes.firstOrNull() ?: "application/octet-stream"
+        startDocumentPicker(requestId, intent)
+    }
+
     /**
      * Get the display scale factor (DPI / 160)
      */
@@ -15,6 +15,8 @@ import (
 	"runtime"
 	"strings"
 	"sync"
+	"time"
+	"unicode"
 	"unsafe"
 
 	"github.com/ebitengine/purego"
@@ -37,7 +39,17 @@ var (
 	fnAppRun           func(config uintptr, callback uintptr) int32
 	fnAppRequestExit   func()
 	fnAppRequestRedraw func() int32
+	fnAppSetTargetFPS  func(fps uint32) int32
+	fnAppSetMenu       func(menuJSON uintptr) int32
+	fnBackendSetVSync  func(enabled bool) int32
 	fnEngineVersion    func() uintptr
+	fnErrorString      func(code int32) uintptr
+	fnSetVerboseLog    func(enabled bool)
+	fnSetLogCallback   func(callback uintptr)
+	fnSetWatchdog      func(thresholdMs uint32, presentLastFrame bool)
+	fnLayersHitTest    func(layersJSON uintptr, x float32, y float32, outIDs uintptr, capacity uint64) int32
+	fnInvalidateCaches func(flags uint32) int32
+	fnSetShapingCache  func(capacity uint32) int32
 
 	// Window control functions
 	fnWindowMinimize        func() int32
@@ -47,18 +59,45 @@ var (
 	fnWindowToggleFullscreen func() int32
 	fnWindowClose           func() int32
 	fnWindowSetTitle        func(title uintptr) int32
+	fnWindowSetAspectRatio  func(num uint32, den uint32) int32
+	fnWindowSetCursor       func(kind uint32) int32
+	fnWindowSetCursorVisible func(visible bool) int32
+	fnWindowSetPointerCapture func(enabled, autoRelease bool) int32
+	fnWindowSetBlurBehind   func(enabled bool) int32
+	fnWindowSetOpacity      func(alpha float32) int32
+	fnWindowSetIMECursorArea func(x, y, width, height float64) int32
 
 	// Image/texture functions
-	fnLoadImage      func(dataPtr uintptr, dataLen uint64) int32
-	fnLoadImageFile  func(path uintptr) int32
-	fnUnloadImage    func(textureID uint32) int32
-	fnGetTextureSize func(textureID uint32, widthOut uintptr, heightOut uintptr) int32
+	fnLoadImage             func(dataPtr uintptr, dataLen uint64) int32
+	fnLoadImageWithOptions  func(dataPtr uintptr, dataLen uint64, flags uint32) int32
+	fnLoadImages            func(images uintptr, count uint64, outIDs uintptr) int32
+	fnLoadImageFile         func(path uintptr) int32
+	fnLoadSVG               func(dataPtr uintptr, dataLen uint64, width uint32, height uint32) int32
+	fnLoadCompressedTexture func(format uint32, dataPtr uintptr, dataLen uint64, width uint32, height uint32, mipLevels uint32) int32
+	fnUnloadImage           func(textureID uint32) int32
+	fnLoadAnimatedImage     func(dataPtr uintptr, dataLen uint64) int32
+	fnAnimationFrame        func(handle uint32, elapsedMs uint64) int32
+	fnUnloadAnimation       func(handle uint32) int32
+	fnRenderToTexture       func(commandsJSON uintptr, width uint32, height uint32) int32
+	fnGetTextureSize        func(textureID uint32, widthOut uintptr, heightOut uintptr) int32
+	fnGetGPULimits          func(out uintptr) int32
+	fnGetRenderStats        func(drawCalls uintptr, triangles uintptr, frameTimeMs uintptr, gpuTimed uintptr) int32
+	fnSurfaceColorSpace     func() int32
+	fnValidateCommands      func(commandsJSON uintptr) uintptr
+	fnRenderToBuffer        func(commandsJSON uintptr, width uint32, height uint32, outPtr uintptr, outLen uint64) int32
+	fnLayoutCompute         func(treeJSON uintptr, width float32, height float32) uintptr
 
 	// Text measurement functions
 	fnMeasureText            func(text uintptr, fontName uintptr, fontSize float32) TextMeasurementC
 	fnMeasureTextPtr         func(text uintptr, fontName uintptr, fontSize float32, out uintptr) int32 // iOS-compatible version
 	fnMeasureTextWidth       func(text uintptr, fontName uintptr, fontSize float32) float32
 	fnMeasureTextToCursor    func(text uintptr, charIndex uint32, fontName uintptr, fontSize float32) float32
+	fnTextMoveCaret          func(text uintptr, charIndex int32, delta int32) int32
+	fnTextLineBreaks         func(text uintptr, wordBreak uint8, out uintptr, capacity uint64) int32
+	fnRegisterFontMemory     func(namePtr uintptr, nameLen uint64, dataPtr uintptr, dataLen uint64) uint64
+	fnUnregisterFont         func(hash uint64) int32
+	fnListFonts              func() uintptr
+	fnAccessibilityTree      func() uintptr
 	fnMeasureTextWithFont    func(text uintptr, fontJSON uintptr) float32
 	fnMeasureTextMetricsWithFont    func(text uintptr, fontJSON uintptr) TextMeasurementC
 	fnMeasureTextMetricsWithFontPtr func(text uintptr, fontJSON uintptr, out uintptr) int32 // iOS-compatible version
@@ -86,23 +125,24 @@ var (
 	fnAudioUpdate     func(playerID uint32) int32
 
 	// Video playback functions
-	fnVideoCreate       func() uint32
-	fnVideoDestroy      func(playerID uint32)
-	fnVideoLoadURL      func(playerID uint32, url uintptr) int32
-	fnVideoLoadFile     func(playerID uint32, path uintptr) int32
-	fnVideoInitStream   func(playerID uint32, width uint32, height uint32) int32
-	fnVideoPushFrame    func(playerID uint32, width uint32, height uint32, dataPtr uintptr, dataLen uint64, timestampMs uint64) int32
-	fnVideoPlay         func(playerID uint32) int32
-	fnVideoPause        func(playerID uint32) int32
-	fnVideoSeek         func(playerID uint32, timestampMs uint64) int32
-	fnVideoSetLooping   func(playerID uint32, looping bool) int32
-	fnVideoSetMuted     func(playerID uint32, muted bool) int32
-	fnVideoSetVolume    func(playerID uint32, volume float32) int32
-	fnVideoGetState     func(playerID uint32) int32
-	fnVideoGetTime      func(playerID uint32) uint64
-	fnVideoGetInfo      func(playerID uint32, widthOut uintptr, heightOut uintptr, durationOut uintptr) int32
-	fnVideoUpdate       func(playerID uint32) int32
-	fnVideoGetTextureID func(playerID uint32) uint32
+	fnVideoCreate        func() uint32
+	fnVideoDestroy       func(playerID uint32)
+	fnVideoLoadURL       func(playerID uint32, url uintptr) int32
+	fnVideoLoadFile      func(playerID uint32, path uintptr) int32
+	fnVideoInitStream    func(playerID uint32, width uint32, height uint32) int32
+	fnVideoPushFrame     func(playerID uint32, width uint32, height uint32, dataPtr uintptr, dataLen uint64, timestampMs uint64) int32
+	fnVideoPlay          func(playerID uint32) int32
+	fnVideoPause         func(playerID uint32) int32
+	fnVideoSeek          func(playerID uint32, timestampMs uint64) int32
+	fnVideoSetLooping    func(playerID uint32, looping bool) int32
+	fnVideoSetMuted      func(playerID uint32, muted bool) int32
+	fnVideoSetVolume     func(playerID uint32, volume float32) int32
+	fnVideoGetState      func(playerID uint32) int32
+	fnVideoGetTime       func(playerID uint32) uint64
+	fnVideoGetBufferedMs func(playerID uint32) uint64
+	fnVideoGetInfo       func(playerID uint32, widthOut uintptr, heightOut uintptr, durationOut uintptr) int32
+	fnVideoUpdate        func(playerID uint32) int32
+	fnVideoGetTextureID  func(playerID uint32) uint32
 
 	// Audio input functions
 	fnAudioInputCreate            func() uint32
@@ -144,8 +184,15 @@ var (
 	fnGetAppFilesDir          func() uintptr
 
 	// Clipboard functions (Rust implementation)
-	fnClipboardGet func() uintptr
-	fnClipboardSet func(text uintptr)
+	fnClipboardGet       func() uintptr
+	fnClipboardSet       func(text uintptr)
+	fnClipboardGetImage  func(outPtr uintptr, outLen uintptr, widthOut uintptr, heightOut uintptr) int32
+	fnClipboardImageFree func(ptr uintptr, len uint64)
+	fnClipboardSetImage  func(dataPtr uintptr, dataLen uint64, width uint32, height uint32) int32
+
+	// Notification functions
+	fnShowNotification    func(title uintptr, body uintptr, iconPath uintptr) int32
+	fnDismissNotification func(id uint32) int32
 
 	// Keyboard functions (iOS)
 	fnKeyboardShow      func()
@@ -162,6 +209,8 @@ var (
 	fnFileDialogOpen       func(title uintptr, directory uintptr, filters uintptr, multiple int32) uintptr
 	fnFileDialogSave       func(title uintptr, directory uintptr, filters uintptr) uintptr
 	fnFileDialogResultFree func(result uintptr)
+	fnFileDialogOpenAsync  func(title uintptr, directory uintptr, filters uintptr, multiple int32, requestID uint32, callback uintptr) int32
+	fnFileDialogSaveAsync  func(title uintptr, directory uintptr, filters uintptr, requestID uint32, callback uintptr) int32
 
 	// Tray icon functions (Rust implementation)
 	fnTrayIconCreate             func() int32
@@ -189,6 +238,7 @@ type TextMeasurementC struct {
 	Height  float32
 	Ascent  float32
 	Descent float32
+	LineGap float32
 }
 
 // SafeAreaInsetsC matches the C struct layout for safe area insets (iOS/Android)
@@ -201,11 +251,18 @@ type SafeAreaInsetsC struct {
 
 // AppEventC matches the C struct layout for events from Rust
 type AppEventC struct {
-	EventType   uint8
-	_           [7]byte // padding
-	Data1       float64
-	Data2       float64
-	ScaleFactor float64
+	EventType      uint8
+	_              [7]byte // padding
+	Data1          float64
+	Data2          float64
+	ScaleFactor    float64
+	FrameDt        float64
+	RenderFailures uint32
+	ScrollPhase    uint8
+	TouchPhase     uint8
+	_              [2]byte // padding
+	TouchID        uint64
+	Text           uintptr // *const c_char, only valid during the callback
 }
 
 // FrameResponseC matches the C struct layout for frame responses to Rust
@@ -244,6 +301,14 @@ type AppConfigC struct {
 	EnableMinimize        bool
 	EnableMaximize        bool
 	DarkMode              uint8 // 0 = light, 1 = dark, 2 = auto/system
+	TouchMouseEmulation   bool
+	GammaCorrectText      bool
+	SubpixelText          bool
+	WideGamut             bool
+	DisplayP3Colors       bool
+	BlurBehind            bool
+	WindowOpacity         float32
+	FixedTimestepHz       uint32
 }
 
 // getLibraryPath returns the path to the dynamic library
@@ -368,7 +433,17 @@ func registerCoreFunctions() {
 	purego.RegisterLibFunc(&fnAppRun, libHandle, "centered_app_run")
 	purego.RegisterLibFunc(&fnAppRequestExit, libHandle, "centered_app_request_exit")
 	purego.RegisterLibFunc(&fnAppRequestRedraw, libHandle, "centered_app_request_redraw")
+	purego.RegisterLibFunc(&fnAppSetTargetFPS, libHandle, "centered_app_set_target_fps")
+	purego.RegisterLibFunc(&fnAppSetMenu, libHandle, "centered_app_set_menu")
+	purego.RegisterLibFunc(&fnBackendSetVSync, libHandle, "centered_backend_set_vsync")
 	purego.RegisterLibFunc(&fnEngineVersion, libHandle, "centered_engine_version")
+	purego.RegisterLibFunc(&fnErrorString, libHandle, "centered_error_string")
+	purego.RegisterLibFunc(&fnSetVerboseLog, libHandle, "centered_set_verbose_logging")
+	purego.RegisterLibFunc(&fnSetLogCallback, libHandle, "centered_set_log_callback")
+	purego.RegisterLibFunc(&fnSetWatchdog, libHandle, "centered_set_callback_watchdog")
+	purego.RegisterLibFunc(&fnLayersHitTest, libHandle, "centered_layers_hit_test_stack")
+	purego.RegisterLibFunc(&fnInvalidateCaches, libHandle, "centered_engine_invalidate_caches")
+	purego.RegisterLibFunc(&fnSetShapingCache, libHandle, "centered_text_set_shaping_cache_capacity")
 
 	// Window control functions
 	purego.RegisterLibFunc(&fnWindowMinimize, libHandle, "centered_window_minimize")
@@ -378,6 +453,13 @@ func registerCoreFunctions() {
 	purego.RegisterLibFunc(&fnWindowToggleFullscreen, libHandle, "centered_window_toggle_fullscreen")
 	purego.RegisterLibFunc(&fnWindowClose, libHandle, "centered_window_close")
 	purego.RegisterLibFunc(&fnWindowSetTitle, libHandle, "centered_window_set_title")
+	purego.RegisterLibFunc(&fnWindowSetAspectRatio, libHandle, "centered_window_set_aspect_ratio")
+	purego.RegisterLibFunc(&fnWindowSetCursor, libHandle, "centered_window_set_cursor")
+	purego.RegisterLibFunc(&fnWindowSetCursorVisible, libHandle, "centered_window_set_cursor_visible")
+	purego.RegisterLibFunc(&fnWindowSetPointerCapture, libHandle, "centered_window_set_pointer_capture")
+	purego.RegisterLibFunc(&fnWindowSetBlurBehind, libHandle, "centered_window_set_blur_behind")
+	purego.RegisterLibFunc(&fnWindowSetOpacity, libHandle, "centered_window_set_opacity")
+	purego.RegisterLibFunc(&fnWindowSetIMECursorArea, libHandle, "centered_window_set_ime_cursor_area")
 }
 
 func registerIOSFunctions() {
@@ -398,9 +480,23 @@ func registerAndroidFunctions() {
 
 func registerImageFunctions() {
 	purego.RegisterLibFunc(&fnLoadImage, libHandle, "centered_backend_load_image")
+	purego.RegisterLibFunc(&fnLoadImageWithOptions, libHandle, "centered_backend_load_image_with_options")
+	purego.RegisterLibFunc(&fnLoadImages, libHandle, "centered_backend_load_images")
 	purego.RegisterLibFunc(&fnLoadImageFile, libHandle, "centered_backend_load_image_file")
+	purego.RegisterLibFunc(&fnLoadSVG, libHandle, "centered_backend_load_svg")
+	purego.RegisterLibFunc(&fnLoadCompressedTexture, libHandle, "centered_backend_load_compressed_texture")
 	purego.RegisterLibFunc(&fnUnloadImage, libHandle, "centered_backend_unload_image")
+	purego.RegisterLibFunc(&fnLoadAnimatedImage, libHandle, "centered_backend_load_animated_image")
+	purego.RegisterLibFunc(&fnAnimationFrame, libHandle, "centered_backend_animation_frame")
+	purego.RegisterLibFunc(&fnUnloadAnimation, libHandle, "centered_backend_unload_animation")
+	purego.RegisterLibFunc(&fnRenderToTexture, libHandle, "centered_backend_render_to_texture")
 	purego.RegisterLibFunc(&fnGetTextureSize, libHandle, "centered_backend_get_texture_size")
+	purego.RegisterLibFunc(&fnGetGPULimits, libHandle, "centered_backend_get_limits")
+	purego.RegisterLibFunc(&fnGetRenderStats, libHandle, "centered_backend_get_stats")
+	purego.RegisterLibFunc(&fnSurfaceColorSpace, libHandle, "centered_backend_surface_color_space")
+	purego.RegisterLibFunc(&fnValidateCommands, libHandle, "centered_validate_commands")
+	purego.RegisterLibFunc(&fnRenderToBuffer, libHandle, "centered_render_to_buffer")
+	purego.RegisterLibFunc(&fnLayoutCompute, libHandle, "centered_layout_compute")
 }
 
 func registerTextFunctions() {
@@ -413,6 +509,12 @@ func registerTextFunctions() {
 	purego.RegisterLibFunc(&fnMeasureTextPtr, libHandle, "centered_measure_text_ptr")
 	purego.RegisterLibFunc(&fnMeasureTextWidth, libHandle, "centered_measure_text_width")
 	purego.RegisterLibFunc(&fnMeasureTextToCursor, libHandle, "centered_measure_text_to_cursor")
+	purego.RegisterLibFunc(&fnTextMoveCaret, libHandle, "centered_text_move_caret")
+	purego.RegisterLibFunc(&fnTextLineBreaks, libHandle, "centered_text_line_breaks")
+	purego.RegisterLibFunc(&fnRegisterFontMemory, libHandle, "centered_register_font_memory")
+	purego.RegisterLibFunc(&fnUnregisterFont, libHandle, "centered_unregister_font")
+	purego.RegisterLibFunc(&fnListFonts, libHandle, "centered_list_fonts")
+	purego.RegisterLibFunc(&fnAccessibilityTree, libHandle, "centered_accessibility_tree")
 	purego.RegisterLibFunc(&fnMeasureTextWithFont, libHandle, "centered_measure_text_with_font")
 	// Register metrics-with-font function (returns TextMeasurement struct)
 	// Only macOS supports direct struct returns in purego
@@ -459,6 +561,7 @@ func registerVideoFunctions() {
 	purego.RegisterLibFunc(&fnVideoSetVolume, libHandle, "centered_video_set_volume")
 	purego.RegisterLibFunc(&fnVideoGetState, libHandle, "centered_video_get_state")
 	purego.RegisterLibFunc(&fnVideoGetTime, libHandle, "centered_video_get_time")
+	purego.RegisterLibFunc(&fnVideoGetBufferedMs, libHandle, "centered_video_get_buffered_ms")
 	purego.RegisterLibFunc(&fnVideoGetInfo, libHandle, "centered_video_get_info")
 	purego.RegisterLibFunc(&fnVideoUpdate, libHandle, "centered_video_update")
 	purego.RegisterLibFunc(&fnVideoGetTextureID, libHandle, "centered_video_get_texture_id")
@@ -501,6 +604,11 @@ func registerSystemFunctions() {
 	// For now, we'll check if they exist and skip if not available
 	registerOptionalFunc(&fnClipboardGet, "centered_clipboard_get")
 	registerOptionalFunc(&fnClipboardSet, "centered_clipboard_set")
+	registerOptionalFunc(&fnClipboardGetImage, "centered_clipboard_get_image")
+	registerOptionalFunc(&fnClipboardImageFree, "centered_clipboard_image_free")
+	registerOptionalFunc(&fnClipboardSetImage, "centered_clipboard_set_image")
+	registerOptionalFunc(&fnShowNotification, "centered_show_notification")
+	registerOptionalFunc(&fnDismissNotification, "centered_dismiss_notification")
 	registerOptionalFunc(&fnKeyboardShow, "centered_keyboard_show")
 	registerOptionalFunc(&fnKeyboardHide, "centered_keyboard_hide")
 	registerOptionalFunc(&fnKeyboardIsVisible, "centered_keyboard_is_visible")
@@ -509,6 +617,8 @@ func registerSystemFunctions() {
 	registerOptionalFunc(&fnFileDialogOpen, "centered_file_dialog_open")
 	registerOptionalFunc(&fnFileDialogSave, "centered_file_dialog_save")
 	registerOptionalFunc(&fnFileDialogResultFree, "centered_file_dialog_result_free")
+	registerOptionalFunc(&fnFileDialogOpenAsync, "centered_file_dialog_open_async")
+	registerOptionalFunc(&fnFileDialogSaveAsync, "centered_file_dialog_save_async")
 	registerOptionalFunc(&fnTrayIconCreate, "centered_tray_icon_create")
 	registerOptionalFunc(&fnTrayIconDestroy, "centered_tray_icon_destroy")
 	registerOptionalFunc(&fnTrayIconSetIconFile, "centered_tray_icon_set_icon_file")
@@ -590,6 +700,20 @@ const (
 	EventSuspended            EventType = 11
 	EventResumed              EventType = 12
 	EventKeyboardFrameChanged EventType = 13
+	EventTouch                EventType = 14
+	EventImePreedit           EventType = 15
+	EventImeCommit            EventType = 16
+	EventFixedUpdate          EventType = 17
+	EventMenuItemClicked      EventType = 18
+	EventFileDragEnter        EventType = 19
+	EventFileDragOver         EventType = 20
+	EventFileDrop             EventType = 21
+	EventFileDragCancel       EventType = 22
+	EventFocusGained          EventType = 23
+	EventFocusLost            EventType = 24
+	EventMinimized            EventType = 25
+	EventRestored             EventType = 26
+	EventOccluded             EventType = 27
 )
 
 // Modifier flags for keyboard events (stored in Data2)
@@ -602,6 +726,9 @@ const (
 	ModSuper
 )
 
+// keyRepeat is set in Data2 of a KeyPressed that is an auto-repeat
+const keyRepeat = 1 << 4
+
 // Common keycodes - stable cross-platform values
 type Keycode uint32
 
@@ -741,16 +868,132 @@ type Event struct {
 	Data1       float64
 	Data2       float64
 	ScaleFactor float64
+
+	// FrameDt is the seconds since the previous frame started on an
+	// EventRedrawRequested (0 for the first frame), measured on the engine's
+	// event loop clock, and the fixed step length on an EventFixedUpdate
+	FrameDt float64
+
+	// RenderFailures is the number of consecutive renders/presents that
+	// failed before this event (0 = the last frame presented successfully)
+	RenderFailures uint32
+
+	// ScrollPhase is where a MouseWheel event falls in a trackpad gesture
+	ScrollPhase ScrollPhase
+
+	// TouchPhase and TouchID describe the touch point of an EventTouch.
+	// The ID stays the same from TouchBegan to TouchEnded/TouchCancelled,
+	// so each finger of a pinch or two-finger pan can be tracked.
+	TouchPhase TouchPhase
+	TouchID    uint64
+
+	// Text is the composition string of an EventImePreedit (empty when
+	// composition ends), the committed text of an EventImeCommit, the
+	// logical key of a KeyPressed/KeyReleased (see LogicalKey), or the
+	// file paths of an EventFileDragEnter/EventFileDrop (see FilePaths)
+	Text string
+}
+
+// ImeCursor returns the selected byte range within the Text of an
+// EventImePreedit, and false when the IME hides the cursor
+func (e Event) ImeCursor() (start, end int, ok bool) {
+	if e.Type != EventImePreedit || e.Data1 < 0 || e.Data2 < 0 {
+		return 0, 0, false
+	}
+	return int(e.Data1), int(e.Data2), true
+}
+
+// ScrollPhase is the gesture phase of a MouseWheel event
+type ScrollPhase uint8
+
+const (
+	// ScrollPhaseNone is a mouse wheel notch, or a platform without phases
+	ScrollPhaseNone ScrollPhase = 0
+	// ScrollPhaseStarted is fingers touching down and starting to scroll
+	ScrollPhaseStarted ScrollPhase = 1
+	// ScrollPhaseMoved is fingers moving
+	ScrollPhaseMoved ScrollPhase = 2
+	// ScrollPhaseEnded is fingers lifting; momentum events may follow
+	ScrollPhaseEnded ScrollPhase = 3
+	// ScrollPhaseMomentum is inertial scrolling after the fingers lifted
+	ScrollPhaseMomentum ScrollPhase = 4
+	// ScrollPhaseMomentumEnded is inertial scrolling coming to rest or
+	// being stopped by a new touch
+	ScrollPhaseMomentumEnded ScrollPhase = 5
+)
+
+// TouchPhase is what happened to the touch point of an EventTouch
+type TouchPhase uint8
+
+const (
+	// TouchNone is any event other than EventTouch
+	TouchNone TouchPhase = 0
+	// TouchBegan is a finger touching down
+	TouchBegan TouchPhase = 1
+	// TouchMoved is a finger moving
+	TouchMoved TouchPhase = 2
+	// TouchEnded is a finger lifting
+	TouchEnded TouchPhase = 3
+	// TouchCancelled is the system taking the touch away (an incoming
+	// call, a system gesture)
+	TouchCancelled TouchPhase = 4
+)
+
+// MenuItemID returns the id of the menu item an EventMenuItemClicked is for
+func (e Event) MenuItemID() uint32 {
+	return uint32(e.Data1)
+}
+
+// FilePaths returns the paths of the files an EventFileDragEnter or
+// EventFileDrop is for. MouseX and MouseY give the cursor position of file
+// drag events.
+func (e Event) FilePaths() []string {
+	if e.Type != EventFileDragEnter && e.Type != EventFileDrop {
+		return nil
+	}
+	var paths []string
+	if err := json.Unmarshal([]byte(e.Text), &paths); err != nil {
+		return nil
+	}
+	return paths
+}
+
+// Hidden reports whether an EventOccluded is for the window becoming hidden
+// (covered by other windows, on another space) rather than visible again.
+// Minimizing is reported as EventMinimized instead. On iOS and Android,
+// EventFocusLost and EventMinimized come with EventSuspended, and
+// EventRestored and EventFocusGained with EventResumed.
+func (e Event) Hidden() bool {
+	return e.Type == EventOccluded && e.Data1 != 0
 }
 
-// Keycode returns the keycode for KeyPressed/KeyReleased events
+// Keycode returns the physical keycode for KeyPressed/KeyReleased events.
+// It names the key's position (KeyZ is the key right of left Shift on any
+// layout); use LogicalKey for what the key means.
 func (e Event) Keycode() uint32 {
 	return uint32(e.Data1)
 }
 
+// LogicalKey returns what a KeyPressed/KeyReleased key means under the
+// active layout: the character it produces (a physical KeyZ gives "y" on a
+// German layout) or a named key such as "Enter" or "ArrowLeft". Empty if
+// the platform doesn't know.
+func (e Event) LogicalKey() string {
+	if e.Type != EventKeyPressed && e.Type != EventKeyReleased {
+		return ""
+	}
+	return e.Text
+}
+
+// IsRepeat returns true for a KeyPressed sent by auto-repeat while the key
+// is held down
+func (e Event) IsRepeat() bool {
+	return e.Type == EventKeyPressed && uint32(e.Data2)&keyRepeat != 0
+}
+
 // Modifiers returns the modifier flags for keyboard events
 func (e Event) Modifiers() Modifiers {
-	return Modifiers(uint32(e.Data2))
+	return Modifiers(uint32(e.Data2) &^ keyRepeat)
 }
 
 // HasShift returns true if Shift was held
@@ -811,6 +1054,19 @@ func (e Event) ScrollDelta() (float64, float64) {
 	return e.Data1, e.Data2
 }
 
+// IsMomentumScroll returns true for MouseWheel events from inertial
+// scrolling after a flick rather than from fingers on the trackpad
+func (e Event) IsMomentumScroll() bool {
+	return e.ScrollPhase == ScrollPhaseMomentum || e.ScrollPhase == ScrollPhaseMomentumEnded
+}
+
+// RenderFailed returns true if the last render or present failed (e.g. the
+// surface was temporarily unavailable during a window drag). Handlers should
+// back off instead of requesting another redraw immediately.
+func (e Event) RenderFailed() bool {
+	return e.RenderFailures > 0
+}
+
 // ============================================================================
 // Frame Response and Handler
 // ============================================================================
@@ -828,6 +1084,36 @@ type LayerInfo struct {
 	Commands []RenderCommand `json:"commands,omitempty"` // Commands for this layer (only if dirty)
 }
 
+// LayersHitTest returns the layers under (x, y) that input should be routed
+// to, topmost first. Layers stack by ZOrder as they are composited; a
+// transparent layer passes the point through to the layers below it, and
+// the first opaque layer ends the list.
+func LayersHitTest(layers []LayerInfo, x, y float32) []uint32 {
+	if !initialized || len(layers) == 0 {
+		return nil
+	}
+
+	// Bounds only; commands aren't needed for hit testing
+	bounds := make([]LayerInfo, len(layers))
+	for i, l := range layers {
+		l.Commands = nil
+		bounds[i] = l
+	}
+	jsonBytes, err := json.Marshal(bounds)
+	if err != nil {
+		return nil
+	}
+	jsonBytes = append(jsonBytes, 0)
+
+	ids := make([]uint32, len(layers))
+	n := fnLayersHitTest(uintptr(unsafe.Pointer(&jsonBytes[0])), x, y, uintptr(unsafe.Pointer(&ids[0])), uint64(len(ids)))
+	runtime.KeepAlive(jsonBytes)
+	if n <= 0 {
+		return nil
+	}
+	return ids[:n]
+}
+
 // DirtyRegion represents a screen region that needs redrawing.
 // Rust will use this to set a scissor rect, skipping pixels outside this area.
 type DirtyRegion struct {
@@ -894,6 +1180,47 @@ type AppConfig struct {
 	EnableMinimize     bool
 	EnableMaximize     bool
 	DarkMode           uint8 // 0 = light, 1 = dark, 2 = auto/system
+
+	// TouchMouseEmulation also delivers touches as left-button mouse
+	// events, for apps that only handle the mouse. EventTouch is sent
+	// either way.
+	TouchMouseEmulation bool
+
+	// GammaCorrectText blends text in linear light on surfaces that blend
+	// encoded values (such as Windows' DX12 swapchains), where dark text on
+	// light backgrounds otherwise looks heavy and muddy.
+	GammaCorrectText bool
+	// SubpixelText uses LCD subpixel antialiasing for text where the GPU
+	// supports dual-source blending and the window is opaque. Sharper on
+	// RGB-striped LCDs, but fringes colors elsewhere and in scaled
+	// screenshots. Both off (the default) draws grayscale antialiased text.
+	SubpixelText bool
+
+	// WideGamut presents in extended-range sRGB where the display supports
+	// it, so colors outside sRGB aren't clipped; SurfaceColorSpace reports
+	// what was chosen.
+	WideGamut bool
+	// DisplayP3Colors takes every color and image as Display-P3 rather than
+	// sRGB, as colors picked on a P3 display are meant. Without WideGamut
+	// (or where it isn't supported) they are clipped to the nearest sRGB
+	// color.
+	DisplayP3Colors bool
+
+	// BlurBehind blurs what's behind the window's transparent areas
+	// (vibrancy on macOS, acrylic on Windows, KDE's blur on Linux) and makes
+	// the window transparent. Clear with a translucent color to see it.
+	BlurBehind bool
+	// WindowOpacity is the opacity of the whole window; 0 (the zero value)
+	// and 1 both mean opaque.
+	WindowOpacity float32
+
+	// FixedTimestepHz turns on fixed-timestep mode: before each
+	// EventRedrawRequested the engine sends one EventFixedUpdate per step
+	// of 1/FixedTimestepHz seconds that fell due on the wall clock, so
+	// physics can advance by a constant dt however the frames fall. Draw in
+	// EventRedrawRequested; responses to EventFixedUpdate are ignored.
+	// 0 (the zero value) turns it off.
+	FixedTimestepHz uint32
 }
 
 // DefaultAppConfig returns sensible defaults
@@ -925,6 +1252,8 @@ func DefaultAppConfig() AppConfig {
 		ShowNativeControls: true,
 		EnableMinimize:     true,
 		EnableMaximize:     true,
+
+		TouchMouseEmulation: true,
 	}
 }
 
@@ -965,10 +1294,16 @@ func appCallback(eventPtr uintptr, responsePtr uintptr, userData uintptr) uintpt
 	event := (*AppEventC)(unsafe.Pointer(eventPtr))
 
 	goEvent := Event{
-		Type:        EventType(event.EventType),
-		Data1:       event.Data1,
-		Data2:       event.Data2,
-		ScaleFactor: event.ScaleFactor,
+		Type:           EventType(event.EventType),
+		Data1:          event.Data1,
+		Data2:          event.Data2,
+		ScaleFactor:    event.ScaleFactor,
+		FrameDt:        event.FrameDt,
+		RenderFailures: event.RenderFailures,
+		ScrollPhase:    ScrollPhase(event.ScrollPhase),
+		TouchPhase:     TouchPhase(event.TouchPhase),
+		TouchID:        event.TouchID,
+		Text:           goString(event.Text),
 	}
 
 	// Call the Go handler
@@ -1091,6 +1426,14 @@ func iosReadyCallback() {
 		EnableMinimize:        config.EnableMinimize,
 		EnableMaximize:        config.EnableMaximize,
 		DarkMode:              config.DarkMode,
+		TouchMouseEmulation:   config.TouchMouseEmulation,
+		GammaCorrectText:      config.GammaCorrectText,
+		SubpixelText:          config.SubpixelText,
+		WideGamut:             config.WideGamut,
+		DisplayP3Colors:       config.DisplayP3Colors,
+		BlurBehind:            config.BlurBehind,
+		WindowOpacity:         config.WindowOpacity,
+		FixedTimestepHz:       config.FixedTimestepHz,
 	}
 
 	// Keep titleBytes alive
@@ -1171,6 +1514,14 @@ func androidReadyCallback() {
 		EnableMinimize:        config.EnableMinimize,
 		EnableMaximize:        config.EnableMaximize,
 		DarkMode:              config.DarkMode,
+		TouchMouseEmulation:   config.TouchMouseEmulation,
+		GammaCorrectText:      config.GammaCorrectText,
+		SubpixelText:          config.SubpixelText,
+		WideGamut:             config.WideGamut,
+		DisplayP3Colors:       config.DisplayP3Colors,
+		BlurBehind:            config.BlurBehind,
+		WindowOpacity:         config.WindowOpacity,
+		FixedTimestepHz:       config.FixedTimestepHz,
 	}
 
 	// Keep titleBytes alive
@@ -1296,6 +1647,14 @@ func Run(config AppConfig, handler EventHandler) error {
 		EnableMinimize:        config.EnableMinimize,
 		EnableMaximize:        config.EnableMaximize,
 		DarkMode:              config.DarkMode,
+		TouchMouseEmulation:   config.TouchMouseEmulation,
+		GammaCorrectText:      config.GammaCorrectText,
+		SubpixelText:          config.SubpixelText,
+		WideGamut:             config.WideGamut,
+		DisplayP3Colors:       config.DisplayP3Colors,
+		BlurBehind:            config.BlurBehind,
+		WindowOpacity:         config.WindowOpacity,
+		FixedTimestepHz:       config.FixedTimestepHz,
 	}
 
 	// Keep titleBytes alive
@@ -1326,6 +1685,101 @@ func RequestRedraw() {
 	fnAppRequestRedraw()
 }
 
+// SetTargetFPS changes the target frame rate while the app runs, e.g. 30
+// while idle and 120 during an animation. On desktop it caps continuous
+// redraws; 0 removes the cap so vsync paces them again. On iOS and Android
+// call it from the event handler (main thread).
+func SetTargetFPS(fps uint32) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnAppSetTargetFPS(fps) != 0 {
+		return fmt.Errorf("no event loop running")
+	}
+	return nil
+}
+
+// Menu is a top-level menu of the application menu bar
+type Menu struct {
+	Title string     `json:"title"`
+	Items []MenuItem `json:"items,omitempty"`
+}
+
+// MenuItem is an entry of a Menu: a clickable item with a non-zero ID, a
+// separator, or a submenu (Items set)
+type MenuItem struct {
+	ID    uint32 `json:"id,omitempty"`
+	Title string `json:"title,omitempty"`
+	// Key of the shortcut: a character ("s", ",") or a named key ("Enter",
+	// "Escape", "Tab", "Space", "Backspace", "Delete", "Up", "Down", "Left",
+	// "Right", "Home", "End", "PageUp", "PageDown", "F1"-"F12")
+	Key string `json:"key,omitempty"`
+	// Modifiers held with Key: "cmd", "shift", "alt", "ctrl". Nil means
+	// "cmd" alone.
+	Modifiers []string   `json:"modifiers,omitempty"`
+	Disabled  bool       `json:"-"`
+	Checked   bool       `json:"checked,omitempty"`
+	Separator bool       `json:"separator,omitempty"`
+	Items     []MenuItem `json:"items,omitempty"`
+}
+
+// MarshalJSON writes Disabled as the engine's "enabled" field
+func (m MenuItem) MarshalJSON() ([]byte, error) {
+	type plain MenuItem
+	return json.Marshal(struct {
+		plain
+		Enabled bool `json:"enabled"`
+	}{plain(m), !m.Disabled})
+}
+
+// MenuSeparator returns a separator line for a menu
+func MenuSeparator() MenuItem {
+	return MenuItem{Separator: true}
+}
+
+// AppSetMenu replaces the application menu bar. Clicking an item, or
+// pressing its shortcut, sends an EventMenuItemClicked whose MenuItemID is
+// the item's ID. Call it once the app is running (e.g. on EventReady) and
+// again with the whole bar to change it. Safe to call from any goroutine.
+//
+// On macOS the first menu is the application menu, which the system titles
+// with the app's name. A no-op on Windows and Linux for now.
+func AppSetMenu(menus []Menu) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if menus == nil {
+		menus = []Menu{}
+	}
+	menuJSON, err := json.Marshal(menus)
+	if err != nil {
+		return err
+	}
+	menuBytes := append(menuJSON, 0)
+	code := fnAppSetMenu(uintptr(unsafe.Pointer(&menuBytes[0])))
+	runtime.KeepAlive(menuBytes)
+	switch code {
+	case 0:
+		return nil
+	case CodeInvalidArgument:
+		return fmt.Errorf("invalid menu bar (missing or duplicate item IDs, or an unknown key)")
+	default:
+		return fmt.Errorf("no event loop running")
+	}
+}
+
+// SetVSync turns vsync on or off without recreating the renderer.
+// Safe to call from the event handler.
+func SetVSync(enabled bool) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnBackendSetVSync(enabled) != 0 {
+		return fmt.Errorf("renderer not initialized")
+	}
+	return nil
+}
+
 // ============================================================================
 // Window Control Functions
 // ============================================================================
@@ -1397,6 +1851,143 @@ func WindowSetTitle(title string) {
 	runtime.KeepAlive(titleBytes)
 }
 
+// WindowSetAspectRatio locks the window to a num:den aspect ratio while
+// resizing (e.g. 16, 9 for a video player). Pass 0, 0 to unlock.
+// Safe to call from any goroutine.
+func WindowSetAspectRatio(num, den uint32) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	switch fnWindowSetAspectRatio(num, den) {
+	case 0:
+		return nil
+	case CodeInvalidArgument:
+		return fmt.Errorf("invalid aspect ratio %d:%d", num, den)
+	default:
+		return fmt.Errorf("no event loop running")
+	}
+}
+
+// CursorKind is a standard cursor shape for WindowSetCursor
+type CursorKind uint32
+
+const (
+	CursorDefault CursorKind = iota
+	// CursorPointer is the pointing hand for links and buttons
+	CursorPointer
+	// CursorText is the I-beam for editable or selectable text
+	CursorText
+	CursorGrab
+	CursorGrabbing
+	CursorNotAllowed
+	CursorCrosshair
+	CursorMove
+	CursorWait
+	CursorProgress
+	CursorHelp
+	CursorEwResize
+	CursorNsResize
+	CursorNeswResize
+	CursorNwseResize
+	CursorColResize
+	CursorRowResize
+	CursorZoomIn
+	CursorZoomOut
+)
+
+// WindowSetCursor sets the cursor shape shown over the window until it is
+// changed again, e.g. CursorText over a text field. A custom cursor image
+// takes precedence while one is set.
+// Safe to call from any goroutine.
+func WindowSetCursor(kind CursorKind) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	switch fnWindowSetCursor(uint32(kind)) {
+	case 0:
+		return nil
+	case CodeInvalidArgument:
+		return fmt.Errorf("unknown cursor kind %d", kind)
+	default:
+		return fmt.Errorf("no event loop running")
+	}
+}
+
+// WindowSetCursorVisible shows or hides the cursor while it is over the
+// window, e.g. for games.
+// Safe to call from any goroutine.
+func WindowSetCursorVisible(visible bool) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnWindowSetCursorVisible(visible) != 0 {
+		return fmt.Errorf("no event loop running")
+	}
+	return nil
+}
+
+// WindowSetPointerCapture turns pointer capture on or off. While it is on, a
+// drag that leaves the window keeps sending EventMouseMoved (with positions
+// outside the window) and EventMouseReleased, so sliders and scrollbars
+// don't get stuck. With autoRelease, capture turns itself off when the last
+// held mouse button is released; call it on EventMousePressed.
+// Safe to call from any goroutine.
+func WindowSetPointerCapture(enabled, autoRelease bool) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnWindowSetPointerCapture(enabled, autoRelease) != 0 {
+		return fmt.Errorf("no event loop running")
+	}
+	return nil
+}
+
+// WindowSetBlurBehind turns blur-behind (vibrancy) on or off. The blur shows
+// through transparent areas only, so create the window with BlurBehind or
+// Transparent set. A no-op where the platform or compositor has no blur,
+// such as GNOME.
+// Safe to call from any goroutine.
+func WindowSetBlurBehind(enabled bool) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnWindowSetBlurBehind(enabled) != 0 {
+		return fmt.Errorf("no event loop running")
+	}
+	return nil
+}
+
+// WindowSetOpacity sets the opacity of the whole window, from 0 (invisible)
+// to 1 (opaque). A no-op on Wayland and on X11 without a compositor.
+// Safe to call from any goroutine.
+func WindowSetOpacity(alpha float32) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	switch fnWindowSetOpacity(alpha) {
+	case 0:
+		return nil
+	case CodeInvalidArgument:
+		return fmt.Errorf("opacity %v out of range 0-1", alpha)
+	default:
+		return fmt.Errorf("no event loop running")
+	}
+}
+
+// WindowSetIMECursorArea tells the IME where the text cursor is, in logical
+// pixels, so its candidate window opens next to it. Call it when a text
+// field gains focus and whenever its caret moves during composition.
+// Safe to call from any goroutine.
+func WindowSetIMECursorArea(x, y, width, height float64) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnWindowSetIMECursorArea(x, y, width, height) != 0 {
+		return fmt.Errorf("no event loop running")
+	}
+	return nil
+}
+
 // Version returns the engine version string
 func Version() string {
 	if !initialized {
@@ -1408,22 +1999,129 @@ func Version() string {
 	return goString(ptr)
 }
 
+// Error codes returned by engine functions. A code means the same thing
+// from every function (see EngineError in engine/src/error.rs).
+const (
+	CodeInvalidArgument = -1 // null pointer, invalid UTF-8 or JSON, bad argument
+	CodeNotFound        = -2 // no player, input, texture or layer with that ID
+	CodeOperationFailed = -3 // decoding, loading or a platform call failed
+	CodeGPUUploadFailed = -4
+	CodeUnsupported     = -5 // not supported on this platform or GPU
+	CodeNotInitialized  = -6 // engine, GPU backend or tray icon not created yet
+	CodeNoEventLoop     = -7
+	CodeBufferTooSmall  = -8
+	CodeNotReady        = -9 // nothing available yet (no media loaded, no frame)
+)
+
+// ErrorString describes an error code returned by an engine function
+func ErrorString(code int) string {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return fmt.Sprintf("error %d", code)
+		}
+	}
+	return goString(fnErrorString(int32(code)))
+}
+
+var logCallbackPtr uintptr
+
+// SetVerboseLogging turns the engine's diagnostic logging (window lifecycle,
+// resizes, safe-area changes) on or off. It is off by default. Messages are
+// written with the standard log package.
+func SetVerboseLogging(enabled bool) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return
+		}
+	}
+	if enabled {
+		installLogCallback()
+	}
+	fnSetVerboseLog(enabled)
+}
+
+// SetCallbackWatchdog warns when a frame callback runs longer than threshold,
+// which would otherwise freeze the UI silently (a long GC pause, a blocking
+// call, a deadlock). The warning is written with the standard log package.
+// With presentLastFrame, the last frame is presented again every threshold
+// interval until the callback returns. The callback is never interrupted.
+// A threshold of 0 turns the watchdog off.
+func SetCallbackWatchdog(threshold time.Duration, presentLastFrame bool) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return
+		}
+	}
+	if threshold > 0 {
+		installLogCallback()
+	}
+	fnSetWatchdog(uint32(threshold.Milliseconds()), presentLastFrame)
+}
+
+// CacheFlags selects engine caches for InvalidateCaches
+type CacheFlags uint32
+
+const (
+	// CacheText holds loaded font faces and font lookups (family to file)
+	CacheText CacheFlags = 1 << iota
+	// CacheGlyphAtlas holds rasterized glyphs
+	CacheGlyphAtlas
+	// CacheStyle holds style classes parsed against the theme
+	CacheStyle
+	// CacheShaping holds laid-out text (see SetTextShapingCacheCapacity)
+	CacheShaping
+
+	CacheAll = CacheText | CacheGlyphAtlas | CacheStyle | CacheShaping
+)
+
+// InvalidateCaches clears engine caches after the app font or theme changes
+// at runtime, so the next frame doesn't mix stale and fresh rendering. The
+// next frame is rendered in full and a redraw is requested.
+func InvalidateCaches(flags CacheFlags) error {
+	if !initialized {
+		return fmt.Errorf("engine not initialized")
+	}
+	if fnInvalidateCaches(uint32(flags)) != 0 {
+		return fmt.Errorf("invalid cache flags %#x", uint32(flags))
+	}
+	return nil
+}
+
+// SetTextShapingCacheCapacity sets how many laid-out texts DrawText and
+// text measurement keep for reuse (512 by default), so labels drawn every
+// frame skip shaping and line breaking. 0 turns the cache off; clear it with
+// InvalidateCaches(CacheShaping).
+func SetTextShapingCacheCapacity(capacity int) error {
+	if !initialized {
+		return fmt.Errorf("engine not initialized")
+	}
+	if capacity < 0 {
+		return fmt.Errorf("negative shaping cache capacity %d", capacity)
+	}
+	if fnSetShapingCache(uint32(capacity)) != 0 {
+		return fmt.Errorf("renderer not initialized")
+	}
+	return nil
+}
+
+// installLogCallback routes engine log messages to the standard log package
+func installLogCallback() {
+	if logCallbackPtr != 0 {
+		return
+	}
+	logCallbackPtr = purego.NewCallback(func(message uintptr) {
+		log.Print(goString(message))
+	})
+	fnSetLogCallback(logCallbackPtr)
+}
+
 // AppError represents an error from the engine
 type AppError struct {
 	Code int
 }
 
 func (e *AppError) Error() string {
-	switch e.Code {
-	case -1:
-		return "null config"
-	case -2:
-		return "failed to create event loop"
-	case -3:
-		return "event loop error"
-	default:
-		return "unknown error"
-	}
+	return "app error: " + ErrorString(e.Code)
 }
 
 // ============================================================================
@@ -1432,16 +2130,29 @@ func (e *AppError) Error() string {
 
 // RenderCommand represents a single rendering operation
 type RenderCommand struct {
-	DrawRect        *DrawRectCmd        `json:"DrawRect,omitempty"`
-	DrawText        *DrawTextCmd        `json:"DrawText,omitempty"`
-	DrawImage       *DrawImageCmd       `json:"DrawImage,omitempty"`
-	DrawShadow      *DrawShadowCmd      `json:"DrawShadow,omitempty"`
-	Clear           *ClearCmd           `json:"Clear,omitempty"`
-	PushClip        *PushClipCmd        `json:"PushClip,omitempty"`
-	PopClip         *struct{}           `json:"PopClip,omitempty"`
-	BeginScrollView *BeginScrollViewCmd `json:"BeginScrollView,omitempty"`
-	EndScrollView   *struct{}           `json:"EndScrollView,omitempty"`
-	SetOpacity      *float32            `json:"SetOpacity,omitempty"`
+	DrawRect         *DrawRectCmd        `json:"DrawRect,omitempty"`
+	DrawText         *DrawTextCmd        `json:"DrawText,omitempty"`
+	DrawRichText     *DrawRichTextCmd    `json:"DrawRichText,omitempty"`
+	DrawImage        *DrawImageCmd       `json:"DrawImage,omitempty"`
+	DrawNinePatch    *DrawNinePatchCmd   `json:"DrawNinePatch,omitempty"`
+	DrawLine         *DrawLineCmd        `json:"DrawLine,omitempty"`
+	DrawArc          *DrawArcCmd         `json:"DrawArc,omitempty"`
+	DrawPath         *DrawPathCmd        `json:"DrawPath,omitempty"`
+	DrawShadow       *DrawShadowCmd      `json:"DrawShadow,omitempty"`
+	Clear            *ClearCmd           `json:"Clear,omitempty"`
+	PushClip         *PushClipCmd        `json:"PushClip,omitempty"`
+	PushRoundedClip  *PushRoundedClipCmd `json:"PushRoundedClip,omitempty"`
+	PopClip          *struct{}           `json:"PopClip,omitempty"`
+	BeginScrollView  *BeginScrollViewCmd `json:"BeginScrollView,omitempty"`
+	EndScrollView    *struct{}           `json:"EndScrollView,omitempty"`
+	PushTransform    *PushTransformCmd   `json:"PushTransform,omitempty"`
+	PopTransform     *struct{}           `json:"PopTransform,omitempty"`
+	SetOpacity       *float32            `json:"SetOpacity,omitempty"`
+	PushOpacityLayer *float32            `json:"PushOpacityLayer,omitempty"`
+	PopOpacityLayer  *struct{}           `json:"PopOpacityLayer,omitempty"`
+	Caret            *CaretCmd           `json:"Caret,omitempty"`
+	FocusRing        *FocusRingCmd       `json:"FocusRing,omitempty"`
+	Spinner          *SpinnerCmd         `json:"Spinner,omitempty"`
 }
 
 type BeginScrollViewCmd struct {
@@ -1463,8 +2174,12 @@ type DrawRectCmd struct {
 	Color       uint32     `json:"color"`
 	CornerRadii [4]float32 `json:"corner_radii"`
 	Rotation    float32    `json:"rotation,omitempty"`
-	Border      *Border    `json:"border,omitempty"`
-	Gradient    *Gradient  `json:"gradient,omitempty"`
+	// Point to rotate about, relative to the top-left corner (nil = center)
+	Pivot       *[2]float32  `json:"pivot,omitempty"`
+	Border      *Border      `json:"border,omitempty"`
+	Gradient    *Gradient    `json:"gradient,omitempty"`
+	InnerShadow *InnerShadow `json:"inner_shadow,omitempty"`
+	Shadow      *BoxShadow   `json:"shadow,omitempty"`
 }
 
 type DrawImageCmd struct {
@@ -1475,17 +2190,73 @@ type DrawImageCmd struct {
 	TextureID   uint32      `json:"texture_id"`
 	SourceRect  *[4]float32 `json:"source_rect,omitempty"`
 	CornerRadii [4]float32  `json:"corner_radii,omitempty"`
-}
+	// Opacity 0-1, multiplied with SetOpacity (nil = opaque)
+	Opacity *float32 `json:"opacity,omitempty"`
+	// Rotation in radians about Pivot (relative to the top-left corner; nil = center)
+	Rotation float32     `json:"rotation,omitempty"`
+	Pivot    *[2]float32 `json:"pivot,omitempty"`
+	// Region of the texture to draw in texture pixels, e.g. a sprite atlas
+	// cell (nil = SourceRect or the whole texture)
+	Src *RectBounds `json:"src,omitempty"`
+	// Color (0xRRGGBBAA) multiplied with each sampled texel (nil = none)
+	Tint *uint32 `json:"tint,omitempty"`
+	// How texels are filtered when scaled (empty = Trilinear)
+	Sampling Sampling `json:"sampling,omitempty"`
+}
+
+// Sampling is how an image's texels are filtered when it is drawn larger or
+// smaller than its texture. Images get mipmaps on upload, so Trilinear stays
+// smooth when scaled far down; Nearest keeps pixel art crisp.
+type Sampling string
+
+const (
+	SamplingNearest   Sampling = "Nearest"
+	SamplingLinear    Sampling = "Linear"
+	SamplingTrilinear Sampling = "Trilinear"
+)
 
 type Border struct {
-	Width float32 `json:"width"`
-	Color uint32  `json:"color"`
-	Style string  `json:"style"`
+	Width float32     `json:"width"`
+	Color uint32      `json:"color"`
+	Style BorderStyle `json:"style,omitempty"` // Empty = Solid
+	// Dash length for Dashed (0 = 3x Width)
+	DashLength float32 `json:"dash_length,omitempty"`
+	// Space between dashes or dots (0 = DashLength for Dashed, Width for Dotted)
+	GapLength float32 `json:"gap_length,omitempty"`
+}
+
+type BorderStyle string
+
+const (
+	BorderStyleSolid  BorderStyle = "Solid"
+	BorderStyleDashed BorderStyle = "Dashed"
+	BorderStyleDotted BorderStyle = "Dotted"
+)
+
+// InnerShadow is a shadow inside a rect, drawn over the fill and under the
+// border. Offset (0, 4) shades the top edge most.
+type InnerShadow struct {
+	Blur    float32 `json:"blur"`
+	Spread  float32 `json:"spread"`
+	OffsetX float32 `json:"offset_x"`
+	OffsetY float32 `json:"offset_y"`
+	Color   uint32  `json:"color"`
+}
+
+// BoxShadow is a drop shadow behind a rect, like CSS box-shadow. Offset
+// (0, 4) drops it below the rect; Spread grows it before blurring.
+type BoxShadow struct {
+	OffsetX    float32 `json:"offset_x"`
+	OffsetY    float32 `json:"offset_y"`
+	BlurRadius float32 `json:"blur_radius"`
+	Spread     float32 `json:"spread"`
+	Color      uint32  `json:"color"`
 }
 
 type Gradient struct {
 	Linear *LinearGradient `json:"Linear,omitempty"`
 	Radial *RadialGradient `json:"Radial,omitempty"`
+	Conic  *ConicGradient  `json:"Conic,omitempty"`
 }
 
 type LinearGradient struct {
@@ -1499,6 +2270,16 @@ type RadialGradient struct {
 	Stops   []GradientStop `json:"stops"`
 }
 
+// ConicGradient sweeps clockwise around a center, for pie charts and
+// progress rings. StartAngle is in degrees clockwise from 12 o'clock and stop
+// positions are fractions of a full turn.
+type ConicGradient struct {
+	CenterX    float32        `json:"center_x"`
+	CenterY    float32        `json:"center_y"`
+	StartAngle float32        `json:"start_angle"`
+	Stops      []GradientStop `json:"stops"`
+}
+
 type GradientStop struct {
 	Position float32 `json:"position"`
 	Color    uint32  `json:"color"`
@@ -1511,6 +2292,49 @@ type DrawTextCmd struct {
 	Font   FontDescriptor   `json:"font"`
 	Color  uint32           `json:"color"`
 	Layout TextLayoutConfig `json:"layout"`
+	// Gradient, if set, replaces Color. It spans the laid-out text box
+	// (all lines), not each glyph.
+	Gradient *Gradient `json:"gradient,omitempty"`
+}
+
+// DrawRichTextCmd draws a paragraph of text runs in different colors and
+// fonts with inline images, wrapped together on shared baselines. X, Y is
+// the paragraph's top-left; Layout supplies the wrap width, line height,
+// alignment and line limit. Use DrawText for single-style text.
+type DrawRichTextCmd struct {
+	X      float32          `json:"x"`
+	Y      float32          `json:"y"`
+	Runs   []TextRun        `json:"runs"`
+	Layout TextLayoutConfig `json:"layout"`
+	// Gradient, if set, replaces the text runs' colors. It spans the
+	// paragraph box (all lines), across runs; images are not tinted.
+	Gradient *Gradient `json:"gradient,omitempty"`
+}
+
+// TextRun is one run of a DrawRichText paragraph: text when Text is set,
+// otherwise an inline image. Build runs with RichTextRun and RichImageRun.
+type TextRun struct {
+	Text  *string         `json:"text,omitempty"`
+	Color uint32          `json:"color"`
+	Font  *FontDescriptor `json:"font,omitempty"`
+
+	ImageTextureID uint32  `json:"image_texture_id"`
+	Width          float32 `json:"width"`
+	Height         float32 `json:"height"`
+	// How far the image's bottom edge sits below the baseline (0 rests it
+	// on the baseline)
+	BaselineOffset float32 `json:"baseline_offset"`
+}
+
+// RichTextRun is a run of text in one color and font.
+func RichTextRun(text string, color uint32, font FontDescriptor) TextRun {
+	return TextRun{Text: &text, Color: color, Font: &font}
+}
+
+// RichImageRun is an inline image of a loaded texture, width x height
+// logical pixels, with its bottom baselineOffset below the baseline.
+func RichImageRun(textureID uint32, width, height, baselineOffset float32) TextRun {
+	return TextRun{ImageTextureID: textureID, Width: width, Height: height, BaselineOffset: baselineOffset}
 }
 
 type FontDescriptor struct {
@@ -1521,8 +2345,19 @@ type FontDescriptor struct {
 }
 
 type FontSource struct {
-	System  *string `json:"System,omitempty"`
-	Bundled *string `json:"Bundled,omitempty"`
+	System             *string           `json:"System,omitempty"`
+	Bundled            *string           `json:"Bundled,omitempty"`
+	// Memory is a font registered with RegisterFontMemory
+	Memory             *MemoryFontSource `json:"Memory,omitempty"`
+	// SystemWithFallback lists system families in order, primary first.
+	// Each character is drawn with the first family that has a glyph for it.
+	SystemWithFallback []string          `json:"SystemWithFallback,omitempty"`
+}
+
+// MemoryFontSource refers to font bytes registered with RegisterFontMemory
+type MemoryFontSource struct {
+	Name     string `json:"name"`
+	DataHash uint64 `json:"data_hash"`
 }
 
 type FontStyle string
@@ -1544,6 +2379,8 @@ type TextLayoutConfig struct {
 	WordBreak     WordBreak     `json:"word_break"`
 	Overflow      TextOverflow  `json:"overflow"`
 	WhiteSpace    WhiteSpace    `json:"white_space"`
+	Anchor        TextAnchor    `json:"anchor,omitempty"`         // Empty = TopLeft
+	BaseDirection TextDirection `json:"base_direction,omitempty"` // Empty = Auto
 }
 
 type TextAlign string
@@ -1553,6 +2390,8 @@ const (
 	TextAlignCenter  TextAlign = "Center"
 	TextAlignRight   TextAlign = "Right"
 	TextAlignJustify TextAlign = "Justify"
+	TextAlignStart   TextAlign = "Start" // Left in LTR text, right in RTL text
+	TextAlignEnd     TextAlign = "End"   // Right in LTR text, left in RTL text
 )
 
 type VerticalAlign string
@@ -1561,7 +2400,7 @@ const (
 	VerticalAlignTop      VerticalAlign = "Top"
 	VerticalAlignMiddle   VerticalAlign = "Middle"
 	VerticalAlignBottom   VerticalAlign = "Bottom"
-	VerticalAlignBaseline VerticalAlign = "Baseline"
+	VerticalAlignBaseline VerticalAlign = "Baseline" // y is the first line's baseline, whatever the Anchor
 )
 
 type WordBreak string
@@ -1590,6 +2429,144 @@ const (
 	WhiteSpacePreWrap WhiteSpace = "PreWrap"
 )
 
+// TextAnchor controls how DrawText's y coordinate is interpreted
+type TextAnchor string
+
+const (
+	TextAnchorTopLeft  TextAnchor = "TopLeft"
+	TextAnchorBaseline TextAnchor = "Baseline"
+	TextAnchorCenter   TextAnchor = "Center"
+)
+
+// TextDirection is the paragraph direction text is reordered and aligned in.
+// Auto takes it from the first strong character (Arabic, Hebrew, Latin...).
+type TextDirection string
+
+const (
+	TextDirectionAuto TextDirection = "Auto"
+	TextDirectionLtr  TextDirection = "Ltr"
+	TextDirectionRtl  TextDirection = "Rtl"
+)
+
+// DrawLineCmd draws a straight line segment Width logical pixels wide
+type DrawLineCmd struct {
+	X1    float32 `json:"x1"`
+	Y1    float32 `json:"y1"`
+	X2    float32 `json:"x2"`
+	Y2    float32 `json:"y2"`
+	Width float32 `json:"width"`
+	Color uint32  `json:"color"`
+	Cap   LineCap `json:"cap,omitempty"` // Empty = Butt
+}
+
+// DrawNinePatchCmd stretches a texture over DestRect keeping its borders at
+// their own size: corners unscaled, edges stretched along their length and
+// the middle filling the rest. Insets are the borders [top, right, bottom,
+// left] in texture pixels; a DestRect too small for them shrinks them to fit.
+type DrawNinePatchCmd struct {
+	TextureID uint32     `json:"texture_id"`
+	DestRect  RectBounds `json:"dest_rect"`
+	Insets    [4]float32 `json:"insets"`
+}
+
+// DrawArcCmd draws an antialiased arc of a circle: a ring segment Thickness
+// logical pixels wide inside Radius, or a filled wedge when Thickness is 0.
+// Angles are in radians clockwise from 3 o'clock, and the arc covers the
+// sweep from StartAngle to EndAngle in either direction; a full turn draws a
+// whole ring or circle.
+type DrawArcCmd struct {
+	CX         float32 `json:"cx"`
+	CY         float32 `json:"cy"`
+	Radius     float32 `json:"radius"`
+	StartAngle float32 `json:"start_angle"`
+	EndAngle   float32 `json:"end_angle"`
+	Thickness  float32 `json:"thickness,omitempty"`
+	Color      uint32  `json:"color"`
+}
+
+// LineCap is how the ends of a line are drawn. Round and Square extend past
+// the endpoints by half the width, so a zero-length line still draws a dot.
+type LineCap string
+
+const (
+	LineCapButt   LineCap = "Butt"
+	LineCapRound  LineCap = "Round"
+	LineCapSquare LineCap = "Square"
+)
+
+// LineJoin is how the corners between stroked path segments are drawn.
+// Miter corners are beveled where they'd reach past four times the width.
+type LineJoin string
+
+const (
+	LineJoinMiter LineJoin = "Miter"
+	LineJoinRound LineJoin = "Round"
+	LineJoinBevel LineJoin = "Bevel"
+)
+
+// FillRule is which parts of a self-intersecting or nested path are filled:
+// with NonZero a star drawn as one crossing outline is solid, with EvenOdd
+// its center is a hole.
+type FillRule string
+
+const (
+	FillRuleNonZero FillRule = "NonZero"
+	FillRuleEvenOdd FillRule = "EvenOdd"
+)
+
+// DrawPathCmd fills and/or strokes an outline of lines and bezier curves, in
+// logical pixels. Subpaths left open are filled as if closed but stroked
+// open, and the stroke is drawn over the fill.
+type DrawPathCmd struct {
+	Segments []PathSegment `json:"segments"`
+	// Fill color (0xRRGGBBAA; nil = stroke only)
+	Fill *uint32 `json:"fill,omitempty"`
+	// FillRule for overlapping subpaths (empty = NonZero)
+	FillRule FillRule `json:"fill_rule,omitempty"`
+	Stroke   *Stroke  `json:"stroke,omitempty"`
+}
+
+// PathSegment is one step of a path outline, with exactly one field set
+type PathSegment struct {
+	MoveTo  *PathPoint `json:"MoveTo,omitempty"`
+	LineTo  *PathPoint `json:"LineTo,omitempty"`
+	QuadTo  *PathQuad  `json:"QuadTo,omitempty"`
+	CubicTo *PathCubic `json:"CubicTo,omitempty"`
+	Close   *struct{}  `json:"Close,omitempty"`
+}
+
+type PathPoint struct {
+	X float32 `json:"x"`
+	Y float32 `json:"y"`
+}
+
+// PathQuad is a quadratic bezier to (X, Y) with control point (CX, CY)
+type PathQuad struct {
+	CX float32 `json:"cx"`
+	CY float32 `json:"cy"`
+	X  float32 `json:"x"`
+	Y  float32 `json:"y"`
+}
+
+// PathCubic is a cubic bezier to (X, Y) with control points (C1X, C1Y) and
+// (C2X, C2Y)
+type PathCubic struct {
+	C1X float32 `json:"c1x"`
+	C1Y float32 `json:"c1y"`
+	C2X float32 `json:"c2x"`
+	C2Y float32 `json:"c2y"`
+	X   float32 `json:"x"`
+	Y   float32 `json:"y"`
+}
+
+// Stroke is an outline Width logical pixels wide, centered on a path
+type Stroke struct {
+	Width float32  `json:"width"`
+	Color uint32   `json:"color"`
+	Cap   LineCap  `json:"cap,omitempty"`  // Empty = Butt
+	Join  LineJoin `json:"join,omitempty"` // Empty = Miter
+}
+
 type DrawShadowCmd struct {
 	X           float32    `json:"x"`
 	Y           float32    `json:"y"`
@@ -1602,9 +2579,53 @@ type DrawShadowCmd struct {
 	CornerRadii [4]float32 `json:"corner_radii"`
 }
 
-type ClearCmd struct {
-	R uint8 `json:"r"`
-	G uint8 `json:"g"`
+// CaretCmd draws a text caret that the engine blinks on its own frame clock,
+// so no RedrawAfterMs is needed to animate it
+type CaretCmd struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width,omitempty"` // 0 = 1 logical pixel
+	Height float32 `json:"height"`
+	Color  uint32  `json:"color"`
+	// Time shown (and then hidden) per blink; 0 keeps the caret solid
+	BlinkMs uint32 `json:"blink_ms"`
+}
+
+// RectBounds is a rect's position and size in logical pixels
+type RectBounds struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// FocusRingCmd draws a dashed ring just outside a rect. With a non-zero
+// Speed the engine marches the dashes on its own frame clock and keeps
+// redrawing while the ring is submitted.
+type FocusRingCmd struct {
+	Rect        RectBounds `json:"rect"`
+	CornerRadii [4]float32 `json:"corner_radii"`
+	Color       uint32     `json:"color"`
+	Thickness   float32    `json:"thickness,omitempty"` // 0 = 2 logical pixels
+	Dash        float32    `json:"dash,omitempty"`      // dash and gap length; 0 = 4 logical pixels
+	Offset      float32    `json:"offset,omitempty"`    // shifts the dash pattern along the outline
+	Speed       float32    `json:"speed,omitempty"`     // logical pixels per second clockwise; 0 = static
+}
+
+// SpinnerCmd draws an indeterminate progress ring that the engine turns on
+// its own frame clock, redrawing while the spinner is submitted
+type SpinnerCmd struct {
+	CenterX   float32 `json:"center_x"`
+	CenterY   float32 `json:"center_y"`
+	Radius    float32 `json:"radius"`
+	Color     uint32  `json:"color"`
+	Speed     float32 `json:"speed"`               // revolutions per second
+	Thickness float32 `json:"thickness,omitempty"` // 0 = a fifth of the radius
+}
+
+type ClearCmd struct {
+	R uint8 `json:"r"`
+	G uint8 `json:"g"`
 	B uint8 `json:"b"`
 	A uint8 `json:"a"`
 }
@@ -1616,6 +2637,22 @@ type PushClipCmd struct {
 	Height float32 `json:"height"`
 }
 
+// PushRoundedClipCmd clips to a rounded rectangle. CornerRadii are
+// top-left, top-right, bottom-right, bottom-left.
+type PushRoundedClipCmd struct {
+	X           float32    `json:"x"`
+	Y           float32    `json:"y"`
+	Width       float32    `json:"width"`
+	Height      float32    `json:"height"`
+	CornerRadii [4]float32 `json:"corner_radii"`
+}
+
+// PushTransformCmd holds the [a, b, c, d, e, f] matrix of a PushTransform,
+// in CSS matrix() order.
+type PushTransformCmd struct {
+	Matrix [6]float32 `json:"matrix"`
+}
+
 // ============================================================================
 // Command Builders
 // ============================================================================
@@ -1640,6 +2677,76 @@ func RoundedRect(x, y, width, height float32, color uint32, radius float32) Rend
 	}
 }
 
+// Line draws a line from (x1, y1) to (x2, y2)
+func Line(x1, y1, x2, y2, width float32, color uint32, cap LineCap) RenderCommand {
+	return RenderCommand{
+		DrawLine: &DrawLineCmd{X1: x1, Y1: y1, X2: x2, Y2: y2, Width: width, Color: color, Cap: cap},
+	}
+}
+
+// Arc draws an arc from startAngle to endAngle (radians clockwise from
+// 3 o'clock) around (cx, cy). A thickness of 0 fills the wedge, for pie
+// slices; otherwise the stroke lies inside radius, for spinners and gauges.
+func Arc(cx, cy, radius, startAngle, endAngle, thickness float32, color uint32) RenderCommand {
+	return RenderCommand{
+		DrawArc: &DrawArcCmd{
+			CX: cx, CY: cy, Radius: radius,
+			StartAngle: startAngle, EndAngle: endAngle,
+			Thickness: thickness, Color: color,
+		},
+	}
+}
+
+// Circle draws an antialiased filled circle: a full-turn Arc
+func Circle(cx, cy, radius float32, color uint32) RenderCommand {
+	return Arc(cx, cy, radius, 0, 2*math.Pi, 0, color)
+}
+
+// MoveTo starts a new subpath at (x, y)
+func MoveTo(x, y float32) PathSegment {
+	return PathSegment{MoveTo: &PathPoint{X: x, Y: y}}
+}
+
+// LineTo draws a straight line to (x, y)
+func LineTo(x, y float32) PathSegment {
+	return PathSegment{LineTo: &PathPoint{X: x, Y: y}}
+}
+
+// QuadTo draws a quadratic bezier to (x, y) bending towards (cx, cy)
+func QuadTo(cx, cy, x, y float32) PathSegment {
+	return PathSegment{QuadTo: &PathQuad{CX: cx, CY: cy, X: x, Y: y}}
+}
+
+// CubicTo draws a cubic bezier to (x, y) with control points (c1x, c1y)
+// and (c2x, c2y)
+func CubicTo(c1x, c1y, c2x, c2y, x, y float32) PathSegment {
+	return PathSegment{CubicTo: &PathCubic{C1X: c1x, C1Y: c1y, C2X: c2x, C2Y: c2y, X: x, Y: y}}
+}
+
+// ClosePath closes the subpath with a line back to its start
+func ClosePath() PathSegment {
+	return PathSegment{Close: &struct{}{}}
+}
+
+// FillPath fills the shape outlined by segments, e.g. a speech bubble tail
+// or a vector icon
+func FillPath(segments []PathSegment, color uint32, rule FillRule) RenderCommand {
+	return RenderCommand{
+		DrawPath: &DrawPathCmd{Segments: segments, Fill: &color, FillRule: rule},
+	}
+}
+
+// StrokePath draws a line width logical pixels wide along segments, e.g. a
+// signature stroke
+func StrokePath(segments []PathSegment, width float32, color uint32, cap LineCap, join LineJoin) RenderCommand {
+	return RenderCommand{
+		DrawPath: &DrawPathCmd{
+			Segments: segments,
+			Stroke:   &Stroke{Width: width, Color: color, Cap: cap, Join: join},
+		},
+	}
+}
+
 func Shadow(x, y, width, height, blur float32, color uint32, offsetX, offsetY float32, radii [4]float32) RenderCommand {
 	return RenderCommand{
 		DrawShadow: &DrawShadowCmd{
@@ -1663,12 +2770,86 @@ func PushClip(x, y, width, height float32) RenderCommand {
 	}
 }
 
+// PushRoundedClip clips everything until the matching PopClip to a rounded
+// rectangle with per-corner radii (top-left, top-right, bottom-right,
+// bottom-left). Nested clips intersect; PopClip restores the enclosing one.
+func PushRoundedClip(x, y, width, height float32, cornerRadii [4]float32) RenderCommand {
+	return RenderCommand{
+		PushRoundedClip: &PushRoundedClipCmd{X: x, Y: y, Width: width, Height: height, CornerRadii: cornerRadii},
+	}
+}
+
 func PopClip() RenderCommand {
 	return RenderCommand{
 		PopClip: &struct{}{},
 	}
 }
 
+// PushTransform transforms everything drawn until the matching
+// PopTransform, clips and text included: (x, y) maps to
+// (a*x + c*y + e, b*x + d*y + f). Transforms nest.
+func PushTransform(a, b, c, d, e, f float32) RenderCommand {
+	return RenderCommand{
+		PushTransform: &PushTransformCmd{Matrix: [6]float32{a, b, c, d, e, f}},
+	}
+}
+
+// PushPanZoom is the PushTransform of a zoomable canvas: content is scaled
+// by zoom about the origin, then moved by (panX, panY).
+func PushPanZoom(panX, panY, zoom float32) RenderCommand {
+	return PushTransform(zoom, 0, 0, zoom, panX, panY)
+}
+
+func PopTransform() RenderCommand {
+	return RenderCommand{
+		PopTransform: &struct{}{},
+	}
+}
+
+// PushOpacityLayer draws everything until the matching PopOpacityLayer as
+// one group faded to alpha (0-1). Unlike SetOpacity, which fades each draw
+// on its own, overlapping draws in the group don't show through each other.
+// Layers nest; clips pushed outside the layer clip the whole group.
+func PushOpacityLayer(alpha float32) RenderCommand {
+	return RenderCommand{
+		PushOpacityLayer: &alpha,
+	}
+}
+
+func PopOpacityLayer() RenderCommand {
+	return RenderCommand{
+		PopOpacityLayer: &struct{}{},
+	}
+}
+
+// Caret draws a blinking text caret. It restarts solid whenever it moves.
+func Caret(x, y, height float32, color uint32, blinkMs uint32) RenderCommand {
+	return RenderCommand{
+		Caret: &CaretCmd{X: x, Y: y, Height: height, Color: color, BlinkMs: blinkMs},
+	}
+}
+
+// FocusRing draws a dashed focus ring around a rect, marching its dashes at
+// speed logical pixels per second (0 for a static ring).
+func FocusRing(x, y, width, height float32, radii [4]float32, color uint32, speed float32) RenderCommand {
+	return RenderCommand{
+		FocusRing: &FocusRingCmd{
+			Rect:        RectBounds{X: x, Y: y, Width: width, Height: height},
+			CornerRadii: radii,
+			Color:       color,
+			Speed:       speed,
+		},
+	}
+}
+
+// Spinner draws an indeterminate progress spinner of the given outer radius
+// around (cx, cy), turning at speed revolutions per second.
+func Spinner(cx, cy, radius float32, color uint32, speed float32) RenderCommand {
+	return RenderCommand{
+		Spinner: &SpinnerCmd{CenterX: cx, CenterY: cy, Radius: radius, Color: color, Speed: speed},
+	}
+}
+
 func BeginScrollView(x, y, width, height, scrollX, scrollY float32) RenderCommand {
 	return RenderCommand{
 		BeginScrollView: &BeginScrollViewCmd{
@@ -1796,6 +2977,15 @@ func SystemFontBlack(name string, size float32) FontDescriptor {
 	}
 }
 
+// SystemFontWithFallback uses the first of names that has a glyph for each
+// character, e.g. a Latin UI font followed by CJK and emoji fonts.
+func SystemFontWithFallback(names []string, size float32) FontDescriptor {
+	return FontDescriptor{
+		Source: FontSource{SystemWithFallback: names},
+		Weight: 400, Style: FontStyleNormal, Size: size,
+	}
+}
+
 func BundledFont(path string, size float32) FontDescriptor {
 	return FontDescriptor{
 		Source: FontSource{Bundled: &path},
@@ -1810,6 +3000,150 @@ func BundledFontWithWeight(path string, size float32, weight uint16) FontDescrip
 	}
 }
 
+// MemoryFont uses the font registered as name by RegisterFontMemory,
+// which returned hash
+func MemoryFont(name string, hash uint64, size float32) FontDescriptor {
+	return FontDescriptor{
+		Source: FontSource{Memory: &MemoryFontSource{Name: name, DataHash: hash}},
+		Weight: 400, Style: FontStyleNormal, Size: size,
+	}
+}
+
+// RegisterFontMemory registers font file bytes (TTF/OTF) as family name and
+// returns the hash to draw with via MemoryFont. The bytes are copied.
+// Registering identical bytes again returns the same hash without storing
+// them twice; balance every call with UnregisterFont.
+func RegisterFontMemory(name string, data []byte) (uint64, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return 0, err
+		}
+	}
+	if len(data) == 0 {
+		return 0, fmt.Errorf("empty font data")
+	}
+
+	nameBytes := []byte(name)
+	var namePtr uintptr
+	if len(nameBytes) > 0 {
+		namePtr = uintptr(unsafe.Pointer(&nameBytes[0]))
+	} else {
+		namePtr = uintptr(unsafe.Pointer(&data[0])) // any non-null pointer for an empty name
+	}
+	hash := fnRegisterFontMemory(namePtr, uint64(len(nameBytes)), uintptr(unsafe.Pointer(&data[0])), uint64(len(data)))
+	runtime.KeepAlive(nameBytes)
+	runtime.KeepAlive(data)
+
+	if hash == 0 {
+		return 0, fmt.Errorf("failed to register font %q", name)
+	}
+	return hash, nil
+}
+
+// UnregisterFont drops a RegisterFontMemory registration. The bytes are
+// freed once every registration of them is dropped.
+func UnregisterFont(hash uint64) error {
+	if !initialized {
+		return fmt.Errorf("not initialized")
+	}
+	if fnUnregisterFont(hash) != 0 {
+		return fmt.Errorf("font %#x is not registered", hash)
+	}
+	return nil
+}
+
+// FontFamilyInfo is an installed font family, as listed by ListFonts
+type FontFamilyInfo struct {
+	Family string `json:"family"`
+	// Weights of the installed faces on the CSS scale (100-900), ascending
+	Weights   []uint16 `json:"weights"`
+	HasItalic bool     `json:"has_italic"`
+}
+
+// ListFonts returns the installed system font families sorted by name, e.g.
+// for a font picker. The list is built on the first call, which can be
+// slow, and cached after that.
+func ListFonts() ([]FontFamilyInfo, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return nil, err
+		}
+	}
+
+	ptr := fnListFonts()
+	if ptr == 0 {
+		return nil, fmt.Errorf("failed to list fonts")
+	}
+	defer fnFreeString(ptr)
+
+	var families []FontFamilyInfo
+	if err := json.Unmarshal([]byte(goString(ptr)), &families); err != nil {
+		return nil, err
+	}
+	return families, nil
+}
+
+// AccessibilityRole is what an accessibility node is, for screen readers
+type AccessibilityRole string
+
+const (
+	AccessibilityRoleGroup       AccessibilityRole = "Group" // Containers and custom widgets
+	AccessibilityRoleScrollArea  AccessibilityRole = "ScrollArea"
+	AccessibilityRoleStaticText  AccessibilityRole = "StaticText"
+	AccessibilityRoleHeading     AccessibilityRole = "Heading"
+	AccessibilityRoleButton      AccessibilityRole = "Button"
+	AccessibilityRoleTextField   AccessibilityRole = "TextField"
+	AccessibilityRoleTextArea    AccessibilityRole = "TextArea"
+	AccessibilityRoleCheckBox    AccessibilityRole = "CheckBox"
+	AccessibilityRoleRadioButton AccessibilityRole = "RadioButton"
+	AccessibilityRoleSlider      AccessibilityRole = "Slider"
+)
+
+// AccessibilityBounds is a node's area in window coordinates (logical pixels)
+type AccessibilityBounds struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// AccessibilityNode is a visible widget as screen readers see it. Label is
+// the widget's label, or its text for text, headings, buttons, checkboxes
+// and radios; Value is its value, or a text field's text.
+type AccessibilityNode struct {
+	// ID is the widget's id in the form widget deltas use
+	ID       json.RawMessage     `json:"id"`
+	Role     AccessibilityRole   `json:"role"`
+	Label    string              `json:"label"`
+	Value    string              `json:"value"`
+	Bounds   AccessibilityBounds `json:"bounds"`
+	Focused  bool                `json:"focused"`
+	Disabled bool                `json:"disabled"`
+	Children []AccessibilityNode `json:"children"`
+}
+
+// AccessibilityTree returns the retained widget tree as screen readers see
+// it, as of the last widget delta. The root is nil in immediate mode.
+func AccessibilityTree() (*AccessibilityNode, error) {
+	if !initialized {
+		return nil, fmt.Errorf("not initialized")
+	}
+
+	ptr := fnAccessibilityTree()
+	if ptr == 0 {
+		return nil, fmt.Errorf("failed to get accessibility tree")
+	}
+	defer fnFreeString(ptr)
+
+	var tree struct {
+		Root *AccessibilityNode `json:"root"`
+	}
+	if err := json.Unmarshal([]byte(goString(ptr)), &tree); err != nil {
+		return nil, err
+	}
+	return tree.Root, nil
+}
+
 // LoadBundledFont preloads a bundled font from the given path.
 // On native platforms, fonts are loaded lazily by the engine, so this is a no-op.
 // On web, this must be called before using the font to register it with the browser.
@@ -1939,38 +3273,130 @@ func (e *ImageError) Error() string {
 	return e.Message
 }
 
-func imageErrorMessage(code int) string {
-	switch code {
-	case -1:
-		return "invalid parameters"
-	case -2:
-		return "backend not initialized"
-	case -3:
-		return "failed to decode image"
-	case -4:
-		return "failed to upload to GPU"
-	default:
-		return "unknown image error"
+func LoadImage(data []byte) (TextureID, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return 0, err
+		}
 	}
+	if len(data) == 0 {
+		return 0, &ImageError{Code: CodeInvalidArgument, Message: "empty image data"}
+	}
+
+	result := fnLoadImage(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)))
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return TextureID(result), nil
 }
 
-func LoadImage(data []byte) (TextureID, error) {
+// ImageOptions controls how LoadImageWithOptions uploads an image. The zero
+// value uploads exactly like LoadImage.
+type ImageOptions struct {
+	// Premultiply straight-alpha pixels on upload. Use it for images with
+	// soft or antialiased edges, which otherwise show a dark fringe where
+	// they are scaled or filtered against transparent pixels.
+	Premultiply bool
+	// NoMipmaps uploads only the full-size image, for images that are only
+	// drawn at 1:1 or are replaced often
+	NoMipmaps bool
+}
+
+// Upload flags, matching the engine's IMAGE_* constants
+const (
+	imagePremultiply uint32 = 0x01
+	imageNoMipmaps   uint32 = 0x02
+)
+
+func (o ImageOptions) flags() uint32 {
+	var flags uint32
+	if o.Premultiply {
+		flags |= imagePremultiply
+	}
+	if o.NoMipmaps {
+		flags |= imageNoMipmaps
+	}
+	return flags
+}
+
+// LoadImageWithOptions decodes and uploads an image like LoadImage, with
+// upload options
+func LoadImageWithOptions(data []byte, opts ImageOptions) (TextureID, error) {
 	if !initialized {
 		if err := initLibrary(); err != nil {
 			return 0, err
 		}
 	}
 	if len(data) == 0 {
-		return 0, &ImageError{Code: -1, Message: "empty image data"}
+		return 0, &ImageError{Code: CodeInvalidArgument, Message: "empty image data"}
 	}
 
-	result := fnLoadImage(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)))
+	result := fnLoadImageWithOptions(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)), opts.flags())
 	if result < 0 {
-		return 0, &ImageError{Code: int(result), Message: imageErrorMessage(int(result))}
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return TextureID(result), nil
 }
 
+// ImageBatchError reports, by index, the images in a LoadImages batch that
+// failed to load
+type ImageBatchError map[int]*ImageError
+
+func (e ImageBatchError) Error() string {
+	return fmt.Sprintf("%d of the images failed to load", len(e))
+}
+
+// imageData mirrors the engine's FFIImageData
+type imageData struct {
+	ptr uintptr
+	len uint64
+}
+
+// LoadImages decodes and uploads a batch of images in one call. ids[i] is
+// the texture for images[i], or 0 if that image failed, in which case err is
+// an ImageBatchError.
+func LoadImages(images [][]byte) ([]TextureID, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return nil, err
+		}
+	}
+	if len(images) == 0 {
+		return nil, nil
+	}
+
+	var pinner runtime.Pinner
+	defer pinner.Unpin()
+	entries := make([]imageData, len(images))
+	for i, data := range images {
+		if len(data) > 0 {
+			pinner.Pin(&data[0])
+			entries[i] = imageData{ptr: uintptr(unsafe.Pointer(&data[0])), len: uint64(len(data))}
+		}
+	}
+	results := make([]int32, len(images))
+	status := fnLoadImages(uintptr(unsafe.Pointer(&entries[0])), uint64(len(entries)), uintptr(unsafe.Pointer(&results[0])))
+	runtime.KeepAlive(entries)
+	runtime.KeepAlive(results)
+	if status < 0 {
+		return nil, &ImageError{Code: int(status), Message: ErrorString(int(status))}
+	}
+
+	ids := make([]TextureID, len(images))
+	failed := ImageBatchError{}
+	for i, result := range results {
+		if result < 0 {
+			failed[i] = &ImageError{Code: int(result), Message: ErrorString(int(result))}
+			continue
+		}
+		ids[i] = TextureID(result)
+	}
+	if len(failed) > 0 {
+		return ids, failed
+	}
+	return ids, nil
+}
+
 func LoadImageFile(path string) (TextureID, error) {
 	if !initialized {
 		if err := initLibrary(); err != nil {
@@ -1983,7 +3409,87 @@ func LoadImageFile(path string) (TextureID, error) {
 	runtime.KeepAlive(pathBytes)
 
 	if result < 0 {
-		return 0, &ImageError{Code: int(result), Message: imageErrorMessage(int(result))}
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return TextureID(result), nil
+}
+
+// LoadSVG rasterizes an SVG at width x height pixels. Pass the size it will
+// be displayed at times the scale factor for crisp icons; a 0 dimension
+// follows the SVG's aspect ratio. Nothing is cached.
+func LoadSVG(data []byte, width, height uint32) (TextureID, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return 0, err
+		}
+	}
+	if len(data) == 0 {
+		return 0, &ImageError{Code: CodeInvalidArgument, Message: "empty SVG data"}
+	}
+
+	result := fnLoadSVG(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)), width, height)
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return TextureID(result), nil
+}
+
+// CompressedFormat is a block-compressed texture format. Check GetGPULimits
+// for which families the GPU supports before uploading.
+type CompressedFormat uint32
+
+const (
+	CompressedBC1RGBASrgb  CompressedFormat = 0
+	CompressedBC3RGBASrgb  CompressedFormat = 1
+	CompressedBC4R         CompressedFormat = 2
+	CompressedBC5RG        CompressedFormat = 3
+	CompressedBC7RGBASrgb  CompressedFormat = 4
+	CompressedETC2RGBSrgb  CompressedFormat = 5
+	CompressedETC2RGBASrgb CompressedFormat = 6
+	CompressedASTC4x4Srgb  CompressedFormat = 7
+	CompressedASTC8x8Srgb  CompressedFormat = 8
+)
+
+// LoadCompressedTexture uploads BCn, ETC2 or ASTC data to the GPU without
+// decompressing it. data holds mipLevels levels, largest first, each tightly
+// packed; width and height are the level 0 size and must be multiples of the
+// block size. Fails with code -5 if the GPU doesn't support the format.
+func LoadCompressedTexture(format CompressedFormat, data []byte, width, height, mipLevels uint32) (TextureID, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return 0, err
+		}
+	}
+	if len(data) == 0 {
+		return 0, &ImageError{Code: CodeInvalidArgument, Message: "empty texture data"}
+	}
+
+	result := fnLoadCompressedTexture(uint32(format), uintptr(unsafe.Pointer(&data[0])), uint64(len(data)), width, height, mipLevels)
+	runtime.KeepAlive(data)
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return TextureID(result), nil
+}
+
+// RenderToTexture renders commands into a new texture, width x height
+// logical pixels, to cache an expensive sub-tree. Draw the result with
+// Image(id, x, y, width, height) and free it with UnloadImage. Without a
+// Clear command the texture starts transparent.
+func RenderToTexture(commands []RenderCommand, width, height uint32) (TextureID, error) {
+	if !initialized {
+		return 0, &ImageError{Code: CodeNotInitialized, Message: "not initialized"}
+	}
+
+	jsonBytes, err := json.Marshal(commands)
+	if err != nil {
+		return 0, err
+	}
+	jsonBytes = append(jsonBytes, 0)
+	result := fnRenderToTexture(uintptr(unsafe.Pointer(&jsonBytes[0])), width, height)
+	runtime.KeepAlive(jsonBytes)
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return TextureID(result), nil
 }
@@ -1994,24 +3500,311 @@ func UnloadImage(id TextureID) error {
 	}
 	result := fnUnloadImage(uint32(id))
 	if result < 0 {
-		return &ImageError{Code: int(result), Message: imageErrorMessage(int(result))}
+		return &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return nil
+}
+
+// AnimationID identifies an image loaded with LoadAnimatedImage
+type AnimationID uint32
+
+// LoadAnimatedImage loads an animated GIF or APNG as one texture per frame.
+// Still images load as a single frame.
+func LoadAnimatedImage(data []byte) (AnimationID, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return 0, err
+		}
+	}
+	if len(data) == 0 {
+		return 0, &ImageError{Code: CodeInvalidArgument, Message: "empty image data"}
+	}
+
+	result := fnLoadAnimatedImage(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)))
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return AnimationID(result), nil
+}
+
+// AnimationFrame returns the texture to draw elapsed after the animation
+// started. It loops as many times as the image asks, then holds the last
+// frame.
+func AnimationFrame(id AnimationID, elapsed time.Duration) (TextureID, error) {
+	if !initialized {
+		return 0, &ImageError{Code: CodeNotInitialized, Message: "not initialized"}
+	}
+	result := fnAnimationFrame(uint32(id), uint64(elapsed.Milliseconds()))
+	if result < 0 {
+		return 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
+	}
+	return TextureID(result), nil
+}
+
+// UnloadAnimation frees an animation's frame textures.
+func UnloadAnimation(id AnimationID) error {
+	if !initialized {
+		return nil
+	}
+	result := fnUnloadAnimation(uint32(id))
+	if result < 0 {
+		return &ImageError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func GetTextureSize(id TextureID) (uint32, uint32, error) {
 	if !initialized {
-		return 0, 0, &ImageError{Code: -2, Message: "not initialized"}
+		return 0, 0, &ImageError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var width, height uint32
 	result := fnGetTextureSize(uint32(id), uintptr(unsafe.Pointer(&width)), uintptr(unsafe.Pointer(&height)))
 	if result < 0 {
-		return 0, 0, &ImageError{Code: int(result), Message: imageErrorMessage(int(result))}
+		return 0, 0, &ImageError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return width, height, nil
 }
 
+// GPULimits describes what the GPU can do. Images wider or taller than
+// MaxTextureDimension fail to load, so scale them down first.
+type GPULimits struct {
+	MaxTextureDimension uint32
+	MaxBindGroups       uint32
+	TimestampQueries    bool
+	BCCompression       bool
+	ETC2Compression     bool
+	ASTCCompression     bool
+}
+
+// gpuLimitsC matches the C struct layout of GpuLimitsFFI
+type gpuLimitsC struct {
+	MaxTextureDimension uint32
+	MaxBindGroups       uint32
+	TimestampQueries    uint8
+	BCCompression       uint8
+	ETC2Compression     uint8
+	ASTCCompression     uint8
+}
+
+// GetGPULimits returns the GPU limits of the initialized backend.
+func GetGPULimits() (GPULimits, error) {
+	if !initialized {
+		return GPULimits{}, fmt.Errorf("not initialized")
+	}
+
+	var out gpuLimitsC
+	if result := fnGetGPULimits(uintptr(unsafe.Pointer(&out))); result < 0 {
+		return GPULimits{}, fmt.Errorf("failed to get GPU limits: %s", ErrorString(int(result)))
+	}
+	return GPULimits{
+		MaxTextureDimension: out.MaxTextureDimension,
+		MaxBindGroups:       out.MaxBindGroups,
+		TimestampQueries:    out.TimestampQueries != 0,
+		BCCompression:       out.BCCompression != 0,
+		ETC2Compression:     out.ETC2Compression != 0,
+		ASTCCompression:     out.ASTCCompression != 0,
+	}, nil
+}
+
+// RenderStats describes the work and timing of the last presented frame.
+type RenderStats struct {
+	DrawCalls uint32
+	Triangles uint32
+	// FrameTimeMs is GPU time of the frame's render pass when GPUTimed is
+	// set, otherwise wall-clock time of submitting and presenting the frame
+	FrameTimeMs float32
+	GPUTimed    bool
+}
+
+// GetRenderStats returns draw calls, triangles and frame time of the last
+// presented frame. GPU timing starts with the first call, so the first few
+// results after it are wall-clock times.
+func GetRenderStats() (RenderStats, error) {
+	if !initialized {
+		return RenderStats{}, fmt.Errorf("not initialized")
+	}
+
+	var stats RenderStats
+	result := fnGetRenderStats(
+		uintptr(unsafe.Pointer(&stats.DrawCalls)),
+		uintptr(unsafe.Pointer(&stats.Triangles)),
+		uintptr(unsafe.Pointer(&stats.FrameTimeMs)),
+		uintptr(unsafe.Pointer(&stats.GPUTimed)),
+	)
+	if result < 0 {
+		return RenderStats{}, fmt.Errorf("failed to get render stats: %s", ErrorString(int(result)))
+	}
+	return stats, nil
+}
+
+// ColorSpace is the color space a window's frames are presented in.
+type ColorSpace int32
+
+const (
+	// ColorSpaceSRGB clips colors outside sRGB.
+	ColorSpaceSRGB ColorSpace = 0
+	// ColorSpaceExtendedSRGB is extended-range linear sRGB, which reaches
+	// all of Display-P3.
+	ColorSpaceExtendedSRGB ColorSpace = 1
+)
+
+// SurfaceColorSpace returns the color space the window presents in:
+// extended sRGB when AppConfig.WideGamut was set and the display supports
+// it, sRGB otherwise.
+func SurfaceColorSpace() (ColorSpace, error) {
+	if !initialized {
+		return ColorSpaceSRGB, fmt.Errorf("not initialized")
+	}
+	result := fnSurfaceColorSpace()
+	if result < 0 {
+		return ColorSpaceSRGB, fmt.Errorf("failed to get surface color space: %s", ErrorString(int(result)))
+	}
+	return ColorSpace(result), nil
+}
+
+// CommandIssue is a problem ValidateCommands found in a frame.
+type CommandIssue struct {
+	// Index of the offending command
+	Index int `json:"index"`
+	// One of unbalanced_clip, unknown_texture, non_finite, empty_text,
+	// out_of_range, invalid_command
+	Kind    string `json:"kind"`
+	Message string `json:"message"`
+}
+
+// ValidateCommands checks a frame without rendering it, for tests and dev
+// tooling. It reports unbalanced clips, scroll views and transforms,
+// textures that aren't loaded, NaN/infinite coordinates, empty text and
+// out-of-range opacities. A clean frame returns no issues.
+func ValidateCommands(commands []RenderCommand) ([]CommandIssue, error) {
+	if !initialized {
+		return nil, fmt.Errorf("not initialized")
+	}
+
+	jsonBytes, err := json.Marshal(commands)
+	if err != nil {
+		return nil, err
+	}
+	jsonBytes = append(jsonBytes, 0)
+	ptr := fnValidateCommands(uintptr(unsafe.Pointer(&jsonBytes[0])))
+	runtime.KeepAlive(jsonBytes)
+	if ptr == 0 {
+		return nil, fmt.Errorf("failed to validate commands")
+	}
+	defer fnFreeString(ptr)
+
+	var issues []CommandIssue
+	if err := json.Unmarshal([]byte(goString(ptr)), &issues); err != nil {
+		return nil, err
+	}
+	return issues, nil
+}
+
+// RenderToBuffer renders commands without a window and returns width x
+// height RGBA pixels (sRGB, rows top to bottom). It needs no app or window
+// and falls back to a software renderer without a GPU, and animations are
+// frozen at their first frame, so the same commands give the same bytes on
+// a given machine - hash them for golden-image tests.
+func RenderToBuffer(commands []RenderCommand, width, height uint32) ([]byte, error) {
+	if !initialized {
+		if err := initLibrary(); err != nil {
+			return nil, err
+		}
+	}
+	if width == 0 || height == 0 {
+		return nil, fmt.Errorf("invalid size %dx%d", width, height)
+	}
+
+	jsonBytes, err := json.Marshal(commands)
+	if err != nil {
+		return nil, err
+	}
+	jsonBytes = append(jsonBytes, 0)
+	pixels := make([]byte, int(width)*int(height)*4)
+	result := fnRenderToBuffer(uintptr(unsafe.Pointer(&jsonBytes[0])), width, height, uintptr(unsafe.Pointer(&pixels[0])), uint64(len(pixels)))
+	runtime.KeepAlive(jsonBytes)
+	runtime.KeepAlive(pixels)
+	if result != 0 {
+		return nil, &AppError{Code: int(result)}
+	}
+	return pixels, nil
+}
+
+// LayoutNode is one node of a tree for ComputeLayout. Sizes are CSS
+// dimensions ("auto", "16px", "50%", "calc(100% - 16px)"); empty means auto.
+// Enum fields use the engine's names ("Flex", "Row", "SpaceBetween", ...) and
+// default to a row flex container that stretches its items.
+type LayoutNode struct {
+	// ID is echoed back in the node's LayoutRect
+	ID        uint64 `json:"id"`
+	Algorithm string `json:"algorithm,omitempty"` // Flex, Block, Absolute
+
+	Width     string `json:"width,omitempty"`
+	Height    string `json:"height,omitempty"`
+	MinWidth  string `json:"min_width,omitempty"`
+	MinHeight string `json:"min_height,omitempty"`
+	MaxWidth  string `json:"max_width,omitempty"`
+	MaxHeight string `json:"max_height,omitempty"`
+
+	PaddingTop    float32 `json:"padding_top,omitempty"`
+	PaddingRight  float32 `json:"padding_right,omitempty"`
+	PaddingBottom float32 `json:"padding_bottom,omitempty"`
+	PaddingLeft   float32 `json:"padding_left,omitempty"`
+	MarginTop     float32 `json:"margin_top,omitempty"`
+	MarginRight   float32 `json:"margin_right,omitempty"`
+	MarginBottom  float32 `json:"margin_bottom,omitempty"`
+	MarginLeft    float32 `json:"margin_left,omitempty"`
+
+	FlexDirection  string   `json:"flex_direction,omitempty"`  // Row, RowReverse, Column, ColumnReverse
+	FlexWrap       string   `json:"flex_wrap,omitempty"`       // NoWrap, Wrap, WrapReverse
+	JustifyContent string   `json:"justify_content,omitempty"` // FlexStart, FlexEnd, Center, SpaceBetween, SpaceAround, SpaceEvenly
+	AlignItems     string   `json:"align_items,omitempty"`     // FlexStart, FlexEnd, Center, Stretch, Baseline
+	FlexGrow       float32  `json:"flex_grow,omitempty"`
+	FlexShrink     *float32 `json:"flex_shrink,omitempty"` // nil means 1
+	FlexBasis      string   `json:"flex_basis,omitempty"`
+	Gap            float32  `json:"gap,omitempty"`
+
+	Children []LayoutNode `json:"children,omitempty"`
+}
+
+// LayoutRect is where ComputeLayout put a node, relative to the area's
+// top-left corner.
+type LayoutRect struct {
+	ID     uint64  `json:"id"`
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// ComputeLayout lays out a tree in a width x height area with the engine's
+// flexbox, block and absolute layout, returning every node's rect in
+// depth-first order.
+func ComputeLayout(root LayoutNode, width, height float32) ([]LayoutRect, error) {
+	if !initialized {
+		return nil, fmt.Errorf("not initialized")
+	}
+
+	jsonBytes, err := json.Marshal(root)
+	if err != nil {
+		return nil, err
+	}
+	jsonBytes = append(jsonBytes, 0)
+	ptr := fnLayoutCompute(uintptr(unsafe.Pointer(&jsonBytes[0])), width, height)
+	runtime.KeepAlive(jsonBytes)
+	if ptr == 0 {
+		return nil, fmt.Errorf("failed to compute layout")
+	}
+	defer fnFreeString(ptr)
+
+	var rects []LayoutRect
+	if err := json.Unmarshal([]byte(goString(ptr)), &rects); err != nil {
+		return nil, err
+	}
+	return rects, nil
+}
+
 // ============================================================================
 // Image Command Builders
 // ============================================================================
@@ -2052,6 +3845,18 @@ func ImageWithSourceRectAndCornerRadii(textureID TextureID, x, y, width, height
 	}
 }
 
+// ImageWithOpacity draws an image at an opacity of its own (0-1), multiplied
+// with SetOpacity. For a cross-fade, draw the outgoing image at 1-t and the
+// incoming one at t.
+func ImageWithOpacity(textureID TextureID, x, y, width, height, opacity float32) RenderCommand {
+	return RenderCommand{
+		DrawImage: &DrawImageCmd{
+			X: x, Y: y, Width: width, Height: height,
+			TextureID: uint32(textureID), Opacity: &opacity,
+		},
+	}
+}
+
 func Sprite(textureID TextureID, x, y, width, height float32, spriteX, spriteY, cols, rows int) RenderCommand {
 	spriteWidth := 1.0 / float32(cols)
 	spriteHeight := 1.0 / float32(rows)
@@ -2067,15 +3872,53 @@ func Sprite(textureID TextureID, x, y, width, height float32, spriteX, spriteY,
 	}
 }
 
+// ImageRegion draws the srcWidth x srcHeight pixel region at (srcX, srcY) of
+// a texture, such as one cell of a sprite atlas
+func ImageRegion(textureID TextureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight float32) RenderCommand {
+	return RenderCommand{
+		DrawImage: &DrawImageCmd{
+			X: x, Y: y, Width: width, Height: height,
+			TextureID: uint32(textureID),
+			Src:       &RectBounds{X: srcX, Y: srcY, Width: srcWidth, Height: srcHeight},
+		},
+	}
+}
+
+// ImageRegionTinted is ImageRegion with each texel multiplied by tint
+// (0xRRGGBBAA), e.g. to color a white monochrome icon
+func ImageRegionTinted(textureID TextureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight float32, tint uint32) RenderCommand {
+	cmd := ImageRegion(textureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight)
+	cmd.DrawImage.Tint = &tint
+	return cmd
+}
+
+// NinePatch draws a texture stretched over a rect with its insets
+// ([top, right, bottom, left] texture pixels) kept unscaled, e.g. a chat
+// bubble or button background from one small image
+func NinePatch(textureID TextureID, x, y, width, height float32, insets [4]float32) RenderCommand {
+	return RenderCommand{
+		DrawNinePatch: &DrawNinePatchCmd{
+			TextureID: uint32(textureID),
+			DestRect:  RectBounds{X: x, Y: y, Width: width, Height: height},
+			Insets:    insets,
+		},
+	}
+}
+
 // ============================================================================
 // Text Measurement
 // ============================================================================
 
+// TextMeasurement holds a string's width and its resolved font's vertical
+// metrics at the requested size, in logical pixels. Ascent and Descent are
+// what DrawText places lines with, so to share a baseline across font sizes
+// draw each string at y - Ascent (or at y with VerticalAlignBaseline).
 type TextMeasurement struct {
 	Width   float32
 	Height  float32
-	Ascent  float32
-	Descent float32
+	Ascent  float32 // Baseline to top of the font
+	Descent float32 // Baseline to bottom of the font, positive
+	LineGap float32 // Extra spacing the font recommends between lines
 }
 
 func MeasureText(text string, fontName string, fontSize float32) TextMeasurement {
@@ -2104,6 +3947,7 @@ func MeasureText(text string, fontName string, fontSize float32) TextMeasurement
 		Height:  resultC.Height,
 		Ascent:  resultC.Ascent,
 		Descent: resultC.Descent,
+		LineGap: resultC.LineGap,
 	}
 }
 
@@ -2208,9 +4052,76 @@ func MeasureTextToCursor(text string, charIndex int, fontName string, fontSize f
 	)
 
 	runtime.KeepAlive(textBytes)
-	runtime.KeepAlive(fontBytes)
+	runtime.KeepAlive(fontBytes)
+
+	return result
+}
+
+// TextMoveCaret moves a caret (a rune index into text) by delta grapheme
+// clusters, so emoji ZWJ sequences and skin-tone modifiers are stepped over
+// as one character. Falls back to moving by runes if the engine isn't loaded.
+func TextMoveCaret(text string, charIndex int, delta int) int {
+	if !initialized || fnTextMoveCaret == nil {
+		return clampCaret(text, charIndex+delta)
+	}
+
+	textBytes := append([]byte(text), 0)
+	result := fnTextMoveCaret(uintptr(unsafe.Pointer(&textBytes[0])), int32(charIndex), int32(delta))
+	runtime.KeepAlive(textBytes)
+
+	if result < 0 {
+		return clampCaret(text, charIndex+delta)
+	}
+	return int(result)
+}
+
+// TextLineBreaks reports where text may wrap, using the same rules as the
+// renderer: breakable[i] is true if a line may start at rune i. CJK text can
+// break between ideographs, a no-break space never breaks, and URLs only
+// break if they must. Falls back to breaking after spaces if the engine
+// isn't loaded.
+func TextLineBreaks(text string) []bool {
+	runes := []rune(text)
+	if !initialized || fnTextLineBreaks == nil || len(runes) == 0 {
+		return spaceLineBreaks(runes)
+	}
+
+	textBytes := append([]byte(text), 0)
+	indices := make([]uint32, len(runes))
+	n := fnTextLineBreaks(uintptr(unsafe.Pointer(&textBytes[0])), 0, uintptr(unsafe.Pointer(&indices[0])), uint64(len(indices)))
+	runtime.KeepAlive(textBytes)
+	if n < 0 {
+		return spaceLineBreaks(runes)
+	}
 
-	return result
+	breakable := make([]bool, len(runes)+1)
+	for _, i := range indices[:min(int(n), len(indices))] {
+		if int(i) <= len(runes) {
+			breakable[i] = true
+		}
+	}
+	return breakable
+}
+
+// spaceLineBreaks allows a break after every space
+func spaceLineBreaks(runes []rune) []bool {
+	breakable := make([]bool, len(runes)+1)
+	for i, r := range runes {
+		if unicode.IsSpace(r) {
+			breakable[i+1] = true
+		}
+	}
+	return breakable
+}
+
+func clampCaret(text string, pos int) int {
+	if pos < 0 {
+		return 0
+	}
+	if n := len([]rune(text)); pos > n {
+		return n
+	}
+	return pos
 }
 
 func MeasureTextWithFont(text string, font FontDescriptor) float32 {
@@ -2241,7 +4152,7 @@ func MeasureTextWithFont(text string, font FontDescriptor) float32 {
 	return result
 }
 
-// MeasureTextMetricsWithFont measures text and returns full metrics (width, height, ascent, descent).
+// MeasureTextMetricsWithFont measures text and returns full metrics (width, height, ascent, descent, line gap).
 // This supports both system fonts and bundled fonts via the FontDescriptor.
 // Note: text can be empty - in that case width will be 0 but height will reflect font metrics.
 func MeasureTextMetricsWithFont(text string, font FontDescriptor) TextMeasurement {
@@ -2273,6 +4184,7 @@ func MeasureTextMetricsWithFont(text string, font FontDescriptor) TextMeasuremen
 			Height:  result.Height,
 			Ascent:  result.Ascent,
 			Descent: result.Descent,
+			LineGap: result.LineGap,
 		}
 	}
 
@@ -2291,6 +4203,7 @@ func MeasureTextMetricsWithFont(text string, font FontDescriptor) TextMeasuremen
 		Height:  resultC.Height,
 		Ascent:  resultC.Ascent,
 		Descent: resultC.Descent,
+		LineGap: resultC.LineGap,
 	}
 }
 
@@ -2371,6 +4284,93 @@ func ClipboardSetString(text string) {
 	runtime.KeepAlive(textBytes)
 }
 
+// ClipboardGetImage returns the image on the clipboard as straight-alpha
+// RGBA pixels, rows top to bottom. When the clipboard holds no image the
+// error is an *AppError with Code CodeNotFound.
+func ClipboardGetImage() (rgba []byte, width, height uint32, err error) {
+	if !initialized || fnClipboardGetImage == nil {
+		return nil, 0, 0, fmt.Errorf("clipboard images not available")
+	}
+	var ptr uintptr
+	var length uint64
+	result := fnClipboardGetImage(
+		uintptr(unsafe.Pointer(&ptr)),
+		uintptr(unsafe.Pointer(&length)),
+		uintptr(unsafe.Pointer(&width)),
+		uintptr(unsafe.Pointer(&height)),
+	)
+	if result != 0 {
+		return nil, 0, 0, &AppError{Code: int(result)}
+	}
+	rgba = make([]byte, length)
+	copy(rgba, unsafe.Slice((*byte)(unsafe.Pointer(ptr)), length))
+	fnClipboardImageFree(ptr, length)
+	return rgba, width, height, nil
+}
+
+// ClipboardSetImage replaces the clipboard contents with an image of
+// straight-alpha RGBA pixels (len(rgba) must be width*height*4)
+func ClipboardSetImage(rgba []byte, width, height uint32) error {
+	if !initialized || fnClipboardSetImage == nil {
+		return fmt.Errorf("clipboard images not available")
+	}
+	if width == 0 || height == 0 || len(rgba) != int(width)*int(height)*4 {
+		return fmt.Errorf("%d bytes is not a %dx%d RGBA image", len(rgba), width, height)
+	}
+	result := fnClipboardSetImage(uintptr(unsafe.Pointer(&rgba[0])), uint64(len(rgba)), width, height)
+	runtime.KeepAlive(rgba)
+	if result != 0 {
+		return &AppError{Code: int(result)}
+	}
+	return nil
+}
+
+// ============================================================================
+// Notification Functions
+// ============================================================================
+
+// ShowNotification shows a system notification and returns its ID for
+// DismissNotification. iconPath is an image file shown with it ("" for
+// none); Windows needs an .ico file. On macOS and iOS the app must run
+// from a bundle.
+func ShowNotification(title, body, iconPath string) (uint32, error) {
+	if !initialized || fnShowNotification == nil {
+		return 0, fmt.Errorf("notifications not available")
+	}
+	titleBytes := append([]byte(title), 0)
+	bodyBytes := append([]byte(body), 0)
+	var iconPtr uintptr
+	var iconBytes []byte
+	if iconPath != "" {
+		iconBytes = append([]byte(iconPath), 0)
+		iconPtr = uintptr(unsafe.Pointer(&iconBytes[0]))
+	}
+	result := fnShowNotification(
+		uintptr(unsafe.Pointer(&titleBytes[0])),
+		uintptr(unsafe.Pointer(&bodyBytes[0])),
+		iconPtr,
+	)
+	runtime.KeepAlive(titleBytes)
+	runtime.KeepAlive(bodyBytes)
+	runtime.KeepAlive(iconBytes)
+	if result < 0 {
+		return 0, &AppError{Code: int(result)}
+	}
+	return uint32(result), nil
+}
+
+// DismissNotification removes a notification shown by ShowNotification.
+// Dismissing one that is already gone is not an error.
+func DismissNotification(id uint32) error {
+	if !initialized || fnDismissNotification == nil {
+		return fmt.Errorf("notifications not available")
+	}
+	if result := fnDismissNotification(id); result != 0 {
+		return &AppError{Code: int(result)}
+	}
+	return nil
+}
+
 // ============================================================================
 // Keyboard Functions (iOS)
 // ============================================================================
@@ -2555,6 +4555,138 @@ func SaveFileDialog(title, directory string, filters []FileFilter) (string, bool
 	return path, path != ""
 }
 
+// fileDialogCString returns s as a C string, or a null pointer for ""
+func fileDialogCString(s string) ([]byte, uintptr) {
+	if s == "" {
+		return nil, 0
+	}
+	b := append([]byte(s), 0)
+	return b, uintptr(unsafe.Pointer(&b[0]))
+}
+
+// fileDialogExtensions joins the extensions of all filters with commas
+func fileDialogExtensions(filters []FileFilter) string {
+	var exts []string
+	for _, f := range filters {
+		exts = append(exts, f.Extensions...)
+	}
+	return strings.Join(exts, ",")
+}
+
+var (
+	fileDialogMu          sync.Mutex
+	fileDialogHandlers    map[uint32]func(result uintptr)
+	nextFileDialogID      uint32
+	fileDialogCallbackPtr uintptr
+)
+
+// fileDialogCallback hands an async dialog's result to the handler it was
+// opened with, then frees it
+func fileDialogCallback(requestID uint32, result uintptr) uintptr {
+	fileDialogMu.Lock()
+	handler := fileDialogHandlers[requestID]
+	delete(fileDialogHandlers, requestID)
+	fileDialogMu.Unlock()
+
+	if handler != nil {
+		handler(result)
+	}
+	if result != 0 {
+		fnFileDialogResultFree(result)
+	}
+	return 0
+}
+
+// startFileDialog registers handler under a new request ID and calls open
+// with it. If the dialog can't be shown, handler gets a null result.
+func startFileDialog(handler func(result uintptr), open func(requestID uint32, callback uintptr) int32) {
+	fileDialogMu.Lock()
+	if fileDialogCallbackPtr == 0 {
+		fileDialogCallbackPtr = purego.NewCallback(fileDialogCallback)
+		fileDialogHandlers = map[uint32]func(result uintptr){}
+	}
+	nextFileDialogID++
+	requestID := nextFileDialogID
+	fileDialogHandlers[requestID] = handler
+	fileDialogMu.Unlock()
+
+	if open(requestID, fileDialogCallbackPtr) < 0 {
+		fileDialogMu.Lock()
+		delete(fileDialogHandlers, requestID)
+		fileDialogMu.Unlock()
+		handler(0)
+	}
+}
+
+// OpenFileDialogAsync shows an open dialog without blocking and calls done
+// with the selected paths, or ok=false if it was cancelled. On Android it
+// shows the system document picker, the paths are content:// URIs, and done
+// runs on the main thread once the picker closes. Elsewhere the dialog is
+// modal and done runs before this returns.
+func OpenFileDialogAsync(title, directory string, filters []FileFilter, multiple bool, done func(paths []string, ok bool)) {
+	if !initialized || fnFileDialogOpenAsync == nil {
+		done(nil, false)
+		return
+	}
+
+	titleBytes, titlePtr := fileDialogCString(title)
+	dirBytes, dirPtr := fileDialogCString(directory)
+	filtersBytes, filtersPtr := fileDialogCString(fileDialogExtensions(filters))
+	multipleInt := int32(0)
+	if multiple {
+		multipleInt = 1
+	}
+
+	startFileDialog(func(result uintptr) {
+		if result == 0 {
+			done(nil, false)
+			return
+		}
+		var paths []string
+		if err := json.Unmarshal([]byte(goString(result)), &paths); err != nil {
+			done(nil, false)
+			return
+		}
+		done(paths, len(paths) > 0)
+	}, func(requestID uint32, callback uintptr) int32 {
+		return fnFileDialogOpenAsync(titlePtr, dirPtr, filtersPtr, multipleInt, requestID, callback)
+	})
+
+	runtime.KeepAlive(titleBytes)
+	runtime.KeepAlive(dirBytes)
+	runtime.KeepAlive(filtersBytes)
+}
+
+// SaveFileDialogAsync shows a save dialog without blocking and calls done
+// with the chosen path, or ok=false if it was cancelled. On Android the
+// path is the content:// URI of the newly created document; see
+// OpenFileDialogAsync for when done runs.
+func SaveFileDialogAsync(title, directory string, filters []FileFilter, done func(path string, ok bool)) {
+	if !initialized || fnFileDialogSaveAsync == nil {
+		done("", false)
+		return
+	}
+
+	titleBytes, titlePtr := fileDialogCString(title)
+	dirBytes, dirPtr := fileDialogCString(directory)
+	filtersBytes, filtersPtr := fileDialogCString(fileDialogExtensions(filters))
+
+	startFileDialog(func(result uintptr) {
+		if result == 0 {
+			done("", false)
+			return
+		}
+		path := goString(result)
+		done(path, path != "")
+	}, func(requestID uint32, callback uintptr) int32 {
+		return fnFileDialogSaveAsync(titlePtr, dirPtr, filtersPtr, requestID, callback)
+	})
+
+	runtime.KeepAlive(titleBytes)
+	runtime.KeepAlive(dirBytes)
+	runtime.KeepAlive(filtersBytes)
+}
+
 // ============================================================================
 // Tray Icon Functions
 // ============================================================================
@@ -2575,7 +4707,7 @@ func TrayIconCreate() error {
 	}
 	result := fnTrayIconCreate()
 	if result < 0 {
-		return fmt.Errorf("failed to create tray icon: %d", result)
+		return fmt.Errorf("failed to create tray icon: %s", ErrorString(int(result)))
 	}
 	return nil
 }
@@ -2596,7 +4728,7 @@ func TrayIconSetIconFile(path string) error {
 	runtime.KeepAlive(pathBytes)
 
 	if result < 0 {
-		return fmt.Errorf("failed to set tray icon: %d", result)
+		return fmt.Errorf("failed to set tray icon: %s", ErrorString(int(result)))
 	}
 	return nil
 }
@@ -2611,7 +4743,7 @@ func TrayIconSetIconData(data []byte) error {
 
 	result := fnTrayIconSetIconData(uintptr(unsafe.Pointer(&data[0])), uint64(len(data)))
 	if result < 0 {
-		return fmt.Errorf("failed to set tray icon: %d", result)
+		return fmt.Errorf("failed to set tray icon: %s", ErrorString(int(result)))
 	}
 	return nil
 }
@@ -2789,19 +4921,6 @@ func (e *AudioError) Error() string {
 	return e.Message
 }
 
-func audioErrorMessage(code int) string {
-	switch code {
-	case -1:
-		return "invalid parameters"
-	case -2:
-		return "player not found"
-	case -3:
-		return "audio operation failed"
-	default:
-		return "unknown audio error"
-	}
-}
-
 func AudioCreate() AudioPlayerID {
 	if !initialized {
 		if err := initLibrary(); err != nil {
@@ -2820,94 +4939,94 @@ func AudioDestroy(id AudioPlayerID) {
 
 func AudioLoadURL(id AudioPlayerID, url string) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	urlBytes := append([]byte(url), 0)
 	result := fnAudioLoadURL(uint32(id), uintptr(unsafe.Pointer(&urlBytes[0])))
 	runtime.KeepAlive(urlBytes)
 
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioLoadFile(id AudioPlayerID, path string) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	pathBytes := append([]byte(path), 0)
 	result := fnAudioLoadFile(uint32(id), uintptr(unsafe.Pointer(&pathBytes[0])))
 	runtime.KeepAlive(pathBytes)
 
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioPlay(id AudioPlayerID) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioPlay(uint32(id))
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioPause(id AudioPlayerID) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioPause(uint32(id))
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioStop(id AudioPlayerID) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioStop(uint32(id))
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioSeek(id AudioPlayerID, timestampMs uint64) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioSeek(uint32(id), timestampMs)
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioSetLooping(id AudioPlayerID, looping bool) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioSetLooping(uint32(id), looping)
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioSetVolume(id AudioPlayerID, volume float32) error {
 	if !initialized {
-		return &AudioError{Code: -2, Message: "not initialized"}
+		return &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioSetVolume(uint32(id), volume)
 	if result < 0 {
-		return &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -2932,7 +5051,7 @@ func AudioGetTime(id AudioPlayerID) uint64 {
 
 func AudioGetInfo(id AudioPlayerID) (*AudioInfo, error) {
 	if !initialized {
-		return nil, &AudioError{Code: -2, Message: "not initialized"}
+		return nil, &AudioError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var durationMs uint64
@@ -2944,7 +5063,7 @@ func AudioGetInfo(id AudioPlayerID) (*AudioInfo, error) {
 		uintptr(unsafe.Pointer(&channels)))
 
 	if result < 0 {
-		return nil, &AudioError{Code: int(result), Message: audioErrorMessage(int(result))}
+		return nil, &AudioError{Code: int(result), Message: ErrorString(int(result))}
 	}
 
 	return &AudioInfo{
@@ -2984,12 +5103,13 @@ type VideoPlayerID uint32
 type VideoState int32
 
 const (
-	VideoStateIdle    VideoState = 0
-	VideoStateLoading VideoState = 1
-	VideoStatePlaying VideoState = 2
-	VideoStatePaused  VideoState = 3
-	VideoStateEnded   VideoState = 4
-	VideoStateError   VideoState = 5
+	VideoStateIdle      VideoState = 0
+	VideoStateLoading   VideoState = 1
+	VideoStatePlaying   VideoState = 2
+	VideoStatePaused    VideoState = 3
+	VideoStateEnded     VideoState = 4
+	VideoStateError     VideoState = 5
+	VideoStateBuffering VideoState = 6 // stalled mid-playback waiting for data
 )
 
 func (s VideoState) String() string {
@@ -3006,6 +5126,8 @@ func (s VideoState) String() string {
 		return "ended"
 	case VideoStateError:
 		return "error"
+	case VideoStateBuffering:
+		return "buffering"
 	default:
 		return "unknown"
 	}
@@ -3026,23 +5148,6 @@ func (e *VideoError) Error() string {
 	return e.Message
 }
 
-func videoErrorMessage(code int) string {
-	switch code {
-	case -1:
-		return "invalid parameters"
-	case -2:
-		return "player not found"
-	case -3:
-		return "video operation failed"
-	case -4:
-		return "failed to create texture"
-	case -5:
-		return "failed to update texture"
-	default:
-		return "unknown video error"
-	}
-}
-
 func VideoCreate() VideoPlayerID {
 	if !initialized {
 		if err := initLibrary(); err != nil {
@@ -3061,122 +5166,122 @@ func VideoDestroy(id VideoPlayerID) {
 
 func VideoLoadURL(id VideoPlayerID, url string) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	urlBytes := append([]byte(url), 0)
 	result := fnVideoLoadURL(uint32(id), uintptr(unsafe.Pointer(&urlBytes[0])))
 	runtime.KeepAlive(urlBytes)
 
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoLoadFile(id VideoPlayerID, path string) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	pathBytes := append([]byte(path), 0)
 	result := fnVideoLoadFile(uint32(id), uintptr(unsafe.Pointer(&pathBytes[0])))
 	runtime.KeepAlive(pathBytes)
 
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoInitStream(id VideoPlayerID, width, height uint32) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoInitStream(uint32(id), width, height)
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoPushFrame(id VideoPlayerID, width, height uint32, data []byte, timestampMs uint64) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	if len(data) == 0 {
-		return &VideoError{Code: -1, Message: "empty frame data"}
+		return &VideoError{Code: CodeInvalidArgument, Message: "empty frame data"}
 	}
 
 	result := fnVideoPushFrame(uint32(id), width, height,
 		uintptr(unsafe.Pointer(&data[0])), uint64(len(data)), timestampMs)
 
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoPlay(id VideoPlayerID) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoPlay(uint32(id))
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoPause(id VideoPlayerID) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoPause(uint32(id))
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoSeek(id VideoPlayerID, timestampMs uint64) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoSeek(uint32(id), timestampMs)
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoSetLooping(id VideoPlayerID, looping bool) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoSetLooping(uint32(id), looping)
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoSetMuted(id VideoPlayerID, muted bool) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoSetMuted(uint32(id), muted)
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoSetVolume(id VideoPlayerID, volume float32) error {
 	if !initialized {
-		return &VideoError{Code: -2, Message: "not initialized"}
+		return &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoSetVolume(uint32(id), volume)
 	if result < 0 {
-		return &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -3199,9 +5304,18 @@ func VideoGetTime(id VideoPlayerID) uint64 {
 	return fnVideoGetTime(uint32(id))
 }
 
+// VideoGetBufferedMs returns the position in milliseconds up to which the
+// video is downloaded, for drawing a buffered bar against the duration.
+func VideoGetBufferedMs(id VideoPlayerID) uint64 {
+	if !initialized {
+		return 0
+	}
+	return fnVideoGetBufferedMs(uint32(id))
+}
+
 func VideoGetInfo(id VideoPlayerID) (*VideoInfo, error) {
 	if !initialized {
-		return nil, &VideoError{Code: -2, Message: "not initialized"}
+		return nil, &VideoError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var width, height uint32
@@ -3213,7 +5327,7 @@ func VideoGetInfo(id VideoPlayerID) (*VideoInfo, error) {
 		uintptr(unsafe.Pointer(&durationMs)))
 
 	if result < 0 {
-		return nil, &VideoError{Code: int(result), Message: videoErrorMessage(int(result))}
+		return nil, &VideoError{Code: int(result), Message: ErrorString(int(result))}
 	}
 
 	return &VideoInfo{
@@ -3273,19 +5387,6 @@ func (e *AudioInputError) Error() string {
 	return e.Message
 }
 
-func audioInputErrorMessage(code int) string {
-	switch code {
-	case -1:
-		return "invalid parameter"
-	case -2:
-		return "input not found"
-	case -3:
-		return "input operation failed"
-	default:
-		return "unknown audio input error"
-	}
-}
-
 func AudioInputCreate() AudioInputID {
 	if !initialized {
 		if err := initLibrary(); err != nil {
@@ -3304,14 +5405,14 @@ func AudioInputDestroy(id AudioInputID) {
 
 func AudioInputRequestPermission(id AudioInputID) error {
 	if !initialized {
-		return &AudioInputError{Code: -2, Message: "not initialized"}
+		return &AudioInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioInputRequestPermission(uint32(id))
 	if result != 0 {
 		if result == 1 {
 			return &AudioInputError{Code: 1, Message: "microphone permission required"}
 		}
-		return &AudioInputError{Code: int(result), Message: audioInputErrorMessage(int(result))}
+		return &AudioInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -3325,12 +5426,12 @@ func AudioInputHasPermission(id AudioInputID) bool {
 
 func AudioInputListDevices(id AudioInputID) ([]AudioInputDevice, error) {
 	if !initialized {
-		return nil, &AudioInputError{Code: -2, Message: "not initialized"}
+		return nil, &AudioInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	ptr := fnAudioInputListDevices(uint32(id))
 	if ptr == 0 {
-		return nil, &AudioInputError{Code: -1, Message: "failed to list devices"}
+		return nil, &AudioInputError{Code: CodeOperationFailed, Message: "failed to list devices"}
 	}
 	defer fnFreeString(ptr)
 
@@ -3344,7 +5445,7 @@ func AudioInputListDevices(id AudioInputID) ([]AudioInputDevice, error) {
 
 func AudioInputOpen(id AudioInputID, deviceID string, sampleRate, channels uint32) error {
 	if !initialized {
-		return &AudioInputError{Code: -2, Message: "not initialized"}
+		return &AudioInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var devicePtr uintptr
@@ -3358,29 +5459,29 @@ func AudioInputOpen(id AudioInputID, deviceID string, sampleRate, channels uint3
 	runtime.KeepAlive(deviceBytes)
 
 	if result < 0 {
-		return &AudioInputError{Code: int(result), Message: audioInputErrorMessage(int(result))}
+		return &AudioInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioInputStart(id AudioInputID) error {
 	if !initialized {
-		return &AudioInputError{Code: -2, Message: "not initialized"}
+		return &AudioInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioInputStart(uint32(id))
 	if result < 0 {
-		return &AudioInputError{Code: int(result), Message: audioInputErrorMessage(int(result))}
+		return &AudioInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func AudioInputStop(id AudioInputID) error {
 	if !initialized {
-		return &AudioInputError{Code: -2, Message: "not initialized"}
+		return &AudioInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnAudioInputStop(uint32(id))
 	if result < 0 {
-		return &AudioInputError{Code: int(result), Message: audioInputErrorMessage(int(result))}
+		return &AudioInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -3453,19 +5554,6 @@ func (e *VideoInputError) Error() string {
 	return e.Message
 }
 
-func videoInputErrorMessage(code int) string {
-	switch code {
-	case -1:
-		return "invalid parameter"
-	case -2:
-		return "input not found"
-	case -3:
-		return "input operation failed"
-	default:
-		return "unknown video input error"
-	}
-}
-
 func VideoInputCreate() VideoInputID {
 	if !initialized {
 		if err := initLibrary(); err != nil {
@@ -3484,14 +5572,14 @@ func VideoInputDestroy(id VideoInputID) {
 
 func VideoInputRequestPermission(id VideoInputID) error {
 	if !initialized {
-		return &VideoInputError{Code: -2, Message: "not initialized"}
+		return &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoInputRequestPermission(uint32(id))
 	if result != 0 {
 		if result == 1 {
 			return &VideoInputError{Code: 1, Message: "camera permission required"}
 		}
-		return &VideoInputError{Code: int(result), Message: videoInputErrorMessage(int(result))}
+		return &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -3505,12 +5593,12 @@ func VideoInputHasPermission(id VideoInputID) bool {
 
 func VideoInputListDevices(id VideoInputID) ([]VideoInputDevice, error) {
 	if !initialized {
-		return nil, &VideoInputError{Code: -2, Message: "not initialized"}
+		return nil, &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	ptr := fnVideoInputListDevices(uint32(id))
 	if ptr == 0 {
-		return nil, &VideoInputError{Code: -1, Message: "failed to list devices"}
+		return nil, &VideoInputError{Code: CodeOperationFailed, Message: "failed to list devices"}
 	}
 	defer fnFreeString(ptr)
 
@@ -3524,7 +5612,7 @@ func VideoInputListDevices(id VideoInputID) ([]VideoInputDevice, error) {
 
 func VideoInputOpen(id VideoInputID, deviceID string, width, height, frameRate uint32) error {
 	if !initialized {
-		return &VideoInputError{Code: -2, Message: "not initialized"}
+		return &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var devicePtr uintptr
@@ -3538,29 +5626,29 @@ func VideoInputOpen(id VideoInputID, deviceID string, width, height, frameRate u
 	runtime.KeepAlive(deviceBytes)
 
 	if result < 0 {
-		return &VideoInputError{Code: int(result), Message: videoInputErrorMessage(int(result))}
+		return &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoInputStart(id VideoInputID) error {
 	if !initialized {
-		return &VideoInputError{Code: -2, Message: "not initialized"}
+		return &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoInputStart(uint32(id))
 	if result < 0 {
-		return &VideoInputError{Code: int(result), Message: videoInputErrorMessage(int(result))}
+		return &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
 
 func VideoInputStop(id VideoInputID) error {
 	if !initialized {
-		return &VideoInputError{Code: -2, Message: "not initialized"}
+		return &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 	result := fnVideoInputStop(uint32(id))
 	if result < 0 {
-		return &VideoInputError{Code: int(result), Message: videoInputErrorMessage(int(result))}
+		return &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return nil
 }
@@ -3585,7 +5673,7 @@ func VideoInputGetState(id VideoInputID) VideoInputState {
 
 func VideoInputGetDimensions(id VideoInputID) (uint32, uint32, error) {
 	if !initialized {
-		return 0, 0, &VideoInputError{Code: -2, Message: "not initialized"}
+		return 0, 0, &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	var width, height uint32
@@ -3594,30 +5682,19 @@ func VideoInputGetDimensions(id VideoInputID) (uint32, uint32, error) {
 		uintptr(unsafe.Pointer(&height)))
 
 	if result < 0 {
-		return 0, 0, &VideoInputError{Code: int(result), Message: videoInputErrorMessage(int(result))}
+		return 0, 0, &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return width, height, nil
 }
 
 func VideoInputGetFrameTexture(id VideoInputID, existingTextureID uint32) (uint32, error) {
 	if !initialized {
-		return 0, &VideoInputError{Code: -2, Message: "not initialized"}
+		return 0, &VideoInputError{Code: CodeNotInitialized, Message: "not initialized"}
 	}
 
 	result := fnVideoInputGetFrameTexture(uint32(id), existingTextureID)
 	if result < 0 {
-		switch result {
-		case -1:
-			return 0, &VideoInputError{Code: -1, Message: "backend not initialized"}
-		case -2:
-			return 0, &VideoInputError{Code: -2, Message: "video input not found"}
-		case -3:
-			return 0, &VideoInputError{Code: -3, Message: "no frame available"}
-		case -4:
-			return 0, &VideoInputError{Code: -4, Message: "texture upload failed"}
-		default:
-			return 0, &VideoInputError{Code: int(result), Message: "unknown error"}
-		}
+		return 0, &VideoInputError{Code: int(result), Message: ErrorString(int(result))}
 	}
 	return uint32(result), nil
 }
@@ -3626,7 +5703,15 @@ func VideoInputGetFrameTexture(id VideoInputID, existingTextureID uint32) (uint3
 // Binary Render Command Serialization
 // ============================================================================
 
+// SerializeRenderCommands encodes commands in the engine's binary frame
+// format, documented at decode_render_commands in engine/src/ffi.rs. Keep the
+// two in sync; the engine also accepts these bytes directly through
+// centered_backend_render_frame_binary.
+//
+// Texts go in a string table ahead of the commands, each distinct text once,
+// and commands refer to them by index.
 func SerializeRenderCommands(commands []RenderCommand) []byte {
+	texts := stringTable{indices: make(map[string]uint32)}
 	buf := make([]byte, 0, len(commands)*64+4)
 	buf = appendU32(buf, uint32(len(commands)))
 
@@ -3654,6 +5739,15 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 			if cmd.DrawRect.Gradient != nil {
 				flags |= 0x02
 			}
+			if cmd.DrawRect.Pivot != nil {
+				flags |= 0x04
+			}
+			if cmd.DrawRect.InnerShadow != nil {
+				flags |= 0x08
+			}
+			if cmd.DrawRect.Shadow != nil {
+				flags |= 0x10
+			}
 			buf = append(buf, flags)
 
 			if cmd.DrawRect.Border != nil {
@@ -3661,154 +5755,53 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 				buf = appendU32(buf, cmd.DrawRect.Border.Color)
 				var style byte
 				switch cmd.DrawRect.Border.Style {
-				case "Dashed":
+				case BorderStyleDashed:
 					style = 1
-				case "Dotted":
+				case BorderStyleDotted:
 					style = 2
 				default:
 					style = 0
 				}
 				buf = append(buf, style)
+				buf = appendF32(buf, cmd.DrawRect.Border.DashLength)
+				buf = appendF32(buf, cmd.DrawRect.Border.GapLength)
 			}
 
 			if cmd.DrawRect.Gradient != nil {
-				if cmd.DrawRect.Gradient.Linear != nil {
-					buf = append(buf, 0)
-					buf = appendF32(buf, cmd.DrawRect.Gradient.Linear.Angle)
-					buf = append(buf, byte(len(cmd.DrawRect.Gradient.Linear.Stops)))
-					for _, stop := range cmd.DrawRect.Gradient.Linear.Stops {
-						buf = appendF32(buf, stop.Position)
-						buf = appendU32(buf, stop.Color)
-					}
-				} else if cmd.DrawRect.Gradient.Radial != nil {
-					buf = append(buf, 1)
-					buf = appendF32(buf, cmd.DrawRect.Gradient.Radial.CenterX)
-					buf = appendF32(buf, cmd.DrawRect.Gradient.Radial.CenterY)
-					buf = append(buf, byte(len(cmd.DrawRect.Gradient.Radial.Stops)))
-					for _, stop := range cmd.DrawRect.Gradient.Radial.Stops {
-						buf = appendF32(buf, stop.Position)
-						buf = appendU32(buf, stop.Color)
-					}
-				}
-			}
-		} else if cmd.DrawText != nil {
-			buf = append(buf, 0x02)
-			buf = appendF32(buf, cmd.DrawText.X)
-			buf = appendF32(buf, cmd.DrawText.Y)
-			buf = appendString(buf, cmd.DrawText.Text)
-
-			var sourceType byte
-			var fontName string
-			if cmd.DrawText.Font.Source.Bundled != nil {
-				sourceType = 1
-				fontName = *cmd.DrawText.Font.Source.Bundled
-			} else if cmd.DrawText.Font.Source.System != nil {
-				sourceType = 0
-				fontName = *cmd.DrawText.Font.Source.System
-			} else {
-				sourceType = 0
-				fontName = "system"
-			}
-			buf = append(buf, sourceType)
-			buf = appendString(buf, fontName)
-			buf = appendU16(buf, cmd.DrawText.Font.Weight)
-			var style byte
-			if cmd.DrawText.Font.Style == FontStyleItalic {
-				style = 1
+				buf = appendGradient(buf, cmd.DrawRect.Gradient)
 			}
-			buf = append(buf, style)
-			buf = appendF32(buf, cmd.DrawText.Font.Size)
 
-			buf = appendU32(buf, cmd.DrawText.Color)
-
-			var layoutFlags byte
-			if cmd.DrawText.Layout.MaxWidth != nil {
-				layoutFlags |= 0x01
-			}
-			if cmd.DrawText.Layout.MaxHeight != nil {
-				layoutFlags |= 0x02
-			}
-			if cmd.DrawText.Layout.MaxLines != nil {
-				layoutFlags |= 0x04
+			if cmd.DrawRect.Pivot != nil {
+				buf = appendF32(buf, cmd.DrawRect.Pivot[0])
+				buf = appendF32(buf, cmd.DrawRect.Pivot[1])
 			}
-			buf = append(buf, layoutFlags)
 
-			if cmd.DrawText.Layout.MaxWidth != nil {
-				buf = appendF32(buf, *cmd.DrawText.Layout.MaxWidth)
-			}
-			if cmd.DrawText.Layout.MaxHeight != nil {
-				buf = appendF32(buf, *cmd.DrawText.Layout.MaxHeight)
-			}
-			if cmd.DrawText.Layout.MaxLines != nil {
-				buf = appendU32(buf, uint32(*cmd.DrawText.Layout.MaxLines))
+			if cmd.DrawRect.InnerShadow != nil {
+				buf = appendF32(buf, cmd.DrawRect.InnerShadow.Blur)
+				buf = appendF32(buf, cmd.DrawRect.InnerShadow.Spread)
+				buf = appendF32(buf, cmd.DrawRect.InnerShadow.OffsetX)
+				buf = appendF32(buf, cmd.DrawRect.InnerShadow.OffsetY)
+				buf = appendU32(buf, cmd.DrawRect.InnerShadow.Color)
 			}
 
-			buf = appendF32(buf, cmd.DrawText.Layout.LineHeight)
-			buf = appendF32(buf, cmd.DrawText.Layout.LetterSpacing)
-			buf = appendF32(buf, cmd.DrawText.Layout.WordSpacing)
-
-			var alignment byte
-			switch cmd.DrawText.Layout.Alignment {
-			case TextAlignCenter:
-				alignment = 1
-			case TextAlignRight:
-				alignment = 2
-			case TextAlignJustify:
-				alignment = 3
-			default:
-				alignment = 0
-			}
-			buf = append(buf, alignment)
-
-			var vertAlign byte
-			switch cmd.DrawText.Layout.VerticalAlign {
-			case VerticalAlignMiddle:
-				vertAlign = 1
-			case VerticalAlignBottom:
-				vertAlign = 2
-			case VerticalAlignBaseline:
-				vertAlign = 3
-			default:
-				vertAlign = 0
-			}
-			buf = append(buf, vertAlign)
-
-			var wordBreak byte
-			switch cmd.DrawText.Layout.WordBreak {
-			case WordBreakBreakAll:
-				wordBreak = 1
-			case WordBreakKeepAll:
-				wordBreak = 2
-			case WordBreakBreakWord:
-				wordBreak = 3
-			default:
-				wordBreak = 0
-			}
-			buf = append(buf, wordBreak)
-
-			var overflow byte
-			switch cmd.DrawText.Layout.Overflow {
-			case TextOverflowEllipsis:
-				overflow = 1
-			case TextOverflowWrap:
-				overflow = 2
-			default:
-				overflow = 0
+			if cmd.DrawRect.Shadow != nil {
+				buf = appendF32(buf, cmd.DrawRect.Shadow.OffsetX)
+				buf = appendF32(buf, cmd.DrawRect.Shadow.OffsetY)
+				buf = appendF32(buf, cmd.DrawRect.Shadow.BlurRadius)
+				buf = appendF32(buf, cmd.DrawRect.Shadow.Spread)
+				buf = appendU32(buf, cmd.DrawRect.Shadow.Color)
 			}
-			buf = append(buf, overflow)
-
-			var whiteSpace byte
-			switch cmd.DrawText.Layout.WhiteSpace {
-			case WhiteSpaceNoWrap:
-				whiteSpace = 1
-			case WhiteSpacePre:
-				whiteSpace = 2
-			case WhiteSpacePreWrap:
-				whiteSpace = 3
-			default:
-				whiteSpace = 0
+		} else if cmd.DrawText != nil {
+			buf = append(buf, 0x02)
+			buf = appendF32(buf, cmd.DrawText.X)
+			buf = appendF32(buf, cmd.DrawText.Y)
+			buf = appendU32(buf, texts.ref(cmd.DrawText.Text))
+			buf = appendFont(buf, cmd.DrawText.Font)
+			buf = appendU32(buf, cmd.DrawText.Color)
+			buf = appendTextLayout(buf, cmd.DrawText.Layout, cmd.DrawText.Gradient != nil)
+			if cmd.DrawText.Gradient != nil {
+				buf = appendGradient(buf, cmd.DrawText.Gradient)
 			}
-			buf = append(buf, whiteSpace)
 		} else if cmd.DrawImage != nil {
 			buf = append(buf, 0x03)
 			buf = appendF32(buf, cmd.DrawImage.X)
@@ -3821,6 +5814,24 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 			if cmd.DrawImage.SourceRect != nil {
 				flags |= 0x01
 			}
+			if cmd.DrawImage.Opacity != nil {
+				flags |= 0x02
+			}
+			if cmd.DrawImage.Rotation != 0 {
+				flags |= 0x04
+			}
+			if cmd.DrawImage.Pivot != nil {
+				flags |= 0x08
+			}
+			if cmd.DrawImage.Src != nil {
+				flags |= 0x10
+			}
+			if cmd.DrawImage.Tint != nil {
+				flags |= 0x20
+			}
+			if cmd.DrawImage.Sampling != "" {
+				flags |= 0x40
+			}
 			buf = append(buf, flags)
 
 			if cmd.DrawImage.SourceRect != nil {
@@ -3834,6 +5845,40 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 			buf = appendF32(buf, cmd.DrawImage.CornerRadii[1])
 			buf = appendF32(buf, cmd.DrawImage.CornerRadii[2])
 			buf = appendF32(buf, cmd.DrawImage.CornerRadii[3])
+
+			if cmd.DrawImage.Opacity != nil {
+				buf = appendF32(buf, *cmd.DrawImage.Opacity)
+			}
+
+			if cmd.DrawImage.Rotation != 0 {
+				buf = appendF32(buf, cmd.DrawImage.Rotation)
+			}
+
+			if cmd.DrawImage.Pivot != nil {
+				buf = appendF32(buf, cmd.DrawImage.Pivot[0])
+				buf = appendF32(buf, cmd.DrawImage.Pivot[1])
+			}
+
+			if cmd.DrawImage.Src != nil {
+				buf = appendF32(buf, cmd.DrawImage.Src.X)
+				buf = appendF32(buf, cmd.DrawImage.Src.Y)
+				buf = appendF32(buf, cmd.DrawImage.Src.Width)
+				buf = appendF32(buf, cmd.DrawImage.Src.Height)
+			}
+
+			if cmd.DrawImage.Tint != nil {
+				buf = appendU32(buf, *cmd.DrawImage.Tint)
+			}
+
+			switch cmd.DrawImage.Sampling {
+			case "":
+			case SamplingNearest:
+				buf = append(buf, 0)
+			case SamplingLinear:
+				buf = append(buf, 1)
+			default:
+				buf = append(buf, 2)
+			}
 		} else if cmd.DrawShadow != nil {
 			buf = append(buf, 0x04)
 			buf = appendF32(buf, cmd.DrawShadow.X)
@@ -3854,6 +5899,16 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 			buf = appendF32(buf, cmd.PushClip.Y)
 			buf = appendF32(buf, cmd.PushClip.Width)
 			buf = appendF32(buf, cmd.PushClip.Height)
+		} else if cmd.PushRoundedClip != nil {
+			buf = append(buf, 0x10)
+			buf = appendF32(buf, cmd.PushRoundedClip.X)
+			buf = appendF32(buf, cmd.PushRoundedClip.Y)
+			buf = appendF32(buf, cmd.PushRoundedClip.Width)
+			buf = appendF32(buf, cmd.PushRoundedClip.Height)
+			buf = appendF32(buf, cmd.PushRoundedClip.CornerRadii[0])
+			buf = appendF32(buf, cmd.PushRoundedClip.CornerRadii[1])
+			buf = appendF32(buf, cmd.PushRoundedClip.CornerRadii[2])
+			buf = appendF32(buf, cmd.PushRoundedClip.CornerRadii[3])
 		} else if cmd.PopClip != nil {
 			buf = append(buf, 0x06)
 		} else if cmd.BeginScrollView != nil {
@@ -3885,9 +5940,316 @@ func SerializeRenderCommands(commands []RenderCommand) []byte {
 		} else if cmd.SetOpacity != nil {
 			buf = append(buf, 0x09)
 			buf = appendF32(buf, *cmd.SetOpacity)
+		} else if cmd.Caret != nil {
+			buf = append(buf, 0x0A)
+			buf = appendF32(buf, cmd.Caret.X)
+			buf = appendF32(buf, cmd.Caret.Y)
+			buf = appendF32(buf, cmd.Caret.Width)
+			buf = appendF32(buf, cmd.Caret.Height)
+			buf = appendU32(buf, cmd.Caret.Color)
+			buf = appendU32(buf, cmd.Caret.BlinkMs)
+		} else if cmd.FocusRing != nil {
+			buf = append(buf, 0x0B)
+			buf = appendF32(buf, cmd.FocusRing.Rect.X)
+			buf = appendF32(buf, cmd.FocusRing.Rect.Y)
+			buf = appendF32(buf, cmd.FocusRing.Rect.Width)
+			buf = appendF32(buf, cmd.FocusRing.Rect.Height)
+			buf = appendF32(buf, cmd.FocusRing.CornerRadii[0])
+			buf = appendF32(buf, cmd.FocusRing.CornerRadii[1])
+			buf = appendF32(buf, cmd.FocusRing.CornerRadii[2])
+			buf = appendF32(buf, cmd.FocusRing.CornerRadii[3])
+			buf = appendU32(buf, cmd.FocusRing.Color)
+			buf = appendF32(buf, cmd.FocusRing.Thickness)
+			buf = appendF32(buf, cmd.FocusRing.Dash)
+			buf = appendF32(buf, cmd.FocusRing.Offset)
+			buf = appendF32(buf, cmd.FocusRing.Speed)
+		} else if cmd.DrawLine != nil {
+			buf = append(buf, 0x0C)
+			buf = appendF32(buf, cmd.DrawLine.X1)
+			buf = appendF32(buf, cmd.DrawLine.Y1)
+			buf = appendF32(buf, cmd.DrawLine.X2)
+			buf = appendF32(buf, cmd.DrawLine.Y2)
+			buf = appendF32(buf, cmd.DrawLine.Width)
+			buf = appendU32(buf, cmd.DrawLine.Color)
+			switch cmd.DrawLine.Cap {
+			case LineCapRound:
+				buf = append(buf, 1)
+			case LineCapSquare:
+				buf = append(buf, 2)
+			default:
+				buf = append(buf, 0)
+			}
+		} else if cmd.DrawArc != nil {
+			buf = append(buf, 0x11)
+			buf = appendF32(buf, cmd.DrawArc.CX)
+			buf = appendF32(buf, cmd.DrawArc.CY)
+			buf = appendF32(buf, cmd.DrawArc.Radius)
+			buf = appendF32(buf, cmd.DrawArc.StartAngle)
+			buf = appendF32(buf, cmd.DrawArc.EndAngle)
+			buf = appendF32(buf, cmd.DrawArc.Thickness)
+			buf = appendU32(buf, cmd.DrawArc.Color)
+		} else if cmd.DrawPath != nil {
+			buf = append(buf, 0x13)
+			buf = appendPath(buf, cmd.DrawPath)
+		} else if cmd.DrawNinePatch != nil {
+			buf = append(buf, 0x12)
+			buf = appendU32(buf, cmd.DrawNinePatch.TextureID)
+			buf = appendF32(buf, cmd.DrawNinePatch.DestRect.X)
+			buf = appendF32(buf, cmd.DrawNinePatch.DestRect.Y)
+			buf = appendF32(buf, cmd.DrawNinePatch.DestRect.Width)
+			buf = appendF32(buf, cmd.DrawNinePatch.DestRect.Height)
+			for _, inset := range cmd.DrawNinePatch.Insets {
+				buf = appendF32(buf, inset)
+			}
+		} else if cmd.DrawRichText != nil {
+			buf = append(buf, 0x0D)
+			buf = appendF32(buf, cmd.DrawRichText.X)
+			buf = appendF32(buf, cmd.DrawRichText.Y)
+			buf = appendTextLayout(buf, cmd.DrawRichText.Layout, cmd.DrawRichText.Gradient != nil)
+			if cmd.DrawRichText.Gradient != nil {
+				buf = appendGradient(buf, cmd.DrawRichText.Gradient)
+			}
+			buf = appendU32(buf, uint32(len(cmd.DrawRichText.Runs)))
+			for _, run := range cmd.DrawRichText.Runs {
+				if run.Text != nil {
+					buf = append(buf, 0)
+					buf = appendU32(buf, texts.ref(*run.Text))
+					var font FontDescriptor
+					if run.Font != nil {
+						font = *run.Font
+					}
+					buf = appendFont(buf, font)
+					buf = appendU32(buf, run.Color)
+				} else {
+					buf = append(buf, 1)
+					buf = appendU32(buf, run.ImageTextureID)
+					buf = appendF32(buf, run.Width)
+					buf = appendF32(buf, run.Height)
+					buf = appendF32(buf, run.BaselineOffset)
+				}
+			}
+		} else if cmd.PushTransform != nil {
+			buf = append(buf, 0x0E)
+			for _, v := range cmd.PushTransform.Matrix {
+				buf = appendF32(buf, v)
+			}
+		} else if cmd.PopTransform != nil {
+			buf = append(buf, 0x0F)
+		} else if cmd.PushOpacityLayer != nil {
+			buf = append(buf, 0x14)
+			buf = appendF32(buf, *cmd.PushOpacityLayer)
+		} else if cmd.PopOpacityLayer != nil {
+			buf = append(buf, 0x15)
+		} else if cmd.Spinner != nil {
+			buf = append(buf, 0x16)
+			buf = appendF32(buf, cmd.Spinner.CenterX)
+			buf = appendF32(buf, cmd.Spinner.CenterY)
+			buf = appendF32(buf, cmd.Spinner.Radius)
+			buf = appendU32(buf, cmd.Spinner.Color)
+			buf = appendF32(buf, cmd.Spinner.Speed)
+			buf = appendF32(buf, cmd.Spinner.Thickness)
+		}
+	}
+
+	return texts.prepend(buf)
+}
+
+// stringTable collects the distinct texts of a binary frame.
+type stringTable struct {
+	indices map[string]uint32
+	strings []string
+}
+
+// ref returns the index of s in the table, adding it if it is new.
+func (t *stringTable) ref(s string) uint32 {
+	if i, ok := t.indices[s]; ok {
+		return i
+	}
+	i := uint32(len(t.strings))
+	t.indices[s] = i
+	t.strings = append(t.strings, s)
+	return i
+}
+
+// prepend returns the encoded table followed by commands.
+func (t *stringTable) prepend(commands []byte) []byte {
+	size := 4 + len(commands)
+	for _, s := range t.strings {
+		size += 4 + len(s)
+	}
+	buf := make([]byte, 0, size)
+	buf = appendU32(buf, uint32(len(t.strings)))
+	for _, s := range t.strings {
+		buf = appendString(buf, s)
+	}
+	return append(buf, commands...)
+}
+
+// appendFont encodes a font descriptor as the binary frame's font_data.
+func appendFont(buf []byte, font FontDescriptor) []byte {
+	var sourceType byte
+	var fontName string
+	if font.Source.Bundled != nil {
+		sourceType = 1
+		fontName = *font.Source.Bundled
+	} else if font.Source.System != nil {
+		sourceType = 0
+		fontName = *font.Source.System
+	} else if font.Source.Memory != nil {
+		sourceType = 2
+		fontName = font.Source.Memory.Name
+	} else if len(font.Source.SystemWithFallback) > 0 {
+		sourceType = 3
+		fontName = strings.Join(font.Source.SystemWithFallback, ",")
+	} else {
+		sourceType = 0
+		fontName = "system"
+	}
+	buf = append(buf, sourceType)
+	buf = appendString(buf, fontName)
+	if sourceType == 2 {
+		buf = appendU64(buf, font.Source.Memory.DataHash)
+	}
+	buf = appendU16(buf, font.Weight)
+	var style byte
+	if font.Style == FontStyleItalic {
+		style = 1
+	}
+	buf = append(buf, style)
+	buf = appendF32(buf, font.Size)
+	return buf
+}
+
+// appendTextLayout encodes a layout config as the binary frame's
+// layout_data; hasGradient sets the flag saying gradient data follows.
+func appendTextLayout(buf []byte, layout TextLayoutConfig, hasGradient bool) []byte {
+	var layoutFlags byte
+	if layout.MaxWidth != nil {
+		layoutFlags |= 0x01
+	}
+	if layout.MaxHeight != nil {
+		layoutFlags |= 0x02
+	}
+	if layout.MaxLines != nil {
+		layoutFlags |= 0x04
+	}
+	if layout.Anchor != "" {
+		layoutFlags |= 0x08
+	}
+	if hasGradient {
+		layoutFlags |= 0x10
+	}
+	if layout.BaseDirection != "" {
+		layoutFlags |= 0x20
+	}
+	buf = append(buf, layoutFlags)
+
+	if layout.MaxWidth != nil {
+		buf = appendF32(buf, *layout.MaxWidth)
+	}
+	if layout.MaxHeight != nil {
+		buf = appendF32(buf, *layout.MaxHeight)
+	}
+	if layout.MaxLines != nil {
+		buf = appendU32(buf, uint32(*layout.MaxLines))
+	}
+
+	buf = appendF32(buf, layout.LineHeight)
+	buf = appendF32(buf, layout.LetterSpacing)
+	buf = appendF32(buf, layout.WordSpacing)
+
+	var alignment byte
+	switch layout.Alignment {
+	case TextAlignCenter:
+		alignment = 1
+	case TextAlignRight:
+		alignment = 2
+	case TextAlignJustify:
+		alignment = 3
+	case TextAlignStart:
+		alignment = 4
+	case TextAlignEnd:
+		alignment = 5
+	default:
+		alignment = 0
+	}
+	buf = append(buf, alignment)
+
+	var vertAlign byte
+	switch layout.VerticalAlign {
+	case VerticalAlignMiddle:
+		vertAlign = 1
+	case VerticalAlignBottom:
+		vertAlign = 2
+	case VerticalAlignBaseline:
+		vertAlign = 3
+	default:
+		vertAlign = 0
+	}
+	buf = append(buf, vertAlign)
+
+	var wordBreak byte
+	switch layout.WordBreak {
+	case WordBreakBreakAll:
+		wordBreak = 1
+	case WordBreakKeepAll:
+		wordBreak = 2
+	case WordBreakBreakWord:
+		wordBreak = 3
+	default:
+		wordBreak = 0
+	}
+	buf = append(buf, wordBreak)
+
+	var overflow byte
+	switch layout.Overflow {
+	case TextOverflowEllipsis:
+		overflow = 1
+	case TextOverflowWrap:
+		overflow = 2
+	default:
+		overflow = 0
+	}
+	buf = append(buf, overflow)
+
+	var whiteSpace byte
+	switch layout.WhiteSpace {
+	case WhiteSpaceNoWrap:
+		whiteSpace = 1
+	case WhiteSpacePre:
+		whiteSpace = 2
+	case WhiteSpacePreWrap:
+		whiteSpace = 3
+	default:
+		whiteSpace = 0
+	}
+	buf = append(buf, whiteSpace)
+
+	if layout.Anchor != "" {
+		var anchor byte
+		switch layout.Anchor {
+		case TextAnchorBaseline:
+			anchor = 1
+		case TextAnchorCenter:
+			anchor = 2
+		default:
+			anchor = 0
 		}
+		buf = append(buf, anchor)
 	}
 
+	if layout.BaseDirection != "" {
+		var direction byte
+		switch layout.BaseDirection {
+		case TextDirectionLtr:
+			direction = 1
+		case TextDirectionRtl:
+			direction = 2
+		default:
+			direction = 0
+		}
+		buf = append(buf, direction)
+	}
 	return buf
 }
 
@@ -3923,6 +6285,10 @@ func appendU32(buf []byte, v uint32) []byte {
 	return append(buf, byte(v), byte(v>>8), byte(v>>16), byte(v>>24))
 }
 
+func appendU64(buf []byte, v uint64) []byte {
+	return appendU32(appendU32(buf, uint32(v)), uint32(v>>32))
+}
+
 func appendF32(buf []byte, v float32) []byte {
 	bits := math.Float32bits(v)
 	return appendU32(buf, bits)
@@ -3932,3 +6298,107 @@ func appendString(buf []byte, s string) []byte {
 	buf = appendU32(buf, uint32(len(s)))
 	return append(buf, s...)
 }
+
+// appendPath encodes a DrawPath as flags(1) + [fill(4)] + [stroke width(4) +
+// color(4) + cap(1) + join(1)] + segment_count(4) + segments, each a verb(1)
+// followed by its points.
+func appendPath(buf []byte, path *DrawPathCmd) []byte {
+	var flags byte
+	if path.Fill != nil {
+		flags |= 0x01
+	}
+	if path.Stroke != nil {
+		flags |= 0x02
+	}
+	if path.FillRule == FillRuleEvenOdd {
+		flags |= 0x04
+	}
+	buf = append(buf, flags)
+
+	if path.Fill != nil {
+		buf = appendU32(buf, *path.Fill)
+	}
+	if s := path.Stroke; s != nil {
+		buf = appendF32(buf, s.Width)
+		buf = appendU32(buf, s.Color)
+		switch s.Cap {
+		case LineCapRound:
+			buf = append(buf, 1)
+		case LineCapSquare:
+			buf = append(buf, 2)
+		default:
+			buf = append(buf, 0)
+		}
+		switch s.Join {
+		case LineJoinRound:
+			buf = append(buf, 1)
+		case LineJoinBevel:
+			buf = append(buf, 2)
+		default:
+			buf = append(buf, 0)
+		}
+	}
+
+	buf = appendU32(buf, uint32(len(path.Segments)))
+	for _, seg := range path.Segments {
+		switch {
+		case seg.MoveTo != nil:
+			buf = append(buf, 0)
+			buf = appendF32(buf, seg.MoveTo.X)
+			buf = appendF32(buf, seg.MoveTo.Y)
+		case seg.LineTo != nil:
+			buf = append(buf, 1)
+			buf = appendF32(buf, seg.LineTo.X)
+			buf = appendF32(buf, seg.LineTo.Y)
+		case seg.QuadTo != nil:
+			buf = append(buf, 2)
+			for _, v := range []float32{seg.QuadTo.CX, seg.QuadTo.CY, seg.QuadTo.X, seg.QuadTo.Y} {
+				buf = appendF32(buf, v)
+			}
+		case seg.CubicTo != nil:
+			c := seg.CubicTo
+			buf = append(buf, 3)
+			for _, v := range []float32{c.C1X, c.C1Y, c.C2X, c.C2Y, c.X, c.Y} {
+				buf = appendF32(buf, v)
+			}
+		default:
+			buf = append(buf, 4)
+		}
+	}
+	return buf
+}
+
+// appendGradient encodes a gradient as type(1) + params + stop_count(1) + stops.
+// Linear: angle(4). Radial: center_x(4) + center_y(4). Conic: center_x(4) +
+// center_y(4) + start_angle(4). Each stop: position(4) + color(4).
+func appendGradient(buf []byte, g *Gradient) []byte {
+	if g.Linear != nil {
+		buf = append(buf, 0)
+		buf = appendF32(buf, g.Linear.Angle)
+		buf = append(buf, byte(len(g.Linear.Stops)))
+		for _, stop := range g.Linear.Stops {
+			buf = appendF32(buf, stop.Position)
+			buf = appendU32(buf, stop.Color)
+		}
+	} else if g.Radial != nil {
+		buf = append(buf, 1)
+		buf = appendF32(buf, g.Radial.CenterX)
+		buf = appendF32(buf, g.Radial.CenterY)
+		buf = append(buf, byte(len(g.Radial.Stops)))
+		for _, stop := range g.Radial.Stops {
+			buf = appendF32(buf, stop.Position)
+			buf = appendU32(buf, stop.Color)
+		}
+	} else if g.Conic != nil {
+		buf = append(buf, 2)
+		buf = appendF32(buf, g.Conic.CenterX)
+		buf = appendF32(buf, g.Conic.CenterY)
+		buf = appendF32(buf, g.Conic.StartAngle)
+		buf = append(buf, byte(len(g.Conic.Stops)))
+		for _, stop := range g.Conic.Stops {
+			buf = appendF32(buf, stop.Position)
+			buf = appendU32(buf, stop.Color)
+		}
+	}
+	return buf
+}
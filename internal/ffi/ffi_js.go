@@ -6,8 +6,12 @@
 package ffi
 
 import (
+	"encoding/json"
 	"fmt"
+	"math"
 	"syscall/js"
+	"time"
+	"unicode"
 )
 
 // ============================================================================
@@ -31,6 +35,7 @@ var (
 	// Animation loop control
 	animationLoopRunning bool
 	animationFrame       js.Func
+	frameClock           webFrameClock
 
 	// Bundled font registry: path -> CSS font-family name
 	bundledFonts = make(map[string]string)
@@ -74,6 +79,20 @@ const (
 	EventTouchEnd              EventType = 13
 	EventTouchCancel           EventType = 14
 	EventKeyboardFrameChanged  EventType = 15
+	EventTouch                 EventType = 16
+	EventImePreedit            EventType = 17
+	EventImeCommit             EventType = 18
+	EventFixedUpdate           EventType = 19
+	EventMenuItemClicked       EventType = 20
+	EventFileDragEnter         EventType = 21
+	EventFileDragOver          EventType = 22
+	EventFileDrop              EventType = 23
+	EventFileDragCancel        EventType = 24
+	EventFocusGained           EventType = 25
+	EventFocusLost             EventType = 26
+	EventMinimized             EventType = 27
+	EventRestored              EventType = 28
+	EventOccluded              EventType = 29
 )
 
 // Event represents a platform event
@@ -85,17 +104,95 @@ type Event struct {
 	Data4     float64 // scroll delta y
 	Text      string  // for text input
 	Timestamp float64
+
+	// FrameDt is the seconds since the previous frame on an
+	// EventRedrawRequested (0 for the first frame), from the
+	// requestAnimationFrame clock, and the step length on an EventFixedUpdate
+	FrameDt float64
+
+	// RenderFailures is the number of consecutive failed presents before
+	// this event. The browser presents for us, so this is always 0.
+	RenderFailures uint32
+
+	// ScrollPhase is where a MouseWheel event falls in a trackpad gesture.
+	// Browser wheel events have no phases, so this is always None.
+	ScrollPhase ScrollPhase
+
+	// TouchPhase and TouchID describe the finger of an EventTouch; the ID
+	// is the browser's Touch.identifier.
+	TouchPhase TouchPhase
+	TouchID    uint64
+}
+
+// ImeCursor returns the selected byte range within the Text of an
+// EventImePreedit. The browser build has no IME events, so this is
+// always false.
+func (e Event) ImeCursor() (start, end int, ok bool) { return 0, 0, false }
+
+// MenuItemID returns the id of the menu item an EventMenuItemClicked is for.
+// The browser build has no menu bar, so these events never arrive.
+func (e Event) MenuItemID() uint32 { return uint32(e.Data1) }
+
+// FilePaths returns the names of the files an EventFileDrop is for. Pages
+// can't see file paths, and the names are only known once the files are
+// dropped, so an EventFileDragEnter has none. MouseX and MouseY give the
+// cursor position of file drag events.
+func (e Event) FilePaths() []string {
+	if e.Type != EventFileDragEnter && e.Type != EventFileDrop {
+		return nil
+	}
+	var paths []string
+	if err := json.Unmarshal([]byte(e.Text), &paths); err != nil {
+		return nil
+	}
+	return paths
 }
 
+// Hidden reports whether an EventOccluded is for the page becoming hidden
+// rather than visible again. Browsers don't say whether the window was
+// minimized or the tab switched away, so both come as EventOccluded and
+// EventMinimized and EventRestored never arrive.
+func (e Event) Hidden() bool { return e.Type == EventOccluded && e.Data1 != 0 }
+
+// ScrollPhase is the gesture phase of a MouseWheel event
+type ScrollPhase uint8
+
+const (
+	ScrollPhaseNone          ScrollPhase = 0
+	ScrollPhaseStarted       ScrollPhase = 1
+	ScrollPhaseMoved         ScrollPhase = 2
+	ScrollPhaseEnded         ScrollPhase = 3
+	ScrollPhaseMomentum      ScrollPhase = 4
+	ScrollPhaseMomentumEnded ScrollPhase = 5
+)
+
+// TouchPhase is what happened to the touch point of an EventTouch
+type TouchPhase uint8
+
+const (
+	TouchNone      TouchPhase = 0
+	TouchBegan     TouchPhase = 1
+	TouchMoved     TouchPhase = 2
+	TouchEnded     TouchPhase = 3
+	TouchCancelled TouchPhase = 4
+)
+
 // Event accessor methods
 func (e Event) Keycode() uint32         { return uint32(e.Data1) }
-func (e Event) Modifiers() Modifiers    { return Modifiers(uint32(e.Data2)) }
+func (e Event) Modifiers() Modifiers    { return Modifiers(uint32(e.Data2) &^ keyRepeat) }
+func (e Event) IsRepeat() bool          { return e.Type == EventKeyPressed && uint32(e.Data2)&keyRepeat != 0 }
+func (e Event) LogicalKey() string {
+	if e.Type != EventKeyPressed && e.Type != EventKeyReleased {
+		return ""
+	}
+	return e.Text
+}
 func (e Event) HasShift() bool          { return e.Modifiers()&ModShift != 0 }
 func (e Event) HasCtrl() bool           { return e.Modifiers()&ModCtrl != 0 }
 func (e Event) HasAlt() bool            { return e.Modifiers()&ModAlt != 0 }
 func (e Event) HasSuper() bool          { return e.Modifiers()&ModSuper != 0 }
 func (e Event) Char() rune {
-	if len(e.Text) > 0 {
+	if e.Type == EventCharInput && len(e.Text) > 0 {
 		return []rune(e.Text)[0]
 	}
 	return 0
@@ -106,6 +203,10 @@ func (e Event) MouseButton() int        { return int(e.Data3) }
 func (e Event) Width() float64          { return e.Data1 }
 func (e Event) Height() float64         { return e.Data2 }
 func (e Event) ScrollDelta() (float64, float64) { return e.Data3, e.Data4 }
+func (e Event) RenderFailed() bool { return e.RenderFailures > 0 }
+func (e Event) IsMomentumScroll() bool {
+	return e.ScrollPhase == ScrollPhaseMomentum || e.ScrollPhase == ScrollPhaseMomentumEnded
+}
 
 // Modifiers for keyboard events
 type Modifiers uint32
@@ -117,6 +218,9 @@ const (
 	ModSuper Modifiers = 1 << 3
 )
 
+// keyRepeat is set in Data2 of a KeyPressed that is an auto-repeat
+const keyRepeat = 1 << 4
+
 // ============================================================================
 // Keycodes
 // ============================================================================
@@ -201,6 +305,10 @@ type AppConfig struct {
 	Height    int
 	Resizable bool
 	TargetFPS int
+
+	// FixedTimestepHz sends EventFixedUpdate steps before each
+	// EventRedrawRequested, as on native; 0 turns it off
+	FixedTimestepHz int
 }
 
 func DefaultAppConfig() AppConfig {
@@ -233,16 +341,29 @@ type EventHandler func(event Event) FrameResponse
 
 // RenderCommand represents a single rendering operation (tagged union style)
 type RenderCommand struct {
-	DrawRect        *DrawRectCmd        `json:"DrawRect,omitempty"`
-	DrawText        *DrawTextCmd        `json:"DrawText,omitempty"`
-	DrawImage       *DrawImageCmd       `json:"DrawImage,omitempty"`
-	DrawShadow      *DrawShadowCmd      `json:"DrawShadow,omitempty"`
-	Clear           *ClearCmd           `json:"Clear,omitempty"`
-	PushClip        *PushClipCmd        `json:"PushClip,omitempty"`
-	PopClip         *struct{}           `json:"PopClip,omitempty"`
-	BeginScrollView *BeginScrollViewCmd `json:"BeginScrollView,omitempty"`
-	EndScrollView   *struct{}           `json:"EndScrollView,omitempty"`
-	SetOpacity      *float32            `json:"SetOpacity,omitempty"`
+	DrawRect         *DrawRectCmd        `json:"DrawRect,omitempty"`
+	DrawText         *DrawTextCmd        `json:"DrawText,omitempty"`
+	DrawRichText     *DrawRichTextCmd    `json:"DrawRichText,omitempty"`
+	DrawImage        *DrawImageCmd       `json:"DrawImage,omitempty"`
+	DrawNinePatch    *DrawNinePatchCmd   `json:"DrawNinePatch,omitempty"`
+	DrawLine         *DrawLineCmd        `json:"DrawLine,omitempty"`
+	DrawArc          *DrawArcCmd         `json:"DrawArc,omitempty"`
+	DrawPath         *DrawPathCmd        `json:"DrawPath,omitempty"`
+	DrawShadow       *DrawShadowCmd      `json:"DrawShadow,omitempty"`
+	Clear            *ClearCmd           `json:"Clear,omitempty"`
+	PushClip         *PushClipCmd        `json:"PushClip,omitempty"`
+	PushRoundedClip  *PushRoundedClipCmd `json:"PushRoundedClip,omitempty"`
+	PopClip          *struct{}           `json:"PopClip,omitempty"`
+	BeginScrollView  *BeginScrollViewCmd `json:"BeginScrollView,omitempty"`
+	EndScrollView    *struct{}           `json:"EndScrollView,omitempty"`
+	PushTransform    *PushTransformCmd   `json:"PushTransform,omitempty"`
+	PopTransform     *struct{}           `json:"PopTransform,omitempty"`
+	SetOpacity       *float32            `json:"SetOpacity,omitempty"`
+	PushOpacityLayer *float32            `json:"PushOpacityLayer,omitempty"`
+	PopOpacityLayer  *struct{}           `json:"PopOpacityLayer,omitempty"`
+	Caret            *CaretCmd           `json:"Caret,omitempty"`
+	FocusRing        *FocusRingCmd       `json:"FocusRing,omitempty"`
+	Spinner          *SpinnerCmd         `json:"Spinner,omitempty"`
 	// Web-specific extensions
 	DrawVideo      *DrawVideoCmd      `json:"-"`
 	DrawVideoInput *DrawVideoInputCmd `json:"-"`
@@ -267,8 +388,12 @@ type DrawRectCmd struct {
 	Color       uint32     `json:"color"`
 	CornerRadii [4]float32 `json:"corner_radii"`
 	Rotation    float32    `json:"rotation,omitempty"`
-	Border      *Border    `json:"border,omitempty"`
-	Gradient    *Gradient  `json:"gradient,omitempty"`
+	// Point to rotate about, relative to the top-left corner (nil = center)
+	Pivot       *[2]float32  `json:"pivot,omitempty"`
+	Border      *Border      `json:"border,omitempty"`
+	Gradient    *Gradient    `json:"gradient,omitempty"`
+	InnerShadow *InnerShadow `json:"inner_shadow,omitempty"`
+	Shadow      *BoxShadow   `json:"shadow,omitempty"`
 }
 
 type DrawImageCmd struct {
@@ -279,7 +404,30 @@ type DrawImageCmd struct {
 	TextureID   uint32      `json:"texture_id"`
 	SourceRect  *[4]float32 `json:"source_rect,omitempty"`
 	CornerRadii [4]float32  `json:"corner_radii,omitempty"`
-}
+	// Opacity 0-1, multiplied with SetOpacity (nil = opaque)
+	Opacity *float32 `json:"opacity,omitempty"`
+	// Rotation in radians about Pivot (relative to the top-left corner; nil = center)
+	Rotation float32     `json:"rotation,omitempty"`
+	Pivot    *[2]float32 `json:"pivot,omitempty"`
+	// Region of the texture to draw in texture pixels, e.g. a sprite atlas
+	// cell (nil = SourceRect or the whole texture)
+	Src *RectBounds `json:"src,omitempty"`
+	// Color (0xRRGGBBAA) multiplied with each sampled texel (nil = none)
+	Tint *uint32 `json:"tint,omitempty"`
+	// How texels are filtered when scaled (empty = Trilinear)
+	Sampling Sampling `json:"sampling,omitempty"`
+}
+
+// Sampling is how an image's texels are filtered when it is drawn larger or
+// smaller than its texture. On web, Nearest disables image smoothing and
+// Linear and Trilinear both use the browser's smoothing.
+type Sampling string
+
+const (
+	SamplingNearest   Sampling = "Nearest"
+	SamplingLinear    Sampling = "Linear"
+	SamplingTrilinear Sampling = "Trilinear"
+)
 
 type DrawVideoCmd struct {
 	X           float32
@@ -300,14 +448,47 @@ type DrawVideoInputCmd struct {
 }
 
 type Border struct {
-	Width float32 `json:"width"`
-	Color uint32  `json:"color"`
-	Style string  `json:"style"`
+	Width float32     `json:"width"`
+	Color uint32      `json:"color"`
+	Style BorderStyle `json:"style,omitempty"` // Empty = Solid
+	// Dash length for Dashed (0 = 3x Width)
+	DashLength float32 `json:"dash_length,omitempty"`
+	// Space between dashes or dots (0 = DashLength for Dashed, Width for Dotted)
+	GapLength float32 `json:"gap_length,omitempty"`
+}
+
+type BorderStyle string
+
+const (
+	BorderStyleSolid  BorderStyle = "Solid"
+	BorderStyleDashed BorderStyle = "Dashed"
+	BorderStyleDotted BorderStyle = "Dotted"
+)
+
+// InnerShadow is a shadow inside a rect, drawn over the fill and under the
+// border. Offset (0, 4) shades the top edge most.
+type InnerShadow struct {
+	Blur    float32 `json:"blur"`
+	Spread  float32 `json:"spread"`
+	OffsetX float32 `json:"offset_x"`
+	OffsetY float32 `json:"offset_y"`
+	Color   uint32  `json:"color"`
+}
+
+// BoxShadow is a drop shadow behind a rect, like CSS box-shadow. Offset
+// (0, 4) drops it below the rect; Spread grows it before blurring.
+type BoxShadow struct {
+	OffsetX    float32 `json:"offset_x"`
+	OffsetY    float32 `json:"offset_y"`
+	BlurRadius float32 `json:"blur_radius"`
+	Spread     float32 `json:"spread"`
+	Color      uint32  `json:"color"`
 }
 
 type Gradient struct {
 	Linear *LinearGradient `json:"Linear,omitempty"`
 	Radial *RadialGradient `json:"Radial,omitempty"`
+	Conic  *ConicGradient  `json:"Conic,omitempty"`
 }
 
 type LinearGradient struct {
@@ -321,6 +502,16 @@ type RadialGradient struct {
 	Stops   []GradientStop `json:"stops"`
 }
 
+// ConicGradient sweeps clockwise around a center, for pie charts and
+// progress rings. StartAngle is in degrees clockwise from 12 o'clock and stop
+// positions are fractions of a full turn.
+type ConicGradient struct {
+	CenterX    float32        `json:"center_x"`
+	CenterY    float32        `json:"center_y"`
+	StartAngle float32        `json:"start_angle"`
+	Stops      []GradientStop `json:"stops"`
+}
+
 type GradientStop struct {
 	Position float32 `json:"position"`
 	Color    uint32  `json:"color"`
@@ -333,6 +524,128 @@ type DrawTextCmd struct {
 	Font   FontDescriptor   `json:"font"`
 	Color  uint32           `json:"color"`
 	Layout TextLayoutConfig `json:"layout"`
+	// Gradient, if set, replaces Color. It spans the laid-out text box
+	// (all lines), not each glyph.
+	Gradient *Gradient `json:"gradient,omitempty"`
+}
+
+// DrawLineCmd draws a straight line segment Width logical pixels wide
+type DrawLineCmd struct {
+	X1    float32 `json:"x1"`
+	Y1    float32 `json:"y1"`
+	X2    float32 `json:"x2"`
+	Y2    float32 `json:"y2"`
+	Width float32 `json:"width"`
+	Color uint32  `json:"color"`
+	Cap   LineCap `json:"cap,omitempty"` // Empty = Butt
+}
+
+// DrawNinePatchCmd stretches a texture over DestRect keeping its borders at
+// their own size: corners unscaled, edges stretched along their length and
+// the middle filling the rest. Insets are the borders [top, right, bottom,
+// left] in texture pixels; a DestRect too small for them shrinks them to fit.
+type DrawNinePatchCmd struct {
+	TextureID uint32     `json:"texture_id"`
+	DestRect  RectBounds `json:"dest_rect"`
+	Insets    [4]float32 `json:"insets"`
+}
+
+// DrawArcCmd draws an antialiased arc of a circle: a ring segment Thickness
+// logical pixels wide inside Radius, or a filled wedge when Thickness is 0.
+// Angles are in radians clockwise from 3 o'clock, and the arc covers the
+// sweep from StartAngle to EndAngle in either direction; a full turn draws a
+// whole ring or circle.
+type DrawArcCmd struct {
+	CX         float32 `json:"cx"`
+	CY         float32 `json:"cy"`
+	Radius     float32 `json:"radius"`
+	StartAngle float32 `json:"start_angle"`
+	EndAngle   float32 `json:"end_angle"`
+	Thickness  float32 `json:"thickness,omitempty"`
+	Color      uint32  `json:"color"`
+}
+
+// LineCap is how the ends of a line are drawn. Round and Square extend past
+// the endpoints by half the width, so a zero-length line still draws a dot.
+type LineCap string
+
+const (
+	LineCapButt   LineCap = "Butt"
+	LineCapRound  LineCap = "Round"
+	LineCapSquare LineCap = "Square"
+)
+
+// LineJoin is how the corners between stroked path segments are drawn.
+// Miter corners are beveled where they'd reach past four times the width.
+type LineJoin string
+
+const (
+	LineJoinMiter LineJoin = "Miter"
+	LineJoinRound LineJoin = "Round"
+	LineJoinBevel LineJoin = "Bevel"
+)
+
+// FillRule is which parts of a self-intersecting or nested path are filled:
+// with NonZero a star drawn as one crossing outline is solid, with EvenOdd
+// its center is a hole.
+type FillRule string
+
+const (
+	FillRuleNonZero FillRule = "NonZero"
+	FillRuleEvenOdd FillRule = "EvenOdd"
+)
+
+// DrawPathCmd fills and/or strokes an outline of lines and bezier curves, in
+// logical pixels. Subpaths left open are filled as if closed but stroked
+// open, and the stroke is drawn over the fill.
+type DrawPathCmd struct {
+	Segments []PathSegment `json:"segments"`
+	// Fill color (0xRRGGBBAA; nil = stroke only)
+	Fill *uint32 `json:"fill,omitempty"`
+	// FillRule for overlapping subpaths (empty = NonZero)
+	FillRule FillRule `json:"fill_rule,omitempty"`
+	Stroke   *Stroke  `json:"stroke,omitempty"`
+}
+
+// PathSegment is one step of a path outline, with exactly one field set
+type PathSegment struct {
+	MoveTo  *PathPoint `json:"MoveTo,omitempty"`
+	LineTo  *PathPoint `json:"LineTo,omitempty"`
+	QuadTo  *PathQuad  `json:"QuadTo,omitempty"`
+	CubicTo *PathCubic `json:"CubicTo,omitempty"`
+	Close   *struct{}  `json:"Close,omitempty"`
+}
+
+type PathPoint struct {
+	X float32 `json:"x"`
+	Y float32 `json:"y"`
+}
+
+// PathQuad is a quadratic bezier to (X, Y) with control point (CX, CY)
+type PathQuad struct {
+	CX float32 `json:"cx"`
+	CY float32 `json:"cy"`
+	X  float32 `json:"x"`
+	Y  float32 `json:"y"`
+}
+
+// PathCubic is a cubic bezier to (X, Y) with control points (C1X, C1Y) and
+// (C2X, C2Y)
+type PathCubic struct {
+	C1X float32 `json:"c1x"`
+	C1Y float32 `json:"c1y"`
+	C2X float32 `json:"c2x"`
+	C2Y float32 `json:"c2y"`
+	X   float32 `json:"x"`
+	Y   float32 `json:"y"`
+}
+
+// Stroke is an outline Width logical pixels wide, centered on a path
+type Stroke struct {
+	Width float32  `json:"width"`
+	Color uint32   `json:"color"`
+	Cap   LineCap  `json:"cap,omitempty"`  // Empty = Butt
+	Join  LineJoin `json:"join,omitempty"` // Empty = Miter
 }
 
 type DrawShadowCmd struct {
@@ -347,6 +660,50 @@ type DrawShadowCmd struct {
 	CornerRadii [4]float32 `json:"corner_radii"`
 }
 
+// CaretCmd draws a text caret that the engine blinks on its own frame clock,
+// so no RedrawAfterMs is needed to animate it
+type CaretCmd struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width,omitempty"` // 0 = 1 logical pixel
+	Height float32 `json:"height"`
+	Color  uint32  `json:"color"`
+	// Time shown (and then hidden) per blink; 0 keeps the caret solid
+	BlinkMs uint32 `json:"blink_ms"`
+}
+
+// RectBounds is a rect's position and size in logical pixels
+type RectBounds struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// FocusRingCmd draws a dashed ring just outside a rect. With a non-zero
+// Speed the engine marches the dashes on its own frame clock and keeps
+// redrawing while the ring is submitted.
+type FocusRingCmd struct {
+	Rect        RectBounds `json:"rect"`
+	CornerRadii [4]float32 `json:"corner_radii"`
+	Color       uint32     `json:"color"`
+	Thickness   float32    `json:"thickness,omitempty"` // 0 = 2 logical pixels
+	Dash        float32    `json:"dash,omitempty"`      // dash and gap length; 0 = 4 logical pixels
+	Offset      float32    `json:"offset,omitempty"`    // shifts the dash pattern along the outline
+	Speed       float32    `json:"speed,omitempty"`     // logical pixels per second clockwise; 0 = static
+}
+
+// SpinnerCmd draws an indeterminate progress ring that the engine turns on
+// its own frame clock, redrawing while the spinner is submitted
+type SpinnerCmd struct {
+	CenterX   float32 `json:"center_x"`
+	CenterY   float32 `json:"center_y"`
+	Radius    float32 `json:"radius"`
+	Color     uint32  `json:"color"`
+	Speed     float32 `json:"speed"`               // revolutions per second
+	Thickness float32 `json:"thickness,omitempty"` // 0 = a fifth of the radius
+}
+
 type ClearCmd struct {
 	R uint8 `json:"r"`
 	G uint8 `json:"g"`
@@ -361,6 +718,22 @@ type PushClipCmd struct {
 	Height float32 `json:"height"`
 }
 
+// PushRoundedClipCmd clips to a rounded rectangle. CornerRadii are
+// top-left, top-right, bottom-right, bottom-left.
+type PushRoundedClipCmd struct {
+	X           float32    `json:"x"`
+	Y           float32    `json:"y"`
+	Width       float32    `json:"width"`
+	Height      float32    `json:"height"`
+	CornerRadii [4]float32 `json:"corner_radii"`
+}
+
+// PushTransformCmd holds the [a, b, c, d, e, f] matrix of a PushTransform,
+// in CSS matrix() order.
+type PushTransformCmd struct {
+	Matrix [6]float32 `json:"matrix"`
+}
+
 type TextureID = int32
 
 type TextAlign string
@@ -370,6 +743,8 @@ const (
 	TextAlignCenter  TextAlign = "Center"
 	TextAlignRight   TextAlign = "Right"
 	TextAlignJustify TextAlign = "Justify"
+	TextAlignStart   TextAlign = "Start" // Left in LTR text, right in RTL text
+	TextAlignEnd     TextAlign = "End"   // Right in LTR text, left in RTL text
 )
 
 type VerticalAlign string
@@ -407,10 +782,69 @@ const (
 	WhiteSpacePreWrap WhiteSpace = "PreWrap"
 )
 
+// TextAnchor controls how DrawText's y coordinate is interpreted
+type TextAnchor string
+
+const (
+	TextAnchorTopLeft  TextAnchor = "TopLeft"
+	TextAnchorBaseline TextAnchor = "Baseline"
+	TextAnchorCenter   TextAnchor = "Center"
+)
+
+// TextDirection is the paragraph direction text is reordered and aligned in.
+// Auto takes it from the first strong character (Arabic, Hebrew, Latin...).
+type TextDirection string
+
+const (
+	TextDirectionAuto TextDirection = "Auto"
+	TextDirectionLtr  TextDirection = "Ltr"
+	TextDirectionRtl  TextDirection = "Rtl"
+)
+
 // ============================================================================
 // Font Configuration
 // ============================================================================
 
+// DrawRichTextCmd draws a paragraph of text runs in different colors and
+// fonts with inline images, wrapped together on shared baselines. X, Y is
+// the paragraph's top-left; Layout supplies the wrap width, line height,
+// alignment and line limit. Use DrawText for single-style text.
+type DrawRichTextCmd struct {
+	X      float32          `json:"x"`
+	Y      float32          `json:"y"`
+	Runs   []TextRun        `json:"runs"`
+	Layout TextLayoutConfig `json:"layout"`
+	// Gradient, if set, replaces the text runs' colors. It spans the
+	// paragraph box (all lines), across runs; images are not tinted.
+	Gradient *Gradient `json:"gradient,omitempty"`
+}
+
+// TextRun is one run of a DrawRichText paragraph: text when Text is set,
+// otherwise an inline image. Build runs with RichTextRun and RichImageRun.
+type TextRun struct {
+	Text  *string         `json:"text,omitempty"`
+	Color uint32          `json:"color"`
+	Font  *FontDescriptor `json:"font,omitempty"`
+
+	ImageTextureID uint32  `json:"image_texture_id"`
+	Width          float32 `json:"width"`
+	Height         float32 `json:"height"`
+	// How far the image's bottom edge sits below the baseline (0 rests it
+	// on the baseline)
+	BaselineOffset float32 `json:"baseline_offset"`
+}
+
+// RichTextRun is a run of text in one color and font.
+func RichTextRun(text string, color uint32, font FontDescriptor) TextRun {
+	return TextRun{Text: &text, Color: color, Font: &font}
+}
+
+// RichImageRun is an inline image of a loaded texture, width x height
+// logical pixels, with its bottom baselineOffset below the baseline.
+func RichImageRun(textureID uint32, width, height, baselineOffset float32) TextRun {
+	return TextRun{ImageTextureID: textureID, Width: width, Height: height, BaselineOffset: baselineOffset}
+}
+
 type FontDescriptor struct {
 	Source FontSource `json:"source"`
 	Weight uint16     `json:"weight"`
@@ -419,8 +853,16 @@ type FontDescriptor struct {
 }
 
 type FontSource struct {
-	System  *string `json:"System,omitempty"`
-	Bundled *string `json:"Bundled,omitempty"`
+	System             *string           `json:"System,omitempty"`
+	Bundled            *string           `json:"Bundled,omitempty"`
+	Memory             *MemoryFontSource `json:"Memory,omitempty"`
+	SystemWithFallback []string          `json:"SystemWithFallback,omitempty"`
+}
+
+// MemoryFontSource refers to font bytes registered with RegisterFontMemory
+type MemoryFontSource struct {
+	Name     string `json:"name"`
+	DataHash uint64 `json:"data_hash"`
 }
 
 type FontStyle string
@@ -470,6 +912,10 @@ func SystemFontBlack(name string, size float32) FontDescriptor {
 	return FontDescriptor{Source: FontSource{System: &name}, Weight: 900, Style: FontStyleNormal, Size: size}
 }
 
+func SystemFontWithFallback(names []string, size float32) FontDescriptor {
+	return FontDescriptor{Source: FontSource{SystemWithFallback: names}, Weight: 400, Style: FontStyleNormal, Size: size}
+}
+
 func BundledFont(path string, size float32) FontDescriptor {
 	return FontDescriptor{Source: FontSource{Bundled: &path}, Weight: 400, Style: FontStyleNormal, Size: size}
 }
@@ -478,6 +924,10 @@ func BundledFontWithWeight(path string, size float32, weight uint16) FontDescrip
 	return FontDescriptor{Source: FontSource{Bundled: &path}, Weight: weight, Style: FontStyleNormal, Size: size}
 }
 
+func MemoryFont(name string, hash uint64, size float32) FontDescriptor {
+	return FontDescriptor{Source: FontSource{Memory: &MemoryFontSource{Name: name, DataHash: hash}}, Weight: 400, Style: FontStyleNormal, Size: size}
+}
+
 // ============================================================================
 // Text Layout Configuration
 // ============================================================================
@@ -494,6 +944,8 @@ type TextLayoutConfig struct {
 	WordBreak     WordBreak     `json:"word_break"`
 	Overflow      TextOverflow  `json:"overflow"`
 	WhiteSpace    WhiteSpace    `json:"white_space"`
+	Anchor        TextAnchor    `json:"anchor,omitempty"`         // Empty = TopLeft
+	BaseDirection TextDirection `json:"base_direction,omitempty"` // Empty = Auto
 }
 
 func DefaultTextLayout() TextLayoutConfig {
@@ -618,6 +1070,76 @@ func RoundedRectWithBorder(x, y, width, height float32, color uint32, radii [4]f
 	}
 }
 
+// Line draws a line from (x1, y1) to (x2, y2)
+func Line(x1, y1, x2, y2, width float32, color uint32, cap LineCap) RenderCommand {
+	return RenderCommand{
+		DrawLine: &DrawLineCmd{X1: x1, Y1: y1, X2: x2, Y2: y2, Width: width, Color: color, Cap: cap},
+	}
+}
+
+// Arc draws an arc from startAngle to endAngle (radians clockwise from
+// 3 o'clock) around (cx, cy). A thickness of 0 fills the wedge, for pie
+// slices; otherwise the stroke lies inside radius, for spinners and gauges.
+func Arc(cx, cy, radius, startAngle, endAngle, thickness float32, color uint32) RenderCommand {
+	return RenderCommand{
+		DrawArc: &DrawArcCmd{
+			CX: cx, CY: cy, Radius: radius,
+			StartAngle: startAngle, EndAngle: endAngle,
+			Thickness: thickness, Color: color,
+		},
+	}
+}
+
+// Circle draws an antialiased filled circle: a full-turn Arc
+func Circle(cx, cy, radius float32, color uint32) RenderCommand {
+	return Arc(cx, cy, radius, 0, 2*math.Pi, 0, color)
+}
+
+// MoveTo starts a new subpath at (x, y)
+func MoveTo(x, y float32) PathSegment {
+	return PathSegment{MoveTo: &PathPoint{X: x, Y: y}}
+}
+
+// LineTo draws a straight line to (x, y)
+func LineTo(x, y float32) PathSegment {
+	return PathSegment{LineTo: &PathPoint{X: x, Y: y}}
+}
+
+// QuadTo draws a quadratic bezier to (x, y) bending towards (cx, cy)
+func QuadTo(cx, cy, x, y float32) PathSegment {
+	return PathSegment{QuadTo: &PathQuad{CX: cx, CY: cy, X: x, Y: y}}
+}
+
+// CubicTo draws a cubic bezier to (x, y) with control points (c1x, c1y)
+// and (c2x, c2y)
+func CubicTo(c1x, c1y, c2x, c2y, x, y float32) PathSegment {
+	return PathSegment{CubicTo: &PathCubic{C1X: c1x, C1Y: c1y, C2X: c2x, C2Y: c2y, X: x, Y: y}}
+}
+
+// ClosePath closes the subpath with a line back to its start
+func ClosePath() PathSegment {
+	return PathSegment{Close: &struct{}{}}
+}
+
+// FillPath fills the shape outlined by segments, e.g. a speech bubble tail
+// or a vector icon
+func FillPath(segments []PathSegment, color uint32, rule FillRule) RenderCommand {
+	return RenderCommand{
+		DrawPath: &DrawPathCmd{Segments: segments, Fill: &color, FillRule: rule},
+	}
+}
+
+// StrokePath draws a line width logical pixels wide along segments, e.g. a
+// signature stroke
+func StrokePath(segments []PathSegment, width float32, color uint32, cap LineCap, join LineJoin) RenderCommand {
+	return RenderCommand{
+		DrawPath: &DrawPathCmd{
+			Segments: segments,
+			Stroke:   &Stroke{Width: width, Color: color, Cap: cap, Join: join},
+		},
+	}
+}
+
 func Shadow(x, y, width, height, blur float32, color uint32, offsetX, offsetY float32, radii [4]float32) RenderCommand {
 	return RenderCommand{
 		DrawShadow: &DrawShadowCmd{
@@ -697,6 +1219,18 @@ func ImageWithSourceRectAndCornerRadii(textureID TextureID, x, y, width, height
 	}
 }
 
+// ImageWithOpacity draws an image at an opacity of its own (0-1), multiplied
+// with SetOpacity. For a cross-fade, draw the outgoing image at 1-t and the
+// incoming one at t.
+func ImageWithOpacity(textureID TextureID, x, y, width, height, opacity float32) RenderCommand {
+	return RenderCommand{
+		DrawImage: &DrawImageCmd{
+			X: x, Y: y, Width: width, Height: height,
+			TextureID: uint32(textureID), Opacity: &opacity,
+		},
+	}
+}
+
 func Sprite(textureID TextureID, x, y, width, height float32, spriteX, spriteY, cols, rows int) RenderCommand {
 	spriteWidth := 1.0 / float32(cols)
 	spriteHeight := 1.0 / float32(rows)
@@ -712,18 +1246,125 @@ func Sprite(textureID TextureID, x, y, width, height float32, spriteX, spriteY,
 	}
 }
 
+// ImageRegion draws the srcWidth x srcHeight pixel region at (srcX, srcY) of
+// a texture, such as one cell of a sprite atlas
+func ImageRegion(textureID TextureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight float32) RenderCommand {
+	return RenderCommand{
+		DrawImage: &DrawImageCmd{
+			X: x, Y: y, Width: width, Height: height,
+			TextureID: uint32(textureID),
+			Src:       &RectBounds{X: srcX, Y: srcY, Width: srcWidth, Height: srcHeight},
+		},
+	}
+}
+
+// ImageRegionTinted is ImageRegion with each texel multiplied by tint
+// (0xRRGGBBAA), e.g. to color a white monochrome icon
+func ImageRegionTinted(textureID TextureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight float32, tint uint32) RenderCommand {
+	cmd := ImageRegion(textureID, x, y, width, height, srcX, srcY, srcWidth, srcHeight)
+	cmd.DrawImage.Tint = &tint
+	return cmd
+}
+
+// NinePatch draws a texture stretched over a rect with its insets
+// ([top, right, bottom, left] texture pixels) kept unscaled, e.g. a chat
+// bubble or button background from one small image
+func NinePatch(textureID TextureID, x, y, width, height float32, insets [4]float32) RenderCommand {
+	return RenderCommand{
+		DrawNinePatch: &DrawNinePatchCmd{
+			TextureID: uint32(textureID),
+			DestRect:  RectBounds{X: x, Y: y, Width: width, Height: height},
+			Insets:    insets,
+		},
+	}
+}
+
 func PushClip(x, y, width, height float32) RenderCommand {
 	return RenderCommand{
 		PushClip: &PushClipCmd{X: x, Y: y, Width: width, Height: height},
 	}
 }
 
+// PushRoundedClip clips everything until the matching PopClip to a rounded
+// rectangle with per-corner radii (top-left, top-right, bottom-right,
+// bottom-left). Nested clips intersect; PopClip restores the enclosing one.
+func PushRoundedClip(x, y, width, height float32, cornerRadii [4]float32) RenderCommand {
+	return RenderCommand{
+		PushRoundedClip: &PushRoundedClipCmd{X: x, Y: y, Width: width, Height: height, CornerRadii: cornerRadii},
+	}
+}
+
 func PopClip() RenderCommand {
 	return RenderCommand{
 		PopClip: &struct{}{},
 	}
 }
 
+// PushTransform transforms everything drawn until the matching
+// PopTransform, clips and text included: (x, y) maps to
+// (a*x + c*y + e, b*x + d*y + f). Transforms nest.
+func PushTransform(a, b, c, d, e, f float32) RenderCommand {
+	return RenderCommand{
+		PushTransform: &PushTransformCmd{Matrix: [6]float32{a, b, c, d, e, f}},
+	}
+}
+
+// PushPanZoom is the PushTransform of a zoomable canvas: content is scaled
+// by zoom about the origin, then moved by (panX, panY).
+func PushPanZoom(panX, panY, zoom float32) RenderCommand {
+	return PushTransform(zoom, 0, 0, zoom, panX, panY)
+}
+
+func PopTransform() RenderCommand {
+	return RenderCommand{
+		PopTransform: &struct{}{},
+	}
+}
+
+// PushOpacityLayer draws everything until the matching PopOpacityLayer as
+// one group faded to alpha (0-1). Unlike SetOpacity, which fades each draw
+// on its own, overlapping draws in the group don't show through each other.
+// Layers nest; clips pushed outside the layer clip the whole group.
+func PushOpacityLayer(alpha float32) RenderCommand {
+	return RenderCommand{
+		PushOpacityLayer: &alpha,
+	}
+}
+
+func PopOpacityLayer() RenderCommand {
+	return RenderCommand{
+		PopOpacityLayer: &struct{}{},
+	}
+}
+
+// Caret draws a blinking text caret. It restarts solid whenever it moves.
+func Caret(x, y, height float32, color uint32, blinkMs uint32) RenderCommand {
+	return RenderCommand{
+		Caret: &CaretCmd{X: x, Y: y, Height: height, Color: color, BlinkMs: blinkMs},
+	}
+}
+
+// FocusRing draws a dashed focus ring around a rect, marching its dashes at
+// speed logical pixels per second (0 for a static ring).
+func FocusRing(x, y, width, height float32, radii [4]float32, color uint32, speed float32) RenderCommand {
+	return RenderCommand{
+		FocusRing: &FocusRingCmd{
+			Rect:        RectBounds{X: x, Y: y, Width: width, Height: height},
+			CornerRadii: radii,
+			Color:       color,
+			Speed:       speed,
+		},
+	}
+}
+
+// Spinner draws an indeterminate progress spinner of the given outer radius
+// around (cx, cy), turning at speed revolutions per second.
+func Spinner(cx, cy, radius float32, color uint32, speed float32) RenderCommand {
+	return RenderCommand{
+		Spinner: &SpinnerCmd{CenterX: cx, CenterY: cy, Radius: radius, Color: color, Speed: speed},
+	}
+}
+
 func BeginScrollView(x, y, width, height, scrollX, scrollY float32) RenderCommand {
 	return RenderCommand{
 		BeginScrollView: &BeginScrollViewCmd{
@@ -817,9 +1458,51 @@ func GetSafeAreaInsets() SafeAreaInsets {
 // Core Functions
 // ============================================================================
 
+// maxFixedStepsPerFrame caps the EventFixedUpdate steps of one frame; a
+// longer backlog (a background tab) is dropped, as on native
+const maxFixedStepsPerFrame = 8
+
+// webFrameClock times frames from requestAnimationFrame timestamps, the
+// web counterpart of the engine's frame pacer
+type webFrameClock struct {
+	lastMs      float64 // 0 before the first frame
+	stepMs      float64 // 0 outside fixed-timestep mode
+	accumulator float64
+}
+
+// beginFrame starts a frame at the rAF timestamp nowMs, first sending
+// EventFixedUpdate for each fixed step that fell due, and returns the
+// seconds since the last frame
+func (c *webFrameClock) beginFrame(nowMs float64) float64 {
+	dtMs := 0.0
+	if c.lastMs > 0 && nowMs > c.lastMs {
+		dtMs = nowMs - c.lastMs
+	}
+	c.lastMs = nowMs
+	if c.stepMs <= 0 {
+		return dtMs / 1000
+	}
+
+	c.accumulator += dtMs
+	steps := 0
+	for c.accumulator >= c.stepMs && steps < maxFixedStepsPerFrame {
+		currentHandler(Event{Type: EventFixedUpdate, FrameDt: c.stepMs / 1000})
+		c.accumulator -= c.stepMs
+		steps++
+	}
+	if c.accumulator >= c.stepMs {
+		c.accumulator = math.Mod(c.accumulator, c.stepMs)
+	}
+	return dtMs / 1000
+}
+
 // Run starts the application event loop
 func Run(config AppConfig, handler EventHandler) error {
 	currentHandler = handler
+	frameClock = webFrameClock{}
+	if config.FixedTimestepHz > 0 {
+		frameClock.stepMs = 1000 / float64(config.FixedTimestepHz)
+	}
 
 	// Set up canvas
 	canvas := jsDocument.Call("getElementById", "centered-canvas")
@@ -893,7 +1576,7 @@ func Run(config AppConfig, handler EventHandler) error {
 		}
 
 		// Request redraw event
-		event := Event{Type: EventRedrawRequested}
+		event := Event{Type: EventRedrawRequested, FrameDt: frameClock.beginFrame(args[0].Float())}
 		resp := currentHandler(event)
 
 		if resp.Exit {
@@ -965,16 +1648,119 @@ func setupEventListeners(canvas js.Value) {
 		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
 			renderFrame(resp.ImmediateCommands)
 		}
+		autoReleaseWebPointerCapture(e)
 		return nil
 	}))
 
+	// Pointer capture: moves and releases off the canvas reach the document
+	// (past the browser window too, during a drag). Ones over the canvas
+	// were already handled above.
+	jsDocument.Call("addEventListener", "mousemove", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		e := args[0]
+		if !webPointerCapture || e.Get("target").Equal(canvas) {
+			return nil
+		}
+		rect := canvas.Call("getBoundingClientRect")
+		x := e.Get("clientX").Float() - rect.Get("left").Float()
+		y := e.Get("clientY").Float() - rect.Get("top").Float()
+
+		event := Event{Type: EventMouseMoved, Data1: x, Data2: y}
+		resp := currentHandler(event)
+		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
+			renderFrame(resp.ImmediateCommands)
+		}
+		return nil
+	}))
+	jsDocument.Call("addEventListener", "mouseup", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		e := args[0]
+		if !webPointerCapture || e.Get("target").Equal(canvas) {
+			return nil
+		}
+		rect := canvas.Call("getBoundingClientRect")
+		x := e.Get("clientX").Float() - rect.Get("left").Float()
+		y := e.Get("clientY").Float() - rect.Get("top").Float()
+		button := e.Get("button").Int()
+
+		event := Event{Type: EventMouseReleased, Data1: x, Data2: y, Data3: float64(button)}
+		resp := currentHandler(event)
+		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
+			renderFrame(resp.ImmediateCommands)
+		}
+		autoReleaseWebPointerCapture(e)
+		return nil
+	}))
+
+	// Files dragged in from the desktop. Canvas has no children, so
+	// dragenter and dragleave come in pairs.
+	fileDrag := func(eventType EventType) js.Func {
+		return js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+			e := args[0]
+			if !dragCarriesFiles(e) {
+				return nil
+			}
+			// Without preventDefault the browser opens the file instead
+			e.Call("preventDefault")
+			rect := canvas.Call("getBoundingClientRect")
+			x := e.Get("clientX").Float() - rect.Get("left").Float()
+			y := e.Get("clientY").Float() - rect.Get("top").Float()
+
+			event := Event{Type: eventType, Data1: x, Data2: y}
+			switch eventType {
+			case EventFileDragEnter:
+				event.Text = "[]"
+			case EventFileDrop:
+				files := e.Get("dataTransfer").Get("files")
+				names := make([]string, files.Length())
+				for i := range names {
+					names[i] = files.Index(i).Get("name").String()
+				}
+				namesJSON, _ := json.Marshal(names)
+				event.Text = string(namesJSON)
+			case EventFileDragCancel:
+				event.Data1, event.Data2 = 0, 0
+			}
+			resp := currentHandler(event)
+			if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
+				renderFrame(resp.ImmediateCommands)
+			}
+			return nil
+		})
+	}
+	canvas.Call("addEventListener", "dragenter", fileDrag(EventFileDragEnter))
+	canvas.Call("addEventListener", "dragover", fileDrag(EventFileDragOver))
+	canvas.Call("addEventListener", "dragleave", fileDrag(EventFileDragCancel))
+	canvas.Call("addEventListener", "drop", fileDrag(EventFileDrop))
+
+	// Focus and page visibility
+	windowState := func(eventType EventType, data1 func() float64) js.Func {
+		return js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+			resp := currentHandler(Event{Type: eventType, Data1: data1()})
+			if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
+				renderFrame(resp.ImmediateCommands)
+			}
+			return nil
+		})
+	}
+	none := func() float64 { return 0 }
+	jsWindow.Call("addEventListener", "focus", windowState(EventFocusGained, none))
+	jsWindow.Call("addEventListener", "blur", windowState(EventFocusLost, none))
+	jsDocument.Call("addEventListener", "visibilitychange", windowState(EventOccluded, func() float64 {
+		if jsDocument.Get("hidden").Bool() {
+			return 1
+		}
+		return 0
+	}))
+
 	// Keyboard events (on document for global capture)
 	jsDocument.Call("addEventListener", "keydown", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		e := args[0]
 		keyCode := e.Get("keyCode").Int()
-		mods := getModifiers(e)
+		mods := uint32(getModifiers(e))
+		if e.Get("repeat").Bool() {
+			mods |= keyRepeat
+		}
 
-		event := Event{Type: EventKeyPressed, Data1: float64(keyCode), Data2: float64(mods)}
+		event := Event{Type: EventKeyPressed, Data1: float64(keyCode), Data2: float64(mods), Text: e.Get("key").String()}
 		resp := currentHandler(event)
 		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
 			renderFrame(resp.ImmediateCommands)
@@ -998,7 +1784,7 @@ func setupEventListeners(canvas js.Value) {
 		keyCode := e.Get("keyCode").Int()
 		mods := getModifiers(e)
 
-		event := Event{Type: EventKeyReleased, Data1: float64(keyCode), Data2: float64(mods)}
+		event := Event{Type: EventKeyReleased, Data1: float64(keyCode), Data2: float64(mods), Text: e.Get("key").String()}
 		resp := currentHandler(event)
 		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
 			renderFrame(resp.ImmediateCommands)
@@ -1030,6 +1816,7 @@ func setupEventListeners(canvas js.Value) {
 	canvas.Call("addEventListener", "touchstart", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		e := args[0]
 		e.Call("preventDefault")
+		dispatchTouches(canvas, e, TouchBegan)
 		touches := e.Get("touches")
 		if touches.Length() > 0 {
 			touch := touches.Index(0)
@@ -1049,6 +1836,7 @@ func setupEventListeners(canvas js.Value) {
 	canvas.Call("addEventListener", "touchmove", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		e := args[0]
 		e.Call("preventDefault")
+		dispatchTouches(canvas, e, TouchMoved)
 		touches := e.Get("touches")
 		if touches.Length() > 0 {
 			touch := touches.Index(0)
@@ -1066,7 +1854,7 @@ func setupEventListeners(canvas js.Value) {
 	}))
 
 	canvas.Call("addEventListener", "touchend", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
-		_ = args[0] // Touch event data available if needed
+		dispatchTouches(canvas, args[0], TouchEnded)
 		event := Event{Type: EventTouchEnd}
 		resp := currentHandler(event)
 		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
@@ -1075,6 +1863,11 @@ func setupEventListeners(canvas js.Value) {
 		return nil
 	}))
 
+	canvas.Call("addEventListener", "touchcancel", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		dispatchTouches(canvas, args[0], TouchCancelled)
+		return nil
+	}))
+
 	// Resize
 	jsWindow.Call("addEventListener", "resize", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		// Update canvas size to match container
@@ -1123,6 +1916,27 @@ func setupEventListeners(canvas js.Value) {
 	}))
 }
 
+// dispatchTouches sends an EventTouch for each finger that changed in a
+// browser touch event, so multi-touch gestures can follow every finger
+func dispatchTouches(canvas js.Value, e js.Value, phase TouchPhase) {
+	rect := canvas.Call("getBoundingClientRect")
+	changed := e.Get("changedTouches")
+	for i := 0; i < changed.Length(); i++ {
+		touch := changed.Index(i)
+		event := Event{
+			Type:       EventTouch,
+			Data1:      touch.Get("clientX").Float() - rect.Get("left").Float(),
+			Data2:      touch.Get("clientY").Float() - rect.Get("top").Float(),
+			TouchPhase: phase,
+			TouchID:    uint64(touch.Get("identifier").Int()),
+		}
+		resp := currentHandler(event)
+		if resp.RequestRedraw && len(resp.ImmediateCommands) > 0 {
+			renderFrame(resp.ImmediateCommands)
+		}
+	}
+}
+
 func getModifiers(e js.Value) Modifiers {
 	var mods Modifiers
 	if e.Get("shiftKey").Bool() {
@@ -1146,8 +1960,13 @@ func renderFrame(commands []RenderCommand) {
 	if ctx.IsUndefined() {
 		return
 	}
+	drawCommands(ctx, commands)
+}
 
-	for _, cmd := range commands {
+// drawCommands draws commands onto a Canvas 2D context
+func drawCommands(ctx js.Value, commands []RenderCommand) {
+	for i := 0; i < len(commands); i++ {
+		cmd := commands[i]
 		switch {
 		case cmd.Clear != nil:
 			// Use logical dimensions (context is already scaled for HiDPI)
@@ -1163,18 +1982,44 @@ func renderFrame(commands []RenderCommand) {
 		case cmd.DrawText != nil:
 			drawText(ctx, cmd.DrawText)
 
+		case cmd.DrawRichText != nil:
+			drawRichText(ctx, cmd.DrawRichText)
+
+		case cmd.DrawLine != nil:
+			drawLine(ctx, cmd.DrawLine)
+
+		case cmd.DrawArc != nil:
+			drawArc(ctx, cmd.DrawArc)
+
+		case cmd.DrawPath != nil:
+			drawPath(ctx, cmd.DrawPath)
+
 		case cmd.DrawShadow != nil:
 			drawShadow(ctx, cmd.DrawShadow)
 
 		case cmd.DrawImage != nil:
 			drawImage(ctx, cmd.DrawImage)
 
+		case cmd.DrawNinePatch != nil:
+			// Image textures aren't drawn on web yet, so the whole rect stands in
+			r := cmd.DrawNinePatch.DestRect
+			drawImage(ctx, &DrawImageCmd{X: r.X, Y: r.Y, Width: r.Width, Height: r.Height, TextureID: cmd.DrawNinePatch.TextureID})
+
 		case cmd.PushClip != nil:
 			ctx.Call("save")
 			ctx.Call("beginPath")
 			ctx.Call("rect", cmd.PushClip.X, cmd.PushClip.Y, cmd.PushClip.Width, cmd.PushClip.Height)
 			ctx.Call("clip")
 
+		case cmd.PushRoundedClip != nil:
+			rc := cmd.PushRoundedClip
+			ctx.Call("save")
+			ctx.Call("beginPath")
+			ctx.Call("roundRect", rc.X, rc.Y, rc.Width, rc.Height, []interface{}{
+				rc.CornerRadii[0], rc.CornerRadii[1], rc.CornerRadii[2], rc.CornerRadii[3],
+			})
+			ctx.Call("clip")
+
 		case cmd.PopClip != nil:
 			ctx.Call("restore")
 
@@ -1189,6 +2034,19 @@ func renderFrame(commands []RenderCommand) {
 		case cmd.EndScrollView != nil:
 			ctx.Call("restore")
 
+		case cmd.PushTransform != nil:
+			m := cmd.PushTransform.Matrix
+			ctx.Call("save")
+			ctx.Call("transform", m[0], m[1], m[2], m[3], m[4], m[5])
+
+		case cmd.PopTransform != nil:
+			ctx.Call("restore")
+
+		case cmd.PushOpacityLayer != nil:
+			end := matchingPopOpacityLayer(commands, i)
+			drawOpacityLayer(ctx, *cmd.PushOpacityLayer, commands[i+1:end])
+			i = end
+
 		case cmd.DrawVideo != nil:
 			drawVideo(ctx, cmd.DrawVideo)
 
@@ -1198,6 +2056,44 @@ func renderFrame(commands []RenderCommand) {
 	}
 }
 
+// matchingPopOpacityLayer returns the index of the PopOpacityLayer closing
+// the layer pushed at commands[push], or len(commands) if it's never closed
+func matchingPopOpacityLayer(commands []RenderCommand, push int) int {
+	depth := 0
+	for i := push + 1; i < len(commands); i++ {
+		switch {
+		case commands[i].PushOpacityLayer != nil:
+			depth++
+		case commands[i].PopOpacityLayer != nil:
+			if depth == 0 {
+				return i
+			}
+			depth--
+		}
+	}
+	return len(commands)
+}
+
+// drawOpacityLayer draws commands onto a transparent canvas the size of
+// ctx's, then composites that onto ctx at alpha, so the group fades as one.
+// ctx's clip applies to the composite; clips inside the layer end with it.
+func drawOpacityLayer(ctx js.Value, alpha float32, commands []RenderCommand) {
+	canvas := ctx.Get("canvas")
+	layer := jsDocument.Call("createElement", "canvas")
+	layer.Set("width", canvas.Get("width"))
+	layer.Set("height", canvas.Get("height"))
+	layerCtx := layer.Call("getContext", "2d")
+	// Same transform as ctx, HiDPI scale included
+	layerCtx.Call("setTransform", ctx.Call("getTransform"))
+	drawCommands(layerCtx, commands)
+
+	ctx.Call("save")
+	ctx.Call("setTransform", 1, 0, 0, 1, 0, 0)
+	ctx.Set("globalAlpha", ctx.Get("globalAlpha").Float()*math.Max(0, math.Min(1, float64(alpha))))
+	ctx.Call("drawImage", layer, 0, 0)
+	ctx.Call("restore")
+}
+
 func drawVideo(ctx js.Value, cmd *DrawVideoCmd) {
 	videoEl := VideoGetElement(cmd.VideoID)
 	if videoEl.IsUndefined() || videoEl.IsNull() {
@@ -1290,13 +2186,46 @@ func drawRect(ctx js.Value, cmd *DrawRectCmd) {
 
 	ctx.Call("closePath")
 	ctx.Set("fillStyle", colorToCSS(cmd.Color))
-	ctx.Call("fill")
+	if cmd.Shadow != nil {
+		// The fill casts the shadow; canvas shadows have no spread
+		ctx.Call("save")
+		ctx.Set("shadowColor", colorToCSS(cmd.Shadow.Color))
+		ctx.Set("shadowBlur", cmd.Shadow.BlurRadius)
+		ctx.Set("shadowOffsetX", cmd.Shadow.OffsetX)
+		ctx.Set("shadowOffsetY", cmd.Shadow.OffsetY)
+		ctx.Call("fill")
+		ctx.Call("restore")
+	} else {
+		ctx.Call("fill")
+	}
 
 	// Draw border if present
 	if cmd.Border != nil && cmd.Border.Width > 0 {
 		ctx.Set("strokeStyle", colorToCSS(cmd.Border.Color))
 		ctx.Set("lineWidth", cmd.Border.Width)
+		switch cmd.Border.Style {
+		case BorderStyleDashed:
+			dash := cmd.Border.DashLength
+			if dash <= 0 {
+				dash = cmd.Border.Width * 3
+			}
+			gap := cmd.Border.GapLength
+			if gap <= 0 {
+				gap = dash
+			}
+			ctx.Call("setLineDash", []interface{}{dash, gap})
+		case BorderStyleDotted:
+			gap := cmd.Border.GapLength
+			if gap <= 0 {
+				gap = cmd.Border.Width
+			}
+			// Zero-length dashes with round caps draw as dots
+			ctx.Set("lineCap", "round")
+			ctx.Call("setLineDash", []interface{}{0, cmd.Border.Width + gap})
+		}
 		ctx.Call("stroke")
+		ctx.Call("setLineDash", []interface{}{})
+		ctx.Set("lineCap", "butt")
 	}
 }
 
@@ -1320,6 +2249,13 @@ func drawText(ctx js.Value, cmd *DrawTextCmd) {
 			// Font not loaded yet - log warning and use system font
 			fmt.Printf("Warning: bundled font '%s' not loaded, using system font\n", path)
 		}
+	} else if cmd.Font.Source.Memory != nil {
+		if family := getBundledFontFamily(cmd.Font.Source.Memory.Name); family != "" {
+			fontFamily = fmt.Sprintf("'%s', system-ui, sans-serif", family)
+		}
+	} else if len(cmd.Font.Source.SystemWithFallback) > 0 {
+		// The browser falls back per character through the list itself
+		fontFamily = fallbackFontFamily(cmd.Font.Source.SystemWithFallback)
 	} else if cmd.Font.Source.System != nil {
 		name := *cmd.Font.Source.System
 		switch name {
@@ -1358,34 +2294,199 @@ func drawText(ctx js.Value, cmd *DrawTextCmd) {
 	yOffset := (lineHeight - 1.0) * fontSize * 0.5
 	adjustedY := float64(cmd.Y) + float64(yOffset)
 
-	// Handle text alignment
-	// Canvas textAlign changes what the X coordinate means:
-	// - "left": X is the left edge of the text
-	// - "center": X is the center of the text
-	// - "right": X is the right edge of the text
-	// Our cmd.X is always the left edge, so we need to adjust for center/right
-	adjustedX := float64(cmd.X)
-	var maxWidth float64
-	if cmd.Layout.MaxWidth != nil {
-		maxWidth = float64(*cmd.Layout.MaxWidth)
+	// Baseline alignment puts y on the alphabetic baseline, as on native
+	if cmd.Layout.VerticalAlign == VerticalAlignBaseline || cmd.Layout.Anchor == TextAnchorBaseline {
+		ctx.Set("textBaseline", "alphabetic")
+		adjustedY = float64(cmd.Y)
+	}
+
+	// Handle text alignment
+	// Canvas textAlign changes what the X coordinate means:
+	// - "left": X is the left edge of the text
+	// - "center": X is the center of the text
+	// - "right": X is the right edge of the text
+	// Our cmd.X is always the left edge, so we need to adjust for center/right
+	adjustedX := float64(cmd.X)
+	var maxWidth float64
+	if cmd.Layout.MaxWidth != nil {
+		maxWidth = float64(*cmd.Layout.MaxWidth)
+	}
+
+	// The browser reorders bidi text itself; Start and End follow an
+	// explicit direction (Auto counts as LTR here)
+	alignment := cmd.Layout.Alignment
+	rtl := cmd.Layout.BaseDirection == TextDirectionRtl
+	if (alignment == TextAlignStart && rtl) || (alignment == TextAlignEnd && !rtl) {
+		alignment = TextAlignRight
+	}
+	switch cmd.Layout.BaseDirection {
+	case TextDirectionRtl:
+		ctx.Set("direction", "rtl")
+	case TextDirectionLtr:
+		ctx.Set("direction", "ltr")
+	default:
+		ctx.Set("direction", "inherit")
+	}
+
+	switch alignment {
+	case TextAlignCenter:
+		ctx.Set("textAlign", "center")
+		if maxWidth > 0 {
+			adjustedX = float64(cmd.X) + maxWidth/2
+		}
+	case TextAlignRight:
+		ctx.Set("textAlign", "right")
+		if maxWidth > 0 {
+			adjustedX = float64(cmd.X) + maxWidth
+		}
+	default:
+		ctx.Set("textAlign", "left")
+	}
+
+	ctx.Call("fillText", cmd.Text, adjustedX, adjustedY)
+}
+
+// drawRichText draws the runs left to right on one baseline. Like drawText,
+// the canvas fallback doesn't wrap.
+func drawRichText(ctx js.Value, cmd *DrawRichTextCmd) {
+	// Canvas has no font metrics here; take a font's ascent as 0.8em
+	const ascentEm = 0.8
+	var ascent float32
+	for _, run := range cmd.Runs {
+		if run.Text == nil {
+			ascent = max(ascent, run.Height-run.BaselineOffset)
+		} else if run.Font != nil {
+			ascent = max(ascent, run.Font.Size*ascentEm)
+		}
+	}
+	baseline := cmd.Y + ascent
+
+	x := cmd.X
+	for _, run := range cmd.Runs {
+		if run.Text == nil {
+			drawImage(ctx, &DrawImageCmd{
+				X:         x,
+				Y:         baseline + run.BaselineOffset - run.Height,
+				Width:     run.Width,
+				Height:    run.Height,
+				TextureID: run.ImageTextureID,
+			})
+			x += run.Width
+			continue
+		}
+		var font FontDescriptor
+		if run.Font != nil {
+			font = *run.Font
+		}
+		drawText(ctx, &DrawTextCmd{
+			X:      x,
+			Y:      baseline - font.Size*ascentEm,
+			Text:   *run.Text,
+			Font:   font,
+			Color:  run.Color,
+			Layout: TextLayoutConfig{LineHeight: 1},
+		})
+		// drawText left the run's font set
+		x += float32(ctx.Call("measureText", *run.Text).Get("width").Float())
+	}
+}
+
+func drawLine(ctx js.Value, cmd *DrawLineCmd) {
+	ctx.Call("save")
+	ctx.Set("strokeStyle", colorToCSS(cmd.Color))
+	ctx.Set("lineWidth", cmd.Width)
+	switch cmd.Cap {
+	case LineCapRound:
+		ctx.Set("lineCap", "round")
+	case LineCapSquare:
+		ctx.Set("lineCap", "square")
+	default:
+		ctx.Set("lineCap", "butt")
+	}
+
+	ctx.Call("beginPath")
+	ctx.Call("moveTo", cmd.X1, cmd.Y1)
+	ctx.Call("lineTo", cmd.X2, cmd.Y2)
+	ctx.Call("stroke")
+	ctx.Call("restore")
+}
+
+func drawPath(ctx js.Value, cmd *DrawPathCmd) {
+	ctx.Call("save")
+	ctx.Call("beginPath")
+	for _, seg := range cmd.Segments {
+		switch {
+		case seg.MoveTo != nil:
+			ctx.Call("moveTo", seg.MoveTo.X, seg.MoveTo.Y)
+		case seg.LineTo != nil:
+			ctx.Call("lineTo", seg.LineTo.X, seg.LineTo.Y)
+		case seg.QuadTo != nil:
+			q := seg.QuadTo
+			ctx.Call("quadraticCurveTo", q.CX, q.CY, q.X, q.Y)
+		case seg.CubicTo != nil:
+			c := seg.CubicTo
+			ctx.Call("bezierCurveTo", c.C1X, c.C1Y, c.C2X, c.C2Y, c.X, c.Y)
+		case seg.Close != nil:
+			ctx.Call("closePath")
+		}
 	}
 
-	switch cmd.Layout.Alignment {
-	case TextAlignCenter:
-		ctx.Set("textAlign", "center")
-		if maxWidth > 0 {
-			adjustedX = float64(cmd.X) + maxWidth/2
+	if cmd.Fill != nil {
+		ctx.Set("fillStyle", colorToCSS(*cmd.Fill))
+		if cmd.FillRule == FillRuleEvenOdd {
+			ctx.Call("fill", "evenodd")
+		} else {
+			ctx.Call("fill", "nonzero")
 		}
-	case TextAlignRight:
-		ctx.Set("textAlign", "right")
-		if maxWidth > 0 {
-			adjustedX = float64(cmd.X) + maxWidth
+	}
+	if s := cmd.Stroke; s != nil && s.Width > 0 {
+		ctx.Set("strokeStyle", colorToCSS(s.Color))
+		ctx.Set("lineWidth", s.Width)
+		switch s.Cap {
+		case LineCapRound:
+			ctx.Set("lineCap", "round")
+		case LineCapSquare:
+			ctx.Set("lineCap", "square")
+		default:
+			ctx.Set("lineCap", "butt")
 		}
-	default:
-		ctx.Set("textAlign", "left")
+		switch s.Join {
+		case LineJoinRound:
+			ctx.Set("lineJoin", "round")
+		case LineJoinBevel:
+			ctx.Set("lineJoin", "bevel")
+		default:
+			ctx.Set("lineJoin", "miter")
+			ctx.Set("miterLimit", 4)
+		}
+		ctx.Call("stroke")
 	}
+	ctx.Call("restore")
+}
 
-	ctx.Call("fillText", cmd.Text, adjustedX, adjustedY)
+func drawArc(ctx js.Value, cmd *DrawArcCmd) {
+	// Canvas arcs take a direction instead of sweeping either way
+	counterclockwise := cmd.EndAngle < cmd.StartAngle
+	ctx.Call("save")
+	ctx.Call("beginPath")
+	if cmd.Thickness <= 0 || cmd.Thickness >= cmd.Radius {
+		full := math.Abs(float64(cmd.EndAngle-cmd.StartAngle)) >= 2*math.Pi
+		if !full {
+			ctx.Call("moveTo", cmd.CX, cmd.CY)
+		}
+		ctx.Call("arc", cmd.CX, cmd.CY, cmd.Radius, cmd.StartAngle, cmd.EndAngle, counterclockwise)
+		ctx.Call("closePath")
+		ctx.Set("fillStyle", colorToCSS(cmd.Color))
+		ctx.Call("fill")
+	} else {
+		// Canvas strokes straddle the path; the stroke lies inside Radius
+		ctx.Call("arc", cmd.CX, cmd.CY, cmd.Radius-cmd.Thickness/2, cmd.StartAngle, cmd.EndAngle, counterclockwise)
+		ctx.Set("strokeStyle", colorToCSS(cmd.Color))
+		ctx.Set("lineWidth", cmd.Thickness)
+		ctx.Set("lineCap", "butt")
+		ctx.Call("stroke")
+	}
+	ctx.Call("restore")
 }
 
 func drawShadow(ctx js.Value, cmd *DrawShadowCmd) {
@@ -1432,6 +2533,17 @@ func drawImage(ctx js.Value, cmd *DrawImageCmd) {
 	hasRadius := radii[0] > 0 || radii[1] > 0 || radii[2] > 0 || radii[3] > 0
 	textureID := cmd.TextureID
 
+	if cmd.Opacity != nil {
+		prevAlpha := ctx.Get("globalAlpha")
+		ctx.Set("globalAlpha", prevAlpha.Float()*float64(*cmd.Opacity))
+		defer ctx.Set("globalAlpha", prevAlpha)
+	}
+
+	if cmd.Sampling == SamplingNearest {
+		ctx.Set("imageSmoothingEnabled", false)
+		defer ctx.Set("imageSmoothingEnabled", true)
+	}
+
 	// Check if this is a camera input (has offset bit set)
 	if textureID >= cameraTextureOffset {
 		cameraID := VideoInputID(textureID - cameraTextureOffset)
@@ -1541,7 +2653,7 @@ func RequestExit() {
 func RequestRedraw() {
 	// Trigger animation frame
 	jsWindow.Call("requestAnimationFrame", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
-		event := Event{Type: EventRedrawRequested}
+		event := Event{Type: EventRedrawRequested, FrameDt: frameClock.beginFrame(args[0].Float())}
 		resp := currentHandler(event)
 		if len(resp.ImmediateCommands) > 0 {
 			renderFrame(resp.ImmediateCommands)
@@ -1550,6 +2662,48 @@ func RequestRedraw() {
 	}))
 }
 
+// SetTargetFPS is not applicable for web; requestAnimationFrame paces frames.
+func SetTargetFPS(fps uint32) error { return nil }
+
+// Menu is a top-level menu of the application menu bar
+type Menu struct {
+	Title string     `json:"title"`
+	Items []MenuItem `json:"items,omitempty"`
+}
+
+// MenuItem is an entry of a Menu: a clickable item with a non-zero ID, a
+// separator, or a submenu (Items set)
+type MenuItem struct {
+	ID        uint32     `json:"id,omitempty"`
+	Title     string     `json:"title,omitempty"`
+	Key       string     `json:"key,omitempty"`
+	Modifiers []string   `json:"modifiers,omitempty"`
+	Disabled  bool       `json:"-"`
+	Checked   bool       `json:"checked,omitempty"`
+	Separator bool       `json:"separator,omitempty"`
+	Items     []MenuItem `json:"items,omitempty"`
+}
+
+// MarshalJSON writes Disabled as the engine's "enabled" field
+func (m MenuItem) MarshalJSON() ([]byte, error) {
+	type plain MenuItem
+	return json.Marshal(struct {
+		plain
+		Enabled bool `json:"enabled"`
+	}{plain(m), !m.Disabled})
+}
+
+// MenuSeparator returns a separator line for a menu
+func MenuSeparator() MenuItem {
+	return MenuItem{Separator: true}
+}
+
+// AppSetMenu is not applicable for web; pages have no menu bar.
+func AppSetMenu(menus []Menu) error { return nil }
+
+// SetVSync is not applicable for web; the browser always syncs to the display.
+func SetVSync(enabled bool) error { return nil }
+
 func WindowMinimize()          {} // Not applicable for web
 func WindowToggleMaximize()    {} // Could use Fullscreen API
 func WindowEnterFullscreen()   { jsDocument.Get("documentElement").Call("requestFullscreen") }
@@ -1558,18 +2712,151 @@ func WindowToggleFullscreen()  {} // Toggle based on current state
 func WindowClose()             {} // Not applicable for web
 func WindowSetTitle(title string) { jsDocument.Set("title", title) }
 
+// WindowSetAspectRatio is not applicable for web; the page owns the size.
+func WindowSetAspectRatio(num, den uint32) error { return nil }
+
+// CursorKind is a standard cursor shape for WindowSetCursor
+type CursorKind uint32
+
+const (
+	CursorDefault CursorKind = iota
+	// CursorPointer is the pointing hand for links and buttons
+	CursorPointer
+	// CursorText is the I-beam for editable or selectable text
+	CursorText
+	CursorGrab
+	CursorGrabbing
+	CursorNotAllowed
+	CursorCrosshair
+	CursorMove
+	CursorWait
+	CursorProgress
+	CursorHelp
+	CursorEwResize
+	CursorNsResize
+	CursorNeswResize
+	CursorNwseResize
+	CursorColResize
+	CursorRowResize
+	CursorZoomIn
+	CursorZoomOut
+)
+
+// CSS cursor names, indexed by CursorKind
+var cssCursors = [...]string{
+	"default", "pointer", "text", "grab", "grabbing", "not-allowed", "crosshair",
+	"move", "wait", "progress", "help", "ew-resize", "ns-resize", "nesw-resize",
+	"nwse-resize", "col-resize", "row-resize", "zoom-in", "zoom-out",
+}
+
+var (
+	webCursor       = "default"
+	webCursorHidden bool
+)
+
+// applyWebCursor sets the canvas CSS cursor from the current shape and visibility
+func applyWebCursor() {
+	canvas := jsDocument.Call("getElementById", "centered-canvas")
+	if canvas.IsNull() || canvas.IsUndefined() {
+		return
+	}
+	cursor := webCursor
+	if webCursorHidden {
+		cursor = "none"
+	}
+	canvas.Get("style").Set("cursor", cursor)
+}
+
+// WindowSetCursor sets the canvas's CSS cursor.
+func WindowSetCursor(kind CursorKind) error {
+	if int(kind) >= len(cssCursors) {
+		return fmt.Errorf("unknown cursor kind %d", kind)
+	}
+	webCursor = cssCursors[kind]
+	applyWebCursor()
+	return nil
+}
+
+// WindowSetCursorVisible hides the cursor over the canvas with CSS cursor: none.
+func WindowSetCursorVisible(visible bool) error {
+	webCursorHidden = !visible
+	applyWebCursor()
+	return nil
+}
+
+var (
+	webPointerCapture     bool
+	webPointerAutoRelease bool
+)
+
+// WindowSetPointerCapture keeps reporting mouse moves and releases that
+// happen off the canvas, from listeners on the document.
+func WindowSetPointerCapture(enabled, autoRelease bool) error {
+	webPointerCapture = enabled
+	webPointerAutoRelease = enabled && autoRelease
+	return nil
+}
+
+// dragCarriesFiles reports whether a drag event is for files from the desktop
+func dragCarriesFiles(e js.Value) bool {
+	transfer := e.Get("dataTransfer")
+	if transfer.IsNull() || transfer.IsUndefined() {
+		return false
+	}
+	types := transfer.Get("types")
+	for i := 0; i < types.Length(); i++ {
+		if types.Index(i).String() == "Files" {
+			return true
+		}
+	}
+	return false
+}
+
+// autoReleaseWebPointerCapture turns auto-release capture off once no mouse
+// button is held
+func autoReleaseWebPointerCapture(e js.Value) {
+	if webPointerAutoRelease && e.Get("buttons").Int() == 0 {
+		webPointerCapture = false
+		webPointerAutoRelease = false
+	}
+}
+
+// WindowSetBlurBehind blurs the page behind the canvas's transparent areas
+// with CSS backdrop-filter.
+func WindowSetBlurBehind(enabled bool) error {
+	canvas := jsDocument.Call("getElementById", "centered-canvas")
+	if canvas.IsNull() || canvas.IsUndefined() {
+		return nil
+	}
+	filter := "none"
+	if enabled {
+		filter = "blur(20px)"
+	}
+	canvas.Get("style").Set("backdropFilter", filter)
+	return nil
+}
+
+// WindowSetOpacity sets the canvas's CSS opacity.
+func WindowSetOpacity(alpha float32) error {
+	if alpha < 0 || alpha > 1 || math.IsNaN(float64(alpha)) {
+		return fmt.Errorf("opacity %v out of range 0-1", alpha)
+	}
+	canvas := jsDocument.Call("getElementById", "centered-canvas")
+	if canvas.IsNull() || canvas.IsUndefined() {
+		return nil
+	}
+	canvas.Get("style").Set("opacity", alpha)
+	return nil
+}
+
+// WindowSetIMECursorArea is a no-op for web; the browser places its own IME UI.
+func WindowSetIMECursorArea(x, y, width, height float64) error { return nil }
+
 // ============================================================================
 // Text Measurement
 // ============================================================================
 
 func MeasureTextWidth(text, fontName string, fontSize float32) float32 {
-	ctx := jsGlobal.Get("centeredCtx")
-	if ctx.IsUndefined() {
-		// Create temporary canvas for measurement
-		tempCanvas := jsDocument.Call("createElement", "canvas")
-		ctx = tempCanvas.Call("getContext", "2d")
-	}
-
 	// Map font names to proper CSS font families
 	fontFamily := "system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif"
 	switch fontName {
@@ -1582,6 +2869,17 @@ func MeasureTextWidth(text, fontName string, fontSize float32) float32 {
 	default:
 		fontFamily = fmt.Sprintf("'%s', system-ui, sans-serif", fontName)
 	}
+	return measureTextWithFamily(text, fontFamily, fontSize)
+}
+
+// measureTextWithFamily measures text with a CSS font-family list
+func measureTextWithFamily(text, fontFamily string, fontSize float32) float32 {
+	ctx := jsGlobal.Get("centeredCtx")
+	if ctx.IsUndefined() {
+		// Create temporary canvas for measurement
+		tempCanvas := jsDocument.Call("createElement", "canvas")
+		ctx = tempCanvas.Call("getContext", "2d")
+	}
 
 	font := fmt.Sprintf("400 %.0fpx %s", fontSize, fontFamily)
 	ctx.Set("font", font)
@@ -1612,12 +2910,25 @@ func MeasureTextWithFont(text string, font FontDescriptor) float32 {
 		if family := getBundledFontFamily(path); family != "" {
 			fontName = family
 		}
+	} else if font.Source.Memory != nil {
+		fontName = getBundledFontFamily(font.Source.Memory.Name)
+	} else if len(font.Source.SystemWithFallback) > 0 {
+		return measureTextWithFamily(text, fallbackFontFamily(font.Source.SystemWithFallback), font.Size)
 	} else if font.Source.System != nil {
 		fontName = *font.Source.System
 	}
 	return MeasureTextWidth(text, fontName, font.Size)
 }
 
+// fallbackFontFamily turns a fallback chain into a CSS font-family list
+func fallbackFontFamily(names []string) string {
+	family := ""
+	for _, name := range names {
+		family += fmt.Sprintf("'%s', ", name)
+	}
+	return family + "system-ui, sans-serif"
+}
+
 func MeasureTextToCursor(text string, charIndex int, fontName string, fontSize float32) float32 {
 	if charIndex >= len(text) {
 		return MeasureTextWidth(text, fontName, fontSize)
@@ -1625,6 +2936,35 @@ func MeasureTextToCursor(text string, charIndex int, fontName string, fontSize f
 	return MeasureTextWidth(text[:charIndex], fontName, fontSize)
 }
 
+// TextMoveCaret moves a caret (a rune index into text) by delta characters.
+// The browser build has no grapheme segmentation and moves by runes.
+func TextMoveCaret(text string, charIndex int, delta int) int {
+	return clampCaret(text, charIndex+delta)
+}
+
+// TextLineBreaks reports where text may wrap: breakable[i] is true if a
+// line may start at rune i. The browser build breaks after spaces.
+func TextLineBreaks(text string) []bool {
+	runes := []rune(text)
+	breakable := make([]bool, len(runes)+1)
+	for i, r := range runes {
+		if unicode.IsSpace(r) {
+			breakable[i+1] = true
+		}
+	}
+	return breakable
+}
+
+func clampCaret(text string, pos int) int {
+	if pos < 0 {
+		return 0
+	}
+	if n := len([]rune(text)); pos > n {
+		return n
+	}
+	return pos
+}
+
 func GetScaleFactor() float64 {
 	return jsWindow.Get("devicePixelRatio").Float()
 }
@@ -1644,6 +2984,30 @@ func LoadImage(data []byte) (TextureID, error) {
 	return id, nil
 }
 
+// ImageOptions controls how LoadImageWithOptions uploads an image. The zero
+// value uploads exactly like LoadImage.
+type ImageOptions struct {
+	// Premultiply straight-alpha pixels on upload, avoiding dark fringes on
+	// soft edges
+	Premultiply bool
+	// NoMipmaps uploads only the full-size image
+	NoMipmaps bool
+}
+
+// LoadImageWithOptions loads an image like LoadImage. The browser decodes
+// and filters images itself, so the options have no effect.
+func LoadImageWithOptions(data []byte, opts ImageOptions) (TextureID, error) {
+	return LoadImage(data)
+}
+
+func LoadImages(images [][]byte) ([]TextureID, error) {
+	ids := make([]TextureID, len(images))
+	for i, data := range images {
+		ids[i], _ = LoadImage(data)
+	}
+	return ids, nil
+}
+
 func LoadImageFile(path string) (TextureID, error) {
 	// Load image from URL
 	id := nextTextureID
@@ -1651,15 +3015,176 @@ func LoadImageFile(path string) (TextureID, error) {
 	return id, nil
 }
 
+// LoadSVG is not available on web; browsers draw SVGs in <img>.
+func LoadSVG(data []byte, width, height uint32) (TextureID, error) {
+	return 0, fmt.Errorf("SVG loading not available on web")
+}
+
+// CompressedFormat is a block-compressed texture format.
+type CompressedFormat uint32
+
+const (
+	CompressedBC1RGBASrgb  CompressedFormat = 0
+	CompressedBC3RGBASrgb  CompressedFormat = 1
+	CompressedBC4R         CompressedFormat = 2
+	CompressedBC5RG        CompressedFormat = 3
+	CompressedBC7RGBASrgb  CompressedFormat = 4
+	CompressedETC2RGBSrgb  CompressedFormat = 5
+	CompressedETC2RGBASrgb CompressedFormat = 6
+	CompressedASTC4x4Srgb  CompressedFormat = 7
+	CompressedASTC8x8Srgb  CompressedFormat = 8
+)
+
+// LoadCompressedTexture is not available on web.
+func LoadCompressedTexture(format CompressedFormat, data []byte, width, height, mipLevels uint32) (TextureID, error) {
+	return 0, fmt.Errorf("compressed textures not available on web")
+}
+
+// RenderToTexture is not available on web yet.
+func RenderToTexture(commands []RenderCommand, width, height uint32) (TextureID, error) {
+	return 0, fmt.Errorf("render to texture not available on web")
+}
+
 func UnloadImage(textureID TextureID) error {
 	delete(imageCache, textureID)
 	return nil
 }
 
+// AnimationID identifies an image loaded with LoadAnimatedImage
+type AnimationID uint32
+
+// LoadAnimatedImage is not available on web; browsers animate GIFs in <img>.
+func LoadAnimatedImage(data []byte) (AnimationID, error) {
+	return 0, fmt.Errorf("animated images not available on web")
+}
+
+// AnimationFrame is not available on web.
+func AnimationFrame(id AnimationID, elapsed time.Duration) (TextureID, error) {
+	return 0, fmt.Errorf("animated images not available on web")
+}
+
+// UnloadAnimation is not available on web.
+func UnloadAnimation(id AnimationID) error {
+	return nil
+}
+
 func GetTextureSize(textureID TextureID) (uint32, uint32, error) {
 	return 0, 0, fmt.Errorf("texture not found")
 }
 
+// GPULimits describes what the GPU can do.
+type GPULimits struct {
+	MaxTextureDimension uint32
+	MaxBindGroups       uint32
+	TimestampQueries    bool
+	BCCompression       bool
+	ETC2Compression     bool
+	ASTCCompression     bool
+}
+
+// GetGPULimits is not available on web; the browser decodes and scales images.
+func GetGPULimits() (GPULimits, error) {
+	return GPULimits{}, fmt.Errorf("GPU limits not available on web")
+}
+
+// RenderStats describes the work and timing of the last presented frame.
+type RenderStats struct {
+	DrawCalls   uint32
+	Triangles   uint32
+	FrameTimeMs float32
+	GPUTimed    bool
+}
+
+// GetRenderStats is not available on web; use the browser's performance tools.
+func GetRenderStats() (RenderStats, error) {
+	return RenderStats{}, fmt.Errorf("render stats not available on web")
+}
+
+// ColorSpace is the color space a window's frames are presented in.
+type ColorSpace int32
+
+const (
+	// ColorSpaceSRGB clips colors outside sRGB.
+	ColorSpaceSRGB ColorSpace = 0
+	// ColorSpaceExtendedSRGB is extended-range linear sRGB, which reaches
+	// all of Display-P3.
+	ColorSpaceExtendedSRGB ColorSpace = 1
+)
+
+// SurfaceColorSpace always reports sRGB on web, where the canvas is sRGB.
+func SurfaceColorSpace() (ColorSpace, error) {
+	return ColorSpaceSRGB, nil
+}
+
+// CommandIssue is a problem ValidateCommands found in a frame.
+type CommandIssue struct {
+	Index   int    `json:"index"`
+	Kind    string `json:"kind"`
+	Message string `json:"message"`
+}
+
+// ValidateCommands is not available on web; validate on a desktop build.
+func ValidateCommands(commands []RenderCommand) ([]CommandIssue, error) {
+	return nil, fmt.Errorf("command validation not available on web")
+}
+
+// RenderToBuffer is not available on web; render snapshots on a desktop build.
+func RenderToBuffer(commands []RenderCommand, width, height uint32) ([]byte, error) {
+	return nil, fmt.Errorf("headless rendering not available on web")
+}
+
+// LayoutNode is one node of a tree for ComputeLayout. Sizes are CSS
+// dimensions ("auto", "16px", "50%", "calc(100% - 16px)"); empty means auto.
+// Enum fields use the engine's names ("Flex", "Row", "SpaceBetween", ...) and
+// default to a row flex container that stretches its items.
+type LayoutNode struct {
+	// ID is echoed back in the node's LayoutRect
+	ID        uint64 `json:"id"`
+	Algorithm string `json:"algorithm,omitempty"` // Flex, Block, Absolute
+
+	Width     string `json:"width,omitempty"`
+	Height    string `json:"height,omitempty"`
+	MinWidth  string `json:"min_width,omitempty"`
+	MinHeight string `json:"min_height,omitempty"`
+	MaxWidth  string `json:"max_width,omitempty"`
+	MaxHeight string `json:"max_height,omitempty"`
+
+	PaddingTop    float32 `json:"padding_top,omitempty"`
+	PaddingRight  float32 `json:"padding_right,omitempty"`
+	PaddingBottom float32 `json:"padding_bottom,omitempty"`
+	PaddingLeft   float32 `json:"padding_left,omitempty"`
+	MarginTop     float32 `json:"margin_top,omitempty"`
+	MarginRight   float32 `json:"margin_right,omitempty"`
+	MarginBottom  float32 `json:"margin_bottom,omitempty"`
+	MarginLeft    float32 `json:"margin_left,omitempty"`
+
+	FlexDirection  string   `json:"flex_direction,omitempty"`  // Row, RowReverse, Column, ColumnReverse
+	FlexWrap       string   `json:"flex_wrap,omitempty"`       // NoWrap, Wrap, WrapReverse
+	JustifyContent string   `json:"justify_content,omitempty"` // FlexStart, FlexEnd, Center, SpaceBetween, SpaceAround, SpaceEvenly
+	AlignItems     string   `json:"align_items,omitempty"`     // FlexStart, FlexEnd, Center, Stretch, Baseline
+	FlexGrow       float32  `json:"flex_grow,omitempty"`
+	FlexShrink     *float32 `json:"flex_shrink,omitempty"` // nil means 1
+	FlexBasis      string   `json:"flex_basis,omitempty"`
+	Gap            float32  `json:"gap,omitempty"`
+
+	Children []LayoutNode `json:"children,omitempty"`
+}
+
+// LayoutRect is where ComputeLayout put a node, relative to the area's
+// top-left corner.
+type LayoutRect struct {
+	ID     uint64  `json:"id"`
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// ComputeLayout is not available on web; use CSS layout in the browser.
+func ComputeLayout(root LayoutNode, width, height float32) ([]LayoutRect, error) {
+	return nil, fmt.Errorf("layout not available on web")
+}
+
 // ============================================================================
 // Bundled Font Loading
 // ============================================================================
@@ -1789,6 +3314,88 @@ func lastIndexByte(s string, c byte) int {
 	return -1
 }
 
+// RegisterFontMemory loads font bytes as family name through the FontFace
+// API (see LoadBundledFontFromData) and returns a hash of the bytes for
+// MemoryFont. Identical bytes return the same hash.
+func RegisterFontMemory(name string, data []byte) (uint64, error) {
+	if len(data) == 0 {
+		return 0, fmt.Errorf("empty font data")
+	}
+	if err := LoadBundledFontFromData(name, data); err != nil {
+		return 0, err
+	}
+	// FNV-1a; 0 is reserved for failure as on native
+	hash := uint64(14695981039346656037)
+	for _, b := range data {
+		hash = (hash ^ uint64(b)) * 1099511628211
+	}
+	if hash == 0 {
+		hash = 1
+	}
+	return hash, nil
+}
+
+// UnregisterFont is a no-op on web; the browser keeps loaded FontFaces.
+func UnregisterFont(hash uint64) error { return nil }
+
+// FontFamilyInfo is an installed font family, as listed by ListFonts
+type FontFamilyInfo struct {
+	Family string `json:"family"`
+	// Weights of the installed faces on the CSS scale (100-900), ascending
+	Weights   []uint16 `json:"weights"`
+	HasItalic bool     `json:"has_italic"`
+}
+
+// ListFonts is not supported on web; browsers don't expose installed fonts
+// without a permission prompt.
+func ListFonts() ([]FontFamilyInfo, error) {
+	return nil, fmt.Errorf("listing fonts not supported on web")
+}
+
+// AccessibilityRole is what an accessibility node is, for screen readers
+type AccessibilityRole string
+
+const (
+	AccessibilityRoleGroup       AccessibilityRole = "Group" // Containers and custom widgets
+	AccessibilityRoleScrollArea  AccessibilityRole = "ScrollArea"
+	AccessibilityRoleStaticText  AccessibilityRole = "StaticText"
+	AccessibilityRoleHeading     AccessibilityRole = "Heading"
+	AccessibilityRoleButton      AccessibilityRole = "Button"
+	AccessibilityRoleTextField   AccessibilityRole = "TextField"
+	AccessibilityRoleTextArea    AccessibilityRole = "TextArea"
+	AccessibilityRoleCheckBox    AccessibilityRole = "CheckBox"
+	AccessibilityRoleRadioButton AccessibilityRole = "RadioButton"
+	AccessibilityRoleSlider      AccessibilityRole = "Slider"
+)
+
+// AccessibilityBounds is a node's area in window coordinates (logical pixels)
+type AccessibilityBounds struct {
+	X      float32 `json:"x"`
+	Y      float32 `json:"y"`
+	Width  float32 `json:"width"`
+	Height float32 `json:"height"`
+}
+
+// AccessibilityNode is a visible widget as screen readers see it. Label is
+// the widget's label, or its text for text, headings, buttons, checkboxes
+// and radios; Value is its value, or a text field's text.
+type AccessibilityNode struct {
+	// ID is the widget's id in the form widget deltas use
+	ID       json.RawMessage     `json:"id"`
+	Role     AccessibilityRole   `json:"role"`
+	Label    string              `json:"label"`
+	Value    string              `json:"value"`
+	Bounds   AccessibilityBounds `json:"bounds"`
+	Focused  bool                `json:"focused"`
+	Disabled bool                `json:"disabled"`
+	Children []AccessibilityNode `json:"children"`
+}
+
+// AccessibilityTree is not supported on web yet.
+func AccessibilityTree() (*AccessibilityNode, error) {
+	return nil, fmt.Errorf("accessibility tree not supported on web")
+}
+
 // getBundledFontFamily returns the CSS font-family name for a bundled font path.
 // Returns empty string if the font hasn't been loaded.
 func getBundledFontFamily(path string) string {
@@ -1826,6 +3433,31 @@ func ClipboardSetString(text string) {
 	jsGlobal.Get("navigator").Get("clipboard").Call("writeText", text)
 }
 
+// ClipboardGetImage is not available on web; the async Clipboard API can't
+// be read synchronously
+func ClipboardGetImage() (rgba []byte, width, height uint32, err error) {
+	return nil, 0, 0, fmt.Errorf("clipboard images not supported on web")
+}
+
+// ClipboardSetImage is not available on web
+func ClipboardSetImage(rgba []byte, width, height uint32) error {
+	return fmt.Errorf("clipboard images not supported on web")
+}
+
+// ============================================================================
+// Notification Functions (stubs - the web Notification API needs a permission prompt)
+// ============================================================================
+
+// ShowNotification is not available on web
+func ShowNotification(title, body, iconPath string) (uint32, error) {
+	return 0, fmt.Errorf("notifications not supported on web")
+}
+
+// DismissNotification is not available on web
+func DismissNotification(id uint32) error {
+	return fmt.Errorf("notifications not supported on web")
+}
+
 // ============================================================================
 // Keyboard Functions (stubs - web keyboards are automatic)
 // ============================================================================
@@ -1889,11 +3521,12 @@ const (
 
 // Video state constants
 const (
-	VideoStateIdle    = 0
-	VideoStatePlaying = 1
-	VideoStatePaused  = 2
-	VideoStateEnded   = 3
-	VideoStateError   = 4
+	VideoStateIdle      = 0
+	VideoStatePlaying   = 1
+	VideoStatePaused    = 2
+	VideoStateEnded     = 3
+	VideoStateError     = 4
+	VideoStateBuffering = 5
 )
 
 // AudioInfo contains metadata about an audio track
@@ -2328,6 +3961,26 @@ func VideoCreate() VideoPlayerID {
 		return nil
 	}))
 
+	// Stalls mid-playback fire waiting, and playing once data arrives
+	video.Call("addEventListener", "waiting", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		if videoStates[id] == VideoStatePlaying {
+			videoStates[id] = VideoStateBuffering
+			if cb, ok := videoCallbacks[id]; ok {
+				cb(VideoStateBuffering)
+			}
+		}
+		return nil
+	}))
+	video.Call("addEventListener", "playing", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		if videoStates[id] == VideoStateBuffering {
+			videoStates[id] = VideoStatePlaying
+			if cb, ok := videoCallbacks[id]; ok {
+				cb(VideoStatePlaying)
+			}
+		}
+		return nil
+	}))
+
 	// Log when video can play
 	video.Call("addEventListener", "canplay", js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		jsGlobal.Get("console").Call("log", fmt.Sprintf("Video %d ready to play", id))
@@ -2440,6 +4093,25 @@ func VideoGetTime(id VideoPlayerID) uint64 {
 	return 0
 }
 
+func VideoGetBufferedMs(id VideoPlayerID) uint64 {
+	video, ok := videoPlayers[id]
+	if !ok {
+		return 0
+	}
+	// The buffered range the playhead is in; others were left by seeking
+	now := video.Get("currentTime").Float()
+	buffered := now
+	ranges := video.Get("buffered")
+	for i := 0; i < ranges.Get("length").Int(); i++ {
+		start := ranges.Call("start", i).Float()
+		end := ranges.Call("end", i).Float()
+		if start <= now && now <= end && end > buffered {
+			buffered = end
+		}
+	}
+	return uint64(buffered * 1000)
+}
+
 func VideoGetInfo(id VideoPlayerID) (*VideoInfo, error) {
 	// First check the cache (populated when metadata loads)
 	if info, ok := videoInfoCache[id]; ok && info.Width > 0 && info.Height > 0 {
@@ -2696,6 +4368,14 @@ func SaveFileDialog(title, directory string, filters []FileFilter) (string, bool
 	return "", false
 }
 
+func OpenFileDialogAsync(title, directory string, filters []FileFilter, multiple bool, done func(paths []string, ok bool)) {
+	done(nil, false)
+}
+
+func SaveFileDialogAsync(title, directory string, filters []FileFilter, done func(path string, ok bool)) {
+	done("", false)
+}
+
 // ============================================================================
 // Tray Icon Functions (stubs - not applicable for web)
 // ============================================================================
@@ -2718,7 +4398,78 @@ func TrayIconSetMenuItemEnabled(index int, enabled bool)           {}
 func TrayIconSetMenuItemChecked(index int, checked bool)           {}
 func TrayIconSetMenuItemLabel(index int, label string)             {}
 
+// SetVerboseLogging is a no-op on web; the engine doesn't log there.
+func SetVerboseLogging(enabled bool) {}
+
+// SetCallbackWatchdog is a no-op on web; frames are driven by the browser.
+func SetCallbackWatchdog(threshold time.Duration, presentLastFrame bool) {}
+
+// CacheFlags selects engine caches for InvalidateCaches
+type CacheFlags uint32
+
+const (
+	// CacheText holds loaded font faces and font lookups (family to file)
+	CacheText CacheFlags = 1 << iota
+	// CacheGlyphAtlas holds rasterized glyphs
+	CacheGlyphAtlas
+	// CacheStyle holds style classes parsed against the theme
+	CacheStyle
+	// CacheShaping holds laid-out text (see SetTextShapingCacheCapacity)
+	CacheShaping
+
+	CacheAll = CacheText | CacheGlyphAtlas | CacheStyle | CacheShaping
+)
+
+// InvalidateCaches is not yet supported on web and does nothing.
+func InvalidateCaches(flags CacheFlags) error { return nil }
+
+// SetTextShapingCacheCapacity does nothing on web; the browser lays out
+// canvas text.
+func SetTextShapingCacheCapacity(capacity int) error { return nil }
+
 // Version returns the engine version
 func Version() string {
 	return "0.1.0-web"
 }
+
+// Error codes returned by engine functions. A code means the same thing
+// from every function (see EngineError in engine/src/error.rs).
+const (
+	CodeInvalidArgument = -1 // null pointer, invalid UTF-8 or JSON, bad argument
+	CodeNotFound        = -2 // no player, input, texture or layer with that ID
+	CodeOperationFailed = -3 // decoding, loading or a platform call failed
+	CodeGPUUploadFailed = -4
+	CodeUnsupported     = -5 // not supported on this platform or GPU
+	CodeNotInitialized  = -6 // engine, GPU backend or tray icon not created yet
+	CodeNoEventLoop     = -7
+	CodeBufferTooSmall  = -8
+	CodeNotReady        = -9 // nothing available yet (no media loaded, no frame)
+)
+
+// ErrorString describes an error code returned by an engine function
+func ErrorString(code int) string {
+	switch {
+	case code >= 0:
+		return "success"
+	case code == CodeInvalidArgument:
+		return "invalid argument"
+	case code == CodeNotFound:
+		return "not found"
+	case code == CodeOperationFailed:
+		return "operation failed"
+	case code == CodeGPUUploadFailed:
+		return "failed to upload to the GPU"
+	case code == CodeUnsupported:
+		return "not supported on this platform"
+	case code == CodeNotInitialized:
+		return "not initialized"
+	case code == CodeNoEventLoop:
+		return "no event loop running"
+	case code == CodeBufferTooSmall:
+		return "buffer too small"
+	case code == CodeNotReady:
+		return "nothing available yet"
+	default:
+		return "unknown error"
+	}
+}
@@ -159,7 +159,7 @@ func (t *sharedMemoryTransport) ExecuteBatch(cmds []CommandType, payloads [][]by
 	)
 
 	if result < 0 {
-		if result == -2 {
+		if result == CodeBufferTooSmall {
 			// Response buffer too small, grow and retry
 			newSize := len(t.responseBuf) * 2
 			if newSize > puregoMaxBufferSize {
@@ -552,6 +552,10 @@ type Loop struct {
 	frameCount    atomic.Uint64
 	droppedFrames atomic.Uint64
 
+	// Consecutive failed presents reported by the engine on the last event.
+	// While non-zero, continuous redraws back off instead of busy-looping.
+	renderFailures uint32
+
 	// Deferred overlay commands (rendered on top of all widgets)
 	// Used for select dropdowns, popups, tooltips, etc.
 	deferredOverlays []ffi.RenderCommand
@@ -822,6 +826,8 @@ func preloadBundledFonts() {
 
 // handleEvent is the FFI callback that drives the loop.
 func (l *Loop) handleEvent(event ffi.Event) ffi.FrameResponse {
+	l.renderFailures = event.RenderFailures
+
 	// Let user handle event first (they can consume it)
 	if l.onEvent != nil && l.onEvent(event) {
 		// User consumed the event - only redraw if they modified widget state
@@ -1307,6 +1313,13 @@ func (l *Loop) tick() ffi.FrameResponse {
 		redrawAfterMs = msUntilNextBlink
 	}
 
+	// The surface is temporarily unavailable (e.g. during a window drag or
+	// resize on some platforms): retry with backoff instead of spinning
+	if needsContinuousRedraw && l.renderFailures > 0 {
+		needsContinuousRedraw = false
+		redrawAfterMs = renderRetryDelayMs(l.renderFailures)
+	}
+
 	// Convert dirty region to FFI format
 	var dirtyRegion *ffi.DirtyRegion
 	if l.dirtyRegion != nil {
@@ -1327,6 +1340,16 @@ func (l *Loop) tick() ffi.FrameResponse {
 	}
 }
 
+// renderRetryDelayMs returns the redraw delay after n consecutive failed
+// presents: one frame at first, doubling up to a quarter second.
+func renderRetryDelayMs(failures uint32) uint32 {
+	delay := uint32(16)
+	for i := uint32(1); i < failures && delay < 250; i++ {
+		delay *= 2
+	}
+	return min(delay, 250)
+}
+
 // initializeLayers sets up the layer structure based on the current widget tree.
 // Called after layout changes to ensure layers match widget positions.
 func (l *Loop) initializeLayers() {
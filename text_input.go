@@ -360,21 +360,15 @@ func (b *TextBuffer) Delete(count int) {
 	b.saveUndoState()
 
 	if count > 0 {
-		// Delete forward
-		delEnd := b.cursor + count
-		if delEnd > len(b.content) {
-			delEnd = len(b.content)
-		}
+		// Delete forward (whole grapheme clusters, e.g. emoji ZWJ sequences)
+		delEnd := ffi.TextMoveCaret(string(b.content), b.cursor, count)
 		if b.cursor < delEnd {
 			b.content = append(b.content[:b.cursor], b.content[delEnd:]...)
 			b.notifyChange()
 		}
 	} else {
-		// Delete backward
-		delStart := b.cursor + count
-		if delStart < 0 {
-			delStart = 0
-		}
+		// Delete backward (whole grapheme clusters)
+		delStart := ffi.TextMoveCaret(string(b.content), b.cursor, count)
 		if delStart < b.cursor {
 			b.content = append(b.content[:delStart], b.content[b.cursor:]...)
 			b.cursor = delStart
@@ -427,8 +421,9 @@ func (b *TextBuffer) DeleteWord(forward bool) {
 	}
 }
 
-// MoveCursor moves the cursor by delta characters.
-// If extend is true, extends selection; otherwise clears it.
+// MoveCursor moves the cursor by delta characters (grapheme clusters, so an
+// emoji ZWJ sequence is one step). If extend is true, extends selection;
+// otherwise clears it.
 func (b *TextBuffer) MoveCursor(delta int, extend bool) {
 	b.mu.Lock()
 	defer b.mu.Unlock()
@@ -446,7 +441,7 @@ func (b *TextBuffer) MoveCursor(delta int, extend bool) {
 		return
 	}
 
-	newPos := b.clampPosition(b.cursor + delta)
+	newPos := b.clampPosition(ffi.TextMoveCaret(string(b.content), b.cursor, delta))
 	b.cursor = newPos
 	if !extend {
 		b.selectionAnchor = b.cursor
@@ -1550,6 +1545,7 @@ func WrapTextWithFont(text string, maxWidth, fontSize float32, fontName string,
 
 	var lines []WrappedLine
 	runes := []rune(text)
+	breakable := ffi.TextLineBreaks(text)
 	lineStart := 0
 
 	for lineStart < len(runes) {
@@ -1575,8 +1571,13 @@ func WrapTextWithFont(text string, maxWidth, fontSize float32, fontName string,
 				continue
 			}
 
-			// Track word boundaries for soft wrapping
-			if unicode.IsSpace(r) {
+			// Track break opportunities for soft wrapping; a space before
+			// one stays on the line even if it overflows
+			if lineEnd > lineStart && breakable[lineEnd] {
+				lastWordEnd = lineEnd
+				lastWordEndRune = lineEnd
+			}
+			if unicode.IsSpace(r) && breakable[lineEnd+1] {
 				lastWordEnd = lineEnd + 1
 				lastWordEndRune = lineEnd + 1
 			}